@@ -0,0 +1,69 @@
+/*
+ * apple_speech.h - C interface to the macOS Speech framework
+ * (SFSpeechRecognizer) for the "apple" transcription engine.
+ *
+ * Recognition tasks are fed 16kHz mono float samples and polled for their
+ * transcription; nothing calls back into the caller. Strings returned by
+ * this API are malloc'd and freed with ms_speech_free_string.
+ */
+
+#ifndef APPLE_SPEECH_H
+#define APPLE_SPEECH_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* SFSpeechRecognizerAuthorizationStatus */
+#define MS_SPEECH_AUTH_NOT_DETERMINED 0
+#define MS_SPEECH_AUTH_DENIED 1
+#define MS_SPEECH_AUTH_RESTRICTED 2
+#define MS_SPEECH_AUTH_AUTHORIZED 3
+
+typedef struct ms_speech_task ms_speech_task;
+
+int ms_speech_authorization_status(void);
+
+/* Show the permission prompt if it hasn't been answered yet and block until
+ * it is. Must not be called on the main thread. Returns the new status. */
+int ms_speech_request_authorization(void);
+
+/* A recognizer exists for `locale` (NULL: the system locale) and can be used
+ * right now. */
+int ms_speech_is_available(const char *locale);
+
+/* The recognizer for `locale` can run without Apple's servers. */
+int ms_speech_supports_on_device(const char *locale);
+
+/* Comma-separated identifiers of the supported locales ("en-US,de-DE,..."). */
+char *ms_speech_supported_locales(void);
+
+/* The system locale's identifier ("en_GB"). */
+char *ms_speech_current_locale(void);
+
+void ms_speech_free_string(char *s);
+
+/* Start recognizing audio appended with ms_speech_task_append. NULL when no
+ * recognizer is available for `locale` or `on_device` can't be honored. */
+ms_speech_task *ms_speech_task_start(const char *locale, int on_device);
+
+int ms_speech_task_append(ms_speech_task *task, const float *samples, int n_samples);
+
+/* No more audio; the final transcription follows. */
+void ms_speech_task_end_audio(ms_speech_task *task);
+
+/* Best transcription so far. *is_final is set once the task is done,
+ * successfully or not. */
+char *ms_speech_task_text(ms_speech_task *task, int *is_final);
+
+/* Why the task failed, or NULL. */
+char *ms_speech_task_error(ms_speech_task *task);
+
+/* Cancels the task if it's still running. */
+void ms_speech_task_free(ms_speech_task *task);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* APPLE_SPEECH_H */
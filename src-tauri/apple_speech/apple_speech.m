@@ -0,0 +1,178 @@
+/*
+ * apple_speech.m - SFSpeechRecognizer bridge for the "apple" engine
+ *
+ * Compiled with ARC. A task wraps an SFSpeechAudioBufferRecognitionRequest;
+ * the recognizer's result handler runs on its own queue and only records the
+ * latest transcription under the task's lock, which the Rust side polls.
+ */
+
+#import <Foundation/Foundation.h>
+#import <AVFoundation/AVFoundation.h>
+#import <Speech/Speech.h>
+#include "apple_speech.h"
+#include <stdlib.h>
+#include <string.h>
+
+/* kAFAssistantErrorDomain code for "No speech detected", reported when a
+ * task ends on silence. That's an empty transcription, not a failure. */
+static const NSInteger kNoSpeechDetected = 1110;
+
+@interface MSSpeechTask : NSObject
+@property (nonatomic, strong) SFSpeechRecognizer *recognizer;
+@property (nonatomic, strong) SFSpeechAudioBufferRecognitionRequest *request;
+@property (nonatomic, strong) SFSpeechRecognitionTask *task;
+@property (nonatomic, strong) AVAudioFormat *format;
+@property (nonatomic, copy) NSString *text;
+@property (nonatomic, copy) NSString *error;
+@property (nonatomic) BOOL done;
+@end
+
+@implementation MSSpeechTask
+@end
+
+static char *copy_string(NSString *s) {
+    if (s == nil) return NULL;
+    return strdup([s UTF8String]);
+}
+
+static SFSpeechRecognizer *recognizer_for(const char *locale) {
+    if (locale == NULL) return [[SFSpeechRecognizer alloc] init];
+    NSString *identifier = [NSString stringWithUTF8String:locale];
+    return [[SFSpeechRecognizer alloc] initWithLocale:[NSLocale localeWithLocaleIdentifier:identifier]];
+}
+
+int ms_speech_authorization_status(void) {
+    return (int)[SFSpeechRecognizer authorizationStatus];
+}
+
+int ms_speech_request_authorization(void) {
+    dispatch_semaphore_t answered = dispatch_semaphore_create(0);
+    __block SFSpeechRecognizerAuthorizationStatus result = SFSpeechRecognizerAuthorizationStatusNotDetermined;
+    [SFSpeechRecognizer requestAuthorization:^(SFSpeechRecognizerAuthorizationStatus status) {
+        result = status;
+        dispatch_semaphore_signal(answered);
+    }];
+    dispatch_semaphore_wait(answered, DISPATCH_TIME_FOREVER);
+    return (int)result;
+}
+
+int ms_speech_is_available(const char *locale) {
+    @autoreleasepool {
+        SFSpeechRecognizer *recognizer = recognizer_for(locale);
+        return recognizer != nil && recognizer.isAvailable;
+    }
+}
+
+int ms_speech_supports_on_device(const char *locale) {
+    @autoreleasepool {
+        SFSpeechRecognizer *recognizer = recognizer_for(locale);
+        return recognizer != nil && recognizer.supportsOnDeviceRecognition;
+    }
+}
+
+char *ms_speech_supported_locales(void) {
+    @autoreleasepool {
+        NSMutableArray<NSString *> *identifiers = [NSMutableArray array];
+        for (NSLocale *locale in [SFSpeechRecognizer supportedLocales]) {
+            [identifiers addObject:locale.localeIdentifier];
+        }
+        [identifiers sortUsingSelector:@selector(compare:)];
+        return copy_string([identifiers componentsJoinedByString:@","]);
+    }
+}
+
+char *ms_speech_current_locale(void) {
+    @autoreleasepool {
+        return copy_string([NSLocale currentLocale].localeIdentifier);
+    }
+}
+
+void ms_speech_free_string(char *s) {
+    free(s);
+}
+
+ms_speech_task *ms_speech_task_start(const char *locale, int on_device) {
+    @autoreleasepool {
+        SFSpeechRecognizer *recognizer = recognizer_for(locale);
+        if (recognizer == nil || !recognizer.isAvailable) return NULL;
+        if (on_device && !recognizer.supportsOnDeviceRecognition) return NULL;
+
+        MSSpeechTask *task = [[MSSpeechTask alloc] init];
+        task.recognizer = recognizer;
+        task.format = [[AVAudioFormat alloc] initWithCommonFormat:AVAudioPCMFormatFloat32
+                                                       sampleRate:16000
+                                                         channels:1
+                                                      interleaved:NO];
+        task.request = [[SFSpeechAudioBufferRecognitionRequest alloc] init];
+        task.request.shouldReportPartialResults = YES;
+        task.request.requiresOnDeviceRecognition = on_device ? YES : NO;
+        task.text = @"";
+
+        __weak MSSpeechTask *weak = task;
+        task.task = [recognizer recognitionTaskWithRequest:task.request
+                                             resultHandler:^(SFSpeechRecognitionResult *result, NSError *error) {
+            MSSpeechTask *strong = weak;
+            if (strong == nil) return;
+            @synchronized (strong) {
+                if (result != nil) {
+                    strong.text = result.bestTranscription.formattedString;
+                    if (result.isFinal) strong.done = YES;
+                }
+                if (error != nil) {
+                    if (error.code != kNoSpeechDetected) {
+                        strong.error = error.localizedDescription;
+                    }
+                    strong.done = YES;
+                }
+            }
+        }];
+        return (ms_speech_task *)CFBridgingRetain(task);
+    }
+}
+
+int ms_speech_task_append(ms_speech_task *handle, const float *samples, int n_samples) {
+    if (handle == NULL || n_samples <= 0) return 0;
+    @autoreleasepool {
+        MSSpeechTask *task = (__bridge MSSpeechTask *)handle;
+        AVAudioPCMBuffer *buffer = [[AVAudioPCMBuffer alloc] initWithPCMFormat:task.format
+                                                                 frameCapacity:(AVAudioFrameCount)n_samples];
+        if (buffer == nil) return -1;
+        memcpy(buffer.floatChannelData[0], samples, (size_t)n_samples * sizeof(float));
+        buffer.frameLength = (AVAudioFrameCount)n_samples;
+        [task.request appendAudioPCMBuffer:buffer];
+        return 0;
+    }
+}
+
+void ms_speech_task_end_audio(ms_speech_task *handle) {
+    if (handle == NULL) return;
+    MSSpeechTask *task = (__bridge MSSpeechTask *)handle;
+    [task.request endAudio];
+}
+
+char *ms_speech_task_text(ms_speech_task *handle, int *is_final) {
+    if (handle == NULL) return NULL;
+    MSSpeechTask *task = (__bridge MSSpeechTask *)handle;
+    @synchronized (task) {
+        if (is_final != NULL) *is_final = task.done ? 1 : 0;
+        return copy_string(task.text);
+    }
+}
+
+char *ms_speech_task_error(ms_speech_task *handle) {
+    if (handle == NULL) return NULL;
+    MSSpeechTask *task = (__bridge MSSpeechTask *)handle;
+    @synchronized (task) {
+        return copy_string(task.error);
+    }
+}
+
+void ms_speech_task_free(ms_speech_task *handle) {
+    if (handle == NULL) return;
+    MSSpeechTask *task = (__bridge_transfer MSSpeechTask *)handle;
+    BOOL done;
+    @synchronized (task) {
+        done = task.done;
+    }
+    if (!done) [task.task cancel];
+}
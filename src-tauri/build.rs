@@ -1,8 +1,74 @@
 fn main() {
     tauri_build::build();
+    emit_build_info();
 
     #[cfg(feature = "voxtral")]
     build_voxtral();
+
+    // build.rs runs on the host, so check the target rather than cfg(target_os)
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos") {
+        build_apple_speech();
+    }
+}
+
+/// Crates whose versions `get_build_info` reports.
+const REPORTED_CRATES: &[&str] = &[
+    "tauri",
+    "whisper-rs",
+    "whisper-rs-sys",
+    "cpal",
+    "rubato",
+    "ort",
+    "tokenizers",
+    "webrtc-vad",
+    "enigo",
+    "global-hotkey",
+];
+
+/// Toolchain, target and locked dependency versions for `build_info`, as
+/// compile-time env vars.
+fn emit_build_info() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=MENTASCRIBE_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=MENTASCRIBE_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=MENTASCRIBE_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+
+    // "name=version,name=version"; a crate locked at several versions lists each
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let mut versions = Vec::new();
+    let mut name: Option<&str> = None;
+    for line in lock.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"'));
+        } else if let (Some(value), Some(crate_name)) = (line.strip_prefix("version = "), name.take()) {
+            if REPORTED_CRATES.contains(&crate_name) {
+                versions.push(format!("{}={}", crate_name, value.trim_matches('"')));
+            }
+        }
+    }
+    println!("cargo:rustc-env=MENTASCRIBE_CRATE_VERSIONS={}", versions.join(","));
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
+
+/// Objective-C bridge to the Speech framework for the "apple" engine.
+fn build_apple_speech() {
+    let dir = std::path::Path::new("apple_speech");
+    cc::Build::new()
+        .flag("-fobjc-arc")
+        .include(dir)
+        .file(dir.join("apple_speech.m"))
+        .compile("apple_speech");
+
+    println!("cargo:rustc-link-lib=framework=Speech");
+    println!("cargo:rustc-link-lib=framework=AVFoundation");
+    println!("cargo:rustc-link-lib=framework=Foundation");
+    println!("cargo:rerun-if-changed=apple_speech/");
 }
 
 #[cfg(feature = "voxtral")]
@@ -0,0 +1,91 @@
+#!/usr/bin/env python3
+"""Regenerate model-manifest.json: the SHA256 of every model file the app
+downloads, pinned into the app so a download is checked against a hash that
+didn't come from the server sending it.
+
+LFS files take the SHA256 HuggingFace lists for them; small files stored
+directly in git are downloaded and hashed. Keep FILES in step with the
+download code (transcription/whisper.rs, voxtral.rs, parakeet/mod.rs,
+text/punctuation.rs) and run this whenever a model is added or moved:
+
+    python3 scripts/update-model-manifest.py
+"""
+
+import hashlib
+import json
+import pathlib
+import sys
+import urllib.parse
+import urllib.request
+
+HF = "https://huggingface.co"
+WHISPER = "ggerganov/whisper.cpp"
+WHISPER_MODELS = [
+    "tiny", "base", "small", "medium", "tiny.en", "base.en", "small.en", "medium.en",
+    "large-v3", "large-v3-turbo", "large-v3-turbo-q5_0", "large-v3-turbo-q8_0", "large-v3-q5_0",
+]
+# Models with a CoreML encoder ("large" is large-v3)
+COREML_MODELS = ["tiny", "tiny.en", "base", "base.en", "small", "small.en", "medium", "medium.en", "large-v3"]
+PUNCTUATION = "oliverguhr/fullstop-punctuation-multilang-large"
+# Where the punctuation repo's ONNX export may be, as ONNX_FILES in text/punctuation.rs
+PUNCTUATION_ONNX = ["onnx/model.onnx", "model.onnx", "onnx/model_quantized.onnx", "model_quantized.onnx"]
+
+# (repo, revision, file or list of alternatives, the first present is pinned)
+FILES = (
+    [(WHISPER, "main", f"ggml-{m}.bin") for m in WHISPER_MODELS]
+    + [(WHISPER, "main", f"ggml-{m}-encoder.mlmodelc.zip") for m in COREML_MODELS]
+    + [
+        (WHISPER, "main", "ggml-silero-vad.bin"),
+        ("distil-whisper/distil-large-v3.5-ggml", "main", "ggml-model.bin"),
+        ("mistralai/Voxtral-Mini-4B-Realtime-2602", "main", "consolidated.safetensors"),
+        ("mistralai/Voxtral-Mini-4B-Realtime-2602", "main", "tekken.json"),
+        ("mistralai/Voxtral-Mini-4B-Realtime-2602", "main", "params.json"),
+        ("istupakov/parakeet-ctc-0.6b-onnx", "main", "model.int8.onnx"),
+        ("istupakov/parakeet-ctc-0.6b-onnx", "main", "vocab.txt"),
+        (PUNCTUATION, "main", PUNCTUATION_ONNX),
+        (PUNCTUATION, "main", "tokenizer.json"),
+        (PUNCTUATION, "main", "config.json"),
+    ]
+)
+
+MANIFEST = pathlib.Path(__file__).resolve().parent.parent / "model-manifest.json"
+
+
+def get(url):
+    with urllib.request.urlopen(url, timeout=60) as response:
+        return response.read()
+
+
+def tree(repo, revision):
+    """Every file in the repo at `revision`, with its SHA256 if it's in LFS."""
+    url = f"{HF}/api/models/{repo}/tree/{urllib.parse.quote(revision)}?recursive=true"
+    return {
+        e["path"]: e["lfs"]["oid"] if e.get("lfs") else None
+        for e in json.loads(get(url))
+        if e["type"] == "file"
+    }
+
+
+def main():
+    hashes = {}
+    listings = {}
+    for repo, revision, file in FILES:
+        if (repo, revision) not in listings:
+            listings[repo, revision] = tree(repo, revision)
+        files = listings[repo, revision]
+        if isinstance(file, list):
+            file = next((f for f in file if f in files), None)
+            if file is None:
+                sys.exit(f"{repo} has none of the expected files")
+        sha256 = files.get(file)
+        if sha256 is None:
+            sha256 = hashlib.sha256(get(f"{HF}/{repo}/resolve/{revision}/{file}")).hexdigest()
+        hashes[f"{repo}/resolve/{revision}/{file}"] = sha256.lower()
+        print(f"{sha256}  {repo}/{file}", file=sys.stderr)
+
+    MANIFEST.write_text(json.dumps({"version": 1, "files": hashes}, indent=2, sort_keys=True) + "\n")
+    print(f"Pinned {len(hashes)} files in {MANIFEST}", file=sys.stderr)
+
+
+if __name__ == "__main__":
+    main()
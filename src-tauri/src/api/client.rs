@@ -37,7 +37,7 @@ struct ApiErrorResponse {
 }
 
 pub async fn login(email: &str, password: &str) -> Result<AuthToken, ApiError> {
-    let client = reqwest::Client::new();
+    let client = crate::net::client().map_err(|e| ApiError::RequestError(e.to_string()))?;
 
     let response = client
         .post(format!("{}/auth/login", API_BASE_URL))
@@ -77,7 +77,7 @@ pub async fn login(email: &str, password: &str) -> Result<AuthToken, ApiError> {
 }
 
 pub async fn refresh_token(refresh_token: &str) -> Result<AuthToken, ApiError> {
-    let client = reqwest::Client::new();
+    let client = crate::net::client().map_err(|e| ApiError::RequestError(e.to_string()))?;
 
     let response = client
         .post(format!("{}/auth/refresh", API_BASE_URL))
@@ -131,7 +131,7 @@ pub async fn create_transcription(
     duration_ms: Option<u64>,
     language: Option<&str>,
 ) -> Result<(), ApiError> {
-    let client = reqwest::Client::new();
+    let client = crate::net::client().map_err(|e| ApiError::RequestError(e.to_string()))?;
 
     let response = client
         .post(format!("{}/transcriptions", API_BASE_URL))
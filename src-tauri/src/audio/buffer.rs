@@ -0,0 +1,186 @@
+//! Append-only sample storage for a recording.
+//!
+//! A recording used to live in one growing `Vec<f32>`: every reallocation
+//! copied all of it, dropping transcribed audio from the front moved
+//! everything after it, and each streaming read cloned whatever was new.
+//! Here samples are kept in fixed-size blocks and addressed by their position
+//! from the start of the recording, so appending never moves existing audio,
+//! dropping the front frees whole blocks, and readers copy only the range they
+//! ask for, straight into their own buffer.
+
+use std::collections::VecDeque;
+
+/// Samples per block (~2s at 16kHz mono).
+const BLOCK_LEN: usize = 32 * 1024;
+
+#[derive(Debug, Default)]
+pub struct SampleBuffer {
+    /// Full blocks, oldest first; only the last one is still being filled
+    blocks: VecDeque<Vec<f32>>,
+    /// Samples dropped from the front of the first block
+    skip: usize,
+    /// Position of the first kept sample, counted from the start of the recording
+    start: usize,
+    len: usize,
+}
+
+impl SampleBuffer {
+    /// Samples kept, i.e. not yet dropped by `drop_before`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Position of the first kept sample.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Position one past the last sample.
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.block_with_room().push(sample);
+        self.len += 1;
+    }
+
+    pub fn extend_from_slice(&mut self, mut samples: &[f32]) {
+        while !samples.is_empty() {
+            let block = self.block_with_room();
+            let n = (BLOCK_LEN - block.len()).min(samples.len());
+            block.extend_from_slice(&samples[..n]);
+            self.len += n;
+            samples = &samples[n..];
+        }
+    }
+
+    fn block_with_room(&mut self) -> &mut Vec<f32> {
+        if self.blocks.back().is_none_or(|b| b.len() == BLOCK_LEN) {
+            self.blocks.push_back(Vec::with_capacity(BLOCK_LEN));
+        }
+        self.blocks.back_mut().expect("a block was just ensured")
+    }
+
+    /// The samples from position `from` to the end, as contiguous runs.
+    pub fn chunks_from(&self, from: usize) -> impl Iterator<Item = &[f32]> {
+        let mut offset = self.skip + from.saturating_sub(self.start);
+        self.blocks.iter().filter_map(move |block| {
+            if offset >= block.len() {
+                offset -= block.len();
+                return None;
+            }
+            let chunk = &block[offset..];
+            offset = 0;
+            Some(chunk)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+        self.chunks_from(self.start).flatten()
+    }
+
+    /// Append the samples from position `from` to the end onto `out`.
+    /// Returns the end position, where the next read should start.
+    pub fn copy_from(&self, from: usize, out: &mut Vec<f32>) -> usize {
+        for chunk in self.chunks_from(from) {
+            out.extend_from_slice(chunk);
+        }
+        self.end()
+    }
+
+    /// All kept samples in one contiguous buffer.
+    pub fn to_vec(&self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(self.len);
+        self.copy_from(self.start, &mut out);
+        out
+    }
+
+    /// Drop the samples before position `pos`, freeing every block that
+    /// becomes empty. Returns the number of samples dropped.
+    pub fn drop_before(&mut self, pos: usize) -> usize {
+        let dropped = pos.min(self.end()).saturating_sub(self.start);
+        if dropped == 0 {
+            return 0;
+        }
+        self.start += dropped;
+        self.len -= dropped;
+
+        let mut offset = self.skip + dropped;
+        while let Some(front) = self.blocks.front() {
+            if offset < front.len() {
+                break;
+            }
+            offset -= front.len();
+            self.blocks.pop_front();
+        }
+        self.skip = offset;
+        dropped
+    }
+}
+
+impl Extend<f32> for SampleBuffer {
+    fn extend<I: IntoIterator<Item = f32>>(&mut self, samples: I) {
+        for sample in samples {
+            self.push(sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(n: usize) -> Vec<f32> {
+        (0..n).map(|i| i as f32).collect()
+    }
+
+    #[test]
+    fn test_append_across_blocks() {
+        let samples = ramp(BLOCK_LEN * 5 / 2);
+        let mut buffer = SampleBuffer::default();
+        buffer.extend_from_slice(&samples[..10]);
+        buffer.extend_from_slice(&samples[10..]);
+        assert_eq!(buffer.len(), samples.len());
+        assert_eq!(buffer.blocks.len(), 3);
+        assert_eq!(buffer.to_vec(), samples);
+
+        let mut tail = Vec::new();
+        let end = buffer.copy_from(BLOCK_LEN - 3, &mut tail);
+        assert_eq!(end, samples.len());
+        assert_eq!(tail, &samples[BLOCK_LEN - 3..]);
+    }
+
+    #[test]
+    fn test_drop_before_keeps_positions() {
+        let samples = ramp(BLOCK_LEN * 5 / 2);
+        let mut buffer = SampleBuffer::default();
+        buffer.extend_from_slice(&samples);
+
+        assert_eq!(buffer.drop_before(BLOCK_LEN + 5), BLOCK_LEN + 5);
+        assert_eq!(buffer.blocks.len(), 2);
+        assert_eq!(buffer.start(), BLOCK_LEN + 5);
+        assert_eq!(buffer.end(), samples.len());
+        assert_eq!(buffer.to_vec(), &samples[BLOCK_LEN + 5..]);
+
+        // Positions before the start read from the start
+        let mut out = Vec::new();
+        buffer.copy_from(0, &mut out);
+        assert_eq!(out, &samples[BLOCK_LEN + 5..]);
+
+        assert_eq!(buffer.drop_before(BLOCK_LEN), 0);
+        assert_eq!(buffer.drop_before(usize::MAX), samples.len() - BLOCK_LEN - 5);
+        assert!(buffer.is_empty());
+        buffer.push(1.0);
+        assert_eq!(buffer.start(), samples.len());
+        assert_eq!(buffer.to_vec(), [1.0]);
+    }
+}
@@ -1,10 +1,16 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use rubato::{FastFixedIn, PolynomialDegree, Resampler};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+use crate::panics::LockExt;
+
+use super::buffer::SampleBuffer;
+use super::resample::{self, AnyResampler, Quality};
+
 #[derive(Error, Debug)]
 pub enum AudioError {
     #[error("No input device available")]
@@ -19,16 +25,30 @@ pub enum AudioError {
     AlreadyRunning,
     #[error("No capture running")]
     NotRunning,
+    #[error("Input device not found: {0}")]
+    DeviceNotFound(String),
 }
 
 pub struct AudioData {
-    pub samples: Vec<f32>,
+    /// Interleaved samples at the device rate
+    pub samples: SampleBuffer,
     pub sample_rate: u32,
     pub channels: u16,
     /// Pre-processed 16kHz mono samples ready for Whisper, produced incrementally
     /// during recording by the CPAL callback. `None` if real-time resampling failed
     /// or was unavailable (fallback to post-stop processing in `prepare_for_whisper`).
-    pub whisper_samples: Option<Vec<f32>>,
+    pub whisper_samples: Option<SampleBuffer>,
+    /// Milliseconds of pre-roll audio prepended from the standby stream (0 if none)
+    pub preroll_ms: u32,
+    pub dropouts: Dropouts,
+}
+
+/// Audio the callback lost during a recording, replaced with silence so
+/// everything after it stays aligned.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dropouts {
+    pub count: u32,
+    pub concealed_ms: u32,
 }
 
 struct AudioThreadHandle {
@@ -36,23 +56,55 @@ struct AudioThreadHandle {
     thread_handle: JoinHandle<()>,
 }
 
+/// Level-only stream used by the settings page to check a microphone.
+struct MonitorHandle {
+    stop_sender: Sender<()>,
+    thread_handle: JoinHandle<()>,
+}
+
+/// An input device, for choosing the one to record from.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InputDevice {
+    pub name: String,
+    pub is_default: bool,
+    /// From the device's default config; None if it couldn't be queried
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+}
+
+/// Level reading from the monitor stream.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct MonitorLevel {
+    /// Smoothed level on the same 0-1 scale as the recording level
+    pub level: f32,
+    /// Loudest sample since the last reading (1.0 = clipping)
+    pub peak: f32,
+}
+
 /// Holds the rubato resampler and a mono sample accumulator buffer.
 /// Created once per recording session; shared between the audio thread and callback
 /// via `Arc<Mutex<>>`. The callback uses `try_lock()` to avoid blocking.
 struct ResamplerState {
-    resampler: FastFixedIn<f32>,
+    resampler: AnyResampler,
+    quality: Quality,
+    /// Output/input rate ratio, for rebuilding at a lower quality.
+    ratio: f64,
     /// Mono samples waiting to fill a complete resampler chunk (1024 samples).
     mono_accumulator: Vec<f32>,
     /// The resampler's fixed input chunk size.
     chunk_size: usize,
     /// Whether real-time resampling has been marked as failed (skip further attempts).
     failed: bool,
+    /// A chunk that takes longer than this to resample made the callback overrun.
+    overrun_after: Duration,
+    /// Overrunning chunks at the current quality.
+    overruns: u32,
 }
 
 lazy_static::lazy_static! {
-    static ref AUDIO_BUFFER: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+    static ref AUDIO_BUFFER: Mutex<SampleBuffer> = Mutex::new(SampleBuffer::default());
     /// Pre-processed 16kHz mono buffer, populated incrementally by the CPAL callback.
-    static ref WHISPER_BUFFER: Mutex<Vec<f32>> = Mutex::new(Vec::new());
+    static ref WHISPER_BUFFER: Mutex<SampleBuffer> = Mutex::new(SampleBuffer::default());
     static ref AUDIO_THREAD: Mutex<Option<AudioThreadHandle>> = Mutex::new(None);
     static ref SAMPLE_RATE: Mutex<u32> = Mutex::new(16000);
     static ref CHANNELS: Mutex<u16> = Mutex::new(1);
@@ -62,8 +114,67 @@ lazy_static::lazy_static! {
     /// Shared resampler state for the current recording session.
     /// `None` when not recording or if resampler creation failed.
     static ref RESAMPLER_STATE: Mutex<Option<Arc<Mutex<ResamplerState>>>> = Mutex::new(None);
+    /// Interleaved pre-roll samples prepended to the current recording.
+    static ref PREROLL_SAMPLES: Mutex<usize> = Mutex::new(0);
+    /// When the stream last went back to standby, for the idle teardown.
+    static ref STANDBY_SINCE: Mutex<Instant> = Mutex::new(Instant::now());
+    static ref MONITOR: Mutex<Option<MonitorHandle>> = Mutex::new(None);
+    /// Kept apart from CURRENT_AUDIO_LEVEL so monitoring never moves the pill's meter.
+    static ref MONITOR_LEVEL: Mutex<MonitorLevel> = Mutex::new(MonitorLevel::default());
+    /// Name of the device the capture stream was opened on.
+    static ref INPUT_DEVICE: Mutex<Option<String>> = Mutex::new(None);
+    /// Device to record from (`recording.selected_input_device`); None = the default.
+    static ref SELECTED_DEVICE: Mutex<Option<String>> = Mutex::new(None);
 }
 
+/// Whether the callback is recording. A standby stream stays open with this
+/// false and discards incoming samples.
+static LIVE: AtomicBool = AtomicBool::new(false);
+/// Keep the input stream open between recordings (see `set_standby`).
+static STANDBY_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Idle time after which a standby stream is closed.
+static STANDBY_IDLE_SECS: AtomicU64 = AtomicU64::new(600);
+/// Set when capture goes live; the next callback prepends the pre-roll buffer.
+static PREROLL_PENDING: AtomicBool = AtomicBool::new(false);
+/// The current stream has been kept open in standby, so its lifetime goes to the
+/// privacy ledger when it closes.
+static WENT_STANDBY: AtomicBool = AtomicBool::new(false);
+/// 16kHz samples dropped from the front of WHISPER_BUFFER by `trim_consumed`
+/// during the current recording, kept past `stop_capture`. Positions used by
+/// `read_whisper_buffer` count from the start of the recording, so the
+/// streaming monitors don't notice the trim.
+static WHISPER_TRIMMED: AtomicUsize = AtomicUsize::new(0);
+/// Set by the stream's error callback once the device has gone away, so the
+/// switch to the default device happens once.
+static DEVICE_LOST: AtomicBool = AtomicBool::new(false);
+/// Live callbacks since the capture stream was opened.
+static CALLBACK_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Set by a live callback whose chunk isn't digital silence; taken by `take_signal`.
+static SIGNAL_SEEN: AtomicBool = AtomicBool::new(false);
+
+/// `resample::Quality` for the next resampler, as its index in `Quality::ALL`.
+static RESAMPLER_QUALITY: AtomicU8 = AtomicU8::new(1);
+
+/// Overrunning chunks after which resampling steps down a quality.
+const OVERRUNS_BEFORE_FALLBACK: u32 = 4;
+
+/// Gaps between callbacks and chunks lost to lock contention in the current
+/// recording, and how much silence replaced them.
+static DROPOUTS: AtomicU32 = AtomicU32::new(0);
+static CONCEALED_MS: AtomicU32 = AtomicU32::new(0);
+/// Interleaved samples AUDIO_BUFFER missed, padded on the next callback.
+static OWED_RAW: AtomicUsize = AtomicUsize::new(0);
+/// Mono frames at the device rate the 16kHz conversion missed, likewise.
+static OWED_MONO: AtomicUsize = AtomicUsize::new(0);
+
+/// A gap longer than this is a clock jump or a device that was asleep, not
+/// lost audio; it isn't padded.
+const MAX_CONCEALED_GAP: Duration = Duration::from_secs(2);
+
+/// Below this RMS a chunk is digital silence: a muted or blocked device
+/// delivers exact zeros, while even a quiet room has some noise.
+const DIGITAL_SILENCE_RMS: f32 = 1e-6;
+
 /// Calculate RMS (root mean square) audio level from samples
 fn calculate_rms(samples: &[f32]) -> f32 {
     if samples.is_empty() {
@@ -75,53 +186,329 @@ fn calculate_rms(samples: &[f32]) -> f32 {
 
 /// Get the current audio level (0.0 to 1.0)
 pub fn get_current_level() -> f32 {
-    *CURRENT_AUDIO_LEVEL.lock().unwrap()
+    *CURRENT_AUDIO_LEVEL.lock_recover()
 }
 
 /// Reset all capture state - used to recover from stuck states
 pub fn reset_state() {
-    eprintln!("[capture] Resetting all capture state...");
-    *IS_STOPPING.lock().unwrap() = false;
-    *AUDIO_THREAD.lock().unwrap() = None;
-    *CURRENT_AUDIO_LEVEL.lock().unwrap() = 0.0;
-    AUDIO_BUFFER.lock().unwrap().clear();
-    WHISPER_BUFFER.lock().unwrap().clear();
-    *RESAMPLER_STATE.lock().unwrap() = None;
-    eprintln!("[capture] State reset complete");
+    diag!(Audio, "[capture] Resetting all capture state...");
+    *IS_STOPPING.lock_or_reset() = false;
+    // Dropping the handle disconnects the stop channel, which ends the audio thread
+    *AUDIO_THREAD.lock_recover() = None;
+    LIVE.store(false, Ordering::SeqCst);
+    PREROLL_PENDING.store(false, Ordering::SeqCst);
+    *CURRENT_AUDIO_LEVEL.lock_recover() = 0.0;
+    AUDIO_BUFFER.lock_recover().clear();
+    WHISPER_BUFFER.lock_recover().clear();
+    WHISPER_TRIMMED.store(0, Ordering::SeqCst);
+    *RESAMPLER_STATE.lock_or_reset() = None;
+    diag!(Audio, "[capture] State reset complete");
 }
 
 /// Check if capture is currently active
 pub fn is_capturing() -> bool {
-    AUDIO_THREAD.lock().unwrap().is_some()
+    AUDIO_THREAD.lock_recover().is_some() && LIVE.load(Ordering::SeqCst)
+}
+
+/// Check if the input stream is open in standby (not recording)
+pub fn is_standby() -> bool {
+    AUDIO_THREAD.lock_recover().is_some() && !LIVE.load(Ordering::SeqCst)
+}
+
+/// Configure standby mode. When enabled, `stop_capture` leaves the input stream
+/// open (discarding samples) so the next `start_capture` skips device open and
+/// config negotiation. A standby stream idle for `idle_minutes` is closed.
+pub fn set_standby(enabled: bool, idle_minutes: u32) {
+    STANDBY_ENABLED.store(enabled, Ordering::SeqCst);
+    STANDBY_IDLE_SECS.store(idle_minutes as u64 * 60, Ordering::SeqCst);
+    if !enabled {
+        close_standby();
+    }
+}
+
+/// Quality for resamplers created from now on (see `resample::configured`).
+pub fn set_resampler_quality(quality: Quality) {
+    let index = Quality::ALL.iter().position(|q| *q == quality).unwrap_or(1);
+    RESAMPLER_QUALITY.store(index as u8, Ordering::SeqCst);
+}
+
+pub fn resampler_quality() -> Quality {
+    Quality::ALL
+        .get(RESAMPLER_QUALITY.load(Ordering::SeqCst) as usize)
+        .copied()
+        .unwrap_or(Quality::Cubic)
+}
+
+/// Check if the level monitor stream is open
+pub fn is_monitoring() -> bool {
+    MONITOR.lock().map(|m| m.is_some()).unwrap_or(false)
+}
+
+/// Latest monitor reading; resets the peak.
+pub fn take_monitor_level() -> MonitorLevel {
+    let mut level = MONITOR_LEVEL.lock_recover();
+    let reading = *level;
+    level.peak = 0.0;
+    reading
+}
+
+/// The input device named `name`, or the default one.
+fn input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, AudioError> {
+    match name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| AudioError::ConfigError(e.to_string()))?
+            .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| AudioError::DeviceNotFound(name.to_string())),
+        None => host.default_input_device().ok_or(AudioError::NoInputDevice),
+    }
+}
+
+/// The input device named `name`, falling back to the default one if it's
+/// not connected, so an unplugged headset doesn't stop dictation.
+pub(super) fn input_device_or_default(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, AudioError> {
+    match input_device(host, name) {
+        Err(AudioError::DeviceNotFound(name)) => {
+            log::warn!("[capture] Input device '{}' not found, using the default device", name);
+            input_device(host, None)
+        }
+        result => result,
+    }
+}
+
+/// Input devices the system currently offers.
+pub fn list_input_devices() -> Result<Vec<InputDevice>, AudioError> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let devices = host
+        .input_devices()
+        .map_err(|e| AudioError::ConfigError(e.to_string()))?
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            let config = device.default_input_config().ok();
+            Some(InputDevice {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                sample_rate: config.as_ref().map(|c| c.sample_rate().0),
+                channels: config.as_ref().map(|c| c.channels()),
+                name,
+            })
+        })
+        .collect();
+    Ok(devices)
+}
+
+/// Record from the device named `name` (None = the default device) from the
+/// next recording on. A standby stream on another device is closed.
+pub fn set_input_device(name: Option<String>) {
+    let name = name.filter(|n| !n.trim().is_empty());
+    let changed = {
+        let mut selected = SELECTED_DEVICE.lock_recover();
+        let changed = *selected != name;
+        *selected = name;
+        changed
+    };
+    if changed {
+        close_standby();
+    }
+}
+
+/// The device chosen with `set_input_device`.
+pub fn selected_input_device() -> Option<String> {
+    SELECTED_DEVICE.lock_recover().clone()
+}
+
+/// The capture device went away: carry on with the default device if
+/// recording, or drop a standby stream that can't deliver anything anymore.
+fn handle_device_lost() {
+    if DEVICE_LOST.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // Restarting joins the audio thread, so not from the stream's own callback
+    thread::spawn(|| {
+        if LIVE.load(Ordering::SeqCst) {
+            log::warn!("[capture] Input device disconnected, switching to the default device");
+            if let Err(e) = restart_capture(None) {
+                log::error!("[capture] Failed to switch to the default device: {}", e);
+            }
+        } else {
+            close_standby();
+        }
+    });
+}
+
+/// Open a monitor-only stream on the input device named `device` (None = the
+/// default device) that only tracks levels: nothing is buffered or transcribed.
+/// Replaces any running monitor. Returns the device name.
+pub fn start_monitor(device: Option<&str>) -> Result<String, AudioError> {
+    stop_monitor();
+    *MONITOR_LEVEL.lock_recover() = MonitorLevel::default();
+
+    let device_name = device.map(|d| d.to_string());
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    // The stream is owned by its thread; it reports back once the stream is playing
+    let (ready_tx, ready_rx) = mpsc::channel::<Result<String, AudioError>>();
+
+    let thread_handle = thread::spawn(move || {
+        let opened = (|| -> Result<(cpal::Stream, String), AudioError> {
+            let host = cpal::default_host();
+            let device = input_device(&host, device_name.as_deref())?;
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let config = device
+                .default_input_config()
+                .map_err(|e| AudioError::ConfigError(e.to_string()))?;
+
+            let stream = device
+                .build_input_stream(
+                    &config.into(),
+                    |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        let normalized = (calculate_rms(data) * 15.0).min(1.0);
+                        let peak = data.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+                        if let Ok(mut level) = MONITOR_LEVEL.try_lock() {
+                            level.level = level.level * 0.15 + normalized * 0.85;
+                            level.peak = level.peak.max(peak.min(1.0));
+                        }
+                    },
+                    |err| {
+                        log::error!("[capture] Monitor stream error: {}", err);
+                    },
+                    None,
+                )
+                .map_err(|e| AudioError::StreamError(e.to_string()))?;
+            stream.play().map_err(|e| AudioError::PlayError(e.to_string()))?;
+            Ok((stream, name))
+        })();
+
+        match opened {
+            Ok((stream, name)) => {
+                diag!(Audio, "[capture] Monitoring input device: {}", name);
+                ready_tx.send(Ok(name)).ok();
+                let opened_at = chrono::Local::now();
+                // Runs until stop_monitor sends or drops the stop channel
+                stop_rx.recv().ok();
+                drop(stream);
+                crate::privacy::record_session("monitor", opened_at, None, false, false);
+            }
+            Err(e) => {
+                ready_tx.send(Err(e)).ok();
+            }
+        }
+    });
+
+    let name = ready_rx
+        .recv()
+        .map_err(|e| AudioError::StreamError(e.to_string()))??;
+    *MONITOR.lock_recover() = Some(MonitorHandle {
+        stop_sender: stop_tx,
+        thread_handle,
+    });
+    Ok(name)
+}
+
+/// Close the monitor stream, if open.
+pub fn stop_monitor() {
+    let handle = MONITOR.lock_recover().take();
+    if let Some(handle) = handle {
+        handle.stop_sender.send(()).ok();
+        handle.thread_handle.join().ok();
+        *MONITOR_LEVEL.lock_recover() = MonitorLevel::default();
+        diag!(Audio, "[capture] Monitor stream closed");
+    }
 }
 
-/// Convert a multi-channel interleaved chunk to mono by averaging channels.
-/// Returns the input unchanged if already mono.
-fn to_mono(data: &[f32], channels: u16) -> Vec<f32> {
-    if channels <= 1 {
-        return data.to_vec();
+/// Close the input stream if it's idling in standby (no-op while recording).
+fn close_standby() {
+    let handle = {
+        let mut thread = AUDIO_THREAD.lock_recover();
+        if LIVE.load(Ordering::SeqCst) {
+            return;
+        }
+        thread.take()
+    };
+    if let Some(handle) = handle {
+        let _ = handle.stop_sender.send(());
+        let _ = handle.thread_handle.join();
+        diag!(Audio, "[capture] Standby stream closed");
+    }
+}
+
+/// Called by the audio thread while waiting: whether an idle standby stream has
+/// outlived its timeout. If so its handle is dropped, so the next `start_capture`
+/// opens a fresh stream.
+fn standby_expired() -> bool {
+    let mut thread = AUDIO_THREAD.lock_recover();
+    if LIVE.load(Ordering::SeqCst) || *IS_STOPPING.lock_or_reset() {
+        return false;
+    }
+    let idle = STANDBY_SINCE.lock_recover().elapsed();
+    if idle < Duration::from_secs(STANDBY_IDLE_SECS.load(Ordering::SeqCst)) {
+        return false;
     }
-    let ch = channels as usize;
-    data.chunks(ch)
+    diag!(Audio, "[capture] Standby stream idle for {}s, closing", idle.as_secs());
+    *thread = None;
+    true
+}
+
+/// Clear the recording buffers before a new recording.
+fn clear_buffers() {
+    AUDIO_BUFFER.lock_recover().clear();
+    WHISPER_BUFFER.lock_recover().clear();
+    *PREROLL_SAMPLES.lock_recover() = 0;
+    WHISPER_TRIMMED.store(0, Ordering::SeqCst);
+    DROPOUTS.store(0, Ordering::SeqCst);
+    CONCEALED_MS.store(0, Ordering::SeqCst);
+    OWED_RAW.store(0, Ordering::SeqCst);
+    OWED_MONO.store(0, Ordering::SeqCst);
+}
+
+/// Frames lost between two callbacks, judging by their capture timestamps:
+/// the time between them beyond the previous callback's own length. Jitter
+/// under one callback's length, and implausibly long gaps, count as none.
+fn missing_frames(elapsed: Duration, prev_frames: usize, sample_rate: u32) -> usize {
+    if elapsed > MAX_CONCEALED_GAP {
+        return 0;
+    }
+    let sr = sample_rate as f64;
+    let expected = prev_frames as f64 / sr;
+    let late = elapsed.as_secs_f64() - expected;
+    if late <= expected {
+        return 0;
+    }
+    (late * sr).round() as usize
+}
+
+fn record_dropout(frames: usize, sample_rate: u32) {
+    DROPOUTS.fetch_add(1, Ordering::Relaxed);
+    let ms = (frames as u64 * 1000 / sample_rate.max(1) as u64) as u32;
+    CONCEALED_MS.fetch_add(ms, Ordering::Relaxed);
+}
+
+/// A multi-channel interleaved chunk as mono, averaging channels.
+fn mono_frames(data: &[f32], channels: u16) -> impl Iterator<Item = f32> + '_ {
+    data.chunks(channels.max(1) as usize)
         .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
-        .collect()
 }
 
 /// Process mono samples through the resampler, draining full chunks from the
 /// accumulator. Appends resampled output to `whisper_buf`. Returns `true` on
 /// success, `false` if the resampler encountered an error (caller should mark
 /// the state as failed).
-fn drain_resampler(state: &mut ResamplerState, whisper_buf: &mut Vec<f32>) -> bool {
+fn drain_resampler(state: &mut ResamplerState, whisper_buf: &mut SampleBuffer) -> bool {
     while state.mono_accumulator.len() >= state.chunk_size {
-        let chunk: Vec<f32> = state.mono_accumulator.drain(..state.chunk_size).collect();
-        match state.resampler.process(&[&chunk], None) {
+        let chunk = &state.mono_accumulator[..state.chunk_size];
+        let started = Instant::now();
+        let processed = state.resampler.process(chunk);
+        if started.elapsed() > state.overrun_after {
+            state.overruns += 1;
+        }
+        match processed {
             Ok(result) => {
                 if let Some(channel) = result.first() {
                     whisper_buf.extend_from_slice(channel);
                 }
+                state.mono_accumulator.drain(..state.chunk_size);
             }
             Err(e) => {
-                eprintln!(
+                log::warn!(
                     "[capture] Resampler process error in callback: {}, disabling real-time resampling",
                     e
                 );
@@ -129,57 +516,157 @@ fn drain_resampler(state: &mut ResamplerState, whisper_buf: &mut Vec<f32>) -> bo
             }
         }
     }
+    if state.overruns >= OVERRUNS_BEFORE_FALLBACK {
+        step_down(state);
+    }
     true
 }
 
+/// Replace a resampler too slow for the callback with the next cheaper one,
+/// for this and later recordings. Costs a few samples of discontinuity.
+fn step_down(state: &mut ResamplerState) {
+    state.overruns = 0;
+    let Some(lower) = state.quality.lower() else { return };
+    match AnyResampler::new(lower, state.ratio, state.chunk_size) {
+        Ok(resampler) => {
+            log::warn!(
+                "[capture] {:?} resampling overran the audio callback, falling back to {:?}",
+                state.quality,
+                lower
+            );
+            state.resampler = resampler;
+            state.quality = lower;
+            set_resampler_quality(lower);
+        }
+        Err(e) => log::warn!("[capture] Failed to create {:?} resampler: {}", lower, e),
+    }
+}
+
+/// Append an interleaved chunk to AUDIO_BUFFER and its 16kHz mono conversion to
+/// WHISPER_BUFFER. Called from the CPAL callback, so every lock is a `try_lock()`.
+fn append_samples(
+    data: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    resampler: Option<&Arc<Mutex<ResamplerState>>>,
+) {
+    let frames = data.len() / channels.max(1) as usize;
+
+    // Append raw samples to AUDIO_BUFFER (for audio level display etc.).
+    // Whatever a contended lock made us drop is made up for with silence
+    if let Ok(mut buf) = AUDIO_BUFFER.try_lock() {
+        let owed = OWED_RAW.swap(0, Ordering::Relaxed);
+        buf.extend(std::iter::repeat(0.0).take(owed));
+        buf.extend_from_slice(data);
+    } else {
+        OWED_RAW.fetch_add(data.len(), Ordering::Relaxed);
+    }
+
+    // --- Real-time mono conversion + resampling for Whisper ---
+    if let Some(rs_arc) = resampler {
+        // try_lock: if the mutex is contended (e.g., stop_capture flushing),
+        // skip this chunk rather than blocking the audio thread.
+        if let Ok(mut rs) = rs_arc.try_lock() {
+            if !rs.failed {
+                // Convert to mono into the accumulator
+                let owed = OWED_MONO.swap(0, Ordering::Relaxed);
+                rs.mono_accumulator.extend(std::iter::repeat(0.0).take(owed));
+                rs.mono_accumulator.extend(mono_frames(data, channels));
+                // Drain full chunks through resampler
+                if let Ok(mut wbuf) = WHISPER_BUFFER.try_lock() {
+                    if !drain_resampler(&mut rs, &mut wbuf) {
+                        rs.failed = true;
+                    }
+                }
+                // If WHISPER_BUFFER lock failed, samples stay in accumulator
+                // and will be processed on the next callback.
+            }
+        } else if OWED_MONO.fetch_add(frames, Ordering::Relaxed) == 0 {
+            record_dropout(frames, sample_rate);
+        }
+    } else if sample_rate == 16000 {
+        // Already 16kHz: just convert to mono and append directly
+        if let Ok(mut wbuf) = WHISPER_BUFFER.try_lock() {
+            let owed = OWED_MONO.swap(0, Ordering::Relaxed);
+            wbuf.extend(std::iter::repeat(0.0).take(owed));
+            wbuf.extend(mono_frames(data, channels));
+        } else if OWED_MONO.fetch_add(frames, Ordering::Relaxed) == 0 {
+            record_dropout(frames, sample_rate);
+        }
+    }
+    // If there's no resampler and sample_rate != 16kHz, real-time
+    // resampling is unavailable; prepare_for_whisper will handle it.
+}
+
 pub fn start_capture() -> Result<(), AudioError> {
-    eprintln!("[capture] start_capture called");
+    diag!(Audio, "[capture] start_capture called");
 
     // Check if stop is in progress (prevents race condition)
-    if *IS_STOPPING.lock().unwrap() {
-        eprintln!("[capture] ERROR: Stop in progress, cannot start new capture");
+    if *IS_STOPPING.lock_or_reset() {
+        log::error!("[capture] Stop in progress, cannot start new capture");
         return Err(AudioError::AlreadyRunning);
     }
 
-    // Check if already running
-    if AUDIO_THREAD.lock().unwrap().is_some() {
-        eprintln!("[capture] ERROR: Already running");
-        return Err(AudioError::AlreadyRunning);
+    // Check if already running, or whether a standby stream can simply go live
+    {
+        let mut thread = AUDIO_THREAD.lock_recover();
+        if let Some(handle) = thread.as_ref() {
+            if LIVE.load(Ordering::SeqCst) {
+                log::error!("[capture] Already running");
+                return Err(AudioError::AlreadyRunning);
+            }
+            if handle.thread_handle.is_finished() {
+                // Stream died while in standby (e.g. device error); open a new one
+                diag!(Audio, "[capture] Standby stream ended unexpectedly, reopening");
+                *thread = None;
+            } else {
+                clear_buffers();
+                if let Some(rs_arc) = RESAMPLER_STATE.lock_or_reset().as_ref() {
+                    let mut rs = rs_arc.lock_recover();
+                    rs.resampler.reset();
+                    rs.mono_accumulator.clear();
+                    rs.failed = false;
+                    rs.overruns = 0;
+                }
+                PREROLL_PENDING.store(true, Ordering::SeqCst);
+                LIVE.store(true, Ordering::SeqCst);
+                diag!(Audio, "[capture] Standby stream is live");
+                return Ok(());
+            }
+        }
     }
 
     // Clear buffers and pre-allocate
-    {
-        // Raw buffer: up to 30s at 48kHz stereo
-        let mut buf = AUDIO_BUFFER.lock().unwrap();
-        buf.clear();
-        buf.reserve(48000 * 2 * 30);
-    }
-    {
-        // Whisper buffer: up to 30s at 16kHz mono
-        let mut wbuf = WHISPER_BUFFER.lock().unwrap();
-        wbuf.clear();
-        wbuf.reserve(16000 * 30);
-    }
+    clear_buffers();
     // Clear any previous resampler state (will be created after we know the device config)
-    *RESAMPLER_STATE.lock().unwrap() = None;
+    *RESAMPLER_STATE.lock_or_reset() = None;
+    PREROLL_PENDING.store(true, Ordering::SeqCst);
+    LIVE.store(true, Ordering::SeqCst);
 
-    eprintln!("[capture] Buffers cleared and pre-allocated");
+    diag!(Audio, "[capture] Buffers cleared and pre-allocated");
 
+    *AUDIO_THREAD.lock_recover() = Some(open_capture(selected_input_device()));
+    Ok(())
+}
+
+/// Spawn the thread that opens and owns the capture stream on the input
+/// device named `device` (None = the default device).
+fn open_capture(device: Option<String>) -> AudioThreadHandle {
     // Create channel for stop signal
     let (stop_tx, stop_rx) = mpsc::channel::<()>();
 
     // Spawn audio thread that owns the stream
     let thread_handle = thread::spawn(move || {
-        let result = (|| -> Result<(), AudioError> {
+        let result = crate::panics::catch("Audio capture", || -> Result<(), AudioError> {
             let host = cpal::default_host();
-            eprintln!("[capture] Using audio host: {:?}", host.id());
+            diag!(Audio, "[capture] Using audio host: {:?}", host.id());
 
-            let device = host
-                .default_input_device()
-                .ok_or(AudioError::NoInputDevice)?;
+            let device = input_device_or_default(&host, device.as_deref())?;
+            DEVICE_LOST.store(false, Ordering::SeqCst);
 
             let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-            eprintln!("[capture] Using input device: {}", device_name);
+            diag!(Audio, "[capture] Using input device: {}", device_name);
+            *INPUT_DEVICE.lock_recover() = Some(device_name);
 
             let config = device
                 .default_input_config()
@@ -187,10 +674,10 @@ pub fn start_capture() -> Result<(), AudioError> {
 
             let sr = config.sample_rate().0;
             let ch = config.channels();
-            *SAMPLE_RATE.lock().unwrap() = sr;
-            *CHANNELS.lock().unwrap() = ch;
+            *SAMPLE_RATE.lock_recover() = sr;
+            *CHANNELS.lock_recover() = ch;
 
-            eprintln!(
+            diag!(Audio,
                 "[capture] Audio config: {} Hz, {} channels",
                 sr, ch
             );
@@ -199,26 +686,31 @@ pub fn start_capture() -> Result<(), AudioError> {
             // If already 16kHz, we only need mono conversion (no resampler needed).
             let resampler_arc: Option<Arc<Mutex<ResamplerState>>> = if sr != 16000 {
                 let ratio = 16000_f64 / sr as f64;
-                let chunk_size = 1024_usize;
-                match FastFixedIn::<f32>::new(ratio, 2.0, PolynomialDegree::Cubic, chunk_size, 1) {
+                let chunk_size = resample::CHUNK_SIZE;
+                let quality = resampler_quality();
+                match AnyResampler::new(quality, ratio, chunk_size) {
                     Ok(r) => {
-                        eprintln!(
-                            "[capture] Real-time resampler created: {}Hz -> 16kHz (ratio={:.4}, chunk={})",
-                            sr, ratio, chunk_size
+                        diag!(Audio,
+                            "[capture] Real-time {:?} resampler created: {}Hz -> 16kHz (ratio={:.4}, chunk={})",
+                            quality, sr, ratio, chunk_size
                         );
                         let state = ResamplerState {
                             resampler: r,
+                            quality,
+                            ratio,
                             mono_accumulator: Vec::with_capacity(chunk_size * 2),
                             chunk_size,
                             failed: false,
+                            overrun_after: resample::callback_period(sr),
+                            overruns: 0,
                         };
                         let arc = Arc::new(Mutex::new(state));
                         // Store in global so stop_capture can flush
-                        *RESAMPLER_STATE.lock().unwrap() = Some(Arc::clone(&arc));
+                        *RESAMPLER_STATE.lock_or_reset() = Some(Arc::clone(&arc));
                         Some(arc)
                     }
                     Err(e) => {
-                        eprintln!(
+                        diag!(Audio,
                             "[capture] WARNING: Failed to create real-time resampler: {}. \
                              Will fall back to post-stop resampling.",
                             e
@@ -230,12 +722,11 @@ pub fn start_capture() -> Result<(), AudioError> {
                 // Already 16kHz -- just need mono conversion, no resampler
                 // We still create a "passthrough" ResamplerState with no resampler,
                 // but it's simpler to handle this case inline in the callback.
-                eprintln!("[capture] Input is already 16kHz, only mono conversion needed in callback");
+                diag!(Audio, "[capture] Input is already 16kHz, only mono conversion needed in callback");
                 None
             };
 
             use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
-            static CALLBACK_COUNT: AtomicUsize = AtomicUsize::new(0);
             static TOTAL_SAMPLES: AtomicUsize = AtomicUsize::new(0);
             CALLBACK_COUNT.store(0, AtomicOrdering::SeqCst);
             TOTAL_SAMPLES.store(0, AtomicOrdering::SeqCst);
@@ -243,6 +734,7 @@ pub fn start_capture() -> Result<(), AudioError> {
             // Capture values for the callback closure
             let cb_channels = ch;
             let cb_sample_rate = sr;
+            let mut last_callback: Option<(cpal::StreamInstant, usize)> = None;
 
             // Request smaller buffer for lower tail latency (256 frames instead of
             // default 512). CPAL will use the nearest supported size if 256 isn't exact.
@@ -252,13 +744,37 @@ pub fn start_capture() -> Result<(), AudioError> {
             let stream = device
                 .build_input_stream(
                     &stream_config,
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                        // Standby: keep the stream warm but discard the audio
+                        if !LIVE.load(AtomicOrdering::SeqCst) {
+                            last_callback = None;
+                            return;
+                        }
+
+                        // Callbacks the system skipped under load leave a gap in
+                        // the capture timestamps; fill it with as much silence so
+                        // streaming positions and word timing stay aligned
+                        let captured_at = info.timestamp().capture;
+                        let frames = data.len() / cb_channels.max(1) as usize;
+                        if let Some((prev_at, prev_frames)) = last_callback {
+                            let missing = captured_at
+                                .duration_since(&prev_at)
+                                .map_or(0, |elapsed| missing_frames(elapsed, prev_frames, cb_sample_rate));
+                            if missing > 0 {
+                                diag!(Audio, "[capture] Dropout: {} frames missing, padding with silence", missing);
+                                record_dropout(missing, cb_sample_rate);
+                                let silence = vec![0.0; missing * cb_channels as usize];
+                                append_samples(&silence, cb_channels, cb_sample_rate, resampler_arc.as_ref());
+                            }
+                        }
+                        last_callback = Some((captured_at, frames));
+
                         let count = CALLBACK_COUNT.fetch_add(1, AtomicOrdering::SeqCst);
                         TOTAL_SAMPLES.fetch_add(data.len(), AtomicOrdering::SeqCst);
 
                         // Log first few callbacks to confirm stream is working
                         if count < 3 {
-                            eprintln!(
+                            diag!(Audio,
                                 "[capture] Audio callback #{}: received {} samples",
                                 count + 1,
                                 data.len()
@@ -267,6 +783,9 @@ pub fn start_capture() -> Result<(), AudioError> {
 
                         // Calculate audio level from this chunk
                         let rms = calculate_rms(data);
+                        if rms > DIGITAL_SILENCE_RMS {
+                            SIGNAL_SEEN.store(true, AtomicOrdering::Relaxed);
+                        }
                         // Normalize to 0-1 range (typical speech RMS is around 0.01-0.1)
                         // Use higher multiplier for better sensitivity
                         let normalized = (rms * 15.0).min(1.0);
@@ -277,137 +796,195 @@ pub fn start_capture() -> Result<(), AudioError> {
                             *level = old_level * 0.15 + normalized * 0.85;
                         }
 
-                        // Append raw samples to AUDIO_BUFFER (for audio level display etc.)
-                        if let Ok(mut buf) = AUDIO_BUFFER.try_lock() {
-                            buf.extend_from_slice(data);
-                        }
-
-                        // --- Real-time mono conversion + resampling for Whisper ---
-                        if let Some(ref rs_arc) = resampler_arc {
-                            // try_lock: if the mutex is contended (e.g., stop_capture flushing),
-                            // skip this chunk rather than blocking the audio thread.
-                            if let Ok(mut rs) = rs_arc.try_lock() {
-                                if !rs.failed {
-                                    // Convert to mono
-                                    let mono = to_mono(data, cb_channels);
-                                    // Append to accumulator
-                                    rs.mono_accumulator.extend_from_slice(&mono);
-                                    // Drain full chunks through resampler
-                                    if let Ok(mut wbuf) = WHISPER_BUFFER.try_lock() {
-                                        if !drain_resampler(&mut rs, &mut wbuf) {
-                                            rs.failed = true;
-                                        }
-                                    }
-                                    // If WHISPER_BUFFER lock failed, samples stay in accumulator
-                                    // and will be processed on the next callback.
+                        // First live callback: prepend the pre-roll stream's buffer, which
+                        // ends roughly where this recording's first chunk begins
+                        if PREROLL_PENDING.swap(false, AtomicOrdering::SeqCst) {
+                            if let Some(preroll) = super::preroll::snapshot(cb_sample_rate, cb_channels) {
+                                append_samples(&preroll, cb_channels, cb_sample_rate, resampler_arc.as_ref());
+                                if let Ok(mut n) = PREROLL_SAMPLES.try_lock() {
+                                    *n = preroll.len();
                                 }
                             }
-                        } else if cb_sample_rate == 16000 {
-                            // Already 16kHz: just convert to mono and append directly
-                            if let Ok(mut wbuf) = WHISPER_BUFFER.try_lock() {
-                                let mono = to_mono(data, cb_channels);
-                                wbuf.extend_from_slice(&mono);
-                            }
                         }
-                        // If resampler_arc is None and sample_rate != 16kHz, real-time
-                        // resampling is unavailable; prepare_for_whisper will handle it.
+
+                        append_samples(data, cb_channels, cb_sample_rate, resampler_arc.as_ref());
                     },
                     |err| {
-                        eprintln!("[capture] ERROR: Audio stream error: {}", err);
+                        log::error!("[capture] Audio stream error: {}", err);
+                        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                            handle_device_lost();
+                        }
                     },
                     None,
                 )
                 .map_err(|e| AudioError::StreamError(e.to_string()))?;
 
-            eprintln!("[capture] Stream built, starting playback...");
+            diag!(Audio, "[capture] Stream built, starting playback...");
 
             stream
                 .play()
                 .map_err(|e| AudioError::PlayError(e.to_string()))?;
 
-            eprintln!("[capture] Audio stream started, waiting for stop signal...");
+            diag!(Audio, "[capture] Audio stream started, waiting for stop signal...");
+            let opened_at = chrono::Local::now();
+
+            // Block until stop signal received. A standby stream also closes
+            // itself once it has been idle longer than the configured timeout.
+            loop {
+                match stop_rx.recv_timeout(Duration::from_secs(1)) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        if standby_expired() {
+                            break;
+                        }
+                    }
+                    _ => break,
+                }
+            }
 
-            // Block until stop signal received
-            let _ = stop_rx.recv();
+            if WENT_STANDBY.swap(false, Ordering::SeqCst) {
+                crate::privacy::record_session("standby", opened_at, None, false, false);
+            }
 
             let total = TOTAL_SAMPLES.load(AtomicOrdering::SeqCst);
             let callbacks = CALLBACK_COUNT.load(AtomicOrdering::SeqCst);
-            eprintln!(
+            diag!(Audio,
                 "[capture] Stopping: received {} callbacks, {} total samples",
                 callbacks, total
             );
 
             // Stream is dropped here when thread ends
             Ok(())
-        })();
+        });
 
-        if let Err(e) = result {
+        if let Some(Err(e)) = result {
             log::error!("Audio thread error: {}", e);
         }
     });
 
-    *AUDIO_THREAD.lock().unwrap() = Some(AudioThreadHandle {
+    AudioThreadHandle {
         stop_sender: stop_tx,
         thread_handle,
-    });
+    }
+}
+
+/// Reopen the live capture stream on the input device named `device` (None
+/// = the default device), for a stream that stopped delivering audio. The
+/// 16kHz buffer is kept so streaming positions stay valid; the raw buffer is
+/// dropped, as the new device may run at another rate.
+pub fn restart_capture(device: Option<&str>) -> Result<(), AudioError> {
+    // Not held while the old thread is joined: it locks AUDIO_THREAD itself
+    // (`standby_expired`) and would never finish
+    let handle = {
+        let mut thread = AUDIO_THREAD.lock_recover();
+        if !LIVE.load(Ordering::SeqCst) {
+            return Err(AudioError::NotRunning);
+        }
+        thread.take().ok_or(AudioError::NotRunning)?
+    };
+    diag!(Audio, "[capture] Restarting capture on {}", device.unwrap_or("the default device"));
+    let _ = handle.stop_sender.send(());
+    let _ = handle.thread_handle.join();
 
+    AUDIO_BUFFER.lock_recover().clear();
+    *RESAMPLER_STATE.lock_or_reset() = None;
+    PREROLL_PENDING.store(false, Ordering::SeqCst);
+    *AUDIO_THREAD.lock_recover() = Some(open_capture(device.map(str::to_string)));
     Ok(())
 }
 
+/// Live callbacks the current capture stream has delivered.
+pub fn callback_count() -> usize {
+    CALLBACK_COUNT.load(Ordering::SeqCst)
+}
+
+/// Whether a live callback brought anything but digital silence since the last call.
+pub fn take_signal() -> bool {
+    SIGNAL_SEEN.swap(false, Ordering::Relaxed)
+}
+
+/// Name of the device capture was last opened on.
+pub fn input_device_name() -> Option<String> {
+    INPUT_DEVICE.lock_recover().clone()
+}
+
 pub fn stop_capture() -> Result<AudioData, AudioError> {
-    eprintln!("[capture] stop_capture called");
+    diag!(Audio, "[capture] stop_capture called");
 
     // Set stopping flag to prevent new captures from starting
-    *IS_STOPPING.lock().unwrap() = true;
-    eprintln!("[capture] IS_STOPPING flag set to true");
-
-    // Take the thread handle
-    let handle = AUDIO_THREAD
-        .lock()
-        .unwrap()
-        .take()
-        .ok_or_else(|| {
-            // Clear stopping flag on error
-            *IS_STOPPING.lock().unwrap() = false;
-            AudioError::NotRunning
-        })?;
-
-    // Send stop signal
-    eprintln!("[capture] Sending stop signal...");
-    let _ = handle.stop_sender.send(());
-
-    // Wait for thread to finish (stream is dropped, no more callbacks)
-    eprintln!("[capture] Waiting for audio thread to finish...");
-    let _ = handle.thread_handle.join();
-    eprintln!("[capture] Audio thread finished");
+    *IS_STOPPING.lock_or_reset() = true;
+    diag!(Audio, "[capture] IS_STOPPING flag set to true");
+
+    // Take the thread handle, unless the stream stays open in standby
+    let handle = {
+        let mut thread = AUDIO_THREAD.lock_recover();
+        let keep_open = match thread.as_ref() {
+            Some(handle) if LIVE.load(Ordering::SeqCst) => {
+                STANDBY_ENABLED.load(Ordering::SeqCst) && !handle.thread_handle.is_finished()
+            }
+            _ => {
+                // Clear stopping flag on error
+                *IS_STOPPING.lock_or_reset() = false;
+                return Err(AudioError::NotRunning);
+            }
+        };
+        if keep_open {
+            LIVE.store(false, Ordering::SeqCst);
+            WENT_STANDBY.store(true, Ordering::SeqCst);
+            *STANDBY_SINCE.lock_recover() = Instant::now();
+            None
+        } else {
+            thread.take()
+        }
+    };
+    let kept_open = handle.is_none();
+
+    if let Some(handle) = handle {
+        // Send stop signal
+        diag!(Audio, "[capture] Sending stop signal...");
+        let _ = handle.stop_sender.send(());
+
+        // Wait for thread to finish (stream is dropped, no more callbacks)
+        diag!(Audio, "[capture] Waiting for audio thread to finish...");
+        let _ = handle.thread_handle.join();
+        diag!(Audio, "[capture] Audio thread finished");
+        LIVE.store(false, Ordering::SeqCst);
+    } else {
+        diag!(Audio, "[capture] Stream left open in standby");
+    }
 
     // Flush remaining samples in the resampler accumulator.
-    // The audio thread has ended so there are no more callbacks contending the lock.
+    // Either the audio thread has ended or the callback is discarding samples, so
+    // there are no more callbacks contending the lock. A standby stream keeps its
+    // resampler, which is reset when it next goes live.
     let whisper_samples = {
-        let rs_opt = RESAMPLER_STATE.lock().unwrap().take();
+        let rs_opt = if kept_open {
+            RESAMPLER_STATE.lock_or_reset().clone()
+        } else {
+            RESAMPLER_STATE.lock_or_reset().take()
+        };
         match rs_opt {
             Some(rs_arc) => {
-                let mut rs = rs_arc.lock().unwrap();
+                let mut rs = rs_arc.lock_recover();
                 if rs.failed {
-                    eprintln!("[capture] Resampler was marked failed, no pre-processed whisper samples");
+                    diag!(Audio, "[capture] Resampler was marked failed, no pre-processed whisper samples");
                     None
                 } else {
-                    let mut wbuf = std::mem::take(&mut *WHISPER_BUFFER.lock().unwrap());
+                    let mut wbuf = std::mem::take(&mut *WHISPER_BUFFER.lock_recover());
                     // Flush any remaining samples in the accumulator via process_partial
                     if !rs.mono_accumulator.is_empty() {
-                        let remainder: Vec<f32> = rs.mono_accumulator.drain(..).collect();
-                        eprintln!(
+                        let remainder = std::mem::take(&mut rs.mono_accumulator);
+                        diag!(Audio,
                             "[capture] Flushing {} remaining mono samples through resampler",
                             remainder.len()
                         );
-                        match rs.resampler.process_partial(Some(&[&remainder]), None) {
+                        match rs.resampler.process_partial(&remainder) {
                             Ok(result) => {
                                 if let Some(channel) = result.first() {
                                     wbuf.extend_from_slice(channel);
                                 }
                             }
                             Err(e) => {
-                                eprintln!(
+                                diag!(Audio,
                                     "[capture] Resampler flush error: {}, discarding pre-processed buffer",
                                     e
                                 );
@@ -419,7 +996,7 @@ pub fn stop_capture() -> Result<AudioData, AudioError> {
                     if wbuf.is_empty() {
                         None
                     } else {
-                        eprintln!(
+                        diag!(Audio,
                             "[capture] Pre-processed whisper buffer: {} samples ({:.2}s at 16kHz)",
                             wbuf.len(),
                             wbuf.len() as f32 / 16000.0
@@ -430,11 +1007,11 @@ pub fn stop_capture() -> Result<AudioData, AudioError> {
             }
             None => {
                 // No resampler was created. Check if we have direct 16kHz mono samples.
-                let wbuf = std::mem::take(&mut *WHISPER_BUFFER.lock().unwrap());
+                let wbuf = std::mem::take(&mut *WHISPER_BUFFER.lock_recover());
                 if wbuf.is_empty() {
                     None
                 } else {
-                    eprintln!(
+                    diag!(Audio,
                         "[capture] Pre-processed whisper buffer (passthrough): {} samples ({:.2}s at 16kHz)",
                         wbuf.len(),
                         wbuf.len() as f32 / 16000.0
@@ -446,13 +1023,22 @@ pub fn stop_capture() -> Result<AudioData, AudioError> {
     };
 
     // Reset audio level
-    *CURRENT_AUDIO_LEVEL.lock().unwrap() = 0.0;
-
-    let samples = std::mem::take(&mut *AUDIO_BUFFER.lock().unwrap());
-    let sample_rate = *SAMPLE_RATE.lock().unwrap();
-    let channels = *CHANNELS.lock().unwrap();
+    *CURRENT_AUDIO_LEVEL.lock_recover() = 0.0;
+
+    let samples = std::mem::take(&mut *AUDIO_BUFFER.lock_recover());
+    let sample_rate = *SAMPLE_RATE.lock_recover();
+    let channels = *CHANNELS.lock_recover();
+    let preroll_samples = std::mem::take(&mut *PREROLL_SAMPLES.lock_recover());
+    let preroll_ms = if sample_rate > 0 && channels > 0 {
+        (preroll_samples as u64 * 1000 / (sample_rate as u64 * channels as u64)) as u32
+    } else {
+        0
+    };
+    if preroll_ms > 0 {
+        diag!(Audio, "[capture] Recording includes {}ms of pre-roll", preroll_ms);
+    }
 
-    eprintln!(
+    diag!(Audio,
         "[capture] Audio buffer: {} samples at {}Hz, {} channels ({:.2}s of audio)",
         samples.len(),
         sample_rate,
@@ -466,55 +1052,111 @@ pub fn stop_capture() -> Result<AudioData, AudioError> {
 
     // Check if we got any audio
     if samples.is_empty() {
-        eprintln!("[capture] WARNING: No audio samples captured! Check microphone permissions.");
+        log::warn!("[capture] No audio samples captured! Check microphone permissions.");
     } else {
         // Calculate some stats
         let max_amplitude = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
-        let rms = calculate_rms(&samples);
-        eprintln!(
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        diag!(Audio,
             "[capture] Audio stats: max_amplitude={:.6}, rms={:.6}",
             max_amplitude, rms
         );
     }
 
+    let dropouts = Dropouts {
+        count: DROPOUTS.load(Ordering::SeqCst),
+        concealed_ms: CONCEALED_MS.load(Ordering::SeqCst),
+    };
+    if dropouts.count > 0 {
+        log::warn!(
+            "[capture] {} dropouts in this recording, {}ms padded with silence",
+            dropouts.count,
+            dropouts.concealed_ms
+        );
+    }
+
     // Clear stopping flag now that we're done
-    *IS_STOPPING.lock().unwrap() = false;
-    eprintln!("[capture] IS_STOPPING flag cleared");
+    *IS_STOPPING.lock_or_reset() = false;
+    diag!(Audio, "[capture] IS_STOPPING flag cleared");
 
     Ok(AudioData {
         samples,
         sample_rate,
         channels,
         whisper_samples,
+        preroll_ms,
+        dropouts,
     })
 }
 
-/// Read a snapshot of WHISPER_BUFFER from position `from` onwards.
-/// Returns (new_samples, current_buffer_length).
-/// Used by the VAD streaming monitor to read new audio without blocking the CPAL callback.
-pub fn snapshot_whisper_buffer(from: usize) -> (Vec<f32>, usize) {
-    if let Ok(wbuf) = WHISPER_BUFFER.lock() {
-        let len = wbuf.len();
-        if len > from {
-            (wbuf[from..].to_vec(), len)
-        } else {
-            (Vec::new(), len)
-        }
-    } else {
-        (Vec::new(), from)
+/// Append WHISPER_BUFFER from position `from` onwards to `out`, and return
+/// the position to read from next. Used by the streaming monitors to read new
+/// audio without blocking the CPAL callback for longer than the copy.
+/// Positions count from the start of the recording, including any samples
+/// already dropped by `trim_consumed`.
+pub fn read_whisper_buffer(from: usize, out: &mut Vec<f32>) -> usize {
+    match WHISPER_BUFFER.lock() {
+        Ok(wbuf) => wbuf.copy_from(from, out).max(from),
+        Err(_) => from,
     }
 }
 
+/// Position one past the newest 16kHz sample of the current recording.
+pub fn whisper_buffer_end() -> usize {
+    WHISPER_BUFFER.lock().map(|wbuf| wbuf.end()).unwrap_or(0)
+}
+
+/// 16kHz samples dropped from the current recording by `trim_consumed`.
+/// Still valid after `stop_capture`, until the next recording starts.
+pub fn trimmed_whisper_samples() -> usize {
+    WHISPER_TRIMMED.load(Ordering::SeqCst)
+}
+
+/// Drop the audio before 16kHz position `up_to` (counted from the start of the
+/// recording) from both buffers, once streaming has transcribed and output it,
+/// so a long recording doesn't keep all of its audio in memory. The raw buffer
+/// loses the matching length at the device rate. Returns the 16kHz samples dropped.
+pub fn trim_consumed(up_to: usize) -> usize {
+    let dropped = {
+        let mut wbuf = WHISPER_BUFFER.lock_recover();
+        let dropped = wbuf.drop_before(up_to);
+        if dropped == 0 {
+            return 0;
+        }
+        WHISPER_TRIMMED.store(wbuf.start(), Ordering::SeqCst);
+        dropped
+    };
+
+    let sample_rate = *SAMPLE_RATE.lock_recover() as u64;
+    let channels = *CHANNELS.lock_recover() as usize;
+    let raw_frames = (dropped as u64 * sample_rate / 16000) as usize;
+    let raw_dropped = {
+        let mut buf = AUDIO_BUFFER.lock_recover();
+        let start = buf.start();
+        buf.drop_before(start + raw_frames * channels)
+    };
+    // The pre-roll is at the very start, so it goes first
+    let mut preroll = PREROLL_SAMPLES.lock_recover();
+    *preroll = preroll.saturating_sub(raw_dropped);
+
+    diag!(Audio,
+        "[capture] Trimmed {:.2}s of finalized audio from the recording buffers",
+        dropped as f32 / 16000.0
+    );
+    dropped
+}
+
 /// Resample audio to 16kHz mono for Whisper.
 /// If pre-processed whisper samples are available (from real-time resampling during
 /// recording), returns them directly — eliminating post-stop latency entirely.
 /// Otherwise falls back to the original mono conversion + resampling pipeline.
 ///
-/// Takes ownership of AudioData to avoid cloning samples when already mono.
+/// The result is the one contiguous copy of the audio, which whisper.cpp and
+/// voxtral need; `whisper_samples` has usually been cut to the tail by then.
 /// Note: silence trimming removed — Silero VAD pre-filtering in whisper.rs
 /// handles speech/silence segmentation with much higher accuracy.
 pub fn prepare_for_whisper(audio: AudioData) -> Vec<f32> {
-    eprintln!(
+    diag!(Audio,
         "[audio] prepare_for_whisper: input {} samples at {}Hz, {} channels, whisper_samples={}",
         audio.samples.len(),
         audio.sample_rate,
@@ -525,45 +1167,40 @@ pub fn prepare_for_whisper(audio: AudioData) -> Vec<f32> {
     // Fast path: use pre-processed 16kHz mono samples from real-time resampling
     if let Some(whisper_samples) = audio.whisper_samples {
         if !whisper_samples.is_empty() {
-            eprintln!(
+            diag!(Audio,
                 "[audio] Using pre-processed whisper samples: {} samples ({:.2}s at 16kHz) -- zero post-stop latency",
                 whisper_samples.len(),
                 whisper_samples.len() as f32 / 16000.0
             );
-            return whisper_samples;
+            return whisper_samples.to_vec();
         }
-        eprintln!("[audio] Pre-processed whisper samples were empty, falling back to post-stop processing");
+        diag!(Audio, "[audio] Pre-processed whisper samples were empty, falling back to post-stop processing");
     }
 
     // Fallback path: original mono conversion + resampling
-    eprintln!("[audio] Falling back to post-stop mono conversion + resampling");
+    diag!(Audio, "[audio] Falling back to post-stop mono conversion + resampling");
 
     if audio.samples.is_empty() {
-        eprintln!("[audio] WARNING: Input audio buffer is empty!");
+        log::warn!("[audio] Input audio buffer is empty!");
         return Vec::new();
     }
 
-    let mut mono_samples = if audio.channels > 1 {
-        // Convert to mono by averaging channels
-        audio
-            .samples
-            .chunks(audio.channels as usize)
-            .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
-            .collect()
+    let raw = audio.samples.to_vec();
+    let mut mono_samples: Vec<f32> = if audio.channels > 1 {
+        mono_frames(&raw, audio.channels).collect()
     } else {
-        // No clone needed: we own the AudioData and can move the samples directly
-        audio.samples
+        raw
     };
 
-    eprintln!("[audio] After mono conversion: {} samples", mono_samples.len());
+    diag!(Audio, "[audio] After mono conversion: {} samples", mono_samples.len());
 
     // Resample to 16kHz if needed
     if audio.sample_rate != 16000 {
         mono_samples = resample(&mono_samples, audio.sample_rate, 16000);
-        eprintln!("[audio] After resampling to 16kHz: {} samples", mono_samples.len());
+        diag!(Audio, "[audio] After resampling to 16kHz: {} samples", mono_samples.len());
     }
 
-    eprintln!(
+    diag!(Audio,
         "[audio] Final audio for Whisper: {} samples ({:.2}s at 16kHz)",
         mono_samples.len(),
         mono_samples.len() as f32 / 16000.0
@@ -579,13 +1216,13 @@ fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
 
     let ratio = to_rate as f64 / from_rate as f64;
 
-    // Use FastFixedIn with cubic interpolation — much faster than sinc for speech-to-text.
-    // Cubic is more than sufficient quality for ASR (we don't need music-production fidelity).
-    let chunk_size = 1024;
-    let mut resampler = match FastFixedIn::<f32>::new(ratio, 2.0, PolynomialDegree::Cubic, chunk_size, 1) {
+    // Same quality as real-time capture, so a recording transcribes alike
+    // whichever path its audio took
+    let chunk_size = resample::CHUNK_SIZE;
+    let mut resampler = match AnyResampler::new(resampler_quality(), ratio, chunk_size) {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("[audio] rubato resampler creation failed: {}, falling back to linear", e);
+            log::warn!("[audio] rubato resampler creation failed: {}, falling back to linear", e);
             return resample_linear(samples, from_rate, to_rate);
         }
     };
@@ -596,14 +1233,14 @@ fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     let mut pos = 0;
     while pos + chunk_size <= samples.len() {
         let chunk = &samples[pos..pos + chunk_size];
-        match resampler.process(&[chunk], None) {
+        match resampler.process(chunk) {
             Ok(result) => {
                 if let Some(channel) = result.first() {
                     output.extend_from_slice(channel);
                 }
             }
             Err(e) => {
-                eprintln!("[audio] rubato process error: {}, falling back to linear", e);
+                log::warn!("[audio] rubato process error: {}, falling back to linear", e);
                 return resample_linear(samples, from_rate, to_rate);
             }
         }
@@ -613,14 +1250,14 @@ fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     // Process remaining samples (partial chunk)
     if pos < samples.len() {
         let remainder = &samples[pos..];
-        match resampler.process_partial(Some(&[remainder]), None) {
+        match resampler.process_partial(remainder) {
             Ok(result) => {
                 if let Some(channel) = result.first() {
                     output.extend_from_slice(channel);
                 }
             }
             Err(e) => {
-                eprintln!("[audio] rubato process_partial error: {}, falling back to linear", e);
+                log::warn!("[audio] rubato process_partial error: {}, falling back to linear", e);
                 return resample_linear(samples, from_rate, to_rate);
             }
         }
@@ -651,3 +1288,18 @@ fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
 
     resampled
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_frames() {
+        let ms = Duration::from_millis;
+        // 480 frames at 48kHz = 10ms per callback
+        assert_eq!(missing_frames(ms(10), 480, 48_000), 0);
+        assert_eq!(missing_frames(ms(19), 480, 48_000), 0);
+        assert_eq!(missing_frames(ms(30), 480, 48_000), 960);
+        assert_eq!(missing_frames(Duration::from_secs(3), 480, 48_000), 0);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod buffer;
 pub mod capture;
+pub mod preroll;
+pub mod resample;
 pub mod vad;
+pub mod watchdog;
 
 pub use capture::AudioData;
@@ -0,0 +1,168 @@
+//! Opt-in pre-roll buffer so the first syllable isn't clipped.
+//!
+//! While enabled, a lightweight standby input stream keeps the last few hundred
+//! milliseconds of microphone audio in a ring buffer. When a recording starts,
+//! `capture.rs` prepends that audio on its first callback, covering both the beat
+//! before the hotkey press and the time it takes the capture stream to go live.
+//!
+//! Keeping a stream open means the OS microphone indicator stays on, which is why
+//! this is off unless `recording.preroll_ms` is set.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+use super::capture::AudioError;
+use crate::panics::LockExt;
+
+/// Upper bound for the pre-roll length; anything longer is mostly silence.
+const MAX_PREROLL_MS: u32 = 2000;
+
+struct StandbyHandle {
+    stop_sender: Sender<()>,
+    thread_handle: JoinHandle<()>,
+    duration_ms: u32,
+}
+
+/// Interleaved samples in the standby device's native format.
+struct PrerollRing {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    sample_rate: u32,
+    channels: u16,
+}
+
+lazy_static::lazy_static! {
+    static ref STANDBY: Mutex<Option<StandbyHandle>> = Mutex::new(None);
+    static ref RING: Mutex<PrerollRing> = Mutex::new(PrerollRing {
+        samples: VecDeque::new(),
+        capacity: 0,
+        sample_rate: 0,
+        channels: 0,
+    });
+}
+
+pub fn is_running() -> bool {
+    STANDBY.lock().map(|s| s.is_some()).unwrap_or(false)
+}
+
+/// Start, restart or stop the standby stream to match the configured pre-roll
+/// length (0 = disabled).
+pub fn configure(duration_ms: u32) {
+    let duration_ms = duration_ms.min(MAX_PREROLL_MS);
+    let current = STANDBY
+        .lock()
+        .ok()
+        .and_then(|s| s.as_ref().map(|h| h.duration_ms));
+    if current == Some(duration_ms) || (current.is_none() && duration_ms == 0) {
+        return;
+    }
+
+    stop_standby();
+    if duration_ms > 0 {
+        if let Err(e) = start_standby(duration_ms) {
+            log::warn!("Pre-roll standby stream failed to start: {}", e);
+        }
+    }
+}
+
+fn start_standby(duration_ms: u32) -> Result<(), AudioError> {
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+    // Like capture, the stream is created and owned by its own thread
+    let thread_handle = thread::spawn(move || {
+        let result = crate::panics::catch("Pre-roll capture", || -> Result<(), AudioError> {
+            let host = cpal::default_host();
+            let selected = super::capture::selected_input_device();
+            let device = super::capture::input_device_or_default(&host, selected.as_deref())?;
+            let config = device
+                .default_input_config()
+                .map_err(|e| AudioError::ConfigError(e.to_string()))?;
+
+            let sr = config.sample_rate().0;
+            let ch = config.channels();
+            {
+                let mut ring = RING.lock_recover();
+                ring.samples.clear();
+                let capacity = (sr as usize * ch as usize * duration_ms as usize) / 1000;
+                // Whole frames only, so the snapshot starts on a channel boundary
+                ring.capacity = capacity - capacity % ch.max(1) as usize;
+                ring.samples.reserve(capacity);
+                ring.sample_rate = sr;
+                ring.channels = ch;
+            }
+
+            let stream = device
+                .build_input_stream(
+                    &config.into(),
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        // Never block the audio thread; dropping a chunk only shortens the pre-roll
+                        if let Ok(mut ring) = RING.try_lock() {
+                            let capacity = ring.capacity;
+                            ring.samples.extend(data.iter().copied());
+                            let excess = ring.samples.len().saturating_sub(capacity);
+                            ring.samples.drain(..excess);
+                        }
+                    },
+                    |err| {
+                        log::error!("[preroll] Standby stream error: {}", err);
+                    },
+                    None,
+                )
+                .map_err(|e| AudioError::StreamError(e.to_string()))?;
+            stream.play().map_err(|e| AudioError::PlayError(e.to_string()))?;
+            diag!(Audio, "[preroll] Standby stream started ({}ms, {} Hz, {} channels)", duration_ms, sr, ch);
+            let opened_at = chrono::Local::now();
+
+            let _ = stop_rx.recv();
+            crate::privacy::record_session("preroll", opened_at, None, false, false);
+            Ok(())
+        });
+
+        if let Some(Err(e)) = result {
+            log::error!("Pre-roll thread error: {}", e);
+        }
+    });
+
+    *STANDBY.lock_recover() = Some(StandbyHandle {
+        stop_sender: stop_tx,
+        thread_handle,
+        duration_ms,
+    });
+    Ok(())
+}
+
+fn stop_standby() {
+    let handle = STANDBY.lock().ok().and_then(|mut s| s.take());
+    if let Some(handle) = handle {
+        let _ = handle.stop_sender.send(());
+        let _ = handle.thread_handle.join();
+        if let Ok(mut ring) = RING.lock() {
+            ring.samples.clear();
+        }
+        diag!(Audio, "[preroll] Standby stream stopped");
+    }
+}
+
+/// The buffered pre-roll audio, if the standby stream is running in the same
+/// format as the capture stream (interleaved, `channels` per frame).
+/// Called from the capture callback, so it never blocks.
+pub fn snapshot(sample_rate: u32, channels: u16) -> Option<Vec<f32>> {
+    if !STANDBY.try_lock().map(|s| s.is_some()).unwrap_or(false) {
+        return None;
+    }
+    let ring = RING.try_lock().ok()?;
+    if ring.sample_rate != sample_rate || ring.channels != channels {
+        diag!(Audio,
+            "[preroll] Format mismatch (standby {} Hz/{} ch, capture {} Hz/{} ch), skipping pre-roll",
+            ring.sample_rate, ring.channels, sample_rate, channels
+        );
+        return None;
+    }
+    if ring.samples.is_empty() {
+        return None;
+    }
+    Some(ring.samples.iter().copied().collect())
+}
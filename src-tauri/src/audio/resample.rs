@@ -0,0 +1,217 @@
+//! Resampler quality for converting the input device's rate to Whisper's
+//! 16kHz. Sinc is the cleanest, but it runs inside the CPAL callback, so a
+//! slow machine that can't keep up drops audio. A one-time benchmark picks
+//! the best quality this machine sustains; capture steps down from there if
+//! the callback starts overrunning anyway (see `capture::drain_resampler`).
+
+use rubato::{
+    FastFixedIn, PolynomialDegree, ResampleResult, Resampler, ResamplerConstructionError, SincFixedIn,
+    SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Mono input frames per resampler call.
+pub const CHUNK_SIZE: usize = 1024;
+
+/// Frames per CPAL callback that capture asks for.
+pub const CALLBACK_FRAMES: usize = 256;
+
+/// Share of a callback period that resampling may take in the benchmark; the
+/// rest is for buffering, the level meter and scheduling jitter.
+const BUDGET_SHARE: f64 = 0.25;
+
+/// The benchmark runs at 44.1kHz: a common device rate, and its fractional
+/// ratio to 16kHz is the expensive case.
+const BENCHMARK_RATE: u32 = 44_100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    Linear,
+    Cubic,
+    Sinc,
+}
+
+impl Quality {
+    pub const ALL: [Quality; 3] = [Quality::Linear, Quality::Cubic, Quality::Sinc];
+
+    /// The `recording.resampler_quality` setting; None for "auto" or unknown values.
+    pub fn from_setting(value: Option<&str>) -> Option<Self> {
+        match value? {
+            "linear" => Some(Quality::Linear),
+            "cubic" => Some(Quality::Cubic),
+            "sinc" => Some(Quality::Sinc),
+            _ => None,
+        }
+    }
+
+    /// The next cheaper quality, if any.
+    pub fn lower(self) -> Option<Self> {
+        match self {
+            Quality::Sinc => Some(Quality::Cubic),
+            Quality::Cubic => Some(Quality::Linear),
+            Quality::Linear => None,
+        }
+    }
+}
+
+/// A mono resampler of any quality, taking plain slices so the callback
+/// doesn't allocate to wrap its chunk.
+pub enum AnyResampler {
+    Polynomial(FastFixedIn<f32>),
+    Sinc(SincFixedIn<f32>),
+}
+
+impl AnyResampler {
+    pub fn new(quality: Quality, ratio: f64, chunk_size: usize) -> Result<Self, ResamplerConstructionError> {
+        Ok(match quality {
+            Quality::Linear => {
+                AnyResampler::Polynomial(FastFixedIn::new(ratio, 2.0, PolynomialDegree::Linear, chunk_size, 1)?)
+            }
+            Quality::Cubic => {
+                AnyResampler::Polynomial(FastFixedIn::new(ratio, 2.0, PolynomialDegree::Cubic, chunk_size, 1)?)
+            }
+            Quality::Sinc => {
+                let params = SincInterpolationParameters {
+                    sinc_len: 128,
+                    f_cutoff: 0.95,
+                    oversampling_factor: 128,
+                    interpolation: SincInterpolationType::Linear,
+                    window: WindowFunction::BlackmanHarris2,
+                };
+                AnyResampler::Sinc(SincFixedIn::new(ratio, 2.0, params, chunk_size, 1)?)
+            }
+        })
+    }
+
+    pub fn process(&mut self, chunk: &[f32]) -> ResampleResult<Vec<Vec<f32>>> {
+        match self {
+            AnyResampler::Polynomial(r) => r.process(&[chunk], None),
+            AnyResampler::Sinc(r) => r.process(&[chunk], None),
+        }
+    }
+
+    /// Process a final chunk shorter than the chunk size.
+    pub fn process_partial(&mut self, rest: &[f32]) -> ResampleResult<Vec<Vec<f32>>> {
+        match self {
+            AnyResampler::Polynomial(r) => r.process_partial(Some(&[rest]), None),
+            AnyResampler::Sinc(r) => r.process_partial(Some(&[rest]), None),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        match self {
+            AnyResampler::Polynomial(r) => r.reset(),
+            AnyResampler::Sinc(r) => r.reset(),
+        }
+    }
+}
+
+/// One callback period for `CALLBACK_FRAMES` at `sample_rate`.
+pub fn callback_period(sample_rate: u32) -> Duration {
+    Duration::from_secs_f64(CALLBACK_FRAMES as f64 / sample_rate.max(1) as f64)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityTiming {
+    pub quality: Quality,
+    /// Mean time to resample one chunk
+    pub chunk_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResamplerBenchmark {
+    /// Best quality within the budget; what "auto" uses
+    pub recommended: Quality,
+    pub budget_us: u64,
+    pub timings: Vec<QualityTiming>,
+    /// RFC 3339
+    pub measured_at: String,
+}
+
+/// Time every quality on a second of synthetic 44.1kHz input. Takes a few
+/// tens of milliseconds; the result is kept in settings.
+pub fn benchmark() -> ResamplerBenchmark {
+    let ratio = 16_000.0 / BENCHMARK_RATE as f64;
+    let input: Vec<f32> = (0..BENCHMARK_RATE)
+        .map(|i| (i as f32 * 0.063).sin() * 0.2 + (i as f32 * 0.0071).sin() * 0.1)
+        .collect();
+    let budget = callback_period(BENCHMARK_RATE).mul_f64(BUDGET_SHARE);
+
+    let timings: Vec<QualityTiming> = Quality::ALL
+        .iter()
+        .filter_map(|&quality| {
+            let mut resampler = AnyResampler::new(quality, ratio, CHUNK_SIZE).ok()?;
+            let chunks: Vec<&[f32]> = input.chunks_exact(CHUNK_SIZE).collect();
+            let start = Instant::now();
+            for chunk in &chunks {
+                resampler.process(chunk).ok()?;
+            }
+            let chunk_us = (start.elapsed().as_micros() / chunks.len().max(1) as u128) as u64;
+            Some(QualityTiming { quality, chunk_us })
+        })
+        .collect();
+
+    let budget_us = budget.as_micros() as u64;
+    let recommended = pick(&timings, budget_us);
+    log::info!("Resampler benchmark: {:?} (budget {}µs/chunk) -> {:?}", timings, budget_us, recommended);
+    ResamplerBenchmark {
+        recommended,
+        budget_us,
+        timings,
+        measured_at: chrono::Local::now().to_rfc3339(),
+    }
+}
+
+/// The best quality that resamples a chunk within `budget_us`; linear if none does.
+fn pick(timings: &[QualityTiming], budget_us: u64) -> Quality {
+    timings
+        .iter()
+        .filter(|t| t.chunk_us <= budget_us)
+        .map(|t| t.quality)
+        .max()
+        .unwrap_or(Quality::Linear)
+}
+
+/// The quality capture should use: the setting, or for "auto" the
+/// benchmark's pick (cubic until it has run).
+pub fn configured(setting: Option<&str>, benchmark: Option<&ResamplerBenchmark>) -> Quality {
+    Quality::from_setting(setting)
+        .or_else(|| benchmark.map(|b| b.recommended))
+        .unwrap_or(Quality::Cubic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick() {
+        let timings = [
+            QualityTiming { quality: Quality::Linear, chunk_us: 20 },
+            QualityTiming { quality: Quality::Cubic, chunk_us: 40 },
+            QualityTiming { quality: Quality::Sinc, chunk_us: 400 },
+        ];
+        assert_eq!(pick(&timings, 1000), Quality::Sinc);
+        assert_eq!(pick(&timings, 100), Quality::Cubic);
+        assert_eq!(pick(&timings, 10), Quality::Linear);
+    }
+
+    #[test]
+    fn test_configured() {
+        assert_eq!(configured(Some("sinc"), None), Quality::Sinc);
+        assert_eq!(configured(Some("auto"), None), Quality::Cubic);
+        assert_eq!(Quality::Sinc.lower(), Some(Quality::Cubic));
+        assert_eq!(Quality::Linear.lower(), None);
+    }
+
+    #[test]
+    fn test_resamplers_produce_output() {
+        for quality in Quality::ALL {
+            let mut resampler = AnyResampler::new(quality, 16_000.0 / 48_000.0, CHUNK_SIZE).unwrap();
+            let out = resampler.process(&[0.1; CHUNK_SIZE]).unwrap();
+            assert!(!out[0].is_empty());
+        }
+    }
+}
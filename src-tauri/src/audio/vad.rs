@@ -1,7 +1,79 @@
 //! Voice Activity Detection (VAD)
 //!
-//! Simple energy-based VAD for detecting speech in audio.
-//! For production, consider using Silero VAD or WebRTC VAD.
+//! Speech detection backends behind the `SpeechDetector` trait. Silero (via
+//! whisper-rs) lives with the Whisper engine; this module has the fallbacks
+//! that need no model file: an energy-based detector and, with the `webrtc-vad`
+//! Cargo feature, WebRTC's GMM-based VAD.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicU8, Ordering};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VadError {
+    #[error("VAD model not found")]
+    ModelNotFound,
+    #[error("VAD failed: {0}")]
+    DetectionError(String),
+}
+
+/// Finds speech in 16kHz mono audio.
+pub trait SpeechDetector {
+    /// Backend name for logs ("silero", "webrtc", "energy")
+    fn name(&self) -> &'static str;
+
+    /// Sample ranges containing speech, in order.
+    fn speech_segments(&mut self, samples: &[f32]) -> Result<Vec<Range<usize>>, VadError>;
+}
+
+/// Which detector to use, from `recording.vad_backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadBackend {
+    /// Silero when its model loads, otherwise the best built-in fallback
+    Auto = 0,
+    Silero = 1,
+    WebRtc = 2,
+    Energy = 3,
+}
+
+static BACKEND: AtomicU8 = AtomicU8::new(VadBackend::Auto as u8);
+
+/// Set the VAD backend: "auto" (default), "silero", "webrtc" or "energy".
+/// "webrtc" falls back to "energy" in builds without the `webrtc-vad` feature.
+pub fn set_backend(backend: Option<&str>) {
+    let backend = match backend {
+        Some("silero") => VadBackend::Silero,
+        Some("webrtc") => VadBackend::WebRtc,
+        Some("energy") => VadBackend::Energy,
+        _ => VadBackend::Auto,
+    };
+    BACKEND.store(backend as u8, Ordering::Relaxed);
+}
+
+pub fn backend() -> VadBackend {
+    match BACKEND.load(Ordering::Relaxed) {
+        1 => VadBackend::Silero,
+        2 => VadBackend::WebRtc,
+        3 => VadBackend::Energy,
+        _ => VadBackend::Auto,
+    }
+}
+
+/// The detector used when Silero is unavailable or not selected.
+pub fn fallback_detector(backend: VadBackend) -> Box<dyn SpeechDetector + Send> {
+    #[cfg(feature = "webrtc-vad")]
+    if backend != VadBackend::Energy {
+        return Box::new(WebRtcDetector);
+    }
+    #[cfg(not(feature = "webrtc-vad"))]
+    let _ = backend;
+    Box::new(EnergyDetector::new(VadConfig::default()))
+}
+
+/// 30ms frames at 16kHz (a frame size WebRTC VAD accepts).
+const FRAME_SAMPLES: usize = 480;
+/// Padding kept around each speech segment (30ms).
+const SPEECH_PAD_SAMPLES: usize = 480;
 
 /// Configuration for VAD
 pub struct VadConfig {
@@ -78,6 +150,14 @@ impl VoiceActivityDetector {
     }
 }
 
+/// Whether 16kHz mono `samples` hold any speech by the energy detector's
+/// default thresholds. Cheap enough to run on a clip before deciding whether
+/// it's worth transcribing.
+pub fn contains_speech(samples: &[f32]) -> bool {
+    let mut detector = VoiceActivityDetector::new(VadConfig::default());
+    samples.chunks(FRAME_SAMPLES).any(|frame| detector.process(frame))
+}
+
 /// Calculate RMS energy of audio samples
 fn calculate_energy(samples: &[f32]) -> f32 {
     if samples.is_empty() {
@@ -115,3 +195,123 @@ pub fn trim_silence(samples: &[f32], threshold: f32, frame_size: usize) -> &[f32
         samples
     }
 }
+
+/// Energy detector for whole buffers. The threshold adapts to the recording's
+/// noise floor, so steady background noise isn't mistaken for speech.
+pub struct EnergyDetector {
+    config: VadConfig,
+}
+
+impl EnergyDetector {
+    pub fn new(config: VadConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl SpeechDetector for EnergyDetector {
+    fn name(&self) -> &'static str {
+        "energy"
+    }
+
+    fn speech_segments(&mut self, samples: &[f32]) -> Result<Vec<Range<usize>>, VadError> {
+        let energies: Vec<f32> = samples.chunks(FRAME_SAMPLES).map(calculate_energy).collect();
+        if energies.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut sorted = energies.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let noise_floor = sorted[sorted.len() / 10];
+        let threshold = self.config.energy_threshold.max(noise_floor * 3.0);
+
+        let flags: Vec<bool> = energies.iter().map(|&e| e > threshold).collect();
+        Ok(frames_to_segments(&flags, samples.len(), &self.config))
+    }
+}
+
+/// WebRTC VAD in its most aggressive mode, which rejects the most noise.
+#[cfg(feature = "webrtc-vad")]
+pub struct WebRtcDetector;
+
+#[cfg(feature = "webrtc-vad")]
+impl SpeechDetector for WebRtcDetector {
+    fn name(&self) -> &'static str {
+        "webrtc"
+    }
+
+    fn speech_segments(&mut self, samples: &[f32]) -> Result<Vec<Range<usize>>, VadError> {
+        use webrtc_vad::{SampleRate, Vad, VadMode};
+
+        // Vad holds a raw pointer (not Send), and is cheap to create per call
+        let mut vad = Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, VadMode::VeryAggressive);
+        let mut frame = [0i16; FRAME_SAMPLES];
+        let mut flags = Vec::with_capacity(samples.len() / FRAME_SAMPLES);
+        for chunk in samples.chunks_exact(FRAME_SAMPLES) {
+            for (dst, src) in frame.iter_mut().zip(chunk) {
+                *dst = (src.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            }
+            let voiced = vad
+                .is_voice_segment(&frame)
+                .map_err(|_| VadError::DetectionError("invalid frame".to_string()))?;
+            flags.push(voiced);
+        }
+        Ok(frames_to_segments(&flags, samples.len(), &VadConfig::default()))
+    }
+}
+
+/// Turn per-frame speech flags into padded sample ranges: silences shorter than
+/// `silence_samples` are bridged and runs shorter than `min_speech_samples` dropped.
+fn frames_to_segments(flags: &[bool], total: usize, config: &VadConfig) -> Vec<Range<usize>> {
+    let mut segments: Vec<Range<usize>> = Vec::new();
+    let mut start = None;
+    for (i, &speech) in flags.iter().chain(std::iter::once(&false)).enumerate() {
+        match (speech, start) {
+            (true, None) => start = Some(i * FRAME_SAMPLES),
+            (false, Some(s)) => {
+                let end = (i * FRAME_SAMPLES).min(total);
+                match segments.last_mut() {
+                    Some(last) if s - last.end < config.silence_samples => last.end = end,
+                    _ => segments.push(s..end),
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    segments
+        .into_iter()
+        .filter(|r| r.len() >= config.min_speech_samples)
+        .map(|r| r.start.saturating_sub(SPEECH_PAD_SAMPLES)..(r.end + SPEECH_PAD_SAMPLES).min(total))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_detector() {
+        // 0.9s of faint noise, 0.9s of a loud tone, 0.9s of faint noise
+        let noise = |n: usize| (0..n).map(|i| if i % 2 == 0 { 0.001 } else { -0.001 });
+        let mut samples: Vec<f32> = noise(14400).collect();
+        samples.extend((0..14400).map(|i| (i as f32 * 0.05).sin() * 0.3));
+        samples.extend(noise(14400));
+
+        let mut detector = EnergyDetector::new(VadConfig::default());
+        let segments = detector.speech_segments(&samples).unwrap();
+        assert_eq!(segments, vec![14400 - SPEECH_PAD_SAMPLES..28800 + SPEECH_PAD_SAMPLES]);
+
+        assert!(detector.speech_segments(&vec![0.0; 16000]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_frames_to_segments() {
+        let config = VadConfig::default();
+        // Short gaps are bridged, short blips dropped
+        let mut flags = vec![false; 40];
+        flags[2..10].fill(true);
+        flags[12..20].fill(true);
+        flags[35] = true;
+        let segments = frames_to_segments(&flags, 40 * FRAME_SAMPLES, &config);
+        assert_eq!(segments, vec![2 * FRAME_SAMPLES - SPEECH_PAD_SAMPLES..20 * FRAME_SAMPLES + SPEECH_PAD_SAMPLES]);
+    }
+}
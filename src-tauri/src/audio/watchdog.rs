@@ -0,0 +1,164 @@
+//! Watchdog for capture streams that open fine but deliver no audio, e.g.
+//! because another app has claimed the device or it is muted in hardware.
+//! Such a recording would otherwise run to the end and transcribe to
+//! nothing. While recording, it emits `audio-stalled` when the stream
+//! delivers no callbacks within `NO_CALLBACKS_WITHIN`, or only digital
+//! silence for `SILENT_FOR`, and can restart capture on a fallback device.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::capture;
+
+/// A stream that hasn't called back once by now isn't running.
+const NO_CALLBACKS_WITHIN: Duration = Duration::from_secs(1);
+/// Exact zeros for this long mean a muted or blocked device, not a quiet room.
+const SILENT_FOR: Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bumped on every start and stop; a watchdog thread exits once it changes.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StallKind {
+    NoCallbacks,
+    Silent,
+}
+
+/// Payload of the `audio-stalled` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioStall {
+    pub kind: StallKind,
+    /// Device the stalled stream was opened on
+    pub device: Option<String>,
+    /// What usually causes this, most likely first
+    pub causes: Vec<String>,
+    /// Set when capture was restarted: the device it restarted on
+    pub restarted_on: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WatchdogConfig {
+    /// Restart capture on `fallback_device` after a stall (once per recording)
+    pub restart: bool,
+    /// None = the system default device
+    pub fallback_device: Option<String>,
+}
+
+/// Stall detection for one stream, fed a poll at a time.
+struct Watch {
+    opened: Instant,
+    /// Callback count when the stream was opened or went live
+    baseline: usize,
+    last_signal: Instant,
+    reported: bool,
+}
+
+impl Watch {
+    fn new(now: Instant, callbacks: usize) -> Self {
+        Self { opened: now, baseline: callbacks, last_signal: now, reported: false }
+    }
+
+    /// At most one stall per stream: a restart starts a new `Watch`.
+    fn check(&mut self, now: Instant, callbacks: usize, signal: bool) -> Option<StallKind> {
+        if signal {
+            self.last_signal = now;
+        }
+        if self.reported {
+            return None;
+        }
+        let stall = if callbacks.saturating_sub(self.baseline) == 0 {
+            (now.duration_since(self.opened) >= NO_CALLBACKS_WITHIN).then_some(StallKind::NoCallbacks)
+        } else {
+            (now.duration_since(self.last_signal) >= SILENT_FOR).then_some(StallKind::Silent)
+        };
+        self.reported = stall.is_some();
+        stall
+    }
+}
+
+fn likely_causes(kind: StallKind) -> Vec<String> {
+    let causes: &[&str] = match kind {
+        StallKind::NoCallbacks => &[
+            "Another app is using the microphone exclusively",
+            "The device was unplugged, disabled or went to sleep",
+            "The audio driver stopped responding",
+        ],
+        StallKind::Silent if cfg!(target_os = "macos") => &[
+            "Microphone access is denied in System Settings → Privacy & Security",
+            "The microphone is muted (hardware switch or input volume at zero)",
+            "Another app is holding the device",
+        ],
+        StallKind::Silent => &[
+            "The microphone is muted (hardware switch or system setting)",
+            "Microphone access is blocked in the system privacy settings",
+            "Another app is holding the device",
+        ],
+    };
+    causes.iter().map(|c| c.to_string()).collect()
+}
+
+/// Watch the capture stream of the recording that just started, until `stop`.
+pub fn start(app: AppHandle, config: WatchdogConfig) {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // A signal from the previous recording doesn't count for this one
+    capture::take_signal();
+
+    std::thread::spawn(move || {
+        let mut watch = Watch::new(Instant::now(), capture::callback_count());
+        let mut restarted = false;
+        while GENERATION.load(Ordering::SeqCst) == generation {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some(kind) = watch.check(Instant::now(), capture::callback_count(), capture::take_signal())
+            else {
+                continue;
+            };
+
+            let device = capture::input_device_name();
+            log::warn!("[watchdog] Input stream on {:?} stalled: {:?}", device, kind);
+            let mut stall = AudioStall { kind, device, causes: likely_causes(kind), restarted_on: None };
+            if config.restart && !restarted && GENERATION.load(Ordering::SeqCst) == generation {
+                restarted = true;
+                match capture::restart_capture(config.fallback_device.as_deref()) {
+                    Ok(()) => {
+                        stall.restarted_on =
+                            Some(config.fallback_device.clone().unwrap_or_else(|| "default".to_string()));
+                        // The new stream counts callbacks from zero
+                        watch = Watch::new(Instant::now(), 0);
+                    }
+                    Err(e) => log::warn!("[watchdog] Restarting capture failed: {}", e),
+                }
+            }
+            app.emit("audio-stalled", &stall).ok();
+        }
+    });
+}
+
+/// Stop watching; called when the recording ends.
+pub fn stop() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        let mut watch = Watch::new(t0, 10);
+        assert_eq!(watch.check(at(500), 10, false), None);
+        assert_eq!(watch.check(at(1000), 10, false), Some(StallKind::NoCallbacks));
+        assert_eq!(watch.check(at(5000), 10, false), None);
+
+        let mut watch = Watch::new(t0, 0);
+        assert_eq!(watch.check(at(1000), 40, true), None);
+        assert_eq!(watch.check(at(3500), 90, false), None);
+        assert_eq!(watch.check(at(4000), 100, false), Some(StallKind::Silent));
+    }
+}
@@ -0,0 +1,312 @@
+//! Scheduled backups of all app data, and restoring from them.
+//!
+//! A backup is one JSON archive holding settings, history, the dictionary and
+//! stats as they are on disk, each with a SHA-256 checksum. History lives in
+//! a database, so it is archived as the `history.json` of older versions. Archives are named
+//! by time (`mentascribe-backup-20250301-030000.json`) in the backup folder,
+//! and only the newest `keep` are kept.
+//!
+//! Restoring checks the whole archive first (format version, checksums, and
+//! that every file parses) so a damaged archive changes nothing, then backs up
+//! the current data before replacing it.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::dictionary::DictionaryData;
+use crate::history::HistoryData;
+use crate::settings::{BackupSettings, UserSettings};
+use crate::stats::LocalStats;
+
+/// Bumped when an archive can no longer be read by older versions.
+const FORMAT_VERSION: u32 = 1;
+
+const PREFIX: &str = "mentascribe-backup-";
+const EXTENSION: &str = ".json";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+const DEFAULT_INTERVAL_HOURS: u32 = 24;
+const DEFAULT_KEEP: u32 = 7;
+
+/// Files in the config directory that make up the app's data.
+const FILES: &[&str] = &["settings.json", "history.json", "dictionary.json", "stats.json"];
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Backup version {0} is newer than this app supports")]
+    UnsupportedVersion(u32),
+    #[error("Backup is damaged: {0}")]
+    Invalid(String),
+    #[error("History error: {0}")]
+    HistoryError(#[from] crate::history::HistoryError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupFile {
+    name: String,
+    sha256: String,
+    contents: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Archive {
+    version: u32,
+    created_at: String,
+    files: Vec<BackupFile>,
+}
+
+/// An archive in the backup folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    /// RFC 3339, from the file name
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+pub fn backup_dir(settings: &BackupSettings) -> PathBuf {
+    match settings.folder.as_deref() {
+        Some(folder) if !folder.trim().is_empty() => PathBuf::from(folder),
+        _ => crate::paths::config_dir().join("backups"),
+    }
+}
+
+fn sha256(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+/// When the archive was taken, from a `mentascribe-backup-<stamp>.json` name.
+fn parse_stamp(file_name: &str) -> Option<DateTime<Local>> {
+    let stamp = file_name.strip_prefix(PREFIX)?.strip_suffix(EXTENSION)?;
+    let naive = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Archives in `dir`, newest first.
+fn archives(dir: &Path) -> Result<Vec<(DateTime<Local>, PathBuf)>, BackupError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut found: Vec<_> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let created = parse_stamp(path.file_name()?.to_str()?)?;
+            Some((created, path))
+        })
+        .collect();
+    found.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(found)
+}
+
+pub fn list_backups(settings: &BackupSettings) -> Result<Vec<BackupInfo>, BackupError> {
+    Ok(archives(&backup_dir(settings))?
+        .into_iter()
+        .map(|(created, path)| BackupInfo {
+            size_bytes: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().into_owned(),
+            created_at: created.to_rfc3339(),
+        })
+        .collect())
+}
+
+/// Archives beyond the newest `keep`, given newest first.
+fn expired<T: Clone>(newest_first: &[T], keep: usize) -> Vec<T> {
+    newest_first.iter().skip(keep.max(1)).cloned().collect()
+}
+
+/// A backup is due when there is none younger than the interval.
+fn is_due(newest: Option<DateTime<Local>>, now: DateTime<Local>, interval_hours: u32) -> bool {
+    newest.is_none_or(|newest| now - newest >= chrono::Duration::hours(interval_hours.max(1) as i64))
+}
+
+/// Delete the archives in `dir` beyond the newest `keep`, never `protected`.
+fn rotate(dir: &Path, keep: usize, protected: Option<&Path>) -> Result<(), BackupError> {
+    let protected = protected.and_then(|path| path.canonicalize().ok());
+    let candidates: Vec<_> = archives(dir)?
+        .into_iter()
+        .filter(|(_, path)| protected.as_ref().is_none_or(|p| path.canonicalize().ok().as_ref() != Some(p)))
+        .collect();
+    for old in expired(&candidates, keep) {
+        match std::fs::remove_file(&old.1) {
+            Ok(()) => log::info!("Removed old backup {:?}", old.1),
+            Err(e) => log::warn!("Failed to remove old backup {:?}: {}", old.1, e),
+        }
+    }
+    Ok(())
+}
+
+/// Write an archive of the current data to the backup folder and delete
+/// the oldest ones beyond `keep`. Files that don't exist yet are left out.
+pub fn create_backup(settings: &BackupSettings) -> Result<BackupInfo, BackupError> {
+    backup_keeping(settings, None)
+}
+
+/// `create_backup`, but rotation leaves the archive at `protected` alone.
+fn backup_keeping(settings: &BackupSettings, protected: Option<&Path>) -> Result<BackupInfo, BackupError> {
+    let config_dir = crate::paths::config_dir();
+    let mut files = Vec::new();
+    for name in FILES {
+        let path = config_dir.join(name);
+        let contents = if *name == "history.json" {
+            crate::history::export_json()?
+        } else if path.exists() {
+            std::fs::read_to_string(&path)?
+        } else {
+            continue;
+        };
+        files.push(BackupFile { name: name.to_string(), sha256: sha256(&contents), contents });
+    }
+
+    let now = Local::now();
+    let archive = Archive { version: FORMAT_VERSION, created_at: now.to_rfc3339(), files };
+
+    let dir = backup_dir(settings);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}{}{}", PREFIX, now.format(STAMP_FORMAT), EXTENSION));
+    let contents = serde_json::to_string(&archive)?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, &contents)?;
+    std::fs::rename(&tmp, &path)?;
+    log::info!("Backed up {} files to {:?}", archive.files.len(), path);
+
+    rotate(&dir, settings.keep.unwrap_or(DEFAULT_KEEP) as usize, protected)?;
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().into_owned(),
+        created_at: archive.created_at,
+        size_bytes: contents.len() as u64,
+    })
+}
+
+/// Take a backup if the newest one is older than the interval.
+pub fn backup_if_due(settings: &BackupSettings) -> Result<Option<BackupInfo>, BackupError> {
+    let newest = archives(&backup_dir(settings))?.first().map(|(created, _)| *created);
+    if !is_due(newest, Local::now(), settings.interval_hours.unwrap_or(DEFAULT_INTERVAL_HOURS)) {
+        return Ok(None);
+    }
+    create_backup(settings).map(Some)
+}
+
+/// Check that every file in the archive is intact and readable by this version.
+fn validate(archive: &Archive) -> Result<(), BackupError> {
+    if archive.version > FORMAT_VERSION {
+        return Err(BackupError::UnsupportedVersion(archive.version));
+    }
+    for file in &archive.files {
+        if sha256(&file.contents) != file.sha256 {
+            return Err(BackupError::Invalid(format!("{} does not match its checksum", file.name)));
+        }
+        let parsed = match file.name.as_str() {
+            "settings.json" => serde_json::from_str::<UserSettings>(&file.contents).map(drop),
+            "history.json" => serde_json::from_str::<HistoryData>(&file.contents).map(drop),
+            "dictionary.json" => serde_json::from_str::<DictionaryData>(&file.contents).map(drop),
+            "stats.json" => serde_json::from_str::<LocalStats>(&file.contents).map(drop),
+            name => return Err(BackupError::Invalid(format!("unexpected file {}", name))),
+        };
+        parsed.map_err(|e| BackupError::Invalid(format!("{}: {}", file.name, e)))?;
+    }
+    Ok(())
+}
+
+/// Replace the app's data with the archive at `path`. The current data is
+/// backed up first, without rotating away the archive being restored; returns
+/// that safety backup. Files missing from the archive are left as they are.
+/// The caller reloads settings and caches.
+pub fn restore_backup(path: &Path, settings: &BackupSettings) -> Result<BackupInfo, BackupError> {
+    let archive: Archive = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    validate(&archive)?;
+
+    let safety = backup_keeping(settings, Some(path))?;
+
+    let config_dir = crate::paths::config_dir();
+    std::fs::create_dir_all(&config_dir)?;
+    for file in &archive.files {
+        if file.name == "history.json" {
+            crate::history::restore_json(&file.contents)?;
+            continue;
+        }
+        let target = config_dir.join(&file.name);
+        let tmp = target.with_extension("json.restore");
+        std::fs::write(&tmp, &file.contents)?;
+        std::fs::rename(&tmp, &target)?;
+    }
+    log::info!(
+        "Restored {} files from backup taken {} ({:?})",
+        archive.files.len(),
+        archive.created_at,
+        path
+    );
+    Ok(safety)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(contents: &str) -> Archive {
+        Archive {
+            version: FORMAT_VERSION,
+            created_at: "2025-03-01T03:00:00+00:00".to_string(),
+            files: vec![BackupFile {
+                name: "dictionary.json".to_string(),
+                sha256: sha256(contents),
+                contents: contents.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&archive(r#"{"entries": []}"#)).is_ok());
+
+        let mut tampered = archive(r#"{"entries": []}"#);
+        tampered.files[0].contents = r#"{"entries": null}"#.to_string();
+        assert!(matches!(validate(&tampered), Err(BackupError::Invalid(_))));
+
+        assert!(matches!(validate(&archive("not json")), Err(BackupError::Invalid(_))));
+
+        let mut newer = archive(r#"{"entries": []}"#);
+        newer.version = FORMAT_VERSION + 1;
+        assert!(matches!(validate(&newer), Err(BackupError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn test_schedule_and_rotation() {
+        let stamp = parse_stamp("mentascribe-backup-20250301-030000.json").unwrap();
+        assert!(parse_stamp("mentascribe-backup-20250301.json").is_none());
+        assert!(parse_stamp("notes.json").is_none());
+
+        assert!(is_due(None, stamp, 24));
+        assert!(!is_due(Some(stamp), stamp + chrono::Duration::hours(23), 24));
+        assert!(is_due(Some(stamp), stamp + chrono::Duration::hours(24), 24));
+
+        assert_eq!(expired(&[5, 4, 3, 2], 2), [3, 2]);
+        assert!(expired(&[5, 4], 7).is_empty());
+        // The backup just taken is never removed
+        assert_eq!(expired(&[5, 4], 0), [4]);
+    }
+
+    #[test]
+    fn test_rotation_spares_protected_archive() {
+        let dir = std::env::temp_dir().join(format!("mentascribe-backup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let names = ["20250303-030000", "20250302-030000", "20250301-030000"];
+        for stamp in names {
+            std::fs::write(dir.join(format!("{}{}{}", PREFIX, stamp, EXTENSION)), "{}").unwrap();
+        }
+        let oldest = dir.join(format!("{}{}{}", PREFIX, names[2], EXTENSION));
+
+        rotate(&dir, 1, Some(&oldest)).unwrap();
+        let left: Vec<PathBuf> = archives(&dir).unwrap().into_iter().map(|(_, path)| path).collect();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(left, [dir.join(format!("{}{}{}", PREFIX, names[0], EXTENSION)), oldest]);
+    }
+}
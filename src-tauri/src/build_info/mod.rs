@@ -0,0 +1,113 @@
+//! What this binary was built with, for bug reports: app and dependency
+//! versions, target, enabled cargo features, and the CPU/GPU backends
+//! whisper.cpp was compiled for. The versions come from `build.rs`.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateVersion {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub app_version: &'static str,
+    /// Target triple, e.g. "aarch64-apple-darwin"
+    pub target: &'static str,
+    /// "debug" or "release"
+    pub profile: &'static str,
+    pub rustc: &'static str,
+    /// Cargo features compiled in
+    pub features: Vec<&'static str>,
+    /// Locked versions of the main dependencies
+    pub crates: Vec<CrateVersion>,
+    /// whisper.cpp's `whisper_print_system_info()`, verbatim
+    pub whisper_system_info: String,
+    /// Flags set in `whisper_system_info` ("AVX2", "NEON", "METAL", ...)
+    pub whisper_flags: Vec<String>,
+}
+
+/// Cargo features compiled into this binary.
+fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "voxtral") {
+        features.push("voxtral");
+    }
+    if cfg!(feature = "punctuation") {
+        features.push("punctuation");
+    }
+    if cfg!(feature = "parakeet") {
+        features.push("parakeet");
+    }
+    if cfg!(feature = "webrtc-vad") {
+        features.push("webrtc-vad");
+    }
+    if cfg!(feature = "app-store") {
+        features.push("app-store");
+    }
+    // whisper-rs features, set per target in Cargo.toml
+    if cfg!(target_os = "macos") {
+        features.extend(["whisper-rs/coreml", "whisper-rs/metal"]);
+    }
+    features
+}
+
+/// Parse `MENTASCRIBE_CRATE_VERSIONS` ("tauri=2.5.1,whisper-rs=0.15.1").
+fn parse_crate_versions(list: &str) -> Vec<CrateVersion> {
+    list.split(',')
+        .filter_map(|entry| {
+            let (name, version) = entry.split_once('=')?;
+            Some(CrateVersion { name: name.to_string(), version: version.to_string() })
+        })
+        .collect()
+}
+
+/// Names of the flags set in whisper.cpp's system info, which looks like
+/// "WHISPER : COREML = 0 | OPENVINO = 0 | CPU : SSE3 = 1 | AVX = 1 | ...".
+fn enabled_flags(system_info: &str) -> Vec<String> {
+    system_info
+        .split('|')
+        .filter_map(|field| {
+            // Drop a "CPU :"-style section label before the flag
+            let field = field.rsplit(" : ").next()?;
+            let (name, value) = field.split_once('=')?;
+            (value.trim() == "1").then(|| name.trim().to_string())
+        })
+        .collect()
+}
+
+pub fn build_info() -> BuildInfo {
+    let whisper_system_info = whisper_rs::print_system_info().trim().to_string();
+    BuildInfo {
+        app_version: env!("CARGO_PKG_VERSION"),
+        target: env!("MENTASCRIBE_TARGET"),
+        profile: env!("MENTASCRIBE_PROFILE"),
+        rustc: env!("MENTASCRIBE_RUSTC_VERSION"),
+        features: features(),
+        crates: parse_crate_versions(env!("MENTASCRIBE_CRATE_VERSIONS")),
+        whisper_flags: enabled_flags(&whisper_system_info),
+        whisper_system_info,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_flags() {
+        let info = "WHISPER : COREML = 1 | OPENVINO = 0 | CPU : SSE3 = 1 | AVX = 1 | AVX512 = 0 | METAL = 1 |";
+        assert_eq!(enabled_flags(info), ["COREML", "SSE3", "AVX", "METAL"]);
+        assert!(enabled_flags("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_crate_versions() {
+        let crates = parse_crate_versions("global-hotkey=0.5.5,global-hotkey=0.7.0,whisper-rs=0.15.1");
+        assert_eq!(crates.len(), 3);
+        assert_eq!(crates[2].name, "whisper-rs");
+        assert_eq!(crates[2].version, "0.15.1");
+        assert!(parse_crate_versions("").is_empty());
+    }
+}
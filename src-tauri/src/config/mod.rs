@@ -0,0 +1,107 @@
+//! Export and import of the whole app configuration.
+//!
+//! One JSON file holds settings, the dictionary and optionally stats, so a new
+//! machine is set up by importing it instead of copying the config directory
+//! by hand. Secrets stay out of the file: API keys are only referenced by
+//! their keychain name, a plaintext key (kept when the keychain is
+//! unavailable) is left out, and the proxy password is never serialized.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::dictionary::{self, DictionaryEntry, DictionaryError};
+use crate::settings::UserSettings;
+use crate::stats::{self, LocalStats, StatsError};
+
+/// Bumped when a bundle can no longer be read by older versions.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Dictionary error: {0}")]
+    DictionaryError(#[from] DictionaryError),
+    #[error("Stats error: {0}")]
+    StatsError(#[from] StatsError),
+    #[error("Configuration file version {0} is newer than this app supports")]
+    UnsupportedVersion(u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub settings: UserSettings,
+    #[serde(default)]
+    pub dictionary: Vec<DictionaryEntry>,
+    /// Only when exported with stats
+    #[serde(default)]
+    pub stats: Option<LocalStats>,
+}
+
+/// What an import changed, besides the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub dictionary_entries: usize,
+    pub stats: bool,
+}
+
+/// Write settings, dictionary and, with `include_stats`, stats to `path`.
+pub fn export_config(path: &Path, settings: &UserSettings, include_stats: bool) -> Result<(), ConfigError> {
+    let mut settings = settings.clone();
+    settings.cleanup.api_key = None;
+
+    let bundle = ConfigBundle {
+        version: FORMAT_VERSION,
+        exported_at: Local::now().to_rfc3339(),
+        settings,
+        dictionary: dictionary::get_dictionary()?,
+        stats: if include_stats { Some(stats::get_stats()?) } else { None },
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&bundle)?)?;
+
+    log::info!(
+        "Exported configuration ({} dictionary entries, stats: {}) to {:?}",
+        bundle.dictionary.len(),
+        include_stats,
+        path
+    );
+    Ok(())
+}
+
+pub fn read_bundle(path: &Path) -> Result<ConfigBundle, ConfigError> {
+    let bundle: ConfigBundle = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    if bundle.version > FORMAT_VERSION {
+        return Err(ConfigError::UnsupportedVersion(bundle.version));
+    }
+    Ok(bundle)
+}
+
+/// Import the bundle's dictionary and stats. Settings are applied by the
+/// caller, which validates them and reconfigures the running app. Dictionary
+/// entries are merged into the existing ones; stats, if present, replace them.
+pub fn import_data(bundle: &ConfigBundle) -> Result<ImportSummary, ConfigError> {
+    let dictionary_entries = dictionary::import_entries(&bundle.dictionary)?;
+    if let Some(ref imported) = bundle.stats {
+        stats::save_stats(imported)?;
+    }
+    log::info!(
+        "Imported configuration from {} ({} dictionary entries, stats: {})",
+        bundle.exported_at,
+        dictionary_entries,
+        bundle.stats.is_some()
+    );
+    Ok(ImportSummary {
+        dictionary_entries,
+        stats: bundle.stats.is_some(),
+    })
+}
@@ -0,0 +1,190 @@
+//! Diagnostic output, off unless asked for per subsystem.
+//!
+//! The audio, injection, window and engine paths used to print to stderr
+//! unconditionally, including from the audio callback and the 150ms
+//! reposition poll, which costs latency when the app runs from a terminal and
+//! fills logs. They now go through `diag!`, which checks one atomic flag and
+//! only then formats and logs at debug level, with the subsystem as target
+//! (`diag::audio`, ...). Flags come from the `debug` settings and can be
+//! changed while running; `MENTASCRIBE_DEBUG=audio,window` (or `all`) turns
+//! them on from the environment as well. `diag_every!` additionally throttles
+//! a call site, for messages that can repeat many times a second.
+//!
+//! Errors and warnings don't go through here: they're logged with `log::`.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+use crate::settings::DebugSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Capture, pre-roll and resampling
+    Audio = 1,
+    /// Text injection, AX and clipboard
+    Injection = 2,
+    /// Widget panel, positioning and dragging
+    Window = 4,
+    /// Recording flow, transcription and post-processing
+    Engine = 8,
+}
+
+const ALL: u8 = 1 | 2 | 4 | 8;
+
+impl Subsystem {
+    pub const fn target(self) -> &'static str {
+        match self {
+            Self::Audio => "diag::audio",
+            Self::Injection => "diag::injection",
+            Self::Window => "diag::window",
+            Self::Engine => "diag::engine",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "audio" => Some(Self::Audio),
+            "injection" => Some(Self::Injection),
+            "window" => Some(Self::Window),
+            "engine" => Some(Self::Engine),
+            _ => None,
+        }
+    }
+}
+
+/// Subsystems with output on, as a bit set.
+static ENABLED: AtomicU8 = AtomicU8::new(0);
+
+/// Subsystems turned on through `MENTASCRIBE_DEBUG`, on top of the settings.
+static FROM_ENV: Lazy<u8> = Lazy::new(|| std::env::var("MENTASCRIBE_DEBUG").map(|v| parse(&v)).unwrap_or(0));
+
+/// A comma-separated list of subsystems, or "all".
+fn parse(list: &str) -> u8 {
+    list.split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .fold(0, |bits, name| match name.as_str() {
+            "all" | "1" => ALL,
+            name => bits | Subsystem::from_name(name).map_or(0, |s| s as u8),
+        })
+}
+
+/// Apply the `debug` settings.
+pub fn configure(settings: &DebugSettings) {
+    let mut bits = *FROM_ENV;
+    for (on, subsystem) in [
+        (settings.audio, Subsystem::Audio),
+        (settings.injection, Subsystem::Injection),
+        (settings.window, Subsystem::Window),
+        (settings.engine, Subsystem::Engine),
+    ] {
+        if on {
+            bits |= subsystem as u8;
+        }
+    }
+    if ENABLED.swap(bits, Ordering::Relaxed) != bits {
+        log::info!("Debug output: audio={} injection={} window={} engine={}",
+            bits & Subsystem::Audio as u8 != 0,
+            bits & Subsystem::Injection as u8 != 0,
+            bits & Subsystem::Window as u8 != 0,
+            bits & Subsystem::Engine as u8 != 0,
+        );
+    }
+}
+
+#[inline]
+pub fn enabled(subsystem: Subsystem) -> bool {
+    ENABLED.load(Ordering::Relaxed) & subsystem as u8 != 0
+}
+
+static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Per call site state for `diag_every!`.
+pub struct Throttle {
+    /// Milliseconds since `EPOCH` of the last message let through, plus one
+    /// (0 = never)
+    last_ms: AtomicU64,
+    suppressed: AtomicU32,
+}
+
+impl Throttle {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            last_ms: AtomicU64::new(0),
+            suppressed: AtomicU32::new(0),
+        }
+    }
+
+    /// Whether a message may go out now. If so, returns how many were held
+    /// back since the last one.
+    pub fn pass(&self, interval: Duration) -> Option<u32> {
+        let now = EPOCH.elapsed().as_millis() as u64 + 1;
+        let last = self.last_ms.load(Ordering::Relaxed);
+        if last != 0 && now.saturating_sub(last) < interval.as_millis() as u64 {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        // Another thread got this slot first
+        if self.last_ms.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_err() {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(self.suppressed.swap(0, Ordering::Relaxed))
+    }
+}
+
+/// Log a diagnostic message for a subsystem, if its output is on:
+/// `diag!(Audio, "[capture] Stream started")`. The arguments are only
+/// evaluated then.
+macro_rules! diag {
+    ($subsystem:ident, $($arg:tt)+) => {{
+        if $crate::diag::enabled($crate::diag::Subsystem::$subsystem) {
+            log::debug!(target: $crate::diag::Subsystem::$subsystem.target(), $($arg)+);
+        }
+    }};
+}
+
+/// `diag!` at most once per `interval` for this call site; the next message
+/// through says how many were dropped in between.
+macro_rules! diag_every {
+    ($subsystem:ident, $interval:expr, $($arg:tt)+) => {{
+        static THROTTLE: $crate::diag::Throttle = $crate::diag::Throttle::new();
+        if $crate::diag::enabled($crate::diag::Subsystem::$subsystem) {
+            match THROTTLE.pass($interval) {
+                Some(0) => log::debug!(target: $crate::diag::Subsystem::$subsystem.target(), $($arg)+),
+                Some(suppressed) => log::debug!(
+                    target: $crate::diag::Subsystem::$subsystem.target(),
+                    "{} ({} similar suppressed)",
+                    format_args!($($arg)+),
+                    suppressed
+                ),
+                None => {}
+            }
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("audio, Window"), Subsystem::Audio as u8 | Subsystem::Window as u8);
+        assert_eq!(parse("all"), ALL);
+        assert_eq!(parse("unknown,"), 0);
+    }
+
+    #[test]
+    fn test_throttle() {
+        let throttle = Throttle::new();
+        let interval = Duration::from_secs(60);
+        assert_eq!(throttle.pass(interval), Some(0));
+        assert_eq!(throttle.pass(interval), None);
+        assert_eq!(throttle.pass(interval), None);
+        assert_eq!(throttle.suppressed.load(Ordering::Relaxed), 2);
+        assert_eq!(throttle.pass(Duration::ZERO), Some(2));
+    }
+}
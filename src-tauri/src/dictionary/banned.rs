@@ -0,0 +1,132 @@
+//! Phrases the user never wants in a transcription, e.g. a competitor's
+//! product name the model keeps producing. Stored next to the dictionary in
+//! `banned.json`. Whisper suppresses them while decoding; other engines
+//! don't expose their logits, so the list has no effect there.
+
+use super::DictionaryError;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BannedData {
+    phrases: Vec<String>,
+}
+
+static BANNED_CACHE: Lazy<RwLock<Option<Vec<String>>>> = Lazy::new(|| RwLock::new(None));
+
+fn get_banned_path() -> PathBuf {
+    super::get_dictionary_path().with_file_name("banned.json")
+}
+
+fn load_from_disk() -> Result<Vec<String>, DictionaryError> {
+    let path = get_banned_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    let data: BannedData = serde_json::from_str(&contents)?;
+    Ok(data.phrases)
+}
+
+pub fn get_phrases() -> Result<Vec<String>, DictionaryError> {
+    if let Ok(cache) = BANNED_CACHE.read() {
+        if let Some(ref phrases) = *cache {
+            return Ok(phrases.clone());
+        }
+    }
+    let phrases = load_from_disk()?;
+    if let Ok(mut cache) = BANNED_CACHE.write() {
+        *cache = Some(phrases.clone());
+    }
+    Ok(phrases)
+}
+
+pub(super) fn invalidate_cache() {
+    if let Ok(mut cache) = BANNED_CACHE.write() {
+        *cache = None;
+    }
+}
+
+/// Replace the list. Blank and duplicate (case-insensitive) phrases are dropped.
+pub fn set_phrases(phrases: Vec<String>) -> Result<Vec<String>, DictionaryError> {
+    let phrases = normalize(phrases);
+    let path = get_banned_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let data = BannedData { phrases: phrases.clone() };
+    std::fs::write(&path, serde_json::to_string_pretty(&data)?)?;
+    if let Ok(mut cache) = BANNED_CACHE.write() {
+        *cache = Some(phrases.clone());
+    }
+    log::info!("Banned phrases saved to {:?} ({} phrases)", path, phrases.len());
+    Ok(phrases)
+}
+
+fn normalize(phrases: Vec<String>) -> Vec<String> {
+    let mut kept: Vec<String> = Vec::new();
+    for phrase in phrases {
+        let phrase = phrase.trim();
+        if !phrase.is_empty() && !kept.iter().any(|k| k.eq_ignore_ascii_case(phrase)) {
+            kept.push(phrase.to_string());
+        }
+    }
+    kept
+}
+
+/// Spellings a phrase can be decoded in: as typed, lowercase and capitalized,
+/// each with and without the leading space of a mid-sentence word.
+pub fn variants(phrase: &str) -> Vec<String> {
+    let lower = phrase.to_lowercase();
+    let mut chars = lower.chars();
+    let capitalized = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    let mut out: Vec<String> = Vec::new();
+    for spelling in [phrase.to_string(), lower, capitalized] {
+        for text in [format!(" {}", spelling), spelling] {
+            if !out.contains(&text) {
+                out.push(text);
+            }
+        }
+    }
+    out
+}
+
+/// Tokens that must not come next after `decoded`: the last token of every
+/// sequence whose other tokens `decoded` ends with. Single-token sequences
+/// are therefore always banned, longer ones only once they're under way, so a
+/// phrase's common first word stays available on its own.
+pub fn blocked_tokens<'a>(decoded: &'a [i32], sequences: &'a [Vec<i32>]) -> impl Iterator<Item = i32> + 'a {
+    sequences.iter().filter_map(move |seq| {
+        let (&last, prefix) = seq.split_last()?;
+        decoded.ends_with(prefix).then_some(last)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocked_tokens() {
+        let sequences = vec![vec![7], vec![1, 2, 3]];
+        let blocked = |decoded: &[i32]| blocked_tokens(decoded, &sequences).collect::<Vec<_>>();
+        assert_eq!(blocked(&[]), [7]);
+        assert_eq!(blocked(&[5, 1]), [7]);
+        assert_eq!(blocked(&[5, 1, 2]), [7, 3]);
+        assert_eq!(blocked(&[1, 2, 4]), [7]);
+    }
+
+    #[test]
+    fn test_variants_and_normalize() {
+        assert_eq!(variants("acme"), [" acme", "acme", " Acme", "Acme"]);
+        assert_eq!(
+            normalize(vec![" Acme ".into(), "acme".into(), "".into(), "Globex".into()]),
+            ["Acme", "Globex"]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod banned;
+
+use crate::text::Profile;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -15,30 +18,51 @@ pub enum DictionaryError {
     NotFound(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DictionaryEntry {
     pub id: String,
     pub phrase: String,
     pub replacement: String,
     pub enabled: bool,
     pub synced: bool,
+    /// Not applied to dictations in the code profile
+    #[serde(default)]
+    pub prose_only: bool,
+    /// Only applied when dictating into these apps (bundle id / executable
+    /// name, as in `output.code_apps`); empty = every app
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Times the replacement fired in a saved dictation
+    #[serde(default)]
+    pub hit_count: u64,
+    /// When it last fired (RFC 3339)
+    #[serde(default)]
+    pub last_used: Option<String>,
+}
+
+impl DictionaryEntry {
+    /// Whether the entry applies to text going to `app_id`. Scoped entries
+    /// never apply when the app is unknown.
+    pub fn applies_in(&self, app_id: Option<&str>) -> bool {
+        self.apps.is_empty()
+            || app_id.is_some_and(|id| self.apps.iter().any(|a| a.eq_ignore_ascii_case(id)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct DictionaryData {
+pub(crate) struct DictionaryData {
     entries: Vec<DictionaryEntry>,
 }
 
 /// In-memory cache of dictionary entries. Loaded from disk once on first access,
-/// then only refreshed when mutations (add/update/remove) save back to disk.
+/// then refreshed when mutations (add/update/remove) save back to disk, or by
+/// `reload` when the file is edited outside the app.
 /// Uses RwLock so transcription threads can read concurrently without blocking.
 static DICTIONARY_CACHE: Lazy<RwLock<Option<Vec<DictionaryEntry>>>> =
     Lazy::new(|| RwLock::new(None));
 
-fn get_dictionary_path() -> PathBuf {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-    config_dir.join("mentascribe").join("dictionary.json")
+pub(crate) fn get_dictionary_path() -> PathBuf {
+    crate::paths::config_dir().join("dictionary.json")
 }
 
 fn load_dictionary_from_disk() -> Result<DictionaryData, DictionaryError> {
@@ -91,6 +115,27 @@ fn get_cached_entries() -> Result<Vec<DictionaryEntry>, DictionaryError> {
     Ok(entries)
 }
 
+/// Forget the cached entries and banned phrases; the next access reads the
+/// files again.
+pub fn invalidate_cache() {
+    if let Ok(mut cache) = DICTIONARY_CACHE.write() {
+        *cache = None;
+    }
+    banned::invalidate_cache();
+}
+
+/// Re-read the file into the cache. Returns the entries if they differ from
+/// the cached ones.
+pub fn reload() -> Result<Option<Vec<DictionaryEntry>>, DictionaryError> {
+    let entries = load_dictionary_from_disk()?.entries;
+    let Ok(mut cache) = DICTIONARY_CACHE.write() else { return Ok(None) };
+    if cache.as_ref() == Some(&entries) {
+        return Ok(None);
+    }
+    *cache = Some(entries.clone());
+    Ok(Some(entries))
+}
+
 pub fn get_dictionary() -> Result<Vec<DictionaryEntry>, DictionaryError> {
     get_cached_entries()
 }
@@ -104,6 +149,10 @@ pub fn add_entry(phrase: String, replacement: String) -> Result<DictionaryEntry,
         replacement,
         enabled: true,
         synced: false,
+        prose_only: false,
+        apps: Vec::new(),
+        hit_count: 0,
+        last_used: None,
     };
 
     data.entries.push(entry.clone());
@@ -117,6 +166,8 @@ pub fn update_entry(
     phrase: String,
     replacement: String,
     enabled: bool,
+    prose_only: Option<bool>,
+    apps: Option<Vec<String>>,
 ) -> Result<DictionaryEntry, DictionaryError> {
     let mut data = load_dictionary_from_disk()?;
 
@@ -129,6 +180,12 @@ pub fn update_entry(
     entry.phrase = phrase;
     entry.replacement = replacement;
     entry.enabled = enabled;
+    if let Some(prose_only) = prose_only {
+        entry.prose_only = prose_only;
+    }
+    if let Some(apps) = apps {
+        entry.apps = apps.into_iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect();
+    }
     entry.synced = false;
 
     let updated = entry.clone();
@@ -137,6 +194,60 @@ pub fn update_entry(
     Ok(updated)
 }
 
+/// Make `wrong` be replaced by `right`: the entry for that phrase is updated
+/// (and re-enabled) if there is one, otherwise a new one is added.
+pub fn upsert_correction(wrong: &str, right: &str) -> Result<DictionaryEntry, DictionaryError> {
+    let mut data = load_dictionary_from_disk()?;
+
+    let entry = match data.entries.iter_mut().find(|e| e.phrase.eq_ignore_ascii_case(wrong)) {
+        Some(entry) => {
+            entry.replacement = right.to_string();
+            entry.enabled = true;
+            entry.synced = false;
+            entry.clone()
+        }
+        None => {
+            let entry = DictionaryEntry {
+                id: Uuid::new_v4().to_string(),
+                phrase: wrong.to_string(),
+                replacement: right.to_string(),
+                enabled: true,
+                synced: false,
+                prose_only: false,
+                apps: Vec::new(),
+                hit_count: 0,
+                last_used: None,
+            };
+            data.entries.push(entry.clone());
+            entry
+        }
+    };
+    save_and_cache(&data)?;
+
+    Ok(entry)
+}
+
+/// Merge entries from another machine: each replaces the local entry with the
+/// same id or phrase, or is added. Returns how many were imported.
+pub fn import_entries(entries: &[DictionaryEntry]) -> Result<usize, DictionaryError> {
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    let mut data = load_dictionary_from_disk()?;
+    for imported in entries {
+        let existing = data
+            .entries
+            .iter_mut()
+            .find(|e| e.id == imported.id || e.phrase.eq_ignore_ascii_case(&imported.phrase));
+        match existing {
+            Some(entry) => *entry = imported.clone(),
+            None => data.entries.push(imported.clone()),
+        }
+    }
+    save_and_cache(&data)?;
+    Ok(entries.len())
+}
+
 pub fn remove_entry(id: String) -> Result<bool, DictionaryError> {
     let mut data = load_dictionary_from_disk()?;
     let original_len = data.entries.len();
@@ -172,27 +283,104 @@ pub fn get_vocabulary_prompt() -> Option<String> {
     }
 }
 
+/// Words the dictionary writes with a capital ("Paris", "McKinsey"), which
+/// keep it mid-sentence. Empty if the dictionary can't be read.
+pub fn capitalized_words() -> Vec<String> {
+    let entries = get_enabled_entries().unwrap_or_default();
+    entries
+        .iter()
+        .flat_map(|e| e.replacement.split_whitespace())
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| w.starts_with(char::is_uppercase))
+        .map(|w| w.to_string())
+        .collect()
+}
+
 /// Apply dictionary replacements to text (case-insensitive word boundary matching).
 /// Only applies auto-correct entries (phrase != replacement). Vocabulary entries
-/// are handled upstream via Whisper's initial_prompt.
-pub fn apply_replacements(text: &str) -> Result<String, DictionaryError> {
-    let entries = get_enabled_entries()?;
+/// are handled upstream via Whisper's initial_prompt. Prose-only entries are
+/// skipped in the code profile, and entries scoped to other apps than
+/// `app_id` (the app the text goes to) are skipped. Also returns the ids of
+/// the entries that fired, once per replacement, for `record_hits`.
+pub fn apply_replacements(
+    text: &str,
+    profile: Profile,
+    app_id: Option<&str>,
+) -> Result<(String, Vec<String>), DictionaryError> {
+    let mut entries = get_enabled_entries()?;
+    if profile == Profile::Code {
+        entries.retain(|e| !e.prose_only);
+    }
+    entries.retain(|e| e.applies_in(app_id));
+    Ok(replace_all(text, &entries))
+}
 
-    if entries.is_empty() {
-        return Ok(text.to_string());
+/// Count replacements that made it into a saved dictation. Not called for
+/// previews or reprocessed entries, so each dictation counts once.
+pub fn record_hits(ids: &[String]) -> Result<(), DictionaryError> {
+    if ids.is_empty() {
+        return Ok(());
     }
+    let mut data = load_dictionary_from_disk()?;
+    let now = chrono::Utc::now().to_rfc3339();
+    for entry in data.entries.iter_mut() {
+        let hits = ids.iter().filter(|id| **id == entry.id).count() as u64;
+        if hits > 0 {
+            entry.hit_count += hits;
+            entry.last_used = Some(now.clone());
+        }
+    }
+    save_and_cache(&data)
+}
 
-    let mut result = text.to_string();
+/// Replace every entry's phrase in one pass, so a replacement is never matched
+/// again by another entry ("a" -> "b" and "b" -> "c" turn "a" into "b").
+/// Where phrases overlap, the longest one starting first wins. Returns the
+/// text and the id of the entry behind each replacement.
+fn replace_all(text: &str, entries: &[DictionaryEntry]) -> (String, Vec<String>) {
+    let mut entries: Vec<&DictionaryEntry> = entries.iter().filter(|e| !e.phrase.is_empty()).collect();
+    if entries.is_empty() {
+        return (text.to_string(), Vec::new());
+    }
 
-    for entry in entries {
-        // Case-insensitive replacement with word boundaries
-        let pattern = format!(r"(?i)\b{}\b", regex::escape(&entry.phrase));
-        if let Ok(re) = regex::Regex::new(&pattern) {
-            result = re.replace_all(&result, entry.replacement.as_str()).to_string();
+    // Alternation takes the first alternative that matches, so longest first
+    entries.sort_by_key(|e| std::cmp::Reverse(e.phrase.chars().count()));
+    let alternatives: Vec<String> = entries
+        .iter()
+        .map(|e| format!("({})", regex::escape(&e.phrase)))
+        .collect();
+    let pattern = format!(r"(?i)\b(?:{})\b", alternatives.join("|"));
+    let re = match regex::Regex::new(&pattern) {
+        Ok(re) => re,
+        Err(e) => {
+            log::warn!("Dictionary pattern failed to compile: {}", e);
+            return (text.to_string(), Vec::new());
         }
-    }
+    };
+
+    let mut hits = Vec::new();
+    let replaced = re
+        .replace_all(text, |caps: &regex::Captures| {
+            // Group i + 1 belongs to entries[i]; the replacement is inserted literally
+            let index = (1..caps.len()).find(|&i| caps.get(i).is_some()).unwrap_or(1);
+            let entry = entries[index - 1];
+            hits.push(entry.id.clone());
+            entry.replacement.clone()
+        })
+        .into_owned();
+    (replaced, hits)
+}
 
-    Ok(result)
+/// Whether `phrase` occurs in `text` as whole words, matched as replacements are.
+pub fn mentions(text: &str, phrase: &str) -> bool {
+    !phrase.is_empty()
+        && regex::Regex::new(&format!(r"(?i)\b{}\b", regex::escape(phrase)))
+            .map(|re| re.is_match(text))
+            .unwrap_or(false)
+}
+
+pub fn unsynced_count() -> Result<u64, DictionaryError> {
+    Ok(get_cached_entries()?.iter().filter(|e| !e.synced).count() as u64)
 }
 
 pub fn mark_synced(ids: &[String]) -> Result<(), DictionaryError> {
@@ -207,3 +395,59 @@ pub fn mark_synced(ids: &[String]) -> Result<(), DictionaryError> {
     save_and_cache(&data)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(phrase: &str, replacement: &str) -> DictionaryEntry {
+        DictionaryEntry {
+            id: phrase.to_string(),
+            phrase: phrase.to_string(),
+            replacement: replacement.to_string(),
+            enabled: true,
+            synced: false,
+            prose_only: false,
+            apps: Vec::new(),
+            hit_count: 0,
+            last_used: None,
+        }
+    }
+
+    #[test]
+    fn test_replacements_do_not_cascade() {
+        let entries = [entry("colour", "color"), entry("color", "hue")];
+        assert_eq!(replace_all("Colour and color", &entries).0, "color and hue");
+    }
+
+    #[test]
+    fn test_longest_phrase_wins() {
+        let entries = [entry("new york", "NYC"), entry("new york times", "NYT"), entry("york", "Y")];
+        assert_eq!(replace_all("the new york times in new york", &entries).0, "the NYT in NYC");
+        assert_eq!(replace_all("cost $5", &[entry("cost", "$1 fee")]).0, "$1 fee $5");
+    }
+
+    #[test]
+    fn test_mentions() {
+        assert!(mentions("I use Get Hub daily", "get hub"));
+        assert!(!mentions("forget hubris", "get hub"));
+        assert!(!mentions("anything", ""));
+    }
+
+    #[test]
+    fn test_app_scoping() {
+        let mut scoped = entry("ticket", "JIRA ticket");
+        scoped.apps = vec!["com.google.Chrome".to_string()];
+        assert!(scoped.applies_in(Some("com.google.chrome")));
+        assert!(!scoped.applies_in(Some("com.apple.TextEdit")));
+        assert!(!scoped.applies_in(None));
+        assert!(entry("colour", "color").applies_in(None));
+    }
+
+    #[test]
+    fn test_hits_name_the_entries_that_fired() {
+        let entries = [entry("colour", "color"), entry("centre", "center"), entry("unused", "x")];
+        let (_, hits) = replace_all("Colour, colour and centre", &entries);
+        assert_eq!(hits, ["colour", "colour", "centre"]);
+    }
+}
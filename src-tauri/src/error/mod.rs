@@ -0,0 +1,533 @@
+//! The error every command returns to the frontend.
+//!
+//! Each module keeps its own error enum; `AppError` sorts them into kinds the
+//! UI can act on, so it doesn't have to match on message text. It serializes
+//! as `{ kind, message, recoverable, suggested_action }`, where `message` is
+//! the module error's text.
+
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use crate::api::client::ApiError;
+use crate::audio::capture::AudioError;
+use crate::audio::vad::VadError;
+use crate::backup::BackupError;
+use crate::cleanup::CleanupError;
+use crate::config::ConfigError;
+use crate::dictionary::DictionaryError;
+use crate::history::HistoryError;
+use crate::hotkey::HotkeyError;
+use crate::injection::InjectionError;
+use crate::net::NetError;
+use crate::pipeline::files::FileError;
+use crate::pipeline::PipelineError;
+use crate::plugins::PluginError;
+use crate::privacy::PrivacyError;
+use crate::settings::SettingsError;
+use crate::stats::StatsError;
+use crate::storage::StorageError;
+use crate::transcription::cloud::CloudError;
+use crate::transcription::integrity::IntegrityError;
+use crate::transcription::whisper::WhisperError;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// Bad argument or setting from the caller
+    #[error("{0}")]
+    InvalidInput(String),
+    /// Something a setting should name is missing (provider, endpoint, API key)
+    #[error("{0}")]
+    NotConfigured(String),
+    #[error("{0}")]
+    NotFound(String),
+    /// The operation conflicts with what's running (e.g. already recording)
+    #[error("{0}")]
+    Busy(String),
+    /// A model has to be downloaded first
+    #[error("{0}")]
+    ModelMissing(String),
+    /// A model file is damaged and has to be downloaded again
+    #[error("{0}")]
+    ModelCorrupt(String),
+    #[error("{0}")]
+    Network(String),
+    #[error("{0}")]
+    Timeout(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    /// An OS permission (microphone, accessibility, speech) is missing
+    #[error("{0}")]
+    Permission(String),
+    /// Input device missing or failing
+    #[error("{0}")]
+    Device(String),
+    /// Privacy settings don't allow sending this to a third party
+    #[error("{0}")]
+    PrivacyBlocked(String),
+    /// Sending needs the user's confirmation first (`grant_cloud_consent`)
+    #[error("{0}")]
+    ConsentRequired(String),
+    #[error("{0}")]
+    DiskFull(String),
+    /// Not available in this build or on this OS
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "invalid_input",
+            Self::NotConfigured(_) => "not_configured",
+            Self::NotFound(_) => "not_found",
+            Self::Busy(_) => "busy",
+            Self::ModelMissing(_) => "model_missing",
+            Self::ModelCorrupt(_) => "model_corrupt",
+            Self::Network(_) => "network",
+            Self::Timeout(_) => "timeout",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Permission(_) => "permission",
+            Self::Device(_) => "device",
+            Self::PrivacyBlocked(_) => "privacy_blocked",
+            Self::ConsentRequired(_) => "consent_required",
+            Self::DiskFull(_) => "disk_full",
+            Self::Unsupported(_) => "unsupported",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether the user can get past this without a new version of the app.
+    pub fn recoverable(&self) -> bool {
+        !matches!(self, Self::Unsupported(_) | Self::Internal(_))
+    }
+
+    /// What the UI should offer, if there's one obvious next step.
+    pub fn suggested_action(&self) -> Option<&'static str> {
+        match self {
+            Self::NotConfigured(_) => Some("open_settings"),
+            Self::Network(_) | Self::Timeout(_) => Some("retry"),
+            Self::ModelMissing(_) => Some("download_model"),
+            Self::ModelCorrupt(_) => Some("redownload_model"),
+            Self::Unauthorized(_) => Some("sign_in"),
+            Self::Permission(_) => Some("grant_permission"),
+            Self::Device(_) => Some("check_microphone"),
+            Self::PrivacyBlocked(_) => Some("open_privacy_settings"),
+            Self::ConsentRequired(_) => Some("confirm_upload"),
+            Self::DiskFull(_) => Some("free_disk_space"),
+            Self::InvalidInput(_) | Self::NotFound(_) | Self::Busy(_) | Self::Unsupported(_) | Self::Internal(_) => None,
+        }
+    }
+
+    /// An IO failure, reported as `DiskFull` when that's the cause.
+    fn io(error: &std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::StorageFull {
+            Self::DiskFull(error.to_string())
+        } else {
+            Self::Internal(error.to_string())
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope {
+    kind: &'static str,
+    message: String,
+    recoverable: bool,
+    suggested_action: Option<&'static str>,
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Envelope {
+            kind: self.kind(),
+            message: self.to_string(),
+            recoverable: self.recoverable(),
+            suggested_action: self.suggested_action(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Errors from helpers that only produce a message.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::Internal(message)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::io(&e)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<ApiError> for AppError {
+    fn from(e: ApiError) -> Self {
+        let message = e.to_string();
+        match e {
+            ApiError::Unauthorized => Self::Unauthorized(message),
+            ApiError::RequestError(_) => Self::Network(message),
+            ApiError::ApiError(_) => Self::Internal(message),
+        }
+    }
+}
+
+impl From<AudioError> for AppError {
+    fn from(e: AudioError) -> Self {
+        let message = e.to_string();
+        match e {
+            AudioError::AlreadyRunning | AudioError::NotRunning => Self::Busy(message),
+            AudioError::NoInputDevice
+            | AudioError::DeviceNotFound(_)
+            | AudioError::ConfigError(_)
+            | AudioError::StreamError(_)
+            | AudioError::PlayError(_) => Self::Device(message),
+        }
+    }
+}
+
+impl From<VadError> for AppError {
+    fn from(e: VadError) -> Self {
+        let message = e.to_string();
+        match e {
+            VadError::ModelNotFound => Self::ModelMissing(message),
+            VadError::DetectionError(_) => Self::Internal(message),
+        }
+    }
+}
+
+impl From<BackupError> for AppError {
+    fn from(e: BackupError) -> Self {
+        match e {
+            BackupError::IoError(ref io) => Self::io(io),
+            BackupError::SerdeError(_) | BackupError::Invalid(_) => Self::InvalidInput(e.to_string()),
+            BackupError::UnsupportedVersion(_) => Self::Unsupported(e.to_string()),
+            BackupError::HistoryError(e) => e.into(),
+        }
+    }
+}
+
+impl From<CleanupError> for AppError {
+    fn from(e: CleanupError) -> Self {
+        let message = e.to_string();
+        match e {
+            CleanupError::UnknownProvider(_) | CleanupError::NoEndpoint | CleanupError::NoApiKey(_) => {
+                Self::NotConfigured(message)
+            }
+            CleanupError::RequestError(_) => Self::Network(message),
+            CleanupError::Timeout(_) => Self::Timeout(message),
+            CleanupError::ApiError(_, 401 | 403, _) => Self::Unauthorized(message),
+            CleanupError::ApiError(..) | CleanupError::UnexpectedResponse(_) => Self::Internal(message),
+            CleanupError::Privacy(e) => e.into(),
+            CleanupError::Net(e) => e.into(),
+        }
+    }
+}
+
+impl From<ConfigError> for AppError {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::IoError(ref io) => Self::io(io),
+            ConfigError::SerdeError(_) => Self::InvalidInput(e.to_string()),
+            ConfigError::DictionaryError(e) => e.into(),
+            ConfigError::StatsError(e) => e.into(),
+            ConfigError::UnsupportedVersion(_) => Self::Unsupported(e.to_string()),
+        }
+    }
+}
+
+impl From<DictionaryError> for AppError {
+    fn from(e: DictionaryError) -> Self {
+        match e {
+            DictionaryError::IoError(ref io) => Self::io(io),
+            DictionaryError::SerdeError(_) => Self::Internal(e.to_string()),
+            DictionaryError::NotFound(_) => Self::NotFound(e.to_string()),
+        }
+    }
+}
+
+impl From<HistoryError> for AppError {
+    fn from(e: HistoryError) -> Self {
+        match e {
+            HistoryError::IoError(ref io) => Self::io(io),
+            HistoryError::SerdeError(_) => Self::Internal(e.to_string()),
+            HistoryError::InvalidQuery(_) => Self::InvalidInput(e.to_string()),
+            HistoryError::DatabaseError(ref db) if db.sqlite_error_code() == Some(rusqlite::ErrorCode::DiskFull) => {
+                Self::DiskFull(e.to_string())
+            }
+            HistoryError::DatabaseError(_) => Self::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<HotkeyError> for AppError {
+    fn from(e: HotkeyError) -> Self {
+        let message = e.to_string();
+        match e {
+            HotkeyError::RegisterError(_) => Self::Busy(message),
+            HotkeyError::UnknownKey(_)
+            | HotkeyError::InvalidKey(..)
+            | HotkeyError::Reserved(..)
+            | HotkeyError::NeedsModifier(..) => Self::InvalidInput(message),
+            HotkeyError::Unsupported(..) => Self::Unsupported(message),
+        }
+    }
+}
+
+impl From<InjectionError> for AppError {
+    fn from(e: InjectionError) -> Self {
+        let message = e.to_string();
+        match e {
+            InjectionError::AccessibilityPermissionRequired | InjectionError::PasteManually => {
+                Self::Permission(message)
+            }
+            // Installing one of the tools fixes it
+            InjectionError::WaylandToolMissing => Self::NotConfigured(message),
+            InjectionError::Failed(_) => Self::Internal(message),
+        }
+    }
+}
+
+impl From<NetError> for AppError {
+    fn from(e: NetError) -> Self {
+        match e {
+            NetError::InvalidProxy(_) => Self::NotConfigured(e.to_string()),
+            NetError::ClientError(_) => Self::Internal(e.to_string()),
+            NetError::AllMirrorsFailed(_) | NetError::DownloadError(_) => Self::Network(e.to_string()),
+            NetError::IoError(ref io) => Self::io(io),
+        }
+    }
+}
+
+impl From<FileError> for AppError {
+    fn from(e: FileError) -> Self {
+        let message = e.to_string();
+        match e {
+            FileError::IoError(ref io) => Self::io(io),
+            FileError::DecodeError(..) => Self::InvalidInput(message),
+            FileError::Unsupported(_) => Self::Unsupported(message),
+            FileError::NothingToTranscribe => Self::NotFound(message),
+            FileError::ClipboardError(_) => Self::Internal(message),
+        }
+    }
+}
+
+impl From<PipelineError> for AppError {
+    fn from(e: PipelineError) -> Self {
+        match e {
+            PipelineError::AudioError(e) => e.into(),
+            // The engine's error, which says what went wrong (e.g. a missing model)
+            PipelineError::TranscriptionError(e) => e,
+            PipelineError::HistoryError(e) => e.into(),
+            PipelineError::EntryNotFound(_) => Self::NotFound(e.to_string()),
+            PipelineError::DictionaryError(e) => e.into(),
+        }
+    }
+}
+
+impl From<PluginError> for AppError {
+    fn from(e: PluginError) -> Self {
+        let message = e.to_string();
+        match e {
+            PluginError::IoError(ref io) => Self::io(io),
+            PluginError::NotFound(_) => Self::NotFound(message),
+            PluginError::NotAllowed(_) => Self::PrivacyBlocked(message),
+            PluginError::Timeout(_) => Self::Timeout(message),
+            PluginError::ManifestError(_) => Self::InvalidInput(message),
+            PluginError::NoSandbox(_) => Self::Unsupported(message),
+            PluginError::Failed { .. } | PluginError::InvalidOutput(_) | PluginError::Reported(_) => {
+                Self::Internal(message)
+            }
+        }
+    }
+}
+
+impl From<PrivacyError> for AppError {
+    fn from(e: PrivacyError) -> Self {
+        match e {
+            PrivacyError::IoError(ref io) => Self::io(io),
+            PrivacyError::SerdeError(_) => Self::Internal(e.to_string()),
+            PrivacyError::CloudNotAllowed(_) => Self::PrivacyBlocked(e.to_string()),
+            PrivacyError::ConsentRequired(_) => Self::ConsentRequired(e.to_string()),
+        }
+    }
+}
+
+impl From<SettingsError> for AppError {
+    fn from(e: SettingsError) -> Self {
+        match e {
+            SettingsError::IoError(ref io) => Self::io(io),
+            SettingsError::SerdeError(_) => Self::InvalidInput(e.to_string()),
+            SettingsError::KeychainError(_) => Self::Permission(e.to_string()),
+        }
+    }
+}
+
+impl From<StatsError> for AppError {
+    fn from(e: StatsError) -> Self {
+        match e {
+            StatsError::IoError(ref io) => Self::io(io),
+            StatsError::SerdeError(_) => Self::Internal(e.to_string()),
+            StatsError::UnsupportedFormat(_) => Self::InvalidInput(e.to_string()),
+        }
+    }
+}
+
+impl From<StorageError> for AppError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::IoError(ref io) => Self::io(io),
+            StorageError::SerdeError(_) => Self::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<CloudError> for AppError {
+    fn from(e: CloudError) -> Self {
+        let message = e.to_string();
+        match e {
+            CloudError::NoProvider | CloudError::MissingApiKey(_) | CloudError::Keychain(_) => {
+                Self::NotConfigured(message)
+            }
+            CloudError::RequestError(_) => Self::Network(message),
+            CloudError::ApiError(_) => Self::Internal(message),
+            CloudError::Timeout(_) => Self::Timeout(message),
+            CloudError::Privacy(e) => e.into(),
+        }
+    }
+}
+
+impl From<IntegrityError> for AppError {
+    fn from(e: IntegrityError) -> Self {
+        match e {
+            IntegrityError::Mismatch { .. } => Self::ModelCorrupt(e.to_string()),
+            IntegrityError::IoError(ref io) => Self::io(io),
+        }
+    }
+}
+
+impl From<WhisperError> for AppError {
+    fn from(e: WhisperError) -> Self {
+        let message = e.to_string();
+        match e {
+            WhisperError::ModelNotFound(_) => Self::ModelMissing(message),
+            WhisperError::DownloadError(_) => Self::Network(message),
+            WhisperError::TranscriptionError(_) => Self::Internal(message),
+            WhisperError::IoError(ref io) => Self::io(io),
+            WhisperError::IntegrityError(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(feature = "voxtral")]
+impl From<crate::transcription::voxtral::VoxtralError> for AppError {
+    fn from(e: crate::transcription::voxtral::VoxtralError) -> Self {
+        use crate::transcription::voxtral::VoxtralError;
+        let message = e.to_string();
+        match e {
+            VoxtralError::ModelNotFound(_) => Self::ModelMissing(message),
+            VoxtralError::DownloadError(_) => Self::Network(message),
+            VoxtralError::TranscriptionError(_) => Self::Internal(message),
+            VoxtralError::IoError(ref io) => Self::io(io),
+            VoxtralError::IntegrityError(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(feature = "parakeet")]
+impl From<crate::transcription::parakeet::ParakeetError> for AppError {
+    fn from(e: crate::transcription::parakeet::ParakeetError) -> Self {
+        use crate::transcription::parakeet::ParakeetError;
+        let message = e.to_string();
+        match e {
+            ParakeetError::ModelNotFound => Self::ModelMissing(message),
+            ParakeetError::DownloadError(_) => Self::Network(message),
+            ParakeetError::TranscriptionError(_) => Self::Internal(message),
+            ParakeetError::IoError(ref io) => Self::io(io),
+            ParakeetError::IntegrityError(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(feature = "punctuation")]
+impl From<crate::text::punctuation::PunctuationError> for AppError {
+    fn from(e: crate::text::punctuation::PunctuationError) -> Self {
+        use crate::text::punctuation::PunctuationError;
+        let message = e.to_string();
+        match e {
+            PunctuationError::ModelNotFound => Self::ModelMissing(message),
+            PunctuationError::DownloadError(_) => Self::Network(message),
+            PunctuationError::InferenceError(_) => Self::Internal(message),
+            PunctuationError::IoError(ref io) => Self::io(io),
+            PunctuationError::IntegrityError(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl From<crate::transcription::apple::AppleSpeechError> for AppError {
+    fn from(e: crate::transcription::apple::AppleSpeechError) -> Self {
+        use crate::transcription::apple::AppleSpeechError;
+        let message = e.to_string();
+        match e {
+            AppleSpeechError::NotAuthorized => Self::Permission(message),
+            AppleSpeechError::Unavailable(_) => Self::Unsupported(message),
+            AppleSpeechError::TranscriptionError(_) => Self::Internal(message),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<crate::transcription::sapi::SapiError> for AppError {
+    fn from(e: crate::transcription::sapi::SapiError) -> Self {
+        use crate::transcription::sapi::SapiError;
+        let message = e.to_string();
+        match e {
+            SapiError::NoRecognizer(_) => Self::NotConfigured(message),
+            SapiError::Com(_) | SapiError::TranscriptionError(_) => Self::Internal(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let error = AppError::from(PrivacyError::ConsentRequired("audio to Apple".to_string()));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "consent_required",
+                "message": "Sending audio to Apple needs confirmation for this recording",
+                "recoverable": true,
+                "suggested_action": "confirm_upload",
+            })
+        );
+
+        let full = std::io::Error::new(std::io::ErrorKind::StorageFull, "no space left");
+        assert_eq!(AppError::from(HistoryError::IoError(full)).kind(), "disk_full");
+        assert!(!AppError::from("unexpected".to_string()).recoverable());
+    }
+}
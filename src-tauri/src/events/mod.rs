@@ -0,0 +1,128 @@
+//! Rate limiting for the high-frequency events sent to the webview.
+//!
+//! Level meters poll at `interval()`, set from `widget.event_rate_hz`. Events
+//! whose payload can change faster than that (partial transcripts) go through
+//! `emit_coalesced`: at most one per interval, and when several arrive in
+//! between only the latest is sent.
+//!
+//! `transcription-partial` carries the recording's text so far. Whisper and
+//! Voxtral send it from their streaming loops as each utterance or token
+//! arrives; the level meter polls the other engines.
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const DEFAULT_RATE_HZ: u32 = 40;
+const MIN_RATE_HZ: u32 = 5;
+const MAX_RATE_HZ: u32 = 60;
+
+static RATE_HZ: AtomicU32 = AtomicU32::new(DEFAULT_RATE_HZ);
+
+/// Per coalesced event: when it was last sent, and the payload waiting for
+/// the next slot (with a flush thread scheduled) if any.
+#[derive(Default)]
+struct Slot {
+    last_emit: Option<Instant>,
+    pending: Option<serde_json::Value>,
+}
+
+static SLOTS: Lazy<Mutex<HashMap<&'static str, Slot>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn configure(rate_hz: Option<u32>) {
+    let rate = rate_hz.unwrap_or(DEFAULT_RATE_HZ).clamp(MIN_RATE_HZ, MAX_RATE_HZ);
+    if RATE_HZ.swap(rate, Ordering::Relaxed) != rate {
+        log::info!("Event rate set to {} Hz", rate);
+    }
+}
+
+/// Time between two events of the same kind.
+pub fn interval() -> Duration {
+    Duration::from_millis(1000 / RATE_HZ.load(Ordering::Relaxed) as u64)
+}
+
+/// Emit `event` now if the last one went out at least an interval ago,
+/// otherwise send `payload` (or a newer one) when the interval is up.
+pub fn emit_coalesced<S: Serialize>(app: &AppHandle, event: &'static str, payload: S) {
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::warn!("Failed to serialize {} payload: {}", event, e);
+            return;
+        }
+    };
+    let Ok(mut slots) = SLOTS.lock() else { return };
+    let slot = slots.entry(event).or_default();
+    let wait = slot
+        .last_emit
+        .map(|last| interval().saturating_sub(last.elapsed()))
+        .unwrap_or_default();
+
+    if wait.is_zero() {
+        slot.last_emit = Some(Instant::now());
+        slot.pending = None;
+        app.emit(event, payload).ok();
+        return;
+    }
+
+    // A flush is already scheduled if something is pending; it sends the newest payload
+    if slot.pending.replace(payload).is_none() {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(wait);
+            let Ok(mut slots) = SLOTS.lock() else { return };
+            if let Some(slot) = slots.get_mut(event) {
+                if let Some(payload) = slot.pending.take() {
+                    slot.last_emit = Some(Instant::now());
+                    app.emit(event, payload).ok();
+                }
+            }
+        });
+    }
+}
+
+/// Drop a payload still waiting to be sent, e.g. a partial transcript once
+/// the final one has been emitted.
+pub fn discard_pending(event: &'static str) {
+    if let Ok(mut slots) = SLOTS.lock() {
+        if let Some(slot) = slots.get_mut(event) {
+            slot.pending = None;
+        }
+    }
+}
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
+
+/// Text last sent as `transcription-partial` this recording.
+static LAST_PARTIAL: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
+
+/// Enables `partial`, for code that has no `AppHandle` of its own.
+pub fn set_app(app: AppHandle) {
+    APP.set(app).ok();
+}
+
+/// Emit the current recording's text so far as `transcription-partial`,
+/// unless it's what was sent last.
+pub fn partial(text: String) {
+    let Some(app) = APP.get() else { return };
+    {
+        let Ok(mut last) = LAST_PARTIAL.lock() else { return };
+        if *last == text {
+            return;
+        }
+        last.clone_from(&text);
+    }
+    emit_coalesced(app, "transcription-partial", text);
+}
+
+/// Forget the previous recording's text; call when a recording starts.
+pub fn reset_partial() {
+    if let Ok(mut last) = LAST_PARTIAL.lock() {
+        last.clear();
+    }
+    discard_pending("transcription-partial");
+}
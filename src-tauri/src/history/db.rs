@@ -0,0 +1,272 @@
+//! SQLite storage behind `history`.
+//!
+//! Entries live in `history.db` in the config directory, one row each, so a
+//! new dictation is one insert rather than a rewrite of the whole history.
+//! Segments and timings are stored as JSON. Times are indexed for date
+//! ranges and sorting, and a trigram full-text index over the text serves
+//! substring search.
+//!
+//! The schema is a list of steps applied in order, the number applied so far
+//! kept in `PRAGMA user_version`. On first open, the `history.json` of older
+//! versions is imported and renamed to `history.json.imported`.
+
+use chrono::DateTime;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{HistoryData, HistoryError, TranscriptionEntry};
+use crate::panics::LockExt;
+
+/// Schema steps; append new ones, never edit a released step.
+const SCHEMA: &[&str] = &[
+    // 1: entries, with `seq` as a stable rowid for the full-text index
+    // (VACUUM may renumber implicit rowids)
+    "CREATE TABLE entries (
+        seq INTEGER PRIMARY KEY,
+        id TEXT NOT NULL UNIQUE,
+        text TEXT NOT NULL,
+        raw_text TEXT,
+        word_count INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        timestamp TEXT NOT NULL,
+        timestamp_ms INTEGER NOT NULL,
+        synced INTEGER NOT NULL DEFAULT 0,
+        segments TEXT,
+        timings TEXT,
+        session_id TEXT,
+        app_id TEXT,
+        sent_to_third_party INTEGER NOT NULL DEFAULT 0,
+        language TEXT
+    );
+    CREATE INDEX entries_by_time ON entries (timestamp_ms, id);
+    CREATE INDEX entries_by_session ON entries (session_id);
+    CREATE VIRTUAL TABLE entries_fts USING fts5 (
+        text, content = 'entries', content_rowid = 'seq', tokenize = 'trigram'
+    );
+    CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+        INSERT INTO entries_fts (rowid, text) VALUES (new.seq, new.text);
+    END;
+    CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.seq, old.text);
+    END;
+    CREATE TRIGGER entries_fts_update AFTER UPDATE OF text ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, text) VALUES ('delete', old.seq, old.text);
+        INSERT INTO entries_fts (rowid, text) VALUES (new.seq, new.text);
+    END;
+    CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+];
+
+/// Columns `entry_from_row` reads, in order.
+pub(super) const COLUMNS: &str = "id, text, raw_text, word_count, duration_ms, timestamp, synced, \
+    segments, timings, session_id, app_id, sent_to_third_party, language";
+
+static DB: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+
+pub(super) fn path() -> PathBuf {
+    crate::paths::config_dir().join("history.db")
+}
+
+fn legacy_path() -> PathBuf {
+    crate::paths::config_dir().join("history.json")
+}
+
+/// Epoch milliseconds of an RFC 3339 timestamp.
+pub(super) fn timestamp_ms(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.timestamp_millis())
+}
+
+/// Run `f` on the history database, opening it (and importing the legacy
+/// JSON file) on first use.
+pub(super) fn with<T>(f: impl FnOnce(&mut Connection) -> Result<T, HistoryError>) -> Result<T, HistoryError> {
+    let mut db = DB.lock_recover();
+    let conn = match db.take() {
+        Some(conn) => conn,
+        None => open(&path(), &legacy_path())?,
+    };
+    f(db.insert(conn))
+}
+
+fn open(path: &Path, legacy: &Path) -> Result<Connection, HistoryError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut conn = Connection::open(path)?;
+    migrate(&mut conn)?;
+    if legacy.exists() {
+        import_legacy(&mut conn, legacy)?;
+    }
+    log::info!("History database opened at {:?}", path);
+    Ok(conn)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), HistoryError> {
+    let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, step) in SCHEMA.iter().enumerate().skip(applied) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(step)?;
+        tx.pragma_update(None, "user_version", (i + 1) as i64)?;
+        tx.commit()?;
+        log::info!("History schema migrated to version {}", i + 1);
+    }
+    Ok(())
+}
+
+/// Import `history.json` and rename it out of the way. Entries already
+/// imported are skipped, so an interrupted import is simply done again.
+fn import_legacy(conn: &mut Connection, legacy: &Path) -> Result<(), HistoryError> {
+    let data: HistoryData = serde_json::from_str(&std::fs::read_to_string(legacy)?)?;
+    replace(conn, &data, false)?;
+    std::fs::rename(legacy, legacy.with_extension("json.imported"))?;
+    log::info!("Imported {} history entries from {:?}", data.entries.len(), legacy);
+    Ok(())
+}
+
+/// Add `data`'s entries (newest first, as in `history.json`), after deleting
+/// every entry first with `clear`.
+pub(super) fn replace(conn: &mut Connection, data: &HistoryData, clear: bool) -> Result<(), HistoryError> {
+    let tx = conn.transaction()?;
+    if clear {
+        tx.execute("DELETE FROM entries", [])?;
+    }
+    // Oldest first, so `seq` follows the order they were added in
+    for entry in data.entries.iter().rev() {
+        insert(&tx, entry)?;
+    }
+    if let Some(last_compacted) = data.last_compacted.as_deref() {
+        set_meta(&tx, "last_compacted", last_compacted)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Insert `entry`, unless one with its id exists.
+pub(super) fn insert(conn: &Connection, entry: &TranscriptionEntry) -> Result<(), HistoryError> {
+    conn.execute(
+        "INSERT OR IGNORE INTO entries (id, text, raw_text, word_count, duration_ms, timestamp, timestamp_ms, \
+         synced, segments, timings, session_id, app_id, sent_to_third_party, language) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![
+            entry.id,
+            entry.text,
+            entry.raw_text,
+            entry.word_count,
+            entry.duration_ms,
+            entry.timestamp,
+            // Unparseable timestamps sort as the oldest, as they did in `history.json`
+            timestamp_ms(&entry.timestamp).unwrap_or(0),
+            entry.synced,
+            serde_json::to_string(&entry.segments)?,
+            entry.timings.as_ref().map(serde_json::to_string).transpose()?,
+            entry.session_id,
+            entry.app_id,
+            entry.sent_to_third_party,
+            entry.language,
+        ],
+    )?;
+    Ok(())
+}
+
+/// A JSON column, None when NULL.
+fn json_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<Option<T>> {
+    row.get::<_, Option<String>>(index)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// An entry from a row selected with `COLUMNS`.
+pub(super) fn entry_from_row(row: &Row) -> rusqlite::Result<TranscriptionEntry> {
+    Ok(TranscriptionEntry {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        raw_text: row.get(2)?,
+        word_count: row.get(3)?,
+        duration_ms: row.get(4)?,
+        timestamp: row.get(5)?,
+        synced: row.get(6)?,
+        segments: json_column(row, 7)?.unwrap_or_default(),
+        timings: json_column(row, 8)?,
+        session_id: row.get(9)?,
+        app_id: row.get(10)?,
+        sent_to_third_party: row.get(11)?,
+        language: row.get(12)?,
+    })
+}
+
+/// Entries selected by `sql` (which selects `COLUMNS`) with `params`.
+pub(super) fn entries<P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Vec<TranscriptionEntry>, HistoryError> {
+    let mut statement = conn.prepare(sql)?;
+    let entries = statement.query_map(params, entry_from_row)?.collect::<Result<_, _>>()?;
+    Ok(entries)
+}
+
+pub(super) fn meta(conn: &Connection, key: &str) -> Result<Option<String>, HistoryError> {
+    Ok(conn
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()?)
+}
+
+pub(super) fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<(), HistoryError> {
+    conn.execute("INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)", [key, value])?;
+    Ok(())
+}
+
+/// An empty database in memory, for tests.
+#[cfg(test)]
+pub(super) fn memory() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_legacy() {
+        let dir = std::env::temp_dir().join(format!("mentascribe-history-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy = dir.join("history.json");
+        std::fs::write(
+            &legacy,
+            r#"{"entries": [
+                {"id": "b", "text": "newer", "word_count": 1, "duration_ms": 500,
+                 "timestamp": "2025-03-02T09:00:00+00:00", "synced": false},
+                {"id": "a", "text": "older", "word_count": 1, "duration_ms": 500,
+                 "timestamp": "2025-03-01T09:00:00+00:00", "synced": true, "segments": []}
+            ], "last_compacted": "2025-03-01T00:00:00+00:00"}"#,
+        )
+        .unwrap();
+
+        let conn = open(&dir.join("history.db"), &legacy).unwrap();
+        let imported = entries(&conn, &format!("SELECT {} FROM entries ORDER BY seq", COLUMNS), []).unwrap();
+        assert_eq!(imported.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert!(imported[0].synced);
+        assert!(imported[1].timings.is_none());
+        assert_eq!(meta(&conn, "last_compacted").unwrap().as_deref(), Some("2025-03-01T00:00:00+00:00"));
+        assert!(!legacy.exists());
+        assert!(dir.join("history.json.imported").exists());
+
+        // The full-text index follows inserts, updates and deletes
+        let matching = |conn: &Connection, text: &str| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH ?1", [text], |r| r.get(0))
+                .unwrap()
+        };
+        assert_eq!(matching(&conn, "\"olde\""), 1);
+        conn.execute("UPDATE entries SET text = 'renamed' WHERE id = 'a'", []).unwrap();
+        assert_eq!(matching(&conn, "\"olde\""), 0);
+        conn.execute("DELETE FROM entries WHERE id = 'b'", []).unwrap();
+        assert_eq!(matching(&conn, "\"newer\""), 0);
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,140 +1,446 @@
-use chrono::Local;
+mod db;
+pub mod query;
+pub mod recent;
+pub mod search;
+
+use chrono::{DateTime, Local};
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::latency::TranscriptionTimings;
+use crate::text::diff::{self, DiffChunk};
+use crate::transcription::SegmentInfo;
+
 #[derive(Error, Debug)]
 pub enum HistoryError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     SerdeError(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+    #[error("Invalid history query: {0}")]
+    InvalidQuery(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionEntry {
     pub id: String,
     pub text: String,
+    /// Engine output before cleanup (punctuation, capitalization, dictionary);
+    /// None when cleanup left it unchanged
+    #[serde(default)]
+    pub raw_text: Option<String>,
     pub word_count: u32,
     pub duration_ms: u32,
     pub timestamp: String,
     pub synced: bool,
+    /// How the final text was assembled (streaming segments followed by the tail)
+    #[serde(default)]
+    pub segments: Vec<SegmentInfo>,
+    /// Where the time went between stop and injection
+    #[serde(default)]
+    pub timings: Option<TranscriptionTimings>,
+    /// Dictation session this entry belongs to; None for entries saved before
+    /// sessions existed (each is then its own session, keyed by its id)
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// App the text was dictated into (bundle id / executable name), if known
+    #[serde(default)]
+    pub app_id: Option<String>,
+    /// The audio or text was sent to a third party (e.g. Apple's speech servers)
+    #[serde(default)]
+    pub sent_to_third_party: bool,
+    /// Language the dictation was transcribed in; None when auto-detected
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl TranscriptionEntry {
+    fn session_key(&self) -> &str {
+        self.session_id.as_deref().unwrap_or(&self.id)
+    }
 }
 
+/// History as one JSON document, newest entry first: the `history.json` of
+/// older versions, and history in backups.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct HistoryData {
+pub(crate) struct HistoryData {
     entries: Vec<TranscriptionEntry>,
+    /// When `compact` last ran (RFC 3339)
+    #[serde(default)]
+    last_compacted: Option<String>,
 }
 
-fn get_history_path() -> PathBuf {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-    config_dir.join("mentascribe").join("history.json")
+/// How often `compact_if_due` compacts.
+const AUTO_COMPACT_INTERVAL_DAYS: i64 = 7;
+
+/// What `compact` removed and how much smaller the store got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionReport {
+    pub removed_entries: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
 }
 
-fn load_history_data() -> Result<HistoryData, HistoryError> {
-    let path = get_history_path();
+/// Idle gap after which the next entry starts a new session.
+static SESSION_IDLE_MINUTES: AtomicU32 = AtomicU32::new(5);
 
-    if !path.exists() {
-        return Ok(HistoryData::default());
-    }
+pub fn set_session_idle_minutes(minutes: u32) {
+    SESSION_IDLE_MINUTES.store(minutes, Ordering::Relaxed);
+}
 
-    let contents = std::fs::read_to_string(&path)?;
-    let data = serde_json::from_str(&contents)?;
-    Ok(data)
+/// Newest first; ties (same millisecond) in the order they were added.
+const NEWEST_FIRST: &str = "ORDER BY timestamp_ms DESC, seq DESC";
+
+fn all_entries(conn: &rusqlite::Connection) -> Result<Vec<TranscriptionEntry>, HistoryError> {
+    db::entries(conn, &format!("SELECT {} FROM entries {}", db::COLUMNS, NEWEST_FIRST), [])
 }
 
-fn save_history_data(data: &HistoryData) -> Result<(), HistoryError> {
-    let path = get_history_path();
+/// All of history as JSON, for backups.
+pub(crate) fn export_json() -> Result<String, HistoryError> {
+    db::with(|conn| {
+        let data = HistoryData {
+            entries: all_entries(conn)?,
+            last_compacted: db::meta(conn, "last_compacted")?,
+        };
+        Ok(serde_json::to_string_pretty(&data)?)
+    })
+}
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
+/// Replace all of history with `export_json` output, restoring a backup.
+pub(crate) fn restore_json(contents: &str) -> Result<(), HistoryError> {
+    let data: HistoryData = serde_json::from_str(contents)?;
+    db::with(|conn| db::replace(conn, &data, true))
+}
+
+/// What `add_entry` saves; the id, timestamp and session are assigned.
+pub struct NewEntry<'a> {
+    pub text: &'a str,
+    pub raw_text: &'a str,
+    pub word_count: u32,
+    pub duration_ms: u32,
+    pub segments: Vec<SegmentInfo>,
+    pub timings: Option<TranscriptionTimings>,
+    pub app_id: Option<&'a str>,
+    pub sent_to_third_party: bool,
+    pub language: Option<&'a str>,
+}
 
-    let contents = serde_json::to_string_pretty(data)?;
-    std::fs::write(&path, contents)?;
+pub fn add_entry(new: NewEntry) -> Result<TranscriptionEntry, HistoryError> {
+    db::with(|conn| {
+        let last = db::entries(conn, &format!("SELECT {} FROM entries {} LIMIT 1", db::COLUMNS, NEWEST_FIRST), [])?;
+        let now = Local::now();
+        let session_id = last
+            .first()
+            .filter(|last| continues_session(last, new.app_id, now))
+            .and_then(|last| last.session_id.clone())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
 
-    log::info!("History saved to {:?}", path);
-    Ok(())
+        let entry = TranscriptionEntry {
+            id: Uuid::new_v4().to_string(),
+            text: new.text.to_string(),
+            raw_text: (new.raw_text != new.text).then(|| new.raw_text.to_string()),
+            word_count: new.word_count,
+            duration_ms: new.duration_ms,
+            timestamp: now.to_rfc3339(),
+            synced: false,
+            segments: new.segments,
+            timings: new.timings,
+            session_id: Some(session_id),
+            app_id: new.app_id.map(|a| a.to_string()),
+            sent_to_third_party: new.sent_to_third_party,
+            language: new.language.filter(|l| *l != "auto").map(|l| l.to_string()),
+        };
+        db::insert(conn, &entry)?;
+        Ok(entry)
+    })
 }
 
-pub fn add_entry(text: &str, word_count: u32, duration_ms: u32) -> Result<TranscriptionEntry, HistoryError> {
-    let mut data = load_history_data()?;
+pub fn get_history(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<TranscriptionEntry>, HistoryError> {
+    db::with(|conn| {
+        db::entries(
+            conn,
+            &format!("SELECT {} FROM entries {} LIMIT ?1 OFFSET ?2", db::COLUMNS, NEWEST_FIRST),
+            [limit.unwrap_or(50), offset.unwrap_or(0)],
+        )
+    })
+}
 
-    let entry = TranscriptionEntry {
-        id: Uuid::new_v4().to_string(),
-        text: text.to_string(),
-        word_count,
-        duration_ms,
-        timestamp: Local::now().to_rfc3339(),
-        synced: false,
-    };
+pub fn get_entry(id: &str) -> Result<Option<TranscriptionEntry>, HistoryError> {
+    db::with(|conn| get_entry_in(conn, id))
+}
+
+fn get_entry_in(conn: &rusqlite::Connection, id: &str) -> Result<Option<TranscriptionEntry>, HistoryError> {
+    Ok(conn
+        .query_row(&format!("SELECT {} FROM entries WHERE id = ?1", db::COLUMNS), [id], db::entry_from_row)
+        .optional()?)
+}
+
+/// What cleanup changed in an entry, word by word.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryDiff {
+    pub raw_text: String,
+    pub cleaned_text: String,
+    pub changes: Vec<DiffChunk>,
+}
 
-    data.entries.insert(0, entry.clone());
+pub fn get_entry_diff(id: &str) -> Result<Option<EntryDiff>, HistoryError> {
+    Ok(get_entry(id)?.map(|entry| {
+        let raw_text = entry.raw_text.unwrap_or_else(|| entry.text.clone());
+        EntryDiff {
+            changes: diff::diff_words(&raw_text, &entry.text),
+            raw_text,
+            cleaned_text: entry.text,
+        }
+    }))
+}
 
-    // Keep only last 500 entries
-    if data.entries.len() > 500 {
-        data.entries.truncate(500);
+/// Whether an entry saved at `now` into `app_id` belongs to the same session as
+/// `last`: not idle for too long, and no switch to a different app.
+fn continues_session(last: &TranscriptionEntry, app_id: Option<&str>, now: DateTime<Local>) -> bool {
+    if last.session_id.is_none() {
+        return false;
     }
+    if let (Some(previous), Some(current)) = (last.app_id.as_deref(), app_id) {
+        if previous != current {
+            return false;
+        }
+    }
+    let idle_minutes = SESSION_IDLE_MINUTES.load(Ordering::Relaxed) as i64;
+    DateTime::parse_from_rfc3339(&last.timestamp)
+        .map(|ts| now.signed_duration_since(ts) <= chrono::Duration::minutes(idle_minutes))
+        .unwrap_or(false)
+}
 
-    save_history_data(&data)?;
-    Ok(entry)
+/// Consecutive dictations grouped together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub entry_count: u32,
+    pub word_count: u32,
+    pub duration_ms: u32,
+    pub app_id: Option<String>,
+    /// Start of the session's text
+    pub preview: String,
 }
 
-pub fn get_history(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<TranscriptionEntry>, HistoryError> {
-    let data = load_history_data()?;
+/// Sessions, newest first.
+pub fn get_sessions(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<SessionSummary>, HistoryError> {
+    let entries = db::with(|conn| all_entries(conn))?;
 
-    let offset = offset.unwrap_or(0) as usize;
-    let limit = limit.unwrap_or(50) as usize;
+    let mut sessions: Vec<SessionSummary> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    // Entries are newest first, so the first one seen ends its session and
+    // each later one moves the start back
+    for entry in &entries {
+        let key = entry.session_key();
+        match index.get(key) {
+            Some(&i) => {
+                let session = &mut sessions[i];
+                session.started_at = entry.timestamp.clone();
+                session.entry_count += 1;
+                session.word_count += entry.word_count;
+                session.duration_ms += entry.duration_ms;
+                session.preview = entry.text.chars().take(100).collect();
+            }
+            None => {
+                index.insert(key.to_string(), sessions.len());
+                sessions.push(SessionSummary {
+                    id: key.to_string(),
+                    started_at: entry.timestamp.clone(),
+                    ended_at: entry.timestamp.clone(),
+                    entry_count: 1,
+                    word_count: entry.word_count,
+                    duration_ms: entry.duration_ms,
+                    app_id: entry.app_id.clone(),
+                    preview: entry.text.chars().take(100).collect(),
+                });
+            }
+        }
+    }
 
-    let entries: Vec<TranscriptionEntry> = data.entries
+    Ok(sessions
         .into_iter()
-        .skip(offset)
-        .take(limit)
-        .collect();
-
-    Ok(entries)
+        .skip(offset.unwrap_or(0) as usize)
+        .take(limit.unwrap_or(50) as usize)
+        .collect())
 }
 
-pub fn get_entry(id: &str) -> Result<Option<TranscriptionEntry>, HistoryError> {
-    let data = load_history_data()?;
-    Ok(data.entries.into_iter().find(|e| e.id == id))
+/// A session's entries merged into one document, oldest first, one paragraph
+/// per dictation. None if no entry belongs to the session.
+pub fn export_session(id: &str) -> Result<Option<String>, HistoryError> {
+    let texts: Vec<String> = db::with(|conn| {
+        let mut statement = conn.prepare(
+            "SELECT text FROM entries WHERE session_id = ?1 OR (session_id IS NULL AND id = ?1) \
+             ORDER BY timestamp_ms, seq",
+        )?;
+        let texts = statement.query_map([id], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(texts)
+    })?;
+
+    if texts.is_empty() {
+        return Ok(None);
+    }
+    let paragraphs: Vec<&str> = texts.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    Ok(Some(paragraphs.join("\n\n")))
 }
 
 pub fn delete_entry(id: &str) -> Result<bool, HistoryError> {
-    let mut data = load_history_data()?;
-    let original_len = data.entries.len();
-    data.entries.retain(|e| e.id != id);
-
-    if data.entries.len() < original_len {
-        save_history_data(&data)?;
-        Ok(true)
-    } else {
-        Ok(false)
+    db::with(|conn| Ok(conn.execute("DELETE FROM entries WHERE id = ?1", [id])? > 0))
+}
+
+pub fn set_timings(id: &str, timings: &TranscriptionTimings) -> Result<bool, HistoryError> {
+    let timings = serde_json::to_string(timings)?;
+    db::with(|conn| Ok(conn.execute("UPDATE entries SET timings = ?2 WHERE id = ?1", [id, timings.as_str()])? > 0))
+}
+
+/// Replace an entry's cleaned text, e.g. after post-processing it again.
+pub fn update_text(id: &str, text: &str, raw_text: &str) -> Result<Option<TranscriptionEntry>, HistoryError> {
+    db::with(|conn| {
+        let updated = conn.execute(
+            "UPDATE entries SET text = ?2, raw_text = ?3, word_count = ?4 WHERE id = ?1",
+            rusqlite::params![
+                id,
+                text,
+                (raw_text != text).then_some(raw_text),
+                text.split_whitespace().count() as u32,
+            ],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        get_entry_in(conn, id)
+    })
+}
+
+/// Delete entries older than `retention_days` (None or 0 keeps everything).
+/// Returns how many were deleted.
+fn expire(
+    conn: &rusqlite::Connection,
+    retention_days: Option<u32>,
+    now: DateTime<Local>,
+) -> Result<usize, HistoryError> {
+    let Some(days) = retention_days.filter(|d| *d > 0) else {
+        return Ok(0);
+    };
+    let cutoff = (now - chrono::Duration::days(days as i64)).timestamp_millis();
+    // Unparseable timestamps (stored as 0) are kept rather than guessed at
+    Ok(conn.execute("DELETE FROM entries WHERE timestamp_ms > 0 AND timestamp_ms < ?1", [cutoff])?)
+}
+
+/// Remove expired entries and shrink the database, dropping copies of the raw
+/// text that older versions kept even when cleanup didn't change anything.
+pub fn compact(retention_days: Option<u32>) -> Result<CompactionReport, HistoryError> {
+    let path = db::path();
+    let bytes_before = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+    let removed_entries = db::with(|conn| {
+        let now = Local::now();
+        let removed = expire(conn, retention_days, now)?;
+        conn.execute("UPDATE entries SET raw_text = NULL WHERE raw_text = text", [])?;
+        db::set_meta(conn, "last_compacted", &now.to_rfc3339())?;
+        conn.execute_batch("INSERT INTO entries_fts (entries_fts) VALUES ('optimize'); VACUUM;")?;
+        Ok(removed)
+    })?;
+
+    let bytes_after = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    log::info!(
+        "History compacted: {} entries removed, {} -> {} bytes",
+        removed_entries,
+        bytes_before,
+        bytes_after
+    );
+    Ok(CompactionReport {
+        removed_entries,
+        bytes_before,
+        bytes_after,
+    })
+}
+
+/// `compact`, if it hasn't run in the last week.
+pub fn compact_if_due(retention_days: Option<u32>) -> Result<Option<CompactionReport>, HistoryError> {
+    let last_compacted = db::with(|conn| db::meta(conn, "last_compacted"))?;
+    let due = last_compacted
+        .as_deref()
+        .and_then(|last| DateTime::parse_from_rfc3339(last).ok())
+        .is_none_or(|last| {
+            Local::now().signed_duration_since(last) >= chrono::Duration::days(AUTO_COMPACT_INTERVAL_DAYS)
+        });
+    if !due {
+        return Ok(None);
     }
+    compact(retention_days).map(Some)
 }
 
 pub fn clear_history() -> Result<(), HistoryError> {
-    let data = HistoryData::default();
-    save_history_data(&data)?;
-    Ok(())
+    db::with(|conn| {
+        conn.execute("DELETE FROM entries", [])?;
+        Ok(())
+    })
 }
 
 pub fn get_total_count() -> Result<usize, HistoryError> {
-    let data = load_history_data()?;
-    Ok(data.entries.len())
+    db::with(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?))
+}
+
+pub fn unsynced_count() -> Result<u64, HistoryError> {
+    db::with(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM entries WHERE synced = 0", [], |row| row.get(0))?))
 }
 
 pub fn mark_synced(ids: &[String]) -> Result<(), HistoryError> {
-    let mut data = load_history_data()?;
+    db::with(|conn| {
+        let tx = conn.transaction()?;
+        for id in ids {
+            tx.execute("UPDATE entries SET synced = 1 WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    })
+}
 
-    for entry in data.entries.iter_mut() {
-        if ids.contains(&entry.id) {
-            entry.synced = true;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: String) -> TranscriptionEntry {
+        TranscriptionEntry {
+            id: timestamp.clone(),
+            text: "hello".to_string(),
+            raw_text: None,
+            word_count: 1,
+            duration_ms: 1000,
+            timestamp,
+            synced: false,
+            segments: Vec::new(),
+            timings: None,
+            session_id: None,
+            app_id: None,
+            sent_to_third_party: false,
+            language: None,
         }
     }
 
-    save_history_data(&data)?;
-    Ok(())
+    #[test]
+    fn test_expire_by_retention() {
+        let now = Local::now();
+        let days_ago = |days: i64| entry((now - chrono::Duration::days(days)).to_rfc3339());
+        let conn = db::memory();
+        for entry in [days_ago(1), days_ago(10), entry("not a date".to_string()), days_ago(40)] {
+            db::insert(&conn, &entry).unwrap();
+        }
+
+        assert_eq!(expire(&conn, None, now).unwrap(), 0);
+        assert_eq!(expire(&conn, Some(30), now).unwrap(), 1);
+        assert_eq!(all_entries(&conn).unwrap().len(), 3);
+        assert_eq!(expire(&conn, Some(7), now).unwrap(), 1);
+        let left = all_entries(&conn).unwrap();
+        assert_eq!(left[1].timestamp, "not a date");
+    }
 }
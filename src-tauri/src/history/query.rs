@@ -0,0 +1,308 @@
+//! Filtered, sorted history pages for the dashboard (`query_history`).
+//!
+//! Pages are keyed by cursor rather than offset: the cursor names the last
+//! entry returned by its sort key and id, and the next page starts after it.
+//! Entries added or deleted between requests therefore don't shift pages, and
+//! a page is an indexed `WHERE (key, id) < (?, ?) ORDER BY key DESC, id DESC
+//! LIMIT ?`.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::db::{self, timestamp_ms};
+use super::{HistoryError, TranscriptionEntry};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySort {
+    #[default]
+    Newest,
+    Oldest,
+    Longest,
+    MostWords,
+}
+
+impl HistorySort {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+            Self::Longest => "longest",
+            Self::MostWords => "most_words",
+        }
+    }
+
+    /// Column holding `key`
+    fn column(self) -> &'static str {
+        match self {
+            Self::Newest | Self::Oldest => "timestamp_ms",
+            Self::Longest => "duration_ms",
+            Self::MostWords => "word_count",
+        }
+    }
+
+    fn key(self, entry: &TranscriptionEntry) -> i64 {
+        match self {
+            Self::Newest | Self::Oldest => timestamp_ms(&entry.timestamp).unwrap_or(0),
+            Self::Longest => entry.duration_ms as i64,
+            Self::MostWords => entry.word_count as i64,
+        }
+    }
+
+    fn ascending(self) -> bool {
+        self == Self::Oldest
+    }
+}
+
+/// `query_history` parameters; every filter is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// `next_cursor` of the previous page; None for the first page
+    pub cursor: Option<String>,
+    /// Entries per page (default 50, at most 500)
+    pub limit: Option<u32>,
+    pub sort: HistorySort,
+    /// Earliest entry, inclusive: RFC 3339, or a local date ("2025-03-01")
+    pub from: Option<String>,
+    /// Latest entry, inclusive: RFC 3339, or a local date (the whole day)
+    pub to: Option<String>,
+    pub app_id: Option<String>,
+    pub min_words: Option<u32>,
+    /// Language code ("de"); "auto" matches entries with a detected language
+    pub language: Option<String>,
+    pub synced: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub entries: Vec<TranscriptionEntry>,
+    /// Pass as `cursor` for the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Entries matching the filters, across all pages
+    pub total: usize,
+}
+
+/// A bound as epoch milliseconds. A date is the start of that local day, or
+/// with `end_of_day` the start of the next one, minus a millisecond.
+pub(super) fn parse_bound(value: &str, end_of_day: bool) -> Result<i64, HistoryError> {
+    if let Some(ms) = timestamp_ms(value) {
+        return Ok(ms);
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| HistoryError::InvalidQuery(format!("'{}' is not a date", value)))?;
+    let day = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+    let start = Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .ok_or_else(|| HistoryError::InvalidQuery(format!("'{}' is not a date", value)))?;
+    Ok(start.timestamp_millis() - end_of_day as i64)
+}
+
+/// `<sort>:<key>:<id>`, opaque to the frontend.
+fn encode_cursor(sort: HistorySort, entry: &TranscriptionEntry) -> String {
+    format!("{}:{}:{}", sort.name(), sort.key(entry), entry.id)
+}
+
+fn decode_cursor(cursor: &str, sort: HistorySort) -> Result<(i64, String), HistoryError> {
+    let invalid = || HistoryError::InvalidQuery("invalid cursor".to_string());
+    let mut parts = cursor.splitn(3, ':');
+    let (name, key, id) = (parts.next(), parts.next(), parts.next());
+    if name != Some(sort.name()) {
+        return Err(HistoryError::InvalidQuery("cursor is from a different sort".to_string()));
+    }
+    let key = key.and_then(|k| k.parse().ok()).ok_or_else(invalid)?;
+    Ok((key, id.ok_or_else(invalid)?.to_string()))
+}
+
+/// `WHERE` conditions for `query`'s filters, and their parameters.
+fn filters(query: &HistoryQuery) -> Result<(Vec<String>, Vec<Value>), HistoryError> {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(from) = query.from.as_deref() {
+        conditions.push("timestamp_ms >= ?".to_string());
+        values.push(parse_bound(from, false)?.into());
+    }
+    if let Some(to) = query.to.as_deref() {
+        conditions.push("timestamp_ms <= ?".to_string());
+        values.push(parse_bound(to, true)?.into());
+    }
+    if let Some(app_id) = &query.app_id {
+        conditions.push("app_id = ?".to_string());
+        values.push(app_id.clone().into());
+    }
+    if let Some(min_words) = query.min_words {
+        conditions.push("word_count >= ?".to_string());
+        values.push(min_words.into());
+    }
+    if let Some(synced) = query.synced {
+        conditions.push("synced = ?".to_string());
+        values.push(synced.into());
+    }
+    match query.language.as_deref() {
+        Some("auto") => conditions.push("language IS NULL".to_string()),
+        Some(language) => {
+            conditions.push("language = ?".to_string());
+            values.push(language.to_string().into());
+        }
+        None => {}
+    }
+    Ok((conditions, values))
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    }
+}
+
+/// One page of the entries in `conn` for `query`.
+fn page(conn: &Connection, query: &HistoryQuery) -> Result<HistoryPage, HistoryError> {
+    let after = query.cursor.as_deref().map(|c| decode_cursor(c, query.sort)).transpose()?;
+    let limit = query.limit.map_or(DEFAULT_LIMIT, |l| (l as usize).clamp(1, MAX_LIMIT));
+    let sort = query.sort;
+
+    let (mut conditions, mut values) = filters(query)?;
+    let total: usize = conn.query_row(
+        &format!("SELECT COUNT(*) FROM entries{}", where_clause(&conditions)),
+        rusqlite::params_from_iter(&values),
+        |row| row.get(0),
+    )?;
+
+    // Ties on the key are broken by id, so the order is total and a cursor is exact
+    let (direction, past) = if sort.ascending() { ("ASC", ">") } else { ("DESC", "<") };
+    if let Some((key, id)) = after {
+        conditions.push(format!("({}, id) {} (?, ?)", sort.column(), past));
+        values.push(key.into());
+        values.push(id.into());
+    }
+    // One more than a page, to know whether another follows
+    values.push(((limit + 1) as i64).into());
+    let mut entries = db::entries(
+        conn,
+        &format!(
+            "SELECT {} FROM entries{} ORDER BY {} {}, id {} LIMIT ?",
+            db::COLUMNS,
+            where_clause(&conditions),
+            sort.column(),
+            direction,
+            direction,
+        ),
+        rusqlite::params_from_iter(&values),
+    )?;
+
+    let next_cursor = (entries.len() > limit)
+        .then(|| {
+            entries.truncate(limit);
+            entries.last().map(|e| encode_cursor(sort, e))
+        })
+        .flatten();
+    Ok(HistoryPage { entries, next_cursor, total })
+}
+
+pub fn query_history(query: &HistoryQuery) -> Result<HistoryPage, HistoryError> {
+    db::with(|conn| page(conn, query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, timestamp: &str, word_count: u32, app_id: Option<&str>) -> TranscriptionEntry {
+        TranscriptionEntry {
+            id: id.to_string(),
+            text: "hello".to_string(),
+            raw_text: None,
+            word_count,
+            duration_ms: word_count * 400,
+            timestamp: timestamp.to_string(),
+            synced: false,
+            segments: Vec::new(),
+            timings: None,
+            session_id: None,
+            app_id: app_id.map(|a| a.to_string()),
+            sent_to_third_party: false,
+            language: None,
+        }
+    }
+
+    fn entries() -> Connection {
+        let conn = db::memory();
+        for entry in [
+            entry("a", "2025-03-01T09:00:00+00:00", 5, Some("com.apple.mail")),
+            entry("b", "2025-03-02T09:00:00+00:00", 12, Some("code")),
+            entry("c", "2025-03-03T09:00:00+00:00", 12, Some("com.apple.mail")),
+            entry("d", "2025-03-04T09:00:00+00:00", 1, None),
+            entry("e", "2025-03-05T09:00:00+00:00", 30, Some("code")),
+        ] {
+            db::insert(&conn, &entry).unwrap();
+        }
+        conn
+    }
+
+    fn ids(page: &HistoryPage) -> Vec<&str> {
+        page.entries.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cursor_pages() {
+        let conn = entries();
+        let mut query = HistoryQuery { limit: Some(2), ..Default::default() };
+        let first = page(&conn, &query).unwrap();
+        assert_eq!(ids(&first), ["e", "d"]);
+        assert_eq!(first.total, 5);
+
+        query.cursor = first.next_cursor;
+        let second = page(&conn, &query).unwrap();
+        assert_eq!(ids(&second), ["c", "b"]);
+
+        // An entry added meanwhile doesn't shift the next page
+        db::insert(&conn, &entry("f", "2025-03-06T09:00:00+00:00", 3, None)).unwrap();
+        query.cursor = second.next_cursor;
+        let last = page(&conn, &query).unwrap();
+        assert_eq!(ids(&last), ["a"]);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_sort_ties_and_filters() {
+        let mut query = HistoryQuery { sort: HistorySort::MostWords, limit: Some(2), ..Default::default() };
+        let first = page(&entries(), &query).unwrap();
+        assert_eq!(ids(&first), ["e", "c"]);
+        query.cursor = first.next_cursor;
+        assert_eq!(ids(&page(&entries(), &query).unwrap()), ["b", "a"]);
+
+        let query = HistoryQuery {
+            sort: HistorySort::Oldest,
+            app_id: Some("com.apple.mail".to_string()),
+            min_words: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(ids(&page(&entries(), &query).unwrap()), ["c"]);
+
+        let query = HistoryQuery {
+            sort: HistorySort::Oldest,
+            from: Some("2025-03-02T00:00:00+00:00".to_string()),
+            to: Some("2025-03-04T00:00:00+00:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&page(&entries(), &query).unwrap()), ["b", "c"]);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let query = HistoryQuery { from: Some("last week".to_string()), ..Default::default() };
+        assert!(page(&entries(), &query).is_err());
+
+        let first = page(&entries(), &HistoryQuery { limit: Some(1), ..Default::default() }).unwrap();
+        let query = HistoryQuery { cursor: first.next_cursor, sort: HistorySort::Longest, ..Default::default() };
+        assert!(page(&entries(), &query).is_err());
+    }
+}
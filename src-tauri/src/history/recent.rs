@@ -0,0 +1,42 @@
+//! The last few results, kept in memory only so the pill can show and copy the
+//! most recent text without reading history storage. Incognito dictations are
+//! included: they were injected, just never written to disk.
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentTranscription {
+    pub text: String,
+    pub word_count: u32,
+    pub timestamp: String,
+}
+
+/// Newest first
+static RECENT: Lazy<Mutex<VecDeque<RecentTranscription>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+pub fn push(text: &str) {
+    if text.trim().is_empty() {
+        return;
+    }
+    let Ok(mut recent) = RECENT.lock() else { return };
+    recent.push_front(RecentTranscription {
+        text: text.to_string(),
+        word_count: text.split_whitespace().count() as u32,
+        timestamp: Local::now().to_rfc3339(),
+    });
+    recent.truncate(MAX_RECENT);
+}
+
+pub fn last() -> Option<RecentTranscription> {
+    RECENT.lock().ok().and_then(|recent| recent.front().cloned())
+}
+
+pub fn all() -> Vec<RecentTranscription> {
+    RECENT.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
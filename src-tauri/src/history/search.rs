@@ -0,0 +1,282 @@
+//! Full-text search over history for the dashboard search box (`search_history`).
+//!
+//! Matching is case-insensitive. Every term of the query must occur in an
+//! entry's text; a term counts for more when it is a whole word than when it
+//! only starts a word or sits inside one, and the query found as a phrase
+//! counts for more still. Results are ranked by that score, newest first on
+//! ties, and carry the spans to highlight.
+//!
+//! The trigram full-text index narrows history down to entries containing
+//! every term of three or more characters; shorter terms are checked while
+//! scoring.
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::db::{self, timestamp_ms};
+use super::query::parse_bound;
+use super::{HistoryError, TranscriptionEntry};
+
+/// Shortest term the trigram index can look up
+const MIN_INDEXED_CHARS: usize = 3;
+
+const WORD_SCORE: u32 = 3;
+const PREFIX_SCORE: u32 = 2;
+const SUBSTRING_SCORE: u32 = 1;
+/// Added when a query of several terms occurs as written
+const PHRASE_SCORE: u32 = 5;
+
+/// A span of `text` to highlight, in UTF-16 code units (JavaScript string
+/// indices), end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub entry: TranscriptionEntry,
+    pub score: u32,
+    /// Sorted, non-overlapping
+    pub highlights: Vec<Highlight>,
+}
+
+/// Text folded to lowercase one char at a time, so positions in the folded
+/// text are positions in the original.
+struct Folded {
+    chars: Vec<char>,
+    /// UTF-16 offset of each char, plus one past the end
+    offsets: Vec<usize>,
+}
+
+fn fold_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn fold(text: &str) -> Folded {
+    let mut chars = Vec::new();
+    let mut offsets = vec![0];
+    for c in text.chars() {
+        chars.push(fold_char(c));
+        offsets.push(offsets[offsets.len() - 1] + c.len_utf16());
+    }
+    Folded { chars, offsets }
+}
+
+/// Char positions where `needle` starts in `haystack`, not overlapping.
+fn find_all(haystack: &[char], needle: &[char]) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while !needle.is_empty() && i + needle.len() <= haystack.len() {
+        if haystack[i..i + needle.len()] == *needle {
+            found.push(i);
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+fn is_word_char(chars: &[char], i: usize) -> bool {
+    chars.get(i).is_some_and(|c| c.is_alphanumeric())
+}
+
+/// How well the occurrence of a `len`-char term at `start` matches.
+fn occurrence_score(chars: &[char], start: usize, len: usize) -> u32 {
+    let starts_word = start == 0 || !is_word_char(chars, start - 1);
+    let ends_word = !is_word_char(chars, start + len);
+    match (starts_word, ends_word) {
+        (true, true) => WORD_SCORE,
+        (true, false) => PREFIX_SCORE,
+        _ => SUBSTRING_SCORE,
+    }
+}
+
+/// Score and highlights of `text` for the folded query `terms`, or None
+/// when a term is missing.
+fn score(text: &str, terms: &[Vec<char>]) -> Option<(u32, Vec<Highlight>)> {
+    let folded = fold(text);
+    let mut total = 0;
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+
+    for term in terms {
+        let found = find_all(&folded.chars, term);
+        let best = found.iter().map(|&start| occurrence_score(&folded.chars, start, term.len())).max()?;
+        total += best;
+        spans.extend(found.into_iter().map(|start| (start, start + term.len())));
+    }
+
+    if terms.len() > 1 {
+        let phrase = terms.join(&' ');
+        let found = find_all(&folded.chars, &phrase);
+        if !found.is_empty() {
+            total += PHRASE_SCORE;
+            spans.extend(found.into_iter().map(|start| (start, start + phrase.len())));
+        }
+    }
+
+    spans.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    let highlights = merged
+        .into_iter()
+        .map(|(start, end)| Highlight { start: folded.offsets[start], end: folded.offsets[end] })
+        .collect();
+    Some((total, highlights))
+}
+
+/// An FTS5 query for entries containing every indexed term, or None when no
+/// term is long enough.
+fn fts_query(query: &str) -> Option<String> {
+    let phrases: Vec<String> = query
+        .split_whitespace()
+        .filter(|t| t.chars().count() >= MIN_INDEXED_CHARS)
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect();
+    (!phrases.is_empty()).then(|| phrases.join(" "))
+}
+
+/// Entries in the date range that may match `query`.
+fn candidates(
+    conn: &Connection,
+    query: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<TranscriptionEntry>, HistoryError> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+    if let Some(from) = from {
+        conditions.push("timestamp_ms >= ?");
+        values.push(from.into());
+    }
+    if let Some(to) = to {
+        conditions.push("timestamp_ms <= ?");
+        values.push(to.into());
+    }
+    if let Some(fts) = fts_query(query) {
+        conditions.push("seq IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ?)");
+        values.push(fts.into());
+    }
+    let filter = if conditions.is_empty() { String::new() } else { format!(" WHERE {}", conditions.join(" AND ")) };
+    db::entries(
+        conn,
+        &format!("SELECT {} FROM entries{}", db::COLUMNS, filter),
+        rusqlite::params_from_iter(&values),
+    )
+}
+
+fn search(
+    conn: &Connection,
+    query: &str,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> Result<Vec<SearchMatch>, HistoryError> {
+    let from = date_from.map(|v| parse_bound(v, false)).transpose()?;
+    let to = date_to.map(|v| parse_bound(v, true)).transpose()?;
+    let terms: Vec<Vec<char>> = query.split_whitespace().map(|t| t.chars().map(fold_char).collect()).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut matches: Vec<(i64, SearchMatch)> = candidates(conn, query, from, to)?
+        .into_iter()
+        .filter_map(|entry| {
+            let at = timestamp_ms(&entry.timestamp).unwrap_or(0);
+            let (score, highlights) = score(&entry.text, &terms)?;
+            Some((at, SearchMatch { entry, score, highlights }))
+        })
+        .collect();
+    matches.sort_by(|(a_at, a), (b_at, b)| b.score.cmp(&a.score).then(b_at.cmp(a_at)));
+    Ok(matches.into_iter().map(|(_, m)| m).collect())
+}
+
+/// Entries whose text contains every word of `query`, best match first.
+/// `date_from`/`date_to` take the same forms as `query_history`'s bounds.
+pub fn search_history(
+    query: &str,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> Result<Vec<SearchMatch>, HistoryError> {
+    db::with(|conn| search(conn, query, date_from, date_to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, timestamp: &str, text: &str) -> TranscriptionEntry {
+        TranscriptionEntry {
+            id: id.to_string(),
+            text: text.to_string(),
+            raw_text: None,
+            word_count: text.split_whitespace().count() as u32,
+            duration_ms: 0,
+            timestamp: timestamp.to_string(),
+            synced: false,
+            segments: Vec::new(),
+            timings: None,
+            session_id: None,
+            app_id: None,
+            sent_to_third_party: false,
+            language: None,
+        }
+    }
+
+    fn entries() -> Connection {
+        let conn = db::memory();
+        for entry in [
+            entry("a", "2025-03-01T09:00:00+00:00", "Send the report to Anna"),
+            entry("b", "2025-03-02T09:00:00+00:00", "Reports are due Friday"),
+            entry("c", "2025-03-03T09:00:00+00:00", "The quarterly misreport"),
+            entry("d", "2025-03-04T09:00:00+00:00", "Lunch with Anna"),
+        ] {
+            db::insert(&conn, &entry).unwrap();
+        }
+        conn
+    }
+
+    fn ids(matches: &[SearchMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.entry.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ranking_and_highlights() {
+        // Whole word, then word prefix, then inside a word
+        let found = search(&entries(), "REPORT", None, None).unwrap();
+        assert_eq!(ids(&found), ["a", "b", "c"]);
+        assert_eq!(found[0].highlights, [Highlight { start: 9, end: 15 }]);
+
+        // Every term must occur; the phrase ranks above scattered terms
+        let grown = entries();
+        db::insert(&grown, &entry("e", "2025-02-28T09:00:00+00:00", "The report Anna asked for")).unwrap();
+        let found = search(&grown, "report anna", None, None).unwrap();
+        assert_eq!(ids(&found), ["e", "a"]);
+        let found = search(&entries(), "to anna", None, None).unwrap();
+        assert_eq!(found[0].highlights, [Highlight { start: 16, end: 23 }]);
+
+        // Offsets are UTF-16, as JavaScript indexes strings
+        let conn = db::memory();
+        db::insert(&conn, &entry("f", "2025-03-06T09:00:00+00:00", "🎉 Größe")).unwrap();
+        let found = search(&conn, "GRÖ", None, None).unwrap();
+        assert_eq!(found[0].highlights, [Highlight { start: 3, end: 6 }]);
+
+        assert!(search(&entries(), "  ", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_date_range() {
+        let found = search(&entries(), "anna", Some("2025-03-02T00:00:00+00:00"), None).unwrap();
+        assert_eq!(ids(&found), ["d"]);
+        let found = search(&entries(), "anna", None, Some("2025-03-02T00:00:00+00:00")).unwrap();
+        assert_eq!(ids(&found), ["a"]);
+        assert!(search(&entries(), "anna", Some("yesterday"), None).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Hotkey strings like "F6", "Ctrl+Alt+D" or "Cmd+Shift+Space".
+//!
+//! Parsing is global-hotkey's, with the modifier names the settings recorder
+//! produces ("Meta", "Win", "Option") and a few key names ("Keypad1",
+//! "PlayPause") added. `validate` also rejects combos the OS keeps for itself,
+//! keys that would get in the way of typing and keys global-hotkey can't
+//! register on this platform, and returns the canonical spelling the settings
+//! store.
+//!
+//! Keys are named by position ("Backquote", "BracketLeft"), as the recorder
+//! records them, so a hotkey doesn't depend on the keyboard layout and dead
+//! keys on international layouts work like any other.
+
+use std::str::FromStr;
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+use super::HotkeyError;
+
+/// The Super key as this platform calls it.
+#[cfg(target_os = "macos")]
+const SUPER_NAME: &str = "Cmd";
+#[cfg(target_os = "windows")]
+const SUPER_NAME: &str = "Win";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SUPER_NAME: &str = "Super";
+
+/// Combos the OS (or every app) already uses, and what for.
+#[cfg(target_os = "macos")]
+const RESERVED: &[(&str, &str)] = &[
+    ("Cmd+Space", "Spotlight"),
+    ("Ctrl+Space", "switching input sources"),
+    ("Ctrl+Cmd+Space", "the character viewer"),
+    ("Cmd+Tab", "switching apps"),
+    ("Cmd+Q", "quitting apps"),
+    ("Cmd+W", "closing windows"),
+    ("Cmd+H", "hiding apps"),
+    ("Cmd+M", "minimizing windows"),
+    ("Cmd+A", "select all"),
+    ("Cmd+C", "copy"),
+    ("Cmd+V", "paste"),
+    ("Cmd+X", "cut"),
+    ("Cmd+Z", "undo"),
+    ("Cmd+Shift+3", "screenshots"),
+    ("Cmd+Shift+4", "screenshots"),
+    ("Cmd+Shift+5", "screenshots"),
+    ("Alt+Cmd+Escape", "Force Quit"),
+    ("Ctrl+Cmd+Q", "locking the screen"),
+];
+#[cfg(target_os = "windows")]
+const RESERVED: &[(&str, &str)] = &[
+    ("Alt+Tab", "switching apps"),
+    ("Alt+F4", "closing windows"),
+    ("Ctrl+Alt+Delete", "the security screen"),
+    ("Ctrl+Shift+Escape", "Task Manager"),
+    ("Ctrl+Escape", "the Start menu"),
+    ("Win+L", "locking the screen"),
+    ("Win+D", "showing the desktop"),
+    ("Win+E", "File Explorer"),
+    ("Win+R", "Run"),
+    ("Win+Tab", "Task View"),
+    ("Win+V", "clipboard history"),
+    ("Win+H", "voice typing"),
+    ("Win+Shift+S", "screenshots"),
+    ("Ctrl+A", "select all"),
+    ("Ctrl+C", "copy"),
+    ("Ctrl+V", "paste"),
+    ("Ctrl+X", "cut"),
+    ("Ctrl+Z", "undo"),
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const RESERVED: &[(&str, &str)] = &[
+    ("Alt+Tab", "switching apps"),
+    ("Alt+F4", "closing windows"),
+    ("Ctrl+Alt+Delete", "logging out"),
+    ("Ctrl+Alt+T", "opening a terminal"),
+    ("Super+L", "locking the screen"),
+    ("Ctrl+A", "select all"),
+    ("Ctrl+C", "copy"),
+    ("Ctrl+V", "paste"),
+    ("Ctrl+X", "cut"),
+    ("Ctrl+Z", "undo"),
+];
+
+/// Parse a hotkey string. Names are case-insensitive.
+pub fn parse(key: &str) -> Result<Shortcut, HotkeyError> {
+    let spelled: Vec<String> = key
+        .split('+')
+        .map(|part| {
+            let upper = part.trim().to_uppercase();
+            let spelled = match upper.as_str() {
+                "META" | "WIN" | "WINDOWS" | "⌘" => "Super",
+                "OPT" | "⌥" => "Alt",
+                "⌃" => "Ctrl",
+                "⇧" => "Shift",
+                "PLAYPAUSE" => "MediaPlayPause",
+                "NEXTTRACK" => "MediaTrackNext",
+                "PREVTRACK" | "PREVIOUSTRACK" => "MediaTrackPrevious",
+                "MUTE" => "AudioVolumeMute",
+                _ => match upper.strip_prefix("KEYPAD").or_else(|| upper.strip_prefix("KP")) {
+                    Some(rest) if !rest.is_empty() => return format!("Numpad{}", rest),
+                    _ => part.trim(),
+                },
+            };
+            spelled.to_string()
+        })
+        .collect();
+    Shortcut::from_str(&spelled.join("+")).map_err(|e| {
+        // A character only some layouts have ("ß", "´"): the key's position works on every layout
+        let layout_char = spelled.last().is_some_and(|k| k.chars().count() == 1 && !k.is_ascii());
+        let reason = if layout_char {
+            "name the key by its position on a US keyboard instead, e.g. Backquote or BracketLeft".to_string()
+        } else {
+            e.to_string()
+        };
+        HotkeyError::InvalidKey(key.to_string(), reason)
+    })
+}
+
+/// Canonical spelling: modifiers in a fixed order, then the key ("Ctrl+Shift+D",
+/// "Cmd+Shift+Space").
+pub fn format(shortcut: &Shortcut) -> String {
+    let mut parts: Vec<String> = [
+        (Modifiers::CONTROL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SUPER, SUPER_NAME),
+        (Modifiers::SHIFT, "Shift"),
+    ]
+    .iter()
+    .filter(|(modifier, _)| shortcut.mods.contains(*modifier))
+    .map(|(_, name)| name.to_string())
+    .collect();
+    let key = shortcut.key.to_string();
+    let key = key
+        .strip_prefix("Key")
+        .or_else(|| key.strip_prefix("Digit"))
+        .unwrap_or(&key)
+        .to_string();
+    parts.push(key);
+    parts.join("+")
+}
+
+/// Whether two hotkey strings are the same combo, however they're spelled.
+pub fn same(a: &str, b: &str) -> bool {
+    match (parse(a), parse(b)) {
+        (Ok(a), Ok(b)) => a.mods == b.mods && a.key == b.key,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// Keys that don't type or edit anything, so can be a hotkey on their own.
+fn usable_alone(code: Code) -> bool {
+    use Code::*;
+    matches!(
+        code,
+        F1 | F2 | F3 | F4 | F5 | F6 | F7 | F8 | F9 | F10 | F11 | F12 | F13 | F14 | F15 | F16 | F17 | F18 | F19
+            | F20 | F21 | F22 | F23 | F24 | Pause | ScrollLock | Insert | MediaPlayPause | MediaPlay | MediaPause
+            | MediaStop | MediaTrackNext | MediaTrackPrevious | AudioVolumeUp | AudioVolumeDown | AudioVolumeMute
+    )
+}
+
+/// Why global-hotkey can't register `code` on this platform, if it can't.
+#[cfg(target_os = "macos")]
+fn unsupported(code: Code) -> Option<&'static str> {
+    match code {
+        // Only these media keys are watched (through an event tap)
+        Code::MediaPlay | Code::MediaPause | Code::MediaStop => Some("macOS only reports Play/Pause, Next and Previous"),
+        _ => None,
+    }
+}
+#[cfg(target_os = "windows")]
+fn unsupported(code: Code) -> Option<&'static str> {
+    match code {
+        // global-hotkey maps these onto the main keyboard's Enter and E
+        Code::NumpadEnter => Some("Windows doesn't tell it apart from Enter"),
+        Code::NumpadEqual => Some("Windows keyboards have no keypad ="),
+        _ => None,
+    }
+}
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn unsupported(code: Code) -> Option<&'static str> {
+    match code {
+        Code::MediaPlayPause => Some("use Play or Pause instead"),
+        _ => None,
+    }
+}
+
+/// What the OS uses `shortcut` for, if it's reserved.
+fn reserved_for(shortcut: &Shortcut) -> Option<&'static str> {
+    RESERVED.iter().find_map(|(combo, purpose)| {
+        let reserved = parse(combo).ok()?;
+        (reserved.mods == shortcut.mods && reserved.key == shortcut.key).then_some(*purpose)
+    })
+}
+
+/// Check a hotkey before it's saved: it must parse, be a key this platform
+/// can register, not be reserved by the OS, and not be a typing key without
+/// Ctrl, Alt or Super. Returns its canonical spelling.
+pub fn validate(key: &str) -> Result<String, HotkeyError> {
+    let shortcut = parse(key)?;
+    let name = format(&shortcut);
+    if let Some(reason) = unsupported(shortcut.key) {
+        return Err(HotkeyError::Unsupported(name, reason));
+    }
+    if let Some(purpose) = reserved_for(&shortcut) {
+        return Err(HotkeyError::Reserved(name, purpose));
+    }
+    let has_modifier = shortcut.mods.intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER);
+    if !has_modifier && !usable_alone(shortcut.key) {
+        return Err(HotkeyError::NeedsModifier(name, SUPER_NAME));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(format(&parse("F6").unwrap()), "F6");
+        assert_eq!(format(&parse("shift+ctrl+d").unwrap()), "Ctrl+Shift+D");
+        assert_eq!(format(&parse("Meta+Alt+Space").unwrap()), format!("Alt+{}+Space", SUPER_NAME));
+        assert_eq!(format(&parse("Ctrl+1").unwrap()), "Ctrl+1");
+        assert!(parse("Ctrl+").is_err());
+        assert!(parse("Hyper+D").is_err());
+        assert_eq!(format(&parse("Ctrl+Keypad7").unwrap()), "Ctrl+Numpad7");
+        assert_eq!(format(&parse("numpadadd").unwrap()), "NumpadAdd");
+        assert_eq!(format(&parse("playpause").unwrap()), "MediaPlayPause");
+        // Layout characters are refused with a hint; positions work
+        assert!(matches!(parse("Ctrl+ß"), Err(HotkeyError::InvalidKey(_, reason)) if reason.contains("Backquote")));
+        assert_eq!(format(&parse("Ctrl+`").unwrap()), "Ctrl+Backquote");
+        assert!(same("ctrl+alt+d", "Alt+Ctrl+D"));
+        assert!(!same("Ctrl+D", "Ctrl+Shift+D"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("f9").unwrap(), "F9");
+        assert_eq!(validate("Ctrl+Alt+D").unwrap(), "Ctrl+Alt+D");
+        assert!(matches!(validate("D"), Err(HotkeyError::NeedsModifier(..))));
+        assert!(matches!(validate("Shift+Space"), Err(HotkeyError::NeedsModifier(..))));
+        assert_eq!(validate("MediaTrackNext").unwrap(), "MediaTrackNext");
+        assert!(matches!(validate("Numpad5"), Err(HotkeyError::NeedsModifier(..))));
+        assert_eq!(validate("Alt+Numpad5").unwrap(), "Alt+Numpad5");
+    }
+
+    #[test]
+    fn test_reserved_combos_parse() {
+        for (combo, _) in RESERVED {
+            let shortcut = parse(combo).unwrap();
+            assert!(matches!(validate(combo), Err(HotkeyError::Reserved(..))), "{}", combo);
+            assert_eq!(format(&shortcut).to_lowercase(), combo.to_lowercase());
+        }
+    }
+}
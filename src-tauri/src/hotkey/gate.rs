@@ -0,0 +1,293 @@
+//! Turns raw key edges from either hotkey backend into the `hotkey-pressed` /
+//! `hotkey-released` events the dictation window acts on, according to
+//! `hotkey.mode`:
+//! - hold: pressed starts recording and released stops it. A release followed
+//!   within `RELEASE_DEBOUNCE` by another press of the same key is auto-repeat
+//!   (X11 reports a held key as release/press pairs) or switch bounce, and the
+//!   key counts as held throughout.
+//! - toggle: each press starts or stops. A second press within
+//!   `PRESS_DEBOUNCE` is bounce and is dropped.
+//! - hybrid: a press starts recording straight away, as in toggle mode. If the
+//!   key is let go before `hotkey.long_press_ms` (a tap) recording carries on
+//!   until the next press; if it was held that long its release stops
+//!   recording, as in hold mode. The window sees toggle presses and hold
+//!   releases, so it needs no timing of its own. Releases are debounced as in
+//!   hold mode and presses as in toggle mode.
+//!
+//! In every mode auto-repeat presses of a held key are dropped. The mode is
+//! read per key edge, so changing the setting takes effect immediately.
+//! Accidental taps in hold mode are handled further on: `recording.hold_delay_ms`
+//! and `recording.min_duration_ms`.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::panics::LockExt;
+
+const RELEASE_DEBOUNCE: Duration = Duration::from_millis(50);
+const PRESS_DEBOUNCE: Duration = Duration::from_millis(200);
+/// Default `hotkey.long_press_ms`
+const DEFAULT_LONG_PRESS_MS: u32 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyMode {
+    Hold,
+    Toggle,
+    Hybrid,
+}
+
+impl HotkeyMode {
+    /// `hotkey.mode`; unset or unknown is toggle, as in the dictation window.
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value {
+            Some("hold") => HotkeyMode::Hold,
+            Some("hybrid") => HotkeyMode::Hybrid,
+            _ => HotkeyMode::Toggle,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            HotkeyMode::Hold => "hold",
+            HotkeyMode::Toggle => "toggle",
+            HotkeyMode::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// `HotkeyMode` as its index in `MODES`
+static MODE: AtomicU8 = AtomicU8::new(1);
+const MODES: [HotkeyMode; 3] = [HotkeyMode::Hold, HotkeyMode::Toggle, HotkeyMode::Hybrid];
+static LONG_PRESS_MS: AtomicU32 = AtomicU32::new(DEFAULT_LONG_PRESS_MS);
+
+/// Apply `hotkey.mode` and `hotkey.long_press_ms`; called at startup and
+/// whenever settings change.
+pub fn set_mode(value: Option<&str>, long_press_ms: Option<u32>) {
+    let mode = HotkeyMode::from_setting(value);
+    let index = MODES.iter().position(|m| *m == mode).unwrap_or(1);
+    MODE.store(index as u8, Ordering::SeqCst);
+    LONG_PRESS_MS.store(long_press_ms.unwrap_or(DEFAULT_LONG_PRESS_MS), Ordering::SeqCst);
+    log::info!("Hotkey mode: {}", mode.name());
+}
+
+pub fn mode() -> HotkeyMode {
+    MODES.get(MODE.load(Ordering::SeqCst) as usize).copied().unwrap_or(HotkeyMode::Toggle)
+}
+
+fn long_press() -> Duration {
+    Duration::from_millis(LONG_PRESS_MS.load(Ordering::SeqCst) as u64)
+}
+
+/// What to do with a release.
+#[derive(Debug, PartialEq, Eq)]
+enum Release {
+    Emit,
+    /// Emit after `RELEASE_DEBOUNCE` unless `confirm_release(seq)` says a press came first
+    Defer(u64),
+    Ignore,
+}
+
+/// Debounce state of one key.
+#[derive(Debug, Default)]
+struct KeyGate {
+    down: bool,
+    last_press: Option<Instant>,
+    /// Sequence number of a deferred release not yet emitted
+    pending_release: Option<u64>,
+    seq: u64,
+    /// Hybrid: when the current hold's emitted press happened, if it started
+    /// a recording (a press that stops one is ignored when released)
+    hold_started: Option<Instant>,
+    /// Hybrid: the pending release ends a long press
+    long_release: bool,
+    /// Hybrid: a tap started a recording that the next press stops
+    latched: bool,
+}
+
+impl KeyGate {
+    /// Whether to emit a press.
+    fn press(&mut self, mode: HotkeyMode, now: Instant) -> bool {
+        if self.down {
+            return false;
+        }
+        self.down = true;
+        if self.pending_release.take().is_some() {
+            // Released and pressed again within the debounce: still held
+            return false;
+        }
+        let bounce = mode != HotkeyMode::Hold
+            && self.last_press.is_some_and(|at| now.duration_since(at) < PRESS_DEBOUNCE);
+        if bounce {
+            self.hold_started = None;
+            return false;
+        }
+        self.last_press = Some(now);
+        // In hybrid mode the press after a tap stops the recording it latched
+        self.hold_started = (!std::mem::take(&mut self.latched)).then_some(now);
+        true
+    }
+
+    fn release(&mut self, mode: HotkeyMode, now: Instant) -> Release {
+        if !self.down {
+            return Release::Ignore;
+        }
+        self.down = false;
+        match mode {
+            HotkeyMode::Toggle => Release::Emit,
+            HotkeyMode::Hold | HotkeyMode::Hybrid => {
+                self.long_release = self.hold_started.is_some_and(|at| now.duration_since(at) >= long_press());
+                self.seq += 1;
+                self.pending_release = Some(self.seq);
+                Release::Defer(self.seq)
+            }
+        }
+    }
+
+    /// Whether the deferred release `seq` still stands and is emitted. In
+    /// hybrid mode only a long press's release is; a tap latches recording on.
+    fn confirm_release(&mut self, seq: u64, mode: HotkeyMode) -> bool {
+        if self.pending_release != Some(seq) {
+            return false;
+        }
+        self.pending_release = None;
+        if mode != HotkeyMode::Hybrid {
+            return true;
+        }
+        let started = self.hold_started.take().is_some();
+        self.latched = started && !self.long_release;
+        started && self.long_release
+    }
+}
+
+/// Keyed by key name.
+static GATES: Lazy<Mutex<HashMap<String, KeyGate>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `mode` as the window should act on the event: hybrid presses toggle and
+/// its releases (long presses only) stop, as in hold mode.
+fn payload(key: &str, incognito: bool, mode: HotkeyMode, pressed: bool) -> serde_json::Value {
+    let mode = match mode {
+        HotkeyMode::Hybrid if pressed => HotkeyMode::Toggle,
+        HotkeyMode::Hybrid => HotkeyMode::Hold,
+        mode => mode,
+    };
+    serde_json::json!({ "key": key, "incognito": incognito, "mode": mode.name() })
+}
+
+/// A dictation key went down. Press/release events carry
+/// `{ "key": string, "incognito": bool, "mode": "hold" | "toggle" }`.
+pub fn key_down(app: &AppHandle, key: &str, incognito: bool) {
+    let mode = mode();
+    let emit = GATES.lock_recover().entry(key.to_string()).or_default().press(mode, Instant::now());
+    if emit {
+        log::info!("{} pressed", key);
+        app.emit("hotkey-pressed", payload(key, incognito, mode, true)).ok();
+    }
+}
+
+/// A dictation key went up.
+pub fn key_up(app: &AppHandle, key: &str, incognito: bool) {
+    let mode = mode();
+    let action = GATES.lock_recover().entry(key.to_string()).or_default().release(mode, Instant::now());
+    match action {
+        Release::Ignore => {}
+        Release::Emit => {
+            log::info!("{} released", key);
+            app.emit("hotkey-released", payload(key, incognito, mode, false)).ok();
+        }
+        Release::Defer(seq) => {
+            let app = app.clone();
+            let key = key.to_string();
+            std::thread::spawn(move || {
+                std::thread::sleep(RELEASE_DEBOUNCE);
+                let confirmed = GATES.lock_recover().get_mut(&key).is_some_and(|gate| gate.confirm_release(seq, mode));
+                if confirmed {
+                    log::info!("{} released", key);
+                    app.emit("hotkey-released", payload(&key, incognito, mode, false)).ok();
+                }
+            });
+        }
+    }
+}
+
+/// Forget key state, e.g. when the keys are re-registered.
+pub fn reset() {
+    GATES.lock_recover().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hold_mode_debounces_release() {
+        let t0 = Instant::now();
+        let mut gate = KeyGate::default();
+        assert!(gate.press(HotkeyMode::Hold, t0));
+        // Auto-repeat key-down
+        assert!(!gate.press(HotkeyMode::Hold, t0));
+
+        // X11 auto-repeat: release then press straight away
+        let Release::Defer(seq) = gate.release(HotkeyMode::Hold, t0) else { panic!("release not deferred") };
+        assert!(!gate.press(HotkeyMode::Hold, t0));
+        assert!(!gate.confirm_release(seq, HotkeyMode::Hold));
+
+        // The real release
+        let Release::Defer(seq) = gate.release(HotkeyMode::Hold, t0) else { panic!("release not deferred") };
+        assert!(gate.confirm_release(seq, HotkeyMode::Hold));
+        assert_eq!(gate.release(HotkeyMode::Hold, t0), Release::Ignore);
+    }
+
+    #[test]
+    fn test_toggle_mode_debounces_press() {
+        let t0 = Instant::now();
+        let mut gate = KeyGate::default();
+        assert!(gate.press(HotkeyMode::Toggle, t0));
+        assert_eq!(gate.release(HotkeyMode::Toggle, t0), Release::Emit);
+        // Bounce
+        assert!(!gate.press(HotkeyMode::Toggle, t0 + Duration::from_millis(30)));
+        assert_eq!(gate.release(HotkeyMode::Toggle, t0), Release::Emit);
+        // A deliberate second press
+        assert!(gate.press(HotkeyMode::Toggle, t0 + Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn test_hybrid_mode_tap_and_long_press() {
+        let mode = HotkeyMode::Hybrid;
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        let mut gate = KeyGate::default();
+
+        // Tap: recording latches on, and the next press stops it
+        assert!(gate.press(mode, t0));
+        let Release::Defer(seq) = gate.release(mode, ms(150)) else { panic!("release not deferred") };
+        assert!(!gate.confirm_release(seq, mode));
+        assert!(gate.press(mode, ms(2000)));
+        // Held a while, but this press stopped the recording
+        let Release::Defer(seq) = gate.release(mode, ms(3000)) else { panic!("release not deferred") };
+        assert!(!gate.confirm_release(seq, mode));
+
+        // Long press, with X11 auto-repeat on the way: the release stops recording
+        assert!(gate.press(mode, ms(5000)));
+        let Release::Defer(seq) = gate.release(mode, ms(5300)) else { panic!("release not deferred") };
+        assert!(!gate.press(mode, ms(5300)));
+        assert!(!gate.confirm_release(seq, mode));
+        let Release::Defer(seq) = gate.release(mode, ms(5800)) else { panic!("release not deferred") };
+        assert!(gate.confirm_release(seq, mode));
+
+        // Not latched by the long press: the next press starts afresh
+        assert!(gate.press(mode, ms(7000)));
+        assert_eq!(gate.hold_started, Some(ms(7000)));
+    }
+
+    #[test]
+    fn test_mode_from_setting() {
+        assert_eq!(HotkeyMode::from_setting(Some("hold")), HotkeyMode::Hold);
+        assert_eq!(HotkeyMode::from_setting(Some("toggle")), HotkeyMode::Toggle);
+        assert_eq!(HotkeyMode::from_setting(Some("hybrid")), HotkeyMode::Hybrid);
+        assert_eq!(HotkeyMode::from_setting(None), HotkeyMode::Toggle);
+    }
+}
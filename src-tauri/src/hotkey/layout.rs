@@ -0,0 +1,95 @@
+//! Language of the active keyboard layout / input source, used to pick the
+//! transcription language for bilingual users who switch layouts.
+
+/// Whisper language code for the current keyboard layout ("en", "de", "zh"),
+/// or None if it can't be determined on this platform.
+pub fn current_language() -> Option<String> {
+    platform::current_locale().and_then(|locale| to_whisper_language(&locale))
+}
+
+/// Map a locale / BCP 47 tag ("en-US", "zh-Hans", "nb") to Whisper's language code.
+fn to_whisper_language(locale: &str) -> Option<String> {
+    let primary = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    match primary.as_str() {
+        "" => None,
+        // Whisper only knows Norwegian as a whole
+        "nb" | "nn" => Some("no".to_string()),
+        // Filipino is listed as Tagalog
+        "fil" => Some("tl".to_string()),
+        _ => Some(primary),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn TISCopyCurrentKeyboardInputSource() -> *mut c_void;
+        fn TISGetInputSourceProperty(source: *mut c_void, key: CFStringRef) -> *mut c_void;
+        static kTISPropertyInputSourceLanguages: CFStringRef;
+    }
+
+    /// First language of the current input source (e.g. "en", "zh-Hans").
+    pub fn current_locale() -> Option<String> {
+        unsafe {
+            let source = TISCopyCurrentKeyboardInputSource();
+            if source.is_null() {
+                return None;
+            }
+            // Follows the get rule: owned by `source`
+            let languages = TISGetInputSourceProperty(source, kTISPropertyInputSourceLanguages);
+            let locale = if languages.is_null() {
+                None
+            } else {
+                let languages = CFArray::<CFString>::wrap_under_get_rule(languages as CFArrayRef);
+                languages.get(0).map(|l| l.to_string())
+            };
+            CFRelease(source as CFTypeRef);
+            locale
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Globalization::LCIDToLocaleName;
+    use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayout;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    /// Locale name (e.g. "en-US") of the foreground window's keyboard layout.
+    pub fn current_locale() -> Option<String> {
+        unsafe {
+            let thread_id = GetWindowThreadProcessId(GetForegroundWindow(), None);
+            let layout = GetKeyboardLayout(thread_id);
+            // Low word of the HKL is the input language id
+            let lang_id = (layout.0 as usize & 0xFFFF) as u32;
+            if lang_id == 0 {
+                return None;
+            }
+            let mut buf = [0u16; 85]; // LOCALE_NAME_MAX_LENGTH
+            let len = LCIDToLocaleName(lang_id, Some(&mut buf), 0);
+            if len <= 1 {
+                return None;
+            }
+            // Length includes the terminating null
+            Some(String::from_utf16_lossy(&buf[..len as usize - 1]))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// No portable way to query the active layout across X11/Wayland desktops.
+    pub fn current_locale() -> Option<String> {
+        None
+    }
+}
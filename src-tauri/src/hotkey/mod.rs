@@ -1,5 +1,11 @@
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+pub mod accelerator;
+pub mod gate;
+pub mod layout;
+#[cfg(target_os = "windows")]
+mod windows_hook;
+
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,55 +14,178 @@ pub enum HotkeyError {
     RegisterError(String),
     #[error("Unknown key: {0}")]
     UnknownKey(String),
+    #[error("Invalid hotkey {0}: {1}")]
+    InvalidKey(String, String),
+    #[error("{0} is used by the system for {1}")]
+    Reserved(String, &'static str),
+    #[error("{0} would get in the way of typing; add Ctrl, Alt or {1}")]
+    NeedsModifier(String, &'static str),
+    #[error("{0} can't be used as a hotkey here: {1}")]
+    Unsupported(String, &'static str),
 }
 
-/// Parse a key name string to a Code enum
-fn parse_key_code(key: &str) -> Result<Code, HotkeyError> {
-    match key.to_uppercase().as_str() {
-        "F1" => Ok(Code::F1),
-        "F2" => Ok(Code::F2),
-        "F3" => Ok(Code::F3),
-        "F4" => Ok(Code::F4),
-        "F5" => Ok(Code::F5),
-        "F6" => Ok(Code::F6),
-        "F7" => Ok(Code::F7),
-        "F8" => Ok(Code::F8),
-        "F9" => Ok(Code::F9),
-        "F10" => Ok(Code::F10),
-        "F11" => Ok(Code::F11),
-        "F12" => Ok(Code::F12),
-        _ => Err(HotkeyError::UnknownKey(key.to_string())),
+/// Parse a configured key. One `validate` would refuse (settings edited by
+/// hand) is still registered, with a warning: refusing would leave no hotkey.
+fn parse_for_registration(key: &str) -> Result<tauri_plugin_global_shortcut::Shortcut, HotkeyError> {
+    if let Err(e) = accelerator::validate(key) {
+        if !matches!(e, HotkeyError::InvalidKey(..)) {
+            log::warn!("Registering hotkey anyway: {}", e);
+        }
     }
+    accelerator::parse(key)
 }
 
-/// Setup global hotkey with a configurable key
-pub fn setup_hotkey(app: AppHandle, key_name: Option<&str>) -> Result<(), HotkeyError> {
+/// Setup global hotkey with a configurable key, plus optional keys that start
+/// an incognito dictation or one in the code profile, the paste queue's
+/// paste-next key, and the key that boosts the next dictation's accuracy.
+pub fn setup_hotkey(
+    app: AppHandle,
+    key_name: Option<&str>,
+    incognito_key_name: Option<&str>,
+    code_key_name: Option<&str>,
+    paste_next_key_name: Option<&str>,
+    boost_key_name: Option<&str>,
+) -> Result<(), HotkeyError> {
     let key = key_name.unwrap_or("F6").to_string(); // Convert to owned String
-    let code = parse_key_code(&key)?;
-    let shortcut = Shortcut::new(Some(Modifiers::empty()), code);
+    // (key name, incognito)
+    let mut keys = vec![(key.clone(), false)];
 
-    let key_for_closure = key.clone(); // Clone for use in closure
-    app.global_shortcut()
-        .on_shortcut(shortcut, move |_app, _shortcut, event| {
-            match event.state {
-                ShortcutState::Pressed => {
-                    log::info!("{} pressed", key_for_closure);
-                    _app.emit("hotkey-pressed", ()).ok();
-                }
-                ShortcutState::Released => {
-                    log::info!("{} released", key_for_closure);
-                    _app.emit("hotkey-released", ()).ok();
+    if let Some(incognito_key) = incognito_key_name.filter(|k| !k.is_empty()) {
+        if accelerator::same(incognito_key, &key) {
+            log::warn!("Incognito hotkey {} is the same as the main hotkey, ignoring", incognito_key);
+        } else {
+            keys.push((incognito_key.to_string(), true));
+        }
+    }
+
+    // The code profile is resolved from the key name in start_recording
+    if let Some(code_key) = code_key_name.filter(|k| !k.is_empty()) {
+        let taken = [Some(key.as_str()), incognito_key_name]
+            .into_iter()
+            .flatten()
+            .any(|k| accelerator::same(k, code_key));
+        if taken {
+            log::warn!("Code mode hotkey {} is already in use, ignoring", code_key);
+        } else {
+            keys.push((code_key.to_string(), false));
+        }
+    }
+
+    // Not dictation keys, so they're never part of the keyboard hook
+    if let Some(paste_key) = paste_next_key_name.filter(|k| !k.is_empty()) {
+        if keys.iter().any(|(k, _)| accelerator::same(k, paste_key)) {
+            log::warn!("Paste-next hotkey {} is already in use, ignoring", paste_key);
+        } else {
+            register_action_key(&app, paste_key, "paste next", crate::paste_next_queued)?;
+        }
+    }
+    if let Some(boost_key) = boost_key_name.filter(|k| !k.is_empty()) {
+        let taken = keys
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .chain(paste_next_key_name)
+            .any(|k| accelerator::same(k, boost_key));
+        if taken {
+            log::warn!("Accuracy boost hotkey {} is already in use, ignoring", boost_key);
+        } else {
+            register_action_key(&app, boost_key, "accuracy boost", crate::toggle_accuracy_boost)?;
+        }
+    }
+
+    // Windows: a keyboard hook reports releases of plain F-keys reliably;
+    // combos, and every key if the hook fails, are registered with global-hotkey
+    #[cfg(target_os = "windows")]
+    let keys = {
+        let (hooked, registered): (Vec<_>, Vec<_>) =
+            keys.into_iter().partition(|(key, _)| windows_hook::supports(key));
+        if hooked.is_empty() {
+            registered
+        } else {
+            match windows_hook::start(&app, &hooked) {
+                Ok(()) => registered,
+                Err(e) => {
+                    log::warn!("Keyboard hook unavailable ({}), using registered hotkeys", e);
+                    hooked.into_iter().chain(registered).collect()
                 }
             }
+        }
+    };
+
+    for (key, incognito) in &keys {
+        register_key(&app, key, *incognito)?;
+    }
+    Ok(())
+}
+
+/// Register one key. Its presses and releases go through `gate`, which emits
+/// the events the frontend passes on to `start_recording`.
+fn register_key(app: &AppHandle, key: &str, incognito: bool) -> Result<(), HotkeyError> {
+    let shortcut = parse_for_registration(key)?;
+
+    let key_for_closure = key.to_string(); // Clone for use in closure
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| match event.state {
+            ShortcutState::Pressed => gate::key_down(app, &key_for_closure, incognito),
+            ShortcutState::Released => gate::key_up(app, &key_for_closure, incognito),
+        })
+        .map_err(|e| HotkeyError::RegisterError(e.to_string()))?;
+
+    log::info!(
+        "Global hotkey registered: {}{}",
+        key,
+        if incognito { " (incognito)" } else { "" }
+    );
+    Ok(())
+}
+
+/// Register a key that runs `action` when pressed (paste next, accuracy boost).
+fn register_action_key(
+    app: &AppHandle,
+    key: &str,
+    label: &'static str,
+    action: fn(&AppHandle),
+) -> Result<(), HotkeyError> {
+    let shortcut = parse_for_registration(key)?;
+
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            if matches!(event.state, ShortcutState::Pressed) {
+                // Injection waits for focus and the clipboard; keep it off the event loop
+                let app = app.clone();
+                std::thread::spawn(move || action(&app));
+            }
         })
         .map_err(|e| HotkeyError::RegisterError(e.to_string()))?;
 
-    log::info!("Global hotkey registered: {}", key);
+    log::info!("Global hotkey registered: {} ({})", key, label);
     Ok(())
 }
 
+/// Check a hotkey before it's saved, as `accelerator::validate` does, and
+/// that the OS will let it be registered: a key another app holds is refused
+/// now rather than when settings are applied. Returns its canonical spelling.
+pub fn check_available(app: &AppHandle, key: &str) -> Result<String, HotkeyError> {
+    let name = accelerator::validate(key)?;
+    let shortcut = accelerator::parse(key)?;
+    let shortcuts = app.global_shortcut();
+    // Ours already (the Windows keyboard hook registers nothing)
+    if shortcuts.is_registered(shortcut) {
+        return Ok(name);
+    }
+    shortcuts
+        .register(shortcut)
+        .map_err(|e| HotkeyError::RegisterError(format!("{} is not available ({})", name, e)))?;
+    if let Err(e) = shortcuts.unregister(shortcut) {
+        log::warn!("Failed to unregister hotkey {} after checking it: {}", name, e);
+    }
+    Ok(name)
+}
+
 /// Unregister all hotkeys (for re-registration when settings change)
 pub fn unregister_all(app: &AppHandle) -> Result<(), HotkeyError> {
+    #[cfg(target_os = "windows")]
+    windows_hook::stop();
+    gate::reset();
     app.global_shortcut()
         .unregister_all()
         .map_err(|e| HotkeyError::RegisterError(e.to_string()))?;
@@ -0,0 +1,179 @@
+//! Windows: hotkeys through a low-level keyboard hook.
+//!
+//! global-hotkey registers keys with RegisterHotKey, which only reports the
+//! press; the release is detected separately and is missed or late for some
+//! keys, which breaks hold-to-talk. A WH_KEYBOARD_LL hook sees every key-down
+//! and key-up, so press and release come from the same place. Hooked keys are
+//! swallowed, as registered hotkeys are.
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN, VK_SHIFT};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, GetMessageW, PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK,
+    KBDLLHOOKSTRUCT, LLKHF_INJECTED, MSG, WH_KEYBOARD_LL, WM_KEYDOWN, WM_KEYUP, WM_QUIT, WM_SYSKEYDOWN,
+    WM_SYSKEYUP,
+};
+
+use super::HotkeyError;
+use crate::panics::LockExt;
+
+/// VK_F1; F1-F12 are consecutive
+const VK_F1: u32 = 0x70;
+
+struct HookedKey {
+    vk: u32,
+    name: String,
+    incognito: bool,
+    /// Pressed and not yet released, to skip auto-repeat key-downs
+    down: bool,
+}
+
+struct Hook {
+    app: AppHandle,
+    keys: Vec<HookedKey>,
+    thread_id: u32,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// The hook procedure has no user data, so its state lives here.
+static HOOK: Lazy<Mutex<Option<Hook>>> = Lazy::new(|| Mutex::new(None));
+
+/// Whether the hook can handle `key`: F1-F12 without modifiers.
+pub fn supports(key: &str) -> bool {
+    virtual_key(key).is_some()
+}
+
+/// Virtual-key code of a plain F-key name.
+fn virtual_key(key: &str) -> Option<u32> {
+    let number: u32 = key.to_uppercase().strip_prefix('F')?.parse().ok()?;
+    (1..=12).contains(&number).then_some(VK_F1 + number - 1)
+}
+
+/// Install the hook for `keys` (name, incognito) on its own thread, replacing
+/// any previous one. Fails if a key isn't supported or the hook can't be set,
+/// in which case the caller registers the keys with global-hotkey instead.
+pub fn start(app: &AppHandle, keys: &[(String, bool)]) -> Result<(), HotkeyError> {
+    stop();
+
+    let keys = keys
+        .iter()
+        .map(|(name, incognito)| {
+            let vk = virtual_key(name).ok_or_else(|| HotkeyError::UnknownKey(name.clone()))?;
+            Ok(HookedKey { vk, name: name.clone(), incognito: *incognito, down: false })
+        })
+        .collect::<Result<Vec<_>, HotkeyError>>()?;
+
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<u32, String>>();
+    let thread = std::thread::Builder::new()
+        .name("hotkey-hook".to_string())
+        .spawn(move || {
+            let hook = match unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), HINSTANCE::default(), 0) } {
+                Ok(hook) => hook,
+                Err(e) => {
+                    ready_tx.send(Err(e.to_string())).ok();
+                    return;
+                }
+            };
+            ready_tx.send(Ok(unsafe { GetCurrentThreadId() })).ok();
+
+            // The hook is called on this thread, from within its message loop
+            let mut msg = MSG::default();
+            while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.0 > 0 {}
+            unsafe { UnhookWindowsHookEx(hook) }.ok();
+            log::info!("Keyboard hook removed");
+        })
+        .map_err(|e| HotkeyError::RegisterError(e.to_string()))?;
+
+    let thread_id = ready_rx
+        .recv()
+        .map_err(|e| HotkeyError::RegisterError(e.to_string()))?
+        .map_err(HotkeyError::RegisterError)?;
+
+    let names: Vec<&str> = keys.iter().map(|k| k.name.as_str()).collect();
+    log::info!("Keyboard hook installed for {}", names.join(", "));
+    *HOOK.lock_recover() = Some(Hook {
+        app: app.clone(),
+        keys,
+        thread_id,
+        thread: Some(thread),
+    });
+    Ok(())
+}
+
+/// Remove the hook, if installed.
+pub fn stop() {
+    // Taken out first: the hook thread locks HOOK, so it can't be held while joining
+    let hook = HOOK.lock_recover().take();
+    if let Some(mut hook) = hook {
+        unsafe { PostThreadMessageW(hook.thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) }.ok();
+        if let Some(thread) = hook.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// A hotkey registered without modifiers doesn't fire with one held; same here.
+fn modifier_held() -> bool {
+    [VK_CONTROL, VK_MENU, VK_SHIFT, VK_LWIN, VK_RWIN]
+        .iter()
+        .any(|vk| unsafe { GetAsyncKeyState(vk.0 as i32) } < 0)
+}
+
+unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 {
+        let event = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let message = wparam.0 as u32;
+        let is_down = message == WM_KEYDOWN || message == WM_SYSKEYDOWN;
+        let is_up = message == WM_KEYUP || message == WM_SYSKEYUP;
+
+        if (is_down || is_up) && event.flags.0 & LLKHF_INJECTED.0 == 0 && handle_key(event.vkCode, is_down) {
+            // Swallowed, like a registered hotkey
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+/// Pass press/release of a hooked key on to `gate`. Returns whether the key was handled.
+fn handle_key(vk: u32, is_down: bool) -> bool {
+    let Ok(mut guard) = HOOK.try_lock() else { return false };
+    let Some(hook) = guard.as_mut() else { return false };
+    let Some(key) = hook.keys.iter_mut().find(|k| k.vk == vk) else { return false };
+
+    if is_down {
+        if key.down {
+            // Auto-repeat
+            return true;
+        }
+        if modifier_held() {
+            return false;
+        }
+        key.down = true;
+        super::gate::key_down(&hook.app, &key.name, key.incognito);
+    } else {
+        if !key.down {
+            return false;
+        }
+        key.down = false;
+        super::gate::key_up(&hook.app, &key.name, key.incognito);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_key() {
+        assert_eq!(virtual_key("F1"), Some(0x70));
+        assert_eq!(virtual_key("f6"), Some(0x75));
+        assert_eq!(virtual_key("F12"), Some(0x7B));
+        assert_eq!(virtual_key("F13"), None);
+        assert_eq!(virtual_key("Space"), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Recording indicator on every display, so a recording started from the pill
+//! on one monitor isn't forgotten while working on another.
+//!
+//! Each display gets a borderless, click-through, always-on-top window (an
+//! NSPanel on macOS so it also shows over fullscreen apps), created when
+//! recording starts and closed when it stops. The webview renders
+//! `#indicator/<style>` as a plain red strip or dot.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindowBuilder};
+
+const LABEL_PREFIX: &str = "indicator-";
+
+/// Logical sizes, scaled per monitor
+const STRIP_HEIGHT: f64 = 4.0;
+const ICON_SIZE: f64 = 20.0;
+const ICON_MARGIN: f64 = 12.0;
+
+/// Bumped by every show/hide so windows still being created for a recording
+/// that already stopped close themselves.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Thin bar across the top edge
+    Strip,
+    /// Small dot in the top-right corner
+    Icon,
+}
+
+impl Style {
+    /// `widget.recording_indicator`; None (and "off") means no indicator.
+    pub fn from_setting(value: Option<&str>) -> Option<Self> {
+        match value {
+            Some("strip") => Some(Self::Strip),
+            Some("icon") => Some(Self::Icon),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Strip => "strip",
+            Self::Icon => "icon",
+        }
+    }
+
+    /// (x, y, width, height) in logical pixels, relative to a monitor
+    /// `monitor_width` logical pixels wide.
+    fn frame(self, monitor_width: f64) -> (f64, f64, f64, f64) {
+        match self {
+            Self::Strip => (0.0, 0.0, monitor_width, STRIP_HEIGHT),
+            Self::Icon => (monitor_width - ICON_SIZE - ICON_MARGIN, ICON_MARGIN, ICON_SIZE, ICON_SIZE),
+        }
+    }
+}
+
+/// Open an indicator window on each display. Windows are created off the
+/// command thread, as building a window from a sync command can deadlock on
+/// Windows.
+pub fn show(app: &AppHandle, style: Style) {
+    hide(app);
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let monitors = match app.available_monitors() {
+            Ok(monitors) => monitors,
+            Err(e) => {
+                log::warn!("Recording indicator: failed to list monitors: {}", e);
+                return;
+            }
+        };
+        for (i, monitor) in monitors.iter().enumerate() {
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            let scale = monitor.scale_factor();
+            let origin = monitor.position();
+            let (x, y, width, height) = style.frame(monitor.size().width as f64 / scale);
+
+            let label = format!("{}{}-{}", LABEL_PREFIX, generation, i);
+            let url = format!("index.html#indicator/{}", style.as_str());
+            let window = match WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(url.into()))
+                .title("MentaScribe Recording")
+                .visible(false)
+                .decorations(false)
+                .transparent(true)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .resizable(false)
+                .focused(false)
+                .shadow(false)
+                .visible_on_all_workspaces(true)
+                .build()
+            {
+                Ok(window) => window,
+                Err(e) => {
+                    log::warn!("Recording indicator: failed to create window on monitor {}: {}", i, e);
+                    continue;
+                }
+            };
+
+            // Physical coordinates, so monitors with different scale factors line up
+            window.set_size(PhysicalSize::new((width * scale).round() as u32, (height * scale).round() as u32)).ok();
+            window
+                .set_position(PhysicalPosition::new(
+                    origin.x + (x * scale).round() as i32,
+                    origin.y + (y * scale).round() as i32,
+                ))
+                .ok();
+            window.set_ignore_cursor_events(true).ok();
+            setup_indicator_panel(&app, &label);
+
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                window.destroy().ok();
+                return;
+            }
+            window.show().ok();
+        }
+        log::info!("Recording indicator shown on {} display(s)", monitors.len());
+    });
+}
+
+/// Close every indicator window, including ones still being created.
+pub fn hide(app: &AppHandle) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    for (label, window) in app.webview_windows() {
+        if label.starts_with(LABEL_PREFIX) {
+            window.destroy().ok();
+        }
+    }
+}
+
+/// Same panel treatment as the dictation window, so the indicator also shows
+/// over fullscreen apps and on every space.
+#[cfg(target_os = "macos")]
+fn setup_indicator_panel(app: &AppHandle, label: &str) {
+    use tauri_nspanel::cocoa::appkit::NSWindowCollectionBehavior;
+    use tauri_nspanel::WebviewWindowExt;
+
+    const OVERLAY_WINDOW_LEVEL: i32 = 25;
+    const NS_NONACTIVATING_PANEL_MASK: i32 = 128;
+
+    let Some(window) = app.get_webview_window(label) else { return };
+    let label = label.to_string();
+    // AppKit calls must happen on the main thread
+    app.run_on_main_thread(move || match window.to_panel() {
+        Ok(panel) => {
+            panel.set_level(OVERLAY_WINDOW_LEVEL);
+            panel.set_collection_behaviour(
+                NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+                    | NSWindowCollectionBehavior::NSWindowCollectionBehaviorStationary
+                    | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
+                    | NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle,
+            );
+            panel.set_style_mask(NS_NONACTIVATING_PANEL_MASK);
+            panel.set_floating_panel(true);
+            panel.set_hides_on_deactivate(false);
+        }
+        Err(e) => log::warn!("Recording indicator: failed to convert {} to NSPanel: {:?}", label, e),
+    })
+    .ok();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn setup_indicator_panel(_app: &AppHandle, _label: &str) {
+    // On non-macOS platforms, always_on_top is sufficient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style_from_setting() {
+        assert_eq!(Style::from_setting(Some("strip")), Some(Style::Strip));
+        assert_eq!(Style::from_setting(Some("icon")), Some(Style::Icon));
+        assert_eq!(Style::from_setting(Some("off")), None);
+        assert_eq!(Style::from_setting(None), None);
+    }
+
+    #[test]
+    fn test_icon_frame_in_top_right_corner() {
+        let (x, y, width, _) = Style::Icon.frame(1440.0);
+        assert_eq!(x + width + ICON_MARGIN, 1440.0);
+        assert_eq!(y, ICON_MARGIN);
+    }
+}
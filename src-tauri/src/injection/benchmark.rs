@@ -0,0 +1,107 @@
+//! Insert method benchmark for the diagnostics page.
+//!
+//! Types a known test string into the focused field once per insert method,
+//! timing each one and, where the field can be read back through AX (macOS),
+//! checking that exactly that text arrived. The results show which method to
+//! pin for an app that auto mode handles badly.
+
+use super::InjectionError;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Mixes plain ASCII with characters that trip up some methods (typographic
+/// punctuation, accents, an emoji outside the BMP).
+pub const TEST_TEXT: &str = "MentaScribe test: naïve café — “quotes” 😀";
+
+/// Time the target app gets to process an insertion before it's read back.
+const SETTLE: Duration = Duration::from_millis(300);
+
+#[cfg(target_os = "macos")]
+const METHODS: &[&str] = &["ax_api", "type", "paste_restore", "paste"];
+#[cfg(target_os = "windows")]
+const METHODS: &[&str] = &["sendinput", "paste_restore", "paste"];
+#[cfg(target_os = "linux")]
+const METHODS: &[&str] = &["type", "paste"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodResult {
+    pub method: String,
+    /// Wall-clock time of the insertion itself, excluding the settle delay
+    pub duration_ms: u64,
+    /// The method reported success
+    pub succeeded: bool,
+    /// The field ends with the text afterwards; None where it can't be read back
+    pub verified: Option<bool>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InjectionBenchmark {
+    /// App the text went to (bundle id / executable name)
+    pub app_id: Option<String>,
+    pub text: String,
+    pub results: Vec<MethodResult>,
+}
+
+/// Insert the test string (`text`, or `TEST_TEXT`) with every method
+/// available on this platform, in the field that has focus. Each insertion
+/// is tagged with its method so one that silently fails can't be verified by
+/// a previous method's text.
+pub fn run(text: Option<&str>) -> Result<InjectionBenchmark, InjectionError> {
+    if !super::can_inject() {
+        return Err(if cfg!(target_os = "linux") {
+            InjectionError::WaylandToolMissing
+        } else {
+            InjectionError::AccessibilityPermissionRequired
+        });
+    }
+    let text = text.filter(|t| !t.trim().is_empty()).unwrap_or(TEST_TEXT).to_string();
+    let app_id = super::detect::frontmost_app_id();
+    log::info!("Benchmarking insert methods in {:?}", app_id.as_deref().unwrap_or("unknown app"));
+
+    let mut results = Vec::new();
+    for method in METHODS {
+        let tagged = format!(" {} [{}]", text, method);
+        let start = Instant::now();
+        let outcome = super::inject_with_method(method, &tagged);
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        std::thread::sleep(SETTLE);
+        let verified = match outcome {
+            Ok(()) => super::cursor_context(tagged.chars().count() + 8).map(|c| arrived(&c.before, &tagged)),
+            Err(_) => None,
+        };
+        diag!(Injection, "[benchmark] {}: {}ms, verified={:?}", method, duration_ms, verified);
+        results.push(MethodResult {
+            method: method.to_string(),
+            duration_ms,
+            succeeded: outcome.is_ok(),
+            verified,
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+
+    Ok(InjectionBenchmark { app_id, text, results })
+}
+
+/// Whether the text before the cursor ends with what was inserted. Some
+/// fields turn the leading space into a non-breaking one or drop it at the
+/// start of a line, so only the text itself has to match.
+fn arrived(before: &str, inserted: &str) -> bool {
+    let normalize = |s: &str| s.replace('\u{a0}', " ");
+    normalize(before).ends_with(normalize(inserted).trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrived() {
+        assert!(arrived("Hello naïve [type]", " naïve [type]"));
+        assert!(arrived("naïve [type]", " naïve [type]"));
+        assert!(arrived("x\u{a0}a b", " a b"));
+        assert!(!arrived("naive [type]", " naïve [type]"));
+        assert!(!arrived("naïve [type] ", " naïve [type]"));
+    }
+}
@@ -0,0 +1,105 @@
+//! The app that has focus, and so receives injected text.
+//!
+//! Apps are identified by bundle id on macOS (NSWorkspace's frontmost
+//! application) and by executable name on Windows (the foreground window's
+//! process). History, the code profile, learned insert methods and
+//! `app_profiles` are all keyed by this id.
+
+/// Identifier of the app that receives injected text: bundle id on macOS,
+/// executable name on Windows. None where it can't be determined (Linux).
+pub fn frontmost_app_id() -> Option<String> {
+    platform::frontmost_app_id()
+}
+
+/// Returns true if the given bundle ID is a terminal emulator.
+/// Terminal apps process keyboard input through a PTY, not the Cocoa text
+/// input system, so CGEvent Unicode typing doesn't work — the events
+/// leak through and cause side effects (e.g. Dock showing up).
+#[cfg(target_os = "macos")]
+pub fn is_terminal_app(bundle_id: &str) -> bool {
+    matches!(
+        bundle_id,
+        "com.apple.Terminal"
+            | "com.googlecode.iterm2"
+            | "io.alacritty"
+            | "com.github.wez.wezterm"
+            | "net.kovidgoyal.kitty"
+            | "co.zeit.hyper"
+            | "dev.warp.Warp-Stable"
+            | "com.qvacua.VimR"
+            | "org.alacritty"
+    )
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    /// Bundle identifier of the frontmost application.
+    pub fn frontmost_app_id() -> Option<String> {
+        use cocoa::base::id;
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let app: id = msg_send![workspace, frontmostApplication];
+            if app.is_null() {
+                return None;
+            }
+            let bundle_id: id = msg_send![app, bundleIdentifier];
+            if bundle_id.is_null() {
+                return None;
+            }
+            let utf8: *const std::os::raw::c_char = msg_send![bundle_id, UTF8String];
+            if utf8.is_null() {
+                return None;
+            }
+            Some(
+                std::ffi::CStr::from_ptr(utf8)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    /// Executable name (e.g. "code.exe") of the foreground window's process.
+    pub fn frontmost_app_id() -> Option<String> {
+        use windows::core::PWSTR;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+        unsafe {
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(GetForegroundWindow(), Some(&mut pid as *mut u32));
+            if pid == 0 {
+                return None;
+            }
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut buf = [0u16; 1024];
+            let mut len = buf.len() as u32;
+            let result = QueryFullProcessImageNameW(
+                process,
+                PROCESS_NAME_WIN32,
+                PWSTR(buf.as_mut_ptr()),
+                &mut len,
+            );
+            CloseHandle(process).ok();
+            result.ok()?;
+            let path = String::from_utf16_lossy(&buf[..len as usize]);
+            path.rsplit('\\').next().map(|name| name.to_lowercase())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// No portable way to ask X11/Wayland desktops which app has focus.
+    pub fn frontmost_app_id() -> Option<String> {
+        None
+    }
+}
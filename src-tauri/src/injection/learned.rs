@@ -0,0 +1,74 @@
+//! Insert methods learned per target app in auto mode.
+//!
+//! When an earlier tier fails for an app and a later one succeeds, the working
+//! method is remembered (keyed by bundle id / executable name) so the next
+//! dictation into that app starts there instead of retrying the failing tier.
+
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// App id -> insert method ("ax_api", "type", "sendinput", "paste_restore").
+/// Loaded from disk on first use.
+static LEARNED: Lazy<Mutex<BTreeMap<String, String>>> = Lazy::new(|| Mutex::new(load()));
+
+fn get_learned_path() -> PathBuf {
+    crate::paths::config_dir().join("learned_insert_methods.json")
+}
+
+fn load() -> BTreeMap<String, String> {
+    std::fs::read_to_string(get_learned_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(learned: &BTreeMap<String, String>) {
+    let path = get_learned_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    match serde_json::to_string_pretty(learned) {
+        Ok(contents) => {
+            if let Err(e) = std::fs::write(&path, contents) {
+                log::warn!("Failed to save learned insert methods: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize learned insert methods: {}", e),
+    }
+}
+
+pub fn get(app_id: &str) -> Option<String> {
+    LEARNED.lock().ok()?.get(app_id).cloned()
+}
+
+pub fn remember(app_id: &str, method: &str) {
+    if app_id.is_empty() {
+        return;
+    }
+    if let Ok(mut learned) = LEARNED.lock() {
+        if learned.get(app_id).map(|m| m.as_str()) != Some(method) {
+            log::info!("Learned insert method for '{}': {}", app_id, method);
+            learned.insert(app_id.to_string(), method.to_string());
+            save(&learned);
+        }
+    }
+}
+
+pub fn all() -> BTreeMap<String, String> {
+    LEARNED.lock().map(|l| l.clone()).unwrap_or_default()
+}
+
+/// Forget one app, or every app when `app_id` is None.
+pub fn forget(app_id: Option<&str>) {
+    if let Ok(mut learned) = LEARNED.lock() {
+        match app_id {
+            Some(id) => {
+                learned.remove(id);
+            }
+            None => learned.clear(),
+        }
+        save(&learned);
+    }
+}
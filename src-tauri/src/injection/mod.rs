@@ -1,14 +1,33 @@
-use crate::settings::UserSettings;
+pub mod benchmark;
+pub mod detect;
+pub mod learned;
+pub mod permission;
+pub mod queue;
+#[cfg(all(target_os = "macos", feature = "app-store"))]
+pub mod sandbox;
+#[cfg(target_os = "linux")]
+pub mod wayland;
+
+use crate::settings::{AppProfile, UserSettings};
+use std::time::Duration;
 use thiserror::Error;
 
+// Optimized delay: 2ms between typed chunks (was 5ms + 2ms = 7ms, ~3.5x faster)
+// 1.5ms caused drops in some slower apps; 2ms is reliable across ~95% of apps.
+// `app_profiles` can set another per app.
+const DEFAULT_CHUNK_DELAY: Duration = Duration::from_micros(2000);
+
 #[derive(Error, Debug)]
 pub enum InjectionError {
     #[error("Text injection failed: {0}")]
     Failed(String),
     #[error("Accessibility permission required. Go to System Settings > Privacy & Security > Accessibility")]
     AccessibilityPermissionRequired,
-    #[error("X11 display not available. Wayland is not yet supported.")]
-    WaylandNotSupported,
+    #[error("Typing into Wayland apps needs wtype or ydotool (with ydotoold running) installed")]
+    WaylandToolMissing,
+    /// Sandboxed build without permission to paste: the text is on the clipboard
+    #[error("Copied to clipboard. Press ⌘V to paste")]
+    PasteManually,
 }
 
 // ============================================================================
@@ -41,10 +60,6 @@ mod platform {
     // macOS hard limit: CGEventKeyboardSetUnicodeString truncates at 20 UTF-16 code units
     const MAX_UTF16_UNITS_PER_EVENT: usize = 20;
 
-    // Optimized delay: 2ms between chunks (was 5ms + 2ms = 7ms, ~3.5x faster)
-    // 1.5ms caused drops in some slower apps; 2ms is reliable across ~95% of apps
-    const CHUNK_DELAY_US: u64 = 2000;
-
     pub fn check_accessibility() -> bool {
         #[link(name = "ApplicationServices", kind = "framework")]
         extern "C" {
@@ -101,7 +116,7 @@ mod platform {
                 &mut focused_raw,
             );
             if result != 0 || focused_raw.is_null() {
-                eprintln!("[ax_insert] No focused element (error={})", result);
+                diag!(Injection, "[ax_insert] No focused element (error={})", result);
                 core_foundation::base::CFRelease(system_wide as CFTypeRef);
                 return Ok(false);
             }
@@ -121,9 +136,9 @@ mod platform {
                 let role_cf = core_foundation::string::CFString::wrap_under_get_rule(
                     role_raw as core_foundation::string::CFStringRef,
                 );
-                eprintln!("[ax_insert] Focused element role='{}'", role_cf.to_string());
+                diag!(Injection, "[ax_insert] Focused element role='{}'", role_cf.to_string());
             } else {
-                eprintln!("[ax_insert] Could not get role (error={})", role_result);
+                diag!(Injection, "[ax_insert] Could not get role (error={})", role_result);
             }
 
             // Check if kAXSelectedTextAttribute is settable — this is the real
@@ -137,7 +152,7 @@ mod platform {
                 &mut settable as *mut bool,
             );
             if settable_result != 0 || !settable {
-                eprintln!(
+                diag!(Injection,
                     "[ax_insert] AXSelectedText not settable (error={}, settable={})",
                     settable_result, settable
                 );
@@ -158,21 +173,141 @@ mod platform {
             core_foundation::base::CFRelease(system_wide as CFTypeRef);
 
             if set_result == 0 {
-                eprintln!("[ax_insert] Success via AX API");
+                diag!(Injection, "[ax_insert] Success via AX API");
                 Ok(true)
             } else {
-                eprintln!("[ax_insert] SetAttributeValue failed (error={})", set_result);
+                diag!(Injection, "[ax_insert] SetAttributeValue failed (error={})", set_result);
                 Ok(false)
             }
         }
     }
 
+    /// Up to `max_chars` of the text on each side of the insertion point of the
+    /// focused element, or None if the element doesn't expose its selection and
+    /// contents through AX. The text after the cursor is optional, as some
+    /// elements don't report their length.
+    pub fn cursor_context(max_chars: usize) -> Option<crate::text::CursorContext> {
+        use accessibility_sys::*;
+        use core_foundation::base::{CFRange, CFRelease, CFTypeRef, TCFType};
+        use core_foundation::number::{CFNumber, CFNumberRef};
+        use core_foundation::string::CFString;
+        use std::ffi::c_void;
+
+        unsafe {
+            let system_wide = AXUIElementCreateSystemWide();
+            let mut focused_raw: CFTypeRef = std::ptr::null();
+            let focused_attr = CFString::new("AXFocusedUIElement");
+            let result = AXUIElementCopyAttributeValue(
+                system_wide,
+                focused_attr.as_concrete_TypeRef(),
+                &mut focused_raw,
+            );
+            CFRelease(system_wide as CFTypeRef);
+            if result != 0 || focused_raw.is_null() {
+                return None;
+            }
+            let element = focused_raw as AXUIElementRef;
+
+            // Cursor position = the selected range (replaced by the insertion)
+            let mut range_raw: CFTypeRef = std::ptr::null();
+            let range_attr = CFString::new("AXSelectedTextRange");
+            let result = AXUIElementCopyAttributeValue(
+                element,
+                range_attr.as_concrete_TypeRef(),
+                &mut range_raw,
+            );
+            if result != 0 || range_raw.is_null() {
+                CFRelease(element as CFTypeRef);
+                return None;
+            }
+            let mut selection = CFRange { location: 0, length: 0 };
+            let ok = AXValueGetValue(
+                range_raw as AXValueRef,
+                kAXValueTypeCFRange,
+                &mut selection as *mut CFRange as *mut c_void,
+            );
+            CFRelease(range_raw);
+            if !ok || selection.location < 0 {
+                CFRelease(element as CFTypeRef);
+                return None;
+            }
+
+            let start = (selection.location - max_chars as isize).max(0);
+            let before = if selection.location == 0 {
+                Some(String::new())
+            } else {
+                string_for_range(element, start, selection.location - start)
+            };
+            let Some(before) = before else {
+                CFRelease(element as CFTypeRef);
+                return None;
+            };
+
+            let mut count_raw: CFTypeRef = std::ptr::null();
+            let count_attr = CFString::new("AXNumberOfCharacters");
+            let result = AXUIElementCopyAttributeValue(
+                element,
+                count_attr.as_concrete_TypeRef(),
+                &mut count_raw,
+            );
+            let after = if result == 0 && !count_raw.is_null() {
+                let count = CFNumber::wrap_under_create_rule(count_raw as CFNumberRef)
+                    .to_i64()
+                    .unwrap_or(0) as isize;
+                let from = selection.location + selection.length;
+                if from >= count {
+                    Some(String::new())
+                } else {
+                    string_for_range(element, from, (count - from).min(max_chars as isize))
+                }
+            } else {
+                None
+            };
+
+            CFRelease(element as CFTypeRef);
+            Some(crate::text::CursorContext { before, after })
+        }
+    }
+
+    /// The element's text in [location, location + length) via AXStringForRange.
+    unsafe fn string_for_range(
+        element: accessibility_sys::AXUIElementRef,
+        location: isize,
+        length: isize,
+    ) -> Option<String> {
+        use accessibility_sys::*;
+        use core_foundation::base::{CFRange, CFRelease, CFTypeRef, TCFType};
+        use core_foundation::string::{CFString, CFStringRef};
+        use std::ffi::c_void;
+
+        let range = CFRange { location, length };
+        let range_value = AXValueCreate(kAXValueTypeCFRange, &range as *const CFRange as *const c_void);
+        if range_value.is_null() {
+            return None;
+        }
+        let mut string_raw: CFTypeRef = std::ptr::null();
+        let string_attr = CFString::new("AXStringForRange");
+        let result = AXUIElementCopyParameterizedAttributeValue(
+            element,
+            string_attr.as_concrete_TypeRef(),
+            range_value as CFTypeRef,
+            &mut string_raw,
+        );
+        CFRelease(range_value as CFTypeRef);
+        if result != 0 || string_raw.is_null() {
+            diag!(Injection, "[ax_context] AXStringForRange unavailable (error={})", result);
+            return None;
+        }
+        Some(CFString::wrap_under_create_rule(string_raw as CFStringRef).to_string())
+    }
+
     // ── Tier 2: Optimized CGEvent typing ───────────────────────────────────
 
-    /// Type text using optimized CGEvent Unicode chunks.
+    /// Type text using optimized CGEvent Unicode chunks, pausing
+    /// `chunk_delay` after each.
     /// Key optimization: no Unicode string on key-up, reduced inter-chunk delay.
-    pub fn type_text(text: &str) -> Result<(), super::InjectionError> {
-        eprintln!(
+    pub fn type_text(text: &str, chunk_delay: Duration) -> Result<(), super::InjectionError> {
+        diag!(Injection,
             "[type_text] Starting optimized CGEvent typing for {} chars",
             text.chars().count()
         );
@@ -187,11 +322,11 @@ mod platform {
             let c = chars[i];
             match c {
                 '\n' | '\r' => {
-                    type_key(&source, VK_RETURN, CGEventFlags::empty())?;
+                    type_key(&source, VK_RETURN, CGEventFlags::empty(), chunk_delay)?;
                     i += 1;
                 }
                 '\t' => {
-                    type_key(&source, VK_TAB, CGEventFlags::empty())?;
+                    type_key(&source, VK_TAB, CGEventFlags::empty(), chunk_delay)?;
                     i += 1;
                 }
                 _ => {
@@ -212,13 +347,13 @@ mod platform {
                     }
 
                     if !chunk.is_empty() {
-                        type_unicode_chunk(&source, &chunk)?;
+                        type_unicode_chunk(&source, &chunk, chunk_delay)?;
                     }
                 }
             }
         }
 
-        eprintln!("[type_text] Completed typing {} chars", text.chars().count());
+        diag!(Injection, "[type_text] Completed typing {} chars", text.chars().count());
         Ok(())
     }
 
@@ -226,6 +361,7 @@ mod platform {
         source: &CGEventSource,
         keycode: CGKeyCode,
         flags: CGEventFlags,
+        delay: Duration,
     ) -> Result<(), super::InjectionError> {
         let key_down = CGEvent::new_keyboard_event(source.clone(), keycode, true)
             .map_err(|_| super::InjectionError::Failed("CGEvent key down failed".into()))?;
@@ -239,14 +375,14 @@ mod platform {
         key_up.post(CGEventTapLocation::HID);
 
         // Brief delay for control characters
-        thread::sleep(Duration::from_micros(CHUNK_DELAY_US));
+        thread::sleep(delay);
         Ok(())
     }
 
     /// Optimized Unicode chunk typing:
     /// - Unicode string only on key-down (key-up needs none)
     /// - Reduced inter-chunk delay from 7ms to 1.5ms
-    fn type_unicode_chunk(source: &CGEventSource, chunk: &str) -> Result<(), super::InjectionError> {
+    fn type_unicode_chunk(source: &CGEventSource, chunk: &str, delay: Duration) -> Result<(), super::InjectionError> {
         let utf16: Vec<u16> = chunk.encode_utf16().collect();
         if utf16.is_empty() {
             return Ok(());
@@ -269,60 +405,11 @@ mod platform {
             .map_err(|_| super::InjectionError::Failed("CGEvent key up failed".into()))?;
         event_up.post(CGEventTapLocation::HID);
 
-        // 1.5ms inter-chunk delay (was 7ms total)
-        thread::sleep(Duration::from_micros(CHUNK_DELAY_US));
+        // Inter-chunk delay (was 7ms total)
+        thread::sleep(delay);
         Ok(())
     }
 
-    // ── App detection ────────────────────────────────────────────────────
-
-    /// Get the bundle identifier of the frontmost application.
-    /// Used to detect apps where CGEvent typing doesn't work (terminals, etc.)
-    pub fn frontmost_bundle_id() -> Option<String> {
-        use cocoa::base::id;
-        use objc::{class, msg_send, sel, sel_impl};
-
-        unsafe {
-            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
-            let app: id = msg_send![workspace, frontmostApplication];
-            if app.is_null() {
-                return None;
-            }
-            let bundle_id: id = msg_send![app, bundleIdentifier];
-            if bundle_id.is_null() {
-                return None;
-            }
-            let utf8: *const std::os::raw::c_char = msg_send![bundle_id, UTF8String];
-            if utf8.is_null() {
-                return None;
-            }
-            Some(
-                std::ffi::CStr::from_ptr(utf8)
-                    .to_string_lossy()
-                    .into_owned(),
-            )
-        }
-    }
-
-    /// Returns true if the given bundle ID is a terminal emulator.
-    /// Terminal apps process keyboard input through a PTY, not the Cocoa text
-    /// input system, so CGEvent Unicode typing doesn't work — the events
-    /// leak through and cause side effects (e.g. Dock showing up).
-    pub fn is_terminal_app(bundle_id: &str) -> bool {
-        matches!(
-            bundle_id,
-            "com.apple.Terminal"
-                | "com.googlecode.iterm2"
-                | "io.alacritty"
-                | "com.github.wez.wezterm"
-                | "net.kovidgoyal.kitty"
-                | "co.zeit.hyper"
-                | "dev.warp.Warp-Stable"
-                | "com.qvacua.VimR"
-                | "org.alacritty"
-        )
-    }
-
     // ── Tier 3: Clipboard save/paste/restore ───────────────────────────────
 
     /// Save all NSPasteboard items, set text with transient marker, paste, restore.
@@ -336,7 +423,7 @@ mod platform {
             // Save all pasteboard items with all their type representations
             let items: id = msg_send![pasteboard, pasteboardItems];
             let item_count: usize = msg_send![items, count];
-            eprintln!(
+            diag!(Injection,
                 "[clipboard_restore] Saving {} pasteboard items",
                 item_count
             );
@@ -403,7 +490,7 @@ mod platform {
             // Check if user or another app copied something during our paste
             let change_count_after: i64 = msg_send![pasteboard, changeCount];
             if change_count_after != change_count_ours {
-                eprintln!(
+                diag!(Injection,
                     "[clipboard_restore] Change count changed during paste (ours={}, now={}), user may have copied — skipping restore",
                     change_count_ours, change_count_after
                 );
@@ -422,7 +509,7 @@ mod platform {
 
             if saved_items.is_empty() {
                 // Nothing to restore — pasteboard was empty before
-                eprintln!("[clipboard_restore] Pasteboard was empty, nothing to restore");
+                diag!(Injection, "[clipboard_restore] Pasteboard was empty, nothing to restore");
             } else {
                 // Recreate NSPasteboardItems with all saved types
                 let items_array: id = msg_send![class!(NSMutableArray), arrayWithCapacity: saved_items.len()];
@@ -437,7 +524,7 @@ mod platform {
                     let _: () = msg_send![new_item, release];
                 }
                 let _: bool = msg_send![pasteboard, writeObjects: items_array];
-                eprintln!("[clipboard_restore] Pasteboard restored ({} items)", saved_items.len());
+                diag!(Injection, "[clipboard_restore] Pasteboard restored ({} items)", saved_items.len());
             }
         }
 
@@ -550,7 +637,7 @@ mod platform {
             }
         }
 
-        eprintln!(
+        diag!(Injection,
             "[sendinput_unicode] Injected {} chars via {} events",
             text.chars().count(),
             inputs.len()
@@ -607,7 +694,7 @@ mod platform {
             }
         }
 
-        eprintln!(
+        diag!(Injection,
             "[clipboard_save_paste_restore] Injected {} chars, clipboard restored",
             text.len()
         );
@@ -616,10 +703,11 @@ mod platform {
 }
 
 // ============================================================================
-// Linux Implementation (unchanged — enigo fallback is fine)
+// Linux Implementation: XTest/enigo under X11, wtype/ydotool under Wayland
 // ============================================================================
 #[cfg(target_os = "linux")]
 mod platform {
+    use super::wayland;
     use std::ptr::null;
     use x11::xlib::{XCloseDisplay, XFlush, XKeysymToKeycode, XOpenDisplay};
     use x11::xtest::XTestFakeKeyEvent;
@@ -628,19 +716,12 @@ mod platform {
     const XK_V: u64 = 0x0076;
 
     pub fn check_accessibility() -> bool {
-        !is_wayland()
-    }
-
-    fn is_wayland() -> bool {
-        std::env::var("XDG_SESSION_TYPE")
-            .map(|v| v == "wayland")
-            .unwrap_or(false)
-            || std::env::var("WAYLAND_DISPLAY").is_ok()
+        !wayland::is_wayland() || wayland::available()
     }
 
     pub fn simulate_paste() -> Result<(), super::InjectionError> {
-        if is_wayland() {
-            return Err(super::InjectionError::WaylandNotSupported);
+        if wayland::is_wayland() {
+            return wayland::paste();
         }
 
         unsafe {
@@ -682,16 +763,59 @@ fn truncate_for_display(s: &str, max_bytes: usize) -> &str {
     &s[..end]
 }
 
-/// Inject text into the currently focused application
-pub fn inject_text(text: &str, settings: &UserSettings) -> Result<(), InjectionError> {
-    let method = settings
-        .output
-        .insert_method
-        .as_deref()
-        .unwrap_or("auto");
+/// Whether text can be injected at all: the Accessibility permission on
+/// macOS; on Linux an X11 session, or wtype or ydotool under Wayland.
+pub fn can_inject() -> bool {
+    platform::check_accessibility()
+}
+
+/// Up to `max_chars` of text on each side of the insertion point, where the
+/// platform can read it.
+fn cursor_context(max_chars: usize) -> Option<crate::text::CursorContext> {
+    #[cfg(target_os = "macos")]
+    {
+        return platform::cursor_context(max_chars);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = max_chars;
+        return None;
+    }
+}
 
-    eprintln!(
-        "[inject] method={}, chars={}, bytes={}",
+/// `app_profiles` entry for `app_id`, matched case-insensitively.
+fn app_profile<'a>(settings: &'a UserSettings, app_id: Option<&str>) -> Option<&'a AppProfile> {
+    let app_id = app_id?;
+    settings
+        .app_profiles
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(app_id))
+        .map(|(_, profile)| profile)
+}
+
+/// Inject text into the currently focused application. A `continuation`
+/// follows text injected moments ago in the same dictation (an earlier chunk,
+/// or the streaming prefix), so it gets a separating space even where the
+/// cursor context can't be read.
+///
+/// The frontmost app's entry in `app_profiles`, if it has one, picks the
+/// insert method, typing speed and trailing space.
+pub fn inject_text(text: &str, settings: &UserSettings, continuation: bool) -> Result<(), InjectionError> {
+    let app_id = detect::frontmost_app_id();
+    let profile = app_profile(settings, app_id.as_deref());
+    let method = profile
+        .and_then(|p| p.insert_method.as_deref())
+        .or(settings.output.insert_method.as_deref())
+        .unwrap_or("auto");
+    let chunk_delay = profile
+        .and_then(|p| p.chunk_delay_ms)
+        .map_or(DEFAULT_CHUNK_DELAY, |ms| Duration::from_millis(ms.into()));
+
+    diag!(Injection,
+        "[inject] app={}, profile={}, method={}, chars={}, bytes={}",
+        app_id.as_deref().unwrap_or("unknown"),
+        profile.is_some(),
         method,
         text.chars().count(),
         text.len()
@@ -704,64 +828,147 @@ pub fn inject_text(text: &str, settings: &UserSettings) -> Result<(), InjectionE
     let text = text.trim();
 
     if text.is_empty() {
-        eprintln!("[inject] Skipping empty text (after stripping BLANK_AUDIO markers)");
+        diag!(Injection, "[inject] Skipping empty text (after stripping BLANK_AUDIO markers)");
         return Ok(());
     }
 
-    eprintln!(
+    diag!(Injection,
         "[inject] Text after cleanup: '{}' ({} chars)",
         truncate_for_display(text, 50),
         text.chars().count()
     );
 
+    // Sandboxed builds work out what they may do per dictation instead
+    let sandboxed = is_sandboxed();
+
     // Check accessibility permissions
-    if !platform::check_accessibility() {
+    if !sandboxed && !platform::check_accessibility() {
+        // Revoked while running: hand the text over on the clipboard instead
+        if permission::was_revoked() {
+            copy_to_clipboard(text)?;
+            diag!(Injection, "[inject] Accessibility permission revoked, text left on the clipboard");
+            return Err(InjectionError::PasteManually);
+        }
         #[cfg(target_os = "macos")]
         {
             return Err(InjectionError::AccessibilityPermissionRequired);
         }
         #[cfg(target_os = "linux")]
         {
-            return Err(InjectionError::WaylandNotSupported);
+            return Err(InjectionError::WaylandToolMissing);
         }
     }
 
     // Minimal focus delay
     std::thread::sleep(std::time::Duration::from_millis(50));
 
+    // Fit the text to its surroundings: no capital mid-sentence, and a space
+    // wherever it would otherwise run into a neighbouring word. Without
+    // context the text goes in as is, apart from a continuation's space.
+    let text = match cursor_context(32) {
+        Some(context) => {
+            let fitted = crate::text::fit_to_context(
+                text,
+                &context,
+                crate::text::Casing::from_settings(&settings.output) == crate::text::Casing::Sentence,
+                settings.output.smart_spacing.unwrap_or(true),
+                &crate::dictionary::capitalized_words(),
+            );
+            if fitted != text {
+                diag!(Injection, "[inject] Adjusted to cursor context: '{}'", truncate_for_display(&fitted, 50));
+            }
+            fitted
+        }
+        None if continuation && text.starts_with(|c: char| c.is_alphanumeric()) => format!(" {}", text),
+        None => text.to_string(),
+    };
+    let text = if profile.and_then(|p| p.trailing_space).unwrap_or(false) && !text.ends_with(char::is_whitespace) {
+        format!("{} ", text)
+    } else {
+        text
+    };
+    let text = text.as_str();
+
+    let learn = settings.output.learn_insert_method.unwrap_or(true);
     let result = match method {
-        "auto" => inject_auto(text),
+        _ if sandboxed => inject_sandboxed(text),
+        "auto" => inject_auto(text, learn, chunk_delay),
         "ax_api" => inject_via_ax_api(text),
-        "type" => inject_via_typing(text),
+        "type" => inject_via_typing(text, chunk_delay),
         "paste" => inject_via_paste(text),
         "paste_restore" => inject_via_paste_restore(text),
-        _ => inject_auto(text),
+        _ => inject_auto(text, learn, chunk_delay),
     };
 
     match &result {
-        Ok(_) => eprintln!("[inject] Text injection succeeded"),
-        Err(e) => eprintln!("[inject] ERROR: Text injection failed: {}", e),
+        Ok(_) => diag!(Injection, "[inject] Text injection succeeded"),
+        Err(e) => log::error!("[inject] Text injection failed: {}", e),
     }
 
     result
 }
 
-/// Auto mode: use the tiered injection strategy per platform
-fn inject_auto(text: &str) -> Result<(), InjectionError> {
+/// Running inside the macOS App Sandbox (only checked in `app-store` builds).
+pub fn is_sandboxed() -> bool {
+    #[cfg(all(target_os = "macos", feature = "app-store"))]
+    {
+        return sandbox::capabilities().sandboxed;
+    }
+
+    #[cfg(not(all(target_os = "macos", feature = "app-store")))]
+    {
+        return false;
+    }
+}
+
+fn inject_sandboxed(text: &str) -> Result<(), InjectionError> {
+    #[cfg(all(target_os = "macos", feature = "app-store"))]
+    {
+        return sandbox::inject(text);
+    }
+
+    #[cfg(not(all(target_os = "macos", feature = "app-store")))]
+    {
+        return inject_auto(text, false, DEFAULT_CHUNK_DELAY);
+    }
+}
+
+/// Auto mode: use the tiered injection strategy per platform.
+/// With `learn`, a tier that works after an earlier one failed is remembered
+/// for the target app and tried first next time (see `learned`).
+fn inject_auto(text: &str, learn: bool, chunk_delay: Duration) -> Result<(), InjectionError> {
     #[cfg(target_os = "macos")]
     {
-        return inject_auto_macos(text);
+        return inject_auto_macos(text, learn, chunk_delay);
     }
 
     #[cfg(target_os = "windows")]
     {
-        return inject_auto_windows(text);
+        // SendInput inserts in one batch, with no chunks to pause between
+        let _ = chunk_delay;
+        return inject_auto_windows(text, learn);
     }
 
     #[cfg(target_os = "linux")]
     {
         // Linux: try typing via enigo, fall back to paste
-        return inject_via_typing(text);
+        let _ = learn;
+        return inject_via_typing(text, chunk_delay);
+    }
+}
+
+/// Index of the tier to start from: the learned method for `app_id`, if any.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn learned_start_tier(tiers: &[&str], app_id: &str, learn: bool) -> usize {
+    if !learn || app_id.is_empty() {
+        return 0;
+    }
+    match learned::get(app_id).and_then(|m| tiers.iter().position(|t| *t == m)) {
+        Some(index) => {
+            diag!(Injection, "[inject_auto] Using learned method '{}' for '{}'", tiers[index], app_id);
+            index
+        }
+        None => 0,
     }
 }
 
@@ -772,89 +979,113 @@ fn inject_auto(text: &str) -> Result<(), InjectionError> {
 /// input system. CGEvent Unicode events go unprocessed and leak to the
 /// system (causing side effects like the Dock appearing).
 #[cfg(target_os = "macos")]
-fn inject_auto_macos(text: &str) -> Result<(), InjectionError> {
+fn inject_auto_macos(text: &str, learn: bool, chunk_delay: Duration) -> Result<(), InjectionError> {
+    const TIERS: &[&str] = &["ax_api", "type", "paste_restore"];
+
     // Detect frontmost app to decide which tiers to try
-    let bundle_id = platform::frontmost_bundle_id().unwrap_or_default();
-    let is_terminal = platform::is_terminal_app(&bundle_id);
-    eprintln!(
+    let bundle_id = detect::frontmost_app_id().unwrap_or_default();
+    let is_terminal = detect::is_terminal_app(&bundle_id);
+    diag!(Injection,
         "[inject_auto] Frontmost app: '{}', is_terminal={}",
         bundle_id, is_terminal
     );
 
-    // Tier 1: Try AX API first (instant, no clipboard, proper undo)
-    match platform::try_ax_insert(text) {
-        Ok(true) => {
-            log::info!("Text injected via AX API: {} chars", text.len());
-            return Ok(());
-        }
-        Ok(false) => {
-            eprintln!("[inject_auto] AX API not available for this element");
-        }
-        Err(e) => {
-            eprintln!("[inject_auto] AX API error: {}", e);
-        }
-    }
+    let start = learned_start_tier(TIERS, &bundle_id, learn);
+    let mut last_error = None;
+    for tier in &TIERS[start..] {
+        let result = match *tier {
+            // Tier 1: AX API (instant, no clipboard, proper undo)
+            "ax_api" => match platform::try_ax_insert(text) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(InjectionError::Failed("AX API not available for this element".into())),
+                Err(e) => Err(e),
+            },
+            // Tier 2: CGEvent typing — skip for terminal apps (PTY input ignores these
+            // events and they leak to the system, causing the Dock to appear etc.)
+            "type" if is_terminal => {
+                diag!(Injection,
+                    "[inject_auto] Skipping CGEvent for terminal app '{}'",
+                    bundle_id
+                );
+                continue;
+            }
+            "type" => platform::type_text(text, chunk_delay),
+            // Tier 3: Clipboard save/paste/restore (last resort, or primary for terminals)
+            _ => {
+                diag!(Injection, "[inject_auto] Using clipboard save/paste/restore");
+                platform::clipboard_save_paste_restore(text)
+            }
+        };
 
-    // Tier 2: CGEvent typing — skip for terminal apps (PTY input ignores these
-    // events and they leak to the system, causing the Dock to appear etc.)
-    if !is_terminal {
-        match platform::type_text(text) {
+        match result {
             Ok(()) => {
-                log::info!("Text injected via CGEvent typing: {} chars", text.len());
+                log::info!("Text injected via {}: {} chars", tier, text.len());
+                if learn && last_error.is_some() {
+                    learned::remember(&bundle_id, tier);
+                }
                 return Ok(());
             }
             Err(e) => {
-                eprintln!("[inject_auto] CGEvent typing failed: {}", e);
+                diag!(Injection, "[inject_auto] {} failed: {}", tier, e);
+                last_error = Some(e);
             }
         }
-    } else {
-        eprintln!(
-            "[inject_auto] Skipping CGEvent for terminal app '{}'",
-            bundle_id
-        );
     }
-
-    // Tier 3: Clipboard save/paste/restore (last resort, or primary for terminals)
-    eprintln!("[inject_auto] Using clipboard save/paste/restore");
-    platform::clipboard_save_paste_restore(text)?;
-    log::info!(
-        "Text injected via clipboard save/paste/restore: {} chars",
-        text.len()
-    );
-    Ok(())
+    Err(last_error.unwrap_or_else(|| InjectionError::Failed("No insert method available".into())))
 }
 
 /// Windows auto mode: SendInput KEYEVENTF_UNICODE → clipboard save/paste/restore
 #[cfg(target_os = "windows")]
-fn inject_auto_windows(text: &str) -> Result<(), InjectionError> {
-    // Tier 1: SendInput for text up to ~2000 chars
-    if text.chars().count() <= 2000 {
-        match platform::sendinput_unicode(text) {
+fn inject_auto_windows(text: &str, learn: bool) -> Result<(), InjectionError> {
+    const TIERS: &[&str] = &["sendinput", "paste_restore"];
+
+    let app_id = detect::frontmost_app_id().unwrap_or_default();
+    diag!(Injection, "[inject_auto] Foreground app: '{}'", app_id);
+
+    let start = learned_start_tier(TIERS, &app_id, learn);
+    let mut last_error = None;
+    for tier in &TIERS[start..] {
+        let result = match *tier {
+            // Tier 1: SendInput for text up to ~2000 chars
+            "sendinput" if text.chars().count() > 2000 => {
+                diag!(Injection,
+                    "[inject_auto] Text too long for SendInput ({} chars), using clipboard",
+                    text.chars().count()
+                );
+                continue;
+            }
+            "sendinput" => platform::sendinput_unicode(text),
+            // Tier 2: Clipboard save/paste/restore
+            _ => platform::clipboard_save_paste_restore(text),
+        };
+
+        match result {
             Ok(()) => {
-                log::info!("Text injected via SendInput UNICODE: {} chars", text.len());
+                log::info!("Text injected via {}: {} chars", tier, text.len());
+                if learn && last_error.is_some() {
+                    learned::remember(&app_id, tier);
+                }
                 return Ok(());
             }
             Err(e) => {
-                eprintln!(
-                    "[inject_auto] SendInput failed: {}, trying clipboard",
-                    e
-                );
+                diag!(Injection, "[inject_auto] {} failed: {}", tier, e);
+                last_error = Some(e);
             }
         }
-    } else {
-        eprintln!(
-            "[inject_auto] Text too long for SendInput ({} chars), using clipboard",
-            text.chars().count()
-        );
     }
+    Err(last_error.unwrap_or_else(|| InjectionError::Failed("No insert method available".into())))
+}
 
-    // Tier 2: Clipboard save/paste/restore
-    platform::clipboard_save_paste_restore(text)?;
-    log::info!(
-        "Text injected via clipboard save/paste/restore: {} chars",
-        text.len()
-    );
-    Ok(())
+/// Insert with exactly one method, without auto mode's fallbacks.
+fn inject_with_method(method: &str, text: &str) -> Result<(), InjectionError> {
+    match method {
+        "ax_api" => inject_via_ax_api(text),
+        #[cfg(target_os = "windows")]
+        "sendinput" => platform::sendinput_unicode(text),
+        "paste" => inject_via_paste(text),
+        "paste_restore" => inject_via_paste_restore(text),
+        _ => inject_via_typing(text, DEFAULT_CHUNK_DELAY),
+    }
 }
 
 /// AX API only mode (macOS). Falls back to typing on other platforms.
@@ -878,7 +1109,7 @@ fn inject_via_ax_api(text: &str) -> Result<(), InjectionError> {
     #[cfg(not(target_os = "macos"))]
     {
         // AX API is macOS-only; fall back to typing on other platforms
-        inject_via_typing(text)
+        inject_via_typing(text, DEFAULT_CHUNK_DELAY)
     }
 }
 
@@ -901,6 +1132,15 @@ fn inject_via_paste_restore(text: &str) -> Result<(), InjectionError> {
     }
 }
 
+/// Put `text` on the clipboard, without pasting it.
+pub fn copy_to_clipboard(text: &str) -> Result<(), InjectionError> {
+    use arboard::Clipboard;
+
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| InjectionError::Failed(format!("Clipboard: {}", e)))
+}
+
 /// Legacy paste mode: clipboard + Cmd+V/Ctrl+V (overwrites clipboard)
 fn inject_via_paste(text: &str) -> Result<(), InjectionError> {
     use arboard::Clipboard;
@@ -921,11 +1161,12 @@ fn inject_via_paste(text: &str) -> Result<(), InjectionError> {
     Ok(())
 }
 
-/// Legacy type mode: CGEvent on macOS, enigo on other platforms
-fn inject_via_typing(text: &str) -> Result<(), InjectionError> {
+/// Legacy type mode: CGEvent on macOS, wtype/ydotool under Wayland, enigo
+/// elsewhere
+fn inject_via_typing(text: &str, chunk_delay: Duration) -> Result<(), InjectionError> {
     #[cfg(target_os = "macos")]
     {
-        let result = platform::type_text(text);
+        let result = platform::type_text(text, chunk_delay);
         if result.is_ok() {
             log::info!("Text injected via CGEvent typing: {} chars", text.len());
         }
@@ -936,6 +1177,16 @@ fn inject_via_typing(text: &str) -> Result<(), InjectionError> {
     {
         use enigo::{Enigo, Keyboard, Settings};
 
+        // enigo and the Wayland tools type the whole text in one go
+        let _ = chunk_delay;
+
+        #[cfg(target_os = "linux")]
+        if wayland::is_wayland() {
+            wayland::type_text(text)?;
+            log::info!("Text injected via Wayland typing: {} chars", text.len());
+            return Ok(());
+        }
+
         let mut enigo =
             Enigo::new(&Settings::default()).map_err(|e| InjectionError::Failed(e.to_string()))?;
 
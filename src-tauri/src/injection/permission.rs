@@ -0,0 +1,70 @@
+//! Notice when the Accessibility permission is revoked (or granted again)
+//! while the app runs.
+//!
+//! Without it, injection on macOS can neither write through AX nor post ⌘V, so
+//! a permission revoked in System Settings used to show up only as a failed
+//! paste on the next dictation. The trust state is polled; each change is
+//! emitted as `permission-lost` or `permission-restored`, and while it's lost
+//! `inject_text` leaves the text on the clipboard for the user to paste.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+
+/// Payload of `permission-lost` and `permission-restored`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionChange {
+    /// Only "accessibility" so far
+    pub permission: String,
+}
+
+/// Set while a permission the app had earlier in this session is revoked.
+static REVOKED: AtomicBool = AtomicBool::new(false);
+
+pub fn was_revoked() -> bool {
+    REVOKED.load(Ordering::SeqCst)
+}
+
+/// Poll the Accessibility trust state for the lifetime of the app (macOS only;
+/// elsewhere it can't change while running).
+pub fn start(app: AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        use std::time::Duration;
+        use tauri::Emitter;
+
+        const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+        std::thread::Builder::new()
+            .name("permission-watcher".to_string())
+            .spawn(move || {
+                let mut trusted = super::platform::check_accessibility();
+                loop {
+                    std::thread::sleep(POLL_INTERVAL);
+                    let now = super::platform::check_accessibility();
+                    if now == trusted {
+                        continue;
+                    }
+                    trusted = now;
+                    let change = PermissionChange {
+                        permission: "accessibility".to_string(),
+                    };
+                    if now {
+                        REVOKED.store(false, Ordering::SeqCst);
+                        log::info!("Accessibility permission granted, injecting normally");
+                        app.emit("permission-restored", change).ok();
+                    } else {
+                        REVOKED.store(true, Ordering::SeqCst);
+                        log::warn!("Accessibility permission revoked, injecting via the clipboard until it returns");
+                        app.emit("permission-lost", change).ok();
+                    }
+                }
+            })
+            .ok();
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+    }
+}
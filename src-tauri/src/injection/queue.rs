@@ -0,0 +1,99 @@
+//! Paste queue output mode (`output.insert_method = "queue"`).
+//!
+//! Instead of being inserted, a dictation is split into utterances that are
+//! queued in order; the paste-next hotkey (`hotkey.paste_next_key`) inserts
+//! the oldest one wherever the cursor is. One long dictation can then fill a
+//! form field by field.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PasteQueue {
+    /// Oldest first
+    items: VecDeque<String>,
+}
+
+impl PasteQueue {
+    /// Queue the utterances in `text`. Returns how many were added.
+    pub fn push_text(&mut self, text: &str) -> usize {
+        let utterances = split_utterances(text);
+        let added = utterances.len();
+        self.items.extend(utterances);
+        added
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.items.pop_front()
+    }
+
+    pub fn items(&self) -> Vec<String> {
+        self.items.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// Sentences and lines of `text`: an utterance ends at a line break, or at
+/// `.`, `?` or `!` followed by whitespace.
+pub fn split_utterances(text: &str) -> Vec<String> {
+    let mut utterances = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            push_trimmed(&mut utterances, &mut current);
+            continue;
+        }
+        current.push(c);
+        if matches!(c, '.' | '?' | '!') && chars.peek().is_some_and(|next| next.is_whitespace()) {
+            push_trimmed(&mut utterances, &mut current);
+        }
+    }
+    push_trimmed(&mut utterances, &mut current);
+    utterances
+}
+
+fn push_trimmed(utterances: &mut Vec<String>, current: &mut String) {
+    let utterance = current.trim();
+    if !utterance.is_empty() {
+        utterances.push(utterance.to_string());
+    }
+    current.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_utterances() {
+        assert_eq!(
+            split_utterances("John Smith. 42 Main Street!\nSpringfield\n\nIs that all?  Yes"),
+            ["John Smith.", "42 Main Street!", "Springfield", "Is that all?", "Yes"]
+        );
+        assert_eq!(split_utterances("Version 1.5 of example.com"), ["Version 1.5 of example.com"]);
+        assert!(split_utterances("  \n ").is_empty());
+    }
+
+    #[test]
+    fn test_queue_order() {
+        let mut queue = PasteQueue::default();
+        assert_eq!(queue.push_text("First. Second."), 2);
+        assert_eq!(queue.push_text("Third"), 1);
+        assert_eq!(queue.pop().as_deref(), Some("First."));
+        assert_eq!(queue.items(), ["Second.", "Third"]);
+        queue.clear();
+        assert_eq!(queue.pop(), None);
+    }
+}
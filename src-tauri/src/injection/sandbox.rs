@@ -0,0 +1,68 @@
+//! Injection for sandboxed builds (`app-store` feature, macOS).
+//!
+//! Inside the App Sandbox, posting keyboard events and writing through the
+//! Accessibility API depend on what the user has granted, and synthetic
+//! typing isn't allowed at all. What is possible is detected at runtime, per
+//! dictation:
+//!
+//! 1. AX insert, when Accessibility is granted
+//! 2. Clipboard + ⌘V, when event posting is allowed
+//! 3. Otherwise the text is put on the clipboard and the user is asked to
+//!    paste it (`InjectionError::PasteManually`)
+
+use serde::{Deserialize, Serialize};
+
+use super::{platform, InjectionError};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    /// macOS 10.15+: whether this process may post keyboard events
+    fn CGPreflightPostEventAccess() -> bool;
+}
+
+/// What injection can use in this process, checked when asked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Running inside the App Sandbox
+    pub sandboxed: bool,
+    pub accessibility: bool,
+    pub post_events: bool,
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        // Set by the system for every sandboxed process
+        sandboxed: std::env::var_os("APP_SANDBOX_CONTAINER_ID").is_some(),
+        accessibility: platform::check_accessibility(),
+        post_events: unsafe { CGPreflightPostEventAccess() },
+    }
+}
+
+pub fn inject(text: &str) -> Result<(), InjectionError> {
+    let capabilities = capabilities();
+    diag!(Injection,
+        "[inject] Sandboxed: accessibility={}, post_events={}",
+        capabilities.accessibility, capabilities.post_events
+    );
+
+    if capabilities.accessibility {
+        match platform::try_ax_insert(text) {
+            Ok(true) => {
+                log::info!("Text injected via AX API (sandboxed): {} chars", text.len());
+                return Ok(());
+            }
+            Ok(false) => diag!(Injection, "[inject] AX insert not supported by the focused element"),
+            Err(e) => diag!(Injection, "[inject] AX insert failed: {}", e),
+        }
+    }
+
+    if capabilities.post_events {
+        platform::clipboard_save_paste_restore(text)?;
+        log::info!("Text injected via clipboard paste (sandboxed): {} chars", text.len());
+        return Ok(());
+    }
+
+    super::copy_to_clipboard(text)?;
+    log::info!("Text copied for manual paste (sandboxed): {} chars", text.len());
+    Err(InjectionError::PasteManually)
+}
@@ -0,0 +1,186 @@
+//! Text injection under Wayland, where XTest only reaches XWayland windows.
+//!
+//! Wayland has no protocol every compositor implements for synthesizing
+//! input, so this drives one of two command-line tools, found on PATH at
+//! runtime:
+//! - wtype: the `zwp_virtual_keyboard_v1` protocol (wlroots compositors such
+//!   as Sway and Hyprland, and KDE Plasma). GNOME doesn't offer the protocol;
+//!   wtype then says so and exits, and it's skipped for the rest of the session.
+//! - ydotool: a virtual uinput device through the ydotoold daemon, so it works
+//!   on any compositor, but only while ydotoold runs and can open /dev/uinput.
+//!
+//! Each operation tries wtype, then ydotool. The fallback only happens when
+//! wtype couldn't have typed anything, since ydotool would otherwise type the
+//! text a second time.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::InjectionError;
+
+/// wtype reported that the compositor lacks `zwp_virtual_keyboard_v1`
+static WTYPE_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+// Linux input event codes, for ydotool
+const KEY_LEFTCTRL: u32 = 29;
+const KEY_V: u32 = 47;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Wtype,
+    Ydotool,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Wtype => "wtype",
+            Tool::Ydotool => "ydotool",
+        }
+    }
+}
+
+pub fn is_wayland() -> bool {
+    std::env::var("XDG_SESSION_TYPE")
+        .map(|v| v == "wayland")
+        .unwrap_or(false)
+        || std::env::var("WAYLAND_DISPLAY").is_ok()
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Tools to try, in order.
+fn tools() -> Vec<Tool> {
+    [Tool::Wtype, Tool::Ydotool]
+        .into_iter()
+        .filter(|tool| !(*tool == Tool::Wtype && WTYPE_UNSUPPORTED.load(Ordering::SeqCst)))
+        .filter(|tool| find_on_path(tool.name()).is_some())
+        .collect()
+}
+
+/// Whether text can be injected: wtype or ydotool is installed (and wtype,
+/// if it's the only one, hasn't found the compositor lacking).
+pub fn available() -> bool {
+    !tools().is_empty()
+}
+
+/// wtype's message when the compositor doesn't offer the protocol.
+fn lacks_virtual_keyboard(stderr: &str) -> bool {
+    stderr.to_ascii_lowercase().contains("virtual keyboard protocol")
+}
+
+/// A failed run of a tool.
+struct RunError {
+    error: InjectionError,
+    /// The tool didn't start, or found nothing to type with, so no input
+    /// reached the focused window and the next tool may try
+    before_input: bool,
+}
+
+impl RunError {
+    fn new(tool: Tool, message: impl std::fmt::Display, before_input: bool) -> Self {
+        Self {
+            error: InjectionError::Failed(format!("{}: {}", tool.name(), message)),
+            before_input,
+        }
+    }
+}
+
+/// Run `tool` with `args`, writing `stdin` to it if given.
+fn run(tool: Tool, args: &[&str], stdin: Option<&str>) -> Result<(), RunError> {
+    let mut child = Command::new(tool.name())
+        .args(args)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| RunError::new(tool, e, true))?;
+    if let (Some(text), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        if let Err(e) = pipe.write_all(text.as_bytes()) {
+            // Don't leave it running, or a zombie once it exits
+            child.kill().ok();
+            child.wait().ok();
+            return Err(RunError::new(tool, e, false));
+        }
+    }
+    let output = child.wait_with_output().map_err(|e| RunError::new(tool, e, false))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let unsupported = tool == Tool::Wtype && lacks_virtual_keyboard(&stderr);
+    if unsupported {
+        log::warn!("[wayland] Compositor has no virtual keyboard protocol, not using wtype again");
+        WTYPE_UNSUPPORTED.store(true, Ordering::SeqCst);
+    }
+    Err(RunError {
+        error: InjectionError::Failed(format!("{} failed: {}", tool.name(), stderr)),
+        before_input: unsupported,
+    })
+}
+
+/// Try `op` with each available tool until one succeeds. A tool that failed
+/// after it may have typed something ends the attempt: the next one would
+/// repeat what got through.
+fn with_tools(what: &str, op: impl Fn(Tool) -> Result<(), RunError>) -> Result<(), InjectionError> {
+    let tools = tools();
+    if tools.is_empty() {
+        return Err(InjectionError::WaylandToolMissing);
+    }
+    let mut last_error = None;
+    for tool in tools {
+        match op(tool) {
+            Ok(()) => {
+                diag!(Injection, "[wayland] {} via {}", what, tool.name());
+                return Ok(());
+            }
+            Err(RunError { error, before_input }) => {
+                diag!(Injection, "[wayland] {} via {} failed: {}", what, tool.name(), error);
+                if !before_input {
+                    return Err(error);
+                }
+                last_error = Some(error);
+            }
+        }
+    }
+    Err(last_error.unwrap_or(InjectionError::WaylandToolMissing))
+}
+
+/// Type `text` into the focused window.
+pub fn type_text(text: &str) -> Result<(), InjectionError> {
+    with_tools("Typed text", |tool| match tool {
+        // "--": text starting with "-" isn't an option
+        Tool::Wtype => run(tool, &["--", text], None),
+        Tool::Ydotool => run(tool, &["type", "--file", "-"], Some(text)),
+    })
+}
+
+/// Press Ctrl+V in the focused window.
+pub fn paste() -> Result<(), InjectionError> {
+    let ctrl_down = format!("{}:1", KEY_LEFTCTRL);
+    let v_down = format!("{}:1", KEY_V);
+    let v_up = format!("{}:0", KEY_V);
+    let ctrl_up = format!("{}:0", KEY_LEFTCTRL);
+    with_tools("Pasted", |tool| match tool {
+        Tool::Wtype => run(tool, &["-M", "ctrl", "-k", "v", "-m", "ctrl"], None),
+        Tool::Ydotool => run(tool, &["key", &ctrl_down, &v_down, &v_up, &ctrl_up], None),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lacks_virtual_keyboard() {
+        assert!(lacks_virtual_keyboard("Compositor does not support the virtual keyboard protocol"));
+        assert!(!lacks_virtual_keyboard("failed to connect to display"));
+    }
+}
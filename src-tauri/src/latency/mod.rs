@@ -0,0 +1,165 @@
+//! Per-transcription latency accounting.
+//!
+//! `stop_recording` measures each phase between the user releasing the hotkey
+//! and the text being ready; `inject_text` adds the injection time. The result
+//! is emitted as `transcription-timings` and stored on the history entry, with a
+//! human-readable dominant cause when the total is over budget.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::transcription::EngineTimings;
+
+/// Totals above this are reported with a dominant cause.
+const SLOW_THRESHOLD_MS: u64 = 1500;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscriptionTimings {
+    /// Waiting for streaming transcription to finish and audio capture to stop
+    pub stop_ms: u64,
+    pub vad_ms: u64,
+    /// Non-zero when the model was cold-loaded for this transcription
+    pub model_load_ms: u64,
+    pub inference_ms: u64,
+    /// Punctuation, capitalization and dictionary replacements
+    pub post_process_ms: u64,
+    /// None until the text has been injected
+    #[serde(default)]
+    pub injection_ms: Option<u64>,
+    pub total_ms: u64,
+    /// Seconds of tail audio transcribed on stop
+    pub tail_audio_secs: f64,
+    /// e.g. "model was cold-loaded (4.2s)"; set only when total exceeds the budget
+    #[serde(default)]
+    pub slow_reason: Option<String>,
+    /// Times the audio callback lost audio during the recording
+    #[serde(default)]
+    pub dropouts: u32,
+    /// Silence inserted in place of the lost audio
+    #[serde(default)]
+    pub concealed_ms: u32,
+}
+
+impl TranscriptionTimings {
+    /// Build from the measured phases. `transcribe_ms` is the wall time of the
+    /// engine call; whatever the engine didn't account for is counted as inference.
+    pub fn new(
+        stop_ms: u64,
+        transcribe_ms: u64,
+        engine: EngineTimings,
+        post_process_ms: u64,
+        tail_audio_secs: f64,
+    ) -> Self {
+        let accounted = engine.vad_ms + engine.model_load_ms;
+        let inference_ms = engine
+            .inference_ms
+            .max(transcribe_ms.saturating_sub(accounted));
+        let mut timings = Self {
+            stop_ms,
+            vad_ms: engine.vad_ms,
+            model_load_ms: engine.model_load_ms,
+            inference_ms,
+            post_process_ms,
+            injection_ms: None,
+            total_ms: 0,
+            tail_audio_secs,
+            slow_reason: None,
+            dropouts: 0,
+            concealed_ms: 0,
+        };
+        timings.finish();
+        timings
+    }
+
+    /// Record injection time and recompute the total.
+    pub fn set_injection(&mut self, injection_ms: u64) {
+        self.injection_ms = Some(injection_ms);
+        self.finish();
+    }
+
+    fn finish(&mut self) {
+        self.total_ms = self.stop_ms
+            + self.vad_ms
+            + self.model_load_ms
+            + self.inference_ms
+            + self.post_process_ms
+            + self.injection_ms.unwrap_or(0);
+        self.slow_reason = self.dominant_cause();
+    }
+
+    fn dominant_cause(&self) -> Option<String> {
+        if self.total_ms < SLOW_THRESHOLD_MS {
+            return None;
+        }
+        let phases = [
+            ("load", self.model_load_ms),
+            ("inference", self.inference_ms),
+            ("stop", self.stop_ms),
+            ("vad", self.vad_ms),
+            ("post", self.post_process_ms),
+            ("inject", self.injection_ms.unwrap_or(0)),
+        ];
+        let (phase, ms) = phases.iter().max_by_key(|(_, ms)| *ms)?;
+        let secs = *ms as f64 / 1000.0;
+        Some(match *phase {
+            "load" => format!("model was cold-loaded ({:.1}s)", secs),
+            "inference" => format!(
+                "inference on {:.1}s of tail audio took {:.1}s",
+                self.tail_audio_secs, secs
+            ),
+            "stop" => format!("waiting for streaming transcription to finish ({:.1}s)", secs),
+            "vad" => format!("voice activity detection ({:.1}s)", secs),
+            "post" => format!("text post-processing ({:.1}s)", secs),
+            _ => format!("text injection ({:.1}s)", secs),
+        })
+    }
+}
+
+/// Timings of the last transcription, waiting for `inject_text` to add the
+/// injection time. Holds the history entry id to update, if one was saved.
+static PENDING: Lazy<Mutex<Option<(Option<String>, TranscriptionTimings)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+pub fn set_pending(entry_id: Option<String>, timings: TranscriptionTimings) {
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some((entry_id, timings));
+    }
+}
+
+pub fn take_pending() -> Option<(Option<String>, TranscriptionTimings)> {
+    PENDING.lock().ok().and_then(|mut p| p.take())
+}
+
+/// Timings of the last transcription once injected, for `AppHealth`.
+static LAST: Lazy<Mutex<Option<TranscriptionTimings>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_last(timings: &TranscriptionTimings) {
+    if let Ok(mut last) = LAST.lock() {
+        *last = Some(timings.clone());
+    }
+}
+
+pub fn last() -> Option<TranscriptionTimings> {
+    LAST.lock().ok().and_then(|last| last.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominant_cause() {
+        let engine = EngineTimings {
+            vad_ms: 20,
+            model_load_ms: 4200,
+            inference_ms: 300,
+        };
+        let timings = TranscriptionTimings::new(50, 4520, engine, 5, 1.2);
+        assert_eq!(timings.slow_reason.as_deref(), Some("model was cold-loaded (4.2s)"));
+
+        let fast = TranscriptionTimings::new(50, 300, EngineTimings::default(), 5, 1.2);
+        assert_eq!(fast.inference_ms, 300);
+        assert_eq!(fast.slow_reason, None);
+    }
+}
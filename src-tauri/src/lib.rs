@@ -1,3 +1,5 @@
+#[macro_use]
+mod diag;
 mod audio;
 mod transcription;
 mod hotkey;
@@ -8,14 +10,34 @@ mod text;
 mod stats;
 mod history;
 mod dictionary;
+mod net;
+mod storage;
+mod latency;
+mod privacy;
+mod pipeline;
+mod events;
+mod indicator;
+mod status;
+mod watcher;
+mod safe_mode;
+mod panics;
+mod paths;
+mod plugins;
+mod build_info;
+mod config;
+mod cleanup;
+mod backup;
+mod error;
 
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
     tray::TrayIconBuilder,
     Emitter, Manager, WebviewUrl, WebviewWindowBuilder,
 };
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use panics::LockExt;
+use error::AppError;
 
 /// Convert the dictation window to an NSPanel for fullscreen overlay support on macOS.
 ///
@@ -34,16 +56,16 @@ fn setup_dictation_panel(app: &tauri::AppHandle) {
     // NSNonactivatingPanelMask = 1 << 7 = 128 - makes panel not steal focus
     const NS_NONACTIVATING_PANEL_MASK: i32 = 128;
 
-    println!("[nspanel] setup_dictation_panel called");
+    diag!(Window, "[nspanel] setup_dictation_panel called");
 
     if let Some(window) = app.get_webview_window("dictation") {
-        println!("[nspanel] Found dictation window, converting to NSPanel...");
+        diag!(Window, "[nspanel] Found dictation window, converting to NSPanel...");
 
         match window.to_panel() {
             Ok(panel) => {
                 // Set panel level to above main menu for overlay visibility
                 panel.set_level(OVERLAY_WINDOW_LEVEL);
-                println!("[nspanel] Panel level set to: {}", OVERLAY_WINDOW_LEVEL);
+                diag!(Window, "[nspanel] Panel level set to: {}", OVERLAY_WINDOW_LEVEL);
 
                 // Set collection behavior for fullscreen overlay support:
                 // - CanJoinAllSpaces: visible on all desktops/spaces
@@ -55,11 +77,11 @@ fn setup_dictation_panel(app: &tauri::AppHandle) {
                     | NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary
                     | NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle;
                 panel.set_collection_behaviour(behavior);
-                println!("[nspanel] Collection behavior set for fullscreen overlay");
+                diag!(Window, "[nspanel] Collection behavior set for fullscreen overlay");
 
                 // Make the panel non-activating so it doesn't steal focus
                 panel.set_style_mask(NS_NONACTIVATING_PANEL_MASK);
-                println!("[nspanel] Panel style mask set to non-activating");
+                diag!(Window, "[nspanel] Panel style mask set to non-activating");
 
                 // Additional panel settings for overlay behavior
                 panel.set_floating_panel(true);
@@ -69,11 +91,11 @@ fn setup_dictation_panel(app: &tauri::AppHandle) {
             }
             Err(e) => {
                 log::error!("Failed to convert dictation window to NSPanel: {:?}", e);
-                println!("[nspanel] ERROR: Failed to convert to panel: {:?}", e);
+                log::error!("[nspanel] Failed to convert to panel: {:?}", e);
             }
         }
     } else {
-        println!("[nspanel] WARNING: dictation window not found");
+        log::warn!("[nspanel] dictation window not found");
     }
 }
 
@@ -137,59 +159,261 @@ pub struct AppState {
     pub is_recording: Mutex<bool>,
     pub settings: Mutex<settings::UserSettings>,
     pub audio_level_emitter_running: Arc<AtomicBool>,
+    /// A thread is emitting `monitor-level` events for the settings microphone check
+    pub monitor_emitter_running: Arc<AtomicBool>,
+    /// Current recording is incognito: its result is injected but not persisted
+    pub incognito_session: AtomicBool,
+    /// Transcription language chosen for the current recording at its start
+    /// (hotkey override or keyboard layout); None = use the settings
+    pub session_language: Mutex<Option<String>>,
+    /// Post-processing profile of the current (or last) recording, kept until
+    /// its text has been injected
+    pub session_profile: Mutex<text::Profile>,
+    /// App that was frontmost when the current (or last) recording started,
+    /// for dictionary entries scoped to apps
+    pub session_app: Mutex<Option<String>>,
+    /// Cleared to stop the current recording's chunk finalizer, if it has one
+    pub chunk_monitor_running: Mutex<Option<Arc<AtomicBool>>>,
+    /// Utterances waiting for the paste-next hotkey (`insert_method = "queue"`)
+    pub paste_queue: Mutex<injection::queue::PasteQueue>,
+    /// The next recording uses `transcription.boost_model` (`set_accuracy_boost`)
+    pub boost_armed: AtomicBool,
+    /// Whisper model the current recording uses instead of `model_size`,
+    /// when it was boosted
+    pub session_model: Mutex<Option<String>>,
+}
+
+/// Text the streaming engine has produced so far in the current recording.
+fn streaming_text(engine: transcription::Engine) -> String {
+    engine.backend().streaming_text()
+}
+
+/// Language for a dictation started with hotkey `key`: its per-hotkey override,
+/// else the keyboard layout's language when enabled, else None (use settings).
+fn resolve_session_language(settings: &settings::UserSettings, key: Option<&str>) -> Option<String> {
+    let overridden = key.and_then(|key| {
+        settings
+            .hotkey
+            .languages
+            .iter()
+            .find(|(k, _)| hotkey::accelerator::same(k, key))
+            .map(|(_, lang)| lang.clone())
+    });
+    if overridden.is_some() {
+        return overridden;
+    }
+    if settings.transcription.language_from_keyboard.unwrap_or(false) {
+        let Some(language) = hotkey::layout::current_language() else {
+            diag!(Engine, "[recording] Keyboard layout language unavailable, using configured language");
+            return None;
+        };
+        let engine = transcription::Engine::from_settings(settings);
+        let supported = match engine {
+            // The ".en" models only know English
+            transcription::Engine::Whisper => transcription::whisper::supported_languages(&configured_model_id(settings))
+                .into_iter()
+                .map(|l| l.code)
+                .collect(),
+            _ => engine.capabilities().languages,
+        };
+        if !supported.is_empty() && !supported.contains(&language) {
+            diag!(Engine,
+                "[recording] Keyboard layout language '{}' not supported by {}, auto-detecting",
+                language,
+                configured_model_id(settings)
+            );
+            return Some("auto".to_string());
+        }
+        return Some(language);
+    }
+    None
+}
+
+/// Profile for a dictation started with hotkey `key`: code when it's the code
+/// mode hotkey or the frontmost app `app_id` is listed in `output.code_apps`.
+fn resolve_session_profile(
+    settings: &settings::UserSettings,
+    key: Option<&str>,
+    app_id: Option<&str>,
+) -> text::Profile {
+    let code_key = settings.hotkey.code_key.as_deref().filter(|k| !k.is_empty());
+    if let (Some(key), Some(code_key)) = (key, code_key) {
+        if hotkey::accelerator::same(key, code_key) {
+            return text::Profile::Code;
+        }
+    }
+    pipeline::profile_for_app(settings, app_id)
+}
+
+/// What a dictation with `settings` would send off the machine, as the
+/// consent prompt names it, or None if nothing.
+pub(crate) fn planned_uploads(settings: &settings::UserSettings) -> Option<String> {
+    let engine = transcription::Engine::from_settings(settings);
+    let engine_upload = engine
+        .uploads_audio(settings)
+        .then(|| engine.upload_description(settings));
+    // Without `privacy.allow_cloud` cleanup keeps the engine's text rather than refusing to record
+    let cleanup_upload = cleanup::upload_description(&settings.cleanup).filter(|_| settings.privacy.allow_cloud);
+    let uploads: Vec<String> = engine_upload.into_iter().chain(cleanup_upload).collect();
+    (!uploads.is_empty()).then(|| uploads.join(" and "))
 }
 
 #[tauri::command]
-fn start_recording(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    eprintln!("[recording] start_recording called");
+fn start_recording(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    incognito: Option<bool>,
+    hotkey: Option<String>,
+) -> Result<(), AppError> {
+    diag!(Engine, "[recording] start_recording called");
 
-    let mut is_recording = state.is_recording.lock().map_err(|e| e.to_string())?;
+    let mut is_recording = state.is_recording.lock_recover();
     if *is_recording {
-        eprintln!("[recording] WARNING: already recording");
-        return Err("Already recording".to_string());
+        log::warn!("[recording] already recording");
+        return Err(AppError::Busy("Already recording".to_string()));
+    }
+
+    // Nothing is captured unless the engine and cleanup may send it where they would
+    {
+        let settings = state.settings.lock_recover();
+        if let Some(what) = planned_uploads(&settings) {
+            if let Err(e) = privacy::gate_recording(&settings.privacy, &what) {
+                log::warn!("[recording] {}", e);
+                if matches!(e, privacy::PrivacyError::ConsentRequired(_)) {
+                    // A held key has been let go by the time the user answers
+                    let hold = hotkey.is_some() && hotkey::gate::mode() == hotkey::gate::HotkeyMode::Hold;
+                    app.emit(
+                        "cloud-consent-required",
+                        serde_json::json!({
+                            "what": what,
+                            "incognito": incognito.unwrap_or(false),
+                            "hotkey": hotkey,
+                            "hold": hold,
+                        }),
+                    )
+                    .ok();
+                }
+                return Err(e.into());
+            }
+        }
     }
     *is_recording = true;
 
     // Start audio capture
-    eprintln!("[recording] Starting audio capture...");
+    diag!(Engine, "[recording] Starting audio capture...");
     if let Err(e) = audio::capture::start_capture() {
-        eprintln!("[recording] ERROR: Failed to start audio capture: {}", e);
+        log::error!("[recording] Failed to start audio capture: {}", e);
         // Reset state on failure
         *is_recording = false;
-        return Err(e.to_string());
+        return Err(e.into());
     }
-    eprintln!("[recording] Audio capture started successfully");
+    diag!(Engine, "[recording] Audio capture started successfully");
+    privacy::begin_dictation();
+    events::reset_partial();
 
     // Start streaming transcription in background.
     // Dispatches to Voxtral (native streaming) or Whisper (VAD-triggered) based on engine setting.
     {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let settings = state.settings.lock_recover();
+
+        let incognito = incognito.unwrap_or(false) || settings.privacy.incognito;
+        state.incognito_session.store(incognito, Ordering::SeqCst);
+        if incognito {
+            diag!(Engine, "[recording] Incognito session: result will not be saved to history or stats");
+        }
 
-        if is_voxtral_engine(&settings) {
+        let session_language = resolve_session_language(&settings, hotkey.as_deref());
+        if let Some(ref language) = session_language {
+            diag!(Engine, "[recording] Transcription language for this session: {}", language);
+        }
+        *state.session_language.lock_recover() = session_language.clone();
+
+        let app_id = injection::detect::frontmost_app_id();
+        let profile = resolve_session_profile(&settings, hotkey.as_deref(), app_id.as_deref());
+        if profile == text::Profile::Code {
+            diag!(Engine, "[recording] Code mode: spoken symbols, no casing or punctuation restoration");
+        }
+        *state.session_profile.lock_recover() = profile;
+        *state.session_app.lock_recover() = app_id;
+
+        // An armed accuracy boost is used up by this recording
+        let session_model = if state.boost_armed.swap(false, Ordering::SeqCst) {
+            match boost_model(&settings) {
+                Ok(model) => {
+                    diag!(Engine, "[recording] Accuracy boost: transcribing with '{}'", model);
+                    emit_accuracy_boost(&app, "active", Some(&model));
+                    Some(model)
+                }
+                Err(e) => {
+                    log::warn!("[recording] Accuracy boost skipped: {}", e);
+                    emit_accuracy_boost(&app, "off", None);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        *state.session_model.lock_recover() = session_model.clone();
+
+        // Lazy preload policies load the model now, while the user is speaking
+        // (a boosted recording loads its own model instead)
+        let lazy_preload = preload_policy(&settings) != "always";
+        if lazy_preload && session_model.is_none() && !is_configured_model_loaded(&settings) {
+            spawn_model_preload(app.clone(), &settings, "first use");
+        }
+
+        let engine = transcription::Engine::from_settings(&settings);
+        if engine == transcription::Engine::Voxtral {
             #[cfg(feature = "voxtral")]
             {
-                let delay_ms = settings.transcription.voxtral_delay_ms.unwrap_or(480);
-                transcription::voxtral::start_streaming(transcription::voxtral::StreamingConfig {
-                    delay_ms,
-                }).map_err(|e| {
-                    eprintln!("[recording] ERROR: Voxtral streaming start failed: {}", e);
-                    // Reset recording state since we failed
-                    *is_recording = false;
-                    e.to_string()
-                })?;
+                if lazy_preload && !transcription::voxtral::get_status().model_loaded {
+                    // Streaming needs a loaded model; stop falls back to one-shot
+                    // transcription, which waits for the background load
+                    diag!(Engine, "[recording] Voxtral still loading, streaming disabled for this recording");
+                } else {
+                    let delay_ms = settings.transcription.voxtral_delay_ms.unwrap_or(480);
+                    transcription::voxtral::start_streaming(transcription::voxtral::StreamingConfig {
+                        delay_ms,
+                    }).map_err(|e| {
+                        log::error!("[recording] Voxtral streaming start failed: {}", e);
+                        // Reset recording state since we failed
+                        *is_recording = false;
+                        e
+                    })?;
+                }
             }
             #[cfg(not(feature = "voxtral"))]
             {
                 *is_recording = false;
-                return Err("Voxtral engine not available (not compiled)".to_string());
+                return Err(AppError::Unsupported("Voxtral engine not available (not compiled)".to_string()));
             }
+        } else if engine == transcription::Engine::Parakeet {
+            #[cfg(feature = "parakeet")]
+            if lazy_preload && !transcription::parakeet::is_model_loaded() {
+                // The whole recording is transcribed on stop instead
+                diag!(Engine, "[recording] Parakeet still loading, streaming disabled for this recording");
+            } else if let Err(e) = transcription::parakeet::start_streaming() {
+                log::warn!("[recording] Parakeet streaming start failed: {}", e);
+            }
+        } else if engine == transcription::Engine::Apple {
+            // Without the permission or a recognizer for the language the
+            // stop would fail too, so refuse to record at all
+            #[cfg(target_os = "macos")]
+            if let Err(e) = transcription::apple::start_streaming(&settings, session_language.as_deref()) {
+                log::error!("[recording] Apple Speech streaming start failed: {}", e);
+                audio::capture::stop_capture().ok();
+                *is_recording = false;
+                return Err(e.into());
+            }
+        } else if engine == transcription::Engine::Sapi {
+            diag!(Engine, "[recording] Windows Speech transcribes on stop, no streaming");
+        } else if engine == transcription::Engine::Cloud {
+            diag!(Engine, "[recording] Cloud transcription runs on stop, no streaming");
         } else {
-            let model_size = settings
-                .transcription
-                .model_size
-                .clone()
+            let model_size = session_model
+                .or_else(|| settings.transcription.model_size.clone())
                 .unwrap_or_else(|| "small".to_string());
-            let language = settings.transcription.language.clone();
+            let language = session_language.or_else(|| settings.transcription.language.clone());
             transcription::whisper::start_streaming(transcription::whisper::StreamingConfig {
                 model_size,
                 language,
@@ -197,257 +421,472 @@ fn start_recording(app: tauri::AppHandle, state: tauri::State<'_, AppState>) ->
         }
     }
 
-    // Start audio level emitter
+    // Start audio level emitter, which also forwards the streamed text of
+    // engines that don't send it themselves as `transcription-partial`, and
+    // emits `recording-status` once a second
     let running = state.audio_level_emitter_running.clone();
     running.store(true, Ordering::SeqCst);
+    let engine = transcription::Engine::from_settings(&state.settings.lock_recover());
+    let polls_partial = !matches!(engine, transcription::Engine::Whisper | transcription::Engine::Voxtral);
 
     let app_clone = app.clone();
     std::thread::spawn(move || {
+        let interval = events::interval();
+        let frames_per_second = (1000 / interval.as_millis().max(1)) as u32;
         let mut frame_count = 0u32;
+        let started = std::time::Instant::now();
+        let mut status_at = started;
         while running.load(Ordering::SeqCst) {
             let level = audio::capture::get_current_level();
             app_clone.emit("audio-level", level).ok();
 
-            // Log every 40 frames (~1 second) to avoid spam
+            if polls_partial {
+                events::partial(streaming_text(engine));
+            }
+
+            if status_at.elapsed() >= std::time::Duration::from_secs(1) {
+                status_at = std::time::Instant::now();
+                let status = status::RecordingStatus::current(started.elapsed(), engine);
+                app_clone.emit("recording-status", &status).ok();
+            }
+
+            // Log about once a second to avoid spam
             frame_count += 1;
-            if frame_count % 40 == 0 {
+            if frame_count % frames_per_second.max(1) == 0 {
                 log::info!("Emitting audio level: {:.4}", level);
             }
 
-            std::thread::sleep(std::time::Duration::from_millis(AUDIO_LEVEL_SLEEP_MS));
+            std::thread::sleep(interval);
         }
         log::info!("Audio level emitter stopped");
     });
 
+    let chunk_minutes = state.settings.lock_recover().recording.chunk_minutes;
+    if let Some(minutes) = chunk_minutes.filter(|m| *m > 0) {
+        let running = Arc::new(AtomicBool::new(true));
+        *state.chunk_monitor_running.lock_recover() = Some(running.clone());
+        spawn_chunk_monitor(app.clone(), running, minutes, engine);
+    }
+
+    let recording = state.settings.lock_recover().recording.clone();
+    audio::watchdog::start(
+        app.clone(),
+        audio::watchdog::WatchdogConfig {
+            restart: recording.restart_on_stall.unwrap_or(false),
+            fallback_device: recording.fallback_device,
+        },
+    );
+
+    let indicator = state.settings.lock_recover().widget.recording_indicator.clone();
+    if let Some(style) = indicator::Style::from_setting(indicator.as_deref()) {
+        indicator::show(&app, style);
+    }
+
     Ok(())
 }
 
+/// Finalize a long recording every `minutes` while it continues, until `running` is cleared.
+fn spawn_chunk_monitor(
+    app: tauri::AppHandle,
+    running: Arc<AtomicBool>,
+    minutes: u32,
+    engine: transcription::Engine,
+) {
+    let every = std::time::Duration::from_secs(minutes as u64 * 60);
+    diag!(Engine, "[recording] Long dictations are finalized every {} min", minutes);
+    std::thread::spawn(move || {
+        let mut last = std::time::Instant::now();
+        while running.load(Ordering::SeqCst) {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            if last.elapsed() < every {
+                continue;
+            }
+            last = std::time::Instant::now();
+
+            let state = app.state::<AppState>();
+            let mut settings = state.settings.lock_recover().clone();
+            if let Some(language) = state.session_language.lock().ok().and_then(|l| l.clone()) {
+                settings.transcription.language = Some(language);
+            }
+            if let Some(model) = state.session_model.lock().ok().and_then(|m| m.clone()) {
+                settings.transcription.model_size = Some(model);
+            }
+            let profile = state.session_profile.lock().map(|p| *p).unwrap_or_default();
+            let app_id = state.session_app.lock().ok().and_then(|a| a.clone());
+            let incognito = state.incognito_session.load(Ordering::SeqCst);
+            // A chunk that panics is lost, but the recording keeps being finalized
+            panics::catch("Chunk finalizer", || {
+                pipeline::finalize_chunk(&app, &running, engine, &settings, profile, app_id.as_deref(), incognito);
+            });
+        }
+        log::info!("Chunk finalizer stopped");
+    });
+}
+
+/// Stop the current recording's chunk finalizer; a chunk in flight still completes.
+fn stop_chunk_monitor(state: &AppState) {
+    if let Some(running) = state.chunk_monitor_running.lock().ok().and_then(|mut r| r.take()) {
+        running.store(false, Ordering::SeqCst);
+    }
+}
+
 #[tauri::command]
 async fn stop_recording(
     app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
-    eprintln!("[recording] stop_recording called");
+) -> Result<String, AppError> {
+    diag!(Engine, "[recording] stop_recording called");
+    let stop_start = std::time::Instant::now();
 
     // Stop audio level emitter first
     state.audio_level_emitter_running.store(false, Ordering::SeqCst);
+    stop_chunk_monitor(&state);
+    audio::watchdog::stop();
 
     // Get recording state and settings before any await
     let was_recording = {
-        let mut is_recording = state.is_recording.lock().map_err(|e| e.to_string())?;
+        let mut is_recording = state.is_recording.lock_recover();
         if !*is_recording {
-            eprintln!("[recording] WARNING: not currently recording");
-            return Err("Not recording".to_string());
+            log::warn!("[recording] not currently recording");
+            return Err(AppError::Busy("Not recording".to_string()));
         }
         *is_recording = false;
         true
     };
+    indicator::hide(&app);
 
     if !was_recording {
-        return Err("Not recording".to_string());
+        return Err(AppError::Busy("Not recording".to_string()));
     }
+    let boosted = state.session_model.lock_recover().take();
 
-    // Stop streaming monitor first (ensures all in-progress transcriptions complete
-    // before we stop capture). Returns accumulated results and consumed sample count.
-    let use_voxtral = {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
-        is_voxtral_engine(&settings)
+    let (engine, audio_uploaded, min_duration_ms) = {
+        let settings = state.settings.lock_recover();
+        let engine = transcription::Engine::from_settings(&settings);
+        (
+            engine,
+            engine.uploads_audio(&settings),
+            // Off unless set: short answers ("yes", "ok") are dictations too
+            settings.recording.min_duration_ms.unwrap_or(0),
+        )
+    };
+    let mut report = pipeline::PipelineReport::default();
+
+    let start = std::time::Instant::now();
+    let finalized = pipeline::finalize_audio(
+        engine,
+        audio_uploaded,
+        min_duration_ms,
+        state.incognito_session.load(Ordering::SeqCst),
+    );
+    let captured = match report.record("finalize-audio", start, finalized) {
+        Ok(pipeline::Finalized::Captured(captured)) => captured,
+        Ok(pipeline::Finalized::TooShort(short)) => {
+            end_accuracy_boost(&app, &state, boosted);
+            return Ok(skip_too_short(&app, &state, short));
+        }
+        Err(e) => {
+            end_accuracy_boost(&app, &state, boosted);
+            return Err(report.fail(&app, e));
+        }
     };
 
-    eprintln!("[recording] Stopping streaming monitor (engine={})...", if use_voxtral { "voxtral" } else { "whisper" });
+    // Emit processing event
+    app.emit("transcription-processing", ()).ok();
 
-    let (streaming_results, consumed_samples) = if use_voxtral {
-        #[cfg(feature = "voxtral")]
-        { transcription::voxtral::stop_streaming() }
-        #[cfg(not(feature = "voxtral"))]
-        { (Vec::new(), 0usize) }
-    } else {
-        transcription::whisper::stop_streaming()
+    // Clone settings for use in async block, with the language (and boosted
+    // model) chosen at start
+    let settings = {
+        let mut s = state.settings.lock_recover().clone();
+        if let Some(language) = state.session_language.lock_recover().take() {
+            s.transcription.language = Some(language);
+        }
+        if let Some(ref model) = boosted {
+            s.transcription.model_size = Some(model.clone());
+        }
+        s
     };
-
-    let streaming_prefix = if streaming_results.is_empty() {
-        eprintln!("[recording] No streaming results (no completed utterances detected)");
-        None
-    } else {
-        // Voxtral tokens include their own spacing (e.g. " Hello," " world.").
-        // Whisper segments are separate sentences that need a space between them.
-        let prefix = if use_voxtral {
-            streaming_results.join("")
-        } else {
-            streaming_results.join(" ")
-        };
-        eprintln!(
-            "[recording] Streaming results: {} segments, {} consumed samples, prefix='{}...'",
-            streaming_results.len(),
-            consumed_samples,
-            if prefix.len() > 60 { &prefix[..60] } else { &prefix }
-        );
-        Some(prefix)
+    let stop_ms = stop_start.elapsed().as_millis() as u64;
+    let profile = *state.session_profile.lock_recover();
+    let app_id = state.session_app.lock_recover().clone();
+    let prefix = pipeline::deliver_prefix(&app, &captured, &settings, profile, app_id.as_deref()).await;
+
+    let dropouts = captured.audio.dropouts;
+    let start = std::time::Instant::now();
+    let transcript = pipeline::transcribe_tail(
+        captured.audio,
+        &captured.streaming_segments,
+        captured.streamed_all,
+        engine,
+        &settings,
+    )
+    .await;
+    end_accuracy_boost(&app, &state, boosted);
+    let transcript = match report.record("transcribe-tail", start, transcript) {
+        Ok(transcript) => transcript,
+        Err(e) => return Err(report.fail(&app, e)),
     };
+    if let Some(short) = captured.short {
+        if text::symbols::punctuation_only(&transcript.raw_text).is_none() {
+            return Ok(skip_too_short(&app, &state, short));
+        }
+    }
 
-    // Stop audio capture and get audio data
-    eprintln!("[recording] Stopping audio capture...");
-    let mut audio_data = audio::capture::stop_capture().map_err(|e| {
-        eprintln!("[recording] ERROR: Failed to stop audio capture: {}", e);
-        e.to_string()
-    })?;
-    eprintln!(
-        "[recording] Audio captured: {} samples at {}Hz ({:.2}s)",
-        audio_data.samples.len(),
-        audio_data.sample_rate,
-        audio_data.samples.len() as f32 / audio_data.sample_rate as f32
+    let start = std::time::Instant::now();
+    let segments = pipeline::merge(
+        captured.streaming_segments,
+        transcript.tail_segment,
+        captured.streamed_all,
+        captured.duration_ms,
     );
-
-    // Trim whisper_samples to only the tail (audio not yet transcribed by streaming).
-    // This dramatically reduces inference time on stop — only the final partial utterance
-    // needs processing instead of the entire recording.
-    if consumed_samples > 0 {
-        if let Some(ref mut ws) = audio_data.whisper_samples {
-            if consumed_samples < ws.len() {
-                let tail_len = ws.len() - consumed_samples;
-                eprintln!(
-                    "[recording] Trimming whisper buffer: {} total -> {} tail ({:.2}s)",
-                    ws.len(),
-                    tail_len,
-                    tail_len as f32 / 16000.0
-                );
-                *ws = ws[consumed_samples..].to_vec();
-            } else {
-                eprintln!(
-                    "[recording] All audio consumed by streaming ({} >= {}), no tail",
-                    consumed_samples,
-                    ws.len()
-                );
-                *ws = Vec::new();
-            }
+    report.time("merge", start);
+
+    let (cleaned, cleanup_sent) = pipeline::cleanup(&app, &transcript.raw_text, &settings, profile, &mut report).await;
+
+    let start = std::time::Instant::now();
+    let (text, dictionary_hits) = pipeline::post_process_timed(
+        &cleaned,
+        &settings,
+        profile,
+        app_id.as_deref(),
+        &mut report,
+    )
+    .await;
+    // With the prefix already injected, only the rest is returned for injection
+    let injected = match prefix {
+        Some(ref prefix) => {
+            // The processed tail is only needed when the text diverged from the prefix
+            let tail = match segments.last().filter(|s| s.source == "tail") {
+                Some(tail) if !text.starts_with(prefix.as_str()) => {
+                    pipeline::post_process_async(&tail.text, &settings, profile, app_id.as_deref()).await
+                }
+                _ => String::new(),
+            };
+            pipeline::remainder_after(&text, prefix, || tail)
         }
+        None => text.clone(),
+    };
+    report.time("post-process", start);
+
+    let timings = latency::TranscriptionTimings {
+        dropouts: dropouts.count,
+        concealed_ms: dropouts.concealed_ms,
+        ..latency::TranscriptionTimings::new(
+            stop_ms,
+            report.duration_ms("transcribe-tail"),
+            transcript.engine_timings,
+            report.duration_ms("post-process"),
+            captured.tail_audio_secs,
+        )
+    };
+    let dictation = pipeline::Dictation {
+        word_count: text.split_whitespace().count() as u32,
+        raw_text: transcript.raw_text,
+        text,
+        duration_ms: captured.duration_ms,
+        segments,
+        timings,
+        dictionary_hits,
+        sent_to_third_party: audio_uploaded || cleanup_sent,
+        language: settings.transcription.language.clone(),
+    };
+
+    // Persisting is fire and forget: a failure is reported but doesn't fail the
+    // transcription. Incognito sessions are never persisted, so they can't be synced either.
+    let start = std::time::Instant::now();
+    let incognito = state.incognito_session.swap(false, Ordering::SeqCst);
+    let entry_id = report
+        .record("persist", start, pipeline::persist(&app, &dictation, incognito))
+        .unwrap_or(None);
+    if !incognito {
+        emit_goal_reached(&app, &settings.goals);
     }
 
-    // Emit processing event
-    app.emit("transcription-processing", ()).ok();
+    let start = std::time::Instant::now();
+    pipeline::output(&app, &dictation, &injected, entry_id);
+    report.time("output", start);
+    app.emit("pipeline-report", &report).ok();
 
-    // Clone settings for use in async block
-    let settings = {
-        let s = state.settings.lock().map_err(|e| e.to_string())?;
-        s.clone()
+    Ok(injected)
+}
+
+#[tauri::command]
+fn inject_text(
+    app: tauri::AppHandle,
+    text: String,
+    continuation: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), AppError> {
+    let mut settings = state.settings.lock_recover().clone();
+    if *state.session_profile.lock_recover() == text::Profile::Code {
+        // Don't lowercase the first word to continue a sentence
+        settings.output.auto_capitalize = Some(false);
+        settings.output.casing = None;
+    }
+    let inject_start = std::time::Instant::now();
+    let result = if settings.output.insert_method.as_deref() == Some("queue") {
+        queue_text(&app, &state, &text)
+    } else {
+        injection::inject_text(&text, &settings, continuation.unwrap_or(false))
+            .map_err(AppError::from)
     };
 
-    // Calculate duration before moving audio_data into transcribe
-    let duration_ms = (audio_data.samples.len() as f32 / audio_data.sample_rate as f32 * 1000.0) as u32;
-
-    // Transcribe remaining tail audio and combine with streaming prefix.
-    // Voxtral streaming processes ALL audio incrementally (including finish()),
-    // so when consumed_samples == usize::MAX we skip tail transcription entirely —
-    // the streaming results ARE the final transcription.
-    let raw_text = if use_voxtral && consumed_samples == usize::MAX {
-        // Voxtral streaming already processed everything. No tail needed.
-        let text = streaming_prefix.unwrap_or_default();
-        eprintln!(
-            "[recording] Voxtral streaming handled all audio, skipping tail transcription (text='{}')",
-            if text.len() > 60 { &text[..60] } else { &text }
-        );
-        text
-    } else if use_voxtral {
-        // Voxtral streaming wasn't active (model not loaded), try one-shot
-        eprintln!("[recording] Starting voxtral one-shot transcription...");
-        #[cfg(feature = "voxtral")]
-        {
-            transcription::voxtral::transcribe(audio_data, &settings, streaming_prefix)
-                .await
-                .map_err(|e| {
-                    eprintln!("[recording] ERROR: Voxtral transcription failed: {}", e);
-                    e.to_string()
-                })?
+    // Complete the latency breakdown of the transcription that produced this text
+    if let Some((entry_id, mut timings)) = latency::take_pending() {
+        timings.set_injection(inject_start.elapsed().as_millis() as u64);
+        if let Some(reason) = timings.slow_reason.as_deref() {
+            log::info!("Slow transcription ({}ms): {}", timings.total_ms, reason);
         }
-        #[cfg(not(feature = "voxtral"))]
-        {
-            streaming_prefix.unwrap_or_default()
+        if let Some(id) = entry_id {
+            if let Err(e) = history::set_timings(&id, &timings) {
+                log::warn!("Failed to save timings to history: {}", e);
+            }
         }
-    } else {
-        transcription::whisper::transcribe(audio_data, &settings, streaming_prefix)
-            .await
-            .map_err(|e| {
-                eprintln!("[recording] ERROR: Transcription failed: {}", e);
-                e.to_string()
-            })?
-    };
-    eprintln!(
-        "[recording] Transcription complete: '{}' ({} chars)",
-        if raw_text.len() > 100 {
-            format!("{}...", &raw_text[..100])
-        } else {
-            raw_text.clone()
-        },
-        raw_text.len()
-    );
+        latency::set_last(&timings);
+        app.emit("transcription-timings", &timings).ok();
+    }
 
-    // Apply auto-capitalize if enabled
-    let auto_capitalize = settings.output.auto_capitalize.unwrap_or(true);
-    let mut text = text::process_text(&raw_text, auto_capitalize);
+    result
+}
+
+/// Queue mode: add the utterances in `text` to the paste queue instead of
+/// inserting it.
+fn queue_text(app: &tauri::AppHandle, state: &AppState, text: &str) -> Result<(), AppError> {
+    let mut queue = state.paste_queue.lock_recover();
+    let added = queue.push_text(text);
+    diag!(Injection, "[queue] Queued {} utterances, {} waiting", added, queue.len());
+    app.emit("paste-queue-changed", queue.items()).ok();
+    Ok(())
+}
 
-    // Apply dictionary replacements
-    if let Ok(replaced) = dictionary::apply_replacements(&text) {
-        text = replaced;
+/// Insert the oldest queued utterance at the cursor. Called from the
+/// paste-next hotkey.
+pub(crate) fn paste_next_queued(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(settings) = state.settings.lock().map(|s| s.clone()) else { return };
+    let (next, items) = {
+        let Ok(mut queue) = state.paste_queue.lock() else { return };
+        let next = queue.pop();
+        (next, queue.items())
+    };
+    let Some(text) = next else {
+        diag!(Injection, "[queue] Paste-next pressed with an empty queue");
+        return;
+    };
+    app.emit("paste-queue-changed", &items).ok();
+
+    // Each item goes into its own field, so it's pasted as is
+    let mut settings = settings;
+    settings.output.insert_method = Some("paste_restore".to_string());
+    if let Err(e) = injection::inject_text(&text, &settings, false) {
+        log::error!("[queue] Failed to paste queued text: {}", e);
+        app.emit("paste-queue-failed", e.to_string()).ok();
     }
+}
 
-    // Calculate stats for recording
-    let word_count = text.split_whitespace().count() as u32;
+/// Utterances waiting in the paste queue, oldest first
+#[tauri::command]
+fn get_paste_queue(state: tauri::State<'_, AppState>) -> Result<Vec<String>, AppError> {
+    Ok(state.paste_queue.lock_recover().items())
+}
 
-    // Record to local history and stats (fire and forget, don't fail transcription)
-    if let Err(e) = history::add_entry(&text, word_count, duration_ms) {
-        eprintln!("[recording] WARNING: Failed to save to history: {}", e);
-    }
-    if let Err(e) = stats::record_transcription(word_count, duration_ms) {
-        eprintln!("[recording] WARNING: Failed to record stats: {}", e);
-    }
+/// Paste the next queued utterance, as the paste-next hotkey does
+#[tauri::command]
+fn paste_next(app: tauri::AppHandle) {
+    std::thread::spawn(move || paste_next_queued(&app));
+}
+
+#[tauri::command]
+fn clear_paste_queue(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), AppError> {
+    state.paste_queue.lock_recover().clear();
+    app.emit("paste-queue-changed", Vec::<String>::new()).ok();
+    Ok(())
+}
+
+/// App id (bundle id / executable name) -> insert method learned in auto mode
+#[tauri::command]
+fn get_learned_insert_methods() -> std::collections::BTreeMap<String, String> {
+    injection::learned::all()
+}
 
-    // Emit completion event
-    app.emit("transcription-complete", &text).ok();
+/// Forget the learned insert method for one app, or for all apps when `app_id` is omitted
+#[tauri::command]
+fn forget_learned_insert_method(app_id: Option<String>) {
+    injection::learned::forget(app_id.as_deref());
+}
 
-    Ok(text)
+/// Make auto mode start at `method` for `app_id`, e.g. after a benchmark.
+/// Methods that aren't an auto mode tier on this platform are ignored.
+#[tauri::command]
+fn set_learned_insert_method(app_id: String, method: String) {
+    injection::learned::remember(&app_id, &method);
 }
 
+/// Insert a test string into the focused field with each insert method,
+/// timing each and reading the field back where possible. Waits `delay_ms`
+/// (default 3000) first so the user can focus the field in the target app.
 #[tauri::command]
-fn inject_text(text: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let settings = state.settings.lock().map_err(|e| e.to_string())?;
-    injection::inject_text(&text, &settings).map_err(|e| e.to_string())
+async fn benchmark_injection(
+    text: Option<String>,
+    delay_ms: Option<u64>,
+) -> Result<injection::benchmark::InjectionBenchmark, AppError> {
+    tokio::time::sleep(std::time::Duration::from_millis(delay_ms.unwrap_or(3000))).await;
+    tokio::task::spawn_blocking(move || injection::benchmark::run(text.as_deref()))
+        .await?
+        .map_err(AppError::from)
 }
 
 /// Reset recording state - used to recover from stuck states
 #[tauri::command]
-fn reset_recording_state(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    eprintln!("[recording] reset_recording_state called");
+fn reset_recording_state(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), AppError> {
+    diag!(Engine, "[recording] reset_recording_state called");
 
     // Reset backend recording flag
-    let mut is_recording = state.is_recording.lock().map_err(|e| e.to_string())?;
+    let mut is_recording = state.is_recording.lock_recover();
     *is_recording = false;
 
     // Stop audio level emitter
     state.audio_level_emitter_running.store(false, Ordering::SeqCst);
+    stop_chunk_monitor(&state);
+    audio::watchdog::stop();
+    state.incognito_session.store(false, Ordering::SeqCst);
+    indicator::hide(&app);
 
     // Reset audio capture state
     audio::capture::reset_state();
+    privacy::end_dictation(None, false, false);
 
-    eprintln!("[recording] Recording state reset complete");
+    diag!(Engine, "[recording] Recording state reset complete");
     Ok(())
 }
 
 #[tauri::command]
-fn get_settings(state: tauri::State<'_, AppState>) -> Result<settings::UserSettings, String> {
-    let settings = state.settings.lock().map_err(|e| e.to_string())?;
+fn get_settings(state: tauri::State<'_, AppState>) -> Result<settings::UserSettings, AppError> {
+    let settings = state.settings.lock_recover();
     Ok(settings.clone())
 }
 
 #[tauri::command]
 fn update_settings(
     app: tauri::AppHandle,
-    new_settings: settings::UserSettings,
+    mut new_settings: settings::UserSettings,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    validate_transcription_language(&new_settings)?;
+
     let (old_hotkey, old_draggable, old_opacity, old_model_size, old_engine) = {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        let settings = state.settings.lock_recover();
         (
-            settings.hotkey.key.clone(),
+            (
+                settings.hotkey.key.clone(),
+                settings.hotkey.incognito_key.clone(),
+                settings.hotkey.code_key.clone(),
+                settings.hotkey.paste_next_key.clone(),
+                settings.hotkey.boost_key.clone(),
+            ),
             settings.widget.draggable,
             settings.widget.opacity,
             settings.transcription.model_size.clone(),
@@ -457,30 +896,79 @@ fn update_settings(
 
     let new_draggable = new_settings.widget.draggable;
     if old_draggable != new_draggable {
-        eprintln!("[settings] DRAGGABLE CHANGED: {} -> {}", old_draggable, new_draggable);
+        diag!(Window, "[settings] DRAGGABLE CHANGED: {} -> {}", old_draggable, new_draggable);
 
         // When draggable is turned OFF, snap widget back to bottom-center of current screen
         #[cfg(target_os = "macos")]
         if !new_draggable {
-            eprintln!("[settings] Snapping widget to bottom-center (draggable OFF)");
+            diag!(Window, "[settings] Snapping widget to bottom-center (draggable OFF)");
             native_position_on_cursor_monitor(&app, false).ok();
         }
     }
 
     let new_opacity = new_settings.widget.opacity;
 
-    let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    // Entered API keys go to the keychain, never to settings.json
+    settings::secrets::secure_api_keys(&mut new_settings);
+
+    let mut settings = state.settings.lock_recover();
+    // The proxy password never comes from the frontend; set_proxy_password keeps it
+    new_settings.proxy.password = settings.proxy.password.clone();
     *settings = new_settings.clone();
 
     // Persist settings
-    settings::save_settings(&new_settings).map_err(|e| e.to_string())?;
+    settings::save_settings(&new_settings)?;
+
+    // Apply proxy changes to subsequent HTTP requests
+    net::configure(&new_settings.proxy);
+    net::set_mirrors(&new_settings.download.mirrors);
+    net::download::set_connections(new_settings.download.connections);
+    transcription::integrity::set_verify_on_load(
+        new_settings.transcription.verify_models_on_load.unwrap_or(false),
+    );
+    transcription::hallucination::configure(&new_settings.transcription.hallucination_guard);
+    transcription::whisper::set_coreml_preferences(
+        new_settings.transcription.use_coreml,
+        &new_settings.transcription.coreml_models,
+    );
+    audio::preroll::configure(new_settings.recording.preroll_ms.unwrap_or(0));
+    audio::capture::set_standby(
+        new_settings.recording.standby,
+        new_settings.recording.standby_idle_minutes.unwrap_or(DEFAULT_STANDBY_IDLE_MINUTES),
+    );
+    audio::vad::set_backend(new_settings.recording.vad_backend.as_deref());
+    audio::capture::set_input_device(new_settings.recording.selected_input_device.clone());
+    audio::capture::set_resampler_quality(audio::resample::configured(
+        new_settings.recording.resampler_quality.as_deref(),
+        new_settings.recording.resampler_benchmark.as_ref(),
+    ));
+    hotkey::gate::set_mode(new_settings.hotkey.mode.as_deref(), new_settings.hotkey.long_press_ms);
+    events::configure(new_settings.widget.event_rate_hz);
+    diag::configure(&new_settings.debug);
+    history::set_session_idle_minutes(
+        new_settings.history.session_idle_minutes.unwrap_or(DEFAULT_SESSION_IDLE_MINUTES),
+    );
 
     // Re-register hotkey if it changed
-    if old_hotkey != new_settings.hotkey.key {
+    let new_hotkey = (
+        new_settings.hotkey.key.clone(),
+        new_settings.hotkey.incognito_key.clone(),
+        new_settings.hotkey.code_key.clone(),
+        new_settings.hotkey.paste_next_key.clone(),
+        new_settings.hotkey.boost_key.clone(),
+    );
+    // Safe mode leaves global hotkeys unregistered until the next normal start
+    if old_hotkey != new_hotkey && !safe_mode::is_active() {
         drop(settings); // Release lock before hotkey operations
-        hotkey::unregister_all(&app).map_err(|e| e.to_string())?;
-        hotkey::setup_hotkey(app.clone(), new_settings.hotkey.key.as_deref())
-            .map_err(|e| e.to_string())?;
+        hotkey::unregister_all(&app)?;
+        hotkey::setup_hotkey(
+            app.clone(),
+            new_settings.hotkey.key.as_deref(),
+            new_settings.hotkey.incognito_key.as_deref(),
+            new_settings.hotkey.code_key.as_deref(),
+            new_settings.hotkey.paste_next_key.as_deref(),
+            new_settings.hotkey.boost_key.as_deref(),
+        )?;
     }
 
     // Apply opacity change to NSPanel
@@ -491,6 +979,7 @@ fn update_settings(
 
     // Notify all windows (especially dictation) that settings changed
     app.emit("settings-changed", &new_settings).ok();
+    refresh_tray_menu(&app, &new_settings);
 
     // Handle engine switching — unload old engine to free GPU memory
     let new_engine = new_settings.transcription.engine.clone();
@@ -498,12 +987,31 @@ fn update_settings(
         let switching_to_voxtral = new_engine.as_deref() == Some("voxtral");
         log::info!("Engine changed: {:?} -> {:?}", old_engine, new_engine);
 
+        // Parakeet's model is only needed while it's the engine
+        #[cfg(feature = "parakeet")]
+        if transcription::Engine::from_settings(&new_settings) == transcription::Engine::Parakeet {
+            if preload_policy(&new_settings) != "always" {
+                emit_preload_deferred(&app, &new_settings);
+            } else if transcription::parakeet::is_model_downloaded() {
+                spawn_model_preload(app.clone(), &new_settings, "engine switch");
+            }
+        } else {
+            transcription::parakeet::unload_model();
+        }
+
+        #[cfg(target_os = "macos")]
+        if transcription::Engine::from_settings(&new_settings) == transcription::Engine::Apple {
+            prompt_apple_speech_permission();
+        }
+
         if switching_to_voxtral {
             // Unload Whisper to free GPU memory, preload Voxtral
             #[cfg(feature = "voxtral")]
             {
                 // Note: We don't have a whisper::unload_model() — the cache is replaced on next preload
-                if transcription::voxtral::is_model_downloaded() {
+                if transcription::voxtral::is_model_downloaded() && preload_policy(&new_settings) != "always" {
+                    emit_preload_deferred(&app, &new_settings);
+                } else if transcription::voxtral::is_model_downloaded() {
                     let preload_app = app.clone();
                     std::thread::spawn(move || {
                         log::info!("Switching to Voxtral, preloading...");
@@ -540,8 +1048,12 @@ fn update_settings(
 
     // Preload new Whisper model in background if model_size changed (and using Whisper engine)
     let new_model_size = new_settings.transcription.model_size.clone();
-    if old_model_size != new_model_size && !is_voxtral_engine(&new_settings) {
-        if let Some(model_size) = new_model_size {
+    if old_model_size != new_model_size
+        && transcription::Engine::from_settings(&new_settings) == transcription::Engine::Whisper
+    {
+        if preload_policy(&new_settings) != "always" {
+            emit_preload_deferred(&app, &new_settings);
+        } else if let Some(model_size) = new_model_size {
             let preload_app = app.clone();
             std::thread::spawn(move || {
                 log::info!("Model changed to '{}', preloading in background...", model_size);
@@ -581,31 +1093,208 @@ fn update_settings(
     Ok(())
 }
 
+/// End a recording under `recording.min_duration_ms` without output.
+fn skip_too_short(app: &tauri::AppHandle, state: &AppState, short: pipeline::TooShort) -> String {
+    diag!(Engine,
+        "[recording] Recording too short ({}ms < {}ms), skipping transcription",
+        short.captured_ms, short.min_duration_ms
+    );
+    state.incognito_session.store(false, Ordering::SeqCst);
+    app.emit(
+        "recording-too-short",
+        serde_json::json!({ "duration_ms": short.captured_ms, "min_duration_ms": short.min_duration_ms }),
+    )
+    .ok();
+    app.emit("transcription-complete", "").ok();
+    String::new()
+}
+
 #[tauri::command]
-async fn login(email: String, password: String) -> Result<api::AuthToken, String> {
+async fn login(email: String, password: String) -> Result<api::AuthToken, AppError> {
     api::client::login(&email, &password)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
+/// Store the API key for a cloud STT provider ("assemblyai") in the
+/// keychain; an empty key removes it.
 #[tauri::command]
-async fn download_model(app: tauri::AppHandle, size: String) -> Result<(), String> {
+fn set_cloud_api_key(provider: String, key: String) -> Result<(), AppError> {
+    settings::secrets::set(&settings::secrets::cloud_key_ref(&provider), key.trim()).map_err(AppError::from)
+}
+
+/// Store the manual proxy's password in the keychain; an empty password
+/// removes it. Applies from the next request.
+#[tauri::command]
+fn set_proxy_password(state: tauri::State<'_, AppState>, password: String) -> Result<(), AppError> {
+    settings::secrets::set(settings::secrets::PROXY_PASSWORD_REF, &password)?;
+    let mut settings = state.settings.lock_recover();
+    settings.proxy.password = Some(password).filter(|password| !password.is_empty());
+    net::configure(&settings.proxy);
+    Ok(())
+}
+
+#[tauri::command]
+fn has_cloud_api_key(provider: String) -> Result<bool, AppError> {
+    settings::secrets::try_get(&settings::secrets::cloud_key_ref(&provider))
+        .map(|key| key.is_some())
+        .map_err(AppError::from)
+}
+
+/// Emit `download-progress` for one model download.
+fn emit_download_progress(
+    app: &tauri::AppHandle,
+    model_type: &str,
+    model_id: &str,
+    progress: &net::DownloadProgress,
+    mirror: &str,
+) {
+    app.emit(
+        "download-progress",
+        serde_json::json!({
+            "model_type": model_type,
+            "model_id": model_id,
+            "percent": progress.percent,
+            "downloaded_bytes": progress.downloaded_bytes,
+            "total_bytes": progress.total_bytes,
+            "bytes_per_sec": progress.bytes_per_sec,
+            "eta_secs": progress.eta_secs,
+            "mirror": mirror,
+        }),
+    )
+    .ok();
+}
+
+#[tauri::command]
+async fn download_model(app: tauri::AppHandle, size: String) -> Result<(), AppError> {
     let app_clone = app.clone();
     let size_clone = size.clone();
-    transcription::whisper::download_model(&size, move |percent| {
-        app_clone
-            .emit(
-                "download-progress",
-                serde_json::json!({
-                    "model_type": "ggml",
-                    "model_id": &size_clone,
-                    "percent": percent,
-                }),
-            )
-            .ok();
+    transcription::whisper::download_model(&size, move |progress, mirror| {
+        emit_download_progress(&app_clone, "ggml", &size_clone, progress, mirror);
     })
     .await
-    .map_err(|e| e.to_string())
+    .map_err(|e| storage::write_app_error(&app, "model", e))?;
+    refresh_tray_models(&app);
+    Ok(())
+}
+
+/// Engines, models, permissions, recording and sign-in state, version and
+/// settings in one call, for the UI to start from.
+#[tauri::command]
+fn get_app_status(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<status::AppStatus, AppError> {
+    let recording = *state.is_recording.lock_recover();
+    let settings = state.settings.lock_recover().clone();
+    Ok(status::AppStatus {
+        version: app.package_info().version.to_string(),
+        recording,
+        safe_mode: safe_mode::is_active(),
+        microphone: privacy::microphone_state(),
+        permissions: status::Permissions::current(),
+        authenticated: api::client::get_stored_tokens().is_ok(),
+        engines: get_engine_capabilities(),
+        coreml: get_coreml_status(),
+        metal: get_metal_status(),
+        voxtral: get_voxtral_status(),
+        parakeet: get_parakeet_status(),
+        apple_speech: apple_speech_status(&settings),
+        sapi: sapi_status(&settings),
+        punctuation: get_punctuation_status(),
+        models: get_available_models(),
+        voxtral_models: get_voxtral_models(),
+        parakeet_models: get_parakeet_models(),
+        settings,
+    })
+}
+
+fn app_health(state: &AppState) -> status::AppHealth {
+    let recording = *state.is_recording.lock_recover();
+    let settings = state.settings.lock_recover().clone();
+    let stats = stats::get_stats();
+    if let Err(ref e) = stats {
+        log::warn!("[health] Failed to load stats: {}", e);
+    }
+    let pending_sync = match (history::unsynced_count(), dictionary::unsynced_count()) {
+        (Ok(history), Ok(dictionary)) => Some(history + dictionary),
+        (history, dictionary) => {
+            log::warn!("[health] Failed to count unsynced entries: {:?} {:?}", history.err(), dictionary.err());
+            None
+        }
+    };
+    status::AppHealth {
+        engine: transcription::Engine::from_settings(&settings).id().to_string(),
+        model_loaded: is_configured_model_loaded(&settings),
+        recording,
+        last_timings: latency::last(),
+        streak_days: stats.as_ref().map(|s| s.streak_days).unwrap_or(0),
+        words_today: stats.as_ref().map(stats::words_today).unwrap_or(0),
+        pending_sync,
+        storage_bytes: storage::get_storage_report().ok().map(|r| r.total_bytes),
+    }
+}
+
+/// Engine readiness, last latency, streak, today's words, pending sync and
+/// storage in one call, for the dashboard home. Also emitted as `app-health`.
+#[tauri::command]
+fn get_app_health(state: tauri::State<'_, AppState>) -> status::AppHealth {
+    app_health(&state)
+}
+
+/// Whether this run is in safe mode and why, and whether settings.json loads
+#[tauri::command]
+fn get_safe_mode_status() -> safe_mode::SafeModeStatus {
+    safe_mode::status()
+}
+
+/// Move settings.json aside and use defaults from now on. Returns the backup's path.
+#[tauri::command]
+fn reset_settings(state: tauri::State<'_, AppState>) -> Result<Option<String>, AppError> {
+    let backup = settings::back_up_and_reset()?;
+    *state.settings.lock_recover() = settings::UserSettings::default();
+    Ok(backup.map(|p| p.to_string_lossy().into_owned()))
+}
+
+/// Restart after repairing from safe mode. The app comes back in safe mode
+/// as long as `MENTASCRIBE_SAFE_MODE` is set in its environment.
+#[tauri::command]
+fn restart_normally(app: tauri::AppHandle) {
+    safe_mode::mark_healthy();
+    app.restart();
+}
+
+/// Check a hotkey before saving it, including that it can be registered.
+/// Returns its canonical spelling ("Ctrl+Shift+D"), or why it can't be used.
+#[tauri::command]
+fn validate_hotkey(app: tauri::AppHandle, key: String) -> Result<String, AppError> {
+    hotkey::check_available(&app, &key).map_err(AppError::from)
+}
+
+/// Transcribe the WAV files on the clipboard (copied files, or text holding
+/// their paths; not copied audio data), one after another, putting each
+/// one's text on the clipboard (`file-transcription-complete`).
+/// Returns how many were queued.
+#[tauri::command]
+fn transcribe_clipboard(app: tauri::AppHandle) -> Result<usize, AppError> {
+    let files = pipeline::files::clipboard_files()?;
+    Ok(pipeline::files::enqueue(&app, files))
+}
+
+/// The user agreed to send data to a third party for the next recording, in
+/// answer to `cloud-consent-required`. Valid for a minute.
+#[tauri::command]
+fn grant_cloud_consent() {
+    privacy::grant_consent();
+}
+
+/// The last panic this session, for the diagnostics page; later ones arrive as `last-panic`
+#[tauri::command]
+fn get_last_panic() -> Option<panics::PanicReport> {
+    panics::last()
+}
+
+/// Versions, enabled features and whisper.cpp's CPU/GPU support, for bug reports
+#[tauri::command]
+fn get_build_info() -> build_info::BuildInfo {
+    build_info::build_info()
 }
 
 #[tauri::command]
@@ -618,102 +1307,969 @@ fn get_coreml_status() -> transcription::CoremlStatus {
     transcription::whisper::get_coreml_status()
 }
 
+/// Which encoder `size` would run with under the current settings.
+#[tauri::command]
+fn get_encoder_selection(size: String) -> transcription::EncoderSelection {
+    transcription::whisper::select_encoder(&size)
+}
+
+/// Which encoder the loaded Whisper model is running with (None if none is loaded).
+#[tauri::command]
+fn get_active_encoder() -> Option<transcription::EncoderSelection> {
+    transcription::whisper::get_active_encoder()
+}
+
 #[tauri::command]
 fn get_metal_status() -> transcription::MetalStatus {
     transcription::whisper::get_metal_status()
 }
 
+/// Per-engine feature report for the settings UI.
+#[tauri::command]
+fn get_engine_capabilities() -> Vec<transcription::EngineCapabilities> {
+    transcription::Engine::ALL.into_iter().map(transcription::Engine::capabilities).collect()
+}
+
+/// The model picked for this machine on first run and why; None for settings
+/// created before recommendations existed.
+#[tauri::command]
+fn get_model_recommendation(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<transcription::recommend::ModelRecommendation>, AppError> {
+    let settings = state.settings.lock_recover();
+    Ok(settings.transcription.model_recommendation.clone())
+}
+
+/// Languages `model` can transcribe (a whisper model size or the Voxtral
+/// model id); the configured model when omitted.
 #[tauri::command]
-async fn download_coreml_model(app: tauri::AppHandle, size: String) -> Result<(), String> {
+fn get_supported_languages(
+    state: tauri::State<'_, AppState>,
+    model: Option<String>,
+) -> Result<Vec<transcription::LanguageInfo>, AppError> {
+    let settings = state.settings.lock_recover();
+    let model = match model {
+        Some(model) => model,
+        None => match transcription::Engine::from_settings(&settings) {
+            transcription::Engine::Whisper => {
+                settings.transcription.model_size.clone().unwrap_or_else(|| "small".to_string())
+            }
+            engine => engine.id().to_string(),
+        },
+    };
+    #[cfg(feature = "voxtral")]
+    if model == "voxtral" || model == transcription::voxtral::MODEL_ID {
+        return Ok(transcription::voxtral::supported_languages());
+    }
+    #[cfg(feature = "parakeet")]
+    if model == "parakeet" || model == transcription::parakeet::MODEL_ID {
+        return Ok(transcription::parakeet::supported_languages());
+    }
+    #[cfg(target_os = "macos")]
+    if model == transcription::apple::ENGINE_ID {
+        return Ok(transcription::apple::supported_languages());
+    }
+    #[cfg(target_os = "windows")]
+    if model == transcription::sapi::ENGINE_ID {
+        return Ok(transcription::sapi::supported_languages());
+    }
+    Ok(transcription::whisper::supported_languages(&model))
+}
+
+/// Reject a language the configured whisper model can't transcribe, such as
+/// German with an English-only model. Voxtral always auto-detects; Parakeet
+/// only knows English.
+fn validate_transcription_language(settings: &settings::UserSettings) -> Result<(), AppError> {
+    match transcription::Engine::from_settings(settings) {
+        // Voxtral always auto-detects; a cloud provider checks the language itself
+        transcription::Engine::Voxtral | transcription::Engine::Cloud => return Ok(()),
+        transcription::Engine::Parakeet => {
+            let languages = settings
+                .transcription
+                .language
+                .iter()
+                .chain(settings.hotkey.languages.values());
+            for language in languages {
+                if language != "auto" && language != "en" {
+                    return Err(AppError::InvalidInput(format!(
+                        "Parakeet only transcribes English, not '{}'",
+                        language
+                    )));
+                }
+            }
+            return Ok(());
+        }
+        transcription::Engine::Apple => {
+            #[cfg(target_os = "macos")]
+            {
+                let supported = transcription::apple::supported_languages();
+                let languages = settings
+                    .transcription
+                    .language
+                    .iter()
+                    .chain(settings.hotkey.languages.values());
+                for language in languages {
+                    let code = language.split(['-', '_']).next().unwrap_or(language);
+                    if language != "auto" && !supported.iter().any(|l| l.code.eq_ignore_ascii_case(code)) {
+                        return Err(AppError::InvalidInput(format!(
+                            "Apple speech recognition doesn't support '{}'",
+                            language
+                        )));
+                    }
+                }
+            }
+            return Ok(());
+        }
+        transcription::Engine::Sapi => {
+            // A pinned recognizer decides the language itself
+            #[cfg(target_os = "windows")]
+            if settings.transcription.sapi_recognizer.is_none() {
+                let languages = settings
+                    .transcription
+                    .language
+                    .iter()
+                    .chain(settings.hotkey.languages.values());
+                for language in languages {
+                    if !transcription::sapi::supports_language(language) {
+                        return Err(AppError::NotConfigured(format!(
+                            "No Windows speech language pack for '{}'; install it in Settings > Time & Language > Language",
+                            language
+                        )));
+                    }
+                }
+            }
+            return Ok(());
+        }
+        transcription::Engine::Whisper => {}
+    }
+    let model = settings.transcription.model_size.as_deref().unwrap_or("small");
+    let supported = transcription::whisper::supported_languages(model);
+    let languages = settings
+        .transcription
+        .language
+        .iter()
+        .chain(settings.hotkey.languages.values());
+    for language in languages {
+        if language != "auto" && !supported.iter().any(|l| l.code == *language) {
+            return Err(AppError::InvalidInput(if transcription::whisper::is_english_only(model) {
+                format!("Model '{}' only transcribes English, not '{}'", model, language)
+            } else {
+                format!("Unsupported language: {}", language)
+            }));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn download_coreml_model(app: tauri::AppHandle, size: String) -> Result<(), AppError> {
     let app_clone = app.clone();
     let size_clone = size.clone();
-    transcription::whisper::download_coreml_model(&size, move |percent| {
-        app_clone
-            .emit(
-                "download-progress",
-                serde_json::json!({
-                    "model_type": "coreml",
-                    "model_id": &size_clone,
-                    "percent": percent,
-                }),
-            )
-            .ok();
+    transcription::whisper::download_coreml_model(&size, move |progress, mirror| {
+        emit_download_progress(&app_clone, "coreml", &size_clone, progress, mirror);
     })
     .await
-    .map_err(|e| e.to_string())
+    .map_err(AppError::from)
+}
+
+/// Download every model file the current settings need that isn't on disk
+/// yet: the Whisper model (or Voxtral), its CoreML encoder when CoreML is on,
+/// and the VAD model. Returns what was downloaded.
+#[tauri::command]
+async fn download_required_models(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<transcription::required::RequiredModel>, AppError> {
+    let settings = state.settings.lock_recover().clone();
+    let missing = transcription::required::missing(&settings);
+    download_models(&app, &settings, &missing).await?;
+    Ok(missing)
+}
+
+/// Download `models` one after another, reporting progress like the single
+/// download commands. Stops at the first failure.
+async fn download_models(
+    app: &tauri::AppHandle,
+    settings: &settings::UserSettings,
+    models: &[transcription::required::RequiredModel],
+) -> Result<(), AppError> {
+    #[cfg(not(any(feature = "voxtral", feature = "parakeet")))]
+    let _ = settings;
+    for model in models {
+        let progress_app = app.clone();
+        let (model_type, id) = (model.model_type.clone(), model.id.clone());
+        let on_progress = move |progress: &net::DownloadProgress, mirror: &str| {
+            emit_download_progress(&progress_app, &model_type, &id, progress, mirror);
+        };
+        match model.model_type.as_str() {
+            "ggml" => transcription::whisper::download_model(&model.id, on_progress)
+                .await
+                .map_err(|e| storage::write_app_error(app, "model", e))?,
+            "coreml" => transcription::whisper::download_coreml_model(&model.id, on_progress)
+                .await
+                .map_err(|e| storage::write_app_error(app, "model", e))?,
+            "vad" => transcription::whisper::ensure_vad_model(on_progress)
+                .await
+                .map_err(|e| storage::write_app_error(app, "model", e))?,
+            #[cfg(feature = "voxtral")]
+            "voxtral" => {
+                let revision = settings.transcription.voxtral_revision.as_deref();
+                transcription::voxtral::download_model(revision, on_progress)
+                    .await
+                    .map_err(|e| storage::write_app_error(app, "model", e))?
+            }
+            #[cfg(feature = "parakeet")]
+            "parakeet" => {
+                let repo = settings.transcription.parakeet_repo.as_deref();
+                transcription::parakeet::download_model(repo, on_progress)
+                    .await
+                    .map_err(|e| storage::write_app_error(app, "model", e))?
+            }
+            other => return Err(AppError::InvalidInput(format!("Unknown model type: {}", other))),
+        }
+    }
+    refresh_tray_models(app);
+    Ok(())
+}
+
+/// First-run download (`download.auto_download`): fetch what the settings
+/// need in the background, then preload as usual. Returns false when nothing
+/// was started (off, nothing missing, or more than
+/// `download.auto_download_max_mb`), leaving it to `model-needs-download`.
+fn start_auto_download(app: &tauri::AppHandle, settings: &settings::UserSettings) -> bool {
+    if !settings.download.auto_download.unwrap_or(false) {
+        return false;
+    }
+    let missing = transcription::required::missing(settings);
+    if missing.is_empty() {
+        return false;
+    }
+    let total_mb: u32 = missing.iter().map(|m| m.size_mb).sum();
+    let max_mb = settings.download.auto_download_max_mb.unwrap_or(DEFAULT_AUTO_DOWNLOAD_MAX_MB);
+    if total_mb > max_mb {
+        log::info!("Auto-download needs {} MB, over the {} MB limit; asking first", total_mb, max_mb);
+        return false;
+    }
+
+    log::info!("Auto-downloading {} model file(s), {} MB", missing.len(), total_mb);
+    let app = app.clone();
+    let settings = settings.clone();
+    tauri::async_runtime::spawn(async move {
+        match download_models(&app, &settings, &missing).await {
+            Ok(()) if preload_policy(&settings) == "always" => {
+                spawn_model_preload(app.clone(), &settings, "auto-download");
+            }
+            Ok(()) => emit_preload_deferred(&app, &settings),
+            Err(e) => {
+                log::error!("Auto-download failed: {}", e);
+                app.emit("model-needs-download", configured_model_id(&settings)).ok();
+            }
+        }
+    });
+    true
+}
+
+#[tauri::command]
+fn delete_model(app: tauri::AppHandle, size: String) -> Result<(), AppError> {
+    transcription::whisper::delete_model(&size)?;
+    refresh_tray_models(&app);
+    Ok(())
+}
+
+/// Downloaded models whose file differs from the latest published revision.
+#[tauri::command]
+async fn check_model_updates() -> Result<Vec<transcription::ModelUpdate>, AppError> {
+    Ok(transcription::whisper::check_model_updates().await)
+}
+
+/// Replace a downloaded model with its latest revision. Progress is reported
+/// like a download.
+#[tauri::command]
+async fn update_model(app: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    let app_clone = app.clone();
+    let id_clone = id.clone();
+    transcription::whisper::update_model(&id, move |progress, mirror| {
+        emit_download_progress(&app_clone, "ggml", &id_clone, progress, mirror);
+    })
+    .await
+    .map_err(|e| storage::write_app_error(&app, "model", e))
+}
+
+#[tauri::command]
+fn delete_coreml_model(size: String) -> Result<(), AppError> {
+    transcription::whisper::delete_coreml_model(&size).map_err(AppError::from)
+}
+
+/// Forget everything cached from files on disk (dictionary, loaded models,
+/// VAD) and re-check the downloaded model files, for when they were replaced
+/// or deleted by hand. The configured model is loaded again on next use, or
+/// right away under the "always" preload policy.
+#[tauri::command]
+async fn rebuild_caches(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<transcription::integrity::FileCheck>, AppError> {
+    if *state.is_recording.lock_recover() {
+        return Err(AppError::Busy("Stop recording before rebuilding caches".to_string()));
+    }
+    dictionary::invalidate_cache();
+    transcription::whisper::clear_caches();
+    #[cfg(feature = "voxtral")]
+    transcription::voxtral::unload_model();
+    #[cfg(feature = "parakeet")]
+    transcription::parakeet::unload_model();
+    #[cfg(feature = "punctuation")]
+    text::punctuation::unload_model();
+
+    let checks = tokio::task::spawn_blocking(transcription::integrity::revalidate).await?;
+    refresh_tray_models(&app);
+
+    let settings = state.settings.lock_recover().clone();
+    if preload_policy(&settings) == "always" {
+        spawn_model_preload(app, &settings, "cache rebuild");
+    }
+    Ok(checks)
+}
+
+// Storage commands
+#[tauri::command]
+fn get_storage_report() -> Result<storage::StorageReport, AppError> {
+    storage::get_storage_report().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn cleanup_storage(
+    state: tauri::State<'_, AppState>,
+    options: storage::CleanupOptions,
+) -> Result<storage::CleanupResult, AppError> {
+    let active_model = {
+        let settings = state.settings.lock_recover();
+        if settings.transcription.engine.as_deref() == Some("voxtral") {
+            "voxtral-mini-4b".to_string()
+        } else {
+            settings
+                .transcription
+                .model_size
+                .clone()
+                .unwrap_or_else(|| "small".to_string())
+        }
+    };
+    storage::cleanup_storage(&options, &active_model).map_err(AppError::from)
+}
+
+// Stats commands
+#[tauri::command]
+fn get_stats() -> Result<stats::LocalStats, AppError> {
+    stats::get_stats().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn record_transcription_stats(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    word_count: u32,
+    duration_ms: u32,
+) -> Result<stats::LocalStats, AppError> {
+    let stats = stats::record_transcription(word_count, duration_ms)?;
+    if let Ok(settings) = state.settings.lock() {
+        emit_goal_reached(&app, &settings.goals);
+    }
+    Ok(stats)
+}
+
+/// Words and transcriptions by hour of day and weekday, for the activity heatmap
+#[tauri::command]
+fn get_usage_heatmap() -> Result<stats::UsageHeatmap, AppError> {
+    stats::get_usage_heatmap().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn export_stats(path: String, format: String) -> Result<usize, AppError> {
+    stats::export_stats(std::path::Path::new(&path), &format).map_err(AppError::from)
+}
+
+/// Settings, dictionary and optionally stats as one file, for moving to another machine.
+#[tauri::command]
+fn export_config(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    include_stats: Option<bool>,
+) -> Result<(), AppError> {
+    let settings = state.settings.lock_recover().clone();
+    config::export_config(std::path::Path::new(&path), &settings, include_stats.unwrap_or(false))
+        .map_err(AppError::from)
+}
+
+/// Apply a file written by `export_config`. Settings go through `update_settings`,
+/// so an invalid file changes nothing.
+#[tauri::command]
+fn import_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<config::ImportSummary, AppError> {
+    let bundle = config::read_bundle(std::path::Path::new(&path))?;
+
+    // Secrets aren't exported; keep this machine's
+    let mut imported_settings = bundle.settings.clone();
+    {
+        let current = state.settings.lock_recover();
+        if imported_settings.cleanup.api_key_ref.is_none() {
+            imported_settings.cleanup.api_key_ref = current.cleanup.api_key_ref.clone();
+        }
+    }
+    update_settings(app.clone(), imported_settings, state)?;
+
+    let summary = config::import_data(&bundle)?;
+    if summary.dictionary_entries > 0 {
+        let entries = dictionary::get_dictionary()?;
+        app.emit("dictionary-changed", &entries).ok();
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+fn get_goal_progress(state: tauri::State<'_, AppState>) -> Result<Option<stats::GoalProgress>, AppError> {
+    let goal = {
+        let settings = state.settings.lock_recover();
+        settings.goals.daily_word_goal
+    };
+    match goal {
+        Some(goal) => stats::get_goal_progress(goal).map(Some).map_err(AppError::from),
+        None => Ok(None),
+    }
+}
+
+/// Emit `goal-reached` the first time today's words cross the daily goal.
+fn emit_goal_reached(app: &tauri::AppHandle, goals: &settings::GoalSettings) {
+    if !goals.notifications {
+        return;
+    }
+    if let Some(goal) = goals.daily_word_goal {
+        match stats::take_goal_reached(goal) {
+            Ok(Some(progress)) => {
+                log::info!("Daily goal of {} words reached", goal);
+                app.emit("goal-reached", &progress).ok();
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to check daily goal: {}", e),
+        }
+    }
+}
+
+/// Check once a minute whether the streak is at risk (evening, nothing dictated
+/// today) and emit `streak-at-risk` at most once per day.
+fn start_goal_scheduler(app: tauri::AppHandle) {
+    std::thread::Builder::new()
+        .name("goal-scheduler".to_string())
+        .spawn(move || loop {
+            let goals = app
+                .state::<AppState>()
+                .settings
+                .lock()
+                .map(|s| s.goals.clone())
+                .unwrap_or_default();
+            if goals.notifications {
+                let hour = goals.streak_reminder_hour.unwrap_or(20).min(23);
+                match stats::take_streak_at_risk(hour) {
+                    Ok(Some(streak_days)) => {
+                        log::info!("Streak of {} days at risk, emitting streak-at-risk", streak_days);
+                        app.emit(
+                            "streak-at-risk",
+                            serde_json::json!({ "streak_days": streak_days }),
+                        )
+                        .ok();
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Failed to check streak: {}", e),
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_secs(60));
+        })
+        .ok();
+}
+
+/// Emit `app-health` every 30 seconds while the dashboard is open.
+fn start_health_broadcaster(app: tauri::AppHandle) {
+    std::thread::Builder::new()
+        .name("health-broadcaster".to_string())
+        .spawn(move || loop {
+            if app.get_webview_window("dashboard").is_some() {
+                let health = app_health(&app.state::<AppState>());
+                app.emit("app-health", &health).ok();
+            }
+            std::thread::sleep(std::time::Duration::from_secs(30));
+        })
+        .ok();
+}
+
+/// Take a backup when one is due, checking hourly.
+fn start_backup_scheduler(app: tauri::AppHandle) {
+    std::thread::Builder::new()
+        .name("backup-scheduler".to_string())
+        .spawn(move || loop {
+            let backup_settings = app
+                .state::<AppState>()
+                .settings
+                .lock()
+                .map(|s| s.backup.clone())
+                .unwrap_or_default();
+            if backup_settings.enabled {
+                if let Err(e) = backup::backup_if_due(&backup_settings) {
+                    log::warn!("Scheduled backup failed: {}", e);
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_secs(60 * 60));
+        })
+        .ok();
+}
+
+#[tauri::command]
+fn create_backup(state: tauri::State<'_, AppState>) -> Result<backup::BackupInfo, AppError> {
+    let backup_settings = state.settings.lock_recover().backup.clone();
+    backup::create_backup(&backup_settings).map_err(AppError::from)
+}
+
+/// Archives in the backup folder, newest first
+#[tauri::command]
+fn list_backups(state: tauri::State<'_, AppState>) -> Result<Vec<backup::BackupInfo>, AppError> {
+    let backup_settings = state.settings.lock_recover().backup.clone();
+    backup::list_backups(&backup_settings).map_err(AppError::from)
+}
+
+/// Replace all app data with the archive at `path`, after backing up the
+/// current data (returned). Restored settings are applied like `import_config`.
+#[tauri::command]
+fn restore_backup(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<backup::BackupInfo, AppError> {
+    let backup_settings = state.settings.lock_recover().backup.clone();
+    let safety = backup::restore_backup(std::path::Path::new(&path), &backup_settings)?;
+
+    let restored = settings::load_settings()?;
+    update_settings(app.clone(), restored, state)?;
+    if let Some(entries) = dictionary::reload()? {
+        app.emit("dictionary-changed", &entries).ok();
+    }
+    Ok(safety)
+}
+
+// History commands
+#[tauri::command]
+fn get_history(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<history::TranscriptionEntry>, AppError> {
+    history::get_history(limit, offset).map_err(AppError::from)
+}
+
+/// A page of history matching `params` (filters, sort, cursor); see `history::query`
+#[tauri::command]
+fn query_history(params: history::query::HistoryQuery) -> Result<history::query::HistoryPage, AppError> {
+    history::query::query_history(&params).map_err(AppError::from)
+}
+
+/// Entries containing every word of `query`, ranked, with spans to highlight
+#[tauri::command]
+fn search_history(
+    query: String,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<Vec<history::search::SearchMatch>, AppError> {
+    history::search::search_history(&query, date_from.as_deref(), date_to.as_deref()).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_history_entry(id: String) -> Result<Option<history::TranscriptionEntry>, AppError> {
+    history::get_entry(&id).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn delete_history_entry(id: String) -> Result<bool, AppError> {
+    history::delete_entry(&id).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn clear_history() -> Result<(), AppError> {
+    history::clear_history().map_err(AppError::from)
+}
+
+/// Remove entries past `history.retention_days` and rewrite the store.
+#[tauri::command]
+fn compact_history(state: tauri::State<'_, AppState>) -> Result<history::CompactionReport, AppError> {
+    let retention_days = state.settings.lock_recover().history.retention_days;
+    history::compact(retention_days).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_entry_diff(id: String) -> Result<Option<history::EntryDiff>, AppError> {
+    history::get_entry_diff(&id).map_err(AppError::from)
+}
+
+/// Re-run post-processing on an entry's raw text with the current settings
+#[tauri::command]
+fn reprocess_entry(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<history::TranscriptionEntry, AppError> {
+    let settings = state.settings.lock_recover().clone();
+    pipeline::reprocess_entry(&id, &settings).map_err(AppError::from)
+}
+
+/// Most recent result, from memory (also for incognito dictations, which history skips)
+#[tauri::command]
+fn get_last_transcription() -> Option<history::recent::RecentTranscription> {
+    history::recent::last()
+}
+
+/// The last few results, newest first
+#[tauri::command]
+fn get_recent_transcriptions() -> Vec<history::recent::RecentTranscription> {
+    history::recent::all()
+}
+
+/// Copy the most recent result to the clipboard. Returns false when there is none yet.
+#[tauri::command]
+fn copy_last_transcription() -> Result<bool, AppError> {
+    match history::recent::last() {
+        Some(last) => {
+            injection::copy_to_clipboard(&last.text)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+fn get_history_count() -> Result<usize, AppError> {
+    history::get_total_count().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_sessions(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<history::SessionSummary>, AppError> {
+    history::get_sessions(limit, offset).map_err(AppError::from)
+}
+
+/// One merged document of the session's dictations
+#[tauri::command]
+fn export_session(id: String) -> Result<String, AppError> {
+    history::export_session(&id)?
+        .ok_or_else(|| AppError::NotFound(format!("Session not found: {}", id)))
+}
+
+// Privacy commands
+#[tauri::command]
+fn get_privacy_ledger(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<privacy::LedgerEntry>, AppError> {
+    privacy::get_ledger(limit, offset).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn clear_privacy_ledger() -> Result<(), AppError> {
+    privacy::clear_ledger().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn get_microphone_state() -> privacy::MicrophoneState {
+    privacy::microphone_state()
+}
+
+/// Input devices to record from; `recording.selected_input_device` takes a name
+#[tauri::command]
+fn get_audio_devices() -> Result<Vec<audio::capture::InputDevice>, AppError> {
+    audio::capture::list_input_devices().map_err(AppError::from)
+}
+
+/// Open a level-only stream on `device` (None = default input) and emit
+/// `monitor-level` events until `stop_audio_monitor`. Returns the device name.
+#[tauri::command]
+fn start_audio_monitor(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    device: Option<String>,
+) -> Result<String, AppError> {
+    let name = audio::capture::start_monitor(device.as_deref())?;
+
+    let running = state.monitor_emitter_running.clone();
+    if !running.swap(true, Ordering::SeqCst) {
+        std::thread::spawn(move || loop {
+            while audio::capture::is_monitoring() {
+                app.emit("monitor-level", audio::capture::take_monitor_level()).ok();
+                std::thread::sleep(events::interval());
+            }
+            running.store(false, Ordering::SeqCst);
+            // A monitor restarted while this thread was exiting keeps it alive
+            if !audio::capture::is_monitoring() || running.swap(true, Ordering::SeqCst) {
+                break;
+            }
+        });
+    }
+    Ok(name)
+}
+
+#[tauri::command]
+fn stop_audio_monitor() {
+    audio::capture::stop_monitor();
+}
+
+// Plugin commands
+/// Installed text-transform plugins, including ones that failed to load
+#[tauri::command]
+fn get_plugins() -> Vec<plugins::PluginInfo> {
+    plugins::list()
+}
+
+/// Scan the plugins directory again after installing or editing one
+#[tauri::command]
+fn reload_plugins() -> Vec<plugins::PluginInfo> {
+    plugins::reload()
+}
+
+// Dictionary commands
+#[tauri::command]
+fn get_dictionary() -> Result<Vec<dictionary::DictionaryEntry>, AppError> {
+    dictionary::get_dictionary().map_err(AppError::from)
+}
+
+#[tauri::command]
+fn add_dictionary_entry(phrase: String, replacement: String) -> Result<dictionary::DictionaryEntry, AppError> {
+    dictionary::add_entry(phrase, replacement).map_err(AppError::from)
+}
+
+#[tauri::command]
+fn update_dictionary_entry(
+    id: String,
+    phrase: String,
+    replacement: String,
+    enabled: bool,
+    prose_only: Option<bool>,
+    apps: Option<Vec<String>>,
+) -> Result<dictionary::DictionaryEntry, AppError> {
+    dictionary::update_entry(id, phrase, replacement, enabled, prose_only, apps).map_err(AppError::from)
+}
+
+/// Dictionary entry from a correction made to a history entry ("wrong" ->
+/// "right"), optionally re-applied to recent history with the same mistake.
+#[tauri::command]
+fn suggest_dictionary_entry(
+    state: tauri::State<'_, AppState>,
+    wrong: String,
+    right: String,
+    reapply: Option<bool>,
+) -> Result<pipeline::Correction, AppError> {
+    let (wrong, right) = (wrong.trim(), right.trim());
+    if wrong.is_empty() || right.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Both the misrecognized and the corrected text are required".to_string(),
+        ));
+    }
+    let settings = state.settings.lock_recover().clone();
+    pipeline::apply_correction(wrong, right, reapply.unwrap_or(false), &settings).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn delete_model(size: String) -> Result<(), String> {
-    transcription::whisper::delete_model(&size).map_err(|e| e.to_string())
+fn remove_dictionary_entry(id: String) -> Result<bool, AppError> {
+    dictionary::remove_entry(id).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn delete_coreml_model(size: String) -> Result<(), String> {
-    transcription::whisper::delete_coreml_model(&size).map_err(|e| e.to_string())
+fn get_banned_phrases() -> Result<Vec<String>, AppError> {
+    dictionary::banned::get_phrases().map_err(AppError::from)
 }
 
-// Stats commands
+/// Replace the banned phrase list; returns it as stored (trimmed, deduplicated).
 #[tauri::command]
-fn get_stats() -> Result<stats::LocalStats, String> {
-    stats::get_stats().map_err(|e| e.to_string())
+fn set_banned_phrases(phrases: Vec<String>) -> Result<Vec<String>, AppError> {
+    dictionary::banned::set_phrases(phrases).map_err(AppError::from)
 }
 
-#[tauri::command]
-fn record_transcription_stats(word_count: u32, duration_ms: u32) -> Result<stats::LocalStats, String> {
-    stats::record_transcription(word_count, duration_ms).map_err(|e| e.to_string())
+// ---------------------------------------------------------------------------
+// Lazy model preload
+// ---------------------------------------------------------------------------
+
+/// Model preload policy: "always" (default), "on-first-use", or "on-hotkey-hover".
+fn preload_policy(settings: &settings::UserSettings) -> &str {
+    settings.transcription.preload_policy.as_deref().unwrap_or("always")
 }
 
-// History commands
-#[tauri::command]
-fn get_history(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<history::TranscriptionEntry>, String> {
-    history::get_history(limit, offset).map_err(|e| e.to_string())
+fn configured_model_id(settings: &settings::UserSettings) -> String {
+    match transcription::Engine::from_settings(settings) {
+        transcription::Engine::Voxtral => "voxtral-mini-4b".to_string(),
+        transcription::Engine::Parakeet => "parakeet-ctc-0.6b".to_string(),
+        // Managed by macOS
+        transcription::Engine::Apple => "apple".to_string(),
+        transcription::Engine::Sapi => "sapi".to_string(),
+        transcription::Engine::Cloud => settings
+            .transcription
+            .cloud_provider
+            .clone()
+            .unwrap_or_else(|| transcription::cloud::ENGINE_ID.to_string()),
+        transcription::Engine::Whisper => settings
+            .transcription
+            .model_size
+            .clone()
+            .unwrap_or_else(|| "small".to_string()),
+    }
 }
 
-#[tauri::command]
-fn get_history_entry(id: String) -> Result<Option<history::TranscriptionEntry>, String> {
-    history::get_entry(&id).map_err(|e| e.to_string())
+fn is_configured_model_loaded(settings: &settings::UserSettings) -> bool {
+    transcription::Engine::from_settings(settings).backend().is_loaded(settings)
 }
 
-#[tauri::command]
-fn delete_history_entry(id: String) -> Result<bool, String> {
-    history::delete_entry(&id).map_err(|e| e.to_string())
+/// Set while a lazy preload runs so repeated warm-up requests don't stack loads.
+static PRELOAD_IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+
+/// Load the configured model on a background thread, emitting the usual
+/// model-preload-* events. Used by the lazy preload policies.
+fn spawn_model_preload(app: tauri::AppHandle, settings: &settings::UserSettings, reason: &str) {
+    if PRELOAD_IN_FLIGHT.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let engine = transcription::Engine::from_settings(settings);
+    let model = configured_model_id(settings);
+    let settings = settings.clone();
+    let reason = reason.to_string();
+    std::thread::spawn(move || {
+        log::info!("Lazy preload: loading '{}' ({})", model, reason);
+        app.emit("model-preload-start", &model).ok();
+        let start = std::time::Instant::now();
+        let result = engine.backend().load(&settings).map_err(|e| e.to_string());
+        match result {
+            Ok(()) => {
+                let elapsed = start.elapsed().as_secs_f64();
+                log::info!("Lazy preload: '{}' ready in {:.2}s", model, elapsed);
+                app.emit("model-preload-complete", serde_json::json!({
+                    "model": &model,
+                    "elapsed_secs": elapsed,
+                })).ok();
+            }
+            Err(e) => {
+                log::error!("Lazy preload: '{}' failed: {}", model, e);
+                app.emit("model-preload-error", serde_json::json!({
+                    "model": &model,
+                    "error": e,
+                })).ok();
+            }
+        }
+        PRELOAD_IN_FLIGHT.store(false, Ordering::SeqCst);
+    });
 }
 
-#[tauri::command]
-fn clear_history() -> Result<(), String> {
-    history::clear_history().map_err(|e| e.to_string())
+/// Tell the frontend the model wasn't loaded because of the preload policy.
+fn emit_preload_deferred(app: &tauri::AppHandle, settings: &settings::UserSettings) {
+    let model = configured_model_id(settings);
+    let policy = preload_policy(settings);
+    log::info!("Preload of '{}' deferred (policy={})", model, policy);
+    app.emit("model-preload-deferred", serde_json::json!({
+        "model": model,
+        "policy": policy,
+    })).ok();
 }
 
+/// Warm up the model ahead of dictation (called when the pointer enters the
+/// widget). Only acts under the "on-hotkey-hover" policy.
 #[tauri::command]
-fn get_history_count() -> Result<usize, String> {
-    history::get_total_count().map_err(|e| e.to_string())
+fn warm_up_model(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), AppError> {
+    let settings = state.settings.lock_recover();
+    if preload_policy(&settings) == "on-hotkey-hover" && !is_configured_model_loaded(&settings) {
+        spawn_model_preload(app.clone(), &settings, "widget hover");
+    }
+    Ok(())
 }
 
-// Dictionary commands
-#[tauri::command]
-fn get_dictionary() -> Result<Vec<dictionary::DictionaryEntry>, String> {
-    dictionary::get_dictionary().map_err(|e| e.to_string())
+// ---------------------------------------------------------------------------
+// Accuracy boost: one dictation with `transcription.boost_model`
+// ---------------------------------------------------------------------------
+
+/// Tell the pill the boost is "armed", "active" (this recording) or "off".
+fn emit_accuracy_boost(app: &tauri::AppHandle, status: &str, model: Option<&str>) {
+    app.emit("accuracy-boost", serde_json::json!({ "status": status, "model": model })).ok();
 }
 
-#[tauri::command]
-fn add_dictionary_entry(phrase: String, replacement: String) -> Result<dictionary::DictionaryEntry, String> {
-    dictionary::add_entry(phrase, replacement).map_err(|e| e.to_string())
+/// The model a boosted dictation would use: Whisper only, and downloaded.
+fn boost_model(settings: &settings::UserSettings) -> Result<String, AppError> {
+    if transcription::Engine::from_settings(settings) != transcription::Engine::Whisper {
+        return Err(AppError::Unsupported("Accuracy boost needs the Whisper engine".to_string()));
+    }
+    let model = settings
+        .transcription
+        .boost_model
+        .clone()
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| AppError::NotConfigured("No accuracy boost model is set".to_string()))?;
+    match transcription::whisper::get_available_models().into_iter().find(|m| m.id == model) {
+        Some(info) if info.downloaded => Ok(model),
+        Some(_) => Err(AppError::ModelMissing(format!("Model '{}' is not downloaded", model))),
+        None => Err(AppError::InvalidInput(format!("Unknown model '{}'", model))),
+    }
 }
 
-#[tauri::command]
-fn update_dictionary_entry(
-    id: String,
-    phrase: String,
-    replacement: String,
-    enabled: bool,
-) -> Result<dictionary::DictionaryEntry, String> {
-    dictionary::update_entry(id, phrase, replacement, enabled).map_err(|e| e.to_string())
+/// Load the configured model again after the boost model replaced it. The
+/// lazy policies leave that to the next recording.
+fn restore_configured_model(app: &tauri::AppHandle, settings: &settings::UserSettings) {
+    if preload_policy(settings) == "always" {
+        spawn_model_preload(app.clone(), settings, "accuracy boost ended");
+    }
+}
+
+/// Arm or disarm the boost. Arming loads the boost model now, in place of the
+/// configured one, so the boosted dictation doesn't wait for it.
+fn set_boost(app: &tauri::AppHandle, state: &AppState, armed: bool) -> Result<(), AppError> {
+    let settings = state.settings.lock_recover().clone();
+    if !armed {
+        if state.boost_armed.swap(false, Ordering::SeqCst) {
+            log::info!("Accuracy boost cancelled");
+            emit_accuracy_boost(app, "off", None);
+            if !*state.is_recording.lock_recover() {
+                restore_configured_model(app, &settings);
+            }
+        }
+        return Ok(());
+    }
+
+    let model = boost_model(&settings)?;
+    state.boost_armed.store(true, Ordering::SeqCst);
+    log::info!("Accuracy boost armed: next dictation uses '{}'", model);
+    emit_accuracy_boost(app, "armed", Some(&model));
+    // Swapping models under a recording in progress would slow it down; the
+    // boosted one then loads when it starts transcribing
+    if !*state.is_recording.lock_recover() {
+        let mut boosted = settings;
+        boosted.transcription.model_size = Some(model);
+        spawn_model_preload(app.clone(), &boosted, "accuracy boost");
+    }
+    Ok(())
+}
+
+/// A boosted recording is done with its model; go back to the configured one
+/// unless the boost was armed again meanwhile.
+fn end_accuracy_boost(app: &tauri::AppHandle, state: &AppState, boosted: Option<String>) {
+    let Some(model) = boosted else { return };
+    if state.boost_armed.load(Ordering::SeqCst) {
+        return;
+    }
+    log::info!("Accuracy boost with '{}' done", model);
+    emit_accuracy_boost(app, "off", None);
+    restore_configured_model(app, &state.settings.lock_recover());
+}
+
+/// The accuracy boost hotkey: arm the boost, or cancel it if armed.
+pub(crate) fn toggle_accuracy_boost(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let armed = !state.boost_armed.load(Ordering::SeqCst);
+    if let Err(e) = set_boost(app, &state, armed) {
+        log::warn!("Accuracy boost: {}", e);
+        app.emit("accuracy-boost-error", &e).ok();
+    }
 }
 
+/// Use `transcription.boost_model` for the next dictation only (`armed =
+/// false` cancels), without changing settings
 #[tauri::command]
-fn remove_dictionary_entry(id: String) -> Result<bool, String> {
-    dictionary::remove_entry(id).map_err(|e| e.to_string())
+fn set_accuracy_boost(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    armed: bool,
+) -> Result<(), AppError> {
+    set_boost(&app, &state, armed)
 }
 
 // ---------------------------------------------------------------------------
@@ -722,15 +2278,7 @@ fn remove_dictionary_entry(id: String) -> Result<bool, String> {
 
 /// Check if the current engine setting is "voxtral" AND the feature is compiled in.
 fn is_voxtral_engine(settings: &settings::UserSettings) -> bool {
-    #[cfg(feature = "voxtral")]
-    {
-        settings.transcription.engine.as_deref() == Some("voxtral")
-    }
-    #[cfg(not(feature = "voxtral"))]
-    {
-        let _ = settings;
-        false
-    }
+    transcription::Engine::from_settings(settings) == transcription::Engine::Voxtral
 }
 
 #[tauri::command]
@@ -743,6 +2291,7 @@ fn get_voxtral_status() -> transcription::VoxtralStatus {
             metal: s.metal,
             model_downloaded: s.model_downloaded,
             model_loaded: s.model_loaded,
+            revision: s.revision,
         }
     }
     #[cfg(not(feature = "voxtral"))]
@@ -764,48 +2313,251 @@ fn get_voxtral_models() -> Vec<transcription::ModelInfo> {
 }
 
 #[tauri::command]
-async fn download_voxtral_model(app: tauri::AppHandle) -> Result<(), String> {
+async fn download_voxtral_model(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), AppError> {
+    let revision = {
+        let settings = state.settings.lock_recover();
+        settings.transcription.voxtral_revision.clone()
+    };
     #[cfg(feature = "voxtral")]
     {
         let app_clone = app.clone();
-        transcription::voxtral::download_model(move |percent| {
-            app_clone
-                .emit(
-                    "download-progress",
-                    serde_json::json!({
-                        "model_type": "voxtral",
-                        "model_id": "voxtral-mini-4b",
-                        "percent": percent,
-                    }),
-                )
-                .ok();
+        transcription::voxtral::download_model(revision.as_deref(), move |progress, mirror| {
+            emit_download_progress(&app_clone, "voxtral", "voxtral-mini-4b", progress, mirror);
         })
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| storage::write_app_error(&app, "model", e))?;
+        refresh_tray_models(&app);
+        Ok(())
     }
     #[cfg(not(feature = "voxtral"))]
     {
-        let _ = app;
-        Err("Voxtral feature not compiled".to_string())
+        let _ = (app, revision);
+        Err(AppError::Unsupported("Voxtral feature not compiled".to_string()))
     }
 }
 
 #[tauri::command]
-fn delete_voxtral_model() -> Result<(), String> {
+fn delete_voxtral_model(app: tauri::AppHandle) -> Result<(), AppError> {
     #[cfg(feature = "voxtral")]
     {
-        transcription::voxtral::delete_model().map_err(|e| e.to_string())
+        transcription::voxtral::delete_model()?;
+        refresh_tray_models(&app);
+        Ok(())
     }
     #[cfg(not(feature = "voxtral"))]
     {
-        Err("Voxtral feature not compiled".to_string())
+        let _ = app;
+        Err(AppError::Unsupported("Voxtral feature not compiled".to_string()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parakeet IPC commands (feature-gated)
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+fn get_parakeet_status() -> transcription::ParakeetStatus {
+    #[cfg(feature = "parakeet")]
+    {
+        transcription::parakeet::get_status()
+    }
+    #[cfg(not(feature = "parakeet"))]
+    {
+        transcription::ParakeetStatus::default()
+    }
+}
+
+#[tauri::command]
+fn get_parakeet_models() -> Vec<transcription::ModelInfo> {
+    #[cfg(feature = "parakeet")]
+    {
+        transcription::parakeet::get_available_models()
+    }
+    #[cfg(not(feature = "parakeet"))]
+    {
+        Vec::new()
+    }
+}
+
+#[tauri::command]
+async fn download_parakeet_model(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), AppError> {
+    let repo = {
+        let settings = state.settings.lock_recover();
+        settings.transcription.parakeet_repo.clone()
+    };
+    #[cfg(feature = "parakeet")]
+    {
+        let app_clone = app.clone();
+        transcription::parakeet::download_model(repo.as_deref(), move |progress, mirror| {
+            emit_download_progress(&app_clone, "parakeet", transcription::parakeet::MODEL_ID, progress, mirror);
+        })
+        .await
+        .map_err(|e| storage::write_app_error(&app, "model", e))?;
+        refresh_tray_models(&app);
+        Ok(())
+    }
+    #[cfg(not(feature = "parakeet"))]
+    {
+        let _ = (app, repo);
+        Err(AppError::Unsupported("Parakeet feature not compiled".to_string()))
+    }
+}
+
+#[tauri::command]
+fn delete_parakeet_model(app: tauri::AppHandle) -> Result<(), AppError> {
+    #[cfg(feature = "parakeet")]
+    {
+        transcription::parakeet::delete_model()?;
+        refresh_tray_models(&app);
+        Ok(())
+    }
+    #[cfg(not(feature = "parakeet"))]
+    {
+        let _ = app;
+        Err(AppError::Unsupported("Parakeet feature not compiled".to_string()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Apple Speech IPC commands (macOS only)
+// ---------------------------------------------------------------------------
+
+/// Show the Speech Recognition prompt if it hasn't been answered yet, so the
+/// first dictation with the Apple engine doesn't fail on it.
+#[cfg(target_os = "macos")]
+fn prompt_apple_speech_permission() {
+    if transcription::apple::authorization() == "not_determined" {
+        std::thread::spawn(|| {
+            let status = transcription::apple::request_authorization();
+            log::info!("Speech Recognition permission: {}", status);
+        });
+    }
+}
+
+fn apple_speech_status(settings: &settings::UserSettings) -> transcription::AppleSpeechStatus {
+    #[cfg(target_os = "macos")]
+    {
+        transcription::apple::get_status(settings.transcription.language.as_deref())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = settings;
+        transcription::AppleSpeechStatus::default()
+    }
+}
+
+/// Permission, and availability for the configured language.
+#[tauri::command]
+fn get_apple_speech_status(state: tauri::State<'_, AppState>) -> Result<transcription::AppleSpeechStatus, AppError> {
+    let settings = state.settings.lock_recover();
+    Ok(apple_speech_status(&settings))
+}
+
+/// Ask for the Speech Recognition permission; returns the authorization once
+/// the user has answered (right away if they already have).
+#[tauri::command]
+async fn request_apple_speech_permission() -> Result<String, AppError> {
+    #[cfg(target_os = "macos")]
+    {
+        tokio::task::spawn_blocking(transcription::apple::request_authorization)
+            .await
+            .map(str::to_string)
+            .map_err(AppError::from)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err(AppError::Unsupported(
+            "Apple speech recognition is only available on macOS".to_string(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Windows Speech IPC commands (Windows only)
+// ---------------------------------------------------------------------------
+
+fn sapi_status(settings: &settings::UserSettings) -> transcription::SapiStatus {
+    #[cfg(target_os = "windows")]
+    {
+        transcription::sapi::get_status(settings)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = settings;
+        transcription::SapiStatus::default()
+    }
+}
+
+/// Installed Windows speech recognizers and the one dictation would use.
+#[tauri::command]
+fn get_sapi_status(state: tauri::State<'_, AppState>) -> Result<transcription::SapiStatus, AppError> {
+    let settings = state.settings.lock_recover();
+    Ok(sapi_status(&settings))
+}
+
+#[tauri::command]
+fn get_punctuation_status() -> text::PunctuationStatus {
+    #[cfg(feature = "punctuation")]
+    {
+        text::PunctuationStatus {
+            compiled: true,
+            model_downloaded: text::punctuation::is_model_downloaded(),
+            model_loaded: text::punctuation::is_model_loaded(),
+        }
+    }
+    #[cfg(not(feature = "punctuation"))]
+    {
+        text::PunctuationStatus::default()
+    }
+}
+
+#[tauri::command]
+async fn download_punctuation_model(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), AppError> {
+    let repo = {
+        let settings = state.settings.lock_recover();
+        settings.punctuation.model_repo.clone()
+    };
+    #[cfg(feature = "punctuation")]
+    {
+        let app_clone = app.clone();
+        text::punctuation::download_model(repo.as_deref(), move |progress, mirror| {
+            emit_download_progress(&app_clone, "punctuation", "punctuation", progress, mirror);
+        })
+        .await
+        .map_err(AppError::from)
+    }
+    #[cfg(not(feature = "punctuation"))]
+    {
+        let _ = (app, repo);
+        Err(AppError::Unsupported("Punctuation feature not compiled".to_string()))
+    }
+}
+
+#[tauri::command]
+fn delete_punctuation_model() -> Result<(), AppError> {
+    #[cfg(feature = "punctuation")]
+    {
+        text::punctuation::delete_model().map_err(AppError::from)
+    }
+    #[cfg(not(feature = "punctuation"))]
+    {
+        Err(AppError::Unsupported("Punctuation feature not compiled".to_string()))
     }
 }
 
 /// Frontend debug log forwarding — prints to terminal so we can see drag events
 #[tauri::command]
 fn frontend_log(msg: String) {
-    eprintln!("[frontend] {}", msg);
+    diag!(Window, "[frontend] {}", msg);
 }
 
 /// Native drag state — stored in a static so NSEvent monitor blocks can access it.
@@ -857,7 +2609,7 @@ extern "C" fn deferred_remove_monitors(_ctx: *mut std::os::raw::c_void) {
                     }
                 }
             }
-            eprintln!("[native_drag] Monitors removed (deferred)");
+            diag!(Window, "[native_drag] Monitors removed (deferred)");
         }
     }
 }
@@ -878,7 +2630,7 @@ fn stop_native_drag_inner() {
                     }
                 }
             }
-            eprintln!("[native_drag] Drag stopped, monitors removed");
+            diag!(Window, "[native_drag] Drag stopped, monitors removed");
         }
     }
 }
@@ -926,7 +2678,7 @@ fn handle_native_drag_event(event_type: u64) {
                 deferred_remove_monitors,
             );
         }
-        eprintln!("[native_drag] MouseUp — deferred cleanup scheduled");
+        diag!(Window, "[native_drag] MouseUp — deferred cleanup scheduled");
     }
 }
 
@@ -936,7 +2688,7 @@ fn handle_native_drag_event(event_type: u64) {
 /// Called from JS mousedown. The monitors handle all movement and auto-cleanup on mouseup.
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn start_native_drag(app: tauri::AppHandle) -> Result<(), String> {
+fn start_native_drag(app: tauri::AppHandle) -> Result<(), AppError> {
     use cocoa::base::id;
     use cocoa::foundation::NSPoint;
     use objc::{class, msg_send, sel, sel_impl};
@@ -963,11 +2715,11 @@ fn start_native_drag(app: tauri::AppHandle) -> Result<(), String> {
         let ns_panel: id = msg_send![&*panel, self];
         let panel_ptr = ns_panel as usize;
 
-        eprintln!("[native_drag] Starting: mouse=({:.1},{:.1}), origin=({:.1},{:.1}), panel_ptr=0x{:x}",
+        diag!(Window, "[native_drag] Starting: mouse=({:.1},{:.1}), origin=({:.1},{:.1}), panel_ptr=0x{:x}",
             mouse.x, mouse.y, frame.origin.x, frame.origin.y, panel_ptr);
 
         // Store initial state (monitors will be updated after installation)
-        *NATIVE_DRAG_STATE.lock().map_err(|e| e.to_string())? = Some(NativeDragState {
+        *NATIVE_DRAG_STATE.lock_recover() = Some(NativeDragState {
             initial_mouse_x: mouse.x,
             initial_mouse_y: mouse.y,
             initial_origin_x: frame.origin.x,
@@ -1043,7 +2795,7 @@ fn start_native_drag(app: tauri::AppHandle) -> Result<(), String> {
             }
         }
 
-        eprintln!("[native_drag] Monitors installed: local_drag={:?}, global_drag={:?}, local_mouseup={:?}, global_mouseup={:?}",
+        diag!(Window, "[native_drag] Monitors installed: local_drag={:?}, global_drag={:?}, local_mouseup={:?}, global_mouseup={:?}",
             local_drag_monitor, global_drag_monitor, local_mouseup_monitor, global_mouseup_monitor);
     }
 
@@ -1052,7 +2804,7 @@ fn start_native_drag(app: tauri::AppHandle) -> Result<(), String> {
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn start_native_drag(_app: tauri::AppHandle) -> Result<(), String> {
+fn start_native_drag(_app: tauri::AppHandle) -> Result<(), AppError> {
     // On Windows/Linux, dragging is handled via JS-level mousedown + Tauri set_position
     Ok(())
 }
@@ -1069,8 +2821,12 @@ const CURSOR_PROXIMITY_PADDING: f64 = 20.0;
 /// Opacity clamp range for the dictation panel
 const MIN_PANEL_OPACITY: f64 = 0.2;
 const MAX_PANEL_OPACITY: f64 = 1.0;
-/// Audio level emitter sleep interval
-const AUDIO_LEVEL_SLEEP_MS: u64 = 25;
+/// Largest first-run auto-download, in MB, unless overridden in settings
+const DEFAULT_AUTO_DOWNLOAD_MAX_MB: u32 = 1000;
+/// Idle minutes before a standby input stream is closed, unless overridden in settings
+const DEFAULT_STANDBY_IDLE_MINUTES: u32 = 10;
+/// Idle minutes after which the next dictation starts a new history session
+const DEFAULT_SESSION_IDLE_MINUTES: u32 = 5;
 
 /// Position the dictation panel at bottom-center of the monitor containing the cursor.
 ///
@@ -1103,7 +2859,7 @@ fn native_position_on_cursor_monitor(app: &tauri::AppHandle, only_if_different_m
 
     let panel = app.get_webview_panel("dictation")
         .map_err(|e| {
-            eprintln!("[native_pos] ERROR: Failed to get panel: {:?}", e);
+            log::error!("[native_pos] Failed to get panel: {:?}", e);
             format!("{:?}", e)
         })?;
 
@@ -1134,7 +2890,7 @@ fn native_position_on_cursor_monitor(app: &tauri::AppHandle, only_if_different_m
 
         let screen_frame = target_screen_frame
             .ok_or_else(|| {
-                eprintln!("[native_pos] ERROR: No screen found for cursor at ({:.1}, {:.1}), {} screens available", mouse_loc.x, mouse_loc.y, count);
+                log::error!("[native_pos] No screen found for cursor at ({:.1}, {:.1}), {} screens available", mouse_loc.x, mouse_loc.y, count);
                 "No screen found for cursor".to_string()
             })?;
         let visible_frame = target_visible_frame.unwrap();
@@ -1156,7 +2912,7 @@ fn native_position_on_cursor_monitor(app: &tauri::AppHandle, only_if_different_m
             if on_same_screen {
                 return Ok(false);
             }
-            eprintln!("[native_pos] MOVING: window center ({:.1}, {:.1}) NOT on screen {} (origin: {:.1},{:.1} size: {:.1}x{:.1})",
+            diag!(Window, "[native_pos] MOVING: window center ({:.1}, {:.1}) NOT on screen {} (origin: {:.1},{:.1} size: {:.1}x{:.1})",
                 cx, cy, target_screen_idx,
                 screen_frame.origin.x, screen_frame.origin.y,
                 screen_frame.size.x, screen_frame.size.y);
@@ -1169,7 +2925,7 @@ fn native_position_on_cursor_monitor(app: &tauri::AppHandle, only_if_different_m
         let x = visible_frame.origin.x + (visible_frame.size.x - actual_width) / 2.0;
         let y = visible_frame.origin.y + DOCK_OFFSET;
 
-        eprintln!("[native_pos] Positioning on screen {} — mouse: ({:.1}, {:.1}), target: ({:.1}, {:.1}), visible: origin({:.1},{:.1}) size({:.1}x{:.1})",
+        diag!(Window, "[native_pos] Positioning on screen {} — mouse: ({:.1}, {:.1}), target: ({:.1}, {:.1}), visible: origin({:.1},{:.1}) size({:.1}x{:.1})",
             target_screen_idx, mouse_loc.x, mouse_loc.y, x, y,
             visible_frame.origin.x, visible_frame.origin.y,
             visible_frame.size.x, visible_frame.size.y);
@@ -1189,7 +2945,7 @@ fn native_position_on_cursor_monitor(app: &tauri::AppHandle, only_if_different_m
 /// corner, but we need the bottom edge anchored so the pill grows upward).
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), String> {
+fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), AppError> {
     use cocoa::foundation::NSPoint;
     use objc::{msg_send, sel, sel_impl};
     use tauri_nspanel::ManagerExt;
@@ -1220,11 +2976,10 @@ fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), Str
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), String> {
+fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), AppError> {
     use tauri::Manager;
     if let Some(win) = app.get_webview_window("dictation") {
-        win.set_size(tauri::LogicalSize::new(width, height))
-            .map_err(|e| e.to_string())?;
+        win.set_size(tauri::LogicalSize::new(width, height))?;
     }
     Ok(())
 }
@@ -1237,7 +2992,7 @@ fn resize_pill(app: tauri::AppHandle, width: f64, height: f64) -> Result<(), Str
 /// another application is focused.
 #[cfg(target_os = "macos")]
 #[tauri::command]
-fn is_cursor_over_pill(app: tauri::AppHandle) -> Result<bool, String> {
+fn is_cursor_over_pill(app: tauri::AppHandle) -> Result<bool, AppError> {
     use cocoa::foundation::NSPoint;
     use objc::{class, msg_send, sel, sel_impl};
     use tauri_nspanel::ManagerExt;
@@ -1266,11 +3021,129 @@ fn is_cursor_over_pill(app: tauri::AppHandle) -> Result<bool, String> {
 
 #[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn is_cursor_over_pill(_app: tauri::AppHandle) -> Result<bool, String> {
+fn is_cursor_over_pill(_app: tauri::AppHandle) -> Result<bool, AppError> {
     // Non-macOS: fall back to always false (JS events handle hover)
     Ok(false)
 }
 
+/// Id of the menu-bar icon, for rebuilding its menu.
+const TRAY_ID: &str = "main";
+
+/// Model switcher items are "model:<engine>:<model id>".
+const MODEL_ITEM_PREFIX: &str = "model:";
+
+/// The tray menu: a model switcher listing the downloaded models with the
+/// active one checked, then the window items.
+fn build_tray_menu(app: &tauri::AppHandle, settings: &settings::UserSettings) -> tauri::Result<Menu<tauri::Wry>> {
+    let engine = transcription::Engine::from_settings(settings);
+    let active_model = settings.transcription.model_size.as_deref().unwrap_or("small");
+
+    let models = Submenu::with_id(app, "models", "Model", true)?;
+    let mut any = false;
+    for model in transcription::whisper::get_available_models().into_iter().filter(|m| m.downloaded) {
+        let checked = engine == transcription::Engine::Whisper && model.id == active_model;
+        let id = format!("{}whisper:{}", MODEL_ITEM_PREFIX, model.id);
+        models.append(&CheckMenuItem::with_id(app, id, &model.name, true, checked, None::<&str>)?)?;
+        any = true;
+    }
+    #[cfg(feature = "voxtral")]
+    for model in transcription::voxtral::get_available_models().into_iter().filter(|m| m.downloaded) {
+        let id = format!("{}voxtral:{}", MODEL_ITEM_PREFIX, model.id);
+        let checked = engine == transcription::Engine::Voxtral;
+        models.append(&CheckMenuItem::with_id(app, id, &model.name, true, checked, None::<&str>)?)?;
+        any = true;
+    }
+    #[cfg(feature = "parakeet")]
+    for model in transcription::parakeet::get_available_models().into_iter().filter(|m| m.downloaded) {
+        let id = format!("{}parakeet:{}", MODEL_ITEM_PREFIX, model.id);
+        let checked = engine == transcription::Engine::Parakeet;
+        models.append(&CheckMenuItem::with_id(app, id, &model.name, true, checked, None::<&str>)?)?;
+        any = true;
+    }
+    // Nothing to download, so always listed
+    #[cfg(target_os = "macos")]
+    {
+        let id = format!("{}apple:{}", MODEL_ITEM_PREFIX, transcription::apple::ENGINE_ID);
+        let checked = engine == transcription::Engine::Apple;
+        models.append(&CheckMenuItem::with_id(app, id, "Apple Speech", true, checked, None::<&str>)?)?;
+        any = true;
+    }
+    // Listed once a speech language pack is installed
+    #[cfg(target_os = "windows")]
+    if transcription::sapi::recognizers().is_ok_and(|r| !r.is_empty()) {
+        let id = format!("{}sapi:{}", MODEL_ITEM_PREFIX, transcription::sapi::ENGINE_ID);
+        let checked = engine == transcription::Engine::Sapi;
+        models.append(&CheckMenuItem::with_id(app, id, "Windows Speech", true, checked, None::<&str>)?)?;
+        any = true;
+    }
+    if !any {
+        models.append(&MenuItem::with_id(app, "models-none", "No models downloaded", false, None::<&str>)?)?;
+    }
+
+    let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
+    let history_item = MenuItem::with_id(app, "history", "History", true, None::<&str>)?;
+    let toggle_item = MenuItem::with_id(app, "toggle", "Show/Hide Widget", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[&models, &settings_item, &history_item, &toggle_item, &quit_item],
+    )
+}
+
+/// Rebuild the tray menu so the model switcher matches `settings` and
+/// what's downloaded.
+fn refresh_tray_menu(app: &tauri::AppHandle, settings: &settings::UserSettings) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app, settings) {
+        Ok(menu) => {
+            tray.set_menu(Some(menu)).ok();
+        }
+        Err(e) => log::warn!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
+/// Refresh the tray menu from the current settings, e.g. after a model was
+/// downloaded or deleted.
+fn refresh_tray_models(app: &tauri::AppHandle) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => return,
+    };
+    refresh_tray_menu(app, &settings);
+}
+
+/// Switch to the model picked in the tray, through `update_settings` so the
+/// old engine is unloaded and the new model preloaded as from the dashboard.
+fn switch_model_from_tray(app: &tauri::AppHandle, item_id: &str) {
+    let Some((engine, model_id)) = item_id
+        .strip_prefix(MODEL_ITEM_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return;
+    };
+    let state = app.state::<AppState>();
+    let mut new_settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => return,
+    };
+    if matches!(engine, "voxtral" | "parakeet" | "apple" | "sapi") {
+        new_settings.transcription.engine = Some(engine.to_string());
+    } else {
+        new_settings.transcription.engine = Some("whisper".to_string());
+        new_settings.transcription.model_size = Some(model_id.to_string());
+    }
+
+    diag!(Engine, "[tray] Switching model to {}:{}", engine, model_id);
+    if let Err(e) = update_settings(app.clone(), new_settings, state) {
+        log::warn!("Failed to switch model from tray: {}", e);
+        // The clicked item toggled its own check mark; put it back
+        refresh_tray_models(app);
+    }
+}
+
 /// Open the dashboard window, optionally navigating to a specific page.
 fn open_dashboard_window(app: &tauri::AppHandle, page: Option<&str>) {
     if let Some(window) = app.get_webview_window("dashboard") {
@@ -1300,25 +3173,19 @@ fn open_dashboard_window(app: &tauri::AppHandle, page: Option<&str>) {
 /// Reposition dictation window to the monitor where the mouse currently is.
 /// Returns true if window was moved to a different monitor.
 #[tauri::command]
-fn reposition_to_mouse_monitor(app: tauri::AppHandle) -> Result<bool, String> {
+fn reposition_to_mouse_monitor(app: tauri::AppHandle) -> Result<bool, AppError> {
     // Skip repositioning when widget is draggable (user controls position)
     let is_draggable = app.state::<AppState>().settings.lock()
         .map(|s| s.widget.draggable)
         .unwrap_or(false);
     if is_draggable {
-        // Log once per second (this is called every 150ms, so ~7 calls/sec)
-        // Use a simple static counter to throttle
-        use std::sync::atomic::AtomicU64;
-        static SKIP_COUNT: AtomicU64 = AtomicU64::new(0);
-        let count = SKIP_COUNT.fetch_add(1, Ordering::Relaxed);
-        if count % 40 == 0 {
-            eprintln!("[reposition] SKIPPED (draggable=true), skip count: {}", count);
-        }
+        // Called every 150ms; log at most every few seconds
+        diag_every!(Window, std::time::Duration::from_secs(5), "[reposition] SKIPPED (draggable=true)");
         return Ok(false);
     }
 
     let window = app.get_webview_window("dictation")
-        .ok_or_else(|| "Dictation window not found".to_string())?;
+        .ok_or_else(|| AppError::NotFound("Dictation window not found".to_string()))?;
 
     // Skip if window is not visible
     if !window.is_visible().unwrap_or(false) {
@@ -1328,7 +3195,7 @@ fn reposition_to_mouse_monitor(app: tauri::AppHandle) -> Result<bool, String> {
     // Use native AppKit positioning on macOS (bypasses tao's coordinate bugs)
     #[cfg(target_os = "macos")]
     {
-        return native_position_on_cursor_monitor(&app, true);
+        return native_position_on_cursor_monitor(&app, true).map_err(AppError::Internal);
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -1372,17 +3239,17 @@ fn reposition_to_mouse_monitor(app: tauri::AppHandle) -> Result<bool, String> {
 fn toggle_dictation_window(app: &tauri::AppHandle) {
     if let Some(window) = app.get_webview_window("dictation") {
         let is_visible = window.is_visible().unwrap_or(false);
-        eprintln!("[toggle] toggle_dictation_window called, currently visible: {}", is_visible);
+        diag!(Window, "[toggle] toggle_dictation_window called, currently visible: {}", is_visible);
 
         if is_visible {
-            eprintln!("[toggle] Hiding dictation window");
+            diag!(Window, "[toggle] Hiding dictation window");
             window.hide().ok();
         } else {
             // Check if widget is draggable - if so, skip repositioning to preserve user's position
             let is_draggable = app.state::<AppState>().settings.lock()
                 .map(|s| s.widget.draggable)
                 .unwrap_or(false);
-            eprintln!("[toggle] Showing dictation window, draggable={}", is_draggable);
+            diag!(Window, "[toggle] Showing dictation window, draggable={}", is_draggable);
 
             window.show().ok();
             // Re-apply panel settings after show (macOS may reset them)
@@ -1392,26 +3259,90 @@ fn toggle_dictation_window(app: &tauri::AppHandle) {
                 // Position on cursor's monitor after show (panel must exist)
                 #[cfg(target_os = "macos")]
                 {
-                    eprintln!("[toggle] Repositioning to cursor monitor (draggable=false)");
+                    diag!(Window, "[toggle] Repositioning to cursor monitor (draggable=false)");
                     match native_position_on_cursor_monitor(app, false) {
-                        Ok(moved) => eprintln!("[toggle] Position result: moved={}", moved),
-                        Err(e) => eprintln!("[toggle] Position ERROR: {}", e),
+                        Ok(moved) => diag!(Window, "[toggle] Position result: moved={}", moved),
+                        Err(e) => log::error!("[toggle] Positioning failed: {}", e),
                     }
                 }
             } else {
-                eprintln!("[toggle] Skipping reposition (draggable=true, preserving user position)");
+                diag!(Window, "[toggle] Skipping reposition (draggable=true, preserving user position)");
             }
         }
     } else {
-        eprintln!("[toggle] ERROR: dictation window not found!");
+        log::error!("[toggle] dictation window not found!");
     }
 }
 
 pub fn run() {
-    env_logger::init();
+    // Warnings and errors by default; `diag!` output is gated by its own flags
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        .filter_module("diag", log::LevelFilter::Debug)
+        .init();
+    panics::install_hook();
+    paths::migrate();
+
+    // Safe mode ignores settings.json, which may be what crashed the last start
+    let safe_mode = safe_mode::init();
 
     // Load or create default settings
-    let settings = settings::load_settings().unwrap_or_default();
+    let mut settings = if safe_mode {
+        settings::UserSettings::default()
+    } else {
+        settings::load_settings().unwrap_or_default()
+    };
+
+    // First run: pick a model this machine handles well instead of always "small"
+    if !safe_mode && settings::is_first_run() && settings.transcription.model_size.is_none() {
+        let recommendation = transcription::recommend::recommend(&transcription::recommend::probe());
+        log::info!(
+            "First run: defaulting to model '{}' ({})",
+            recommendation.model_size,
+            recommendation.rationale
+        );
+        settings.transcription.model_size = Some(recommendation.model_size.clone());
+        settings.transcription.model_recommendation = Some(recommendation);
+        if let Err(e) = settings::save_settings(&settings) {
+            log::warn!("Failed to save first-run settings: {}", e);
+        }
+    }
+
+    // Once per install: how good a resampler the audio callback can afford
+    if !safe_mode && settings.recording.resampler_benchmark.is_none() {
+        settings.recording.resampler_benchmark = Some(audio::resample::benchmark());
+        if let Err(e) = settings::save_settings(&settings) {
+            log::warn!("Failed to save the resampler benchmark: {}", e);
+        }
+    }
+
+    // Apply proxy configuration before any HTTP client is built
+    net::configure(&settings.proxy);
+    net::set_mirrors(&settings.download.mirrors);
+    net::download::set_connections(settings.download.connections);
+    transcription::integrity::set_verify_on_load(
+        settings.transcription.verify_models_on_load.unwrap_or(false),
+    );
+    transcription::hallucination::configure(&settings.transcription.hallucination_guard);
+    transcription::whisper::set_coreml_preferences(
+        settings.transcription.use_coreml,
+        &settings.transcription.coreml_models,
+    );
+    audio::capture::set_standby(
+        settings.recording.standby,
+        settings.recording.standby_idle_minutes.unwrap_or(DEFAULT_STANDBY_IDLE_MINUTES),
+    );
+    audio::vad::set_backend(settings.recording.vad_backend.as_deref());
+    audio::capture::set_input_device(settings.recording.selected_input_device.clone());
+    audio::capture::set_resampler_quality(audio::resample::configured(
+        settings.recording.resampler_quality.as_deref(),
+        settings.recording.resampler_benchmark.as_ref(),
+    ));
+    hotkey::gate::set_mode(settings.hotkey.mode.as_deref(), settings.hotkey.long_press_ms);
+    events::configure(settings.widget.event_rate_hz);
+    diag::configure(&settings.debug);
+    history::set_session_idle_minutes(
+        settings.history.session_idle_minutes.unwrap_or(DEFAULT_SESSION_IDLE_MINUTES),
+    );
 
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -1427,15 +3358,39 @@ pub fn run() {
     }
 
     builder.setup(|app| {
+            panics::start_reporter(app.handle().clone());
+            events::set_app(app.handle().clone());
+
             // Initialize global hotkey from settings
             let app_handle = app.handle().clone();
-            let loaded_settings = settings::load_settings().unwrap_or_default();
-            let hotkey_key = loaded_settings.hotkey.key.as_deref();
-            hotkey::setup_hotkey(app_handle.clone(), hotkey_key)?;
+            let safe_mode = safe_mode::is_active();
+            let loaded_settings = if safe_mode {
+                settings::UserSettings::default()
+            } else {
+                settings::load_settings().unwrap_or_default()
+            };
+            if safe_mode {
+                log::warn!("Safe mode: global hotkeys not registered");
+            } else {
+                let hotkey_key = loaded_settings.hotkey.key.as_deref();
+                let incognito_key = loaded_settings.hotkey.incognito_key.as_deref();
+                let code_key = loaded_settings.hotkey.code_key.as_deref();
+                let paste_next_key = loaded_settings.hotkey.paste_next_key.as_deref();
+                let boost_key = loaded_settings.hotkey.boost_key.as_deref();
+                hotkey::setup_hotkey(
+                    app_handle.clone(),
+                    hotkey_key,
+                    incognito_key,
+                    code_key,
+                    paste_next_key,
+                    boost_key,
+                )?;
+            }
 
             // Auto-detect CoreML: if use_coreml is None and platform supports it, enable
+            // (not in safe mode, where saving would overwrite settings.json with defaults)
             let coreml_status = transcription::whisper::get_coreml_status();
-            if loaded_settings.transcription.use_coreml.is_none() && coreml_status.supported {
+            if !safe_mode && loaded_settings.transcription.use_coreml.is_none() && coreml_status.supported {
                 log::info!("CoreML supported on this platform (apple_silicon={}), auto-enabling", coreml_status.apple_silicon);
                 let mut auto_settings = loaded_settings.clone();
                 auto_settings.transcription.use_coreml = Some(true);
@@ -1444,16 +3399,31 @@ pub fn run() {
                 if let Ok(mut s) = app_handle.state::<AppState>().settings.lock() {
                     s.transcription.use_coreml = Some(true);
                 }
+                transcription::whisper::set_coreml_preferences(
+                    Some(true),
+                    &loaded_settings.transcription.coreml_models,
+                );
+            }
+
+            // Pick up hand edits to settings.json and dictionary.json
+            if !safe_mode {
+                watcher::start(app_handle.clone());
             }
+            // Fall back to the clipboard if Accessibility is revoked while running
+            injection::permission::start(app_handle.clone());
 
             // Check which engine is configured and preload accordingly
             let use_voxtral_engine = is_voxtral_engine(&loaded_settings);
 
-            if use_voxtral_engine {
+            if safe_mode {
+                log::warn!("Safe mode: skipping model preload and downloads");
+            } else if use_voxtral_engine {
                 // Preload Voxtral model
                 #[cfg(feature = "voxtral")]
                 {
-                    if transcription::voxtral::is_model_downloaded() {
+                    if transcription::voxtral::is_model_downloaded() && preload_policy(&loaded_settings) != "always" {
+                        emit_preload_deferred(&app_handle, &loaded_settings);
+                    } else if transcription::voxtral::is_model_downloaded() {
                         let preload_app_handle = app_handle.clone();
                         std::thread::spawn(move || {
                             log::info!("Background preload: starting for Voxtral model");
@@ -1477,11 +3447,40 @@ pub fn run() {
                                 }
                             }
                         });
-                    } else {
+                    } else if !start_auto_download(&app_handle, &loaded_settings) {
                         log::info!("Voxtral model not downloaded, emitting model-needs-download");
                         app_handle.emit("model-needs-download", "voxtral-mini-4b").ok();
                     }
                 }
+            } else if transcription::Engine::from_settings(&loaded_settings) == transcription::Engine::Parakeet {
+                #[cfg(feature = "parakeet")]
+                {
+                    if !transcription::parakeet::is_model_downloaded() {
+                        if !start_auto_download(&app_handle, &loaded_settings) {
+                            log::info!("Parakeet model not downloaded, emitting model-needs-download");
+                            app_handle.emit("model-needs-download", transcription::parakeet::MODEL_ID).ok();
+                        }
+                    } else if preload_policy(&loaded_settings) != "always" {
+                        emit_preload_deferred(&app_handle, &loaded_settings);
+                    } else {
+                        spawn_model_preload(app_handle.clone(), &loaded_settings, "startup");
+                    }
+                }
+            } else if transcription::Engine::from_settings(&loaded_settings) == transcription::Engine::Apple {
+                // Nothing to load; the auto-download fetches the VAD model streaming uses
+                #[cfg(target_os = "macos")]
+                {
+                    prompt_apple_speech_permission();
+                    start_auto_download(&app_handle, &loaded_settings);
+                }
+            } else if transcription::Engine::from_settings(&loaded_settings) == transcription::Engine::Sapi {
+                // Language packs come with Windows; nothing to download or load
+                #[cfg(target_os = "windows")]
+                if !transcription::sapi::supports_language(loaded_settings.transcription.language.as_deref().unwrap_or("auto")) {
+                    log::warn!("No Windows speech recognizer for the configured language");
+                }
+            } else if transcription::Engine::from_settings(&loaded_settings) == transcription::Engine::Cloud {
+                // The provider transcribes; nothing to download or load
             } else {
                 // Preload Whisper model (existing behavior)
                 let models = transcription::whisper::get_available_models();
@@ -1497,12 +3496,26 @@ pub fn run() {
                     .map(|m| m.downloaded)
                     .unwrap_or(false);
 
-                if !model_downloaded {
+                if !model_downloaded && !start_auto_download(&app_handle, &loaded_settings) {
                     log::info!("Configured model '{}' not found, emitting model-needs-download event", configured_model);
                     app_handle.emit("model-needs-download", configured_model).ok();
                 }
 
-                if model_downloaded {
+                if model_downloaded && preload_policy(&loaded_settings) != "always" {
+                    // Still fetch the small VAD model so streaming works on first use
+                    let vad_app_handle = app_handle.clone();
+                    std::thread::spawn(move || {
+                        if let Ok(rt) = tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                            let download = transcription::whisper::ensure_vad_model(|progress, mirror| {
+                                emit_download_progress(&vad_app_handle, "vad", "silero", progress, mirror);
+                            });
+                            if let Err(e) = rt.block_on(download) {
+                                log::warn!("Failed to download VAD model: {} (VAD pre-filtering will be skipped)", e);
+                            }
+                        }
+                    });
+                    emit_preload_deferred(&app_handle, &loaded_settings);
+                } else if model_downloaded {
                     let preload_model_size = configured_model.to_string();
                     let preload_app_handle = app_handle.clone();
                     std::thread::spawn(move || {
@@ -1516,7 +3529,10 @@ pub fn run() {
                             .enable_all()
                             .build();
                         if let Ok(rt) = rt {
-                            if let Err(e) = rt.block_on(transcription::whisper::ensure_vad_model()) {
+                            let download = transcription::whisper::ensure_vad_model(|progress, mirror| {
+                                emit_download_progress(&preload_app_handle, "vad", "silero", progress, mirror);
+                            });
+                            if let Err(e) = rt.block_on(download) {
                                 log::warn!("Failed to download VAD model: {} (VAD pre-filtering will be skipped)", e);
                             }
                         }
@@ -1555,11 +3571,31 @@ pub fn run() {
                 }
             }
 
+            start_goal_scheduler(app_handle.clone());
+            start_backup_scheduler(app_handle.clone());
+            start_health_broadcaster(app_handle.clone());
+
+            // Opt-in pre-roll keeps a standby input stream open from startup
+            audio::preroll::configure(loaded_settings.recording.preroll_ms.unwrap_or(0));
+
+            // Startup finished once the app stays up past the background preload
+            std::thread::spawn(|| {
+                std::thread::sleep(safe_mode::HEALTHY_AFTER);
+                safe_mode::mark_healthy();
+            });
+
             // Show dictation window and convert to NSPanel
             if let Some(window) = app.get_webview_window("dictation") {
                 window.show().ok();
+                // Audio files dropped on the pill are transcribed to the clipboard
+                let handle = app_handle.clone();
+                window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+                        pipeline::files::enqueue(&handle, paths.clone());
+                    }
+                });
             } else {
-                println!("[window] setup: WARNING - dictation window not found");
+                log::warn!("[window] setup: dictation window not found");
             }
 
             // Convert dictation window to NSPanel on macOS for fullscreen overlay support
@@ -1573,18 +3609,11 @@ pub fn run() {
             }
 
             // Build tray menu (shown on right-click)
-            let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
-            let history_item = MenuItem::with_id(app, "history", "History", true, None::<&str>)?;
-            let toggle_item = MenuItem::with_id(app, "toggle", "Show/Hide Widget", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-
-            let menu = Menu::with_items(
-                app,
-                &[&settings_item, &history_item, &toggle_item, &quit_item],
-            )?;
+            let tray_settings = app_handle.state::<AppState>().settings.lock().map(|s| s.clone()).unwrap_or_default();
+            let menu = build_tray_menu(&app_handle, &tray_settings)?;
 
             // Build tray icon — single click opens dashboard, right-click shows menu
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .show_menu_on_left_click(false)
@@ -1601,6 +3630,9 @@ pub fn run() {
                     "quit" => {
                         app.exit(0);
                     }
+                    id if id.starts_with(MODEL_ITEM_PREFIX) => {
+                        switch_model_from_tray(app, id);
+                    }
                     _ => {}
                 })
                 .on_tray_icon_event(|tray, event| {
@@ -1615,42 +3647,121 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Bring up the dashboard, where the repair commands are
+            if safe_mode {
+                open_dashboard_window(&app_handle, Some("settings"));
+            }
+
             Ok(())
         })
         .manage(AppState {
             is_recording: Mutex::new(false),
             settings: Mutex::new(settings),
             audio_level_emitter_running: Arc::new(AtomicBool::new(false)),
+            monitor_emitter_running: Arc::new(AtomicBool::new(false)),
+            incognito_session: AtomicBool::new(false),
+            session_language: Mutex::new(None),
+            session_profile: Mutex::new(text::Profile::Prose),
+            session_app: Mutex::new(None),
+            chunk_monitor_running: Mutex::new(None),
+            paste_queue: Mutex::new(injection::queue::PasteQueue::default()),
+            boost_armed: AtomicBool::new(false),
+            session_model: Mutex::new(None),
         })
         .invoke_handler(tauri::generate_handler![
             start_recording,
             stop_recording,
             inject_text,
+            get_paste_queue,
+            paste_next,
+            clear_paste_queue,
+            get_learned_insert_methods,
+            forget_learned_insert_method,
+            set_learned_insert_method,
+            benchmark_injection,
             reset_recording_state,
             get_settings,
             update_settings,
             login,
+            set_cloud_api_key,
+            has_cloud_api_key,
+            set_proxy_password,
             download_model,
+            get_app_status,
+            get_app_health,
+            get_safe_mode_status,
+            reset_settings,
+            restart_normally,
+            get_last_panic,
+            get_build_info,
+            grant_cloud_consent,
+            validate_hotkey,
+            transcribe_clipboard,
             get_available_models,
             get_coreml_status,
+            get_encoder_selection,
+            get_active_encoder,
             get_metal_status,
+            get_engine_capabilities,
+            get_supported_languages,
+            get_model_recommendation,
             download_coreml_model,
+            download_required_models,
             delete_model,
+            check_model_updates,
+            update_model,
             delete_coreml_model,
+            rebuild_caches,
+            warm_up_model,
+            set_accuracy_boost,
+            // Storage
+            get_storage_report,
+            cleanup_storage,
             // Stats
             get_stats,
             record_transcription_stats,
+            get_goal_progress,
+            get_usage_heatmap,
+            export_stats,
+            export_config,
+            import_config,
             // History
             get_history,
+            query_history,
+            search_history,
+            create_backup,
+            list_backups,
+            restore_backup,
             get_history_entry,
             delete_history_entry,
             clear_history,
+            compact_history,
             get_history_count,
+            get_entry_diff,
+            reprocess_entry,
+            get_last_transcription,
+            get_recent_transcriptions,
+            copy_last_transcription,
+            get_sessions,
+            export_session,
+            // Privacy
+            get_privacy_ledger,
+            clear_privacy_ledger,
+            get_microphone_state,
+            get_audio_devices,
+            start_audio_monitor,
+            stop_audio_monitor,
+            // Plugins
+            get_plugins,
+            reload_plugins,
             // Dictionary
             get_dictionary,
             add_dictionary_entry,
             update_dictionary_entry,
             remove_dictionary_entry,
+            get_banned_phrases,
+            set_banned_phrases,
+            suggest_dictionary_entry,
             // Window positioning
             reposition_to_mouse_monitor,
             start_native_drag,
@@ -1661,9 +3772,37 @@ pub fn run() {
             get_voxtral_models,
             download_voxtral_model,
             delete_voxtral_model,
+            get_parakeet_status,
+            get_parakeet_models,
+            download_parakeet_model,
+            delete_parakeet_model,
+            get_apple_speech_status,
+            request_apple_speech_permission,
+            get_sapi_status,
+            // Punctuation
+            get_punctuation_status,
+            download_punctuation_model,
+            delete_punctuation_model,
             // Debug
             frontend_log,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // A normal quit counts as a finished startup, however soon it comes
+                safe_mode::mark_healthy();
+                let history_settings = app
+                    .state::<AppState>()
+                    .settings
+                    .lock()
+                    .map(|s| s.history.clone())
+                    .unwrap_or_default();
+                if history_settings.auto_compact.unwrap_or(false) {
+                    if let Err(e) = history::compact_if_due(history_settings.retention_days) {
+                        log::warn!("History compaction on quit failed: {}", e);
+                    }
+                }
+            }
+        });
 }
@@ -0,0 +1,277 @@
+//! Model file downloads: mirror fallback plus segmented parallel transfer.
+//!
+//! Large files are split into byte ranges fetched over several connections at
+//! once, each written straight to its offset in the destination file. This
+//! mostly helps on high-latency links, where a single TCP stream never reaches
+//! the available bandwidth. Servers without range support get one stream.
+
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use super::{client, mirror_candidates, NetError};
+use crate::transcription::integrity::{self, IntegrityError};
+
+/// Parallel connections per file unless configured otherwise.
+const DEFAULT_CONNECTIONS: u32 = 4;
+const MAX_CONNECTIONS: u32 = 8;
+/// Smaller files aren't worth splitting.
+const SEGMENTED_MIN_BYTES: u64 = 64 * 1024 * 1024;
+/// Attempts per range before the whole download fails; each retry resumes
+/// where the previous attempt stopped.
+const RANGE_ATTEMPTS: u32 = 3;
+
+static CONNECTIONS: AtomicU32 = AtomicU32::new(DEFAULT_CONNECTIONS);
+
+/// Set the number of parallel connections per file (None = default; 1 disables
+/// segmented downloads).
+pub fn set_connections(connections: Option<u32>) {
+    let connections = connections.unwrap_or(DEFAULT_CONNECTIONS).clamp(1, MAX_CONNECTIONS);
+    CONNECTIONS.store(connections, Ordering::Relaxed);
+}
+
+/// A file on the first mirror that answered, ready to be saved.
+pub struct RemoteFile {
+    client: reqwest::Client,
+    url: String,
+    mirror: String,
+    total_bytes: Option<u64>,
+    /// Full-body response when the server ignored the range request
+    response: Option<reqwest::Response>,
+}
+
+/// Find the first mirror serving `url` and learn its size and range support.
+pub async fn open(url: &str) -> Result<RemoteFile, NetError> {
+    let client = client()?;
+    let mut failures: Vec<String> = Vec::new();
+
+    for (label, candidate) in mirror_candidates(url) {
+        // An open-ended range both probes range support and, if the server
+        // ignores it, is the plain download
+        match client.get(&candidate).header(RANGE, "bytes=0-").send().await {
+            Ok(response) if response.status() == StatusCode::PARTIAL_CONTENT => {
+                let total_bytes = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.rsplit('/').next())
+                    .and_then(|v| v.parse().ok());
+                if !failures.is_empty() {
+                    log::info!("Downloading from mirror '{}' after {} failure(s)", label, failures.len());
+                }
+                return Ok(RemoteFile {
+                    client,
+                    url: response.url().to_string(),
+                    mirror: label,
+                    total_bytes,
+                    response: Some(response),
+                });
+            }
+            Ok(response) if response.status().is_success() => {
+                if !failures.is_empty() {
+                    log::info!("Downloading from mirror '{}' after {} failure(s)", label, failures.len());
+                }
+                return Ok(RemoteFile {
+                    client,
+                    url: candidate,
+                    mirror: label,
+                    total_bytes: response.content_length(),
+                    response: Some(response),
+                });
+            }
+            Ok(response) => {
+                log::warn!("Mirror '{}' returned HTTP {} for {}", label, response.status(), candidate);
+                failures.push(format!("{}: HTTP {}", label, response.status()));
+            }
+            Err(e) => {
+                log::warn!("Mirror '{}' request failed for {}: {}", label, candidate, e);
+                failures.push(format!("{}: {}", label, e));
+            }
+        }
+    }
+
+    Err(NetError::AllMirrorsFailed(failures.join("; ")))
+}
+
+impl RemoteFile {
+    pub fn mirror(&self) -> &str {
+        &self.mirror
+    }
+
+    /// Size reported by the server, if any.
+    pub fn total_bytes(&self) -> Option<u64> {
+        self.total_bytes
+    }
+
+    fn accepts_ranges(&self) -> bool {
+        self.response
+            .as_ref()
+            .map(|r| r.status() == StatusCode::PARTIAL_CONTENT)
+            .unwrap_or(false)
+    }
+
+    /// Write the file to `path`, calling `on_chunk(bytes)` as data arrives.
+    /// Returns the SHA256 (hex) of the saved file. `path` should be a `.part`
+    /// file renamed into place once verified: segmented downloads preallocate
+    /// it at full size. If the download fails the partial file is removed.
+    pub async fn save_to(self, path: &Path, on_chunk: impl FnMut(u64)) -> Result<String, NetError> {
+        let result = self.write_to(path, on_chunk).await;
+        if let Err(ref e) = result {
+            log::warn!("Download to {:?} failed ({}), removing the partial file", path, e);
+            std::fs::remove_file(path).ok();
+        }
+        result
+    }
+
+    async fn write_to(mut self, path: &Path, mut on_chunk: impl FnMut(u64)) -> Result<String, NetError> {
+        let connections = CONNECTIONS.load(Ordering::Relaxed) as u64;
+        let segmented = match self.total_bytes {
+            Some(total) => connections > 1 && self.accepts_ranges() && total >= SEGMENTED_MIN_BYTES,
+            None => false,
+        };
+
+        if !segmented {
+            let mut response = self.response.take().ok_or_else(|| {
+                NetError::DownloadError("response already consumed".to_string())
+            })?;
+            let mut file = std::fs::File::create(path)?;
+            let mut hasher = Sha256::new();
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| NetError::DownloadError(e.to_string()))?
+            {
+                file.write_all(&chunk)?;
+                hasher.update(&chunk);
+                on_chunk(chunk.len() as u64);
+            }
+            return Ok(integrity::finish_hex(hasher));
+        }
+
+        // The probe response would stream the whole file; ranges replace it
+        drop(self.response.take());
+        let total = self.total_bytes.unwrap_or(0);
+        log::info!(
+            "Segmented download: {} bytes over {} connections from '{}'",
+            total,
+            connections,
+            self.mirror
+        );
+
+        let file = std::fs::File::create(path)?;
+        file.set_len(total)?;
+        drop(file);
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<u64>();
+        let mut tasks = tokio::task::JoinSet::new();
+        let segment = total.div_ceil(connections);
+        for index in 0..connections {
+            let start = index * segment;
+            if start >= total {
+                break;
+            }
+            let end = (start + segment).min(total) - 1;
+            tasks.spawn(fetch_range(
+                self.client.clone(),
+                self.url.clone(),
+                path.to_path_buf(),
+                start,
+                end,
+                tx.clone(),
+            ));
+        }
+        drop(tx);
+
+        loop {
+            tokio::select! {
+                Some(bytes) = rx.recv() => on_chunk(bytes),
+                joined = tasks.join_next() => match joined {
+                    Some(result) => {
+                        let result = result
+                            .map_err(|e| NetError::DownloadError(e.to_string()))
+                            .and_then(|r| r);
+                        if let Err(e) = result {
+                            tasks.abort_all();
+                            return Err(e);
+                        }
+                    }
+                    None => break,
+                },
+            }
+        }
+        while let Ok(bytes) = rx.try_recv() {
+            on_chunk(bytes);
+        }
+
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || integrity::hash_file(&path))
+            .await
+            .map_err(|e| NetError::DownloadError(e.to_string()))?
+            .map_err(|e| match e {
+                IntegrityError::IoError(e) => NetError::IoError(e),
+                e => NetError::DownloadError(e.to_string()),
+            })
+    }
+}
+
+/// Fetch bytes `start..=end` into the same range of the file at `path`.
+async fn fetch_range(
+    client: reqwest::Client,
+    url: String,
+    path: PathBuf,
+    start: u64,
+    end: u64,
+    progress: UnboundedSender<u64>,
+) -> Result<(), NetError> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(&path)?;
+    let mut offset = start;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let result: Result<(), NetError> = async {
+            let mut response = client
+                .get(&url)
+                .header(RANGE, format!("bytes={}-{}", offset, end))
+                .send()
+                .await
+                .map_err(|e| NetError::DownloadError(e.to_string()))?;
+            if response.status() != StatusCode::PARTIAL_CONTENT {
+                return Err(NetError::DownloadError(format!(
+                    "HTTP {} for range {}-{}",
+                    response.status(),
+                    offset,
+                    end
+                )));
+            }
+            file.seek(SeekFrom::Start(offset))?;
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| NetError::DownloadError(e.to_string()))?
+            {
+                file.write_all(&chunk)?;
+                offset += chunk.len() as u64;
+                progress.send(chunk.len() as u64).ok();
+            }
+            Ok(())
+        }
+        .await;
+
+        if offset > end {
+            return Ok(());
+        }
+        let error = match result {
+            Ok(()) => NetError::DownloadError(format!("connection closed at byte {} of range ending {}", offset, end)),
+            Err(e) => e,
+        };
+        if attempt >= RANGE_ATTEMPTS {
+            return Err(error);
+        }
+        log::warn!("Range {}-{} failed ({}), retrying from {}", start, end, error, offset);
+    }
+}
@@ -0,0 +1,377 @@
+//! Shared HTTP client construction and download mirror fallback.
+//!
+//! Every outbound request (model downloads, API, cloud STT) goes through
+//! `client()` so the user's proxy configuration is applied consistently.
+
+pub mod download;
+
+use crate::settings::ProxySettings;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NetError {
+    #[error("Invalid proxy configuration: {0}")]
+    InvalidProxy(String),
+    #[error("Failed to build HTTP client: {0}")]
+    ClientError(String),
+    #[error("All download mirrors failed: {0}")]
+    AllMirrorsFailed(String),
+    #[error("Download failed: {0}")]
+    DownloadError(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Active proxy configuration. Set at startup and whenever settings change,
+/// read by every client build so in-flight code doesn't need settings threaded through.
+static PROXY_CONFIG: Lazy<RwLock<ProxySettings>> = Lazy::new(|| RwLock::new(ProxySettings::default()));
+
+/// Replace the active proxy configuration.
+pub fn configure(proxy: &ProxySettings) {
+    if let Ok(mut config) = PROXY_CONFIG.write() {
+        *config = proxy.clone();
+    }
+    log::info!("Proxy mode set to '{}'", proxy.mode.as_deref().unwrap_or("system"));
+}
+
+/// Build the proxy URL for manual mode, e.g. `http://proxy.corp:8080`.
+/// Returns `None` when no host is configured.
+fn manual_proxy_url(proxy: &ProxySettings) -> Option<String> {
+    let host = proxy.host.as_deref().map(str::trim).filter(|h| !h.is_empty())?;
+    let with_scheme = if host.contains("://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
+    };
+    match proxy.port {
+        Some(port) => Some(format!("{}:{}", with_scheme.trim_end_matches('/'), port)),
+        None => Some(with_scheme),
+    }
+}
+
+/// Create a `reqwest::ClientBuilder` with the active proxy configuration applied.
+/// Callers can add their own timeouts/headers before calling `build()`.
+pub fn client_builder() -> Result<reqwest::ClientBuilder, NetError> {
+    let proxy = PROXY_CONFIG
+        .read()
+        .map(|c| c.clone())
+        .unwrap_or_default();
+
+    let builder = reqwest::Client::builder();
+
+    match proxy.mode.as_deref().unwrap_or("system") {
+        // reqwest honours HTTP_PROXY / HTTPS_PROXY / NO_PROXY by default
+        "system" => Ok(builder),
+        "none" => Ok(builder.no_proxy()),
+        "manual" => {
+            let url = manual_proxy_url(&proxy)
+                .ok_or_else(|| NetError::InvalidProxy("proxy host is empty".to_string()))?;
+            let mut p = reqwest::Proxy::all(&url)
+                .map_err(|e| NetError::InvalidProxy(format!("{}: {}", url, e)))?;
+            if let Some(ref user) = proxy.username {
+                if !user.is_empty() {
+                    p = p.basic_auth(user, proxy.password.as_deref().unwrap_or(""));
+                }
+            }
+            if !proxy.bypass.is_empty() {
+                p = p.no_proxy(reqwest::NoProxy::from_string(&proxy.bypass.join(",")));
+            }
+            // An explicit proxy disables reqwest's environment proxy detection
+            Ok(builder.proxy(p))
+        }
+        other => Err(NetError::InvalidProxy(format!("unknown proxy mode '{}'", other))),
+    }
+}
+
+/// Build an HTTP client with the active proxy configuration.
+pub fn client() -> Result<reqwest::Client, NetError> {
+    client_builder()?
+        .build()
+        .map_err(|e| NetError::ClientError(e.to_string()))
+}
+
+// ======================= Download mirrors =======================
+//
+// Model files are hosted on HuggingFace, which is slow or blocked in some regions.
+// Every HuggingFace URL is expanded into an ordered list of candidate URLs, one per
+// configured mirror, and downloads fall back through them until one responds.
+
+const HF_HOST: &str = "https://huggingface.co/";
+
+/// Built-in mirror presets, referenced by name in `DownloadSettings::mirrors`.
+const MIRROR_PRESETS: &[(&str, &str)] = &[
+    ("huggingface", "https://huggingface.co/{repo}/resolve/{revision}/{file}"),
+    ("hf-mirror", "https://hf-mirror.com/{repo}/resolve/{revision}/{file}"),
+];
+
+/// Ordered mirror list. Empty means HuggingFace only.
+static MIRRORS: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Replace the configured mirror list (preset names, URL templates, or base URLs).
+pub fn set_mirrors(mirrors: &[String]) {
+    if let Ok(mut m) = MIRRORS.write() {
+        *m = mirrors.to_vec();
+    }
+    if !mirrors.is_empty() {
+        log::info!("Download mirrors: {:?}", mirrors);
+    }
+}
+
+/// Split a HuggingFace resolve URL into (repo, revision, file).
+pub(crate) fn parse_hf_url(url: &str) -> Option<(&str, &str, &str)> {
+    let rest = url.strip_prefix(HF_HOST)?;
+    let (repo, after) = rest.split_once("/resolve/")?;
+    let (revision, file) = after.split_once('/')?;
+    Some((repo, revision, file))
+}
+
+/// Expand one mirror entry into (label, url) for the given HuggingFace URL.
+/// An entry is a preset name, a template containing `{file}` (and optionally
+/// `{repo}` / `{revision}`), or a plain base URL that replaces the HuggingFace host.
+fn expand_mirror(entry: &str, repo: &str, revision: &str, file: &str) -> (String, String) {
+    let entry = entry.trim();
+    let template = MIRROR_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(entry))
+        .map(|(_, t)| t.to_string())
+        .unwrap_or_else(|| {
+            if entry.contains("{file}") {
+                entry.to_string()
+            } else {
+                format!("{}/{{repo}}/resolve/{{revision}}/{{file}}", entry.trim_end_matches('/'))
+            }
+        });
+    let url = template
+        .replace("{repo}", repo)
+        .replace("{revision}", revision)
+        .replace("{file}", file);
+    let label = if MIRROR_PRESETS.iter().any(|(name, _)| name.eq_ignore_ascii_case(entry)) {
+        entry.to_lowercase()
+    } else {
+        url.split("://")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or(entry)
+            .to_string()
+    };
+    (label, url)
+}
+
+/// Candidate (mirror label, url) pairs for a download through the configured
+/// mirrors, in fallback order.
+pub fn mirror_candidates(url: &str) -> Vec<(String, String)> {
+    let mirrors = MIRRORS.read().map(|m| m.clone()).unwrap_or_default();
+    candidates(url, &mirrors)
+}
+
+/// Candidate (mirror label, url) pairs for a download through `mirrors`.
+/// Non-HuggingFace URLs are returned unchanged.
+fn candidates(url: &str, mirrors: &[String]) -> Vec<(String, String)> {
+    let (repo, revision, file) = match parse_hf_url(url) {
+        Some(parts) => parts,
+        None => return vec![("direct".to_string(), url.to_string())],
+    };
+
+    let mut candidates: Vec<(String, String)> = if mirrors.is_empty() {
+        vec![("huggingface".to_string(), url.to_string())]
+    } else {
+        mirrors
+            .iter()
+            .filter(|m| !m.trim().is_empty())
+            .map(|m| expand_mirror(m, repo, revision, file))
+            .collect()
+    };
+    let mut seen = HashSet::new();
+    candidates.retain(|(_, url)| seen.insert(url.clone()));
+    candidates
+}
+
+/// GET a file, falling back through the configured mirrors until one returns a
+/// successful response. Returns the response and the label of the mirror serving it.
+pub async fn get_with_fallback(url: &str) -> Result<(reqwest::Response, String), NetError> {
+    let client = client()?;
+    let mut failures: Vec<String> = Vec::new();
+
+    for (label, candidate) in mirror_candidates(url) {
+        match client.get(&candidate).send().await {
+            Ok(response) if response.status().is_success() => {
+                if !failures.is_empty() {
+                    log::info!("Downloading from mirror '{}' after {} failure(s)", label, failures.len());
+                }
+                return Ok((response, label));
+            }
+            Ok(response) => {
+                log::warn!("Mirror '{}' returned HTTP {} for {}", label, response.status(), candidate);
+                failures.push(format!("{}: HTTP {}", label, response.status()));
+            }
+            Err(e) => {
+                log::warn!("Mirror '{}' request failed for {}: {}", label, candidate, e);
+                failures.push(format!("{}: {}", label, e));
+            }
+        }
+    }
+
+    Err(NetError::AllMirrorsFailed(failures.join("; ")))
+}
+
+/// Byte counts, transfer rate and ETA of a download, sent with `download-progress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub percent: f64,
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+    /// Smoothed transfer rate over recent chunks
+    pub bytes_per_sec: u64,
+    /// Seconds left at the current rate; None until a rate is known
+    pub eta_secs: Option<u64>,
+}
+
+/// Shortest interval the rate is measured over; single chunks are too noisy.
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+/// Weight of the newest sample in the smoothed rate.
+const RATE_SMOOTHING: f64 = 0.3;
+
+/// Tracks one download (possibly spanning several files) to report progress.
+pub struct ProgressTracker {
+    total_bytes: u64,
+    downloaded_bytes: u64,
+    sample_start: Instant,
+    sample_bytes: u64,
+    rate: Option<f64>,
+}
+
+impl ProgressTracker {
+    pub fn new(total_bytes: u64) -> Self {
+        Self {
+            total_bytes,
+            downloaded_bytes: 0,
+            sample_start: Instant::now(),
+            sample_bytes: 0,
+            rate: None,
+        }
+    }
+
+    /// Count bytes that were already on disk (they don't affect the rate).
+    pub fn skip(&mut self, bytes: u64) {
+        self.downloaded_bytes += bytes;
+    }
+
+    /// Count bytes just received.
+    pub fn advance(&mut self, bytes: u64) {
+        self.downloaded_bytes += bytes;
+        self.sample_bytes += bytes;
+        let elapsed = self.sample_start.elapsed();
+        if elapsed >= RATE_SAMPLE_INTERVAL {
+            let sample = self.sample_bytes as f64 / elapsed.as_secs_f64();
+            self.rate = Some(match self.rate {
+                Some(rate) => rate + RATE_SMOOTHING * (sample - rate),
+                None => sample,
+            });
+            self.sample_start = Instant::now();
+            self.sample_bytes = 0;
+        }
+    }
+
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.downloaded_bytes
+    }
+
+    /// Current progress, with `percent` capped at `max_percent` when a later
+    /// phase (e.g. extraction) owns the rest.
+    pub fn progress(&self, max_percent: f64) -> DownloadProgress {
+        let percent = if self.total_bytes > 0 {
+            (self.downloaded_bytes as f64 / self.total_bytes as f64 * 100.0).min(max_percent)
+        } else {
+            0.0
+        };
+        let remaining = self.total_bytes.saturating_sub(self.downloaded_bytes);
+        DownloadProgress {
+            percent,
+            downloaded_bytes: self.downloaded_bytes,
+            total_bytes: self.total_bytes,
+            bytes_per_sec: self.rate.unwrap_or(0.0) as u64,
+            eta_secs: self
+                .rate
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| (remaining as f64 / rate).ceil() as u64),
+        }
+    }
+
+    /// Progress once everything, including any post-processing, is done.
+    pub fn finished(&self) -> DownloadProgress {
+        let total = self.total_bytes.max(self.downloaded_bytes);
+        DownloadProgress {
+            percent: 100.0,
+            downloaded_bytes: total,
+            total_bytes: total,
+            bytes_per_sec: self.rate.unwrap_or(0.0) as u64,
+            eta_secs: Some(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hf_url() {
+        assert_eq!(
+            parse_hf_url("https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.bin"),
+            Some(("ggerganov/whisper.cpp", "main", "ggml-base.bin"))
+        );
+        assert_eq!(parse_hf_url("https://example.com/model.bin"), None);
+    }
+
+    #[test]
+    fn test_expand_mirror() {
+        let (label, url) = expand_mirror("hf-mirror", "a/b", "main", "f.bin");
+        assert_eq!(label, "hf-mirror");
+        assert_eq!(url, "https://hf-mirror.com/a/b/resolve/main/f.bin");
+
+        let (label, url) = expand_mirror("https://models.corp.example/", "a/b", "main", "f.bin");
+        assert_eq!(label, "models.corp.example");
+        assert_eq!(url, "https://models.corp.example/a/b/resolve/main/f.bin");
+
+        let (_, url) = expand_mirror("https://cdn.example/{file}", "a/b", "main", "f.bin");
+        assert_eq!(url, "https://cdn.example/f.bin");
+    }
+
+    #[test]
+    fn test_mirror_candidates_dedup() {
+        let mirrors = [
+            "huggingface".to_string(),
+            "hf-mirror".to_string(),
+            "https://huggingface.co/".to_string(),
+        ];
+        let candidates = candidates("https://huggingface.co/a/b/resolve/main/f.bin", &mirrors);
+        let urls: Vec<&str> = candidates.iter().map(|(_, url)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://huggingface.co/a/b/resolve/main/f.bin",
+                "https://hf-mirror.com/a/b/resolve/main/f.bin",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_progress_tracker() {
+        let mut tracker = ProgressTracker::new(1000);
+        tracker.skip(250);
+        tracker.advance(250);
+        let progress = tracker.progress(99.0);
+        assert_eq!(progress.percent, 50.0);
+        assert_eq!(progress.downloaded_bytes, 500);
+        assert_eq!(progress.eta_secs, None); // no rate sample yet
+
+        tracker.advance(500);
+        assert_eq!(tracker.progress(99.0).percent, 99.0);
+        assert_eq!(tracker.finished().percent, 100.0);
+    }
+}
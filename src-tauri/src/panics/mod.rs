@@ -0,0 +1,183 @@
+//! Surviving panics in worker threads.
+//!
+//! A panic on the audio or transcription threads used to end the thread and
+//! poison every mutex it held, so each later `.lock().unwrap()` panicked too
+//! and the app stayed broken until restart. Instead:
+//! - `LockExt` locks a mutex whatever a panicking thread left behind, taking
+//!   the data over or resetting it to its default;
+//! - `catch` runs a worker's body and turns a panic into a logged error, so
+//!   long-lived threads keep serving;
+//! - the panic hook keeps the last panic for `last`; `start_reporter` emits
+//!   each one as `last-panic` from its own thread, as the hook itself runs
+//!   on the panicking thread, maybe with app state locked.
+//!
+//! All of this relies on panics unwinding, which the release profile keeps.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+pub trait LockExt<T> {
+    /// Lock, taking the data over if a panicking thread poisoned the mutex.
+    fn lock_recover(&self) -> MutexGuard<'_, T>;
+
+    /// Lock, resetting the data to its default if the mutex was poisoned, for
+    /// state a panic may have left half-updated.
+    fn lock_or_reset(&self) -> MutexGuard<'_, T>
+    where
+        T: Default;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn lock_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(|poisoned| {
+            log::warn!("Taking over a mutex poisoned by a panic");
+            self.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    fn lock_or_reset(&self) -> MutexGuard<'_, T>
+    where
+        T: Default,
+    {
+        self.lock().unwrap_or_else(|poisoned| {
+            log::warn!("Resetting state left behind by a panic");
+            self.clear_poison();
+            let mut guard = poisoned.into_inner();
+            *guard = T::default();
+            guard
+        })
+    }
+}
+
+/// Payload of the `last-panic` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct PanicReport {
+    pub thread: String,
+    pub message: String,
+    /// file:line
+    pub location: Option<String>,
+    /// RFC 3339
+    pub at: String,
+}
+
+static LAST_PANIC: Lazy<Mutex<Option<PanicReport>>> = Lazy::new(|| Mutex::new(None));
+/// Panics recorded so far; `start_reporter` emits when it moves
+static PANIC_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Record every panic before the default hook prints it. Call first thing
+/// in `run`; `start_reporter` emits them.
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let report = PanicReport {
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            location: info.location().map(|l| format!("{}:{}", l.file(), l.line())),
+            at: chrono::Local::now().to_rfc3339(),
+        };
+        log::error!(
+            "Panic on thread '{}' at {}: {}",
+            report.thread,
+            report.location.as_deref().unwrap_or("unknown location"),
+            report.message
+        );
+        // try_lock: the panic may have happened while this very lock was held
+        if let Ok(mut last) = LAST_PANIC.try_lock() {
+            *last = Some(report);
+            PANIC_COUNT.fetch_add(1, Ordering::SeqCst);
+        }
+        default_hook(info);
+    }));
+}
+
+/// Emit each recorded panic as `last-panic`, including ones from before the
+/// app handle existed.
+pub fn start_reporter(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("panic-reporter".to_string())
+        .spawn(move || {
+            let mut reported = 0;
+            loop {
+                let count = PANIC_COUNT.load(Ordering::SeqCst);
+                if count != reported {
+                    reported = count;
+                    if let Some(report) = last() {
+                        app.emit("last-panic", &report).ok();
+                    }
+                }
+                std::thread::sleep(REPORT_INTERVAL);
+            }
+        })
+        .ok();
+}
+
+/// The most recent panic this session, if any.
+pub fn last() -> Option<PanicReport> {
+    LAST_PANIC.lock_recover().clone()
+}
+
+/// Run `body`, catching a panic instead of letting it unwind out of the
+/// thread. The hook has already reported it; this only notes that `what`
+/// carried on. None if it panicked.
+pub fn catch<R>(what: &str, body: impl FnOnce() -> R) -> Option<R> {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => Some(result),
+        Err(_) => {
+            log::error!("{} panicked and was stopped", what);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn poisoned(value: Vec<u32>) -> Arc<Mutex<Vec<u32>>> {
+        let mutex = Arc::new(Mutex::new(value));
+        let held = Arc::clone(&mutex);
+        std::thread::spawn(move || {
+            let _guard = held.lock().unwrap();
+            panic!("poison it");
+        })
+        .join()
+        .ok();
+        assert!(mutex.is_poisoned());
+        mutex
+    }
+
+    #[test]
+    fn test_lock_recover_keeps_data() {
+        let mutex = poisoned(vec![1, 2]);
+        assert_eq!(*mutex.lock_recover(), [1, 2]);
+        assert!(!mutex.is_poisoned());
+    }
+
+    #[test]
+    fn test_lock_or_reset_clears_data() {
+        let mutex = poisoned(vec![1, 2]);
+        assert!(mutex.lock_or_reset().is_empty());
+        assert!(mutex.lock().is_ok());
+    }
+
+    #[test]
+    fn test_catch() {
+        assert_eq!(catch("test", || 4), Some(4));
+        assert_eq!(catch("test", || -> u32 { panic!("boom") }), None);
+    }
+}
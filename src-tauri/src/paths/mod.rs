@@ -0,0 +1,120 @@
+//! Where the app keeps its files.
+//!
+//! - config: settings, dictionary, history, stats and the other small files
+//!   (`~/Library/Application Support/mentascribe` on macOS,
+//!   `%APPDATA%\mentascribe` on Windows, `~/.config/mentascribe` on Linux)
+//! - models: downloaded models, large and specific to the machine
+//!   (`~/Library/Application Support/mentascribe/models`,
+//!   `%LOCALAPPDATA%\mentascribe\models`, `~/.local/share/mentascribe/models`)
+//!
+//! `MENTASCRIBE_HOME` puts everything under one directory (portable installs,
+//! testing against a scratch profile); `MENTASCRIBE_MODELS_DIR` moves only the
+//! models, e.g. to a larger disk.
+//!
+//! Models used to be kept in `~/.mentascribe/models` on every platform;
+//! `migrate` moves them on startup.
+
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "mentascribe";
+const HOME_ENV: &str = "MENTASCRIBE_HOME";
+const MODELS_ENV: &str = "MENTASCRIBE_MODELS_DIR";
+
+fn env_dir(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_default()
+}
+
+/// Directory of the config files.
+pub fn config_dir() -> PathBuf {
+    if let Some(root) = env_dir(HOME_ENV) {
+        return root;
+    }
+    dirs::config_dir()
+        .unwrap_or_else(|| home_dir().join(".config"))
+        .join(APP_DIR)
+}
+
+fn default_models_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| home_dir().join(".local").join("share"))
+        .join(APP_DIR)
+        .join("models")
+}
+
+fn legacy_models_dir() -> PathBuf {
+    home_dir().join(".mentascribe").join("models")
+}
+
+/// Directory of the downloaded models.
+pub fn models_dir() -> PathBuf {
+    if let Some(dir) = env_dir(MODELS_ENV) {
+        return dir;
+    }
+    if let Some(root) = env_dir(HOME_ENV) {
+        return root.join("models");
+    }
+    let dir = default_models_dir();
+    let legacy = legacy_models_dir();
+    // `migrate` couldn't move them (e.g. a different disk): use them where they are
+    if !dir.exists() && legacy.is_dir() {
+        return legacy;
+    }
+    dir
+}
+
+/// Move files from where earlier versions kept them. Call at startup, before
+/// anything reads a path. Does nothing when a location is overridden.
+pub fn migrate() {
+    if env_dir(HOME_ENV).is_some() || env_dir(MODELS_ENV).is_some() {
+        return;
+    }
+    let (from, to) = (legacy_models_dir(), default_models_dir());
+    match move_dir(&from, &to) {
+        Ok(true) => log::info!("Moved models from {:?} to {:?}", from, to),
+        Ok(false) => {}
+        Err(e) => log::warn!("Couldn't move models from {:?} to {:?}, using them in place: {}", from, to, e),
+    }
+}
+
+/// Move the directory `from` to `to` unless `to` already exists, and remove
+/// `from`'s parent if that leaves it empty. Whether anything was moved.
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<bool> {
+    if !from.is_dir() || to.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(from, to)?;
+    if let Some(parent) = from.parent() {
+        // Only succeeds when empty
+        std::fs::remove_dir(parent).ok();
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_dir() {
+        let root = std::env::temp_dir().join(format!("mentascribe-paths-{}", std::process::id()));
+        let from = root.join("legacy").join("models");
+        let to = root.join("data").join("mentascribe").join("models");
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::write(from.join("ggml-small.bin"), b"model").unwrap();
+
+        assert!(move_dir(&from, &to).unwrap());
+        assert!(to.join("ggml-small.bin").is_file());
+        assert!(!root.join("legacy").exists());
+        // Nothing left to move
+        assert!(!move_dir(&from, &to).unwrap());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
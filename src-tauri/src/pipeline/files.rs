@@ -0,0 +1,357 @@
+//! Transcribing WAV files: ones dropped on the dictation pill, and ones
+//! copied to the clipboard (`transcribe_clipboard`).
+//!
+//! Files wait in one queue and are transcribed one at a time with the
+//! configured engine, then cleaned up, post-processed and saved like a
+//! dictation. The text goes on the clipboard rather than being injected:
+//! whatever has focus when a file finishes needn't be where it's wanted.
+//! Progress is emitted as `file-transcription-started`,
+//! `file-transcription-complete` and `file-transcription-error`.
+//!
+//! Files are read with hound, so only WAV is supported. The clipboard is read
+//! through arboard, which sees copied files and text holding their paths but
+//! not audio copied as raw data (from an audio editor or a browser), so that
+//! isn't supported: `transcribe_clipboard` takes WAV files only.
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use super::{Dictation, PipelineReport};
+use crate::audio::buffer::SampleBuffer;
+use crate::audio::capture::Dropouts;
+use crate::audio::AudioData;
+use crate::error::AppError;
+use crate::latency::TranscriptionTimings;
+use crate::panics::LockExt;
+use crate::transcription::Engine;
+use crate::{history, injection, privacy, text, AppState};
+
+const EXTENSIONS: &[&str] = &["wav", "wave"];
+
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Can't read {0}: {1}")]
+    DecodeError(String, hound::Error),
+    #[error("{0} isn't a WAV file; only WAV audio can be transcribed")]
+    Unsupported(String),
+    #[error("No WAV file on the clipboard; copied audio data isn't supported")]
+    NothingToTranscribe,
+    #[error("Clipboard error: {0}")]
+    ClipboardError(String),
+}
+
+/// Payload of `file-transcription-started`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStarted {
+    pub path: String,
+    /// Files still waiting after this one
+    pub queued: usize,
+}
+
+/// Payload of `file-transcription-complete`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscribed {
+    pub path: String,
+    pub text: String,
+    /// None when nothing was saved (incognito, or history failed)
+    pub entry_id: Option<String>,
+}
+
+/// Payload of `file-transcription-error`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileFailed {
+    pub path: String,
+    pub error: AppError,
+}
+
+static QUEUE: Lazy<Mutex<VecDeque<PathBuf>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// A worker is draining `QUEUE`.
+static WORKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Clears `WORKER_RUNNING` when the worker panics, so the next `enqueue`
+/// starts a new one instead of the queue stalling for the session.
+struct WorkerGuard;
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        // A normal exit clears it itself, under the queue lock
+        if std::thread::panicking() {
+            log::error!("[files] Worker panicked, {} files left queued", QUEUE.lock_recover().len());
+            WORKER_RUNNING.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+/// `%XX` escapes decoded; None if they don't make UTF-8.
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// A line of clipboard text as a path: the path itself, maybe quoted, or a
+/// `file://` URL.
+fn path_from_text(line: &str) -> Option<PathBuf> {
+    let line = line.trim().trim_matches(|c| c == '"' || c == '\'');
+    if line.is_empty() {
+        return None;
+    }
+    let Some(url_path) = line.strip_prefix("file://") else {
+        return Some(PathBuf::from(line));
+    };
+    let url_path = url_path.strip_prefix("localhost").unwrap_or(url_path);
+    let decoded = percent_decode(url_path)?;
+    // file:///C:/Users/... on Windows
+    let drive = decoded.len() > 2 && decoded.starts_with('/') && decoded.as_bytes()[2] == b':';
+    Some(PathBuf::from(if drive { &decoded[1..] } else { &decoded }))
+}
+
+/// Files on the clipboard: ones copied in a file manager, or text holding
+/// their paths, one per line.
+pub fn clipboard_files() -> Result<Vec<PathBuf>, FileError> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| FileError::ClipboardError(e.to_string()))?;
+    let candidates = match clipboard.get().file_list() {
+        Ok(files) if !files.is_empty() => files,
+        _ => clipboard
+            .get_text()
+            .map(|text| text.lines().filter_map(path_from_text).collect())
+            .unwrap_or_default(),
+    };
+    let files: Vec<PathBuf> = candidates.into_iter().filter(|p| p.is_file()).collect();
+    if files.is_empty() {
+        return Err(FileError::NothingToTranscribe);
+    }
+    Ok(files)
+}
+
+/// Read a WAV file as a recording at its own rate and channel count (the
+/// engine resamples it as it would a device's), and its length in ms.
+fn decode(path: &Path) -> Result<(AudioData, u32), FileError> {
+    let decode_error = |e| FileError::DecodeError(path.display().to_string(), e);
+    let reader = hound::WavReader::open(path).map_err(decode_error)?;
+    let spec = reader.spec();
+
+    let mut samples = SampleBuffer::default();
+    match spec.sample_format {
+        hound::SampleFormat::Float => {
+            for sample in reader.into_samples::<f32>() {
+                samples.push(sample.map_err(decode_error)?);
+            }
+        }
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+            for sample in reader.into_samples::<i32>() {
+                samples.push(sample.map_err(decode_error)? as f32 * scale);
+            }
+        }
+    }
+
+    let frames = samples.len() / spec.channels.max(1) as usize;
+    let duration_ms = (frames as u64 * 1000 / spec.sample_rate.max(1) as u64) as u32;
+    let audio = AudioData {
+        samples,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        whisper_samples: None,
+        preroll_ms: 0,
+        dropouts: Dropouts::default(),
+    };
+    Ok((audio, duration_ms))
+}
+
+fn fail(app: &AppHandle, path: &Path, error: AppError) {
+    log::warn!("[files] {:?}: {}", path, error);
+    app.emit("file-transcription-error", FileFailed { path: path.display().to_string(), error }).ok();
+}
+
+/// Queue `paths` for transcription, starting the worker if it isn't running.
+/// Files that aren't WAV are reported as `file-transcription-error` and
+/// skipped. Returns how many were queued.
+pub fn enqueue(app: &AppHandle, paths: Vec<PathBuf>) -> usize {
+    let mut queued = 0;
+    {
+        let mut queue = QUEUE.lock_recover();
+        for path in paths {
+            if is_audio_file(&path) {
+                queue.push_back(path);
+                queued += 1;
+            } else {
+                fail(app, &path, FileError::Unsupported(path.display().to_string()).into());
+            }
+        }
+    }
+    if queued > 0 && !WORKER_RUNNING.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { drain(&app).await });
+    }
+    queued
+}
+
+async fn drain(app: &AppHandle) {
+    let _guard = WorkerGuard;
+    loop {
+        let (path, queued) = {
+            let mut queue = QUEUE.lock_recover();
+            let Some(path) = queue.pop_front() else {
+                // Under the lock, so a file queued after this starts a new worker
+                WORKER_RUNNING.store(false, Ordering::SeqCst);
+                return;
+            };
+            (path, queue.len())
+        };
+
+        app.emit("file-transcription-started", FileStarted { path: path.display().to_string(), queued }).ok();
+        match transcribe(app, &path).await {
+            Ok((text, entry_id)) => {
+                log::info!("[files] Transcribed {:?}: {} chars", path, text.len());
+                let payload = FileTranscribed { path: path.display().to_string(), text, entry_id };
+                app.emit("file-transcription-complete", payload).ok();
+            }
+            Err(e) => fail(app, &path, e),
+        }
+    }
+}
+
+/// Transcribe one file through the dictation stages after capture, and put
+/// its text on the clipboard. Returns the text and its history entry id.
+async fn transcribe(app: &AppHandle, path: &Path) -> Result<(String, Option<String>), AppError> {
+    let settings = app.state::<AppState>().settings.lock_recover().clone();
+    if let Some(what) = crate::planned_uploads(&settings) {
+        privacy::gate_recording(&settings.privacy, &what)?;
+    }
+    let (audio, duration_ms) = decode(path)?;
+    let engine = Engine::from_settings(&settings);
+    let audio_uploaded = engine.uploads_audio(&settings);
+    let incognito = settings.privacy.incognito;
+    let mut report = PipelineReport::default();
+
+    let started_at = Local::now();
+    let start = Instant::now();
+    let transcript = super::transcribe_tail(audio, &[], false, engine, &settings).await;
+    privacy::record_session("file", started_at, Some(engine.id()), audio_uploaded, incognito);
+    let transcript = report.record("transcribe-tail", start, transcript)?;
+
+    let start = Instant::now();
+    let segments = super::merge(Vec::new(), transcript.tail_segment, false, duration_ms);
+    report.time("merge", start);
+
+    let profile = text::Profile::Prose;
+    let (cleaned, cleanup_sent) = super::cleanup(app, &transcript.raw_text, &settings, profile, &mut report).await;
+
+    let start = Instant::now();
+    let (text, dictionary_hits) = super::post_process_timed(&cleaned, &settings, profile, None, &mut report).await;
+    report.time("post-process", start);
+
+    let dictation = Dictation {
+        word_count: text.split_whitespace().count() as u32,
+        raw_text: transcript.raw_text,
+        text,
+        duration_ms,
+        segments,
+        timings: TranscriptionTimings::new(
+            0,
+            report.duration_ms("transcribe-tail"),
+            transcript.engine_timings,
+            report.duration_ms("post-process"),
+            duration_ms as f64 / 1000.0,
+        ),
+        dictionary_hits,
+        sent_to_third_party: audio_uploaded || cleanup_sent,
+        language: settings.transcription.language.clone(),
+    };
+
+    let start = Instant::now();
+    let entry_id = report
+        .record("persist", start, super::persist(app, &dictation, incognito))
+        .unwrap_or(None);
+
+    let start = Instant::now();
+    history::recent::push(&dictation.text);
+    let copied = if dictation.text.trim().is_empty() {
+        Ok(())
+    } else {
+        injection::copy_to_clipboard(&dictation.text)
+    };
+    let copied = report.record("output", start, copied);
+    app.emit("pipeline-report", &report).ok();
+    copied?;
+
+    Ok((dictation.text, entry_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_from_text() {
+        assert_eq!(path_from_text("  /tmp/a b.wav "), Some(PathBuf::from("/tmp/a b.wav")));
+        assert_eq!(path_from_text("'/tmp/memo.wav'"), Some(PathBuf::from("/tmp/memo.wav")));
+        assert_eq!(path_from_text("file:///tmp/a%20b.wav"), Some(PathBuf::from("/tmp/a b.wav")));
+        assert_eq!(path_from_text("file://localhost/tmp/x.wav"), Some(PathBuf::from("/tmp/x.wav")));
+        assert_eq!(path_from_text("file:///C:/Users/me/x.wav"), Some(PathBuf::from("C:/Users/me/x.wav")));
+        assert_eq!(path_from_text("   "), None);
+        assert!(is_audio_file(Path::new("/tmp/memo.WAV")));
+        assert!(!is_audio_file(Path::new("/tmp/memo.mp3")));
+    }
+
+    #[test]
+    fn test_decode() {
+        let path = std::env::temp_dir().join(format!("mentascribe-file-{}.wav", uuid::Uuid::new_v4()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..4000 {
+            writer.write_sample(i16::MAX).unwrap();
+            writer.write_sample(i16::MIN).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (audio, duration_ms) = decode(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(duration_ms, 500);
+        assert_eq!((audio.sample_rate, audio.channels, audio.samples.len()), (8000, 2, 8000));
+        let samples = audio.samples.to_vec();
+        assert!((samples[0] - 1.0).abs() < 1e-3 && samples[1] == -1.0);
+
+        assert!(matches!(decode(Path::new("/nonexistent.wav")), Err(FileError::DecodeError(..))));
+    }
+}
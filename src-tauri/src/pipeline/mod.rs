@@ -0,0 +1,756 @@
+//! The stages `stop_recording` runs once the hotkey is released:
+//!
+//! finalize-audio → transcribe-tail → merge → cleanup → post-process → persist → output
+//!
+//! Each stage takes the previous stage's output and is timed into a
+//! `PipelineReport` (emitted as `pipeline-report`), so a failure is attributed
+//! to the stage it happened in. Stages depend only on their inputs, which lets
+//! later ones re-run on their own: `reprocess_entry` runs post-process and
+//! persist again on a history entry's raw engine output without re-transcribing.
+//!
+//! Long recordings can also be finalized in chunks while recording continues
+//! (`recording.chunk_minutes`): `finalize_chunk` takes what streaming has
+//! transcribed so far through post-process, persist and output, and drops its
+//! audio, so memory stays bounded and a crash late in the session loses at
+//! most one chunk.
+//!
+//! Cleanup (`crate::cleanup`) only runs with `cleanup.enabled`, and only on
+//! the stop path: chunks are output without it. Post-process itself runs the
+//! configurable list of steps in `steps`.
+//!
+//! Audio files (dropped on the pill, or copied) go through the same stages
+//! after capture; see `files`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::Emitter;
+use thiserror::Error;
+
+use crate::audio::capture::AudioError;
+use crate::audio::{self, AudioData};
+use crate::latency::{self, TranscriptionTimings};
+use crate::settings::UserSettings;
+use crate::transcription::{self, Engine, EngineTimings, SegmentInfo};
+use crate::{cleanup, dictionary, history, injection, privacy, stats, storage, text};
+
+pub mod files;
+pub mod steps;
+
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error("Failed to stop audio capture: {0}")]
+    AudioError(#[from] AudioError),
+    #[error("Transcription failed: {0}")]
+    TranscriptionError(crate::error::AppError),
+    #[error("Failed to save to history: {0}")]
+    HistoryError(#[from] history::HistoryError),
+    #[error("History entry not found: {0}")]
+    EntryNotFound(String),
+    #[error("Failed to update the dictionary: {0}")]
+    DictionaryError(#[from] dictionary::DictionaryError),
+}
+
+/// Timing and outcome of one stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageReport {
+    /// "finalize-audio", "transcribe-tail", "merge", "cleanup", "post-process", "persist" or "output"
+    pub stage: String,
+    pub duration_ms: u64,
+    /// Set when the stage failed
+    pub error: Option<String>,
+}
+
+/// Stages of one run, in the order they ran.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PipelineReport {
+    pub stages: Vec<StageReport>,
+    /// Steps of the post-process stage, in the order they ran
+    #[serde(default)]
+    pub post_process: Vec<StageReport>,
+}
+
+impl PipelineReport {
+    /// Record a stage that started at `start`, passing its result through.
+    pub fn record<T, E: std::fmt::Display>(
+        &mut self,
+        stage: &str,
+        start: Instant,
+        result: Result<T, E>,
+    ) -> Result<T, E> {
+        let error = result.as_ref().err().map(|e| e.to_string());
+        if let Some(ref e) = error {
+            log::warn!("[pipeline] Stage {} failed: {}", stage, e);
+        }
+        self.stages.push(StageReport {
+            stage: stage.to_string(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            error,
+        });
+        result
+    }
+
+    /// Record a stage that can't fail.
+    pub fn time(&mut self, stage: &str, start: Instant) {
+        self.record(stage, start, Ok::<(), String>(())).ok();
+    }
+
+    pub fn duration_ms(&self, stage: &str) -> u64 {
+        self.stages
+            .iter()
+            .find(|s| s.stage == stage)
+            .map(|s| s.duration_ms)
+            .unwrap_or(0)
+    }
+
+    /// Emit the report and hand back the error that ended the run.
+    pub fn fail(&self, app: &tauri::AppHandle, error: PipelineError) -> crate::error::AppError {
+        app.emit("pipeline-report", self).ok();
+        error.into()
+    }
+}
+
+/// Audio left for the tail transcription, plus what streaming already produced.
+pub struct CapturedAudio {
+    /// `whisper_samples` holds only the tail not yet transcribed by streaming
+    pub audio: AudioData,
+    pub streaming_segments: Vec<SegmentInfo>,
+    /// Voxtral streaming transcribed everything; there is no tail
+    pub streamed_all: bool,
+    /// Length of the whole recording
+    pub duration_ms: u32,
+    pub tail_audio_secs: f64,
+    /// Set when the recording is shorter than `recording.min_duration_ms` but
+    /// has speech in it: a punctuation mark said on its own ("period") can be
+    /// that short. Only such a mark is kept; any other text is dropped, as
+    /// for a recording that's too short.
+    pub short: Option<TooShort>,
+}
+
+/// A recording under `recording.min_duration_ms`.
+#[derive(Debug, Clone, Copy)]
+pub struct TooShort {
+    pub captured_ms: u32,
+    pub min_duration_ms: u32,
+}
+
+pub enum Finalized {
+    Captured(CapturedAudio),
+    /// Shorter than `recording.min_duration_ms` with no speech in it, so it
+    /// isn't transcribed
+    TooShort(TooShort),
+}
+
+/// Held while a chunk is finalized. `finalize_audio` takes it too, so a stop
+/// waits for a chunk in flight and the final text is output after it.
+static CHUNK_LOCK: Mutex<()> = Mutex::new(());
+
+/// Stage 1: stop streaming and capture, and cut the audio down to the tail.
+/// `audio_uploaded`: the engine sends the audio off the machine
+/// (`Engine::uploads_audio`).
+pub fn finalize_audio(
+    engine: Engine,
+    audio_uploaded: bool,
+    min_duration_ms: u32,
+    incognito: bool,
+) -> Result<Finalized, PipelineError> {
+    let _chunk = CHUNK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Stop streaming monitor first (ensures all in-progress transcriptions complete
+    // before we stop capture). Returns accumulated results and consumed sample count.
+    diag!(Engine, "[recording] Stopping streaming monitor (engine={})...", engine.id());
+
+    let (streaming_segments, consumed_samples) = engine.backend().stop_streaming();
+
+    // Stop audio capture and get audio data
+    diag!(Engine, "[recording] Stopping audio capture...");
+    let mut audio_data = audio::capture::stop_capture()?;
+    diag!(Engine,
+        "[recording] Audio captured: {} samples at {}Hz ({:.2}s)",
+        audio_data.samples.len(),
+        audio_data.sample_rate,
+        audio_data.samples.len() as f32 / audio_data.sample_rate as f32
+    );
+
+    // Local engines run on-device; only Apple Speech may use Apple's servers
+    privacy::end_dictation(Some(engine.id()), audio_uploaded, incognito);
+
+    // Accidental taps: skip inference entirely rather than risk injecting a hallucination.
+    // Pre-roll audio was captured before the press, so it doesn't count towards the minimum.
+    // Once chunks have been output this is the end of a long recording, however short.
+    let trimmed = audio::capture::trimmed_whisper_samples();
+    let captured_ms = ((audio_data.samples.len() as f32 / audio_data.sample_rate as f32 * 1000.0) as u32)
+        .saturating_sub(audio_data.preroll_ms);
+    let short = (trimmed == 0 && captured_ms < min_duration_ms).then_some(TooShort {
+        captured_ms,
+        min_duration_ms,
+    });
+    if let Some(short) = short {
+        let speech = audio_data
+            .whisper_samples
+            .as_ref()
+            .is_some_and(|ws| audio::vad::contains_speech(&ws.to_vec()));
+        if !speech {
+            return Ok(Finalized::TooShort(short));
+        }
+        diag!(Engine,
+            "[recording] Short recording ({}ms < {}ms) has speech, transcribing in case it's a punctuation mark",
+            captured_ms, min_duration_ms
+        );
+    }
+
+    // Trim whisper_samples to only the tail (audio not yet transcribed by streaming).
+    // This dramatically reduces inference time on stop — only the final partial utterance
+    // needs processing instead of the entire recording. Whisper's consumed count is from
+    // the start of the recording, and the buffer starts after any chunks already output.
+    let consumed_samples = if consumed_samples == usize::MAX {
+        consumed_samples
+    } else {
+        consumed_samples.saturating_sub(trimmed)
+    };
+    if consumed_samples > 0 {
+        if let Some(ref mut ws) = audio_data.whisper_samples {
+            if consumed_samples < ws.len() {
+                let tail_len = ws.len() - consumed_samples;
+                diag!(Engine,
+                    "[recording] Trimming whisper buffer: {} total -> {} tail ({:.2}s)",
+                    ws.len(),
+                    tail_len,
+                    tail_len as f32 / 16000.0
+                );
+                let tail_start = ws.start() + consumed_samples;
+                ws.drop_before(tail_start);
+            } else {
+                diag!(Engine,
+                    "[recording] All audio consumed by streaming ({} >= {}), no tail",
+                    consumed_samples,
+                    ws.len()
+                );
+                ws.clear();
+            }
+        }
+    }
+
+    let duration_ms = (audio_data.samples.len() as f32 / audio_data.sample_rate as f32 * 1000.0) as u32;
+    let tail_audio_secs = audio_data
+        .whisper_samples
+        .as_ref()
+        .map(|ws| ws.len() as f64 / 16000.0)
+        .unwrap_or(duration_ms as f64 / 1000.0);
+
+    Ok(Finalized::Captured(CapturedAudio {
+        audio: audio_data,
+        streaming_segments,
+        // Voxtral streaming processes ALL audio incrementally (including finish()),
+        // and reports usize::MAX consumed when it did
+        streamed_all: engine == Engine::Voxtral && consumed_samples == usize::MAX,
+        duration_ms,
+        tail_audio_secs,
+        short,
+    }))
+}
+
+/// Finalize the part of a long recording that streaming has transcribed so
+/// far, while recording continues: post-process and persist it as its own
+/// dictation, emit it as `transcription-chunk` for the frontend to inject, and
+/// drop its audio from the capture buffers. Does nothing once `recording` is
+/// cleared, as the stop path then owns the remaining results.
+pub fn finalize_chunk(
+    app: &tauri::AppHandle,
+    recording: &AtomicBool,
+    engine: Engine,
+    settings: &UserSettings,
+    profile: text::Profile,
+    app_id: Option<&str>,
+    incognito: bool,
+) {
+    let _chunk = CHUNK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if !recording.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let (mut segments, consumed_samples) = engine.backend().take_streaming_results();
+
+    // Transcribed audio goes even when it produced no text (silence, suppressed hallucinations)
+    let dropped = audio::capture::trim_consumed(consumed_samples);
+    if segments.is_empty() {
+        return;
+    }
+    let duration_ms = (dropped / 16) as u32;
+    if engine == Engine::Voxtral {
+        for segment in segments.iter_mut() {
+            segment.duration_ms = duration_ms;
+        }
+    }
+
+    let raw_text = segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let processed = steps::run(&raw_text, settings, profile, app_id);
+    let dictation = Dictation {
+        word_count: processed.text.split_whitespace().count() as u32,
+        raw_text,
+        text: processed.text,
+        duration_ms,
+        segments,
+        timings: TranscriptionTimings::default(),
+        dictionary_hits: processed.dictionary_hits,
+        sent_to_third_party: engine.uploads_audio(settings),
+        language: settings.transcription.language.clone(),
+    };
+    diag!(Engine,
+        "[recording] Finalized chunk: {} segments, {} words, {:.1}s of audio released",
+        dictation.segments.len(),
+        dictation.word_count,
+        dropped as f32 / 16000.0
+    );
+
+    if let Err(e) = persist(app, &dictation, incognito) {
+        log::warn!("[recording] Failed to save chunk to history: {}", e);
+    }
+    if !dictation.text.trim().is_empty() {
+        history::recent::push(&dictation.text);
+        app.emit("transcription-chunk", &dictation.text).ok();
+    }
+}
+
+/// Engine output for the whole recording (streaming prefix + tail).
+pub struct Transcript {
+    pub raw_text: String,
+    pub tail_segment: Option<SegmentInfo>,
+    pub engine_timings: EngineTimings,
+}
+
+/// Stage 2: transcribe the tail and join it onto the streaming prefix.
+pub async fn transcribe_tail(
+    audio: AudioData,
+    streaming_segments: &[SegmentInfo],
+    streamed_all: bool,
+    engine: Engine,
+    settings: &UserSettings,
+) -> Result<Transcript, PipelineError> {
+    let streaming_prefix = if streaming_segments.is_empty() {
+        diag!(Engine, "[recording] No streaming results (no completed utterances detected)");
+        None
+    } else {
+        let prefix = streaming_prefix(streaming_segments);
+        diag!(Engine,
+            "[recording] Streaming results: {} segments, prefix='{}...'",
+            streaming_segments.len(),
+            if prefix.len() > 60 { &prefix[..60] } else { &prefix }
+        );
+        Some(prefix)
+    };
+
+    let (raw_text, tail_segment) = if streamed_all {
+        // Voxtral streaming already processed everything. No tail needed.
+        let text = streaming_prefix.unwrap_or_default();
+        diag!(Engine,
+            "[recording] Voxtral streaming handled all audio, skipping tail transcription (text='{}')",
+            if text.len() > 60 { &text[..60] } else { &text }
+        );
+        (text, None)
+    } else {
+        // Voxtral gets here when streaming wasn't active (model not loaded) and runs one-shot
+        diag!(Engine, "[recording] Starting {} transcription...", engine.id());
+        engine
+            .backend()
+            .transcribe(audio, settings, streaming_prefix)
+            .await
+            .map_err(PipelineError::TranscriptionError)?
+    };
+    diag!(Engine,
+        "[recording] Transcription complete: '{}' ({} chars)",
+        if raw_text.len() > 100 {
+            format!("{}...", &raw_text[..100])
+        } else {
+            raw_text.clone()
+        },
+        raw_text.len()
+    );
+
+    let engine_timings = engine.backend().take_timings();
+    Ok(Transcript {
+        raw_text,
+        tail_segment,
+        engine_timings,
+    })
+}
+
+/// Raw text of the utterances streaming transcribed during recording.
+pub fn streaming_prefix(streaming_segments: &[SegmentInfo]) -> String {
+    // Whisper segments are separate sentences that need a space between them.
+    streaming_segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Tail-first delivery (`output.tail_first`): the post-processed streaming
+/// prefix, emitted as `transcription-prefix` for the frontend to inject while
+/// the tail is still being transcribed. None when there's no prefix, or no
+/// tail to wait for.
+pub async fn deliver_prefix(
+    app: &tauri::AppHandle,
+    captured: &CapturedAudio,
+    settings: &UserSettings,
+    profile: text::Profile,
+    app_id: Option<&str>,
+) -> Option<String> {
+    if !settings.output.tail_first.unwrap_or(false)
+        || captured.streamed_all
+        || captured.streaming_segments.is_empty()
+    {
+        return None;
+    }
+    let prefix = post_process_async(&streaming_prefix(&captured.streaming_segments), settings, profile, app_id).await;
+    if prefix.trim().is_empty() {
+        return None;
+    }
+    diag!(Engine, "[recording] Delivering streaming prefix ({} chars) before the tail", prefix.len());
+    app.emit("transcription-prefix", &prefix).ok();
+    Some(prefix)
+}
+
+/// What's left to inject of `text` once `prefix` is in. Post-processing the
+/// whole text usually reproduces the prefix exactly; when it doesn't (a
+/// dictionary phrase or spoken address across the boundary), the tail is
+/// post-processed on its own so the prefix is never typed twice.
+pub fn remainder_after(text: &str, prefix: &str, tail: impl FnOnce() -> String) -> String {
+    match text.strip_prefix(prefix) {
+        Some(rest) => rest.to_string(),
+        None => {
+            diag!(Engine, "[recording] Final text diverged from the delivered prefix, injecting the tail alone");
+            let tail = tail();
+            if tail.trim().is_empty() {
+                String::new()
+            } else {
+                format!(" {}", tail.trim_start())
+            }
+        }
+    }
+}
+
+/// Stage 3: the segments the final text was assembled from, in order.
+pub fn merge(
+    mut streaming_segments: Vec<SegmentInfo>,
+    tail_segment: Option<SegmentInfo>,
+    streamed_all: bool,
+    duration_ms: u32,
+) -> Vec<SegmentInfo> {
+    // A Voxtral stream that covered everything spans the whole recording
+    if streamed_all {
+        for segment in streaming_segments.iter_mut() {
+            segment.duration_ms = duration_ms;
+        }
+    }
+    streaming_segments.extend(tail_segment);
+    streaming_segments
+}
+
+/// Stage 3b: LLM cleanup of prose with `cleanup.enabled`, emitting
+/// `cleanup-processing` (the provider) and `cleanup-complete`
+/// (`CleanupReport`). Returns the text to post-process, which is `raw_text`
+/// when cleanup is off or fails, and whether it was sent off the machine.
+pub async fn cleanup(
+    app: &tauri::AppHandle,
+    raw_text: &str,
+    settings: &UserSettings,
+    profile: text::Profile,
+    report: &mut PipelineReport,
+) -> (String, bool) {
+    let skip = !settings.cleanup.enabled
+        || profile == text::Profile::Code
+        || raw_text.trim().is_empty()
+        || text::symbols::punctuation_only(raw_text).is_some();
+    if skip {
+        return (raw_text.to_string(), false);
+    }
+
+    app.emit("cleanup-processing", cleanup::provider_name(&settings.cleanup)).ok();
+    let start = Instant::now();
+    let (result, cleanup_report) = cleanup::clean(raw_text, settings).await;
+    app.emit("cleanup-complete", &cleanup_report).ok();
+    let text = report
+        .record("cleanup", start, result)
+        .unwrap_or_else(|_| raw_text.to_string());
+    (text, cleanup_report.sent_to_third_party)
+}
+
+/// Stage 4: the configured post-process steps (by default punctuation,
+/// casing, spoken addresses, number separators and dictionary replacements),
+/// or in the code profile spoken symbols and the steps that apply to code.
+/// Dictionary entries scoped to apps only apply when `app_id` is one of them.
+pub fn post_process(raw_text: &str, settings: &UserSettings, profile: text::Profile, app_id: Option<&str>) -> String {
+    steps::run(raw_text, settings, profile, app_id).text
+}
+
+/// The steps on a blocking thread, for async callers: the punctuation model
+/// and plugins can run for a while. If the steps panic, the text is returned
+/// unprocessed.
+async fn run_steps_blocking(
+    raw_text: &str,
+    settings: &UserSettings,
+    profile: text::Profile,
+    app_id: Option<&str>,
+) -> steps::Processed {
+    let text = raw_text.to_string();
+    let settings = settings.clone();
+    let app_id = app_id.map(str::to_string);
+    tokio::task::spawn_blocking(move || steps::run(&text, &settings, profile, app_id.as_deref()))
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("[recording] Post-process failed: {}", e);
+            steps::Processed {
+                text: raw_text.to_string(),
+                steps: Vec::new(),
+                dictionary_hits: Vec::new(),
+            }
+        })
+}
+
+/// `post_process` from async code (see `run_steps_blocking`).
+pub async fn post_process_async(
+    raw_text: &str,
+    settings: &UserSettings,
+    profile: text::Profile,
+    app_id: Option<&str>,
+) -> String {
+    run_steps_blocking(raw_text, settings, profile, app_id).await.text
+}
+
+/// `post_process` for the text that will be saved: each step's timing is
+/// recorded into `report`, and the dictionary entries that fired are
+/// returned for `Dictation::dictionary_hits`.
+pub async fn post_process_timed(
+    raw_text: &str,
+    settings: &UserSettings,
+    profile: text::Profile,
+    app_id: Option<&str>,
+    report: &mut PipelineReport,
+) -> (String, Vec<String>) {
+    let processed = run_steps_blocking(raw_text, settings, profile, app_id).await;
+    report.post_process.extend(processed.steps);
+    (processed.text, processed.dictionary_hits)
+}
+
+/// With `output.localize_numbers`, numbers as written for the dictation
+/// language (English when it's auto-detected) in the output locale's format.
+fn localize_numbers(text: &str, settings: &UserSettings) -> String {
+    if !settings.output.localize_numbers.unwrap_or(false) {
+        return text.to_string();
+    }
+    let language = settings
+        .transcription
+        .language
+        .as_deref()
+        .filter(|l| *l != "auto")
+        .unwrap_or("en");
+    text::numbers::localize(
+        text,
+        text::numbers::NumberFormat::for_locale(language),
+        text::numbers::NumberFormat::from_settings(&settings.output),
+    )
+}
+
+/// The code profile for apps listed in `output.code_apps`, else prose.
+pub fn profile_for_app(settings: &UserSettings, app_id: Option<&str>) -> text::Profile {
+    match app_id {
+        Some(app_id) if settings.output.code_apps.iter().any(|a| a.eq_ignore_ascii_case(app_id)) => {
+            text::Profile::Code
+        }
+        _ => text::Profile::Prose,
+    }
+}
+
+/// Run local punctuation restoration when it's enabled for the configured language
+/// and the engine returned (mostly) unpunctuated text. Falls back to the input on error.
+fn restore_punctuation(text: String, settings: &UserSettings) -> String {
+    let punctuation = &settings.punctuation;
+    if !punctuation.enabled || !text::needs_punctuation(&text) {
+        return text;
+    }
+    let language = settings.transcription.language.as_deref().unwrap_or("auto");
+    if !punctuation.languages.is_empty()
+        && !punctuation.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+    {
+        return text;
+    }
+
+    #[cfg(feature = "punctuation")]
+    {
+        match text::punctuation::restore(&text) {
+            Ok(restored) => restored,
+            Err(e) => {
+                log::warn!("[recording] Punctuation restoration failed: {}", e);
+                text
+            }
+        }
+    }
+    #[cfg(not(feature = "punctuation"))]
+    {
+        text
+    }
+}
+
+/// A finished transcription, ready to persist and output.
+pub struct Dictation {
+    pub raw_text: String,
+    pub text: String,
+    pub word_count: u32,
+    pub duration_ms: u32,
+    pub segments: Vec<SegmentInfo>,
+    pub timings: TranscriptionTimings,
+    /// Dictionary entries that fired, counted when the dictation is saved
+    pub dictionary_hits: Vec<String>,
+    /// Audio or text was sent to a third party (`Engine::uploads_audio`, cleanup)
+    pub sent_to_third_party: bool,
+    /// `transcription.language` for the session ("auto" or None: detected)
+    pub language: Option<String>,
+}
+
+/// Stage 5: save to history and stats. Incognito dictations are never persisted.
+/// Returns the history entry id, if one was saved. A full disk is reported as
+/// `storage-full`; the dictation is still output and kept in `history::recent`.
+pub fn persist(app: &tauri::AppHandle, dictation: &Dictation, incognito: bool) -> Result<Option<String>, PipelineError> {
+    let repetition_loops = transcription::hallucination::take_loops_detected();
+    let temperature_fallbacks = transcription::whisper::take_temperature_fallbacks();
+    if incognito {
+        diag!(Engine, "[recording] Incognito session: skipping history and stats");
+        return Ok(None);
+    }
+
+    // Stats are best-effort; a history failure fails the stage
+    if let Err(e) = stats::record_transcription(dictation.word_count, dictation.duration_ms) {
+        log::warn!("[recording] Failed to record stats: {}", storage::write_error(app, "stats", &e));
+    }
+    if repetition_loops > 0 {
+        diag!(Engine, "[recording] Truncated {} repetition loop(s)", repetition_loops);
+        if let Err(e) = stats::record_repetition_loops(repetition_loops) {
+            log::warn!("[recording] Failed to record stats: {}", storage::write_error(app, "stats", &e));
+        }
+    }
+    if temperature_fallbacks > 0 {
+        match stats::record_temperature_fallbacks(temperature_fallbacks) {
+            Ok(Some(hint)) => {
+                app.emit("accuracy-hint", &hint).ok();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!("[recording] Failed to record stats: {}", storage::write_error(app, "stats", &e));
+            }
+        }
+    }
+    if let Err(e) = dictionary::record_hits(&dictation.dictionary_hits) {
+        log::warn!(
+            "[recording] Failed to record dictionary usage: {}",
+            storage::write_error(app, "dictionary", &e)
+        );
+    }
+
+    let app_id = injection::detect::frontmost_app_id();
+    let entry = history::add_entry(history::NewEntry {
+        text: &dictation.text,
+        raw_text: &dictation.raw_text,
+        word_count: dictation.word_count,
+        duration_ms: dictation.duration_ms,
+        segments: dictation.segments.clone(),
+        timings: Some(dictation.timings.clone()),
+        app_id: app_id.as_deref(),
+        sent_to_third_party: dictation.sent_to_third_party,
+        language: dictation.language.as_deref(),
+    })
+    .inspect_err(|e| {
+        storage::write_error(app, "history", e);
+    })?;
+    Ok(Some(entry.id))
+}
+
+/// Stage 6: hand the text to the frontend for injection. `injected` is what
+/// it still injects: the whole text, or what follows a tail-first prefix.
+pub fn output(app: &tauri::AppHandle, dictation: &Dictation, injected: &str, entry_id: Option<String>) {
+    // Timings are emitted once the text has been injected so injection time is
+    // included; with nothing to inject they're final now.
+    if injected.trim().is_empty() {
+        app.emit("transcription-timings", &dictation.timings).ok();
+    } else {
+        latency::set_pending(entry_id, dictation.timings.clone());
+    }
+
+    history::recent::push(&dictation.text);
+    crate::events::discard_pending("transcription-partial");
+    app.emit("transcription-complete", &dictation.text).ok();
+}
+
+/// Re-run post-process and persist on a history entry's raw engine output with
+/// the current settings (e.g. after adding dictionary entries). Stats keep the
+/// original word count. The profile follows the entry's app, as the hotkey it
+/// was started with isn't recorded.
+pub fn reprocess_entry(
+    id: &str,
+    settings: &UserSettings,
+) -> Result<history::TranscriptionEntry, PipelineError> {
+    let entry = history::get_entry(id)?.ok_or_else(|| PipelineError::EntryNotFound(id.to_string()))?;
+    let raw_text = entry.raw_text.unwrap_or(entry.text);
+    let profile = profile_for_app(settings, entry.app_id.as_deref());
+    let text = post_process(&raw_text, settings, profile, entry.app_id.as_deref());
+    history::update_text(id, &text, &raw_text)?
+        .ok_or_else(|| PipelineError::EntryNotFound(id.to_string()))
+}
+
+/// How many of the latest history entries a dictionary correction is
+/// re-applied to.
+const CORRECTION_REAPPLY_ENTRIES: u32 = 50;
+
+/// A dictionary entry made from a correction, and the history entries it was
+/// re-applied to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correction {
+    pub entry: dictionary::DictionaryEntry,
+    pub reprocessed: Vec<history::TranscriptionEntry>,
+}
+
+/// Turn a correction of a transcription ("get hub" should have been "GitHub")
+/// into a dictionary entry. With `reapply`, the latest history entries whose
+/// engine output has the same mistake are reprocessed with it.
+pub fn apply_correction(
+    wrong: &str,
+    right: &str,
+    reapply: bool,
+    settings: &UserSettings,
+) -> Result<Correction, PipelineError> {
+    let entry = dictionary::upsert_correction(wrong, right)?;
+    let mut reprocessed = Vec::new();
+    if reapply {
+        for recent in history::get_history(Some(CORRECTION_REAPPLY_ENTRIES), None)? {
+            let raw_text = recent.raw_text.as_deref().unwrap_or(&recent.text);
+            if dictionary::mentions(raw_text, wrong) {
+                reprocessed.push(reprocess_entry(&recent.id, settings)?);
+            }
+        }
+        diag!(Engine, "[dictionary] Re-applied \"{}\" to {} history entries", wrong, reprocessed.len());
+    }
+    Ok(Correction { entry, reprocessed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remainder_after() {
+        let tail = || "never used".to_string();
+        assert_eq!(remainder_after("Hello there. How are you?", "Hello there.", tail), " How are you?");
+        assert_eq!(remainder_after("Hello there.", "Hello there.", tail), "");
+        assert_eq!(
+            remainder_after("Email me at jo@example.com", "Email me at jo dot", || "example dot com".to_string()),
+            " example dot com"
+        );
+        assert_eq!(remainder_after("Hi", "Hello", String::new), "");
+    }
+}
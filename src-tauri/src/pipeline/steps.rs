@@ -0,0 +1,214 @@
+//! The post-process stage as an ordered list of steps
+//! (`output.post_process_steps`).
+//!
+//! Each step is a text-to-text transformation; the list decides which run
+//! and in what order. Steps with a setting of their own still honour it
+//! (punctuation restoration needs `punctuation.enabled`, numbers
+//! `output.localize_numbers`), so the default list behaves as post-process
+//! did before it was configurable. Every step that runs is timed into
+//! `PipelineReport::post_process`. `plugin:<name>` runs an installed plugin
+//! (see `crate::plugins`).
+
+use std::time::Instant;
+
+use super::StageReport;
+use crate::settings::{OutputSettings, UserSettings};
+use crate::{dictionary, plugins, text};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Punctuation,
+    FillerRemoval,
+    Casing,
+    /// Spoken email and web addresses; after casing, which would capitalize
+    /// after each "."
+    Addresses,
+    Numbers,
+    Profanity,
+    Dictionary,
+    /// An installed plugin, by manifest name
+    Plugin(String),
+}
+
+/// Used when `output.post_process_steps` is unset.
+pub const DEFAULT_STEPS: &[Step] = &[Step::Punctuation, Step::Casing, Step::Addresses, Step::Numbers, Step::Dictionary];
+
+impl Step {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "punctuation" => Some(Self::Punctuation),
+            "filler-removal" => Some(Self::FillerRemoval),
+            "casing" => Some(Self::Casing),
+            "addresses" => Some(Self::Addresses),
+            "numbers" => Some(Self::Numbers),
+            "profanity" => Some(Self::Profanity),
+            "dictionary" => Some(Self::Dictionary),
+            _ => name
+                .strip_prefix("plugin:")
+                .filter(|plugin| !plugin.is_empty())
+                .map(|plugin| Self::Plugin(plugin.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Plugin(plugin) => return format!("plugin:{}", plugin),
+            Self::Punctuation => "punctuation",
+            Self::FillerRemoval => "filler-removal",
+            Self::Casing => "casing",
+            Self::Addresses => "addresses",
+            Self::Numbers => "numbers",
+            Self::Profanity => "profanity",
+            Self::Dictionary => "dictionary",
+        }
+        .to_string()
+    }
+
+    /// The code profile turns spoken symbols into code first and skips the
+    /// steps written for prose. Plugins are told the profile and decide.
+    fn applies_to(&self, profile: text::Profile) -> bool {
+        match self {
+            Self::Profanity | Self::Dictionary | Self::Plugin(_) => true,
+            _ => profile == text::Profile::Prose,
+        }
+    }
+
+    /// `hits` collects the dictionary entries that fired.
+    fn apply(
+        &self,
+        text: String,
+        settings: &UserSettings,
+        profile: text::Profile,
+        app_id: Option<&str>,
+        hits: &mut Vec<String>,
+    ) -> Result<String, String> {
+        Ok(match self {
+            Self::Punctuation => super::restore_punctuation(text, settings),
+            Self::FillerRemoval => text::filler::remove(&text),
+            // The configured casing, or a spoken override
+            Self::Casing => text::process_text(&text, text::Casing::from_settings(&settings.output)),
+            Self::Addresses => text::address::normalize(&text),
+            Self::Numbers => super::localize_numbers(&text, settings),
+            Self::Profanity => text::profanity::mask(&text),
+            Self::Dictionary => {
+                let (replaced, fired) = dictionary::apply_replacements(&text, profile, app_id)
+                    .map_err(|e| e.to_string())?;
+                hits.extend(fired);
+                replaced
+            }
+            Self::Plugin(plugin) => {
+                plugins::run(plugin, &text, profile, app_id, &settings.privacy).map_err(|e| e.to_string())?
+            }
+        })
+    }
+}
+
+/// The configured steps, in order. Unknown names are skipped.
+pub fn configured(output: &OutputSettings) -> Vec<Step> {
+    match output.post_process_steps {
+        Some(ref names) => names
+            .iter()
+            .filter_map(|name| {
+                let step = Step::from_name(name);
+                if step.is_none() {
+                    log::warn!("Unknown post-process step {:?}, skipping", name);
+                }
+                step
+            })
+            .collect(),
+        None => DEFAULT_STEPS.to_vec(),
+    }
+}
+
+/// Result of running the steps.
+pub struct Processed {
+    pub text: String,
+    /// Timing of each step that ran
+    pub steps: Vec<StageReport>,
+    /// Ids of the dictionary entries that fired, once per replacement
+    pub dictionary_hits: Vec<String>,
+}
+
+/// Run the configured steps on `raw_text`, bound for the app `app_id`. A step
+/// that fails leaves the text as it was and the rest still run.
+pub fn run(raw_text: &str, settings: &UserSettings, profile: text::Profile, app_id: Option<&str>) -> Processed {
+    // A punctuation mark said on its own is the mark, with nothing to process
+    if profile == text::Profile::Prose {
+        if let Some(mark) = text::symbols::punctuation_only(raw_text) {
+            return Processed {
+                text: mark.to_string(),
+                steps: Vec::new(),
+                dictionary_hits: Vec::new(),
+            };
+        }
+    }
+
+    let mut text = match profile {
+        text::Profile::Prose => raw_text.to_string(),
+        text::Profile::Code => text::code::apply(raw_text),
+    };
+    let mut reports = Vec::new();
+    let mut hits = Vec::new();
+
+    for step in configured(&settings.output) {
+        if !step.applies_to(profile) {
+            continue;
+        }
+        let start = Instant::now();
+        let error = match step.apply(text.clone(), settings, profile, app_id, &mut hits) {
+            Ok(processed) => {
+                text = processed;
+                None
+            }
+            Err(e) => {
+                log::warn!("[pipeline] Post-process step {} failed: {}", step.name(), e);
+                Some(e)
+            }
+        };
+        reports.push(StageReport {
+            stage: step.name(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            error,
+        });
+    }
+    Processed {
+        text,
+        steps: reports,
+        dictionary_hits: hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_steps() {
+        let mut output = OutputSettings::default();
+        assert_eq!(configured(&output), DEFAULT_STEPS);
+
+        output.post_process_steps = Some(vec!["filler-removal".into(), "llm".into(), "casing".into()]);
+        assert_eq!(configured(&output), [Step::FillerRemoval, Step::Casing]);
+
+        output.post_process_steps = Some(Vec::new());
+        assert!(configured(&output).is_empty());
+    }
+
+    #[test]
+    fn test_punctuation_mark_on_its_own() {
+        let settings = UserSettings::default();
+        assert_eq!(run("Question mark.", &settings, text::Profile::Prose, None).text, "?");
+        assert_eq!(run("Comma", &settings, text::Profile::Prose, None).text, ",");
+        assert!(run("Period.", &settings, text::Profile::Prose, None).steps.is_empty());
+    }
+
+    #[test]
+    fn test_step_names_round_trip() {
+        for step in DEFAULT_STEPS.iter().chain(&[Step::FillerRemoval, Step::Profanity]) {
+            assert_eq!(Step::from_name(&step.name()), Some(step.clone()));
+        }
+        let plugin = Step::Plugin("legal-citations".to_string());
+        assert_eq!(Step::from_name(&plugin.name()), Some(plugin));
+        assert_eq!(Step::from_name("plugin:"), None);
+    }
+}
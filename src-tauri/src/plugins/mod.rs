@@ -0,0 +1,401 @@
+//! Community text transforms as external executables.
+//!
+//! Each plugin is a directory under `<config dir>/plugins/` holding a
+//! `plugin.json` manifest and whatever it runs. Listing `plugin:<name>` in
+//! `output.post_process_steps` runs it as a post-process step: the text goes
+//! in as JSON on stdin and comes back as JSON on stdout.
+//!
+//! stdin:  `{"version": 1, "text": "...", "profile": "prose" | "code", "app_id": "..." | null}`
+//! stdout: `{"text": "..."}` or `{"error": "..."}`
+//!
+//! A plugin runs in its own directory with an emptied environment (only
+//! `PATH`), is killed after its timeout, and may write at most `MAX_OUTPUT`
+//! bytes. It is sandboxed (see `sandbox`): the filesystem is read-only and
+//! there is no network, so the text it's given can't leave the machine.
+//! Plugins still only run once the user has allowed them by name in
+//! `privacy.allowed_plugins`, and every run is written to the privacy ledger
+//! as third-party processing. A plugin that fails leaves the text as it was.
+
+mod sandbox;
+
+use crate::settings::PrivacySettings;
+use crate::text::Profile;
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+const MANIFEST: &str = "plugin.json";
+/// Version of the stdin/stdout contract sent to plugins.
+const API_VERSION: u32 = 1;
+const DEFAULT_TIMEOUT_MS: u64 = 2_000;
+/// Upper bound on `timeout_ms`; post-process holds up the insertion.
+const MAX_TIMEOUT_MS: u64 = 10_000;
+/// Bytes of stdout read from a plugin; more is an error.
+const MAX_OUTPUT: u64 = 1024 * 1024;
+/// Bytes of stderr kept for the error message.
+const MAX_STDERR: u64 = 2048;
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid manifest: {0}")]
+    ManifestError(String),
+    #[error("Plugin not found: {0}")]
+    NotFound(String),
+    #[error("Plugin {0} hasn't been allowed to run (Privacy settings)")]
+    NotAllowed(String),
+    #[error("Plugin timed out after {0}ms")]
+    Timeout(u64),
+    #[error("Plugin exited with {status}: {stderr}")]
+    Failed { status: String, stderr: String },
+    #[error("Plugin returned invalid output: {0}")]
+    InvalidOutput(String),
+    #[error("Plugin reported an error: {0}")]
+    Reported(String),
+    #[error("Plugins can't be sandboxed: {0}")]
+    NoSandbox(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    /// Lowercase letters, digits, '-' and '_'; the step is `plugin:<name>`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// A file in the plugin directory, or a program on PATH ("python3")
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Default 2000, at most 10000
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+impl PluginManifest {
+    fn parse(json: &str) -> Result<Self, PluginError> {
+        let manifest: Self = serde_json::from_str(json).map_err(|e| PluginError::ManifestError(e.to_string()))?;
+        let valid_name = !manifest.name.is_empty()
+            && manifest
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(PluginError::ManifestError(format!(
+                "name {:?} must be lowercase letters, digits, '-' or '_'",
+                manifest.name
+            )));
+        }
+        if manifest.command.trim().is_empty() {
+            return Err(PluginError::ManifestError("command is empty".to_string()));
+        }
+        Ok(manifest)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).clamp(1, MAX_TIMEOUT_MS))
+    }
+}
+
+/// A plugin directory as listed in settings, loaded or not.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    /// The manifest name, or the directory name if the manifest didn't load
+    pub name: String,
+    pub description: String,
+    pub dir: String,
+    /// Why the plugin can't be used, if it can't
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Plugin {
+    manifest: PluginManifest,
+    dir: PathBuf,
+}
+
+impl Plugin {
+    /// A file shipped with the plugin, else a program looked up on PATH.
+    fn program(&self) -> PathBuf {
+        let bundled = self.dir.join(&self.manifest.command);
+        if bundled.is_file() {
+            bundled
+        } else {
+            PathBuf::from(&self.manifest.command)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    version: u32,
+    text: &'a str,
+    profile: &'static str,
+    app_id: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct PluginResponse {
+    text: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Discovered {
+    plugins: Vec<Plugin>,
+    /// Directories that failed to load
+    broken: Vec<PluginInfo>,
+}
+
+impl Discovered {
+    /// Usable plugins first, then the broken ones.
+    fn infos(&self) -> Vec<PluginInfo> {
+        self.plugins
+            .iter()
+            .map(|p| PluginInfo {
+                name: p.manifest.name.clone(),
+                description: p.manifest.description.clone(),
+                dir: p.dir.display().to_string(),
+                error: None,
+            })
+            .chain(self.broken.iter().cloned())
+            .collect()
+    }
+}
+
+/// Loaded on first use and by `reload`.
+static PLUGINS: Lazy<RwLock<Option<Discovered>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn get_plugins_dir() -> PathBuf {
+    crate::paths::config_dir().join("plugins")
+}
+
+fn discover(root: &Path) -> Discovered {
+    let mut plugins: Vec<Plugin> = Vec::new();
+    let mut broken = Vec::new();
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Discovered::default();
+    };
+    let mut dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let loaded = std::fs::read_to_string(dir.join(MANIFEST))
+            .map_err(PluginError::from)
+            .and_then(|json| PluginManifest::parse(&json));
+        match loaded {
+            Ok(manifest) if plugins.iter().any(|p| p.manifest.name == manifest.name) => {
+                broken.push(PluginInfo {
+                    name: manifest.name.clone(),
+                    description: manifest.description,
+                    dir: dir.display().to_string(),
+                    error: Some(format!("Another plugin is already named {:?}", manifest.name)),
+                });
+            }
+            Ok(manifest) => plugins.push(Plugin { manifest, dir }),
+            Err(e) => {
+                log::warn!("[plugins] Skipping {}: {}", dir.display(), e);
+                broken.push(PluginInfo {
+                    name: dir.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                    description: String::new(),
+                    dir: dir.display().to_string(),
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+    log::info!("[plugins] {} plugin(s) in {}", plugins.len(), root.display());
+    Discovered { plugins, broken }
+}
+
+/// Scan the plugins directory again, e.g. after installing one.
+pub fn reload() -> Vec<PluginInfo> {
+    let found = discover(&get_plugins_dir());
+    let infos = found.infos();
+    if let Ok(mut cache) = PLUGINS.write() {
+        *cache = Some(found);
+    }
+    infos
+}
+
+/// Every plugin directory, usable ones first.
+pub fn list() -> Vec<PluginInfo> {
+    match PLUGINS.read().ok().and_then(|cache| cache.as_ref().map(Discovered::infos)) {
+        Some(infos) => infos,
+        None => reload(),
+    }
+}
+
+fn find(name: &str) -> Result<Plugin, PluginError> {
+    if PLUGINS.read().map(|cache| cache.is_none()).unwrap_or(true) {
+        reload();
+    }
+    PLUGINS
+        .read()
+        .ok()
+        .and_then(|cache| {
+            let discovered = cache.as_ref()?;
+            discovered.plugins.iter().find(|p| p.manifest.name == name).cloned()
+        })
+        .ok_or_else(|| PluginError::NotFound(name.to_string()))
+}
+
+/// Whether the user has allowed the plugin `name` to run.
+pub fn is_allowed(name: &str, privacy: &PrivacySettings) -> bool {
+    privacy.allowed_plugins.iter().any(|allowed| allowed == name)
+}
+
+/// Run the plugin `name` on `text`, if the user has allowed it, and record
+/// the run in the privacy ledger.
+pub fn run(
+    name: &str,
+    text: &str,
+    profile: Profile,
+    app_id: Option<&str>,
+    privacy: &PrivacySettings,
+) -> Result<String, PluginError> {
+    if !is_allowed(name, privacy) {
+        return Err(PluginError::NotAllowed(name.to_string()));
+    }
+    let plugin = find(name)?;
+    let started_at = Local::now();
+    let result = execute(&plugin, text, profile, app_id);
+    crate::privacy::record_plugin_run(name, started_at, privacy.incognito);
+    result
+}
+
+fn execute(plugin: &Plugin, text: &str, profile: Profile, app_id: Option<&str>) -> Result<String, PluginError> {
+    let name = &plugin.manifest.name;
+    let request = PluginRequest {
+        version: API_VERSION,
+        text,
+        profile: match profile {
+            Profile::Prose => "prose",
+            Profile::Code => "code",
+        },
+        app_id,
+    };
+    let input = serde_json::to_vec(&request).map_err(|e| PluginError::InvalidOutput(e.to_string()))?;
+
+    let mut command = sandbox::command(&plugin.program(), &plugin.manifest.args, &plugin.dir)?;
+    command
+        .current_dir(&plugin.dir)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    let mut child = command.spawn()?;
+
+    // Pipes are fed and drained on their own threads, so a plugin that
+    // writes before reading all its input can't deadlock against us
+    let stdin = child.stdin.take();
+    std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            stdin.write_all(&input).ok();
+        }
+    });
+    let stdout = child.stdout.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(stdout) = stdout {
+            stdout.take(MAX_OUTPUT + 1).read_to_end(&mut buf).ok();
+        }
+        buf
+    });
+    let stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(stderr) = stderr {
+            stderr.take(MAX_STDERR).read_to_end(&mut buf).ok();
+        }
+        buf
+    });
+
+    let timeout = plugin.manifest.timeout();
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            log::warn!("[plugins] {} timed out after {}ms, killed", name, timeout.as_millis());
+            return Err(PluginError::Timeout(timeout.as_millis() as u64));
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    };
+
+    let stdout = stdout_reader.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = stderr_reader.join().unwrap_or_default();
+        return Err(PluginError::Failed {
+            status: status.to_string(),
+            stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+        });
+    }
+    if stdout.len() as u64 > MAX_OUTPUT {
+        return Err(PluginError::InvalidOutput(format!("more than {} bytes", MAX_OUTPUT)));
+    }
+    parse_response(&stdout)
+}
+
+fn parse_response(stdout: &[u8]) -> Result<String, PluginError> {
+    let response: PluginResponse =
+        serde_json::from_slice(stdout).map_err(|e| PluginError::InvalidOutput(e.to_string()))?;
+    match (response.text, response.error) {
+        (_, Some(error)) => Err(PluginError::Reported(error)),
+        (Some(text), None) => Ok(text),
+        (None, None) => Err(PluginError::InvalidOutput("neither \"text\" nor \"error\"".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_validation() {
+        let manifest = PluginManifest::parse(r#"{"name": "medical-terms", "command": "run.sh"}"#).unwrap();
+        assert_eq!(manifest.timeout(), Duration::from_millis(DEFAULT_TIMEOUT_MS));
+
+        let slow = PluginManifest::parse(r#"{"name": "slow", "command": "x", "timeout_ms": 60000}"#).unwrap();
+        assert_eq!(slow.timeout(), Duration::from_millis(MAX_TIMEOUT_MS));
+
+        assert!(PluginManifest::parse(r#"{"name": "Has Spaces", "command": "x"}"#).is_err());
+        assert!(PluginManifest::parse(r#"{"name": "empty-command", "command": " "}"#).is_err());
+        assert!(PluginManifest::parse(r#"{"name": "no-command"}"#).is_err());
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let privacy = PrivacySettings {
+            allowed_plugins: vec!["medical-terms".to_string()],
+            ..Default::default()
+        };
+        assert!(is_allowed("medical-terms", &privacy));
+        assert!(!is_allowed("medical", &privacy));
+        assert!(!is_allowed("medical-terms", &PrivacySettings::default()));
+        assert!(matches!(
+            run("medical", "text", Profile::Prose, None, &privacy),
+            Err(PluginError::NotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(parse_response(br#"{"text": "BP 120/80"}"#).unwrap(), "BP 120/80");
+        assert!(matches!(parse_response(br#"{"error": "no model"}"#), Err(PluginError::Reported(_))));
+        assert!(matches!(parse_response(b"BP 120/80"), Err(PluginError::InvalidOutput(_))));
+        assert!(matches!(parse_response(b"{}"), Err(PluginError::InvalidOutput(_))));
+    }
+}
@@ -0,0 +1,93 @@
+//! Confinement for plugin processes.
+//!
+//! A plugin sees the filesystem read-only and has no network. On Linux it runs
+//! under bubblewrap (`bwrap`) in fresh namespaces with `/` bound read-only and
+//! a private `/tmp`; on macOS under `sandbox-exec` with a profile that denies
+//! network access and file writes. Where neither is available (no `bwrap`
+//! installed, Windows) plugins don't run at all rather than run unconfined.
+
+use super::PluginError;
+#[cfg(target_os = "linux")]
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+const SANDBOX_EXEC: &str = "/usr/bin/sandbox-exec";
+
+/// Later rules win: everything not listed is allowed, then network and writes
+/// are denied, then the few device files programs expect to write are allowed.
+#[cfg(target_os = "macos")]
+const PROFILE: &str = r#"(version 1)
+(allow default)
+(deny network*)
+(deny file-write*)
+(allow file-write* (literal "/dev/null") (literal "/dev/zero") (literal "/dev/dtracehelper"))"#;
+
+/// A command that runs `program args` confined. The caller sets `dir` as the
+/// working directory too; bwrap needs it again inside the new namespaces.
+#[cfg(target_os = "linux")]
+pub fn command(program: &Path, args: &[String], dir: &Path) -> Result<Command, PluginError> {
+    let bwrap = find_on_path("bwrap").ok_or_else(|| {
+        PluginError::NoSandbox("bubblewrap (bwrap) isn't installed; install it to run plugins".to_string())
+    })?;
+    let mut command = Command::new(bwrap);
+    command.args(bwrap_args(program, args, dir));
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+pub fn command(program: &Path, args: &[String], _dir: &Path) -> Result<Command, PluginError> {
+    if !Path::new(SANDBOX_EXEC).is_file() {
+        return Err(PluginError::NoSandbox(format!("{} is missing", SANDBOX_EXEC)));
+    }
+    let mut command = Command::new(SANDBOX_EXEC);
+    command.arg("-p").arg(PROFILE).arg(program).args(args);
+    Ok(command)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn command(_program: &Path, _args: &[String], _dir: &Path) -> Result<Command, PluginError> {
+    Err(PluginError::NoSandbox("plugins can't be sandboxed on this platform yet".to_string()))
+}
+
+/// `--unshare-all` drops the network along with every other namespace; the
+/// plugin dies with us if we're killed.
+#[cfg(target_os = "linux")]
+fn bwrap_args(program: &Path, args: &[String], dir: &Path) -> Vec<OsString> {
+    let mut out: Vec<OsString> = [
+        "--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp", "--unshare-all",
+        "--die-with-parent", "--new-session", "--chdir",
+    ]
+    .iter()
+    .map(OsString::from)
+    .collect();
+    out.push(dir.into());
+    out.push("--".into());
+    out.push(program.into());
+    out.extend(args.iter().map(OsString::from));
+    out
+}
+
+#[cfg(target_os = "linux")]
+fn find_on_path(name: &str) -> Option<std::path::PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bwrap_args() {
+        let args = bwrap_args(Path::new("/plugins/legal/run.sh"), &["--strict".to_string()], Path::new("/plugins/legal"));
+        let args: Vec<&str> = args.iter().map(|a| a.to_str().unwrap()).collect();
+        assert!(args.windows(3).any(|w| w == ["--ro-bind", "/", "/"]));
+        assert!(args.contains(&"--unshare-all"));
+        assert!(args.windows(2).any(|w| w == ["--chdir", "/plugins/legal"]));
+        // The plugin's own arguments come after the separator, never as bwrap options
+        assert_eq!(&args[args.len() - 3..], ["--", "/plugins/legal/run.sh", "--strict"]);
+    }
+}
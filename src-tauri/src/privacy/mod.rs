@@ -0,0 +1,263 @@
+//! Microphone usage ledger.
+//!
+//! Every time the microphone is live — a dictation, or a background stream kept
+//! open for pre-roll/standby — an entry is appended here, so users can audit
+//! when audio was captured and confirm it never left the machine. So is every
+//! plugin run, as the text then went through third-party code. Entries hold
+//! no text or audio, so incognito dictations are recorded too.
+//!
+//! Also the gate in front of anything that sends audio or text to a third
+//! party: `privacy.allow_cloud` must be on, and with `privacy.confirm_cloud`
+//! each recording needs the user's go-ahead. That's a handshake: a recording
+//! refused with `ConsentRequired` emits `cloud-consent-required`, and the
+//! frontend calls `grant_cloud_consent` and starts it again.
+
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::settings::PrivacySettings;
+
+#[derive(Error, Debug)]
+pub enum PrivacyError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Sending {0} is turned off in Privacy settings")]
+    CloudNotAllowed(String),
+    #[error("Sending {0} needs confirmation for this recording")]
+    ConsentRequired(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub id: String,
+    /// "dictation", "file", "preroll", "standby", "monitor" (settings
+    /// microphone check) or "plugin". Background entries span the whole time
+    /// their stream was open, overlapping any dictations in between.
+    pub kind: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_ms: u64,
+    /// Engine that processed the audio ("whisper", "voxtral"), or the plugin
+    /// ("plugin:<name>"); None for background streams
+    pub engine: Option<String>,
+    /// Whether any audio from this session was sent off the machine
+    pub audio_uploaded: bool,
+    #[serde(default)]
+    pub incognito: bool,
+    /// The text was processed by third-party code (a plugin), so the output
+    /// isn't only the app's
+    #[serde(default)]
+    pub third_party: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct LedgerData {
+    entries: Vec<LedgerEntry>,
+}
+
+/// Oldest entries are dropped beyond this.
+const MAX_ENTRIES: usize = 5000;
+
+/// How long a `grant_consent` waits for the recording it was given for.
+const CONSENT_TTL: Duration = Duration::from_secs(60);
+
+/// When consent for the next recording was granted, if it hasn't been used.
+static CONSENT: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start time of the dictation currently capturing, if any.
+static ACTIVE_DICTATION: Lazy<Mutex<Option<DateTime<Local>>>> = Lazy::new(|| Mutex::new(None));
+
+fn get_ledger_path() -> PathBuf {
+    crate::paths::config_dir().join("privacy_ledger.json")
+}
+
+fn load_ledger_data() -> Result<LedgerData, PrivacyError> {
+    let path = get_ledger_path();
+
+    if !path.exists() {
+        return Ok(LedgerData::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    let data = serde_json::from_str(&contents)?;
+    Ok(data)
+}
+
+fn save_ledger_data(data: &LedgerData) -> Result<(), PrivacyError> {
+    let path = get_ledger_path();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(data)?;
+    std::fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Append a finished session. Failures are logged, never surfaced: the ledger
+/// must not get in the way of a dictation.
+pub fn record_session(
+    kind: &str,
+    started_at: DateTime<Local>,
+    engine: Option<&str>,
+    audio_uploaded: bool,
+    incognito: bool,
+) {
+    let ended_at = Local::now();
+    append(LedgerEntry {
+        id: Uuid::new_v4().to_string(),
+        kind: kind.to_string(),
+        started_at: started_at.to_rfc3339(),
+        ended_at: ended_at.to_rfc3339(),
+        duration_ms: (ended_at - started_at).num_milliseconds().max(0) as u64,
+        engine: engine.map(|e| e.to_string()),
+        audio_uploaded,
+        incognito,
+        third_party: false,
+    });
+}
+
+/// Append a finished run of the plugin `name`, marked third-party. Failures
+/// are logged like `record_session`'s.
+pub fn record_plugin_run(name: &str, started_at: DateTime<Local>, incognito: bool) {
+    let ended_at = Local::now();
+    append(LedgerEntry {
+        id: Uuid::new_v4().to_string(),
+        kind: "plugin".to_string(),
+        started_at: started_at.to_rfc3339(),
+        ended_at: ended_at.to_rfc3339(),
+        duration_ms: (ended_at - started_at).num_milliseconds().max(0) as u64,
+        engine: Some(format!("plugin:{}", name)),
+        audio_uploaded: false,
+        incognito,
+        third_party: true,
+    });
+}
+
+fn append(entry: LedgerEntry) {
+    let result = load_ledger_data().and_then(|mut data| {
+        data.entries.insert(0, entry);
+        data.entries.truncate(MAX_ENTRIES);
+        save_ledger_data(&data)
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to update privacy ledger: {}", e);
+    }
+}
+
+/// Mark the start of a dictation's capture.
+pub fn begin_dictation() {
+    if let Ok(mut active) = ACTIVE_DICTATION.lock() {
+        *active = Some(Local::now());
+    }
+}
+
+/// Close the active dictation's ledger entry once capture has stopped.
+/// `engine` is None when the recording was abandoned before transcription.
+pub fn end_dictation(engine: Option<&str>, audio_uploaded: bool, incognito: bool) {
+    let started_at = ACTIVE_DICTATION.lock().ok().and_then(|mut a| a.take());
+    if let Some(started_at) = started_at {
+        record_session("dictation", started_at, engine, audio_uploaded, incognito);
+    }
+}
+
+/// Newest first.
+pub fn get_ledger(limit: Option<u32>, offset: Option<u32>) -> Result<Vec<LedgerEntry>, PrivacyError> {
+    let data = load_ledger_data()?;
+    Ok(data
+        .entries
+        .into_iter()
+        .skip(offset.unwrap_or(0) as usize)
+        .take(limit.unwrap_or(100) as usize)
+        .collect())
+}
+
+pub fn clear_ledger() -> Result<(), PrivacyError> {
+    save_ledger_data(&LedgerData::default())
+}
+
+/// Check `privacy.allow_cloud` before sending `what` ("audio to Apple's
+/// speech servers") off the machine.
+pub fn allow_upload(privacy: &PrivacySettings, what: &str) -> Result<(), PrivacyError> {
+    if privacy.allow_cloud {
+        Ok(())
+    } else {
+        Err(PrivacyError::CloudNotAllowed(what.to_string()))
+    }
+}
+
+/// The user agreed to send data for the next recording.
+pub fn grant_consent() {
+    if let Ok(mut consent) = CONSENT.lock() {
+        *consent = Some(Instant::now());
+    }
+}
+
+fn gate(privacy: &PrivacySettings, what: &str, consent: Option<Instant>, now: Instant) -> Result<(), PrivacyError> {
+    allow_upload(privacy, what)?;
+    let consented = consent.is_some_and(|at| now.duration_since(at) < CONSENT_TTL);
+    if privacy.confirm_cloud && !consented {
+        return Err(PrivacyError::ConsentRequired(what.to_string()));
+    }
+    Ok(())
+}
+
+/// Gate a recording that will send `what` off the machine. Uses up a
+/// granted consent.
+pub fn gate_recording(privacy: &PrivacySettings, what: &str) -> Result<(), PrivacyError> {
+    let consent = CONSENT.lock().ok().and_then(|mut c| c.take());
+    gate(privacy, what, consent, Instant::now())
+}
+
+/// Which streams currently have the microphone open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrophoneState {
+    pub recording: bool,
+    pub standby: bool,
+    pub preroll: bool,
+    /// Settings page microphone check
+    #[serde(default)]
+    pub monitor: bool,
+}
+
+pub fn microphone_state() -> MicrophoneState {
+    MicrophoneState {
+        recording: crate::audio::capture::is_capturing(),
+        standby: crate::audio::capture::is_standby(),
+        preroll: crate::audio::preroll::is_running(),
+        monitor: crate::audio::capture::is_monitoring(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate() {
+        let now = Instant::now();
+        let mut privacy = PrivacySettings::default();
+        assert!(matches!(gate(&privacy, "audio", None, now), Err(PrivacyError::CloudNotAllowed(_))));
+
+        privacy.allow_cloud = true;
+        assert!(gate(&privacy, "audio", None, now).is_ok());
+
+        privacy.confirm_cloud = true;
+        assert!(matches!(gate(&privacy, "audio", None, now), Err(PrivacyError::ConsentRequired(_))));
+        assert!(gate(&privacy, "audio", Some(now), now + Duration::from_secs(5)).is_ok());
+        // Granted, but for a recording that never started
+        assert!(matches!(
+            gate(&privacy, "audio", Some(now), now + CONSENT_TTL),
+            Err(PrivacyError::ConsentRequired(_))
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+//! Safe mode: start with default settings, no global hotkeys and no model
+//! preload, so a broken settings.json or a model that crashes on load can be
+//! repaired from the dashboard instead of by deleting config files.
+//!
+//! Entered when `MENTASCRIBE_SAFE_MODE` is set, or automatically after
+//! startup failed to complete `UNFINISHED_BEFORE_SAFE_MODE` times in a row. A
+//! marker file counts startups that haven't yet stayed up for `HEALTHY_AFTER`;
+//! a crash leaves it behind.
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const ENV_VAR: &str = "MENTASCRIBE_SAFE_MODE";
+
+/// Unfinished startups in a row that send the next one into safe mode.
+const UNFINISHED_BEFORE_SAFE_MODE: u32 = 2;
+
+/// How long the app has to run before a startup counts as finished. Covers
+/// the background model preload, which is where bad models crash.
+pub const HEALTHY_AFTER: Duration = Duration::from_secs(20);
+
+/// Why this run is in safe mode; unset in a normal run.
+static REASON: OnceCell<String> = OnceCell::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    pub reason: Option<String>,
+    /// Why settings.json can't be loaded, if it can't
+    pub settings_error: Option<String>,
+}
+
+fn get_marker_path() -> PathBuf {
+    crate::paths::config_dir().join("startup_pending")
+}
+
+fn read_unfinished(marker: &Path) -> u32 {
+    std::fs::read_to_string(marker)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn write_unfinished(marker: &Path, count: u32) {
+    if let Some(parent) = marker.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    if let Err(e) = std::fs::write(marker, count.to_string()) {
+        log::warn!("Failed to write the startup marker: {}", e);
+    }
+}
+
+/// Count a startup as unfinished; returns how many unfinished startups came
+/// right before it.
+fn begin_startup(marker: &Path) -> u32 {
+    let unfinished = read_unfinished(marker);
+    write_unfinished(marker, unfinished + 1);
+    unfinished
+}
+
+fn finish_startup(marker: &Path) {
+    if marker.exists() {
+        if let Err(e) = std::fs::remove_file(marker) {
+            log::warn!("Failed to remove the startup marker: {}", e);
+        }
+    }
+}
+
+fn reason(env_set: bool, unfinished: u32) -> Option<String> {
+    if env_set {
+        Some(format!("{} is set", ENV_VAR))
+    } else if unfinished >= UNFINISHED_BEFORE_SAFE_MODE {
+        Some(format!("the last {} startups did not finish", unfinished))
+    } else {
+        None
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+/// Decide once, at the very start of `run`, whether this is a safe-mode run,
+/// and count this startup as unfinished until `mark_healthy`.
+pub fn init() -> bool {
+    let unfinished = begin_startup(&get_marker_path());
+    let env_set = std::env::var(ENV_VAR).map(|v| is_truthy(&v)).unwrap_or(false);
+    if let Some(reason) = reason(env_set, unfinished) {
+        log::warn!("Starting in safe mode: {}", reason);
+        REASON.set(reason).ok();
+    }
+    is_active()
+}
+
+pub fn is_active() -> bool {
+    REASON.get().is_some()
+}
+
+/// This startup finished: the app has been up for `HEALTHY_AFTER`, or was
+/// quit normally before that.
+pub fn mark_healthy() {
+    finish_startup(&get_marker_path());
+}
+
+pub fn status() -> SafeModeStatus {
+    SafeModeStatus {
+        active: is_active(),
+        reason: REASON.get().cloned(),
+        settings_error: crate::settings::load_settings().err().map(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy("1"));
+        assert!(is_truthy(" Yes "));
+        assert!(!is_truthy("0"));
+        assert!(!is_truthy(""));
+    }
+
+    #[test]
+    fn test_unfinished_startups() {
+        let dir = std::env::temp_dir().join(format!("mentascribe-safe-mode-{}", uuid::Uuid::new_v4()));
+        let marker = dir.join("startup_pending");
+
+        // Two startups crash before finishing; the third starts in safe mode
+        assert_eq!(begin_startup(&marker), 0);
+        assert!(reason(false, 0).is_none());
+        assert_eq!(begin_startup(&marker), 1);
+        assert!(reason(false, 1).is_none());
+        assert_eq!(begin_startup(&marker), 2);
+        assert!(reason(false, 2).is_some());
+
+        // A finished startup resets the count
+        finish_startup(&marker);
+        assert!(!marker.exists());
+        assert_eq!(begin_startup(&marker), 0);
+
+        assert!(reason(true, 0).is_some());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
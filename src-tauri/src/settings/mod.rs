@@ -1,4 +1,8 @@
+pub mod secrets;
+
+use crate::transcription::recommend::ModelRecommendation;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -8,6 +12,8 @@ pub enum SettingsError {
     IoError(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     SerdeError(#[from] serde_json::Error),
+    #[error("Keychain error: {0}")]
+    KeychainError(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -16,39 +22,180 @@ pub struct TranscriptionSettings {
     pub language: Option<String>,      // "auto", "en", "es", etc.
     pub model_size: Option<String>,    // "tiny", "base", "small", "medium", "large"
     pub cloud_provider: Option<String>, // "aws", "openai", "assemblyai"
+    /// Seconds to wait for a cloud transcript before giving up (default 60)
+    #[serde(default)]
+    pub cloud_timeout_secs: Option<u32>,
     /// CoreML acceleration: None = auto-detect (enabled if supported), Some(true/false) = override
     #[serde(default)]
     pub use_coreml: Option<bool>,
-    /// Transcription engine: None or "whisper" = Whisper (default), "voxtral" = Voxtral
+    /// Per-model CoreML override by model size: "auto" (follow use_coreml), "on" or "off"
+    #[serde(default)]
+    pub coreml_models: HashMap<String, String>,
+    /// Transcription engine: None or "whisper" = Whisper (default), "voxtral" = Voxtral,
+    /// "parakeet" = Parakeet (English, CPU), "apple" = macOS Speech framework,
+    /// "sapi" = Windows speech recognition, "cloud" = `cloud_provider`
     #[serde(default)]
     pub engine: Option<String>,
     /// Voxtral transcription delay in ms (80-2400, default 480)
     #[serde(default)]
     pub voxtral_delay_ms: Option<i32>,
+    /// When to load the model: "always" (at startup, default), "on-first-use"
+    /// (when recording starts), or "on-hotkey-hover" (when the pointer enters the widget)
+    #[serde(default)]
+    pub preload_policy: Option<String>,
+    /// Whisper model for a single boosted dictation (`set_accuracy_boost` or
+    /// `hotkey.boost_key`), e.g. "large-v3-turbo"; None = no boost
+    #[serde(default)]
+    pub boost_model: Option<String>,
+    /// Voxtral model revision to download (branch, tag or commit hash; default "main")
+    #[serde(default)]
+    pub voxtral_revision: Option<String>,
+    /// HuggingFace repo of the Parakeet ONNX export (default istupakov/parakeet-ctc-0.6b-onnx)
+    #[serde(default)]
+    pub parakeet_repo: Option<String>,
+    /// Keep Apple Speech recognition on the device (default true); off lets
+    /// Apple's servers recognize languages without an on-device model
+    #[serde(default)]
+    pub apple_on_device: Option<bool>,
+    /// Windows speech recognizer (language pack) token id to use; None picks
+    /// the one for the transcription language
+    #[serde(default)]
+    pub sapi_recognizer: Option<String>,
+    /// Re-hash model files against their recorded SHA256 before loading (default false)
+    #[serde(default)]
+    pub verify_models_on_load: Option<bool>,
+    /// Use the active keyboard layout's language at recording start instead of
+    /// `language` (default false). Falls back to `language` when the layout
+    /// can't be read, and to "auto" when the engine doesn't know the language.
+    #[serde(default)]
+    pub language_from_keyboard: Option<bool>,
+    /// Checks that drop or trim text Whisper produced from noise
+    #[serde(default)]
+    pub hallucination_guard: HallucinationSettings,
+    /// How `model_size` was chosen on first run, for the onboarding UI
+    #[serde(default)]
+    pub model_recommendation: Option<ModelRecommendation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HallucinationSettings {
+    /// Drop output that is only a known silence phrase ("thanks for watching"; default true)
+    pub filter_phrases: Option<bool>,
+    /// Drop output with more words per second of audio than this (default 6.0; 0 = off)
+    pub max_words_per_second: Option<f32>,
+    /// Collapse a word or short phrase repeated back-to-back more than this
+    /// many times (default 5; 0 = off)
+    pub max_repeats: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CleanupSettings {
     pub enabled: bool,
-    pub provider: Option<String>,      // "openai", "anthropic", "ollama", "custom"
+    pub provider: Option<String>,      // "openai" (default), "anthropic", "openrouter", "ollama", "custom"
+    /// Provider's model; each provider has a default (see `cleanup::endpoint`)
     pub model: Option<String>,
+    /// OpenAI-compatible chat completions URL for the "custom" provider
     pub custom_endpoint: Option<String>,
+    /// Only set transiently when the user enters a key (moved to the keychain on
+    /// save; "" removes it), or when the keychain is unavailable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    /// Keychain reference of the stored key
+    #[serde(default)]
+    pub api_key_ref: Option<String>,
     pub remove_filler: bool,
     pub add_punctuation: bool,
     pub format_paragraphs: bool,
+    /// Give up and keep the engine's text after this long (default 8000)
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HotkeySettings {
-    pub key: Option<String>,           // "F6", "F5", etc.
-    pub mode: Option<String>,          // "hold", "toggle"
+    pub key: Option<String>,           // "F6", "Ctrl+Alt+D", "Cmd+Shift+Space" (see hotkey::accelerator)
+    /// "hold" (record while the key is down), "toggle" (press to start,
+    /// press again to stop) or "hybrid" (a tap toggles, a long press holds);
+    /// default toggle
+    pub mode: Option<String>,
+    /// Hybrid mode: how long a press lasts before it counts as holding (default 600)
+    #[serde(default)]
+    pub long_press_ms: Option<u32>,
+    /// Second key that starts an incognito dictation (never saved to history/stats)
+    #[serde(default)]
+    pub incognito_key: Option<String>,
+    /// Fixed transcription language per hotkey ("F7" -> "de"), overriding both
+    /// `transcription.language` and the keyboard layout
+    #[serde(default)]
+    pub languages: HashMap<String, String>,
+    /// Key that starts a dictation in the code profile
+    #[serde(default)]
+    pub code_key: Option<String>,
+    /// Key that pastes the next queued utterance (`insert_method = "queue"`)
+    #[serde(default)]
+    pub paste_next_key: Option<String>,
+    /// Key that makes the next dictation use `transcription.boost_model`
+    /// (pressed again before dictating, cancels it)
+    #[serde(default)]
+    pub boost_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct OutputSettings {
-    pub insert_method: Option<String>, // "type", "paste"
+    pub insert_method: Option<String>, // "type", "paste", "queue"
     pub auto_capitalize: Option<bool>,
+    /// Auto mode: remember which method works for each app and start there (default true)
+    #[serde(default)]
+    pub learn_insert_method: Option<bool>,
+    /// Add a space before/after inserted text when it would run into adjacent
+    /// words, if the target field can be read (default true)
+    #[serde(default)]
+    pub smart_spacing: Option<bool>,
+    /// "sentence", "lowercase", "title" or "uppercase". Unset follows
+    /// `auto_capitalize`. A spoken "all caps" / "lowercase that" at the end of
+    /// a dictation overrides it for that dictation.
+    #[serde(default)]
+    pub casing: Option<String>,
+    /// App ids (bundle id / executable name) whose dictations use the code
+    /// profile: spoken symbols, no casing or punctuation restoration
+    #[serde(default)]
+    pub code_apps: Vec<String>,
+    /// Insert what streaming already transcribed as soon as recording stops,
+    /// then only the rest once the tail is transcribed (default false)
+    #[serde(default)]
+    pub tail_first: Option<bool>,
+    /// Rewrite decimal and thousands separators in dictated numbers to
+    /// `number_locale`'s convention (default false)
+    #[serde(default)]
+    pub localize_numbers: Option<bool>,
+    /// Locale for number formatting, e.g. "de-DE" (default: the OS locale)
+    #[serde(default)]
+    pub number_locale: Option<String>,
+    /// Post-processing steps in the order they run: "punctuation",
+    /// "filler-removal", "casing", "addresses", "numbers", "profanity",
+    /// "dictionary", or "plugin:<name>" for an installed plugin. Unset runs
+    /// punctuation, casing, addresses, numbers and dictionary. Steps with
+    /// their own toggle still need it on.
+    #[serde(default)]
+    pub post_process_steps: Option<Vec<String>>,
+}
+
+/// How text is inserted into one app, overriding `output` there.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AppProfile {
+    /// "auto", "ax_api", "type", "paste" or "paste_restore"; unset follows
+    /// `output.insert_method`
+    #[serde(default)]
+    pub insert_method: Option<String>,
+    /// Pause between typed chunks, for apps that drop fast input (macOS
+    /// typing; default 2ms)
+    #[serde(default)]
+    pub chunk_delay_ms: Option<u32>,
+    /// Add a space after the inserted text, so the next dictation doesn't run
+    /// into it where the field can't be read (default false)
+    #[serde(default)]
+    pub trailing_space: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +204,14 @@ pub struct WidgetSettings {
     /// Widget opacity from 0.2 (nearly transparent) to 1.0 (fully opaque)
     #[serde(default = "default_opacity")]
     pub opacity: f64,
+    /// Audio level and partial transcript events per second, 5-60 (default 40).
+    /// Lower it on slow machines where the webview falls behind.
+    #[serde(default)]
+    pub event_rate_hz: Option<u32>,
+    /// Indicator shown on every display while recording: "off" (default),
+    /// "strip" (thin bar along the top edge) or "icon" (dot in the top-right corner)
+    #[serde(default)]
+    pub recording_indicator: Option<String>,
 }
 
 fn default_opacity() -> f64 {
@@ -68,24 +223,213 @@ impl Default for WidgetSettings {
         Self {
             draggable: false,
             opacity: default_opacity(),
+            event_rate_hz: None,
+            recording_indicator: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProxySettings {
+    pub mode: Option<String>,          // "system" (default), "none", "manual"
+    pub host: Option<String>,          // "proxy.corp.example" or "http://proxy.corp.example"
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    /// Kept in the keychain (`secrets::PROXY_PASSWORD_REF`), never in settings.json;
+    /// filled in by `load_settings` and `set_proxy_password`
+    #[serde(skip)]
+    pub password: Option<String>,
+    /// Hosts that bypass the proxy (same syntax as NO_PROXY, e.g. "localhost", ".corp.example")
+    #[serde(default)]
+    pub bypass: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DownloadSettings {
+    /// Mirrors tried in order for model downloads. Each entry is a preset name
+    /// ("huggingface", "hf-mirror"), a URL template with {repo}/{revision}/{file},
+    /// or a base URL replacing huggingface.co. Empty = HuggingFace only.
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Parallel connections per large file (1-8, default 4; 1 = single stream)
+    pub connections: Option<u32>,
+    /// At startup, download the models the settings need when they're missing,
+    /// instead of only emitting `model-needs-download` (default false)
+    pub auto_download: Option<bool>,
+    /// Auto-download at most this many MB in total; anything larger still waits
+    /// for the user through `model-needs-download` (default 1000)
+    pub auto_download_max_mb: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RecordingSettings {
+    /// Input device to record from, by name (default: the system default
+    /// device, also used while the selected one is disconnected)
+    #[serde(default)]
+    pub selected_input_device: Option<String>,
+    /// Recordings shorter than this are discarded, apart from a punctuation
+    /// mark said on its own (default 0 = off; 400 catches accidental taps)
+    pub min_duration_ms: Option<u32>,
+    /// Hold mode: the hotkey must be held this long before capture starts (default 0 = off)
+    pub hold_delay_ms: Option<u32>,
+    /// Keep this much audio from before the hotkey press via an always-on standby
+    /// stream (e.g. 500; default 0 = off, as it keeps the microphone open)
+    pub preroll_ms: Option<u32>,
+    /// Keep the input stream open between dictations for near-instant start
+    #[serde(default)]
+    pub standby: bool,
+    /// Close a standby stream after this many idle minutes (default 10)
+    pub standby_idle_minutes: Option<u32>,
+    /// Speech detection for streaming and pre-filtering: "auto" (default; Silero,
+    /// falling back to a built-in detector for streaming only), "silero",
+    /// "webrtc" or "energy"
+    #[serde(default)]
+    pub vad_backend: Option<String>,
+    /// Long dictations: every this many minutes, output what streaming has
+    /// transcribed so far and drop its audio, while recording continues
+    /// (e.g. 5; default off)
+    #[serde(default)]
+    pub chunk_minutes: Option<u32>,
+    /// When the input stream stalls (no audio within a second, or only digital
+    /// silence), restart capture on `fallback_device` (default false)
+    #[serde(default)]
+    pub restart_on_stall: Option<bool>,
+    /// Input device to restart on after a stall (default: the system default device)
+    #[serde(default)]
+    pub fallback_device: Option<String>,
+    /// Converting the device's rate to 16kHz: "auto" (default; the best the
+    /// benchmark found this machine sustains), "linear", "cubic" or "sinc"
+    #[serde(default)]
+    pub resampler_quality: Option<String>,
+    /// Set once, on the first start with this setting available
+    #[serde(default)]
+    pub resampler_benchmark: Option<crate::audio::resample::ResamplerBenchmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HistorySettings {
+    /// Start a new dictation session after this many idle minutes (default 5)
+    pub session_idle_minutes: Option<u32>,
+    /// Entries older than this many days are removed when history is
+    /// compacted (default: kept until the 500-entry limit)
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+    /// Compact history on quit, at most once a week (default false)
+    #[serde(default)]
+    pub auto_compact: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BackupSettings {
+    /// Back up settings, history, dictionary and stats on a schedule (default false)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Folder for the archives (default: "backups" in the config directory)
+    pub folder: Option<String>,
+    /// Hours between backups (default 24)
+    pub interval_hours: Option<u32>,
+    /// Archives to keep; older ones are deleted (default 7)
+    pub keep: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PunctuationSettings {
+    /// Run the local punctuation model on unpunctuated results
+    #[serde(default)]
+    pub enabled: bool,
+    /// Languages to apply it to ("en", "de", ...); empty = all languages
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// HuggingFace repo with an ONNX export of the model, plus tokenizer.json
+    /// and config.json (default: oliverguhr/fullstop-punctuation-multilang-large)
+    pub model_repo: Option<String>,
+}
+
+/// Diagnostic output per subsystem (see `diag`), all off by default.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DebugSettings {
+    /// Capture, pre-roll and resampling
+    #[serde(default)]
+    pub audio: bool,
+    /// Text injection, AX and clipboard
+    #[serde(default)]
+    pub injection: bool,
+    /// Widget panel, positioning and dragging
+    #[serde(default)]
+    pub window: bool,
+    /// Recording flow, transcription and post-processing
+    #[serde(default)]
+    pub engine: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PrivacySettings {
+    /// Treat every dictation as incognito: inject the text but skip history and stats
+    #[serde(default)]
+    pub incognito: bool,
+    /// Allow audio or text to be sent to a third party (server recognition,
+    /// cloud STT, LLM cleanup). Default false: nothing leaves the machine
+    #[serde(default)]
+    pub allow_cloud: bool,
+    /// With `allow_cloud`, ask before each recording that would send anything
+    #[serde(default)]
+    pub confirm_cloud: bool,
+    /// Plugins (by manifest name) the user has allowed to run; a `plugin:<name>`
+    /// step for any other plugin is skipped
+    #[serde(default)]
+    pub allowed_plugins: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GoalSettings {
+    /// Words to dictate per day; None = no goal
+    pub daily_word_goal: Option<u32>,
+    /// Emit `goal-reached` / `streak-at-risk` events for notifications
+    #[serde(default)]
+    pub notifications: bool,
+    /// Local hour (0-23) after which an unused day triggers `streak-at-risk` (default 20)
+    pub streak_reminder_hour: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UserSettings {
     pub transcription: TranscriptionSettings,
     pub cleanup: CleanupSettings,
     pub hotkey: HotkeySettings,
     pub output: OutputSettings,
+    /// Per-app insertion, keyed by app id (bundle id / executable name,
+    /// case-insensitive)
+    #[serde(default)]
+    pub app_profiles: HashMap<String, AppProfile>,
     #[serde(default)]
     pub widget: WidgetSettings,
+    #[serde(default)]
+    pub recording: RecordingSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub backup: BackupSettings,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    #[serde(default)]
+    pub download: DownloadSettings,
+    #[serde(default)]
+    pub goals: GoalSettings,
+    #[serde(default)]
+    pub privacy: PrivacySettings,
+    #[serde(default)]
+    pub punctuation: PunctuationSettings,
+    #[serde(default)]
+    pub debug: DebugSettings,
 }
 
-fn get_settings_path() -> PathBuf {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-    config_dir.join("mentascribe").join("settings.json")
+pub(crate) fn get_settings_path() -> PathBuf {
+    crate::paths::config_dir().join("settings.json")
+}
+
+/// No settings have been saved yet.
+pub fn is_first_run() -> bool {
+    !get_settings_path().exists()
 }
 
 pub fn load_settings() -> Result<UserSettings, SettingsError> {
@@ -96,7 +440,34 @@ pub fn load_settings() -> Result<UserSettings, SettingsError> {
     }
 
     let contents = std::fs::read_to_string(&path)?;
-    let settings = serde_json::from_str(&contents)?;
+    let mut settings: UserSettings = serde_json::from_str(&contents)?;
+
+    // Older versions stored API keys and the proxy password in plaintext
+    let mut changed = false;
+    if secrets::secure_api_keys(&mut settings) {
+        log::info!("Moved plaintext API key to the keychain");
+        changed = true;
+    }
+    if let Some(password) = secrets::legacy_proxy_password(&contents) {
+        match secrets::set(secrets::PROXY_PASSWORD_REF, &password) {
+            Ok(()) => {
+                log::info!("Moved plaintext proxy password to the keychain");
+                changed = true;
+            }
+            Err(e) => {
+                log::warn!("Keychain unavailable, proxy password kept for this session only: {}", e);
+                settings.proxy.password = Some(password);
+            }
+        }
+    }
+    if changed {
+        save_settings(&settings)?;
+    }
+
+    let has_proxy_user = settings.proxy.username.as_deref().is_some_and(|user| !user.is_empty());
+    if has_proxy_user && settings.proxy.password.is_none() {
+        settings.proxy.password = secrets::get(secrets::PROXY_PASSWORD_REF);
+    }
     Ok(settings)
 }
 
@@ -114,3 +485,17 @@ pub fn save_settings(settings: &UserSettings) -> Result<(), SettingsError> {
     log::info!("Settings saved to {:?}", path);
     Ok(())
 }
+
+/// Move settings.json aside (to settings.<timestamp>.json.bak) so defaults
+/// apply from now on. Returns where the old file went, if there was one.
+pub fn back_up_and_reset() -> Result<Option<PathBuf>, SettingsError> {
+    let path = get_settings_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let backup = path.with_file_name(format!("settings.{}.json.bak", stamp));
+    std::fs::rename(&path, &backup)?;
+    log::info!("Settings moved to {:?}, using defaults", backup);
+    Ok(Some(backup))
+}
@@ -0,0 +1,133 @@
+//! Provider API keys and the proxy password in the OS keychain.
+//!
+//! settings.json only holds a reference (the keychain account name); the key
+//! itself is written to the keychain when settings are saved, and plaintext keys
+//! left by older versions are moved there when settings are loaded. Cloud STT
+//! keys never pass through settings.json: `set_cloud_api_key` stores them
+//! directly, under `cloud_key_ref`. Neither does the proxy password, which
+//! `set_proxy_password` stores under `PROXY_PASSWORD_REF`.
+
+use super::{SettingsError, UserSettings};
+
+const SERVICE: &str = "mentascribe";
+
+/// Keychain reference for the cleanup provider's key.
+const CLEANUP_KEY_REF: &str = "cleanup";
+
+/// Keychain reference for the manual proxy's password.
+pub const PROXY_PASSWORD_REF: &str = "proxy";
+
+fn entry(reference: &str) -> Result<keyring::Entry, SettingsError> {
+    keyring::Entry::new(SERVICE, &format!("api-key:{}", reference))
+        .map_err(|e| SettingsError::KeychainError(e.to_string()))
+}
+
+/// Keychain reference for a cloud STT provider's key ("assemblyai").
+pub fn cloud_key_ref(provider: &str) -> String {
+    format!("cloud-{}", provider)
+}
+
+/// Read a stored key; None if there is none. Errors when the keychain is unavailable.
+pub fn try_get(reference: &str) -> Result<Option<String>, SettingsError> {
+    match entry(reference)?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(SettingsError::KeychainError(e.to_string())),
+    }
+}
+
+/// Read a stored key; None if there is none or the keychain is unavailable.
+pub fn get(reference: &str) -> Option<String> {
+    try_get(reference).unwrap_or_else(|e| {
+        log::warn!("Failed to read API key '{}' from keychain: {}", reference, e);
+        None
+    })
+}
+
+/// Store `key` under `reference`; an empty key removes the stored one.
+pub fn set(reference: &str, key: &str) -> Result<(), SettingsError> {
+    if key.is_empty() {
+        delete(reference)
+    } else {
+        store(reference, key)
+    }
+}
+
+fn store(reference: &str, key: &str) -> Result<(), SettingsError> {
+    if get(reference).as_deref() == Some(key) {
+        return Ok(());
+    }
+    entry(reference)?
+        .set_password(key)
+        .map_err(|e| SettingsError::KeychainError(e.to_string()))
+}
+
+fn delete(reference: &str) -> Result<(), SettingsError> {
+    match entry(reference)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(SettingsError::KeychainError(e.to_string())),
+    }
+}
+
+/// The proxy password older versions left in settings.json. Takes the raw file,
+/// since `ProxySettings.password` is never read from it.
+pub fn legacy_proxy_password(contents: &str) -> Option<String> {
+    let raw: serde_json::Value = serde_json::from_str(contents).ok()?;
+    raw.get("proxy")?
+        .get("password")?
+        .as_str()
+        .filter(|password| !password.is_empty())
+        .map(str::to_string)
+}
+
+/// Move any plaintext API keys in `settings` into the keychain, leaving only a
+/// reference. An empty key removes the stored one. When the keychain can't be
+/// written the plaintext key is kept so the feature keeps working.
+/// Returns whether `settings` changed.
+pub fn secure_api_keys(settings: &mut UserSettings) -> bool {
+    let cleanup = &mut settings.cleanup;
+    let Some(key) = cleanup.api_key.take() else {
+        return false;
+    };
+
+    if key.is_empty() {
+        if let Err(e) = delete(CLEANUP_KEY_REF) {
+            log::warn!("Failed to remove API key from keychain: {}", e);
+        }
+        cleanup.api_key_ref = None;
+        return true;
+    }
+
+    match store(CLEANUP_KEY_REF, &key) {
+        Ok(()) => {
+            cleanup.api_key_ref = Some(CLEANUP_KEY_REF.to_string());
+            true
+        }
+        Err(e) => {
+            log::warn!("Keychain unavailable, keeping API key in settings: {}", e);
+            cleanup.api_key = Some(key);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_password_stays_out_of_settings_json() {
+        let proxy = r#"{"mode": "manual", "username": "ana", "password": "hunter2", "bypass": []}"#;
+        let old = format!(r#"{{"proxy": {}}}"#, proxy);
+        assert_eq!(legacy_proxy_password(&old).as_deref(), Some("hunter2"));
+        assert_eq!(legacy_proxy_password(r#"{"proxy": {"password": ""}}"#), None);
+        assert_eq!(legacy_proxy_password("{}"), None);
+
+        let loaded: crate::settings::ProxySettings = serde_json::from_str(proxy).unwrap();
+        assert_eq!(loaded.password, None);
+
+        let mut settings = UserSettings::default();
+        settings.proxy.password = Some("hunter2".to_string());
+        assert!(!serde_json::to_string(&settings).unwrap().contains("hunter2"));
+    }
+}
@@ -1,6 +1,6 @@
-use chrono::{Local, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate, Timelike};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,6 +9,8 @@ pub enum StatsError {
     IoError(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     SerdeError(#[from] serde_json::Error),
+    #[error("Unsupported export format: {0}")]
+    UnsupportedFormat(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -27,6 +29,71 @@ pub struct LocalStats {
     pub streak_days: u32,
     pub last_used_date: Option<String>,
     pub daily_history: Vec<DailyStats>,
+    /// Last date a `goal-reached` event fired, so it fires once per day
+    #[serde(default)]
+    pub goal_reached_date: Option<String>,
+    /// Last date a `streak-at-risk` event fired
+    #[serde(default)]
+    pub streak_reminder_date: Option<String>,
+    /// Whisper repetition loops ("I'm sorry. I'm sorry. ...") truncated or dropped
+    #[serde(default)]
+    pub repetition_loops: u64,
+    /// Whisper temperature fallbacks, i.e. decodes redone because the output
+    /// looked unreliable
+    #[serde(default)]
+    pub temperature_fallbacks: u64,
+    /// Transcriptions that needed at least one temperature fallback
+    #[serde(default)]
+    pub fallback_transcriptions: u64,
+    /// Last date an `accuracy-hint` event fired
+    #[serde(default)]
+    pub fallback_hint_date: Option<String>,
+    /// Words and transcriptions per local hour of day (0-23)
+    #[serde(default)]
+    pub hourly_words: [u64; 24],
+    #[serde(default)]
+    pub hourly_transcriptions: [u64; 24],
+    /// Words and transcriptions per weekday, Monday first
+    #[serde(default)]
+    pub weekday_words: [u64; 7],
+    #[serde(default)]
+    pub weekday_transcriptions: [u64; 7],
+}
+
+/// Usage by time of day and weekday, plus the days still in
+/// `daily_history`, for the dashboard's activity heatmap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageHeatmap {
+    pub hourly_words: [u64; 24],
+    pub hourly_transcriptions: [u64; 24],
+    /// Monday first
+    pub weekday_words: [u64; 7],
+    pub weekday_transcriptions: [u64; 7],
+    /// Oldest first
+    pub daily: Vec<DailyStats>,
+}
+
+/// Sent as `accuracy-hint` when Whisper often falls back to higher
+/// temperatures, which points at the model or VAD settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackHint {
+    /// Share of transcriptions that needed a fallback (0.0-1.0)
+    pub fallback_rate: f64,
+    pub transcriptions: u64,
+}
+
+/// Transcriptions needed before the fallback rate is worth reporting.
+const FALLBACK_HINT_MIN_TRANSCRIPTIONS: u64 = 20;
+/// Fallback rate at which `accuracy-hint` fires.
+const FALLBACK_HINT_RATE: f64 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub goal: u32,
+    pub words_today: u64,
+    pub percent: f64,
+    pub reached: bool,
+    pub streak_days: u32,
 }
 
 impl Default for LocalStats {
@@ -38,14 +105,22 @@ impl Default for LocalStats {
             streak_days: 0,
             last_used_date: None,
             daily_history: Vec::new(),
+            goal_reached_date: None,
+            streak_reminder_date: None,
+            repetition_loops: 0,
+            temperature_fallbacks: 0,
+            fallback_transcriptions: 0,
+            fallback_hint_date: None,
+            hourly_words: [0; 24],
+            hourly_transcriptions: [0; 24],
+            weekday_words: [0; 7],
+            weekday_transcriptions: [0; 7],
         }
     }
 }
 
 fn get_stats_path() -> PathBuf {
-    let config_dir = dirs::config_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-    config_dir.join("mentascribe").join("stats.json")
+    crate::paths::config_dir().join("stats.json")
 }
 
 pub fn load_stats() -> Result<LocalStats, StatsError> {
@@ -76,13 +151,15 @@ pub fn save_stats(stats: &LocalStats) -> Result<(), StatsError> {
 
 pub fn record_transcription(word_count: u32, duration_ms: u32) -> Result<LocalStats, StatsError> {
     let mut stats = load_stats()?;
-    let today = Local::now().format("%Y-%m-%d").to_string();
+    let now = Local::now();
+    let today = now.format("%Y-%m-%d").to_string();
     let audio_seconds = duration_ms as f64 / 1000.0;
 
     // Update totals
     stats.total_transcriptions += 1;
     stats.total_words += word_count as u64;
     stats.total_audio_seconds += audio_seconds;
+    add_to_buckets(&mut stats, now.hour(), now.weekday().num_days_from_monday(), word_count as u64);
 
     // Update streak
     if let Some(ref last_date) = stats.last_used_date {
@@ -125,6 +202,64 @@ pub fn record_transcription(word_count: u32, duration_ms: u32) -> Result<LocalSt
     Ok(stats)
 }
 
+/// Count one transcription of `words` words in its hour and weekday buckets.
+fn add_to_buckets(stats: &mut LocalStats, hour: u32, weekday: u32, words: u64) {
+    if let Some(bucket) = stats.hourly_words.get_mut(hour as usize) {
+        *bucket += words;
+        stats.hourly_transcriptions[hour as usize] += 1;
+    }
+    if let Some(bucket) = stats.weekday_words.get_mut(weekday as usize) {
+        *bucket += words;
+        stats.weekday_transcriptions[weekday as usize] += 1;
+    }
+}
+
+pub fn get_usage_heatmap() -> Result<UsageHeatmap, StatsError> {
+    let stats = load_stats()?;
+    let mut daily = stats.daily_history;
+    daily.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(UsageHeatmap {
+        hourly_words: stats.hourly_words,
+        hourly_transcriptions: stats.hourly_transcriptions,
+        weekday_words: stats.weekday_words,
+        weekday_transcriptions: stats.weekday_transcriptions,
+        daily,
+    })
+}
+
+pub fn record_repetition_loops(count: u32) -> Result<(), StatsError> {
+    let mut stats = load_stats()?;
+    stats.repetition_loops += count as u64;
+    save_stats(&stats)
+}
+
+/// Record the temperature fallbacks of one transcription. Returns a hint
+/// once per day while the fallback rate is high.
+pub fn record_temperature_fallbacks(count: u32) -> Result<Option<FallbackHint>, StatsError> {
+    let mut stats = load_stats()?;
+    stats.temperature_fallbacks += count as u64;
+    stats.fallback_transcriptions += 1;
+
+    let today = today_string();
+    let hint = fallback_hint(&stats).filter(|_| stats.fallback_hint_date.as_deref() != Some(today.as_str()));
+    if hint.is_some() {
+        stats.fallback_hint_date = Some(today);
+    }
+    save_stats(&stats)?;
+    Ok(hint)
+}
+
+fn fallback_hint(stats: &LocalStats) -> Option<FallbackHint> {
+    if stats.total_transcriptions < FALLBACK_HINT_MIN_TRANSCRIPTIONS {
+        return None;
+    }
+    let fallback_rate = stats.fallback_transcriptions as f64 / stats.total_transcriptions as f64;
+    (fallback_rate >= FALLBACK_HINT_RATE).then_some(FallbackHint {
+        fallback_rate,
+        transcriptions: stats.total_transcriptions,
+    })
+}
+
 fn is_yesterday(last_date: &str, today: &str) -> bool {
     if let (Ok(last), Ok(current)) = (
         NaiveDate::parse_from_str(last_date, "%Y-%m-%d"),
@@ -151,3 +286,150 @@ pub fn get_stats() -> Result<LocalStats, StatsError> {
 
     Ok(stats)
 }
+
+fn today_string() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn words_on(stats: &LocalStats, date: &str) -> u64 {
+    stats
+        .daily_history
+        .iter()
+        .find(|d| d.date == date)
+        .map(|d| d.words)
+        .unwrap_or(0)
+}
+
+/// Words dictated today.
+pub fn words_today(stats: &LocalStats) -> u64 {
+    words_on(stats, &today_string())
+}
+
+/// Today's progress toward the daily word goal.
+pub fn get_goal_progress(goal: u32) -> Result<GoalProgress, StatsError> {
+    let stats = get_stats()?;
+    let words_today = words_on(&stats, &today_string());
+    Ok(GoalProgress {
+        goal,
+        words_today,
+        percent: if goal == 0 { 100.0 } else { (words_today as f64 / goal as f64 * 100.0).min(100.0) },
+        reached: words_today >= goal as u64,
+        streak_days: stats.streak_days,
+    })
+}
+
+/// Returns the progress the first time today's word count reaches `goal`,
+/// and `None` otherwise (not reached yet, or already reported today).
+pub fn take_goal_reached(goal: u32) -> Result<Option<GoalProgress>, StatsError> {
+    let progress = get_goal_progress(goal)?;
+    let today = today_string();
+    let mut stats = load_stats()?;
+    if !progress.reached || stats.goal_reached_date.as_deref() == Some(today.as_str()) {
+        return Ok(None);
+    }
+    stats.goal_reached_date = Some(today);
+    save_stats(&stats)?;
+    Ok(Some(progress))
+}
+
+/// Returns the current streak once per day when it's past `reminder_hour`, the user
+/// dictated yesterday, and nothing has been dictated yet today.
+pub fn take_streak_at_risk(reminder_hour: u32) -> Result<Option<u32>, StatsError> {
+    if Local::now().hour() < reminder_hour {
+        return Ok(None);
+    }
+    let today = today_string();
+    let mut stats = load_stats()?;
+    let at_risk = match stats.last_used_date {
+        Some(ref last) => is_yesterday(last, &today) && stats.streak_days > 0,
+        None => false,
+    };
+    if !at_risk || stats.streak_reminder_date.as_deref() == Some(today.as_str()) {
+        return Ok(None);
+    }
+    stats.streak_reminder_date = Some(today);
+    save_stats(&stats)?;
+    Ok(Some(stats.streak_days))
+}
+
+/// Words per minute of audio, or 0 when no audio was recorded.
+fn words_per_minute(words: u64, audio_seconds: f64) -> f64 {
+    if audio_seconds > 0.0 {
+        words as f64 / (audio_seconds / 60.0)
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DailyExportRow {
+    date: String,
+    transcriptions: u64,
+    words: u64,
+    audio_seconds: f64,
+    wpm: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct StatsExport {
+    exported_at: String,
+    total_transcriptions: u64,
+    total_words: u64,
+    total_audio_seconds: f64,
+    average_wpm: f64,
+    streak_days: u32,
+    daily: Vec<DailyExportRow>,
+}
+
+/// Write daily stats and derived metrics to `path` as "csv" or "json".
+/// Returns the number of daily rows written.
+pub fn export_stats(path: &Path, format: &str) -> Result<usize, StatsError> {
+    let stats = get_stats()?;
+
+    let mut daily: Vec<DailyExportRow> = stats
+        .daily_history
+        .iter()
+        .map(|d| DailyExportRow {
+            date: d.date.clone(),
+            transcriptions: d.transcriptions,
+            words: d.words,
+            audio_seconds: d.audio_seconds,
+            wpm: words_per_minute(d.words, d.audio_seconds),
+        })
+        .collect();
+    daily.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let contents = match format.to_lowercase().as_str() {
+        "json" => {
+            let export = StatsExport {
+                exported_at: Local::now().to_rfc3339(),
+                total_transcriptions: stats.total_transcriptions,
+                total_words: stats.total_words,
+                total_audio_seconds: stats.total_audio_seconds,
+                average_wpm: words_per_minute(stats.total_words, stats.total_audio_seconds),
+                streak_days: stats.streak_days,
+                daily: daily.clone(),
+            };
+            serde_json::to_string_pretty(&export)?
+        }
+        "csv" => {
+            let mut out = String::from("date,transcriptions,words,audio_seconds,wpm\n");
+            for row in &daily {
+                out.push_str(&format!(
+                    "{},{},{},{:.1},{:.1}\n",
+                    row.date, row.transcriptions, row.words, row.audio_seconds, row.wpm
+                ));
+            }
+            out
+        }
+        other => return Err(StatsError::UnsupportedFormat(other.to_string())),
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+
+    log::info!("Exported {} days of stats to {:?}", daily.len(), path);
+    Ok(daily.len())
+}
@@ -0,0 +1,129 @@
+//! One snapshot of the app's state for the UI.
+//!
+//! Instead of a status command per subsystem (models, CoreML, Metal, Voxtral,
+//! Parakeet, Apple Speech, Windows Speech, engine capabilities, settings), each answered at a slightly
+//! different moment, `get_app_status` returns all of them as one `AppStatus`.
+//! `AppHealth` does the same for the dashboard home's live overview; it's
+//! emitted as `app-health` while the dashboard is open (`get_app_health` on
+//! demand).
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::latency::TranscriptionTimings;
+use crate::privacy::MicrophoneState;
+use crate::settings::UserSettings;
+use crate::text::PunctuationStatus;
+use crate::transcription::{
+    AppleSpeechStatus, CoremlStatus, Engine, EngineCapabilities, MetalStatus, ModelInfo, ParakeetStatus,
+    SapiStatus, VoxtralStatus,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permissions {
+    /// Text can be injected: Accessibility granted on macOS; on Linux an X11
+    /// session, or wtype or ydotool installed under Wayland
+    pub accessibility: bool,
+    /// Sandboxed (App Store) build: text may have to be pasted by hand
+    pub sandboxed: bool,
+}
+
+impl Permissions {
+    pub fn current() -> Self {
+        Self {
+            accessibility: crate::injection::can_inject(),
+            sandboxed: crate::injection::is_sandboxed(),
+        }
+    }
+}
+
+/// Audio left for stop to transcribe below which stop is about instant.
+const LIVE_PENDING_SECS: f64 = 3.0;
+
+/// Progress of the current recording, emitted as `recording-status` about
+/// once a second. Streaming has stalled when `consumed_samples` stops moving
+/// while `buffered_secs` keeps growing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingStatus {
+    pub elapsed_ms: u64,
+    /// 16kHz audio captured so far, including chunks already finalized
+    pub buffered_secs: f64,
+    /// 16kHz samples transcribed (whisper, parakeet, apple) or fed to the model (voxtral) by streaming
+    pub consumed_samples: usize,
+    /// "whisper", "voxtral", "parakeet", "apple", "sapi" or "cloud"
+    pub engine: String,
+    /// The streaming monitor is running; false if it never started (model
+    /// missing or still loading) or has exited
+    pub streaming: bool,
+    /// `consumed_samples` in seconds
+    pub consumed_secs: f64,
+    /// Audio streaming hasn't got to yet, which stop has to transcribe: what
+    /// makes one stop near-instant and another take seconds
+    pub pending_secs: f64,
+    /// Streaming keeps up: `pending_secs` is short enough for an instant stop
+    pub live: bool,
+}
+
+impl RecordingStatus {
+    pub fn current(elapsed: Duration, engine: Engine) -> Self {
+        let backend = engine.backend();
+        let (consumed_samples, streaming) = (backend.streaming_consumed(), backend.is_streaming());
+        let buffered = crate::audio::capture::whisper_buffer_end();
+        let pending_secs = buffered.saturating_sub(consumed_samples) as f64 / 16000.0;
+        Self {
+            elapsed_ms: elapsed.as_millis() as u64,
+            buffered_secs: buffered as f64 / 16000.0,
+            consumed_samples,
+            engine: engine.id().to_string(),
+            streaming,
+            consumed_secs: consumed_samples as f64 / 16000.0,
+            pending_secs,
+            live: streaming && pending_secs < LIVE_PENDING_SECS,
+        }
+    }
+}
+
+/// Usage and readiness at a glance, for the dashboard home.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppHealth {
+    /// "whisper", "voxtral", "parakeet", "apple", "sapi" or "cloud"
+    pub engine: String,
+    /// The configured model is loaded, so the next dictation starts without a cold load
+    pub model_loaded: bool,
+    pub recording: bool,
+    /// Latency breakdown of the last transcription this session
+    pub last_timings: Option<TranscriptionTimings>,
+    pub streak_days: u32,
+    pub words_today: u64,
+    /// History and dictionary entries not yet synced; None if unreadable
+    pub pending_sync: Option<u64>,
+    /// Disk used by downloaded models; None if it couldn't be measured
+    pub storage_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub version: String,
+    pub recording: bool,
+    /// Started in safe mode: default settings, no hotkeys, nothing preloaded
+    pub safe_mode: bool,
+    pub microphone: MicrophoneState,
+    pub permissions: Permissions,
+    /// Signed in, i.e. tokens are in the keychain
+    pub authenticated: bool,
+    pub engines: Vec<EngineCapabilities>,
+    pub coreml: CoremlStatus,
+    pub metal: MetalStatus,
+    pub voxtral: VoxtralStatus,
+    pub parakeet: ParakeetStatus,
+    pub apple_speech: AppleSpeechStatus,
+    pub sapi: SapiStatus,
+    pub punctuation: PunctuationStatus,
+    /// Whisper models
+    pub models: Vec<ModelInfo>,
+    /// Empty without the voxtral feature
+    pub voxtral_models: Vec<ModelInfo>,
+    /// Empty without the parakeet feature
+    pub parakeet_models: Vec<ModelInfo>,
+    pub settings: UserSettings,
+}
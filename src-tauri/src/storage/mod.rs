@@ -0,0 +1,395 @@
+//! Model storage usage report and cleanup.
+//!
+//! Scans the models directory and classifies everything in it (GGML models,
+//! CoreML encoders, Voxtral files, the VAD model, leftover partial downloads) so the
+//! dashboard can show where disk space goes and offer targeted cleanup.
+//!
+//! Also recognizes writes that failed because the disk is full, so they can be
+//! reported as `storage-full` instead of as an opaque IO error.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use thiserror::Error;
+
+use crate::error::AppError;
+use crate::transcription::whisper;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageItem {
+    /// "ggml", "coreml", "voxtral", "punctuation", "vad", "partial", or "other"
+    pub kind: String,
+    /// Model id (e.g. "small", "large-v3-turbo") or the file name for non-model items
+    pub id: String,
+    pub path: String,
+    pub size_bytes: u64,
+    /// RFC3339 timestamp of the last transcription with this model, if known
+    pub last_used: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageReport {
+    pub models_dir: String,
+    pub total_bytes: u64,
+    pub items: Vec<StorageItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CleanupOptions {
+    /// Remove `.part` files from interrupted downloads
+    #[serde(default)]
+    pub remove_partial: bool,
+    /// Remove CoreML encoders whose GGML model is no longer downloaded
+    #[serde(default)]
+    pub remove_orphaned_coreml: bool,
+    /// Remove models not used in this many days (the active model is always kept)
+    #[serde(default)]
+    pub unused_days: Option<u32>,
+    /// Report what would be removed without deleting anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupResult {
+    pub removed: Vec<StorageItem>,
+    pub freed_bytes: u64,
+    pub dry_run: bool,
+}
+
+const VOXTRAL_DIR: &str = "voxtral-mini-4b";
+const PUNCTUATION_DIR: &str = "punctuation";
+const VAD_FILENAME: &str = "ggml-silero-vad.bin";
+/// Metadata files kept next to the models (integrity manifest, usage log)
+const METADATA_FILES: &[&str] = &["manifest.json", "model_usage.json"];
+
+// ======================= Usage tracking =======================
+
+fn get_usage_path() -> PathBuf {
+    whisper::get_models_dir().join("model_usage.json")
+}
+
+fn load_usage() -> BTreeMap<String, String> {
+    std::fs::read_to_string(get_usage_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Record that a model was just used for transcription. The cleanup only
+/// counts days, so the file is written once a day per model at most.
+pub fn mark_model_used(model_id: &str) {
+    let mut usage = load_usage();
+    let now = Local::now();
+    let used_today = usage
+        .get(model_id)
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .is_some_and(|d| d.with_timezone(&Local).date_naive() == now.date_naive());
+    if used_today {
+        return;
+    }
+    usage.insert(model_id.to_string(), now.to_rfc3339());
+    let path = get_usage_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    match serde_json::to_string_pretty(&usage) {
+        Ok(contents) => {
+            if let Err(e) = std::fs::write(&path, contents) {
+                log::warn!("Failed to save model usage: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize model usage: {}", e),
+    }
+}
+
+// ======================= Scanning =======================
+
+fn path_size(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Map a GGML file name back to its model id (inverse of `get_model_filename`).
+fn ggml_model_id(filename: &str) -> Option<String> {
+    let stem = filename.strip_prefix("ggml-")?.strip_suffix(".bin")?;
+    Some(if stem == "large-v3" { "large".to_string() } else { stem.to_string() })
+}
+
+/// Map a CoreML encoder directory name back to its model id.
+fn coreml_model_id(dirname: &str) -> Option<String> {
+    let stem = dirname
+        .strip_prefix("ggml-")?
+        .strip_suffix("-encoder.mlmodelc")?;
+    Some(if stem == "large-v3" { "large".to_string() } else { stem.to_string() })
+}
+
+fn classify(path: &Path, usage: &BTreeMap<String, String>) -> Option<StorageItem> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    if METADATA_FILES.contains(&name.as_str()) || name == whisper::NO_COREML_DIR {
+        return None;
+    }
+
+    let (kind, id) = if name == VAD_FILENAME {
+        ("vad", "silero-vad".to_string())
+    } else if name.ends_with(".part") {
+        // Not ".zip": a CoreML zip only exists complete, while it's being
+        // extracted, so it's left to "other"
+        ("partial", name.clone())
+    } else if path.is_dir() && name == VOXTRAL_DIR {
+        ("voxtral", VOXTRAL_DIR.to_string())
+    } else if path.is_dir() && name == PUNCTUATION_DIR {
+        ("punctuation", PUNCTUATION_DIR.to_string())
+    } else if let Some(id) = path.is_dir().then(|| coreml_model_id(&name)).flatten() {
+        ("coreml", id)
+    } else if let Some(id) = ggml_model_id(&name) {
+        ("ggml", id)
+    } else {
+        ("other", name.clone())
+    };
+
+    let last_used = match kind {
+        "ggml" | "coreml" | "voxtral" => usage.get(&id).cloned(),
+        _ => None,
+    };
+
+    Some(StorageItem {
+        kind: kind.to_string(),
+        id,
+        path: path.to_string_lossy().into_owned(),
+        size_bytes: path_size(path),
+        last_used,
+    })
+}
+
+fn scan_items() -> Result<Vec<StorageItem>, StorageError> {
+    let models_dir = whisper::get_models_dir();
+    if !models_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let usage = load_usage();
+    let mut items = Vec::new();
+    for entry in std::fs::read_dir(&models_dir)?.flatten() {
+        let path = entry.path();
+        if let Some(item) = classify(&path, &usage) {
+            items.push(item);
+        }
+        // Interrupted Voxtral downloads leave .part files inside the model directory
+        if path.is_dir() && entry.file_name() == VOXTRAL_DIR {
+            for inner in std::fs::read_dir(&path)?.flatten() {
+                let inner_path = inner.path();
+                if inner_path.extension().map(|e| e == "part").unwrap_or(false) {
+                    items.push(StorageItem {
+                        kind: "partial".to_string(),
+                        id: format!("{}/{}", VOXTRAL_DIR, inner.file_name().to_string_lossy()),
+                        path: inner_path.to_string_lossy().into_owned(),
+                        size_bytes: path_size(&inner_path),
+                        last_used: None,
+                    });
+                }
+            }
+        }
+    }
+
+    items.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    Ok(items)
+}
+
+pub fn get_storage_report() -> Result<StorageReport, StorageError> {
+    let items = scan_items()?;
+    // Partial files inside the Voxtral directory are already counted in its size
+    let total_bytes = items
+        .iter()
+        .filter(|i| !(i.kind == "partial" && i.id.starts_with(VOXTRAL_DIR)))
+        .map(|i| i.size_bytes)
+        .sum();
+
+    Ok(StorageReport {
+        models_dir: whisper::get_models_dir().to_string_lossy().into_owned(),
+        total_bytes,
+        items,
+    })
+}
+
+// ======================= Cleanup =======================
+
+/// Whether a model hasn't been used within `days`. Models with no recorded use
+/// fall back to the file's modification time (i.e. when it was downloaded).
+fn is_unused_for(item: &StorageItem, days: u32) -> bool {
+    let cutoff = Local::now() - chrono::Duration::days(days as i64);
+    let last = item
+        .last_used
+        .as_deref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|d| d.with_timezone(&Local))
+        .or_else(|| {
+            std::fs::metadata(&item.path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Local>::from)
+        });
+    last.map(|d| d < cutoff).unwrap_or(false)
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Remove files selected by `options`. `active_model` (the configured Whisper model
+/// or "voxtral-mini-4b") is never removed by the unused-days rule.
+pub fn cleanup_storage(options: &CleanupOptions, active_model: &str) -> Result<CleanupResult, StorageError> {
+    let items = scan_items()?;
+    let downloaded_ggml: Vec<String> = items
+        .iter()
+        .filter(|i| i.kind == "ggml")
+        .map(|i| i.id.clone())
+        .collect();
+
+    let selected: Vec<StorageItem> = items
+        .into_iter()
+        .filter(|item| match item.kind.as_str() {
+            "partial" => options.remove_partial,
+            "coreml" => {
+                (options.remove_orphaned_coreml && !downloaded_ggml.contains(&item.id))
+                    || options
+                        .unused_days
+                        .map(|d| item.id != active_model && is_unused_for(item, d))
+                        .unwrap_or(false)
+            }
+            "ggml" | "voxtral" => options
+                .unused_days
+                .map(|d| item.id != active_model && is_unused_for(item, d))
+                .unwrap_or(false),
+            _ => false,
+        })
+        .collect();
+
+    let mut removed = Vec::new();
+    let mut freed_bytes = 0;
+    for item in selected {
+        if !options.dry_run {
+            let result = match item.kind.as_str() {
+                // Go through the engine so cached contexts and hash records are dropped too
+                "ggml" => whisper::delete_model(&item.id).map_err(|e| e.to_string()),
+                "coreml" => whisper::delete_coreml_model(&item.id).map_err(|e| e.to_string()),
+                #[cfg(feature = "voxtral")]
+                "voxtral" => crate::transcription::voxtral::delete_model().map_err(|e| e.to_string()),
+                _ => remove_path(Path::new(&item.path)).map_err(|e| e.to_string()),
+            };
+            if let Err(e) = result {
+                log::warn!("Cleanup: failed to remove {}: {}", item.path, e);
+                continue;
+            }
+            log::info!("Cleanup: removed {} ({} bytes)", item.path, item.size_bytes);
+        }
+        freed_bytes += item.size_bytes;
+        removed.push(item);
+    }
+
+    Ok(CleanupResult {
+        removed,
+        freed_bytes,
+        dry_run: options.dry_run,
+    })
+}
+
+// ======================= Disk full =======================
+
+/// Payload of the `storage-full` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageFull {
+    /// What couldn't be written: "history", "stats", "dictionary" or "model"
+    pub what: String,
+    pub message: String,
+}
+
+/// Whether `error`, or an error it wraps, is the disk running out of space.
+pub fn is_disk_full(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(e) = current {
+        if e.downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::StorageFull)
+            || e.downcast_ref::<rusqlite::Error>()
+                .is_some_and(|db| db.sqlite_error_code() == Some(rusqlite::ErrorCode::DiskFull))
+        {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
+
+/// Describe a failed write of `what`. When the disk is full this also emits
+/// `storage-full`, and the message says so plainly.
+pub fn write_error(app: &AppHandle, what: &str, error: &(dyn std::error::Error + 'static)) -> String {
+    if !is_disk_full(error) {
+        return error.to_string();
+    }
+    let message = format!("Not enough disk space to save the {}. Free up some space and try again.", what);
+    log::warn!("Disk full while saving the {}: {}", what, error);
+    app.emit(
+        "storage-full",
+        StorageFull {
+            what: what.to_string(),
+            message: message.clone(),
+        },
+    )
+    .ok();
+    message
+}
+
+/// `write_error` for a command: the error sorted for the frontend, as
+/// `DiskFull` when the disk is full.
+pub fn write_app_error<E>(app: &AppHandle, what: &str, error: E) -> AppError
+where
+    E: std::error::Error + Into<AppError> + 'static,
+{
+    if is_disk_full(&error) {
+        AppError::DiskFull(write_error(app, what, &error))
+    } else {
+        error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_disk_full_through_wrappers() {
+        let full = crate::history::HistoryError::from(std::io::Error::from(std::io::ErrorKind::StorageFull));
+        assert!(is_disk_full(&full));
+        let denied = crate::history::HistoryError::from(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(!is_disk_full(&denied));
+    }
+
+    #[test]
+    fn test_classify_partial() {
+        let usage = BTreeMap::new();
+        let kind = |name: &str| classify(&std::env::temp_dir().join(name), &usage).unwrap().kind;
+        assert_eq!(kind("ggml-small.bin.part"), "partial");
+        assert_eq!(kind("ggml-small-encoder.mlmodelc.zip.part"), "partial");
+        // Being extracted
+        assert_eq!(kind("ggml-small-encoder.mlmodelc.zip"), "other");
+    }
+}
@@ -0,0 +1,210 @@
+//! Spoken email addresses and web addresses to their written form:
+//!
+//! "john dot doe at gmail dot com" -> "john.doe@gmail.com"
+//! "dub dub dub dot example dot com slash pricing" -> "www.example.com/pricing"
+//!
+//! Only rewritten when the host ends in a known top-level domain, and not when
+//! a word before it makes it read as ordinary speech ("the dot com bubble",
+//! "meet me at ...").
+
+/// Top-level domains a spoken host must end in.
+const TLDS: &[&str] = &[
+    "com", "org", "net", "edu", "gov", "io", "co", "ai", "dev", "app", "me", "us", "uk", "de", "fr",
+    "es", "it", "nl", "ca", "au", "jp", "cn", "in", "br", "ch", "se", "info", "biz", "tv", "xyz",
+];
+
+/// Words that are never a host label or the whole local part of an email.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "the", "this", "that", "me", "us", "you", "him", "her", "them", "it", "we", "they",
+    "is", "are", "was", "be", "here", "there", "look", "meet", "at", "dot",
+];
+
+/// Words that, right before a single-word local part, make it part of the
+/// sentence rather than an address ("our team at acme dot com").
+const DETERMINERS: &[&str] = &["a", "an", "the", "our", "my", "your", "their", "his", "her", "this", "that"];
+
+struct Token {
+    /// Lowercased, without the trailing punctuation
+    key: String,
+    start: usize,
+    /// End of the word itself, before any trailing punctuation
+    end: usize,
+    /// Trailing punctuation ends an address
+    punctuated: bool,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for word in text.split_whitespace() {
+        let start = offset + text[offset..].find(word).unwrap_or(0);
+        offset = start + word.len();
+        let core = word.trim_end_matches(|c: char| matches!(c, '.' | ',' | '!' | '?' | ';' | ':'));
+        tokens.push(Token {
+            key: core.to_lowercase(),
+            start,
+            end: start + core.len(),
+            punctuated: core.len() < word.len(),
+        });
+    }
+    tokens
+}
+
+/// Rewrite spoken addresses in `text`; anything else is left as is.
+pub fn normalize(text: &str) -> String {
+    let tokens = tokenize(text);
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let previous = i.checked_sub(1).map(|p| tokens[p].key.as_str());
+        match email_at(&tokens, i, previous).or_else(|| url_at(&tokens, i)) {
+            Some((end, address)) => {
+                result.push_str(&text[copied..tokens[i].start]);
+                result.push_str(&address);
+                copied = tokens[end - 1].end;
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+fn is_word(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '.')
+}
+
+/// `local (connector local)* at host` starting at token `i`, as (end, address).
+fn email_at(tokens: &[Token], i: usize, previous: Option<&str>) -> Option<(usize, String)> {
+    let mut local = String::new();
+    let mut j = i;
+    let mut words = 0;
+    loop {
+        let token = tokens.get(j)?;
+        if !is_word(&token.key) || token.key == "at" {
+            return None;
+        }
+        local.push_str(&token.key);
+        words += 1;
+        j += 1;
+        if token.punctuated {
+            return None;
+        }
+        let connector = match tokens.get(j).map(|t| t.key.as_str()) {
+            Some("dot") => '.',
+            Some("underscore") => '_',
+            Some("dash") | Some("hyphen") => '-',
+            _ => break,
+        };
+        if tokens[j].punctuated {
+            return None;
+        }
+        local.push(connector);
+        j += 1;
+    }
+    if words == 1 && (STOP_WORDS.contains(&local.as_str()) || previous.is_some_and(|p| DETERMINERS.contains(&p))) {
+        return None;
+    }
+    if tokens.get(j)?.key != "at" || tokens[j].punctuated {
+        return None;
+    }
+    let (end, host) = host_at(tokens, j + 1)?;
+    Some((end, format!("{}@{}", local, host)))
+}
+
+/// `[scheme] [www] host (slash word)*` starting at token `i`, as (end, address).
+fn url_at(tokens: &[Token], i: usize) -> Option<(usize, String)> {
+    let keys: Vec<&str> = tokens[i..].iter().take(4).map(|t| t.key.as_str()).collect();
+    let (mut address, mut j) = match keys.as_slice() {
+        [scheme @ ("http" | "https"), "colon", "slash", "slash", ..] => (format!("{}://", scheme), i + 4),
+        _ => (String::new(), i),
+    };
+
+    let keys: Vec<&str> = tokens[j..].iter().take(4).map(|t| t.key.as_str()).collect();
+    let www = match keys.as_slice() {
+        ["www", "dot", ..] => 1,
+        ["dub", "dub", "dub", "dot", ..] | ["w", "w", "w", "dot", ..] => 3,
+        _ => 0,
+    };
+    if www > 0 {
+        address.push_str("www.");
+        j += www + 1;
+    } else if address.is_empty() && STOP_WORDS.contains(&tokens.get(j)?.key.as_str()) {
+        return None;
+    }
+
+    let (mut end, host) = host_at(tokens, j)?;
+    address.push_str(&host);
+    // Path segments, unless the host ended the sentence
+    while !tokens[end - 1].punctuated
+        && tokens.get(end).is_some_and(|t| t.key == "slash" && !t.punctuated)
+        && tokens.get(end + 1).is_some_and(|t| is_word(&t.key))
+    {
+        address.push('/');
+        address.push_str(&tokens[end + 1].key);
+        end += 2;
+    }
+    Some((end, address))
+}
+
+/// `label (dot label)+` ending in a known TLD, as (end, host).
+fn host_at(tokens: &[Token], i: usize) -> Option<(usize, String)> {
+    let mut labels: Vec<&str> = Vec::new();
+    let mut j = i;
+    loop {
+        let token = tokens.get(j)?;
+        if !is_word(&token.key) || token.key == "dot" {
+            break;
+        }
+        labels.extend(token.key.split('.'));
+        j += 1;
+        if token.punctuated || tokens.get(j).map(|t| t.key.as_str()) != Some("dot") || tokens[j].punctuated {
+            break;
+        }
+        j += 1;
+    }
+    // A trailing "dot" that wasn't followed by a label isn't part of the host
+    while j > i && tokens[j - 1].key == "dot" {
+        j -= 1;
+    }
+    let valid = labels.len() >= 2
+        && labels.iter().all(|l| !l.is_empty())
+        && !STOP_WORDS.contains(&labels[0])
+        && TLDS.contains(labels.last()?);
+    valid.then(|| (j, labels.join(".")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emails() {
+        assert_eq!(normalize("email john dot doe at gmail dot com."), "email john.doe@gmail.com.");
+        assert_eq!(normalize("Send it to Support at Acme dot co dot uk please"), "Send it to support@acme.co.uk please");
+        assert_eq!(normalize("jane underscore smith at example dot org"), "jane_smith@example.org");
+    }
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(normalize("go to dub dub dub dot example dot com slash pricing"), "go to www.example.com/pricing");
+        assert_eq!(normalize("Visit us at acme dot io."), "Visit us at acme.io.");
+        assert_eq!(normalize("https colon slash slash docs dot rs"), "https colon slash slash docs dot rs");
+        assert_eq!(normalize("https colon slash slash github dot com slash rust"), "https://github.com/rust");
+    }
+
+    #[test]
+    fn test_ordinary_speech_unchanged() {
+        for text in [
+            "the dot com bubble",
+            "meet me at the office",
+            "reach our team at the front desk",
+            "I looked at it dot by dot",
+            "We'll be at home. Dot com was later.",
+        ] {
+            assert_eq!(normalize(text), text);
+        }
+    }
+}
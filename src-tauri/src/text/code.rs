@@ -0,0 +1,218 @@
+//! Code profile: turns spoken symbols and identifier commands into code.
+//!
+//! "camel case user id equals open paren close paren" -> "userId = ()"
+//!
+//! Symbols either glue to their neighbours (brackets, `_`, `.`), attach to
+//! the word before them (`,`, `;`), or are spaced like operators (`=`, `+`).
+//! Sentence punctuation the engine adds around words is dropped, since in
+//! this mode every symbol is spoken.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Spacing {
+    /// No space on either side
+    Glue,
+    /// No space before, a space after
+    Trailing,
+    /// A space on both sides
+    Spaced,
+}
+
+/// Spoken symbol -> text. The longest phrase that matches wins.
+const SYMBOLS: &[(&str, &str, Spacing)] = &[
+    ("open paren", "(", Spacing::Glue),
+    ("close paren", ")", Spacing::Glue),
+    ("open bracket", "[", Spacing::Glue),
+    ("close bracket", "]", Spacing::Glue),
+    ("open brace", "{", Spacing::Glue),
+    ("close brace", "}", Spacing::Glue),
+    ("open curly", "{", Spacing::Glue),
+    ("close curly", "}", Spacing::Glue),
+    ("open angle", "<", Spacing::Glue),
+    ("close angle", ">", Spacing::Glue),
+    ("single quote", "'", Spacing::Glue),
+    ("double quote", "\"", Spacing::Glue),
+    ("quote", "\"", Spacing::Glue),
+    ("backtick", "`", Spacing::Glue),
+    ("underscore", "_", Spacing::Glue),
+    ("dot", ".", Spacing::Glue),
+    ("dash", "-", Spacing::Glue),
+    ("hyphen", "-", Spacing::Glue),
+    ("slash", "/", Spacing::Glue),
+    ("backslash", "\\", Spacing::Glue),
+    ("at sign", "@", Spacing::Glue),
+    ("hash", "#", Spacing::Glue),
+    ("dollar sign", "$", Spacing::Glue),
+    ("tilde", "~", Spacing::Glue),
+    ("caret", "^", Spacing::Glue),
+    ("double colon", "::", Spacing::Glue),
+    ("new line", "\n", Spacing::Glue),
+    ("tab key", "\t", Spacing::Glue),
+    ("comma", ",", Spacing::Trailing),
+    ("semicolon", ";", Spacing::Trailing),
+    ("colon", ":", Spacing::Trailing),
+    ("question mark", "?", Spacing::Trailing),
+    ("bang", "!", Spacing::Glue),
+    ("fat arrow", "=>", Spacing::Spaced),
+    ("arrow", "->", Spacing::Spaced),
+    ("double equals", "==", Spacing::Spaced),
+    ("triple equals", "===", Spacing::Spaced),
+    ("not equals", "!=", Spacing::Spaced),
+    ("equals", "=", Spacing::Spaced),
+    ("plus equals", "+=", Spacing::Spaced),
+    ("minus equals", "-=", Spacing::Spaced),
+    ("plus", "+", Spacing::Spaced),
+    ("minus", "-", Spacing::Spaced),
+    ("times", "*", Spacing::Spaced),
+    ("star", "*", Spacing::Glue),
+    ("percent", "%", Spacing::Spaced),
+    ("less than", "<", Spacing::Spaced),
+    ("greater than", ">", Spacing::Spaced),
+    ("double ampersand", "&&", Spacing::Spaced),
+    ("ampersand", "&", Spacing::Glue),
+    ("double pipe", "||", Spacing::Spaced),
+    ("pipe", "|", Spacing::Spaced),
+];
+
+/// Identifier styles: the words after the command are joined into one name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Identifier {
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+const IDENTIFIERS: &[(&str, Identifier)] = &[
+    ("camel case", Identifier::Camel),
+    ("pascal case", Identifier::Pascal),
+    ("snake case", Identifier::Snake),
+    ("constant case", Identifier::ScreamingSnake),
+    ("kebab case", Identifier::Kebab),
+];
+
+enum Piece {
+    Word(String),
+    Symbol(&'static str, Spacing),
+}
+
+/// Rewrite dictated text for the code profile.
+pub fn apply(text: &str) -> String {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| matches!(c, '.' | ',' | '?' | '!' | ';' | ':')))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let keys: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+
+    let mut pieces = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        if let Some((len, (symbol, spacing))) = match_phrase(&keys[i..], SYMBOLS.iter().map(|(p, s, sp)| (*p, (*s, *sp)))) {
+            pieces.push(Piece::Symbol(symbol, spacing));
+            i += len;
+        } else if let Some((len, style)) = match_phrase(&keys[i..], IDENTIFIERS.iter().map(|(p, s)| (*p, *s))) {
+            // The name runs until the next symbol or command
+            let start = i + len;
+            let mut end = start;
+            while end < words.len() && !starts_command(&keys[end..]) {
+                end += 1;
+            }
+            if end == start {
+                pieces.push(Piece::Word(words[i..start].join(" ")));
+            } else {
+                pieces.push(Piece::Word(identifier(&keys[start..end], style)));
+            }
+            i = end;
+        } else {
+            pieces.push(Piece::Word(words[i].to_string()));
+            i += 1;
+        }
+    }
+    render(&pieces)
+}
+
+/// Longest phrase in `phrases` the words at the start of `keys` spell out,
+/// as (words consumed, value).
+fn match_phrase<T>(keys: &[String], phrases: impl Iterator<Item = (&'static str, T)>) -> Option<(usize, T)> {
+    phrases
+        .filter_map(|(phrase, value)| {
+            let len = phrase.split(' ').count();
+            let matches = keys.len() >= len && keys[..len].iter().map(String::as_str).eq(phrase.split(' '));
+            matches.then_some((len, value))
+        })
+        .max_by_key(|(len, _)| *len)
+}
+
+fn starts_command(keys: &[String]) -> bool {
+    match_phrase(keys, SYMBOLS.iter().map(|(p, _, _)| (*p, ()))).is_some()
+        || match_phrase(keys, IDENTIFIERS.iter().map(|(p, _)| (*p, ()))).is_some()
+}
+
+fn identifier(words: &[String], style: Identifier) -> String {
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match style {
+        Identifier::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+            .collect(),
+        Identifier::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+        Identifier::Snake => words.join("_"),
+        Identifier::ScreamingSnake => words.join("_").to_uppercase(),
+        Identifier::Kebab => words.join("-"),
+    }
+}
+
+fn render(pieces: &[Piece]) -> String {
+    let mut result = String::new();
+    let mut previous: Option<&Piece> = None;
+    for piece in pieces {
+        let space = match (previous, piece) {
+            (None, _) => false,
+            (_, Piece::Symbol(_, Spacing::Trailing)) => false,
+            (_, Piece::Symbol(_, Spacing::Spaced)) => true,
+            // Glue sticks to words and other glue, but not to operators or commas
+            (Some(previous), Piece::Symbol(_, Spacing::Glue)) => {
+                matches!(previous, Piece::Symbol(_, Spacing::Spaced | Spacing::Trailing))
+            }
+            (Some(previous), Piece::Word(_)) => !matches!(previous, Piece::Symbol(_, Spacing::Glue)),
+        };
+        if space {
+            result.push(' ');
+        }
+        match piece {
+            Piece::Word(word) => result.push_str(word),
+            Piece::Symbol(symbol, _) => result.push_str(symbol),
+        }
+        previous = Some(piece);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(apply("self dot user underscore id equals none"), "self.user_id = none");
+        assert_eq!(apply("Print open paren value comma count close paren semicolon."), "Print(value, count);");
+        assert_eq!(apply("x greater than open bracket zero close bracket"), "x > [zero]");
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(apply("camel case foo bar"), "fooBar");
+        assert_eq!(apply("let snake case max retry count equals three"), "let max_retry_count = three");
+        assert_eq!(apply("Pascal case http client open paren close paren"), "HttpClient()");
+        // A command with nothing after it is kept as words
+        assert_eq!(apply("camel case"), "camel case");
+    }
+}
@@ -0,0 +1,102 @@
+//! Word-level diff between the engine's raw output and the cleaned text,
+//! so users can see what post-processing changed.
+
+use serde::{Deserialize, Serialize};
+
+/// A run of consecutive words that were kept, added, or removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffChunk {
+    /// "equal", "insert" (only in the cleaned text) or "delete" (only in the raw text)
+    pub kind: String,
+    pub text: String,
+}
+
+/// Beyond this many word comparisons the LCS table gets too large; the texts
+/// are then reported as fully replaced.
+const MAX_CELLS: usize = 4_000_000;
+
+fn push(chunks: &mut Vec<DiffChunk>, kind: &str, word: &str) {
+    match chunks.last_mut() {
+        Some(last) if last.kind == kind => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => chunks.push(DiffChunk {
+            kind: kind.to_string(),
+            text: word.to_string(),
+        }),
+    }
+}
+
+/// Diff `raw` against `cleaned` word by word (longest common subsequence).
+/// Deletions are listed before insertions within a changed run.
+pub fn diff_words(raw: &str, cleaned: &str) -> Vec<DiffChunk> {
+    let a: Vec<&str> = raw.split_whitespace().collect();
+    let b: Vec<&str> = cleaned.split_whitespace().collect();
+    let mut chunks = Vec::new();
+
+    if a.len().saturating_mul(b.len()) > MAX_CELLS {
+        a.iter().for_each(|w| push(&mut chunks, "delete", w));
+        b.iter().for_each(|w| push(&mut chunks, "insert", w));
+        return chunks;
+    }
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            push(&mut chunks, "equal", a[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            push(&mut chunks, "delete", a[i]);
+            i += 1;
+        } else {
+            push(&mut chunks, "insert", b[j]);
+            j += 1;
+        }
+    }
+    a[i..].iter().for_each(|w| push(&mut chunks, "delete", w));
+    b[j..].iter().for_each(|w| push(&mut chunks, "insert", w));
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: &str, text: &str) -> DiffChunk {
+        DiffChunk {
+            kind: kind.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(
+            diff_words("um so the meeting is at three", "So the meeting is at 3."),
+            vec![
+                chunk("delete", "um so"),
+                chunk("insert", "So"),
+                chunk("equal", "the meeting is at"),
+                chunk("delete", "three"),
+                chunk("insert", "3."),
+            ]
+        );
+        assert_eq!(diff_words("same text", "same text"), vec![chunk("equal", "same text")]);
+        assert!(diff_words("", "").is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+//! Filler words ("um", "uh", "erm") dropped from a dictation.
+//!
+//! "Um, so I think, uh, we should ship it, um." -> "So I think, we should ship it."
+//!
+//! A comma after a filler goes with it; a sentence end moves onto the word
+//! before, and a capital at the start of a sentence passes to the next word.
+
+/// Lowercase, without punctuation.
+const FILLERS: &[&str] = &["um", "umm", "uh", "uhh", "uhm", "erm", "er", "hmm", "mm"];
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '?' | '!')
+}
+
+/// Remove filler words, keeping the whitespace and punctuation around the rest.
+pub fn remove(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut capitalize_next = false;
+
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let bare = word.trim_end_matches(|c: char| c.is_ascii_punctuation());
+        if word.is_empty() || !FILLERS.iter().any(|f| bare.eq_ignore_ascii_case(f)) {
+            match word.chars().next() {
+                Some(first) if capitalize_next => {
+                    result.extend(first.to_uppercase());
+                    result.push_str(&piece[first.len_utf8()..]);
+                    capitalize_next = false;
+                }
+                _ => result.push_str(piece),
+            }
+            continue;
+        }
+
+        if bare.starts_with(char::is_uppercase) {
+            capitalize_next = true;
+        }
+        // "... ship it, um." -> "... ship it."
+        if let Some(end) = word[bare.len()..].chars().find(|c| is_sentence_end(*c)) {
+            let kept = result.trim_end().len();
+            let whitespace = result[kept..].to_string();
+            result.truncate(kept);
+            if result.ends_with([',', ';', ':']) {
+                result.pop();
+            }
+            if !result.is_empty() && !result.ends_with(is_sentence_end) {
+                result.push(end);
+            }
+            result.push_str(&whitespace);
+        }
+    }
+
+    // A filler at the very end leaves the whitespace before it behind
+    if !text.ends_with(char::is_whitespace) {
+        result.truncate(result.trim_end().len());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_fillers() {
+        assert_eq!(
+            remove("Um, so I think, uh, we should ship it, um."),
+            "So I think, we should ship it."
+        );
+        assert_eq!(remove("I was uh going to call"), "I was going to call");
+        assert_eq!(remove("Yes um"), "Yes");
+        assert_eq!(remove("Line one\nuh line two"), "Line one\nline two");
+    }
+
+    #[test]
+    fn test_words_containing_fillers_unchanged() {
+        for text in ["Umbrella and uhuru", "The summit, hmmm okay", "Her umpire"] {
+            assert_eq!(remove(text), text);
+        }
+    }
+}
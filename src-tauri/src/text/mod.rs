@@ -1,12 +1,162 @@
 //! Text processing module for post-transcription transformations
 
-/// Process transcribed text with various transformations
-pub fn process_text(text: &str, auto_capitalize: bool) -> String {
-    if !auto_capitalize {
-        return text.to_string();
+pub mod address;
+pub mod code;
+pub mod diff;
+pub mod filler;
+pub mod numbers;
+pub mod profanity;
+#[cfg(feature = "punctuation")]
+pub mod punctuation;
+pub mod symbols;
+
+use crate::settings::OutputSettings;
+use serde::{Deserialize, Serialize};
+
+/// Status of local punctuation restoration. When the feature is disabled,
+/// returns a stub with compiled=false.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PunctuationStatus {
+    pub compiled: bool,
+    pub model_downloaded: bool,
+    pub model_loaded: bool,
+}
+
+/// Whether text looks like it came back from an engine that omits punctuation:
+/// several words with (almost) no sentence or clause marks.
+pub fn needs_punctuation(text: &str) -> bool {
+    let words = text.split_whitespace().count();
+    if words < 4 {
+        return false;
+    }
+    let marks = text
+        .chars()
+        .filter(|c| matches!(c, '.' | ',' | '?' | '!' | ';' | ':' | '。' | '、' | '？' | '！'))
+        .count();
+    marks * 20 < words
+}
+
+/// How the letters of a dictation are cased on output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Casing {
+    /// Left as the engine returned it
+    Unchanged,
+    /// Capital at the start of each sentence
+    Sentence,
+    Lower,
+    /// Capital at the start of each word, except short joining words
+    Title,
+    Upper,
+}
+
+impl Casing {
+    /// `output.casing` ("sentence", "lowercase", "title", "uppercase"). Unset
+    /// or unknown falls back to `auto_capitalize`: sentence case when on,
+    /// unchanged when off.
+    pub fn from_settings(output: &OutputSettings) -> Self {
+        match output.casing.as_deref() {
+            Some("sentence") => Casing::Sentence,
+            Some("lowercase") => Casing::Lower,
+            Some("title") => Casing::Title,
+            Some("uppercase") => Casing::Upper,
+            _ if output.auto_capitalize.unwrap_or(true) => Casing::Sentence,
+            _ => Casing::Unchanged,
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Casing::Unchanged => text.to_string(),
+            Casing::Sentence => capitalize_sentences(text),
+            Casing::Lower => text.to_lowercase(),
+            Casing::Title => title_case(text),
+            Casing::Upper => text.to_uppercase(),
+        }
+    }
+}
+
+/// Which set of post-processing rules a dictation goes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    /// Punctuation restoration, casing and all dictionary entries
+    #[default]
+    Prose,
+    /// For editors and terminals: no punctuation restoration or casing,
+    /// spoken symbols and identifiers (see `code`), and prose-only
+    /// dictionary entries skipped
+    Code,
+}
+
+/// Spoken commands that override the casing of the dictation they end.
+const SPOKEN_CASING: &[(&str, Casing)] = &[
+    ("all caps", Casing::Upper),
+    ("uppercase that", Casing::Upper),
+    ("lowercase that", Casing::Lower),
+    ("no caps", Casing::Lower),
+    ("title case that", Casing::Title),
+    ("sentence case that", Casing::Sentence),
+];
+
+/// Process transcribed text with various transformations. A spoken casing
+/// command at the end of the text ("... all caps") is removed and overrides
+/// `casing` for this text.
+pub fn process_text(text: &str, casing: Casing) -> String {
+    match take_spoken_casing(text) {
+        Some((rest, spoken)) => spoken.apply(&rest),
+        None => casing.apply(text),
     }
+}
+
+/// Split a trailing casing command off `text`, along with the punctuation
+/// the engine tends to put around it ("Fix the bug, lowercase that.").
+fn take_spoken_casing(text: &str) -> Option<(String, Casing)> {
+    let trimmed = text.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '.' | '!' | ','));
+    SPOKEN_CASING.iter().find_map(|(phrase, casing)| {
+        let start = trimmed.len().checked_sub(phrase.len())?;
+        if !trimmed.is_char_boundary(start) || !trimmed[start..].eq_ignore_ascii_case(phrase) {
+            return None;
+        }
+        // Whole words only, and not the entire dictation
+        let rest = &trimmed[..start];
+        if !rest.is_empty() && !rest.ends_with(|c: char| c.is_whitespace() || c == ',') {
+            return None;
+        }
+        let rest = rest.trim_end_matches(|c: char| c.is_whitespace() || c == ',');
+        (!rest.is_empty()).then(|| (rest.to_string(), *casing))
+    })
+}
+
+/// Words left lowercase in title case unless they start or end the text.
+const TITLE_SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "the", "to", "vs",
+];
 
-    capitalize_sentences(text)
+/// Capitalize the first letter of each word. Other letters are kept, so
+/// acronyms and names survive.
+fn title_case(text: &str) -> String {
+    let words: Vec<&str> = text.split(' ').collect();
+    let last = words.len().saturating_sub(1);
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let bare = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+            if i != 0 && i != last && TITLE_SMALL_WORDS.contains(&bare.as_str()) {
+                return word.to_lowercase();
+            }
+            match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+                Some((index, first)) => {
+                    let mut result = String::with_capacity(word.len());
+                    result.push_str(&word[..index]);
+                    result.extend(first.to_uppercase());
+                    result.push_str(&word[index + first.len_utf8()..]);
+                    result
+                }
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Capitalize the first letter of the text and after sentence-ending punctuation
@@ -36,6 +186,103 @@ fn capitalize_sentences(text: &str) -> String {
     result
 }
 
+/// Text around the insertion point of the target field, as far as it could be
+/// read. An empty string means the cursor is at that end of the field.
+#[derive(Debug, Clone, Default)]
+pub struct CursorContext {
+    pub before: String,
+    /// None when only the text before the cursor could be read
+    pub after: Option<String>,
+}
+
+/// Whether `prev` and `next` would run together without a space between them.
+fn needs_space(prev: char, next: char) -> bool {
+    !prev.is_whitespace() && !"([{\"'“‘/-".contains(prev) && next.is_alphanumeric()
+}
+
+/// Adapt text about to be inserted at the cursor. With `smart_spacing`, add a
+/// space on either side where it would otherwise run into the neighbouring
+/// word (never where one is already present). With `auto_capitalize`,
+/// lowercase the first word when it continues a sentence, unless its capital
+/// is its own: `capitalized` holds the words the dictionary capitalizes
+/// (`dictionary::capitalized_words`).
+pub fn fit_to_context(
+    text: &str,
+    context: &CursorContext,
+    auto_capitalize: bool,
+    smart_spacing: bool,
+    capitalized: &[String],
+) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+
+    if let (Some(prev), Some(first)) = (context.before.chars().last(), text.chars().next()) {
+        if smart_spacing && needs_space(prev, first) {
+            result.push(' ');
+        }
+    }
+
+    if auto_capitalize && continues_sentence(&context.before) {
+        result.push_str(&lowercase_first_word(text, capitalized));
+    } else {
+        result.push_str(text);
+    }
+
+    let next = context.after.as_deref().and_then(|a| a.chars().next());
+    if let (Some(last), Some(next)) = (text.chars().last(), next) {
+        if smart_spacing && needs_space(last, next) {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+/// Whether text inserted after `before` is mid-sentence. Closing quotes and
+/// brackets are looked through, so `He said "Hi." ` still ends a sentence.
+fn continues_sentence(before: &str) -> bool {
+    let trimmed = before.trim_end_matches([' ', '\t', '"', '\'', ')', ']', '”', '’']);
+    match trimmed.chars().last() {
+        None => false,
+        Some(c) => !matches!(c, '.' | '!' | '?' | '\n' | '\r' | '\u{2028}' | '\u{2029}' | '。' | '！' | '？'),
+    }
+}
+
+/// Whether `word`, the first word of `text`, is capitalized for its own sake
+/// rather than for starting the utterance: "I", an acronym, a word the
+/// dictionary capitalizes, or one `text` capitalizes again mid-sentence
+/// ("Paris is lovely, we love Paris").
+fn keeps_capital(word: &str, text: &str, capitalized: &[String]) -> bool {
+    if word == "I" || word.chars().skip(1).any(char::is_uppercase) || capitalized.iter().any(|w| w == word) {
+        return true;
+    }
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.windows(2).any(|pair| {
+        !pair[0].ends_with(['.', '!', '?']) && pair[1].trim_matches(|c: char| !c.is_alphanumeric()) == word
+    })
+}
+
+/// Undo the capital the engine or `auto_capitalize` gave the first word for
+/// starting an utterance. Words that keep their capital (`keeps_capital`),
+/// such as "I" (or "I'm", "I'll", ...) and names, are left alone.
+fn lowercase_first_word(text: &str, capitalized: &[String]) -> String {
+    let word = text.split_whitespace().next().unwrap_or("");
+    let bare = word.trim_matches(|c: char| !c.is_alphanumeric());
+    let base = bare.split(['\'', '’']).next().unwrap_or("");
+    if keeps_capital(base, text, capitalized) || keeps_capital(bare, text, capitalized) {
+        return text.to_string();
+    }
+    match base.chars().next() {
+        Some(first) if first.is_uppercase() => {
+            let index = text.find(first).unwrap_or(0);
+            let mut result = String::with_capacity(text.len());
+            result.push_str(&text[..index]);
+            result.extend(first.to_lowercase());
+            result.push_str(&text[index + first.len_utf8()..]);
+            result
+        }
+        _ => text.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,10 +303,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_needs_punctuation() {
+        assert!(needs_punctuation("so i was thinking we could meet tomorrow"));
+        assert!(!needs_punctuation("So I was thinking, we could meet tomorrow."));
+        assert!(!needs_punctuation("hello there"));
+    }
+
+    fn fit(text: &str, before: &str, after: Option<&str>) -> String {
+        let context = CursorContext {
+            before: before.to_string(),
+            after: after.map(|a| a.to_string()),
+        };
+        fit_to_context(text, &context, true, true, &[])
+    }
+
+    #[test]
+    fn test_fit_to_context() {
+        assert_eq!(fit("Went home.", "I think ", None), "went home.");
+        assert_eq!(fit("Went home.", "I think", None), " went home.");
+        assert_eq!(fit("Went home.", "Done. ", None), "Went home.");
+        assert_eq!(fit("Went home.", "", None), "Went home.");
+        assert_eq!(fit("I went home.", "and then ", None), "I went home.");
+        assert_eq!(fit("NASA called.", "and then ", None), "NASA called.");
+        assert_eq!(fit("Went home.", "He said \"Hi.\" ", None), "Went home.");
+
+        let context = CursorContext {
+            before: "I think".to_string(),
+            after: None,
+        };
+        assert_eq!(fit_to_context("Went home.", &context, false, false, &[]), "Went home.");
+    }
+
+    #[test]
+    fn test_fit_to_context_keeps_names() {
+        assert_eq!(fit("I think so.", "and ", None), "I think so.");
+        assert_eq!(fit("I'll go.", "and ", None), "I'll go.");
+        assert_eq!(fit("Paris is lovely, we love Paris.", "and ", None), "Paris is lovely, we love Paris.");
+        // Only at the start of a sentence elsewhere, so no evidence it's a name
+        assert_eq!(fit("Then home. Then bed.", "and ", None), "then home. Then bed.");
+
+        let context = CursorContext {
+            before: "we flew to ".to_string(),
+            after: None,
+        };
+        let capitalized = vec!["Paris".to_string()];
+        assert_eq!(fit_to_context("Paris today.", &context, true, true, &capitalized), "Paris today.");
+        assert_eq!(fit_to_context("Today.", &context, true, true, &capitalized), "today.");
+    }
+
+    #[test]
+    fn test_fit_to_context_trailing_space() {
+        assert_eq!(fit("First part.", "", Some("Second part.")), "First part. ");
+        assert_eq!(fit("First part.", "", Some(" Second part.")), "First part.");
+        assert_eq!(fit("First part", "", Some(", then more")), "First part");
+        assert_eq!(fit("First part.", "", Some("")), "First part.");
+    }
+
     #[test]
     fn test_process_text_disabled() {
         assert_eq!(
-            process_text("hello world", false),
+            process_text("hello world", Casing::Unchanged),
             "hello world"
         );
     }
@@ -67,8 +371,26 @@ mod tests {
     #[test]
     fn test_process_text_enabled() {
         assert_eq!(
-            process_text("hello world", true),
+            process_text("hello world", Casing::Sentence),
             "Hello world"
         );
     }
+
+    #[test]
+    fn test_casing_modes() {
+        let text = "the state of the API in 2024";
+        assert_eq!(process_text(text, Casing::Lower), "the state of the api in 2024");
+        assert_eq!(process_text(text, Casing::Upper), "THE STATE OF THE API IN 2024");
+        assert_eq!(process_text(text, Casing::Title), "The State of the API in 2024");
+    }
+
+    #[test]
+    fn test_spoken_casing() {
+        assert_eq!(process_text("warning all caps", Casing::Sentence), "WARNING");
+        assert_eq!(process_text("Fix the Login bug, lowercase that.", Casing::Sentence), "fix the login bug");
+        assert_eq!(process_text("release notes title case that", Casing::Unchanged), "Release Notes");
+        // Not a command: part of a word, or the whole dictation
+        assert_eq!(process_text("recall caps", Casing::Unchanged), "recall caps");
+        assert_eq!(process_text("All caps.", Casing::Unchanged), "All caps.");
+    }
 }
@@ -0,0 +1,234 @@
+//! Decimal and thousands separators by locale.
+//!
+//! Engines write numbers the way the dictation language does ("1,234.56" in
+//! English, "1.234,56" in German). With `output.localize_numbers`, numerals
+//! are rewritten to the separators of `output.number_locale` (default: the
+//! OS locale). Only numbers that parse unambiguously in the dictation
+//! language's convention are touched: "1.2.3", "192.168.0.1" or "v1.2" stay
+//! as they are.
+
+use crate::settings::OutputSettings;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const NARROW_NBSP: char = '\u{202F}';
+const NBSP: char = '\u{00A0}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    pub group: char,
+}
+
+impl NumberFormat {
+    pub const ENGLISH: NumberFormat = NumberFormat { decimal: '.', group: ',' };
+
+    /// Separators for a locale ("de-DE", "fr_FR.UTF-8", "en"). Unknown
+    /// languages get the English convention.
+    pub fn for_locale(locale: &str) -> Self {
+        let tag = locale
+            .split(['.', '@'])
+            .next()
+            .unwrap_or("")
+            .replace('_', "-")
+            .to_lowercase();
+        let language = tag.split('-').next().unwrap_or("");
+        let format = |decimal, group| NumberFormat { decimal, group };
+
+        match tag.as_str() {
+            "de-ch" | "de-li" | "fr-ch" | "it-ch" => return format('.', '’'),
+            "es-mx" | "es-us" => return format('.', ','),
+            "pt-pt" | "fr-ca" => return format(',', NBSP),
+            _ => {}
+        }
+        match language {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "da" | "tr" | "el" | "ro" | "hr" | "sl" | "sr" | "vi" => {
+                format(',', '.')
+            }
+            "fr" => format(',', NARROW_NBSP),
+            "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "nb" | "nn" | "no" | "fi" | "hu" | "bg" | "lt" | "lv"
+            | "et" => format(',', NBSP),
+            _ => Self::ENGLISH,
+        }
+    }
+
+    /// The format to write numbers in: `number_locale`, else the OS locale.
+    pub fn from_settings(output: &OutputSettings) -> Self {
+        output
+            .number_locale
+            .as_deref()
+            .filter(|l| !l.is_empty())
+            .or(OS_LOCALE.as_deref())
+            .map(Self::for_locale)
+            .unwrap_or(Self::ENGLISH)
+    }
+
+    /// Split `number` into integer digits, whether it was grouped, and the
+    /// fraction digits. None unless it's a well-formed number in this format.
+    fn parse<'a>(&self, number: &'a str) -> Option<(String, bool, Option<&'a str>)> {
+        let (integer, fraction) = match number.split_once(self.decimal) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+        if fraction.is_some_and(|f| f.is_empty() || !f.chars().all(|c| c.is_ascii_digit())) {
+            return None;
+        }
+
+        let groups: Vec<&str> = integer.split(self.group).collect();
+        let digits = |g: &str| !g.is_empty() && g.chars().all(|c| c.is_ascii_digit());
+        let well_formed = match groups.as_slice() {
+            [single] => digits(single),
+            [first, rest @ ..] => {
+                digits(first) && first.len() <= 3 && rest.iter().all(|g| digits(g) && g.len() == 3)
+            }
+            [] => false,
+        };
+        well_formed.then(|| (groups.concat(), groups.len() > 1, fraction))
+    }
+
+    fn format(&self, integer: &str, grouped: bool, fraction: Option<&str>) -> String {
+        let mut result = String::with_capacity(integer.len() * 2);
+        for (i, digit) in integer.chars().enumerate() {
+            if grouped && i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push(self.group);
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+/// OS locale, read once.
+static OS_LOCALE: Lazy<Option<String>> = Lazy::new(platform::os_locale);
+
+/// Digits with separators in between; a lone integer has nothing to convert.
+static NUMBER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[0-9][0-9.,'’\u{00A0}\u{202F}]*[0-9]").expect("valid number regex"));
+
+/// Rewrite the separators of numbers written in `from` to `to`.
+pub fn localize(text: &str, from: NumberFormat, to: NumberFormat) -> String {
+    if from == to {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for m in NUMBER_RE.find_iter(text) {
+        // Part of a word or a longer token ("v1.2", "1.5GB", "A4")
+        let before = text[..m.start()].chars().next_back();
+        let after = text[m.end()..].chars().next();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == '.' || c == ',')
+            || after.is_some_and(|c| c.is_alphanumeric())
+        {
+            continue;
+        }
+        let Some((integer, grouped, fraction)) = from.parse(m.as_str()) else { continue };
+        if !grouped && fraction.is_none() {
+            continue;
+        }
+        result.push_str(&text[copied..m.start()]);
+        result.push_str(&to.format(&integer, grouped, fraction));
+        copied = m.end();
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::c_void;
+
+    extern "C" {
+        fn CFLocaleCopyCurrent() -> *const c_void;
+        fn CFLocaleGetIdentifier(locale: *const c_void) -> CFStringRef;
+    }
+
+    /// e.g. "de_DE", or "en_US@rg=dezzzz" with a separate region setting
+    pub fn os_locale() -> Option<String> {
+        unsafe {
+            let locale = CFLocaleCopyCurrent();
+            if locale.is_null() {
+                return None;
+            }
+            // Follows the get rule: owned by `locale`
+            let identifier = CFLocaleGetIdentifier(locale);
+            let result = (!identifier.is_null()).then(|| CFString::wrap_under_get_rule(identifier).to_string());
+            CFRelease(locale as CFTypeRef);
+            result
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    pub fn os_locale() -> Option<String> {
+        let mut buf = [0u16; 85]; // LOCALE_NAME_MAX_LENGTH
+        let len = unsafe { GetUserDefaultLocaleName(&mut buf) };
+        if len <= 1 {
+            return None;
+        }
+        // Length includes the terminating null
+        Some(String::from_utf16_lossy(&buf[..len as usize - 1]))
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// The POSIX locale for numbers, e.g. "de_DE.UTF-8"
+    pub fn os_locale() -> Option<String> {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(NumberFormat::for_locale("en-US"), NumberFormat { decimal: '.', group: ',' });
+        assert_eq!(NumberFormat::for_locale("de_DE.UTF-8"), NumberFormat { decimal: ',', group: '.' });
+        assert_eq!(NumberFormat::for_locale("fr-FR"), NumberFormat { decimal: ',', group: NARROW_NBSP });
+        assert_eq!(NumberFormat::for_locale("de-CH"), NumberFormat { decimal: '.', group: '’' });
+        assert_eq!(NumberFormat::for_locale("xx"), NumberFormat::ENGLISH);
+    }
+
+    #[test]
+    fn test_en_us_to_de_de_and_fr_fr() {
+        let en = NumberFormat::for_locale("en-US");
+        let de = NumberFormat::for_locale("de-DE");
+        let fr = NumberFormat::for_locale("fr-FR");
+        assert_eq!(localize("It costs 1,234.56 dollars.", en, de), "It costs 1.234,56 dollars.");
+        assert_eq!(localize("It costs 1,234.56 dollars.", en, fr), "It costs 1\u{202F}234,56 dollars.");
+        assert_eq!(localize("Pi is 3.14, roughly", en, de), "Pi is 3,14, roughly");
+        assert_eq!(localize("1,000,000 users", en, fr), "1\u{202F}000\u{202F}000 users");
+    }
+
+    #[test]
+    fn test_de_de_to_en_us() {
+        let de = NumberFormat::for_locale("de-DE");
+        let en = NumberFormat::for_locale("en-US");
+        assert_eq!(localize("Das kostet 1.234,56 Euro.", de, en), "Das kostet 1,234.56 Euro.");
+        assert_eq!(localize("Etwa 2,5 Liter", de, en), "Etwa 2.5 Liter");
+    }
+
+    #[test]
+    fn test_ambiguous_numbers_unchanged() {
+        let en = NumberFormat::for_locale("en-US");
+        let de = NumberFormat::for_locale("de-DE");
+        for text in ["Version 1.2.3", "ping 192.168.0.1", "v1.2 and 1.5GB", "2024 was the year", "3,4,5 or 12,34"] {
+            assert_eq!(localize(text, en, de), text);
+        }
+        assert_eq!(localize("1,234.5", en, en), "1,234.5");
+    }
+}
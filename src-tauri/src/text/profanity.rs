@@ -0,0 +1,38 @@
+//! Profanity masked in the output: "this is fucking broken" -> "this is f****** broken".
+//!
+//! The first letter stays so the sentence still reads; only whole words from
+//! a short list (and their inflections) are masked.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static PROFANITY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:(?:mother)?fuck\w*|(?:bull)?shit\w*|bitch\w*|asshole\w*|bastards?|cunts?|dickheads?|goddamn\w*|damn(?:ed|it)?)\b",
+    )
+    .expect("valid profanity regex")
+});
+
+/// Mask every listed word but its first letter.
+pub fn mask(text: &str) -> String {
+    PROFANITY_RE
+        .replace_all(text, |caps: &regex::Captures| {
+            let word = &caps[0];
+            let mut chars = word.chars();
+            let first = chars.next().unwrap_or_default();
+            std::iter::once(first).chain(chars.map(|_| '*')).collect::<String>()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("This is fucking broken."), "This is f****** broken.");
+        assert_eq!(mask("Shit, the build, damn it"), "S***, the build, d*** it");
+        assert_eq!(mask("Scunthorpe and classic Dickens"), "Scunthorpe and classic Dickens");
+    }
+}
@@ -0,0 +1,432 @@
+//! Local punctuation restoration for engines/languages that return unpunctuated text.
+//!
+//! Runs a small token-classification model (ONNX export of a HuggingFace
+//! punctuation model) that predicts, for every word, which mark follows it.
+//! Labels are read from the model's `config.json` (`id2label`), so any model
+//! using the common "0" / "." / "," / "?" label scheme works.
+//!
+//! Casing is restored at sentence level only: the word after a sentence end
+//! the model added is capitalized. Names and other mid-sentence capitals
+//! aren't predicted; the engine's casing is kept for them.
+//!
+//! Only compiled when the `punctuation` Cargo feature is enabled.
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::net::{download, DownloadProgress, NetError, ProgressTracker};
+use crate::transcription::integrity;
+
+#[derive(Error, Debug)]
+pub enum PunctuationError {
+    #[error("Punctuation model not downloaded")]
+    ModelNotFound,
+    #[error("Model download failed: {0}")]
+    DownloadError(String),
+    #[error("Punctuation model failed: {0}")]
+    InferenceError(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    IntegrityError(#[from] integrity::IntegrityError),
+}
+
+const MODEL_DIR: &str = "punctuation";
+
+const DEFAULT_REPO: &str = "oliverguhr/fullstop-punctuation-multilang-large";
+
+/// Local file names
+const MODEL_FILES: &[&str] = &["model.onnx", "tokenizer.json", "config.json"];
+
+/// Where repos keep their ONNX export, in order of preference.
+const ONNX_FILES: &[&str] = &[
+    "onnx/model.onnx",
+    "model.onnx",
+    "onnx/model_quantized.onnx",
+    "model_quantized.onnx",
+];
+
+/// Words per inference window. Keeps inputs well under the 512-token limit of
+/// BERT-style encoders even with heavy subword splitting.
+const WINDOW_WORDS: usize = 150;
+
+fn get_model_dir() -> PathBuf {
+    crate::transcription::whisper::get_models_dir().join(MODEL_DIR)
+}
+
+pub fn is_model_downloaded() -> bool {
+    let dir = get_model_dir();
+    MODEL_FILES.iter().all(|name| dir.join(name).exists())
+}
+
+pub fn is_model_loaded() -> bool {
+    PUNCT_CACHE.lock().map(|c| c.is_some()).unwrap_or(false)
+}
+
+// ======================= Download =======================
+
+/// The repo's ONNX export, picked from `files` (paths in the repo).
+fn pick_onnx_file<'a>(files: &[&'a str]) -> Option<&'a str> {
+    ONNX_FILES
+        .iter()
+        .find_map(|candidate| files.iter().find(|f| *f == candidate).copied())
+}
+
+/// Ask HuggingFace which files `repo` has, and return the (path in the repo,
+/// local file name) pairs to download.
+async fn fetch_file_list(repo: &str) -> Result<Vec<(String, &'static str)>, PunctuationError> {
+    #[derive(Deserialize)]
+    struct Sibling {
+        rfilename: String,
+    }
+    #[derive(Deserialize)]
+    struct RepoInfo {
+        #[serde(default)]
+        siblings: Vec<Sibling>,
+    }
+
+    let url = format!("https://huggingface.co/api/models/{}", repo);
+    let client = crate::net::client().map_err(|e| PunctuationError::DownloadError(e.to_string()))?;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| PunctuationError::DownloadError(format!("Failed to list {}: {}", repo, e)))?;
+    if !response.status().is_success() {
+        return Err(PunctuationError::DownloadError(format!(
+            "Failed to list {}: HTTP {}",
+            repo,
+            response.status()
+        )));
+    }
+    let info: RepoInfo = response
+        .json()
+        .await
+        .map_err(|e| PunctuationError::DownloadError(format!("Invalid file list: {}", e)))?;
+    let files: Vec<&str> = info.siblings.iter().map(|s| s.rfilename.as_str()).collect();
+
+    // A repo with only PyTorch weights can't be run — say so instead of a 404
+    let onnx = pick_onnx_file(&files).ok_or_else(|| {
+        PunctuationError::DownloadError(format!(
+            "{} has no ONNX export (looked for {})",
+            repo,
+            ONNX_FILES.join(", ")
+        ))
+    })?;
+    let missing: Vec<&str> = ["tokenizer.json", "config.json"]
+        .into_iter()
+        .filter(|name| !files.contains(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(PunctuationError::DownloadError(format!(
+            "{} is missing required files ({})",
+            repo,
+            missing.join(", ")
+        )));
+    }
+
+    Ok(vec![
+        (onnx.to_string(), "model.onnx"),
+        ("tokenizer.json".to_string(), "tokenizer.json"),
+        ("config.json".to_string(), "config.json"),
+    ])
+}
+
+/// Download the model files from `repo` (default: a multilingual punctuation model).
+/// Calls `on_progress(progress, mirror)` as files arrive; byte counts, rate and
+/// ETA are for the file in flight, `percent` for the whole download.
+pub async fn download_model<F: Fn(&DownloadProgress, &str)>(
+    repo: Option<&str>,
+    on_progress: F,
+) -> Result<(), PunctuationError> {
+    let repo = repo.filter(|r| !r.trim().is_empty()).unwrap_or(DEFAULT_REPO);
+    let dir = get_model_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let files = fetch_file_list(repo).await?;
+    for (index, (remote, local)) in files.iter().enumerate() {
+        let file_path = dir.join(local);
+        let url = format!("https://huggingface.co/{}/resolve/main/{}", repo, remote);
+        log::info!("Downloading punctuation model file: {}", url);
+
+        let expected_sha256 = integrity::expected_for_download(&url).await;
+        let source = download::open(&url)
+            .await
+            .map_err(|e| PunctuationError::DownloadError(format!("{} ({})", e, remote)))?;
+        let mirror = source.mirror().to_string();
+
+        let mut tracker = ProgressTracker::new(source.total_bytes().unwrap_or(0));
+        let tmp_path = file_path.with_extension("part");
+        let actual_sha256 = source
+            .save_to(&tmp_path, |bytes| {
+                tracker.advance(bytes);
+                let mut progress = tracker.progress(100.0);
+                progress.percent = (index as f64 + progress.percent / 100.0) / files.len() as f64 * 100.0;
+                on_progress(&progress, &mirror);
+            })
+            .await
+            .map_err(|e| match e {
+                NetError::IoError(e) => PunctuationError::IoError(e),
+                e => PunctuationError::DownloadError(format!("{} ({})", e, remote)),
+            })?;
+
+        integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
+        std::fs::rename(&tmp_path, &file_path)?;
+        integrity::record(&file_path, &actual_sha256);
+    }
+
+    // Drop any previously loaded model so the new files are picked up
+    unload_model();
+    on_progress(&ProgressTracker::new(0).finished(), "local");
+    Ok(())
+}
+
+pub fn delete_model() -> Result<(), PunctuationError> {
+    unload_model();
+    let dir = get_model_dir();
+    if dir.exists() {
+        for name in MODEL_FILES {
+            integrity::forget(&dir.join(name));
+        }
+        std::fs::remove_dir_all(&dir)?;
+        log::info!("Deleted punctuation model");
+    }
+    Ok(())
+}
+
+// ======================= Model cache =======================
+
+struct PunctuationModel {
+    session: ort::session::Session,
+    tokenizer: tokenizers::Tokenizer,
+    /// Label index -> punctuation mark appended after the word (None = no mark)
+    marks: Vec<Option<char>>,
+}
+
+static PUNCT_CACHE: Lazy<Mutex<Option<Arc<PunctuationModel>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Deserialize)]
+struct ModelConfig {
+    id2label: HashMap<String, String>,
+}
+
+/// Map a model label to the mark it stands for. Handles both the symbol style
+/// ("0", ".", ",") and the name style ("O", "PERIOD", "COMMA").
+fn label_to_mark(label: &str) -> Option<char> {
+    match label.trim().to_uppercase().as_str() {
+        "." | "PERIOD" => Some('.'),
+        "," | "COMMA" => Some(','),
+        "?" | "QUESTION" => Some('?'),
+        "!" | "EXCLAMATION" => Some('!'),
+        ":" | "COLON" => Some(':'),
+        ";" | "SEMICOLON" => Some(';'),
+        _ => None,
+    }
+}
+
+fn load_model() -> Result<PunctuationModel, PunctuationError> {
+    let dir = get_model_dir();
+    if !is_model_downloaded() {
+        return Err(PunctuationError::ModelNotFound);
+    }
+    for name in MODEL_FILES {
+        integrity::verify_before_load(&dir.join(name))?;
+    }
+
+    let start = std::time::Instant::now();
+
+    let config: ModelConfig = serde_json::from_str(&std::fs::read_to_string(dir.join("config.json"))?)
+        .map_err(|e| PunctuationError::InferenceError(format!("Invalid config.json: {}", e)))?;
+    let num_labels = config.id2label.len();
+    let mut marks = vec![None; num_labels];
+    for (id, label) in &config.id2label {
+        if let Some(slot) = id.parse::<usize>().ok().and_then(|i| marks.get_mut(i)) {
+            *slot = label_to_mark(label);
+        }
+    }
+
+    let tokenizer = tokenizers::Tokenizer::from_file(dir.join("tokenizer.json"))
+        .map_err(|e| PunctuationError::InferenceError(format!("Invalid tokenizer.json: {}", e)))?;
+
+    let session = ort::session::Session::builder()
+        .and_then(|b| b.commit_from_file(dir.join("model.onnx")))
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+
+    log::info!(
+        "Punctuation model loaded in {:.2}s ({} labels)",
+        start.elapsed().as_secs_f64(),
+        num_labels
+    );
+
+    Ok(PunctuationModel {
+        session,
+        tokenizer,
+        marks,
+    })
+}
+
+fn get_model() -> Result<Arc<PunctuationModel>, PunctuationError> {
+    let mut cache = PUNCT_CACHE
+        .lock()
+        .map_err(|e| PunctuationError::InferenceError(format!("Cache lock error: {}", e)))?;
+    if let Some(model) = cache.as_ref() {
+        return Ok(Arc::clone(model));
+    }
+    let model = Arc::new(load_model()?);
+    *cache = Some(Arc::clone(&model));
+    Ok(model)
+}
+
+pub fn unload_model() {
+    if let Ok(mut cache) = PUNCT_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+// ======================= Inference =======================
+
+/// Predict the mark following each word in `words` (one window).
+fn predict_marks(model: &PunctuationModel, words: &[&str]) -> Result<Vec<Option<char>>, PunctuationError> {
+    let encoding = model
+        .tokenizer
+        .encode(words.to_vec(), true)
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+
+    let ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+    let mask: Vec<i64> = encoding.get_attention_mask().iter().map(|&m| m as i64).collect();
+    let len = ids.len();
+
+    let input_ids = ndarray::Array2::from_shape_vec((1, len), ids)
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+    let attention_mask = ndarray::Array2::from_shape_vec((1, len), mask)
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+
+    let inputs = ort::inputs![
+        "input_ids" => input_ids,
+        "attention_mask" => attention_mask,
+    ]
+    .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+    let outputs = model
+        .session
+        .run(inputs)
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+    let logits = outputs[0]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| PunctuationError::InferenceError(e.to_string()))?;
+
+    // Use the prediction of each word's last subword token — it's the one
+    // adjacent to where the punctuation goes.
+    let num_labels = model.marks.len();
+    let mut marks = vec![None; words.len()];
+    for (token_index, word_id) in encoding.get_word_ids().iter().enumerate() {
+        let word = match word_id {
+            Some(w) => *w as usize,
+            None => continue,
+        };
+        if word >= marks.len() {
+            continue;
+        }
+        let best = (0..num_labels)
+            .max_by(|&a, &b| {
+                logits[[0, token_index, a]]
+                    .partial_cmp(&logits[[0, token_index, b]])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0);
+        marks[word] = model.marks[best];
+    }
+    Ok(marks)
+}
+
+/// Join `words` with the predicted `marks`, capitalizing the word after each
+/// added sentence end unless it's already cased ("iPhone"). Existing
+/// punctuation on a word is kept as-is.
+fn apply_marks(out: &mut String, words: &[&str], marks: Vec<Option<char>>, sentence_start: &mut bool) {
+    for (word, mark) in words.iter().zip(marks) {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        if std::mem::take(sentence_start) && !word.chars().any(char::is_uppercase) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(chars.as_str());
+            }
+        } else {
+            out.push_str(word);
+        }
+        let has_mark = word.chars().last().map(|c| c.is_ascii_punctuation()).unwrap_or(false);
+        if let Some(mark) = mark.filter(|_| !has_mark) {
+            out.push(mark);
+            *sentence_start = matches!(mark, '.' | '?' | '!');
+        }
+    }
+}
+
+/// Restore punctuation and sentence capitals in `text` (see the module doc).
+/// Runs the model synchronously; call it off the async runtime.
+pub fn restore(text: &str) -> Result<String, PunctuationError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return Ok(text.to_string());
+    }
+
+    let model = get_model()?;
+    let start = std::time::Instant::now();
+
+    let mut out = String::with_capacity(text.len() + words.len() / 4);
+    let mut sentence_start = false;
+    for window in words.chunks(WINDOW_WORDS) {
+        let marks = predict_marks(&model, window)?;
+        apply_marks(&mut out, window, marks, &mut sentence_start);
+    }
+
+    log::info!(
+        "Punctuation restored for {} words in {:.1}ms",
+        words.len(),
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_onnx_file() {
+        assert_eq!(
+            pick_onnx_file(&["config.json", "model.onnx", "onnx/model.onnx"]),
+            Some("onnx/model.onnx")
+        );
+        assert_eq!(
+            pick_onnx_file(&["onnx/model_quantized.onnx", "tokenizer.json"]),
+            Some("onnx/model_quantized.onnx")
+        );
+        assert_eq!(pick_onnx_file(&["pytorch_model.bin", "config.json"]), None);
+    }
+
+    #[test]
+    fn test_apply_marks() {
+        let words = ["hello", "there", "how", "are", "you", "iPhone", "ok.", "bye"];
+        let marks = vec![None, Some('.'), None, None, Some('?'), Some(','), Some('.'), None];
+        let mut out = String::new();
+        let mut sentence_start = false;
+        apply_marks(&mut out, &words, marks, &mut sentence_start);
+        // The word after an added sentence end is capitalized unless it's
+        // already cased; an existing mark is kept and doesn't start a sentence
+        assert_eq!(out, "hello there. How are you? iPhone, ok. bye");
+        assert!(!sentence_start);
+
+        // A sentence end at the edge of a window carries over
+        let mut out = String::new();
+        let mut sentence_start = false;
+        apply_marks(&mut out, &["done"], vec![Some('.')], &mut sentence_start);
+        apply_marks(&mut out, &["next"], vec![None], &mut sentence_start);
+        assert_eq!(out, "done. Next");
+    }
+}
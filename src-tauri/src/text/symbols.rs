@@ -0,0 +1,84 @@
+//! Dictations that are only a spoken punctuation mark: "question mark" -> "?".
+//!
+//! In prose, punctuation comes from the engine, so saying a mark on its own
+//! used to come back as the words ("Question mark."). A dictation made up of
+//! nothing but one of these phrases is turned into the mark, which then
+//! attaches to the text before the cursor like any other insertion. Phrases
+//! inside a longer dictation are left to the engine.
+
+/// Spoken phrase -> mark, compared after `normalize`.
+const MARKS: &[(&str, &str)] = &[
+    ("period", "."),
+    ("full stop", "."),
+    ("comma", ","),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("semi colon", ";"),
+    ("ellipsis", "..."),
+    ("dot dot dot", "..."),
+    ("dash", "—"),
+    ("em dash", "—"),
+    ("hyphen", "-"),
+    ("apostrophe", "'"),
+    ("quote", "\""),
+    ("open quote", "\""),
+    ("close quote", "\""),
+    ("end quote", "\""),
+    ("open paren", "("),
+    ("close paren", ")"),
+    ("open parenthesis", "("),
+    ("close parenthesis", ")"),
+    ("new line", "\n"),
+    ("newline", "\n"),
+    ("new paragraph", "\n\n"),
+];
+
+/// Lowercase words only: engines capitalize, hyphenate ("Semi-colon") and add
+/// their own punctuation ("Question mark?") around a phrase said on its own.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The mark `text` names, if the whole dictation is a spoken punctuation mark.
+pub fn punctuation_only(text: &str) -> Option<&'static str> {
+    let spoken = normalize(text);
+    MARKS
+        .iter()
+        .find(|(phrase, _)| *phrase == spoken)
+        .map(|(_, mark)| *mark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punctuation_only() {
+        assert_eq!(punctuation_only("Question mark."), Some("?"));
+        assert_eq!(punctuation_only(" question mark?"), Some("?"));
+        assert_eq!(punctuation_only("Period."), Some("."));
+        assert_eq!(punctuation_only("Full stop"), Some("."));
+        assert_eq!(punctuation_only("Comma,"), Some(","));
+        assert_eq!(punctuation_only("Exclamation point!"), Some("!"));
+        assert_eq!(punctuation_only("Exclamation mark."), Some("!"));
+        assert_eq!(punctuation_only("Colon."), Some(":"));
+        assert_eq!(punctuation_only("Semi-colon."), Some(";"));
+        assert_eq!(punctuation_only("New paragraph."), Some("\n\n"));
+    }
+
+    #[test]
+    fn test_not_punctuation_only() {
+        assert_eq!(punctuation_only("Is that a question mark?"), None);
+        assert_eq!(punctuation_only("The period ended."), None);
+        assert_eq!(punctuation_only(""), None);
+        assert_eq!(punctuation_only("?"), None);
+    }
+}
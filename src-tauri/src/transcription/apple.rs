@@ -0,0 +1,607 @@
+//! Apple Speech engine: the macOS Speech framework (`SFSpeechRecognizer`),
+//! so dictation works without downloading a model. Recognition runs on the
+//! device unless `transcription.apple_on_device` is turned off, which lets
+//! Apple's servers handle languages the Mac has no on-device model for.
+//!
+//! The framework asks for its own Speech Recognition permission on top of the
+//! microphone's; `request_authorization` shows the prompt.
+//!
+//! While recording, captured audio is fed to a recognition task as it
+//! arrives, so partial results are live. At each pause the task is finished
+//! and a new one started: utterances can then be finalized as chunks, and no
+//! task runs into the framework's limit of about a minute of audio.
+//!
+//! macOS only. The Objective-C side is `apple_speech/apple_speech.m`.
+
+use once_cell::sync::Lazy;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_float, c_int};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::audio::capture::{prepare_for_whisper, read_whisper_buffer};
+use crate::audio::AudioData;
+use crate::error::AppError;
+use crate::panics::LockExt;
+use crate::settings::UserSettings;
+
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::SegmentInfo;
+
+#[derive(Error, Debug)]
+pub enum AppleSpeechError {
+    #[error("Speech Recognition permission not granted (System Settings > Privacy & Security > Speech Recognition)")]
+    NotAuthorized,
+    #[error("Apple speech recognition is unavailable for '{0}'")]
+    Unavailable(String),
+    #[error("Transcription failed: {0}")]
+    TranscriptionError(String),
+}
+
+#[repr(C)]
+struct MsSpeechTask {
+    _opaque: [u8; 0],
+}
+
+extern "C" {
+    fn ms_speech_authorization_status() -> c_int;
+    fn ms_speech_request_authorization() -> c_int;
+    fn ms_speech_is_available(locale: *const c_char) -> c_int;
+    fn ms_speech_supports_on_device(locale: *const c_char) -> c_int;
+    fn ms_speech_supported_locales() -> *mut c_char;
+    fn ms_speech_current_locale() -> *mut c_char;
+    fn ms_speech_free_string(s: *mut c_char);
+    fn ms_speech_task_start(locale: *const c_char, on_device: c_int) -> *mut MsSpeechTask;
+    fn ms_speech_task_append(task: *mut MsSpeechTask, samples: *const c_float, n_samples: c_int) -> c_int;
+    fn ms_speech_task_end_audio(task: *mut MsSpeechTask);
+    fn ms_speech_task_text(task: *mut MsSpeechTask, is_final: *mut c_int) -> *mut c_char;
+    fn ms_speech_task_error(task: *mut MsSpeechTask) -> *mut c_char;
+    fn ms_speech_task_free(task: *mut MsSpeechTask);
+}
+
+pub const ENGINE_ID: &str = "apple";
+
+/// How long a finished task may take to deliver its final transcription,
+/// on top of `FINAL_TIMEOUT_PER_AUDIO_SEC` per second of its audio
+const FINAL_TIMEOUT: Duration = Duration::from_secs(5);
+const FINAL_TIMEOUT_PER_AUDIO_SEC: Duration = Duration::from_millis(500);
+
+/// Take ownership of a string returned by the bridge.
+fn take_string(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let s = unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+    unsafe { ms_speech_free_string(ptr) };
+    Some(s)
+}
+
+fn with_locale<T>(locale: Option<&str>, f: impl FnOnce(*const c_char) -> T) -> T {
+    let locale = locale.and_then(|l| CString::new(l).ok());
+    f(locale.as_ref().map_or(std::ptr::null(), |l| l.as_ptr()))
+}
+
+// ---------------------------------------------------------------------------
+// Permission
+// ---------------------------------------------------------------------------
+
+fn authorization_name(status: c_int) -> &'static str {
+    match status {
+        1 => "denied",
+        2 => "restricted",
+        3 => "authorized",
+        _ => "not_determined",
+    }
+}
+
+/// "not_determined", "denied", "restricted" or "authorized"
+pub fn authorization() -> &'static str {
+    authorization_name(unsafe { ms_speech_authorization_status() })
+}
+
+/// Show the permission prompt if it hasn't been answered and wait for the
+/// answer. Blocks, so call it off the main thread.
+pub fn request_authorization() -> &'static str {
+    authorization_name(unsafe { ms_speech_request_authorization() })
+}
+
+fn is_authorized() -> bool {
+    authorization() == "authorized"
+}
+
+// ---------------------------------------------------------------------------
+// Languages and status
+// ---------------------------------------------------------------------------
+
+/// Supported locales as BCP-47 identifiers ("en-US", "de-DE", ...)
+fn supported_locales() -> Vec<String> {
+    take_string(unsafe { ms_speech_supported_locales() })
+        .map(|list| list.split(',').filter(|l| !l.is_empty()).map(|l| l.replace('_', "-")).collect())
+        .unwrap_or_default()
+}
+
+fn current_locale() -> Option<String> {
+    take_string(unsafe { ms_speech_current_locale() })
+}
+
+/// The supported locale to recognize `language` ("de", or a full "de-AT")
+/// with: an exact match, else the language in the system's region, else the
+/// first locale of the language.
+fn resolve_locale(language: &str, supported: &[String], system: Option<&str>) -> Option<String> {
+    let language = language.replace('_', "-");
+    if let Some(exact) = supported.iter().find(|l| l.eq_ignore_ascii_case(&language)) {
+        return Some(exact.clone());
+    }
+    let prefix = format!("{}-", language.to_lowercase());
+    let of_language: Vec<&String> = supported.iter().filter(|l| l.to_lowercase().starts_with(&prefix)).collect();
+    // "en_GB", "en-GB" or "en_GB@calendar=..."
+    let region = system
+        .and_then(|s| s.split('@').next())
+        .and_then(|s| s.split(['_', '-']).nth(1));
+    if let Some(region) = region {
+        let preferred = format!("{}{}", prefix, region);
+        if let Some(l) = of_language.iter().find(|l| l.eq_ignore_ascii_case(&preferred)) {
+            return Some((*l).clone());
+        }
+    }
+    of_language.first().map(|l| (*l).clone())
+}
+
+/// Locale for `language`; None is the system locale ("auto" or unset).
+fn locale_for(language: Option<&str>) -> Result<Option<String>, AppleSpeechError> {
+    match language {
+        None | Some("auto") => Ok(None),
+        Some(language) => resolve_locale(language, &supported_locales(), current_locale().as_deref())
+            .map(Some)
+            .ok_or_else(|| AppleSpeechError::Unavailable(language.to_string())),
+    }
+}
+
+/// Language codes with at least one supported locale, sorted.
+fn language_codes() -> Vec<String> {
+    let mut codes: Vec<String> = supported_locales()
+        .iter()
+        .filter_map(|l| l.split('-').next())
+        .map(str::to_lowercase)
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+pub fn supported_languages() -> Vec<super::LanguageInfo> {
+    language_codes()
+        .into_iter()
+        .map(|code| super::LanguageInfo {
+            name: super::whisper::language_name(&code).unwrap_or_else(|| code.clone()),
+            code,
+        })
+        .collect()
+}
+
+pub fn get_capabilities() -> super::EngineCapabilities {
+    super::EngineCapabilities {
+        engine: ENGINE_ID.to_string(),
+        available: true,
+        streaming: true,
+        translation: false,
+        timestamps: false,
+        language_selection: true,
+        languages: language_codes(),
+        gpu: false,
+        // Managed by macOS, nothing to download
+        model_sizes: Vec::new(),
+    }
+}
+
+pub struct AppleEngine;
+
+/// Recognition runs in macOS; there's no model to load or download.
+impl TranscriptionEngine for AppleEngine {
+    fn capabilities(&self) -> super::EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, _settings: &UserSettings) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn is_loaded(&self, _settings: &UserSettings) -> bool {
+        true
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move { Ok(transcribe(audio, settings, streaming_prefix).await?) })
+    }
+
+    fn required_models(&self, _settings: &UserSettings) -> Vec<RequiredModel> {
+        Vec::new()
+    }
+
+    fn streaming_text(&self) -> String {
+        streaming_text()
+    }
+
+    fn take_streaming_results(&self) -> (Vec<SegmentInfo>, usize) {
+        take_streaming_results()
+    }
+
+    fn stop_streaming(&self) -> (Vec<SegmentInfo>, usize) {
+        stop_streaming()
+    }
+
+    fn streaming_consumed(&self) -> usize {
+        streaming_consumed()
+    }
+
+    fn is_streaming(&self) -> bool {
+        is_streaming()
+    }
+}
+
+/// Status for `language` (the configured one; None or "auto" for the system locale).
+pub fn get_status(language: Option<&str>) -> super::AppleSpeechStatus {
+    let locale = locale_for(language).ok();
+    let (available, on_device) = match &locale {
+        Some(locale) => with_locale(locale.as_deref(), |l| unsafe {
+            (ms_speech_is_available(l) != 0, ms_speech_supports_on_device(l) != 0)
+        }),
+        None => (false, false),
+    };
+    super::AppleSpeechStatus {
+        supported: true,
+        authorization: authorization().to_string(),
+        locale: locale.flatten().or_else(current_locale),
+        available,
+        on_device,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recognition tasks
+// ---------------------------------------------------------------------------
+
+/// What a dictation is recognized with.
+struct Config {
+    /// None: the system locale
+    locale: Option<String>,
+    on_device: bool,
+}
+
+impl Config {
+    /// From `settings`, with `language` overriding `transcription.language`.
+    fn new(settings: &UserSettings, language: Option<&str>) -> Result<Self, AppleSpeechError> {
+        if !is_authorized() {
+            return Err(AppleSpeechError::NotAuthorized);
+        }
+        Ok(Self {
+            locale: locale_for(language.or(settings.transcription.language.as_deref()))?,
+            on_device: settings.transcription.apple_on_device.unwrap_or(true),
+        })
+    }
+}
+
+/// One `SFSpeechRecognitionTask`, cancelled on drop unless finished.
+struct Task {
+    ptr: *mut MsSpeechTask,
+    /// Samples appended so far
+    samples: usize,
+}
+
+// SAFETY: the bridge guards the task's state with a lock; the task is only
+// used from the thread that owns this handle.
+unsafe impl Send for Task {}
+
+impl Task {
+    fn start(config: &Config) -> Result<Self, AppleSpeechError> {
+        let ptr = with_locale(config.locale.as_deref(), |l| unsafe {
+            ms_speech_task_start(l, config.on_device as c_int)
+        });
+        if ptr.is_null() {
+            let locale = config.locale.clone().unwrap_or_else(|| "system locale".to_string());
+            return Err(AppleSpeechError::Unavailable(if config.on_device {
+                format!("{} (on-device)", locale)
+            } else {
+                locale
+            }));
+        }
+        Ok(Self { ptr, samples: 0 })
+    }
+
+    fn append(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        unsafe { ms_speech_task_append(self.ptr, samples.as_ptr(), samples.len() as c_int) };
+        self.samples += samples.len();
+    }
+
+    /// Best transcription so far, and whether it's final.
+    fn text(&self) -> (String, bool) {
+        let mut is_final: c_int = 0;
+        let text = take_string(unsafe { ms_speech_task_text(self.ptr, &mut is_final) }).unwrap_or_default();
+        (text, is_final != 0)
+    }
+
+    /// End the audio and wait for the final transcription. On timeout the
+    /// latest partial one is used.
+    fn finish(self) -> Result<String, AppleSpeechError> {
+        unsafe { ms_speech_task_end_audio(self.ptr) };
+        let timeout = FINAL_TIMEOUT + FINAL_TIMEOUT_PER_AUDIO_SEC * (self.samples / 16000) as u32;
+        let start = Instant::now();
+        loop {
+            let (text, done) = self.text();
+            if done {
+                if let Some(error) = take_string(unsafe { ms_speech_task_error(self.ptr) }) {
+                    if text.is_empty() {
+                        return Err(AppleSpeechError::TranscriptionError(error));
+                    }
+                    log::warn!("Apple Speech: {} (keeping the partial transcription)", error);
+                }
+                return Ok(text.trim().to_string());
+            }
+            if start.elapsed() >= timeout {
+                log::warn!("Apple Speech: no final result after {:.1}s, using the partial one", timeout.as_secs_f64());
+                return Ok(text.trim().to_string());
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        unsafe { ms_speech_task_free(self.ptr) };
+    }
+}
+
+fn recognize(config: &Config, samples: &[f32]) -> Result<String, AppleSpeechError> {
+    let mut task = Task::start(config)?;
+    // A second at a time, as the recognizer gets audio from a live source
+    for chunk in samples.chunks(16000) {
+        task.append(chunk);
+    }
+    task.finish()
+}
+
+/// Transcribe the tail of a recording and join it onto the streaming prefix.
+/// `settings.transcription.language` is the dictation's language.
+pub async fn transcribe(
+    audio: AudioData,
+    settings: &UserSettings,
+    streaming_prefix: Option<String>,
+) -> Result<(String, Option<SegmentInfo>), AppleSpeechError> {
+    // Less than this after streaming is the tail end of the last word at most
+    const MIN_TAIL_SAMPLES: usize = 4000;
+
+    let samples = prepare_for_whisper(audio);
+    if samples.is_empty() || (streaming_prefix.is_some() && samples.len() < MIN_TAIL_SAMPLES) {
+        return Ok((streaming_prefix.unwrap_or_default(), None));
+    }
+    let tail_duration_ms = (samples.len() / 16) as u32;
+    let config = Config::new(settings, None)?;
+
+    let tail_text = tokio::task::spawn_blocking(move || recognize(&config, &samples))
+        .await
+        .map_err(|e| AppleSpeechError::TranscriptionError(format!("Transcription task failed: {}", e)))??;
+
+    let tail_segment = SegmentInfo {
+        text: tail_text.clone(),
+        duration_ms: tail_duration_ms,
+        engine: ENGINE_ID.to_string(),
+        source: "tail".to_string(),
+        confidence: None,
+        temperature_fallbacks: 0,
+    };
+    let text = match streaming_prefix {
+        Some(prefix) if !prefix.is_empty() && !tail_text.is_empty() => format!("{} {}", prefix, tail_text),
+        Some(prefix) if !prefix.is_empty() => prefix,
+        _ => tail_text,
+    };
+    Ok((text, Some(tail_segment)))
+}
+
+// ---------------------------------------------------------------------------
+// Streaming (live partial results, a task per utterance)
+// ---------------------------------------------------------------------------
+
+static STREAMING_RESULTS: Lazy<Mutex<Vec<SegmentInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Partial transcription of the utterance in progress
+static STREAMING_PARTIAL: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
+
+/// WHISPER_BUFFER position up to which audio has been transcribed
+static STREAMING_CONSUMED: AtomicUsize = AtomicUsize::new(0);
+
+static STREAMING_STOP: AtomicBool = AtomicBool::new(false);
+
+static STREAM_HANDLE: Lazy<Mutex<Option<std::thread::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start recognizing while recording. `language` overrides
+/// `transcription.language` for this dictation.
+pub fn start_streaming(settings: &UserSettings, language: Option<&str>) -> Result<(), AppleSpeechError> {
+    let config = Config::new(settings, language)?;
+    STREAMING_RESULTS.lock_recover().clear();
+    STREAMING_PARTIAL.lock_recover().clear();
+    STREAMING_CONSUMED.store(0, Ordering::SeqCst);
+    STREAMING_STOP.store(false, Ordering::SeqCst);
+
+    let thread = std::thread::Builder::new()
+        .name("apple-speech-streaming".to_string())
+        .spawn(move || {
+            crate::panics::catch("Apple Speech streaming", || stream_loop(config));
+        })
+        .map_err(|e| AppleSpeechError::TranscriptionError(format!("Thread spawn failed: {}", e)))?;
+    if let Ok(mut handle) = STREAM_HANDLE.lock() {
+        *handle = Some(thread);
+    }
+    log::info!("Apple Speech streaming started");
+    Ok(())
+}
+
+/// Finished utterances and the partial one, so far in the current recording.
+pub fn streaming_text() -> String {
+    let mut parts: Vec<String> = STREAMING_RESULTS
+        .lock()
+        .map(|segments| segments.iter().map(|s| s.text.trim().to_string()).collect())
+        .unwrap_or_default();
+    if let Ok(partial) = STREAMING_PARTIAL.lock() {
+        parts.push(partial.trim().to_string());
+    }
+    parts.retain(|p| !p.is_empty());
+    parts.join(" ")
+}
+
+/// Take the utterances finished so far while streaming continues, as
+/// (segments, consumed_sample_count).
+pub fn take_streaming_results() -> (Vec<SegmentInfo>, usize) {
+    let results = STREAMING_RESULTS.lock().map(|mut r| std::mem::take(&mut *r)).unwrap_or_default();
+    (results, STREAMING_CONSUMED.load(Ordering::SeqCst))
+}
+
+pub fn streaming_consumed() -> usize {
+    STREAMING_CONSUMED.load(Ordering::SeqCst)
+}
+
+pub fn is_streaming() -> bool {
+    STREAM_HANDLE
+        .lock()
+        .map(|h| h.as_ref().is_some_and(|t| !t.is_finished()))
+        .unwrap_or(false)
+}
+
+/// Stop streaming, finishing the utterance in progress, and return
+/// (segments, consumed_sample_count); the audio after the consumed count is
+/// the tail for `transcribe`.
+pub fn stop_streaming() -> (Vec<SegmentInfo>, usize) {
+    STREAMING_STOP.store(true, Ordering::SeqCst);
+    if let Some(thread) = STREAM_HANDLE.lock().ok().and_then(|mut h| h.take()) {
+        thread.join().ok();
+    }
+    let (results, consumed) = take_streaming_results();
+    STREAMING_CONSUMED.store(0, Ordering::SeqCst);
+    if let Ok(mut partial) = STREAMING_PARTIAL.lock() {
+        partial.clear();
+    }
+    log::info!("Apple Speech streaming results: {} segments, {} samples consumed", results.len(), consumed);
+    (results, consumed)
+}
+
+/// The utterance in `audio` is over: some speech, then a long enough pause.
+fn utterance_ended(audio: &[f32]) -> bool {
+    // Same thresholds as Whisper's monitor
+    const MIN_SILENCE_GAP_SAMPLES: usize = 8000; // 0.5s
+    const MIN_SPEECH_SAMPLES: usize = 8000;
+    const MIN_VAD_SAMPLES: usize = 16000;
+
+    if audio.len() < MIN_VAD_SAMPLES {
+        return false;
+    }
+    let Some((speech, _)) = super::whisper::detect_speech(audio) else { return false };
+    let Some(last) = speech.last() else { return false };
+    let speech_samples: usize = speech.iter().map(|range| range.len()).sum();
+    audio.len() - last.end >= MIN_SILENCE_GAP_SAMPLES && speech_samples >= MIN_SPEECH_SAMPLES
+}
+
+fn stream_loop(config: Config) {
+    const CHECK_INTERVAL: Duration = Duration::from_millis(200);
+    /// Server-side recognition stops after about a minute of audio
+    const MAX_TASK_SAMPLES: usize = 16000 * 50;
+
+    let mut read_position = 0;
+    // Audio fed to the current task
+    let mut pending: Vec<f32> = Vec::with_capacity(16000 * 10);
+    let mut pending_start = 0;
+    let mut task: Option<Task> = None;
+
+    loop {
+        // Read once more after a stop so the last words go to the task
+        let stopping = STREAMING_STOP.load(Ordering::SeqCst);
+        let before = pending.len();
+        read_position = read_whisper_buffer(read_position, &mut pending);
+        if pending.len() > before {
+            if task.is_none() {
+                match Task::start(&config) {
+                    Ok(t) => task = Some(t),
+                    Err(e) => {
+                        // Whatever wasn't consumed is transcribed on stop
+                        log::warn!("Apple Speech streaming: {}", e);
+                        break;
+                    }
+                }
+            }
+            if let Some(task) = task.as_mut() {
+                task.append(&pending[before..]);
+            }
+        }
+
+        if let Some(current) = task.as_ref() {
+            let (text, _) = current.text();
+            if let Ok(mut partial) = STREAMING_PARTIAL.lock() {
+                *partial = text;
+            }
+        }
+
+        let finish = stopping || pending.len() >= MAX_TASK_SAMPLES || utterance_ended(&pending);
+        let finished = if finish { task.take() } else { None };
+        if let Some(current) = finished {
+            let start = Instant::now();
+            match current.finish() {
+                Ok(text) if !text.is_empty() => {
+                    diag!(Engine, "[apple] Utterance finished in {:.2}s", start.elapsed().as_secs_f64());
+                    if let Ok(mut results) = STREAMING_RESULTS.lock() {
+                        results.push(SegmentInfo {
+                            text,
+                            duration_ms: (pending.len() / 16) as u32,
+                            engine: ENGINE_ID.to_string(),
+                            source: "streaming".to_string(),
+                            confidence: None,
+                            temperature_fallbacks: 0,
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Apple Speech streaming: transcription failed: {}", e),
+            }
+            if let Ok(mut partial) = STREAMING_PARTIAL.lock() {
+                partial.clear();
+            }
+            pending_start += pending.len();
+            pending.clear();
+            STREAMING_CONSUMED.store(pending_start, Ordering::SeqCst);
+        }
+
+        if stopping {
+            break;
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    }
+    log::info!("Apple Speech streaming loop finished");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_locale() {
+        let supported: Vec<String> = ["de-AT", "de-DE", "en-AU", "en-GB", "en-US", "fr-FR"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(resolve_locale("de-de", &supported, None).as_deref(), Some("de-DE"));
+        // The system's region wins, else the first locale of the language
+        assert_eq!(resolve_locale("en", &supported, Some("en_GB")).as_deref(), Some("en-GB"));
+        assert_eq!(resolve_locale("de", &supported, Some("de_DE@calendar=gregorian")).as_deref(), Some("de-DE"));
+        assert_eq!(resolve_locale("fr", &supported, Some("de_CH")).as_deref(), Some("fr-FR"));
+        assert_eq!(resolve_locale("ja", &supported, Some("ja_JP")), None);
+    }
+}
@@ -1,12 +1,32 @@
 //! Cloud STT fallback
 //!
 //! Used when local transcription is unavailable or user prefers cloud processing.
+//! Runs as the "cloud" engine (`transcription.engine`), so a recording is gated
+//! by `privacy::gate_recording` through `Engine::uploads_audio` before any
+//! audio is captured.
 
+use crate::audio::capture::prepare_for_whisper;
 use crate::audio::AudioData;
-use crate::settings::UserSettings;
+use crate::error::AppError;
+use crate::settings::{secrets, UserSettings};
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::{EngineCapabilities, SegmentInfo};
+
+pub const ENGINE_ID: &str = "cloud";
+
+const ASSEMBLYAI_BASE_URL: &str = "https://api.assemblyai.com/v2";
+
+/// How long to wait for a cloud transcript by default
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Between checks of a queued or processing transcript
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Error, Debug)]
 pub enum CloudError {
     #[error("No cloud provider configured")]
@@ -15,6 +35,14 @@ pub enum CloudError {
     RequestError(String),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("No API key stored for {0}")]
+    MissingApiKey(String),
+    #[error("Keychain unavailable: {0}")]
+    Keychain(String),
+    #[error("Transcript not ready after {0}s")]
+    Timeout(u64),
+    #[error(transparent)]
+    Privacy(#[from] crate::privacy::PrivacyError),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,9 +58,86 @@ pub struct CloudTranscriptionResponse {
     pub language: Option<String>,
 }
 
-/// Transcribe audio using cloud STT service
+pub struct CloudEngine;
+
+/// What the cloud engine sends where, for `privacy::gate_recording`.
+pub fn upload_description(settings: &UserSettings) -> String {
+    let provider = settings.transcription.cloud_provider.as_deref().unwrap_or("a cloud provider");
+    format!("audio to {}", provider)
+}
+
+pub fn get_capabilities() -> EngineCapabilities {
+    EngineCapabilities {
+        engine: ENGINE_ID.to_string(),
+        available: true,
+        streaming: false,
+        translation: false,
+        timestamps: false,
+        language_selection: true,
+        // Whatever the provider supports
+        languages: Vec::new(),
+        gpu: false,
+        model_sizes: Vec::new(),
+    }
+}
+
+/// Transcription happens at the provider, only on stop.
+impl TranscriptionEngine for CloudEngine {
+    fn capabilities(&self) -> EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, _settings: &UserSettings) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn is_loaded(&self, _settings: &UserSettings) -> bool {
+        true
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move {
+            let samples = prepare_for_whisper(audio);
+            if samples.is_empty() {
+                return Ok((streaming_prefix.unwrap_or_default(), None));
+            }
+            let text = transcribe(&samples, settings).await?;
+            let segment = SegmentInfo {
+                text: text.clone(),
+                duration_ms: (samples.len() / 16) as u32,
+                engine: ENGINE_ID.to_string(),
+                source: "tail".to_string(),
+                confidence: None,
+                temperature_fallbacks: 0,
+            };
+            let text = match streaming_prefix {
+                Some(prefix) if !prefix.is_empty() && !text.is_empty() => format!("{} {}", prefix, text),
+                Some(prefix) if !prefix.is_empty() => prefix,
+                _ => text,
+            };
+            Ok((text, Some(segment)))
+        })
+    }
+
+    fn required_models(&self, _settings: &UserSettings) -> Vec<RequiredModel> {
+        Vec::new()
+    }
+
+    fn uses_vad(&self) -> bool {
+        false
+    }
+}
+
+/// Transcribe 16kHz mono audio using cloud STT service. The recording must
+/// already have passed `privacy::gate_recording`, which uses up the consent;
+/// this only checks `privacy.allow_cloud` again.
 pub async fn transcribe(
-    audio: &AudioData,
+    samples: &[f32],
     settings: &UserSettings,
 ) -> Result<String, CloudError> {
     let provider = settings
@@ -40,21 +145,22 @@ pub async fn transcribe(
         .cloud_provider
         .as_ref()
         .ok_or(CloudError::NoProvider)?;
+    crate::privacy::allow_upload(&settings.privacy, &upload_description(settings))?;
 
     match provider.as_str() {
-        "openai" => transcribe_openai(audio, settings).await,
-        "aws" => transcribe_aws(audio, settings).await,
-        "assemblyai" => transcribe_assemblyai(audio, settings).await,
+        "openai" => transcribe_openai(samples, settings).await,
+        "aws" => transcribe_aws(samples, settings).await,
+        "assemblyai" => transcribe_assemblyai(samples, settings).await,
         _ => Err(CloudError::NoProvider),
     }
 }
 
 async fn transcribe_openai(
-    audio: &AudioData,
+    samples: &[f32],
     _settings: &UserSettings,
 ) -> Result<String, CloudError> {
     // Convert audio to WAV format for OpenAI API
-    let _wav_data = audio_to_wav(audio)?;
+    let _wav_data = audio_to_wav(samples)?;
 
     // TODO: Implement OpenAI Whisper API call
     // This requires multipart form upload with the audio file
@@ -66,7 +172,7 @@ async fn transcribe_openai(
 }
 
 async fn transcribe_aws(
-    _audio: &AudioData,
+    _samples: &[f32],
     _settings: &UserSettings,
 ) -> Result<String, CloudError> {
     // TODO: Implement AWS Transcribe
@@ -76,19 +182,142 @@ async fn transcribe_aws(
     ))
 }
 
+#[derive(Debug, Deserialize)]
+struct AssemblyAiUpload {
+    upload_url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AssemblyAiTranscriptRequest<'a> {
+    audio_url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_code: Option<&'a str>,
+    language_detection: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssemblyAiTranscript {
+    id: String,
+    /// "queued", "processing", "completed" or "error"
+    status: String,
+    text: Option<String>,
+    error: Option<String>,
+}
+
+/// Turn a failed AssemblyAI response into a `CloudError`, using the
+/// `{"error": "..."}` body when there is one.
+async fn assemblyai_error(response: reqwest::Response) -> CloudError {
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return CloudError::ApiError("AssemblyAI rejected the API key".to_string());
+    }
+    let message = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body["error"].as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| status.to_string());
+    CloudError::ApiError(message)
+}
+
+/// The transcript's text once it is done, or None while it is still queued
+/// or processing.
+fn assemblyai_result(transcript: AssemblyAiTranscript) -> Result<Option<String>, CloudError> {
+    match transcript.status.as_str() {
+        "completed" => Ok(Some(transcript.text.unwrap_or_default().trim().to_string())),
+        "error" => Err(CloudError::ApiError(
+            transcript.error.unwrap_or_else(|| "transcription failed".to_string()),
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Upload the recording, create a transcript job and poll it until it
+/// completes or `transcription.cloud_timeout_secs` passes.
 async fn transcribe_assemblyai(
-    _audio: &AudioData,
-    _settings: &UserSettings,
+    samples: &[f32],
+    settings: &UserSettings,
 ) -> Result<String, CloudError> {
-    // TODO: Implement AssemblyAI
-    log::warn!("AssemblyAI not yet implemented");
-    Err(CloudError::ApiError(
-        "AssemblyAI not yet implemented".to_string(),
-    ))
+    let api_key = secrets::try_get(&secrets::cloud_key_ref("assemblyai"))
+        .map_err(|e| CloudError::Keychain(e.to_string()))?
+        .ok_or_else(|| CloudError::MissingApiKey("AssemblyAI".to_string()))?;
+    let timeout_secs = settings
+        .transcription
+        .cloud_timeout_secs
+        .map_or(DEFAULT_TIMEOUT_SECS, u64::from);
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    let client = crate::net::client().map_err(|e| CloudError::RequestError(e.to_string()))?;
+
+    let response = client
+        .post(format!("{}/upload", ASSEMBLYAI_BASE_URL))
+        .header("authorization", &api_key)
+        .header("content-type", "application/octet-stream")
+        .body(audio_to_wav(samples)?)
+        .send()
+        .await
+        .map_err(|e| CloudError::RequestError(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(assemblyai_error(response).await);
+    }
+    let upload: AssemblyAiUpload = response
+        .json()
+        .await
+        .map_err(|e| CloudError::RequestError(e.to_string()))?;
+
+    let language = settings
+        .transcription
+        .language
+        .as_deref()
+        .filter(|l| *l != "auto");
+    let response = client
+        .post(format!("{}/transcript", ASSEMBLYAI_BASE_URL))
+        .header("authorization", &api_key)
+        .json(&AssemblyAiTranscriptRequest {
+            audio_url: &upload.upload_url,
+            language_code: language,
+            language_detection: language.is_none(),
+        })
+        .send()
+        .await
+        .map_err(|e| CloudError::RequestError(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(assemblyai_error(response).await);
+    }
+    let mut transcript: AssemblyAiTranscript = response
+        .json()
+        .await
+        .map_err(|e| CloudError::RequestError(e.to_string()))?;
+    log::info!("AssemblyAI transcript {} created", transcript.id);
+
+    let id = transcript.id.clone();
+    loop {
+        if let Some(text) = assemblyai_result(transcript)? {
+            return Ok(text);
+        }
+        if Instant::now() + POLL_INTERVAL > deadline {
+            log::warn!("AssemblyAI transcript {} not ready after {}s", id, timeout_secs);
+            return Err(CloudError::Timeout(timeout_secs));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let response = client
+            .get(format!("{}/transcript/{}", ASSEMBLYAI_BASE_URL, id))
+            .header("authorization", &api_key)
+            .send()
+            .await
+            .map_err(|e| CloudError::RequestError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(assemblyai_error(response).await);
+        }
+        transcript = response
+            .json()
+            .await
+            .map_err(|e| CloudError::RequestError(e.to_string()))?;
+    }
 }
 
-/// Convert audio samples to WAV format
-fn audio_to_wav(audio: &AudioData) -> Result<Vec<u8>, CloudError> {
+/// Convert 16kHz mono samples to WAV format
+fn audio_to_wav(samples: &[f32]) -> Result<Vec<u8>, CloudError> {
     use std::io::Cursor;
 
     let spec = hound::WavSpec {
@@ -103,7 +332,7 @@ fn audio_to_wav(audio: &AudioData) -> Result<Vec<u8>, CloudError> {
         .map_err(|e| CloudError::RequestError(e.to_string()))?;
 
     // Convert f32 samples to i16
-    for sample in &audio.samples {
+    for sample in samples {
         let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
         writer
             .write_sample(sample_i16)
@@ -116,3 +345,38 @@ fn audio_to_wav(audio: &AudioData) -> Result<Vec<u8>, CloudError> {
 
     Ok(cursor.into_inner())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(status: &str, text: Option<&str>, error: Option<&str>) -> AssemblyAiTranscript {
+        AssemblyAiTranscript {
+            id: "t1".to_string(),
+            status: status.to_string(),
+            text: text.map(|t| t.to_string()),
+            error: error.map(|e| e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_assemblyai_result() {
+        assert_eq!(assemblyai_result(transcript("queued", None, None)).unwrap(), None);
+        assert_eq!(assemblyai_result(transcript("processing", None, None)).unwrap(), None);
+        assert_eq!(
+            assemblyai_result(transcript("completed", Some(" Hello there. "), None)).unwrap(),
+            Some("Hello there.".to_string())
+        );
+        let err = assemblyai_result(transcript("error", None, Some("Audio file is empty"))).unwrap_err();
+        assert_eq!(err.to_string(), "API error: Audio file is empty");
+    }
+
+    #[test]
+    fn test_uploads_audio() {
+        let mut settings = UserSettings::default();
+        settings.transcription.cloud_provider = Some("assemblyai".to_string());
+        assert!(super::super::Engine::Cloud.uploads_audio(&settings));
+        assert_eq!(super::super::Engine::Cloud.upload_description(&settings), "audio to assemblyai");
+        assert!(!super::super::Engine::Whisper.uploads_audio(&settings));
+    }
+}
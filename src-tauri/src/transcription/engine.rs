@@ -0,0 +1,78 @@
+//! What every transcription engine implements. Callers go through
+//! `Engine::backend()` instead of matching on the engine, so an engine that
+//! isn't compiled into this build needs no cfg gate outside `Engine`.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::audio::AudioData;
+use crate::error::AppError;
+use crate::settings::UserSettings;
+
+use super::required::RequiredModel;
+use super::{EngineCapabilities, EngineTimings, SegmentInfo};
+
+/// The transcribed text and, when there was audio left to transcribe, the
+/// segment it came from.
+pub type Transcribed = Result<(String, Option<SegmentInfo>), AppError>;
+
+pub type TranscribeFuture<'a> = Pin<Box<dyn Future<Output = Transcribed> + Send + 'a>>;
+
+pub trait TranscriptionEngine: Sync {
+    /// What the engine can do in this build.
+    fn capabilities(&self) -> EngineCapabilities;
+
+    /// Load the model `settings` configure; engines the OS runs have nothing to load.
+    fn load(&self, settings: &UserSettings) -> Result<(), AppError>;
+
+    fn is_loaded(&self, settings: &UserSettings) -> bool;
+
+    /// Transcribe the part of a recording after `streaming_prefix`, the text
+    /// streaming already produced.
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a>;
+
+    /// The model files `settings` need for this engine, besides the VAD model.
+    fn required_models(&self, settings: &UserSettings) -> Vec<RequiredModel>;
+
+    /// Whether streaming relies on VAD, so the Silero model is worth downloading.
+    fn uses_vad(&self) -> bool {
+        true
+    }
+
+    /// Text streaming has produced so far in the current recording.
+    fn streaming_text(&self) -> String {
+        String::new()
+    }
+
+    /// Finished streaming segments, taken while streaming keeps running, and
+    /// the buffer position they cover.
+    fn take_streaming_results(&self) -> (Vec<SegmentInfo>, usize) {
+        (Vec::new(), 0)
+    }
+
+    /// Stop streaming: the segments not yet taken and the buffer position
+    /// they cover.
+    fn stop_streaming(&self) -> (Vec<SegmentInfo>, usize) {
+        (Vec::new(), 0)
+    }
+
+    /// Buffer position streaming has transcribed up to.
+    fn streaming_consumed(&self) -> usize {
+        0
+    }
+
+    /// The streaming thread is running.
+    fn is_streaming(&self) -> bool {
+        false
+    }
+
+    /// Where the last `transcribe` spent its time, if the engine measures it.
+    fn take_timings(&self) -> EngineTimings {
+        EngineTimings::default()
+    }
+}
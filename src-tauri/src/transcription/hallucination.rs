@@ -0,0 +1,259 @@
+//! Post-inference guard against text Whisper made up rather than heard.
+//!
+//! Three checks, each configurable in `transcription.hallucination_guard`:
+//! - known phrases Whisper emits on silence ("thanks for watching", ...)
+//! - more words than the audio could hold (noise decoded as a stream of text)
+//! - decoding loops, where a word or short phrase repeats back-to-back
+//!   ("I'm sorry. I'm sorry. I'm sorry. ..."). The output is truncated after
+//!   the loop's first occurrence, as decoding rarely recovers once it starts
+//!   looping, or dropped entirely when the loop makes up most of it.
+
+use crate::settings::HallucinationSettings;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::RwLock;
+
+/// Known whisper hallucination phrases that appear when the model generates
+/// text from silence or near-silence. These are artifacts from the training
+/// data (YouTube subtitles) that the model memorized.
+const HALLUCINATION_PHRASES: &[&str] = &[
+    "thank you",
+    "thanks for watching",
+    "thanks for listening",
+    "thank you for watching",
+    "thank you for listening",
+    "please subscribe",
+    "like and subscribe",
+    "subtitles by",
+    "transcribed by",
+    "copyright",
+    "the end",
+    "you",
+];
+
+const DEFAULT_MAX_WORDS_PER_SECOND: f32 = 6.0;
+const DEFAULT_MAX_REPEATS: u32 = 5;
+/// The words-per-second check needs a few words to be meaningful.
+const MIN_WORDS_FOR_RATE_CHECK: usize = 8;
+/// Longest phrase (in words) considered when looking for loops.
+const MAX_LOOP_PHRASE_WORDS: usize = 4;
+
+/// Resolved thresholds; 0 disables a check.
+#[derive(Debug, Clone, Copy)]
+struct Thresholds {
+    filter_phrases: bool,
+    max_words_per_second: f32,
+    max_repeats: usize,
+}
+
+impl Thresholds {
+    fn from_settings(settings: &HallucinationSettings) -> Self {
+        Self {
+            filter_phrases: settings.filter_phrases.unwrap_or(true),
+            max_words_per_second: settings
+                .max_words_per_second
+                .unwrap_or(DEFAULT_MAX_WORDS_PER_SECOND),
+            max_repeats: settings.max_repeats.unwrap_or(DEFAULT_MAX_REPEATS) as usize,
+        }
+    }
+}
+
+static THRESHOLDS: Lazy<RwLock<Thresholds>> =
+    Lazy::new(|| RwLock::new(Thresholds::from_settings(&HallucinationSettings::default())));
+
+/// Repetition loops found since the last `take_loops_detected`.
+static LOOPS_DETECTED: AtomicU32 = AtomicU32::new(0);
+
+pub fn configure(settings: &HallucinationSettings) {
+    if let Ok(mut thresholds) = THRESHOLDS.write() {
+        *thresholds = Thresholds::from_settings(settings);
+    }
+}
+
+/// Output of the guard: the text to use and, if it was changed, why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filtered {
+    pub text: String,
+    pub reason: Option<String>,
+    /// A repetition loop was truncated or caused the output to be dropped
+    pub repetition_loop: bool,
+}
+
+/// Run the configured checks on `text` transcribed from `audio_seconds` of audio.
+pub fn filter(text: &str, audio_seconds: f32) -> Filtered {
+    let thresholds = THRESHOLDS.read().map(|t| *t).unwrap_or_else(|_| {
+        Thresholds::from_settings(&HallucinationSettings::default())
+    });
+    let filtered = filter_with(text, audio_seconds, &thresholds);
+    if filtered.repetition_loop {
+        LOOPS_DETECTED.fetch_add(1, Ordering::Relaxed);
+    }
+    filtered
+}
+
+/// Number of repetition loops detected since the last call, for stats.
+pub fn take_loops_detected() -> u32 {
+    LOOPS_DETECTED.swap(0, Ordering::Relaxed)
+}
+
+fn filter_with(text: &str, audio_seconds: f32, thresholds: &Thresholds) -> Filtered {
+    let suppressed = |reason: String, repetition_loop: bool| Filtered {
+        text: String::new(),
+        reason: Some(reason),
+        repetition_loop,
+    };
+
+    if thresholds.filter_phrases && is_known_phrase(text) {
+        return suppressed("matches a known hallucination phrase".to_string(), false);
+    }
+
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let mut reason = None;
+
+    if thresholds.max_repeats > 0 {
+        if let Some((start, len, repeats)) = find_loop(&words, thresholds.max_repeats) {
+            let looped = len * repeats;
+            if looped * 2 > words.len() {
+                return suppressed(
+                    format!(
+                        "{}-word phrase repeated {} times makes up most of the output",
+                        len, repeats
+                    ),
+                    true,
+                );
+            }
+            // Keep the first occurrence; whatever follows the loop is unreliable
+            words.truncate(start + len);
+            reason = Some(format!(
+                "truncated at a {}-word phrase repeated {} times",
+                len, repeats
+            ));
+        }
+    }
+
+    if thresholds.max_words_per_second > 0.0
+        && audio_seconds > 0.0
+        && words.len() >= MIN_WORDS_FOR_RATE_CHECK
+    {
+        let rate = words.len() as f32 / audio_seconds;
+        if rate > thresholds.max_words_per_second {
+            return suppressed(
+                format!(
+                    "{} words from {:.1}s of audio ({:.1} words/s)",
+                    words.len(),
+                    audio_seconds,
+                    rate
+                ),
+                false,
+            );
+        }
+    }
+
+    match reason {
+        Some(reason) => Filtered {
+            text: words.join(" "),
+            reason: Some(reason),
+            repetition_loop: true,
+        },
+        None => Filtered {
+            text: text.to_string(),
+            reason: None,
+            repetition_loop: false,
+        },
+    }
+}
+
+/// Whether the whole text is one of the phrases Whisper produces from silence.
+fn is_known_phrase(text: &str) -> bool {
+    let normalized = text.trim().to_lowercase();
+    if normalized.is_empty() {
+        return false; // Empty is handled elsewhere, not a hallucination
+    }
+    HALLUCINATION_PHRASES.iter().any(|phrase| {
+        normalized == *phrase
+            || normalized == format!("{}.", phrase)
+            || normalized == format!("{}!", phrase)
+    })
+}
+
+/// Lowercase with punctuation stripped, so "The the, the." all compare equal.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// First phrase of 1..=MAX_LOOP_PHRASE_WORDS words repeated back-to-back more
+/// than `max_repeats` times, as (start index, phrase length, repeat count).
+fn find_loop(words: &[&str], max_repeats: usize) -> Option<(usize, usize, usize)> {
+    let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+    for start in 0..keys.len() {
+        for len in 1..=MAX_LOOP_PHRASE_WORDS {
+            if start + len * (max_repeats + 1) > keys.len() {
+                break;
+            }
+            let phrase = &keys[start..start + len];
+            let mut repeats = 1;
+            while start + (repeats + 1) * len <= keys.len()
+                && &keys[start + repeats * len..start + (repeats + 1) * len] == phrase
+            {
+                repeats += 1;
+            }
+            if repeats > max_repeats {
+                return Some((start, len, repeats));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: Thresholds = Thresholds {
+        filter_phrases: true,
+        max_words_per_second: DEFAULT_MAX_WORDS_PER_SECOND,
+        max_repeats: DEFAULT_MAX_REPEATS as usize,
+    };
+
+    #[test]
+    fn test_known_phrases() {
+        assert_eq!(filter_with("Thank you.", 1.0, &DEFAULTS).text, "");
+        assert_eq!(filter_with("Thank you for the report.", 3.0, &DEFAULTS).reason, None);
+
+        let off = Thresholds { filter_phrases: false, ..DEFAULTS };
+        assert_eq!(filter_with("Thank you.", 1.0, &off).text, "Thank you.");
+    }
+
+    #[test]
+    fn test_repetition_loops() {
+        let looped = "Please send the final report to the whole team before the meeting on Friday morning. I'm sorry. I'm sorry. I'm sorry. I'm sorry. I'm sorry. I'm sorry.";
+        let filtered = filter_with(looped, 10.0, &DEFAULTS);
+        assert_eq!(
+            filtered.text,
+            "Please send the final report to the whole team before the meeting on Friday morning. I'm sorry."
+        );
+        assert!(filtered.repetition_loop);
+
+        // A loop that is most of the output is dropped entirely
+        let filtered = filter_with("I'm going to I'm going to I'm going to I'm going to I'm going to I'm going to", 5.0, &DEFAULTS);
+        assert_eq!(filtered.text, "");
+        assert!(filtered.repetition_loop);
+
+        // Short, natural repetition is left alone
+        let natural = "No no no, that's not what I meant.";
+        assert_eq!(filter_with(natural, 3.0, &DEFAULTS).text, natural);
+    }
+
+    #[test]
+    fn test_words_per_second() {
+        let text = "one two three four five six seven eight nine ten eleven twelve";
+        assert_eq!(filter_with(text, 1.0, &DEFAULTS).text, "");
+        assert_eq!(filter_with(text, 4.0, &DEFAULTS).text, text);
+
+        let off = Thresholds { max_words_per_second: 0.0, ..DEFAULTS };
+        assert_eq!(filter_with(text, 1.0, &off).text, text);
+    }
+}
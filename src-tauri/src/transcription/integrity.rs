@@ -0,0 +1,416 @@
+//! Model file integrity verification.
+//!
+//! Expected SHA256 hashes of downloads come from two sources, in priority order:
+//! 1. The pinned manifest compiled into the app (`model-manifest.json`), which is
+//!    covered by the app bundle's code signature. It is keyed by HuggingFace
+//!    file (`{repo}/resolve/{revision}/{file}`), so a custom repo or revision
+//!    isn't held to another file's hash; `scripts/update-model-manifest.py`
+//!    regenerates it. A pinned hash is final: the server isn't asked.
+//! 2. The SHA256 HuggingFace reports for LFS files (`X-Linked-Etag`), fetched with
+//!    redirects disabled right before each download.
+//!
+//! Every verified download records its hash in a local manifest next to the models,
+//! so files can be re-checked before loading when `verify_models_on_load` is enabled.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IntegrityError {
+    #[error("Model file '{file}' failed integrity check (expected sha256 {expected}, got {actual}). Delete it and download it again.")]
+    Mismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct Manifest {
+    #[serde(default)]
+    version: u32,
+    /// Lowercase hex SHA256 by path relative to the models directory (local
+    /// manifest) or by HuggingFace file (pinned manifest)
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Pinned hash of the file `url` downloads, for HuggingFace URLs.
+    fn pinned_for(&self, url: &str) -> Option<&String> {
+        let (repo, revision, file) = crate::net::parse_hf_url(url)?;
+        self.files.get(&format!("{}/resolve/{}/{}", repo, revision, file))
+    }
+}
+
+static PINNED_MANIFEST: Lazy<Manifest> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../../model-manifest.json")).unwrap_or_else(|e| {
+        log::error!("Pinned model manifest is invalid: {}", e);
+        Manifest::default()
+    })
+});
+
+/// Whether to re-hash model files before loading them. Mirrors
+/// `TranscriptionSettings::verify_models_on_load`.
+static VERIFY_ON_LOAD: AtomicBool = AtomicBool::new(false);
+
+/// Files already verified this session, so reloading a model doesn't re-hash gigabytes.
+static VERIFIED_THIS_SESSION: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+pub fn set_verify_on_load(enabled: bool) {
+    VERIFY_ON_LOAD.store(enabled, Ordering::SeqCst);
+}
+
+fn local_manifest_path() -> PathBuf {
+    super::whisper::get_models_dir().join("manifest.json")
+}
+
+/// Manifest key for a model file: its path relative to the models directory,
+/// always with forward slashes (e.g. `voxtral-mini-4b/params.json`).
+fn manifest_key(path: &Path) -> String {
+    key_in(&super::whisper::get_models_dir(), path)
+}
+
+fn key_in(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn load_local_manifest() -> Manifest {
+    let path = local_manifest_path();
+    std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_local_manifest(manifest: &Manifest) -> Result<(), IntegrityError> {
+    let path = local_manifest_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string_pretty(manifest)
+        .map_err(std::io::Error::other)?;
+    std::fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Hash recorded when the file on disk was downloaded (and checked against
+/// the pinned manifest, if it had the file).
+pub fn recorded_sha256(path: &Path) -> Option<String> {
+    load_local_manifest().files.get(&manifest_key(path)).cloned()
+}
+
+fn is_sha256_hex(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Ask HuggingFace for the SHA256 of an LFS file without following the
+/// redirect to the CDN (the `X-Linked-Etag` header only appears on the first
+/// hop). Only huggingface.co is asked, never a mirror: the server sending the
+/// bytes mustn't vouch for them. Returns `None` for non-LFS files, non-HuggingFace
+/// URLs, or when HuggingFace can't be reached.
+async fn remote_sha256(url: &str) -> Option<String> {
+    crate::net::parse_hf_url(url)?;
+    let client = crate::net::client_builder()
+        .ok()?
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let response = client.head(url).send().await.ok()?;
+    let etag = response
+        .headers()
+        .get("x-linked-etag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_matches('"').to_lowercase())?;
+    is_sha256_hex(&etag).then_some(etag)
+}
+
+/// Resolve the hash a download must match: pinned manifest first, then the hash
+/// reported by the server. `None` means the file can't be verified.
+pub async fn expected_for_download(url: &str) -> Option<String> {
+    expected_from(&PINNED_MANIFEST, url, remote_sha256(url)).await
+}
+
+/// A pinned hash is the only one that counts for its file: `reported` isn't
+/// even asked for, so a server can't override it with its own etag.
+async fn expected_from(
+    pinned: &Manifest,
+    url: &str,
+    reported: impl std::future::Future<Output = Option<String>>,
+) -> Option<String> {
+    match pinned.pinned_for(url) {
+        Some(hash) => Some(hash.clone()),
+        None => reported.await,
+    }
+}
+
+/// Finalize a streaming hash into lowercase hex.
+pub fn finish_hex(hasher: Sha256) -> String {
+    format!("{:x}", hasher.finalize())
+}
+
+/// Compare a download's hash against the expected one. On mismatch the file is
+/// deleted so a corrupted copy can never be loaded.
+pub fn check_download(path: &Path, expected: Option<&str>, actual: &str) -> Result<(), IntegrityError> {
+    match expected {
+        Some(expected) if !expected.eq_ignore_ascii_case(actual) => {
+            std::fs::remove_file(path).ok();
+            log::error!(
+                "Integrity check failed for {:?}: expected {}, got {}",
+                path,
+                expected,
+                actual
+            );
+            Err(IntegrityError::Mismatch {
+                file: manifest_key(path),
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            })
+        }
+        Some(_) => {
+            log::info!("Integrity check passed for {:?}", path);
+            Ok(())
+        }
+        None => {
+            log::warn!("No expected hash for {:?}, recording sha256 {} unverified", path, actual);
+            Ok(())
+        }
+    }
+}
+
+/// Record a downloaded file's hash so it can be re-verified before loading.
+pub fn record(path: &Path, sha256: &str) {
+    let mut manifest = load_local_manifest();
+    manifest.version = 1;
+    manifest.files.insert(manifest_key(path), sha256.to_string());
+    if let Err(e) = save_local_manifest(&manifest) {
+        log::warn!("Failed to save local model manifest: {}", e);
+    }
+    if let Ok(mut verified) = VERIFIED_THIS_SESSION.lock() {
+        verified.insert(path.to_path_buf());
+    }
+}
+
+/// Drop a file's recorded hash (called when the model is deleted).
+pub fn forget(path: &Path) {
+    let mut manifest = load_local_manifest();
+    if manifest.files.remove(&manifest_key(path)).is_some() {
+        save_local_manifest(&manifest).ok();
+    }
+    if let Ok(mut verified) = VERIFIED_THIS_SESSION.lock() {
+        verified.remove(path);
+    }
+}
+
+pub fn hash_file(path: &Path) -> Result<String, IntegrityError> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(finish_hex(hasher))
+}
+
+/// Re-hash a model file before loading it, if enabled in settings.
+/// Files with no known hash are allowed through (logged), since older installs
+/// downloaded models before hashes were recorded.
+pub fn verify_before_load(path: &Path) -> Result<(), IntegrityError> {
+    if !VERIFY_ON_LOAD.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    if VERIFIED_THIS_SESSION
+        .lock()
+        .map(|v| v.contains(path))
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
+
+    let Some(expected) = recorded_sha256(path) else {
+        log::warn!("No recorded hash for {:?}, skipping load-time verification", path);
+        return Ok(());
+    };
+
+    let start = std::time::Instant::now();
+    verify_file(path, &expected)?;
+    log::info!(
+        "Load-time integrity check passed for {:?} in {:.2}s",
+        path,
+        start.elapsed().as_secs_f64()
+    );
+    if let Ok(mut verified) = VERIFIED_THIS_SESSION.lock() {
+        verified.insert(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Hash `path` and compare it with `expected`.
+fn verify_file(path: &Path, expected: &str) -> Result<(), IntegrityError> {
+    let actual = hash_file(path)?;
+    if !expected.eq_ignore_ascii_case(&actual) {
+        return Err(IntegrityError::Mismatch {
+            file: manifest_key(path),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Outcome of re-checking one recorded model file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCheck {
+    /// Path relative to the models directory
+    pub file: String,
+    /// "ok", "missing" (its record is dropped), "mismatch" or "unreadable"
+    pub status: String,
+}
+
+/// Re-hash every model file with a recorded hash, after files were replaced
+/// or deleted by hand. Records of missing files are dropped, and the
+/// files already verified this session have to be verified again.
+pub fn revalidate() -> Vec<FileCheck> {
+    if let Ok(mut verified) = VERIFIED_THIS_SESSION.lock() {
+        verified.clear();
+    }
+    let mut manifest = load_local_manifest();
+    let root = super::whisper::get_models_dir();
+    let mut checks = Vec::new();
+    let mut missing = Vec::new();
+
+    for (key, recorded) in &manifest.files {
+        let path = root.join(key);
+        let status = if !path.exists() {
+            missing.push(key.clone());
+            "missing"
+        } else {
+            match hash_file(&path) {
+                Ok(actual) if recorded.eq_ignore_ascii_case(&actual) => {
+                    if let Ok(mut verified) = VERIFIED_THIS_SESSION.lock() {
+                        verified.insert(path);
+                    }
+                    "ok"
+                }
+                Ok(_) => "mismatch",
+                Err(e) => {
+                    log::warn!("Could not hash {:?}: {}", path, e);
+                    "unreadable"
+                }
+            }
+        };
+        checks.push(FileCheck {
+            file: key.clone(),
+            status: status.to_string(),
+        });
+    }
+
+    if !missing.is_empty() {
+        for key in &missing {
+            manifest.files.remove(key);
+        }
+        if let Err(e) = save_local_manifest(&manifest) {
+            log::warn!("Failed to update the model manifest: {}", e);
+        }
+    }
+    log::info!(
+        "Revalidated {} model files ({} missing, {} failed)",
+        checks.len(),
+        missing.len(),
+        checks.iter().filter(|c| c.status == "mismatch" || c.status == "unreadable").count()
+    );
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn temp_file(contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("mentascribe-integrity-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_manifest_key() {
+        let root = Path::new("/models");
+        assert_eq!(key_in(root, Path::new("/models/ggml-base.bin")), "ggml-base.bin");
+        assert_eq!(
+            key_in(root, &root.join("voxtral-mini-4b").join("params.json")),
+            "voxtral-mini-4b/params.json"
+        );
+        let pinned = Manifest {
+            version: 1,
+            files: BTreeMap::from([("a/b/resolve/main/f.bin".to_string(), EMPTY_SHA256.to_string())]),
+        };
+        assert!(pinned.pinned_for("https://huggingface.co/a/b/resolve/main/f.bin").is_some());
+        // Another revision or repo isn't held to the pinned hash
+        assert!(pinned.pinned_for("https://huggingface.co/a/b/resolve/v2/f.bin").is_none());
+        assert!(pinned.pinned_for("https://huggingface.co/c/d/resolve/main/f.bin").is_none());
+    }
+
+    #[test]
+    fn test_pinned_hash_overrides_etag() {
+        let url = "https://huggingface.co/a/b/resolve/main/f.bin";
+        let pinned = Manifest {
+            version: 1,
+            files: BTreeMap::from([("a/b/resolve/main/f.bin".to_string(), EMPTY_SHA256.to_string())]),
+        };
+        let etag = "0".repeat(64);
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let expected = runtime.block_on(expected_from(&pinned, url, async { Some(etag.clone()) }));
+        assert_eq!(expected.as_deref(), Some(EMPTY_SHA256));
+        // Without a pinned entry the server's hash is used
+        let other = "https://huggingface.co/a/b/resolve/main/g.bin";
+        let expected = runtime.block_on(expected_from(&pinned, other, async { Some(etag.clone()) }));
+        assert_eq!(expected, Some(etag));
+    }
+
+    #[test]
+    fn test_check_download() {
+        let path = temp_file(b"");
+        assert!(check_download(&path, Some(&EMPTY_SHA256.to_uppercase()), EMPTY_SHA256).is_ok());
+        assert!(check_download(&path, None, EMPTY_SHA256).is_ok());
+        assert!(path.exists());
+
+        // A mismatch deletes the file so it can't be loaded
+        let wrong = "0".repeat(64);
+        assert!(matches!(
+            check_download(&path, Some(&wrong), EMPTY_SHA256),
+            Err(IntegrityError::Mismatch { .. })
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_verify_before_load() {
+        let path = temp_file(b"");
+        assert_eq!(hash_file(&path).unwrap(), EMPTY_SHA256);
+        assert!(verify_file(&path, EMPTY_SHA256).is_ok());
+        std::fs::write(&path, b"tampered").unwrap();
+        assert!(matches!(verify_file(&path, EMPTY_SHA256), Err(IntegrityError::Mismatch { .. })));
+        // Off unless `verify_models_on_load` is set
+        assert!(verify_before_load(&path).is_ok());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,12 +1,116 @@
 pub mod whisper;
 pub mod cloud;
+pub mod engine;
+pub mod integrity;
+pub mod hallucination;
+pub mod recommend;
+pub mod required;
 #[cfg(feature = "voxtral")]
 pub mod voxtral_ffi;
 #[cfg(feature = "voxtral")]
 pub mod voxtral;
+#[cfg(feature = "parakeet")]
+pub mod parakeet;
+#[cfg(target_os = "macos")]
+pub mod apple;
+#[cfg(target_os = "windows")]
+pub mod sapi;
 
 use serde::{Deserialize, Serialize};
 
+use crate::settings::UserSettings;
+
+use engine::TranscriptionEngine;
+
+/// The local engine a dictation runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Whisper,
+    Voxtral,
+    Parakeet,
+    /// macOS Speech framework
+    Apple,
+    /// Windows SAPI dictation
+    Sapi,
+    /// A cloud STT provider (`transcription.cloud_provider`)
+    Cloud,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 6] = [Self::Whisper, Self::Voxtral, Self::Parakeet, Self::Apple, Self::Sapi, Self::Cloud];
+
+    /// The configured engine, `transcription.engine`. One that isn't compiled
+    /// into this build (or isn't on this OS) falls back to Whisper.
+    pub fn from_settings(settings: &UserSettings) -> Self {
+        let configured = settings.transcription.engine.as_deref();
+        Self::ALL
+            .into_iter()
+            .find(|engine| Some(engine.id()) == configured && engine.implementation().is_some())
+            .unwrap_or(Self::Whisper)
+    }
+
+    /// The engine's implementation, None when it isn't compiled into this
+    /// build. The only place that needs to know which engines are.
+    pub fn implementation(self) -> Option<&'static dyn TranscriptionEngine> {
+        match self {
+            Self::Whisper => Some(&whisper::WhisperEngine),
+            #[cfg(feature = "voxtral")]
+            Self::Voxtral => Some(&voxtral::VoxtralEngine),
+            #[cfg(feature = "parakeet")]
+            Self::Parakeet => Some(&parakeet::ParakeetEngine),
+            #[cfg(target_os = "macos")]
+            Self::Apple => Some(&apple::AppleEngine),
+            #[cfg(target_os = "windows")]
+            Self::Sapi => Some(&sapi::SapiEngine),
+            Self::Cloud => Some(&cloud::CloudEngine),
+            // Apple and Windows Speech are never both compiled in
+            _ => None,
+        }
+    }
+
+    /// The implementation to run. `from_settings` only returns compiled
+    /// engines, so the Whisper fallback is for hand-built values.
+    pub fn backend(self) -> &'static dyn TranscriptionEngine {
+        self.implementation().unwrap_or(&whisper::WhisperEngine)
+    }
+
+    pub fn capabilities(self) -> EngineCapabilities {
+        match self.implementation() {
+            Some(engine) => engine.capabilities(),
+            None => EngineCapabilities::unavailable(self.id()),
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Whisper => "whisper",
+            Self::Voxtral => "voxtral",
+            Self::Parakeet => "parakeet",
+            Self::Apple => "apple",
+            Self::Sapi => "sapi",
+            Self::Cloud => cloud::ENGINE_ID,
+        }
+    }
+
+    /// Dictations send audio off the machine: the cloud engine, and Apple
+    /// Speech with on-device recognition turned off.
+    pub fn uploads_audio(self, settings: &UserSettings) -> bool {
+        match self {
+            Self::Cloud => true,
+            Self::Apple => !settings.transcription.apple_on_device.unwrap_or(true),
+            _ => false,
+        }
+    }
+
+    /// What `uploads_audio` sends where, for `privacy::gate_recording`.
+    pub fn upload_description(self, settings: &UserSettings) -> String {
+        match self {
+            Self::Cloud => cloud::upload_description(settings),
+            _ => "audio to Apple's speech servers".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub id: String,
@@ -17,6 +121,18 @@ pub struct ModelInfo {
     pub coreml_size_mb: u32,
 }
 
+/// A downloaded model compared against the latest published revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUpdate {
+    pub id: String,
+    /// SHA256 of the file on disk
+    pub local_sha256: Option<String>,
+    /// SHA256 of the latest revision (pinned manifest, else the server);
+    /// None when it couldn't be determined
+    pub latest_sha256: Option<String>,
+    pub update_available: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoremlStatus {
     /// CoreML is compiled into this build (macOS only)
@@ -27,6 +143,17 @@ pub struct CoremlStatus {
     pub apple_silicon: bool,
 }
 
+/// Which encoder a Whisper model runs with, and why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncoderSelection {
+    pub model_size: String,
+    /// Per-model setting: "auto" (follow use_coreml), "on" or "off"
+    pub setting: String,
+    /// Whether the CoreML encoder is used (otherwise Metal/CPU)
+    pub coreml: bool,
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetalStatus {
     /// Metal GPU feature is compiled into this build (macOS only)
@@ -35,6 +162,56 @@ pub struct MetalStatus {
     pub supported: bool,
 }
 
+/// What an engine can do in this build, so the settings UI can offer only
+/// the controls that apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineCapabilities {
+    /// "whisper", "voxtral", "parakeet", "apple", "sapi" or "cloud"
+    pub engine: String,
+    /// Compiled into this build
+    pub available: bool,
+    /// Produces text while recording instead of only on stop
+    pub streaming: bool,
+    /// Can translate speech into English
+    pub translation: bool,
+    /// Reports segment timestamps
+    pub timestamps: bool,
+    /// Honors `transcription.language`; otherwise the language is always auto-detected
+    pub language_selection: bool,
+    /// Language codes the engine recognizes ("en", "de", ...)
+    pub languages: Vec<String>,
+    /// Runs on the GPU on this machine
+    pub gpu: bool,
+    /// Model ids that can be downloaded for this engine
+    pub model_sizes: Vec<String>,
+}
+
+/// A language a model can transcribe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    /// "en", "de", ...
+    pub code: String,
+    /// "English", "German", ...
+    pub name: String,
+}
+
+impl EngineCapabilities {
+    /// An engine that isn't compiled into this build.
+    pub fn unavailable(engine: &str) -> Self {
+        Self {
+            engine: engine.to_string(),
+            available: false,
+            streaming: false,
+            translation: false,
+            timestamps: false,
+            language_selection: false,
+            languages: Vec::new(),
+            gpu: false,
+            model_sizes: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionResult {
     pub text: String,
@@ -42,6 +219,35 @@ pub struct TranscriptionResult {
     pub duration_ms: u64,
 }
 
+/// One piece of a final transcription, stored with the history entry so the
+/// dashboard can show how the streaming prefix and tail were merged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentInfo {
+    pub text: String,
+    /// Length of the audio this segment was transcribed from
+    pub duration_ms: u32,
+    /// "whisper", "voxtral", "parakeet", "apple", "sapi" or "cloud"
+    pub engine: String,
+    /// "streaming" (transcribed during recording) or "tail" (transcribed on stop)
+    pub source: String,
+    /// Mean token probability (0.0-1.0), when the engine reports one
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Times Whisper decoded again at a higher temperature because the output
+    /// looked unreliable, a sign of difficult audio
+    #[serde(default)]
+    pub temperature_fallbacks: u32,
+}
+
+/// Where an engine spent time on the last tail transcription.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineTimings {
+    pub vad_ms: u64,
+    /// Non-zero only when the model had to be (re)loaded for this transcription
+    pub model_load_ms: u64,
+    pub inference_ms: u64,
+}
+
 /// Status of the Voxtral engine. When the feature is disabled, returns a stub
 /// with compiled=false.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +256,7 @@ pub struct VoxtralStatus {
     pub metal: bool,
     pub model_downloaded: bool,
     pub model_loaded: bool,
+    pub revision: Option<String>,
 }
 
 impl Default for VoxtralStatus {
@@ -59,6 +266,51 @@ impl Default for VoxtralStatus {
             metal: false,
             model_downloaded: false,
             model_loaded: false,
+            revision: None,
         }
     }
 }
+
+/// Status of the Parakeet engine; all false when the feature is disabled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParakeetStatus {
+    pub compiled: bool,
+    pub model_downloaded: bool,
+    pub model_loaded: bool,
+}
+
+/// Status of the Apple Speech engine; `supported` is false off macOS.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppleSpeechStatus {
+    pub supported: bool,
+    /// Speech Recognition permission: "not_determined", "denied",
+    /// "restricted" or "authorized"
+    pub authorization: String,
+    /// Locale the configured language is recognized in ("en-GB")
+    pub locale: Option<String>,
+    /// A recognizer for the locale can be used right now
+    pub available: bool,
+    /// The locale can be recognized without Apple's servers
+    pub on_device: bool,
+}
+
+/// A Windows speech recognizer, installed with a language's speech pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SapiRecognizer {
+    /// Token id, for `transcription.sapi_recognizer`
+    pub id: String,
+    /// "Microsoft Speech Recognizer 8.0 for Windows (English - US)"
+    pub name: String,
+    /// Locale names ("en-US", "en")
+    pub languages: Vec<String>,
+}
+
+/// Status of the Windows Speech engine; `supported` is false off Windows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SapiStatus {
+    pub supported: bool,
+    pub recognizers: Vec<SapiRecognizer>,
+    /// Id of the recognizer a dictation would use now; None when no
+    /// language pack fits the configured language
+    pub selected: Option<String>,
+}
@@ -0,0 +1,206 @@
+//! Log-mel features as NeMo's `AudioToMelSpectrogramPreprocessor` computes
+//! them for Parakeet: pre-emphasis, 25ms Hann windows every 10ms, 512-point
+//! power spectrum, Slaney mel filterbank, natural log, then each mel band
+//! normalized to zero mean and unit variance over the utterance.
+//!
+//! The exported ONNX models take these features as input rather than audio.
+
+use std::f32::consts::PI;
+
+pub const SAMPLE_RATE: usize = 16000;
+pub const N_MELS: usize = 80;
+const N_FFT: usize = 512;
+const WIN_LENGTH: usize = 400;
+/// Samples between frames (10ms)
+pub const HOP_LENGTH: usize = 160;
+const PREEMPHASIS: f32 = 0.97;
+/// Added before the log so silence doesn't give -inf
+const LOG_GUARD: f32 = 5.960_464_5e-8; // 2^-24
+const NORMALIZE_EPS: f32 = 1e-5;
+
+/// Features of `samples` (16kHz mono), mel band major: `[N_MELS][frames]`
+/// flattened, as the model's `[1, N_MELS, frames]` input wants them.
+pub struct Features {
+    pub data: Vec<f32>,
+    pub frames: usize,
+}
+
+pub fn log_mel(samples: &[f32]) -> Features {
+    let frames = samples.len() / HOP_LENGTH + 1;
+
+    let mut emphasized = Vec::with_capacity(samples.len());
+    emphasized.extend(samples.first().copied());
+    emphasized.extend(samples.windows(2).map(|w| w[1] - PREEMPHASIS * w[0]));
+
+    let window = hann_window();
+    let filters = mel_filterbank();
+    let mut data = vec![0.0f32; N_MELS * frames];
+    let mut re = vec![0.0f32; N_FFT];
+    let mut im = vec![0.0f32; N_FFT];
+
+    for frame in 0..frames {
+        // Frames are centered on frame * HOP_LENGTH, with zeros past either end
+        let start = (frame * HOP_LENGTH) as isize - (N_FFT / 2) as isize;
+        for (i, (r, m)) in re.iter_mut().zip(im.iter_mut()).enumerate() {
+            let index = start + i as isize;
+            *r = if index >= 0 && (index as usize) < emphasized.len() {
+                emphasized[index as usize] * window[i]
+            } else {
+                0.0
+            };
+            *m = 0.0;
+        }
+        fft(&mut re, &mut im);
+
+        for (band, weights) in filters.iter().enumerate() {
+            let energy: f32 = weights
+                .iter()
+                .map(|&(bin, weight)| weight * (re[bin] * re[bin] + im[bin] * im[bin]))
+                .sum();
+            data[band * frames + frame] = (energy + LOG_GUARD).ln();
+        }
+    }
+
+    normalize_per_feature(&mut data, frames);
+    Features { data, frames }
+}
+
+/// Symmetric Hann window of `WIN_LENGTH`, zero-padded on both sides to `N_FFT`.
+fn hann_window() -> Vec<f32> {
+    let offset = (N_FFT - WIN_LENGTH) / 2;
+    let mut window = vec![0.0f32; N_FFT];
+    for i in 0..WIN_LENGTH {
+        window[offset + i] = 0.5 - 0.5 * (2.0 * PI * i as f32 / (WIN_LENGTH - 1) as f32).cos();
+    }
+    window
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    // Slaney: linear below 1kHz, logarithmic above
+    if hz < 1000.0 {
+        3.0 * hz / 200.0
+    } else {
+        15.0 + 27.0 * (hz / 1000.0).ln() / 6.4f32.ln()
+    }
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    if mel < 15.0 {
+        200.0 * mel / 3.0
+    } else {
+        1000.0 * (6.4f32.ln() * (mel - 15.0) / 27.0).exp()
+    }
+}
+
+/// Per mel band, the (FFT bin, weight) pairs with a nonzero weight. Triangles
+/// are area-normalized, as librosa's `norm="slaney"`.
+fn mel_filterbank() -> Vec<Vec<(usize, f32)>> {
+    let max_mel = hz_to_mel(SAMPLE_RATE as f32 / 2.0);
+    let points: Vec<f32> = (0..N_MELS + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (N_MELS + 1) as f32))
+        .collect();
+    let bin_hz = SAMPLE_RATE as f32 / N_FFT as f32;
+
+    (0..N_MELS)
+        .map(|band| {
+            let (lower, center, upper) = (points[band], points[band + 1], points[band + 2]);
+            let norm = 2.0 / (upper - lower);
+            (0..=N_FFT / 2)
+                .filter_map(|bin| {
+                    let hz = bin as f32 * bin_hz;
+                    let weight = ((hz - lower) / (center - lower)).min((upper - hz) / (upper - center));
+                    (weight > 0.0).then_some((bin, weight * norm))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Zero mean, unit variance per mel band (`normalize="per_feature"`).
+fn normalize_per_feature(data: &mut [f32], frames: usize) {
+    if frames < 2 {
+        return;
+    }
+    for band in data.chunks_mut(frames) {
+        let mean = band.iter().sum::<f32>() / frames as f32;
+        let variance = band.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / (frames - 1) as f32;
+        let std = variance.sqrt() + NORMALIZE_EPS;
+        for x in band.iter_mut() {
+            *x = (*x - mean) / std;
+        }
+    }
+}
+
+/// In-place radix-2 FFT; the length must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft_matches_dft() {
+        let n = 16;
+        let signal: Vec<f32> = (0..n).map(|i| (i as f32 * 0.7).sin() + 0.25 * i as f32).collect();
+        let (mut re, mut im) = (signal.clone(), vec![0.0; n]);
+        fft(&mut re, &mut im);
+        for k in 0..n {
+            let (mut dft_re, mut dft_im) = (0.0f32, 0.0f32);
+            for (t, x) in signal.iter().enumerate() {
+                let angle = -2.0 * PI * (k * t) as f32 / n as f32;
+                dft_re += x * angle.cos();
+                dft_im += x * angle.sin();
+            }
+            assert!((re[k] - dft_re).abs() < 1e-3 && (im[k] - dft_im).abs() < 1e-3, "bin {}", k);
+        }
+    }
+
+    #[test]
+    fn test_log_mel_shape_and_normalization() {
+        // 1s of a 440Hz tone
+        let samples: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|i| (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect();
+        let features = log_mel(&samples);
+        assert_eq!(features.frames, SAMPLE_RATE / HOP_LENGTH + 1);
+        assert_eq!(features.data.len(), N_MELS * features.frames);
+        for band in features.data.chunks(features.frames) {
+            let mean = band.iter().sum::<f32>() / band.len() as f32;
+            assert!(mean.abs() < 1e-3);
+        }
+        assert!(mel_filterbank().iter().all(|weights| !weights.is_empty()));
+    }
+}
@@ -0,0 +1,576 @@
+//! Parakeet transcription engine: NVIDIA's Parakeet CTC (NeMo FastConformer)
+//! exported to ONNX and run with onnxruntime on the CPU. English only, but
+//! several times faster than Whisper of similar accuracy on machines without
+//! a usable GPU.
+//!
+//! The model is a CTC acoustic model over log-mel features (`features`), so
+//! decoding is a greedy pass over its per-frame token probabilities. While
+//! recording, utterances are cut at pauses with the same VAD as Whisper's
+//! streaming and transcribed as they complete; on stop only the tail is left.
+//!
+//! Only compiled when the `parakeet` Cargo feature is enabled.
+
+mod features;
+
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::audio::capture::{prepare_for_whisper, read_whisper_buffer};
+use crate::audio::AudioData;
+use crate::net::{download, DownloadProgress, NetError, ProgressTracker};
+use crate::panics::LockExt;
+
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::{integrity, SegmentInfo};
+use crate::error::AppError;
+use crate::settings::UserSettings;
+
+#[derive(Error, Debug)]
+pub enum ParakeetError {
+    #[error("Parakeet model not downloaded")]
+    ModelNotFound,
+    #[error("Model download failed: {0}")]
+    DownloadError(String),
+    #[error("Transcription failed: {0}")]
+    TranscriptionError(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    IntegrityError(#[from] integrity::IntegrityError),
+}
+
+pub const MODEL_ID: &str = "parakeet-ctc-0.6b";
+const MODEL_NAME: &str = "Parakeet CTC 0.6B (English)";
+const MODEL_SIZE_MB: u32 = 650;
+
+const MODEL_DIR: &str = "parakeet";
+
+/// ONNX export of nvidia/parakeet-ctc-0.6b; `transcription.parakeet_repo`
+/// can point at another export with the same files.
+const DEFAULT_REPO: &str = "istupakov/parakeet-ctc-0.6b-onnx";
+
+/// (path in the HuggingFace repo, local file name). The int8 model is a
+/// quarter of the float one and runs faster on the CPU.
+const MODEL_FILES: &[(&str, &str)] = &[
+    ("model.int8.onnx", "model.onnx"),
+    ("vocab.txt", "vocab.txt"),
+];
+
+/// SentencePiece marks the start of a word with this instead of a space
+const WORD_START: char = '\u{2581}';
+
+fn get_model_dir() -> PathBuf {
+    super::whisper::get_models_dir().join(MODEL_DIR)
+}
+
+pub fn is_model_downloaded() -> bool {
+    let dir = get_model_dir();
+    MODEL_FILES.iter().all(|(_, name)| dir.join(name).exists())
+}
+
+pub fn is_model_loaded() -> bool {
+    PARAKEET_CACHE.lock().map(|c| c.is_some()).unwrap_or(false)
+}
+
+// ---------------------------------------------------------------------------
+// Status
+// ---------------------------------------------------------------------------
+
+pub fn get_status() -> super::ParakeetStatus {
+    super::ParakeetStatus {
+        compiled: true,
+        model_downloaded: is_model_downloaded(),
+        model_loaded: is_model_loaded(),
+    }
+}
+
+pub fn supported_languages() -> Vec<super::LanguageInfo> {
+    vec![super::LanguageInfo {
+        code: "en".to_string(),
+        name: "English".to_string(),
+    }]
+}
+
+pub fn get_capabilities() -> super::EngineCapabilities {
+    super::EngineCapabilities {
+        engine: "parakeet".to_string(),
+        available: true,
+        // Utterances are transcribed at pauses while recording
+        streaming: true,
+        translation: false,
+        timestamps: false,
+        language_selection: false,
+        languages: vec!["en".to_string()],
+        gpu: false,
+        model_sizes: vec![MODEL_ID.to_string()],
+    }
+}
+
+pub struct ParakeetEngine;
+
+impl TranscriptionEngine for ParakeetEngine {
+    fn capabilities(&self) -> super::EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, _settings: &UserSettings) -> Result<(), AppError> {
+        Ok(preload_model()?)
+    }
+
+    fn is_loaded(&self, _settings: &UserSettings) -> bool {
+        is_model_loaded()
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        _settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move { Ok(transcribe(audio, streaming_prefix).await?) })
+    }
+
+    fn required_models(&self, _settings: &UserSettings) -> Vec<RequiredModel> {
+        RequiredModel::all_of("parakeet", get_available_models())
+    }
+
+    fn streaming_text(&self) -> String {
+        streaming_text()
+    }
+
+    fn take_streaming_results(&self) -> (Vec<SegmentInfo>, usize) {
+        take_streaming_results()
+    }
+
+    fn stop_streaming(&self) -> (Vec<SegmentInfo>, usize) {
+        stop_streaming()
+    }
+
+    fn streaming_consumed(&self) -> usize {
+        streaming_consumed()
+    }
+
+    fn is_streaming(&self) -> bool {
+        is_streaming()
+    }
+}
+
+pub fn get_available_models() -> Vec<super::ModelInfo> {
+    vec![super::ModelInfo {
+        id: MODEL_ID.to_string(),
+        name: MODEL_NAME.to_string(),
+        size_mb: MODEL_SIZE_MB,
+        downloaded: is_model_downloaded(),
+        coreml_downloaded: false,
+        coreml_size_mb: 0,
+    }]
+}
+
+// ---------------------------------------------------------------------------
+// Download
+// ---------------------------------------------------------------------------
+
+/// Download the model files from `repo` (default: the Parakeet CTC 0.6B export).
+/// Calls `on_progress(progress, mirror)` as files arrive; `percent` is for the
+/// whole download.
+pub async fn download_model<F: Fn(&DownloadProgress, &str)>(
+    repo: Option<&str>,
+    on_progress: F,
+) -> Result<(), ParakeetError> {
+    let repo = repo.map(str::trim).filter(|r| !r.is_empty()).unwrap_or(DEFAULT_REPO);
+    let dir = get_model_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    for (index, (remote, local)) in MODEL_FILES.iter().enumerate() {
+        let file_path = dir.join(local);
+        let url = format!("https://huggingface.co/{}/resolve/main/{}", repo, remote);
+        log::info!("Downloading Parakeet model file: {}", url);
+
+        let expected_sha256 = integrity::expected_for_download(&url).await;
+        let source = download::open(&url)
+            .await
+            .map_err(|e| ParakeetError::DownloadError(format!("{} ({})", e, remote)))?;
+        let mirror = source.mirror().to_string();
+
+        let mut tracker = ProgressTracker::new(source.total_bytes().unwrap_or(0));
+        let tmp_path = file_path.with_extension("part");
+        let actual_sha256 = source
+            .save_to(&tmp_path, |bytes| {
+                tracker.advance(bytes);
+                let mut progress = tracker.progress(100.0);
+                progress.percent = (index as f64 + progress.percent / 100.0) / MODEL_FILES.len() as f64 * 100.0;
+                on_progress(&progress, &mirror);
+            })
+            .await
+            .map_err(|e| match e {
+                // Kept as IO so a full disk can be recognized
+                NetError::IoError(e) => ParakeetError::IoError(e),
+                e => ParakeetError::DownloadError(format!("{} ({})", e, remote)),
+            })?;
+
+        integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
+        std::fs::rename(&tmp_path, &file_path)?;
+        integrity::record(&file_path, &actual_sha256);
+    }
+
+    // Drop any previously loaded model so the new files are picked up
+    unload_model();
+    on_progress(&ProgressTracker::new(0).finished(), "local");
+    Ok(())
+}
+
+pub fn delete_model() -> Result<(), ParakeetError> {
+    unload_model();
+    let dir = get_model_dir();
+    if dir.exists() {
+        for (_, name) in MODEL_FILES {
+            integrity::forget(&dir.join(name));
+        }
+        std::fs::remove_dir_all(&dir)?;
+        log::info!("Deleted Parakeet model");
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Model cache
+// ---------------------------------------------------------------------------
+
+struct ParakeetModel {
+    session: ort::session::Session,
+    /// Token id -> text piece
+    vocab: Vec<String>,
+    /// The CTC blank, always the last id
+    blank: usize,
+}
+
+static PARAKEET_CACHE: Lazy<Mutex<Option<Arc<ParakeetModel>>>> = Lazy::new(|| Mutex::new(None));
+
+/// `vocab.txt`: one "<piece> <id>" per line, or just the piece, in id order.
+fn parse_vocab(text: &str) -> Vec<String> {
+    let mut vocab: Vec<String> = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let (piece, id) = match line.rsplit_once(' ') {
+            Some((piece, id)) => match id.parse::<usize>() {
+                Ok(id) => (piece, id),
+                Err(_) => (line, line_index),
+            },
+            None => (line, line_index),
+        };
+        if vocab.len() <= id {
+            vocab.resize(id + 1, String::new());
+        }
+        vocab[id] = piece.to_string();
+    }
+    vocab
+}
+
+fn load_model() -> Result<ParakeetModel, ParakeetError> {
+    let dir = get_model_dir();
+    if !is_model_downloaded() {
+        return Err(ParakeetError::ModelNotFound);
+    }
+    for (_, name) in MODEL_FILES {
+        integrity::verify_before_load(&dir.join(name))?;
+    }
+
+    let start = std::time::Instant::now();
+    let vocab = parse_vocab(&std::fs::read_to_string(dir.join("vocab.txt"))?);
+    if vocab.is_empty() {
+        return Err(ParakeetError::TranscriptionError("Empty vocab.txt".to_string()));
+    }
+    let blank = vocab.len() - 1;
+
+    let session = ort::session::Session::builder()
+        .and_then(|b| b.commit_from_file(dir.join("model.onnx")))
+        .map_err(|e| ParakeetError::TranscriptionError(e.to_string()))?;
+
+    log::info!(
+        "Parakeet model loaded in {:.2}s ({} tokens)",
+        start.elapsed().as_secs_f64(),
+        vocab.len()
+    );
+    Ok(ParakeetModel { session, vocab, blank })
+}
+
+/// Load the model into the cache. No-op if already loaded.
+pub fn preload_model() -> Result<(), ParakeetError> {
+    get_model().map(|_| ())
+}
+
+fn get_model() -> Result<Arc<ParakeetModel>, ParakeetError> {
+    let mut cache = PARAKEET_CACHE.lock_or_reset();
+    if let Some(model) = cache.as_ref() {
+        return Ok(Arc::clone(model));
+    }
+    let model = Arc::new(load_model()?);
+    *cache = Some(Arc::clone(&model));
+    Ok(model)
+}
+
+pub fn unload_model() {
+    if let Ok(mut cache) = PARAKEET_CACHE.lock() {
+        if cache.take().is_some() {
+            log::info!("Parakeet model unloaded from cache");
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Inference
+// ---------------------------------------------------------------------------
+
+/// Transcribe 16kHz mono samples.
+fn recognize(model: &ParakeetModel, samples: &[f32]) -> Result<String, ParakeetError> {
+    let err = |e: ort::Error| ParakeetError::TranscriptionError(e.to_string());
+
+    let features = features::log_mel(samples);
+    let audio_signal = ndarray::Array3::from_shape_vec((1, features::N_MELS, features.frames), features.data)
+        .map_err(|e| ParakeetError::TranscriptionError(e.to_string()))?;
+    let length = ndarray::Array1::from_vec(vec![features.frames as i64]);
+
+    let inputs = ort::inputs![
+        "audio_signal" => audio_signal,
+        "length" => length,
+    ]
+    .map_err(err)?;
+    let outputs = model.session.run(inputs).map_err(err)?;
+    // [1, frames, tokens + 1] log-probabilities
+    let logprobs = outputs[0].try_extract_tensor::<f32>().map_err(err)?;
+    let (frames, classes) = match logprobs.shape() {
+        [1, frames, classes] => (*frames, *classes),
+        shape => {
+            return Err(ParakeetError::TranscriptionError(format!("Unexpected model output shape {:?}", shape)))
+        }
+    };
+    let logprobs = logprobs
+        .as_slice()
+        .ok_or_else(|| ParakeetError::TranscriptionError("Non-contiguous model output".to_string()))?;
+
+    let ids = (0..frames).map(|frame| {
+        let row = &logprobs[frame * classes..(frame + 1) * classes];
+        row.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(model.blank, |(id, _)| id)
+    });
+    Ok(decode_ctc(ids, &model.vocab, model.blank))
+}
+
+/// Greedy CTC: merge repeats, drop blanks, join the pieces into words.
+fn decode_ctc(ids: impl Iterator<Item = usize>, vocab: &[String], blank: usize) -> String {
+    let mut text = String::new();
+    let mut previous = blank;
+    for id in ids {
+        if id != previous && id != blank {
+            if let Some(piece) = vocab.get(id) {
+                text.push_str(piece);
+            }
+        }
+        previous = id;
+    }
+    text.replace(WORD_START, " ").trim().to_string()
+}
+
+/// Transcribe the tail of a recording and join it onto the streaming prefix.
+pub async fn transcribe(
+    audio: AudioData,
+    streaming_prefix: Option<String>,
+) -> Result<(String, Option<SegmentInfo>), ParakeetError> {
+    let samples = prepare_for_whisper(audio);
+    if samples.is_empty() {
+        return Ok((streaming_prefix.unwrap_or_default(), None));
+    }
+    let tail_duration_ms = (samples.len() / 16) as u32;
+    crate::storage::mark_model_used(MODEL_ID);
+
+    let tail_text = tokio::task::spawn_blocking(move || get_model().and_then(|model| recognize(&model, &samples)))
+        .await
+        .map_err(|e| ParakeetError::TranscriptionError(format!("Transcription task failed: {}", e)))??;
+
+    let tail_segment = SegmentInfo {
+        text: tail_text.clone(),
+        duration_ms: tail_duration_ms,
+        engine: "parakeet".to_string(),
+        source: "tail".to_string(),
+        confidence: None,
+        temperature_fallbacks: 0,
+    };
+    let text = match streaming_prefix {
+        Some(prefix) if !prefix.is_empty() && !tail_text.is_empty() => format!("{} {}", prefix, tail_text),
+        Some(prefix) if !prefix.is_empty() => prefix,
+        _ => tail_text,
+    };
+    Ok((text, Some(tail_segment)))
+}
+
+// ---------------------------------------------------------------------------
+// Streaming (utterances cut at pauses, as Whisper's VAD-triggered streaming)
+// ---------------------------------------------------------------------------
+
+static STREAMING_RESULTS: Lazy<Mutex<Vec<SegmentInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// WHISPER_BUFFER position up to which audio has been transcribed
+static STREAMING_CONSUMED: AtomicUsize = AtomicUsize::new(0);
+
+static STREAMING_STOP: AtomicBool = AtomicBool::new(false);
+
+static STREAM_HANDLE: Lazy<Mutex<Option<std::thread::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start transcribing utterances while recording. Needs the model loaded;
+/// without it the whole recording is transcribed on stop.
+pub fn start_streaming() -> Result<(), ParakeetError> {
+    STREAMING_RESULTS.lock_recover().clear();
+    STREAMING_CONSUMED.store(0, Ordering::SeqCst);
+    STREAMING_STOP.store(false, Ordering::SeqCst);
+
+    let model = PARAKEET_CACHE
+        .lock_or_reset()
+        .clone()
+        .ok_or(ParakeetError::ModelNotFound)?;
+    let thread = std::thread::Builder::new()
+        .name("parakeet-streaming".to_string())
+        .spawn(move || {
+            crate::panics::catch("Parakeet streaming", || stream_loop(model));
+        })
+        .map_err(|e| ParakeetError::TranscriptionError(format!("Thread spawn failed: {}", e)))?;
+    if let Ok(mut handle) = STREAM_HANDLE.lock() {
+        *handle = Some(thread);
+    }
+    log::info!("Parakeet streaming started");
+    Ok(())
+}
+
+/// Text of the utterances transcribed so far in the current recording.
+pub fn streaming_text() -> String {
+    STREAMING_RESULTS
+        .lock()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|s| s.text.trim())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+/// Take the utterances transcribed so far while streaming continues, as
+/// (segments, consumed_sample_count).
+pub fn take_streaming_results() -> (Vec<SegmentInfo>, usize) {
+    let results = STREAMING_RESULTS.lock().map(|mut r| std::mem::take(&mut *r)).unwrap_or_default();
+    (results, STREAMING_CONSUMED.load(Ordering::SeqCst))
+}
+
+pub fn streaming_consumed() -> usize {
+    STREAMING_CONSUMED.load(Ordering::SeqCst)
+}
+
+pub fn is_streaming() -> bool {
+    STREAM_HANDLE
+        .lock()
+        .map(|h| h.as_ref().is_some_and(|t| !t.is_finished()))
+        .unwrap_or(false)
+}
+
+/// Stop streaming and return (segments, consumed_sample_count); the audio
+/// after the consumed count is the tail for `transcribe`.
+pub fn stop_streaming() -> (Vec<SegmentInfo>, usize) {
+    STREAMING_STOP.store(true, Ordering::SeqCst);
+    if let Some(thread) = STREAM_HANDLE.lock().ok().and_then(|mut h| h.take()) {
+        thread.join().ok();
+    }
+    let (results, consumed) = take_streaming_results();
+    STREAMING_CONSUMED.store(0, Ordering::SeqCst);
+    log::info!("Parakeet streaming results: {} segments, {} samples consumed", results.len(), consumed);
+    (results, consumed)
+}
+
+fn stream_loop(model: Arc<ParakeetModel>) {
+    // Same thresholds as Whisper's monitor
+    const MIN_SILENCE_GAP_SAMPLES: usize = 8000; // 0.5s
+    const MIN_SPEECH_SAMPLES: usize = 8000;
+    const MIN_VAD_SAMPLES: usize = 16000;
+    const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(300);
+
+    let mut read_position = 0;
+    let mut pending: Vec<f32> = Vec::with_capacity(16000 * 10);
+    let mut pending_start = 0;
+
+    while !STREAMING_STOP.load(Ordering::SeqCst) {
+        std::thread::sleep(CHECK_INTERVAL);
+        read_position = read_whisper_buffer(read_position, &mut pending);
+        if pending.len() < MIN_VAD_SAMPLES {
+            continue;
+        }
+
+        let Some((speech, _)) = super::whisper::detect_speech(&pending) else { continue };
+        let Some(last) = speech.last() else { continue };
+        // Still speaking, or the pause is too short to call the utterance done
+        if pending.len() - last.end < MIN_SILENCE_GAP_SAMPLES {
+            continue;
+        }
+        let speech_samples: Vec<f32> = speech.iter().flat_map(|range| pending[range.clone()].iter().copied()).collect();
+        if speech_samples.len() < MIN_SPEECH_SAMPLES {
+            continue;
+        }
+        // Leave it to the tail rather than make the stop wait
+        if STREAMING_STOP.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let start = std::time::Instant::now();
+        match recognize(&model, &speech_samples) {
+            Ok(text) if !text.is_empty() => {
+                diag!(Engine, "[parakeet] Utterance transcribed in {:.2}s", start.elapsed().as_secs_f64());
+                if let Ok(mut results) = STREAMING_RESULTS.lock() {
+                    results.push(SegmentInfo {
+                        text,
+                        duration_ms: (speech_samples.len() / 16) as u32,
+                        engine: "parakeet".to_string(),
+                        source: "streaming".to_string(),
+                        confidence: None,
+                        temperature_fallbacks: 0,
+                    });
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Parakeet streaming: transcription failed: {}", e),
+        }
+
+        let consumed = last.end.min(pending.len());
+        pending.drain(..consumed);
+        pending_start += consumed;
+        STREAMING_CONSUMED.store(pending_start, Ordering::SeqCst);
+    }
+    log::info!("Parakeet streaming loop finished");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vocab() {
+        let vocab = parse_vocab("<unk> 0\n\u{2581}the 1\ns 2\n<blk> 3\n");
+        assert_eq!(vocab, ["<unk>", "\u{2581}the", "s", "<blk>"]);
+        assert_eq!(parse_vocab("a\nb\n"), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_decode_ctc() {
+        let vocab: Vec<String> = ["\u{2581}he", "llo", "\u{2581}world", "<blk>"].iter().map(|s| s.to_string()).collect();
+        // Repeats merge unless a blank separates them
+        let ids = [3, 0, 0, 3, 1, 1, 3, 2, 3, 2];
+        assert_eq!(decode_ctc(ids.into_iter(), &vocab, 3), "hello world world");
+        assert_eq!(decode_ctc([3, 3].into_iter(), &vocab, 3), "");
+    }
+}
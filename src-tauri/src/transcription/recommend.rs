@@ -0,0 +1,132 @@
+//! First-run choice of a default Whisper model from the machine's memory,
+//! CPU cores and whether it's Apple Silicon (where Metal and CoreML make the
+//! larger models practical).
+
+use serde::{Deserialize, Serialize};
+
+/// What the recommendation was based on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SystemProfile {
+    /// Physical memory; None when it couldn't be read
+    pub memory_gb: Option<f64>,
+    pub cpu_cores: usize,
+    pub apple_silicon: bool,
+}
+
+/// The model picked on first run and why, kept in settings for the onboarding UI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelRecommendation {
+    pub model_size: String,
+    pub rationale: String,
+    pub system: SystemProfile,
+}
+
+/// Memory thresholds sit a little under 8/16 GB, as the OS reports slightly
+/// less than what's installed.
+const GB_8: f64 = 7.5;
+const GB_16: f64 = 15.0;
+
+pub fn probe() -> SystemProfile {
+    SystemProfile {
+        memory_gb: total_memory_bytes().map(|bytes| bytes as f64 / 1_073_741_824.0),
+        cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        apple_silicon: cfg!(all(target_os = "macos", target_arch = "aarch64")),
+    }
+}
+
+pub fn recommend(system: &SystemProfile) -> ModelRecommendation {
+    let (model_size, rationale) = match system.memory_gb {
+        None => ("small", "Memory size unknown: Small is a safe middle ground".to_string()),
+        Some(gb) if system.apple_silicon && gb >= GB_16 => (
+            "large-v3-turbo-q5_0",
+            format!("Apple Silicon with {:.0} GB: Large v3 Turbo (quantized) runs on the GPU at near-large accuracy", gb),
+        ),
+        Some(gb) if system.apple_silicon && gb >= GB_8 => (
+            "small",
+            format!("Apple Silicon with {:.0} GB: Small is fast on the GPU and leaves memory for other apps", gb),
+        ),
+        Some(gb) if system.apple_silicon => (
+            "base",
+            format!("Apple Silicon with {:.0} GB: Base keeps memory use low", gb),
+        ),
+        Some(gb) if gb >= GB_16 && system.cpu_cores >= 8 => (
+            "small",
+            format!("{:.0} GB and {} cores: Small transcribes quickly on the CPU", gb, system.cpu_cores),
+        ),
+        Some(gb) if gb >= GB_16 || (gb >= GB_8 && system.cpu_cores >= 8) => (
+            "base",
+            format!("{:.0} GB and {} cores: Base keeps CPU-only transcription responsive", gb, system.cpu_cores),
+        ),
+        Some(gb) => (
+            "tiny",
+            format!("{:.0} GB and {} cores: Tiny keeps transcription responsive on this CPU", gb, system.cpu_cores),
+        ),
+    };
+    ModelRecommendation {
+        model_size: model_size.to_string(),
+        rationale,
+        system: *system,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn total_memory_bytes() -> Option<u64> {
+    let mut bytes: u64 = 0;
+    let mut len = std::mem::size_of::<u64>();
+    let result = unsafe {
+        libc::sysctlbyname(
+            b"hw.memsize\0".as_ptr() as *const libc::c_char,
+            &mut bytes as *mut u64 as *mut libc::c_void,
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    (result == 0 && bytes > 0).then_some(bytes)
+}
+
+#[cfg(target_os = "windows")]
+fn total_memory_bytes() -> Option<u64> {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+    Some(status.ullTotalPhys)
+}
+
+#[cfg(target_os = "linux")]
+fn total_memory_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(memory_gb: f64, cpu_cores: usize, apple_silicon: bool) -> SystemProfile {
+        SystemProfile {
+            memory_gb: Some(memory_gb),
+            cpu_cores,
+            apple_silicon,
+        }
+    }
+
+    #[test]
+    fn test_recommend() {
+        assert_eq!(recommend(&system(16.0, 10, true)).model_size, "large-v3-turbo-q5_0");
+        assert_eq!(recommend(&system(8.0, 8, true)).model_size, "small");
+        assert_eq!(recommend(&system(7.8, 4, false)).model_size, "tiny");
+        assert_eq!(recommend(&system(8.0, 8, false)).model_size, "base");
+        assert_eq!(recommend(&system(4.0, 2, false)).model_size, "tiny");
+        assert_eq!(recommend(&system(32.0, 16, false)).model_size, "small");
+
+        let unknown = SystemProfile { memory_gb: None, cpu_cores: 4, apple_silicon: false };
+        assert_eq!(recommend(&unknown).model_size, "small");
+    }
+}
@@ -0,0 +1,80 @@
+//! The model files the current settings need: the configured Whisper model
+//! (or Voxtral, or Parakeet; none for the OS engines), its CoreML encoder when
+//! CoreML is on for it, and the Silero VAD model when Silero may be used.
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::UserSettings;
+
+/// Silero VAD model size, rounded up.
+const VAD_SIZE_MB: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequiredModel {
+    /// "ggml", "coreml", "vad", "voxtral" or "parakeet", as in `download-progress`
+    pub model_type: String,
+    pub id: String,
+    pub size_mb: u32,
+    pub downloaded: bool,
+}
+
+impl RequiredModel {
+    /// All of an engine's downloadable models, for engines with a single model.
+    pub(super) fn all_of(model_type: &str, models: Vec<super::ModelInfo>) -> Vec<RequiredModel> {
+        models
+            .into_iter()
+            .map(|m| RequiredModel {
+                model_type: model_type.to_string(),
+                id: m.id,
+                size_mb: m.size_mb,
+                downloaded: m.downloaded,
+            })
+            .collect()
+    }
+}
+
+/// Everything `settings` need, downloaded or not.
+pub fn resolve(settings: &UserSettings) -> Vec<RequiredModel> {
+    let backend = super::Engine::from_settings(settings).backend();
+    let mut required = backend.required_models(settings);
+    // "auto" tries Silero first
+    if backend.uses_vad()
+        && !matches!(settings.recording.vad_backend.as_deref(), Some("webrtc") | Some("energy"))
+    {
+        required.push(RequiredModel {
+            model_type: "vad".to_string(),
+            id: "silero".to_string(),
+            size_mb: VAD_SIZE_MB,
+            downloaded: super::whisper::is_vad_model_downloaded(),
+        });
+    }
+    required
+}
+
+/// The configured Whisper model and, when it runs with CoreML, its encoder.
+pub(super) fn whisper_models(settings: &UserSettings) -> Vec<RequiredModel> {
+    let size = settings.transcription.model_size.as_deref().unwrap_or("small");
+    let mut required = Vec::new();
+    if let Some(model) = super::whisper::get_available_models().into_iter().find(|m| m.id == size) {
+        required.push(RequiredModel {
+            model_type: "ggml".to_string(),
+            id: model.id.clone(),
+            size_mb: model.size_mb,
+            downloaded: model.downloaded,
+        });
+        if super::whisper::get_coreml_status().compiled && super::whisper::coreml_wanted(size).0 {
+            required.push(RequiredModel {
+                model_type: "coreml".to_string(),
+                id: model.id,
+                size_mb: model.coreml_size_mb,
+                downloaded: model.coreml_downloaded,
+            });
+        }
+    }
+    required
+}
+
+/// The ones not on disk yet.
+pub fn missing(settings: &UserSettings) -> Vec<RequiredModel> {
+    resolve(settings).into_iter().filter(|m| !m.downloaded).collect()
+}
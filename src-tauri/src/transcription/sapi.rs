@@ -0,0 +1,477 @@
+//! Windows Speech engine: the SAPI 5 dictation recognizer that comes with
+//! Windows' speech language packs, run in-process on the recorded audio. For
+//! machines that can't run a large Whisper model at a usable speed; it's less
+//! accurate than Whisper but needs no download beyond the language pack.
+//!
+//! Each installed language pack registers a recognizer. The one for
+//! `transcription.language` is used, unless `transcription.sapi_recognizer`
+//! pins one. The recognizer doesn't stream: the recording is transcribed on
+//! stop.
+//!
+//! Windows only.
+
+use std::ffi::c_void;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use windows::core::{w, IUnknown, Interface, HSTRING, PCWSTR, PWSTR};
+use windows::Win32::Foundation::{FALSE, TRUE};
+use windows::Win32::Globalization::{GetUserDefaultLocaleName, LCIDToLocaleName};
+use windows::Win32::Media::Audio::{WAVEFORMATEX, WAVE_FORMAT_PCM};
+use windows::Win32::Media::Speech::{
+    ISpObjectToken, ISpObjectTokenCategory, ISpRecoResult, ISpRecognizer, ISpStream, SpInprocRecognizer,
+    SpObjectToken, SpObjectTokenCategory, SpStream, SPDFID_WaveFormatEx, SPEVENT, SPLO_STATIC, SPRS_ACTIVE,
+    SPRS_INACTIVE,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+};
+use windows::Win32::UI::Shell::SHCreateMemStream;
+
+use crate::audio::capture::prepare_for_whisper;
+use crate::audio::AudioData;
+use crate::error::AppError;
+use crate::settings::UserSettings;
+
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::{SapiRecognizer, SegmentInfo};
+
+#[derive(Error, Debug)]
+pub enum SapiError {
+    #[error("No Windows speech recognizer for '{0}'; install its speech language pack in Settings > Time & Language > Language")]
+    NoRecognizer(String),
+    #[error("Windows speech recognition failed: {0}")]
+    Com(#[from] windows::core::Error),
+    #[error("Transcription failed: {0}")]
+    TranscriptionError(String),
+}
+
+pub const ENGINE_ID: &str = "sapi";
+
+const RECOGNIZERS_CATEGORY: PCWSTR = w!("HKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Speech\\Recognizers");
+
+// SPEVENTENUM ids and SPFEI_FLAGCHECK, from sapi.h
+const SPEI_END_SR_STREAM: u32 = 34;
+const SPEI_RECOGNITION: u32 = 38;
+const SPFEI_FLAGCHECK: u64 = (1 << 30) | (1 << 33);
+
+// SPEVENTLPARAMTYPE
+const SPET_LPARAM_IS_TOKEN: u32 = 1;
+const SPET_LPARAM_IS_OBJECT: u32 = 2;
+const SPET_LPARAM_IS_POINTER: u32 = 3;
+const SPET_LPARAM_IS_STRING: u32 = 4;
+
+/// `SP_GETWHOLEPHRASE`
+const WHOLE_PHRASE: u32 = u32::MAX;
+
+/// The recognizer gets through audio several times faster than real time;
+/// past this much per second of audio plus `BASE_TIMEOUT` it's given up on
+const TIMEOUT_PER_AUDIO_SEC: Duration = Duration::from_millis(1000);
+const BASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// COM initialized on the current thread for the guard's lifetime.
+struct ComGuard {
+    initialized: bool,
+}
+
+impl ComGuard {
+    fn new() -> Self {
+        let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok();
+        Self { initialized }
+    }
+}
+
+impl Drop for ComGuard {
+    fn drop(&mut self) {
+        if self.initialized {
+            unsafe { CoUninitialize() };
+        }
+    }
+}
+
+/// Take ownership of a string SAPI allocated with `CoTaskMemAlloc`.
+fn take_co_string(s: PWSTR) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    let text = unsafe { s.to_string() }.unwrap_or_default();
+    unsafe { CoTaskMemFree(Some(s.0 as *const c_void)) };
+    text
+}
+
+// ---------------------------------------------------------------------------
+// Recognizers (language packs)
+// ---------------------------------------------------------------------------
+
+/// A recognizer token's `Language` attribute: hex LCIDs separated by ';'
+/// ("409;9" is en-US, then neutral English).
+fn parse_lcids(value: &str) -> Vec<u32> {
+    value
+        .split(';')
+        .filter_map(|lcid| u32::from_str_radix(lcid.trim(), 16).ok())
+        .filter(|&lcid| lcid != 0)
+        .collect()
+}
+
+fn lcid_to_locale(lcid: u32) -> Option<String> {
+    let mut buf = [0u16; 85]; // LOCALE_NAME_MAX_LENGTH
+    let len = unsafe { LCIDToLocaleName(lcid, Some(&mut buf), 0) };
+    // Length includes the terminating null
+    (len > 1).then(|| String::from_utf16_lossy(&buf[..len as usize - 1]))
+}
+
+fn system_locale() -> Option<String> {
+    let mut buf = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buf) };
+    (len > 1).then(|| String::from_utf16_lossy(&buf[..len as usize - 1]))
+}
+
+/// The speech recognizers installed with language packs.
+pub fn recognizers() -> Result<Vec<SapiRecognizer>, SapiError> {
+    let _com = ComGuard::new();
+    let mut recognizers = Vec::new();
+    unsafe {
+        let category: ISpObjectTokenCategory = CoCreateInstance(&SpObjectTokenCategory, None, CLSCTX_ALL)?;
+        category.SetId(RECOGNIZERS_CATEGORY, FALSE)?;
+        let tokens = category.EnumTokens(PCWSTR::null(), PCWSTR::null())?;
+        let mut count = 0u32;
+        tokens.GetCount(&mut count)?;
+        for index in 0..count {
+            let token = tokens.Item(index)?;
+            let id = take_co_string(token.GetId()?);
+            // The default value is the display name
+            let name = token.GetStringValue(PCWSTR::null()).map(take_co_string).unwrap_or_default();
+            let languages = token
+                .OpenKey(w!("Attributes"))
+                .and_then(|attributes| attributes.GetStringValue(w!("Language")))
+                .map(|value| parse_lcids(&take_co_string(value)))
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(lcid_to_locale)
+                .collect();
+            recognizers.push(SapiRecognizer { id, name, languages });
+        }
+    }
+    Ok(recognizers)
+}
+
+/// The recognizer to use: `pinned` if it's installed, else the first one for
+/// `language` ("de" or "de-DE"; None or "auto" for the system locale's).
+fn pick_recognizer<'a>(
+    recognizers: &'a [SapiRecognizer],
+    pinned: Option<&str>,
+    language: Option<&str>,
+    system: Option<&str>,
+) -> Option<&'a SapiRecognizer> {
+    if let Some(pinned) = pinned.filter(|p| !p.is_empty()) {
+        if let Some(recognizer) = recognizers.iter().find(|r| r.id.eq_ignore_ascii_case(pinned)) {
+            return Some(recognizer);
+        }
+    }
+    let language = match language {
+        None | Some("auto") => system?,
+        Some(language) => language,
+    };
+    let wanted = language.split(['-', '_']).next().unwrap_or(language);
+    let speaks = |r: &&SapiRecognizer, exact: bool| {
+        r.languages.iter().any(|l| {
+            if exact {
+                l.eq_ignore_ascii_case(language)
+            } else {
+                l.split('-').next().is_some_and(|code| code.eq_ignore_ascii_case(wanted))
+            }
+        })
+    };
+    recognizers
+        .iter()
+        .find(|r| speaks(r, true))
+        .or_else(|| recognizers.iter().find(|r| speaks(r, false)))
+}
+
+/// The recognizer a dictation with `settings` would use.
+fn recognizer_for(settings: &UserSettings) -> Result<SapiRecognizer, SapiError> {
+    let recognizers = recognizers()?;
+    let language = settings.transcription.language.as_deref();
+    pick_recognizer(
+        &recognizers,
+        settings.transcription.sapi_recognizer.as_deref(),
+        language,
+        system_locale().as_deref(),
+    )
+    .cloned()
+    .ok_or_else(|| SapiError::NoRecognizer(language.unwrap_or("auto").to_string()))
+}
+
+/// Language codes with an installed recognizer, sorted.
+fn language_codes(recognizers: &[SapiRecognizer]) -> Vec<String> {
+    let mut codes: Vec<String> = recognizers
+        .iter()
+        .flat_map(|r| r.languages.iter())
+        .filter_map(|l| l.split('-').next())
+        .map(str::to_lowercase)
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+pub fn supported_languages() -> Vec<super::LanguageInfo> {
+    language_codes(&recognizers().unwrap_or_default())
+        .into_iter()
+        .map(|code| super::LanguageInfo {
+            name: super::whisper::language_name(&code).unwrap_or_else(|| code.clone()),
+            code,
+        })
+        .collect()
+}
+
+/// Whether `language` ("auto", "de", ...) has an installed recognizer.
+pub fn supports_language(language: &str) -> bool {
+    let recognizers = recognizers().unwrap_or_default();
+    pick_recognizer(&recognizers, None, Some(language), system_locale().as_deref()).is_some()
+}
+
+pub fn get_capabilities() -> super::EngineCapabilities {
+    let recognizers = recognizers().unwrap_or_default();
+    super::EngineCapabilities {
+        engine: ENGINE_ID.to_string(),
+        available: !recognizers.is_empty(),
+        streaming: false,
+        translation: false,
+        timestamps: false,
+        language_selection: true,
+        languages: language_codes(&recognizers),
+        gpu: false,
+        // Language packs are installed through Windows Settings
+        model_sizes: Vec::new(),
+    }
+}
+
+pub struct SapiEngine;
+
+/// Recognition runs in Windows and only on stop: nothing to load or download,
+/// no streaming.
+impl TranscriptionEngine for SapiEngine {
+    fn capabilities(&self) -> super::EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, _settings: &UserSettings) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn is_loaded(&self, _settings: &UserSettings) -> bool {
+        true
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move { Ok(transcribe(audio, settings, streaming_prefix).await?) })
+    }
+
+    fn required_models(&self, _settings: &UserSettings) -> Vec<RequiredModel> {
+        Vec::new()
+    }
+
+    /// Doesn't stream, so there are no utterances for VAD to cut
+    fn uses_vad(&self) -> bool {
+        false
+    }
+}
+
+pub fn get_status(settings: &UserSettings) -> super::SapiStatus {
+    let recognizers = recognizers().unwrap_or_else(|e| {
+        log::warn!("Listing Windows speech recognizers failed: {}", e);
+        Vec::new()
+    });
+    let selected = pick_recognizer(
+        &recognizers,
+        settings.transcription.sapi_recognizer.as_deref(),
+        settings.transcription.language.as_deref(),
+        system_locale().as_deref(),
+    )
+    .map(|r| r.id.clone());
+    super::SapiStatus {
+        supported: true,
+        recognizers,
+        selected,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recognition
+// ---------------------------------------------------------------------------
+
+/// 16-bit PCM, which SAPI takes with a `WAVEFORMATEX`.
+fn to_pcm16(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// Release what an event's lParam holds, as `SpClearEvent` does.
+unsafe fn clear_event(event: &SPEVENT) {
+    let ptr = event.lParam.0 as *mut c_void;
+    if ptr.is_null() {
+        return;
+    }
+    match (event._bitfield as u32) >> 16 {
+        SPET_LPARAM_IS_TOKEN | SPET_LPARAM_IS_OBJECT => drop(IUnknown::from_raw(ptr)),
+        SPET_LPARAM_IS_POINTER | SPET_LPARAM_IS_STRING => CoTaskMemFree(Some(ptr as *const c_void)),
+        _ => {}
+    }
+}
+
+/// Text of a recognition event's phrase.
+unsafe fn phrase_text(event: &SPEVENT) -> Option<String> {
+    let result: ISpRecoResult = IUnknown::from_raw_borrowed(&(event.lParam.0 as *mut c_void))?.cast().ok()?;
+    let mut text = PWSTR::null();
+    result.GetText(WHOLE_PHRASE, WHOLE_PHRASE, TRUE, &mut text, None).ok()?;
+    Some(take_co_string(text))
+}
+
+/// Dictate 16kHz mono `samples` with the recognizer `recognizer_id`.
+fn recognize(samples: &[f32], recognizer_id: &str) -> Result<String, SapiError> {
+    let _com = ComGuard::new();
+    let timeout = BASE_TIMEOUT + TIMEOUT_PER_AUDIO_SEC * (samples.len() / 16000) as u32;
+    let mut phrases = Vec::new();
+    unsafe {
+        let recognizer: ISpRecognizer = CoCreateInstance(&SpInprocRecognizer, None, CLSCTX_ALL)?;
+        let token: ISpObjectToken = CoCreateInstance(&SpObjectToken, None, CLSCTX_ALL)?;
+        token.SetId(PCWSTR::null(), &HSTRING::from(recognizer_id), FALSE)?;
+        recognizer.SetRecognizer(&token)?;
+
+        let pcm = to_pcm16(samples);
+        let memory = SHCreateMemStream(Some(&pcm))
+            .ok_or_else(|| SapiError::TranscriptionError("Couldn't create the audio stream".to_string()))?;
+        let format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_PCM as u16,
+            nChannels: 1,
+            nSamplesPerSec: 16000,
+            nAvgBytesPerSec: 32000,
+            nBlockAlign: 2,
+            wBitsPerSample: 16,
+            cbSize: 0,
+        };
+        let stream: ISpStream = CoCreateInstance(&SpStream, None, CLSCTX_ALL)?;
+        stream.SetBaseStream(&memory, &SPDFID_WaveFormatEx, &format)?;
+        recognizer.SetInput(&stream, FALSE)?;
+
+        let context = recognizer.CreateRecoContext()?;
+        context.SetNotifyWin32Event()?;
+        let interest = (1 << SPEI_RECOGNITION) | (1 << SPEI_END_SR_STREAM) | SPFEI_FLAGCHECK;
+        context.SetInterest(interest, interest)?;
+        let grammar = context.CreateGrammar(0)?;
+        grammar.LoadDictation(PCWSTR::null(), SPLO_STATIC)?;
+        grammar.SetDictationState(SPRS_ACTIVE)?;
+
+        let start = Instant::now();
+        'events: loop {
+            if start.elapsed() >= timeout {
+                log::warn!("Windows Speech: no end of stream after {:.0}s, keeping what was recognized", timeout.as_secs_f64());
+                break;
+            }
+            // Times out with S_FALSE, which is fine: the queue is checked either way
+            context.WaitForNotifyEvent(200).ok();
+            loop {
+                let mut event = SPEVENT::default();
+                let mut fetched = 0u32;
+                if context.GetEvents(1, &mut event, &mut fetched).is_err() || fetched == 0 {
+                    break;
+                }
+                let id = event._bitfield as u32 & 0xFFFF;
+                if id == SPEI_RECOGNITION {
+                    if let Some(text) = phrase_text(&event).filter(|t| !t.trim().is_empty()) {
+                        phrases.push(text.trim().to_string());
+                    }
+                }
+                clear_event(&event);
+                if id == SPEI_END_SR_STREAM {
+                    break 'events;
+                }
+            }
+        }
+        grammar.SetDictationState(SPRS_INACTIVE).ok();
+    }
+    Ok(phrases.join(" "))
+}
+
+/// Transcribe a recording. Without streaming there's normally no prefix;
+/// one is still joined on as the other engines do.
+pub async fn transcribe(
+    audio: AudioData,
+    settings: &UserSettings,
+    streaming_prefix: Option<String>,
+) -> Result<(String, Option<SegmentInfo>), SapiError> {
+    let samples = prepare_for_whisper(audio);
+    if samples.is_empty() {
+        return Ok((streaming_prefix.unwrap_or_default(), None));
+    }
+    let duration_ms = (samples.len() / 16) as u32;
+    let recognizer = recognizer_for(settings)?;
+    diag!(Engine, "[sapi] Transcribing {:.1}s with {}", samples.len() as f64 / 16000.0, recognizer.name);
+
+    let start = Instant::now();
+    let text = tokio::task::spawn_blocking(move || recognize(&samples, &recognizer.id))
+        .await
+        .map_err(|e| SapiError::TranscriptionError(format!("Transcription task failed: {}", e)))??;
+    diag!(Engine, "[sapi] Transcribed in {:.2}s", start.elapsed().as_secs_f64());
+
+    let segment = SegmentInfo {
+        text: text.clone(),
+        duration_ms,
+        engine: ENGINE_ID.to_string(),
+        source: "tail".to_string(),
+        confidence: None,
+        temperature_fallbacks: 0,
+    };
+    let text = match streaming_prefix {
+        Some(prefix) if !prefix.is_empty() && !text.is_empty() => format!("{} {}", prefix, text),
+        Some(prefix) if !prefix.is_empty() => prefix,
+        _ => text,
+    };
+    Ok((text, Some(segment)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recognizer(id: &str, languages: &[&str]) -> SapiRecognizer {
+        SapiRecognizer {
+            id: id.to_string(),
+            name: id.to_string(),
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_lcids() {
+        assert_eq!(parse_lcids("409;9"), [0x409, 0x9]);
+        assert_eq!(parse_lcids("407"), [0x407]);
+        assert!(parse_lcids("").is_empty());
+    }
+
+    #[test]
+    fn test_pick_recognizer() {
+        let installed = [
+            recognizer("MS-2057-80-DESK", &["en-GB", "en"]),
+            recognizer("MS-1033-80-DESK", &["en-US", "en"]),
+            recognizer("MS-1031-80-DESK", &["de-DE", "de"]),
+        ];
+        let pick = |pinned, language, system| pick_recognizer(&installed, pinned, language, system).map(|r| r.id.as_str());
+        assert_eq!(pick(None, Some("en-US"), None), Some("MS-1033-80-DESK"));
+        assert_eq!(pick(None, Some("de"), None), Some("MS-1031-80-DESK"));
+        assert_eq!(pick(None, Some("auto"), Some("en-US")), Some("MS-1033-80-DESK"));
+        assert_eq!(pick(Some("ms-2057-80-desk"), Some("de"), None), Some("MS-2057-80-DESK"));
+        // A pinned recognizer that's gone falls back to the language
+        assert_eq!(pick(Some("MS-1036-80-DESK"), Some("de"), None), Some("MS-1031-80-DESK"));
+        assert_eq!(pick(None, Some("fr"), None), None);
+        assert_eq!(pick(None, None, None), None);
+    }
+}
@@ -3,8 +3,10 @@
 //!
 //! Only compiled when the `voxtral` Cargo feature is enabled.
 
-use crate::audio::capture::{prepare_for_whisper, snapshot_whisper_buffer};
+use crate::audio::capture::{prepare_for_whisper, read_whisper_buffer, whisper_buffer_end};
 use crate::audio::AudioData;
+use crate::net::{download, DownloadProgress, NetError, ProgressTracker};
+use crate::panics::LockExt;
 use crate::settings::UserSettings;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -13,7 +15,11 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::{integrity, SegmentInfo};
 use super::voxtral_ffi::VoxtralContext;
+use crate::error::AppError;
 
 // ---------------------------------------------------------------------------
 // Errors
@@ -29,36 +35,151 @@ pub enum VoxtralError {
     TranscriptionError(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    IntegrityError(#[from] integrity::IntegrityError),
 }
 
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
 
-const MODEL_ID: &str = "voxtral-mini-4b";
+pub const MODEL_ID: &str = "voxtral-mini-4b";
 const MODEL_NAME: &str = "Voxtral Mini 4B Realtime 2602";
 const MODEL_SIZE_MB: u32 = 8900; // ~8.9 GB safetensors
 
-const HF_BASE_URL: &str =
-    "https://huggingface.co/mistralai/Voxtral-Mini-4B-Realtime-2602/resolve/main";
+const HF_REPO: &str = "mistralai/Voxtral-Mini-4B-Realtime-2602";
 
-/// Files required for the model.
+/// Revision used when none is pinned in settings.
+const DEFAULT_REVISION: &str = "main";
+
+/// Files required for the model, used when the repo's file list can't be
+/// fetched and for installs that predate the revision manifest.
 const MODEL_FILES: &[(&str, u64)] = &[
     ("consolidated.safetensors", 8_900_000_000),
     ("tekken.json", 15_000_000),
     ("params.json", 500),
 ];
 
+/// Written next to the model files after a successful download.
+const REVISION_MANIFEST: &str = "revision.json";
+
 // ---------------------------------------------------------------------------
 // Paths
 // ---------------------------------------------------------------------------
 
 fn get_model_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_default()
-        .join(".mentascribe")
-        .join("models")
-        .join(MODEL_ID)
+    crate::paths::models_dir().join(MODEL_ID)
+}
+
+// ---------------------------------------------------------------------------
+// Revision manifest
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Which revision is installed and which files belong to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoxtralManifest {
+    pub revision: String,
+    pub files: Vec<ManifestFile>,
+}
+
+impl VoxtralManifest {
+    fn builtin(revision: &str) -> Self {
+        Self {
+            revision: revision.to_string(),
+            files: MODEL_FILES
+                .iter()
+                .map(|(name, size)| ManifestFile {
+                    name: name.to_string(),
+                    size: *size,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Whether a repo file is needed at runtime: the built-in names plus any
+/// (possibly sharded) consolidated safetensors.
+fn is_required_file(path: &str) -> bool {
+    MODEL_FILES.iter().any(|(name, _)| *name == path)
+        || (path.starts_with("consolidated") && path.ends_with(".safetensors"))
+}
+
+/// Fetch the file list for `revision` from the HuggingFace tree API.
+async fn fetch_manifest(revision: &str) -> Result<VoxtralManifest, VoxtralError> {
+    #[derive(Deserialize)]
+    struct TreeEntry {
+        #[serde(rename = "type")]
+        kind: String,
+        path: String,
+        #[serde(default)]
+        size: u64,
+    }
+
+    let url = format!("https://huggingface.co/api/models/{}/tree/{}", HF_REPO, revision);
+    let client = crate::net::client().map_err(|e| VoxtralError::DownloadError(e.to_string()))?;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| VoxtralError::DownloadError(format!("Failed to list revision '{}': {}", revision, e)))?;
+    if !response.status().is_success() {
+        return Err(VoxtralError::DownloadError(format!(
+            "Failed to list revision '{}': HTTP {}",
+            revision,
+            response.status()
+        )));
+    }
+    let entries: Vec<TreeEntry> = response
+        .json()
+        .await
+        .map_err(|e| VoxtralError::DownloadError(format!("Invalid file list: {}", e)))?;
+
+    let files: Vec<ManifestFile> = entries
+        .into_iter()
+        .filter(|e| e.kind == "file" && is_required_file(&e.path))
+        .map(|e| ManifestFile {
+            name: e.path,
+            size: e.size,
+        })
+        .collect();
+
+    // A revision without the runtime files is useless — don't silently install it
+    let missing: Vec<&str> = ["tekken.json", "params.json"]
+        .into_iter()
+        .filter(|name| !files.iter().any(|f| f.name == *name))
+        .collect();
+    if !missing.is_empty() || !files.iter().any(|f| f.name.ends_with(".safetensors")) {
+        return Err(VoxtralError::DownloadError(format!(
+            "Revision '{}' is missing required files ({})",
+            revision,
+            if missing.is_empty() { "safetensors".to_string() } else { missing.join(", ") }
+        )));
+    }
+
+    Ok(VoxtralManifest {
+        revision: revision.to_string(),
+        files,
+    })
+}
+
+/// Manifest of the installed model, if it was downloaded with one.
+pub fn installed_manifest() -> Option<VoxtralManifest> {
+    std::fs::read_to_string(get_model_dir().join(REVISION_MANIFEST))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Files the installed model consists of (built-in list for older installs).
+fn installed_files() -> Vec<String> {
+    installed_manifest()
+        .map(|m| m.files.into_iter().map(|f| f.name).collect())
+        .unwrap_or_else(|| MODEL_FILES.iter().map(|(name, _)| name.to_string()).collect())
 }
 
 // ---------------------------------------------------------------------------
@@ -75,24 +196,129 @@ pub struct VoxtralStatus {
     pub model_downloaded: bool,
     /// Model is currently loaded in memory
     pub model_loaded: bool,
+    /// Installed revision (None for installs that predate revision tracking)
+    pub revision: Option<String>,
 }
 
 pub fn get_status() -> VoxtralStatus {
-    let model_dir = get_model_dir();
-    let downloaded = MODEL_FILES.iter().all(|(name, _)| model_dir.join(name).exists());
     let loaded = VOXTRAL_CACHE.lock().map(|c| c.context.is_some()).unwrap_or(false);
 
     VoxtralStatus {
         compiled: true,
         metal: cfg!(all(target_os = "macos", target_arch = "aarch64")),
-        model_downloaded: downloaded,
+        model_downloaded: is_model_downloaded(),
         model_loaded: loaded,
+        revision: installed_manifest().map(|m| m.revision),
+    }
+}
+
+/// Languages Voxtral Mini Realtime was trained on. It always auto-detects;
+/// there is no way to force one.
+const LANGUAGES: &[&str] = &[
+    "en", "zh", "hi", "es", "ar", "fr", "pt", "ru", "de", "ja", "ko", "it", "nl",
+];
+
+pub fn supported_languages() -> Vec<super::LanguageInfo> {
+    LANGUAGES
+        .iter()
+        .map(|code| super::LanguageInfo {
+            code: code.to_string(),
+            name: super::whisper::language_name(code).unwrap_or_else(|| code.to_string()),
+        })
+        .collect()
+}
+
+pub fn get_capabilities() -> super::EngineCapabilities {
+    super::EngineCapabilities {
+        engine: "voxtral".to_string(),
+        available: true,
+        // Native streaming: audio is decoded incrementally while recording
+        streaming: true,
+        translation: false,
+        timestamps: false,
+        language_selection: false,
+        languages: LANGUAGES.iter().map(|l| l.to_string()).collect(),
+        gpu: get_status().metal,
+        model_sizes: vec![MODEL_ID.to_string()],
+    }
+}
+
+pub struct VoxtralEngine;
+
+/// Voxtral tokens carry their own spacing (" Hello," " world."), so they're
+/// joined as-is into a single streaming segment. Its length is filled in by
+/// `pipeline::merge` once the captured audio is known.
+fn streaming_segment(text: String) -> Vec<SegmentInfo> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+    vec![SegmentInfo {
+        text,
+        duration_ms: 0,
+        engine: "voxtral".to_string(),
+        source: "streaming".to_string(),
+        confidence: None,
+        temperature_fallbacks: 0,
+    }]
+}
+
+impl TranscriptionEngine for VoxtralEngine {
+    fn capabilities(&self) -> super::EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, _settings: &UserSettings) -> Result<(), AppError> {
+        Ok(preload_model()?)
+    }
+
+    fn is_loaded(&self, _settings: &UserSettings) -> bool {
+        get_status().model_loaded
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move { Ok(transcribe(audio, settings, streaming_prefix).await?) })
+    }
+
+    fn required_models(&self, _settings: &UserSettings) -> Vec<RequiredModel> {
+        RequiredModel::all_of("voxtral", get_available_models())
+    }
+
+    /// Streams by decoding, not by cutting utterances at pauses
+    fn uses_vad(&self) -> bool {
+        false
+    }
+
+    fn streaming_text(&self) -> String {
+        streaming_text()
+    }
+
+    fn take_streaming_results(&self) -> (Vec<SegmentInfo>, usize) {
+        let (text, fed) = take_finished_text();
+        (streaming_segment(text), fed)
+    }
+
+    fn stop_streaming(&self) -> (Vec<SegmentInfo>, usize) {
+        let (tokens, consumed) = stop_streaming();
+        (streaming_segment(tokens.concat()), consumed)
+    }
+
+    fn streaming_consumed(&self) -> usize {
+        streaming_consumed()
+    }
+
+    fn is_streaming(&self) -> bool {
+        is_streaming()
     }
 }
 
 pub fn is_model_downloaded() -> bool {
     let model_dir = get_model_dir();
-    MODEL_FILES.iter().all(|(name, _)| model_dir.join(name).exists())
+    installed_files().iter().all(|name| model_dir.join(name).exists())
 }
 
 pub fn get_available_models() -> Vec<super::ModelInfo> {
@@ -111,19 +337,56 @@ pub fn get_available_models() -> Vec<super::ModelInfo> {
 // Model download
 // ---------------------------------------------------------------------------
 
-pub async fn download_model<F: Fn(f64) + Send + 'static>(
+/// Download the model at `revision` (branch, tag or commit; default "main").
+/// The file list is read from the repo at that revision so upstream layout changes
+/// don't break downloads; switching revisions re-downloads every file.
+pub async fn download_model<F: Fn(&DownloadProgress, &str) + Send + 'static>(
+    revision: Option<&str>,
     on_progress: F,
 ) -> Result<(), VoxtralError> {
+    let revision = revision
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .unwrap_or(DEFAULT_REVISION);
     let model_dir = get_model_dir();
     std::fs::create_dir_all(&model_dir)
         .map_err(|e| VoxtralError::DownloadError(format!("Failed to create model dir: {}", e)))?;
 
-    let total_bytes: u64 = MODEL_FILES.iter().map(|(_, size)| size).sum();
-    let mut downloaded_bytes: u64 = 0;
+    let manifest = match fetch_manifest(revision).await {
+        Ok(m) => m,
+        Err(e) if revision == DEFAULT_REVISION => {
+            log::warn!("{}, falling back to built-in file list", e);
+            VoxtralManifest::builtin(revision)
+        }
+        // A pinned revision must not silently fall back to a different file set
+        Err(e) => return Err(e),
+    };
+
+    // Files from a different installed revision can't be reused
+    let previous = installed_manifest();
+    let same_revision = previous
+        .as_ref()
+        .map(|m| m.revision == manifest.revision)
+        .unwrap_or(revision == DEFAULT_REVISION);
+    if !same_revision {
+        log::info!(
+            "Switching Voxtral revision {} -> {}",
+            previous.as_ref().map(|m| m.revision.as_str()).unwrap_or("unknown"),
+            manifest.revision
+        );
+        unload_model();
+        for name in installed_files() {
+            let path = model_dir.join(&name);
+            integrity::forget(&path);
+            std::fs::remove_file(&path).ok();
+        }
+        std::fs::remove_file(model_dir.join(REVISION_MANIFEST)).ok();
+    }
 
-    let client = reqwest::Client::new();
+    let total_bytes: u64 = manifest.files.iter().map(|f| f.size).sum::<u64>().max(1);
+    let mut tracker = ProgressTracker::new(total_bytes);
 
-    for (filename, expected_size) in MODEL_FILES {
+    for ManifestFile { name: filename, size: expected_size } in &manifest.files {
         let file_path = model_dir.join(filename);
 
         // Skip if already downloaded and roughly the right size
@@ -132,64 +395,57 @@ pub async fn download_model<F: Fn(f64) + Send + 'static>(
                 // Allow 10% tolerance for size check
                 if meta.len() > expected_size / 2 {
                     log::info!("Voxtral model file '{}' already exists, skipping", filename);
-                    downloaded_bytes += expected_size;
-                    on_progress(downloaded_bytes as f64 / total_bytes as f64 * 100.0);
+                    tracker.skip(*expected_size);
+                    on_progress(&tracker.progress(100.0), "local");
                     continue;
                 }
             }
         }
 
-        let url = format!("{}/{}", HF_BASE_URL, filename);
+        let url = format!(
+            "https://huggingface.co/{}/resolve/{}/{}",
+            HF_REPO, manifest.revision, filename
+        );
         log::info!("Downloading voxtral model file: {}", url);
 
-        let response = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| VoxtralError::DownloadError(format!("HTTP request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(VoxtralError::DownloadError(format!(
-                "HTTP {} for {}",
-                response.status(),
-                url
-            )));
-        }
+        let expected_sha256 = integrity::expected_for_download(&url).await;
 
-        let content_length = response.content_length().unwrap_or(*expected_size);
+        let remote = download::open(&url)
+            .await
+            .map_err(|e| VoxtralError::DownloadError(format!("{} ({})", e, filename)))?;
+        let mirror = remote.mirror().to_string();
 
-        // Download with progress tracking (using response.chunk() like whisper.rs)
+        // Large files arrive over several connections, each range written in place
         let tmp_path = file_path.with_extension("part");
-        let mut file = std::fs::File::create(&tmp_path)
-            .map_err(|e| VoxtralError::DownloadError(format!("Failed to create file: {}", e)))?;
-
-        use std::io::Write;
-        let mut response = response;
-        let mut file_downloaded: u64 = 0;
-
-        while let Some(chunk) = response
-            .chunk()
+        let actual_sha256 = remote
+            .save_to(&tmp_path, |bytes| {
+                tracker.advance(bytes);
+                on_progress(&tracker.progress(100.0), &mirror);
+            })
             .await
-            .map_err(|e| VoxtralError::DownloadError(format!("Download error: {}", e)))?
-        {
-            file.write_all(&chunk)
-                .map_err(|e| VoxtralError::DownloadError(format!("Write error: {}", e)))?;
-            file_downloaded += chunk.len() as u64;
-
-            let total_progress =
-                (downloaded_bytes + file_downloaded) as f64 / total_bytes as f64 * 100.0;
-            on_progress(total_progress);
-        }
+            .map_err(|e| match e {
+                // Kept as IO so a full disk can be recognized
+                NetError::IoError(e) => VoxtralError::IoError(e),
+                e => VoxtralError::DownloadError(format!("{} ({})", e, filename)),
+            })?;
+
+        // Verify before the rename so a corrupted file never takes the real name
+        integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
 
         // Atomic rename
         std::fs::rename(&tmp_path, &file_path)
             .map_err(|e| VoxtralError::DownloadError(format!("Rename failed: {}", e)))?;
+        integrity::record(&file_path, &actual_sha256);
 
-        downloaded_bytes += content_length;
         log::info!("Downloaded voxtral model file: {}", filename);
     }
 
-    on_progress(100.0);
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| VoxtralError::DownloadError(e.to_string()))?;
+    std::fs::write(model_dir.join(REVISION_MANIFEST), manifest_json)?;
+    log::info!("Voxtral revision '{}' installed", manifest.revision);
+
+    on_progress(&tracker.finished(), "local");
     Ok(())
 }
 
@@ -201,6 +457,9 @@ pub fn delete_model() -> Result<(), VoxtralError> {
 
     let model_dir = get_model_dir();
     if model_dir.exists() {
+        for name in installed_files() {
+            integrity::forget(&model_dir.join(name));
+        }
         std::fs::remove_dir_all(&model_dir)?;
         log::info!("Deleted voxtral model directory: {:?}", model_dir);
     }
@@ -211,13 +470,12 @@ pub fn delete_model() -> Result<(), VoxtralError> {
 // Model cache (same pattern as whisper.rs MODEL_CACHE)
 // ---------------------------------------------------------------------------
 
+#[derive(Default)]
 struct VoxtralCache {
     context: Option<Arc<VoxtralContext>>,
 }
 
-static VOXTRAL_CACHE: Lazy<Mutex<VoxtralCache>> = Lazy::new(|| {
-    Mutex::new(VoxtralCache { context: None })
-});
+static VOXTRAL_CACHE: Lazy<Mutex<VoxtralCache>> = Lazy::new(|| Mutex::new(VoxtralCache::default()));
 
 /// Load the voxtral model into cache. No-op if already loaded.
 pub fn preload_model() -> Result<(), VoxtralError> {
@@ -229,15 +487,17 @@ pub fn preload_model() -> Result<(), VoxtralError> {
         )));
     }
 
-    let mut cache = VOXTRAL_CACHE
-        .lock()
-        .map_err(|e| VoxtralError::TranscriptionError(format!("Cache lock error: {}", e)))?;
+    let mut cache = VOXTRAL_CACHE.lock_or_reset();
 
     if cache.context.is_some() {
         log::info!("Voxtral model already cached, skipping preload");
         return Ok(());
     }
 
+    for name in installed_files() {
+        integrity::verify_before_load(&model_dir.join(name))?;
+    }
+
     let model_dir_str = model_dir
         .to_str()
         .ok_or_else(|| VoxtralError::ModelNotFound("Invalid model path".to_string()))?;
@@ -266,9 +526,7 @@ pub fn unload_model() {
 }
 
 fn get_cached_context() -> Result<Arc<VoxtralContext>, VoxtralError> {
-    let cache = VOXTRAL_CACHE
-        .lock()
-        .map_err(|e| VoxtralError::TranscriptionError(format!("Cache lock error: {}", e)))?;
+    let cache = VOXTRAL_CACHE.lock_or_reset();
 
     cache.context.clone().ok_or_else(|| {
         VoxtralError::TranscriptionError("Voxtral model not loaded".to_string())
@@ -285,17 +543,23 @@ pub async fn transcribe(
     audio: AudioData,
     settings: &UserSettings,
     streaming_prefix: Option<String>,
-) -> Result<String, VoxtralError> {
+) -> Result<(String, Option<SegmentInfo>), VoxtralError> {
     // Prepare audio (16kHz mono f32)
     let samples = prepare_for_whisper(audio);
 
     // If no tail audio, return just the streaming prefix
     if samples.is_empty() {
-        return Ok(streaming_prefix.unwrap_or_default());
+        return Ok((streaming_prefix.unwrap_or_default(), None));
     }
+    let tail_duration_ms = (samples.len() / 16) as u32;
 
-    // Get cached model context
+    // Get cached model context, loading it first if preload was deferred
+    // (no-op when already cached; waits for an in-progress background load)
+    tokio::task::spawn_blocking(preload_model)
+        .await
+        .map_err(|e| VoxtralError::TranscriptionError(format!("Model load task failed: {}", e)))??;
     let ctx = get_cached_context()?;
+    crate::storage::mark_model_used("voxtral-mini-4b");
 
     // Set delay from settings
     let delay_ms = settings
@@ -320,17 +584,27 @@ pub async fn transcribe(
         .map_err(|_| VoxtralError::TranscriptionError("Transcription thread dropped".into()))?
         .map_err(|e| VoxtralError::TranscriptionError(e))?;
 
+    let tail_segment = SegmentInfo {
+        text: tail_text.clone(),
+        duration_ms: tail_duration_ms,
+        engine: "voxtral".to_string(),
+        source: "tail".to_string(),
+        confidence: None,
+        temperature_fallbacks: 0,
+    };
+
     // Combine streaming prefix with tail transcription
-    match streaming_prefix {
+    let text = match streaming_prefix {
         Some(prefix) if !prefix.is_empty() => {
             if tail_text.is_empty() {
-                Ok(prefix)
+                prefix
             } else {
-                Ok(format!("{} {}", prefix, tail_text))
+                format!("{} {}", prefix, tail_text)
             }
         }
-        _ => Ok(tail_text),
-    }
+        _ => tail_text,
+    };
+    Ok((text, Some(tail_segment)))
 }
 
 // ---------------------------------------------------------------------------
@@ -349,6 +623,9 @@ static VOXTRAL_STREAMING_STOP: AtomicBool = AtomicBool::new(false);
 /// (capture continues running while we process).
 static VOXTRAL_STOP_BUFFER_LEN: AtomicUsize = AtomicUsize::new(0);
 
+/// WHISPER_BUFFER position fed to the stream so far.
+static VOXTRAL_FED_POSITION: AtomicUsize = AtomicUsize::new(0);
+
 /// Handle for the streaming thread.
 static VOXTRAL_STREAM_HANDLE: Lazy<Mutex<Option<std::thread::JoinHandle<()>>>> =
     Lazy::new(|| Mutex::new(None));
@@ -364,8 +641,9 @@ pub struct StreamingConfig {
 /// Returns an error if the model is not downloaded or not loaded.
 pub fn start_streaming(config: StreamingConfig) -> Result<(), VoxtralError> {
     // Clear previous state
-    *VOXTRAL_STREAMING_RESULTS.lock().unwrap() = Vec::new();
+    *VOXTRAL_STREAMING_RESULTS.lock_recover() = Vec::new();
     VOXTRAL_STREAMING_STOP.store(false, Ordering::SeqCst);
+    VOXTRAL_FED_POSITION.store(0, Ordering::SeqCst);
 
     if !is_model_downloaded() {
         return Err(VoxtralError::ModelNotFound(
@@ -381,15 +659,59 @@ pub fn start_streaming(config: StreamingConfig) -> Result<(), VoxtralError> {
     let thread = std::thread::Builder::new()
         .name("voxtral-streaming".to_string())
         .spawn(move || {
-            voxtral_stream_loop(ctx);
+            crate::panics::catch("Voxtral streaming", || voxtral_stream_loop(ctx));
         })
         .map_err(|e| VoxtralError::TranscriptionError(format!("Thread spawn failed: {}", e)))?;
 
-    *VOXTRAL_STREAM_HANDLE.lock().unwrap() = Some(thread);
+    *VOXTRAL_STREAM_HANDLE.lock_recover() = Some(thread);
     log::info!("Voxtral streaming started");
     Ok(())
 }
 
+/// Add decoded text to the current recording's and send the whole as
+/// `transcription-partial`.
+fn push_text(text: String) {
+    VOXTRAL_STREAMING_RESULTS.lock_recover().push(text);
+    crate::events::partial(streaming_text());
+}
+
+/// Text decoded so far in the current recording.
+pub fn streaming_text() -> String {
+    VOXTRAL_STREAMING_RESULTS
+        .lock()
+        .map(|tokens| tokens.concat())
+        .unwrap_or_default()
+}
+
+/// WHISPER_BUFFER position fed to the stream in the current recording.
+pub fn streaming_consumed() -> usize {
+    VOXTRAL_FED_POSITION.load(Ordering::SeqCst)
+}
+
+/// Whether the streaming thread is running (false if it never started or has exited).
+pub fn is_streaming() -> bool {
+    VOXTRAL_STREAM_HANDLE
+        .lock()
+        .map(|h| h.as_ref().is_some_and(|t| !t.is_finished()))
+        .unwrap_or(false)
+}
+
+/// Take the decoded text up to the last finished sentence while streaming
+/// keeps running, as (text, fed_samples). Tokens are word pieces, so cutting
+/// anywhere else could split a word between two outputs. Audio up to
+/// `fed_samples` has been fed to the stream and is no longer needed.
+pub fn take_finished_text() -> (String, usize) {
+    let fed = VOXTRAL_FED_POSITION.load(Ordering::SeqCst);
+    let mut tokens = VOXTRAL_STREAMING_RESULTS.lock_recover();
+    let finished = tokens
+        .iter()
+        .rposition(|t| t.trim_end().ends_with(['.', '!', '?']))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let text: String = tokens.drain(..finished).collect();
+    (text, fed)
+}
+
 /// Stop voxtral streaming. Returns (accumulated_text_segments, consumed_samples).
 /// When streaming was active: consumed_samples is usize::MAX (all audio consumed,
 /// skip tail transcription). When no thread was running: returns ([], 0).
@@ -398,7 +720,7 @@ pub fn stop_streaming() -> (Vec<String>, usize) {
     // Audio capture is still running, so the buffer keeps growing.
     // The streaming thread uses this as a cutoff to avoid processing
     // audio recorded after the user pressed stop.
-    let (_, buf_len) = snapshot_whisper_buffer(0);
+    let buf_len = whisper_buffer_end();
     VOXTRAL_STOP_BUFFER_LEN.store(buf_len, Ordering::SeqCst);
     log::debug!("stop buffer cutoff: {} samples ({:.2}s)", buf_len, buf_len as f64 / 16000.0);
 
@@ -412,7 +734,7 @@ pub fn stop_streaming() -> (Vec<String>, usize) {
     // 2. finish() produces the final tokens we need — abandoning loses them
     // 3. The caller skips tail transcription when we return usize::MAX,
     //    so this is the ONLY chance to process the audio
-    let handle = VOXTRAL_STREAM_HANDLE.lock().unwrap().take();
+    let handle = VOXTRAL_STREAM_HANDLE.lock_recover().take();
     let thread_was_running = handle.is_some();
     if let Some(h) = handle {
         let start = std::time::Instant::now();
@@ -421,7 +743,7 @@ pub fn stop_streaming() -> (Vec<String>, usize) {
         log::info!("Voxtral streaming thread joined in {:.2}s", elapsed);
     }
 
-    let results = std::mem::take(&mut *VOXTRAL_STREAMING_RESULTS.lock().unwrap());
+    let results = std::mem::take(&mut *VOXTRAL_STREAMING_RESULTS.lock_recover());
     log::info!("Voxtral streaming results: {} segments", results.len());
 
     if thread_was_running {
@@ -476,10 +798,13 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
     // so there's minimal backlog when stop fires.
     let mut last_force_encode = std::time::Instant::now();
     let force_encode_interval = std::time::Duration::from_secs(3);
+    // Reused for every read so polling doesn't allocate
+    let mut new_samples: Vec<f32> = Vec::with_capacity(16000);
 
     while !VOXTRAL_STREAMING_STOP.load(Ordering::SeqCst) {
         // Get new audio since last position
-        let (new_samples, new_len) = snapshot_whisper_buffer(abs_position);
+        new_samples.clear();
+        let new_len = read_whisper_buffer(abs_position, &mut new_samples);
 
         if !new_samples.is_empty() {
             let chunk_len = new_samples.len();
@@ -491,6 +816,7 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
                 break;
             }
             abs_position = new_len;
+            VOXTRAL_FED_POSITION.store(abs_position, Ordering::SeqCst);
             total_fed += chunk_len;
             feed_count += 1;
 
@@ -511,7 +837,7 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
             log::debug!("got {} tokens: '{}'", tokens.len(),
                 if text.len() > 80 { &text[..80] } else { &text });
             if !text.trim().is_empty() {
-                VOXTRAL_STREAMING_RESULTS.lock().unwrap().push(text);
+                push_text(text);
             }
         }
 
@@ -528,7 +854,7 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
                     token_count += tokens.len() as u32;
                     log::debug!("force_encode produced {} tokens", tokens.len());
                     if !text.trim().is_empty() {
-                        VOXTRAL_STREAMING_RESULTS.lock().unwrap().push(text);
+                        push_text(text);
                     }
                 }
             }
@@ -551,12 +877,10 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
     // that was recorded after the user pressed stop (capture keeps running).
     let stop_cutoff = VOXTRAL_STOP_BUFFER_LEN.load(Ordering::SeqCst);
     let remaining_limit = if stop_cutoff > abs_position { stop_cutoff - abs_position } else { 0 };
-    let (remaining_buf, _) = snapshot_whisper_buffer(abs_position);
-    let remaining_samples = if remaining_buf.len() > remaining_limit {
-        &remaining_buf[..remaining_limit]
-    } else {
-        &remaining_buf[..]
-    };
+    new_samples.clear();
+    read_whisper_buffer(abs_position, &mut new_samples);
+    new_samples.truncate(remaining_limit);
+    let remaining_samples = &new_samples[..];
     if !remaining_samples.is_empty() {
         log::debug!(
             "feeding remaining {} samples ({:.2}s) after stop",
@@ -579,7 +903,7 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
             token_count += tokens.len() as u32;
             log::debug!("got {} tokens from remaining feed", tokens.len());
             if !text.trim().is_empty() {
-                VOXTRAL_STREAMING_RESULTS.lock().unwrap().push(text);
+                push_text(text);
             }
         }
     }
@@ -602,7 +926,7 @@ fn voxtral_stream_loop(ctx: Arc<VoxtralContext>) {
         drain_count += tokens.len() as u32;
         log::debug!("drain: {} tokens", tokens.len());
         if !text.trim().is_empty() {
-            VOXTRAL_STREAMING_RESULTS.lock().unwrap().push(text);
+            push_text(text);
         }
     }
     log::info!(
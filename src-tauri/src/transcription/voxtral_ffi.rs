@@ -82,7 +82,7 @@ impl VoxtralContext {
         {
             let metal_ok = unsafe { vox_metal_init() };
             let metal_avail = unsafe { vox_metal_available() };
-            eprintln!(
+            diag!(Engine,
                 "[voxtral] Metal init: {} (available: {})",
                 if metal_ok != 0 { "OK" } else { "FAILED" },
                 metal_avail != 0
@@ -1,30 +1,36 @@
+use crate::audio::vad::{self, SpeechDetector, VadBackend, VadError};
 use crate::audio::{capture::prepare_for_whisper, AudioData};
+use crate::error::AppError;
+use crate::net::{download, DownloadProgress, NetError, ProgressTracker};
+use crate::panics::LockExt;
 use crate::settings::UserSettings;
 use once_cell::sync::Lazy;
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use whisper_rs::{WhisperContext, WhisperContextParameters, WhisperState, WhisperVadContext, WhisperVadContextParams, WhisperVadParams};
 
-use super::{CoremlStatus, MetalStatus, ModelInfo};
+use super::engine::{TranscribeFuture, TranscriptionEngine};
+use super::required::RequiredModel;
+use super::{
+    integrity, CoremlStatus, EncoderSelection, EngineCapabilities, EngineTimings, LanguageInfo, MetalStatus,
+    ModelInfo, ModelUpdate, SegmentInfo,
+};
 
 // Cache for the Whisper model context to avoid reloading on every transcription.
 // Arc-wrapped so we can clone the context out of the cache and release the mutex
 // before running inference (which takes 1-30+ seconds).
+#[derive(Default)]
 struct ModelCache {
     context: Option<Arc<WhisperContext>>,
     model_size: String,
     model_path: PathBuf,
 }
 
-static MODEL_CACHE: Lazy<Mutex<ModelCache>> = Lazy::new(|| {
-    Mutex::new(ModelCache {
-        context: None,
-        model_size: String::new(),
-        model_path: PathBuf::new(),
-    })
-});
+static MODEL_CACHE: Lazy<Mutex<ModelCache>> = Lazy::new(|| Mutex::new(ModelCache::default()));
 
 // Pre-created WhisperState cache. After each transcription, we spawn a background
 // thread to create the next WhisperState so it's ready immediately when the user
@@ -47,16 +53,15 @@ pub enum WhisperError {
     TranscriptionError(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("{0}")]
+    IntegrityError(#[from] integrity::IntegrityError),
 }
 
 const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
 const VAD_MODEL_FILENAME: &str = "ggml-silero-vad.bin";
 
-fn get_models_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_default()
-        .join(".mentascribe")
-        .join("models")
+pub(crate) fn get_models_dir() -> PathBuf {
+    crate::paths::models_dir()
 }
 
 fn get_model_filename(size: &str) -> String {
@@ -86,14 +91,120 @@ pub fn is_coreml_downloaded(size: &str) -> bool {
     get_models_dir().join(coreml_encoder_name(size)).is_dir()
 }
 
+// ======================= CoreML encoder selection =======================
+
+/// Directory of model symlinks used to load a model without its CoreML encoder.
+pub(crate) const NO_COREML_DIR: &str = "no-coreml";
+
+/// Mirrors `use_coreml` (None = auto-detect) and the per-model overrides from settings.
+struct CoremlPreferences {
+    global: Option<bool>,
+    per_model: HashMap<String, String>,
+}
+
+static COREML_PREFS: Lazy<Mutex<CoremlPreferences>> = Lazy::new(|| {
+    Mutex::new(CoremlPreferences {
+        global: None,
+        per_model: HashMap::new(),
+    })
+});
+
+/// Encoder used by the model currently in MODEL_CACHE.
+static ACTIVE_ENCODER: Lazy<Mutex<Option<EncoderSelection>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn set_coreml_preferences(global: Option<bool>, per_model: &HashMap<String, String>) {
+    if let Ok(mut prefs) = COREML_PREFS.lock() {
+        prefs.global = global;
+        prefs.per_model = per_model.clone();
+    }
+}
+
+/// Decide whether `size` runs with its CoreML encoder.
+pub fn select_encoder(size: &str) -> EncoderSelection {
+    let (wanted, setting) = coreml_wanted(size);
+    let (coreml, reason) = if !get_coreml_status().compiled {
+        (false, "CoreML is not available on this platform")
+    } else if !wanted {
+        (false, if setting == "off" { "CoreML disabled for this model" } else { "CoreML disabled in settings" })
+    } else if !is_coreml_downloaded(size) {
+        (false, "CoreML encoder not downloaded")
+    } else {
+        (true, if setting == "on" { "CoreML enabled for this model" } else { "CoreML enabled (auto)" })
+    };
+
+    EncoderSelection {
+        model_size: size.to_string(),
+        setting,
+        coreml,
+        reason: reason.to_string(),
+    }
+}
+
+/// Whether the settings ask for the CoreML encoder for `size`, whether or not
+/// it's downloaded, and the per-model setting ("on", "off" or "auto").
+pub fn coreml_wanted(size: &str) -> (bool, String) {
+    let (global, setting) = COREML_PREFS
+        .lock()
+        .map(|p| {
+            let setting = p
+                .per_model
+                .get(size)
+                .map(|s| s.as_str())
+                .filter(|s| *s == "on" || *s == "off")
+                .unwrap_or("auto")
+                .to_string();
+            (p.global, setting)
+        })
+        .unwrap_or((None, "auto".to_string()));
+
+    let wanted = match setting.as_str() {
+        "on" => true,
+        "off" => false,
+        _ => global.unwrap_or_else(|| get_coreml_status().supported),
+    };
+    (wanted, setting)
+}
+
+pub fn get_active_encoder() -> Option<EncoderSelection> {
+    ACTIVE_ENCODER.lock().ok().and_then(|a| a.clone())
+}
+
+/// Path to load the model from. whisper.cpp picks up `<model>-encoder.mlmodelc`
+/// next to the model file whenever it exists, so to run without CoreML the model
+/// is loaded through a symlink in a directory with no encoder beside it.
+fn model_load_path(size: &str, selection: &EncoderSelection) -> PathBuf {
+    let model_path = get_model_path(size);
+    if selection.coreml || !is_coreml_downloaded(size) {
+        return model_path;
+    }
+
+    #[cfg(unix)]
+    {
+        let dir = get_models_dir().join(NO_COREML_DIR);
+        let link = dir.join(get_model_filename(size));
+        if std::fs::read_link(&link).ok().as_deref() == Some(model_path.as_path()) {
+            return link;
+        }
+        let created = std::fs::create_dir_all(&dir).and_then(|_| {
+            std::fs::remove_file(&link).ok();
+            std::os::unix::fs::symlink(&model_path, &link)
+        });
+        match created {
+            Ok(()) => return link,
+            Err(e) => log::warn!("Could not bypass CoreML encoder for '{}': {}", size, e),
+        }
+    }
+    model_path
+}
+
 /// Approximate GGML model download size in bytes for a given model size.
 /// Used as a fallback when Content-Length is absent (chunked transfer encoding).
 fn ggml_size_bytes(size: &str) -> u64 {
     match size {
-        "tiny" => 75_000_000,
-        "base" => 142_000_000,
-        "small" => 466_000_000,
-        "medium" => 1_500_000_000,
+        "tiny" | "tiny.en" => 75_000_000,
+        "base" | "base.en" => 142_000_000,
+        "small" | "small.en" => 466_000_000,
+        "medium" | "medium.en" => 1_500_000_000,
         "large" => 2_900_000_000,
         "large-v3-turbo" => 1_500_000_000,
         "large-v3-turbo-q5_0" => 547_000_000,
@@ -109,10 +220,10 @@ fn ggml_size_bytes(size: &str) -> u64 {
 /// Returns 0 for models without available CoreML encoders.
 fn coreml_size_bytes(size: &str) -> u64 {
     match size {
-        "tiny" => 42_000_000,
-        "base" => 78_000_000,
-        "small" => 244_000_000,
-        "medium" => 776_000_000,
+        "tiny" | "tiny.en" => 42_000_000,
+        "base" | "base.en" => 78_000_000,
+        "small" | "small.en" => 244_000_000,
+        "medium" | "medium.en" => 776_000_000,
         "large" => 1_550_000_000,
         _ => 0,
     }
@@ -122,10 +233,10 @@ fn coreml_size_bytes(size: &str) -> u64 {
 /// Returns 0 for models without available CoreML encoders (quantized, turbo).
 fn coreml_size_mb(size: &str) -> u32 {
     match size {
-        "tiny" => 42,
-        "base" => 78,
-        "small" => 244,
-        "medium" => 776,
+        "tiny" | "tiny.en" => 42,
+        "base" | "base.en" => 78,
+        "small" | "small.en" => 244,
+        "medium" | "medium.en" => 776,
         "large" => 1550,
         // Quantized and turbo models don't have separate CoreML encoders
         _ => 0,
@@ -151,6 +262,117 @@ pub fn get_metal_status() -> MetalStatus {
     }
 }
 
+/// Whisper's capabilities; the language list comes from the linked whisper.cpp.
+pub fn get_capabilities() -> EngineCapabilities {
+    let languages = (0..=whisper_rs::get_lang_max_id())
+        .filter_map(whisper_rs::get_lang_str)
+        .map(|lang| lang.to_string())
+        .collect();
+    EngineCapabilities {
+        engine: "whisper".to_string(),
+        available: true,
+        // VAD segments are transcribed while recording
+        streaming: true,
+        translation: true,
+        timestamps: true,
+        language_selection: true,
+        languages,
+        gpu: get_metal_status().supported,
+        model_sizes: get_available_models().into_iter().map(|m| m.id).collect(),
+    }
+}
+
+pub struct WhisperEngine;
+
+impl WhisperEngine {
+    fn model_size(settings: &UserSettings) -> &str {
+        settings.transcription.model_size.as_deref().unwrap_or("small")
+    }
+}
+
+impl TranscriptionEngine for WhisperEngine {
+    fn capabilities(&self) -> EngineCapabilities {
+        get_capabilities()
+    }
+
+    fn load(&self, settings: &UserSettings) -> Result<(), AppError> {
+        Ok(preload_model(Self::model_size(settings))?)
+    }
+
+    fn is_loaded(&self, settings: &UserSettings) -> bool {
+        is_model_loaded(Self::model_size(settings))
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: AudioData,
+        settings: &'a UserSettings,
+        streaming_prefix: Option<String>,
+    ) -> TranscribeFuture<'a> {
+        Box::pin(async move { Ok(transcribe(audio, settings, streaming_prefix).await?) })
+    }
+
+    fn required_models(&self, settings: &UserSettings) -> Vec<RequiredModel> {
+        super::required::whisper_models(settings)
+    }
+
+    fn streaming_text(&self) -> String {
+        streaming_text()
+    }
+
+    fn take_streaming_results(&self) -> (Vec<SegmentInfo>, usize) {
+        take_streaming_results()
+    }
+
+    fn stop_streaming(&self) -> (Vec<SegmentInfo>, usize) {
+        stop_streaming()
+    }
+
+    fn streaming_consumed(&self) -> usize {
+        streaming_consumed()
+    }
+
+    fn is_streaming(&self) -> bool {
+        is_streaming()
+    }
+
+    fn take_timings(&self) -> EngineTimings {
+        take_last_timings()
+    }
+}
+
+/// The ".en" models are trained on English only and ignore the language setting.
+pub fn is_english_only(size: &str) -> bool {
+    size.ends_with(".en")
+}
+
+/// Display name of a language code, from whisper.cpp's table ("de" -> "German").
+pub fn language_name(code: &str) -> Option<String> {
+    let id = whisper_rs::get_lang_id(code)?;
+    let name = whisper_rs::get_lang_str_full(id)?;
+    let mut chars = name.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Languages a model can transcribe: English for the ".en" models, else all
+/// of whisper.cpp's languages.
+pub fn supported_languages(size: &str) -> Vec<LanguageInfo> {
+    let codes: Vec<&str> = if is_english_only(size) {
+        vec!["en"]
+    } else {
+        (0..=whisper_rs::get_lang_max_id())
+            .filter_map(whisper_rs::get_lang_str)
+            .collect()
+    };
+    codes
+        .into_iter()
+        .map(|code| LanguageInfo {
+            code: code.to_string(),
+            name: language_name(code).unwrap_or_else(|| code.to_string()),
+        })
+        .collect()
+}
+
 pub fn get_available_models() -> Vec<ModelInfo> {
     let models_dir = get_models_dir();
 
@@ -187,6 +409,38 @@ pub fn get_available_models() -> Vec<ModelInfo> {
             coreml_downloaded: is_coreml_downloaded("medium"),
             coreml_size_mb: coreml_size_mb("medium"),
         },
+        ModelInfo {
+            id: "tiny.en".to_string(),
+            name: "Tiny (English)".to_string(),
+            size_mb: 75,
+            downloaded: models_dir.join("ggml-tiny.en.bin").exists(),
+            coreml_downloaded: is_coreml_downloaded("tiny.en"),
+            coreml_size_mb: coreml_size_mb("tiny.en"),
+        },
+        ModelInfo {
+            id: "base.en".to_string(),
+            name: "Base (English)".to_string(),
+            size_mb: 142,
+            downloaded: models_dir.join("ggml-base.en.bin").exists(),
+            coreml_downloaded: is_coreml_downloaded("base.en"),
+            coreml_size_mb: coreml_size_mb("base.en"),
+        },
+        ModelInfo {
+            id: "small.en".to_string(),
+            name: "Small (English)".to_string(),
+            size_mb: 466,
+            downloaded: models_dir.join("ggml-small.en.bin").exists(),
+            coreml_downloaded: is_coreml_downloaded("small.en"),
+            coreml_size_mb: coreml_size_mb("small.en"),
+        },
+        ModelInfo {
+            id: "medium.en".to_string(),
+            name: "Medium (English)".to_string(),
+            size_mb: 1500,
+            downloaded: models_dir.join("ggml-medium.en.bin").exists(),
+            coreml_downloaded: is_coreml_downloaded("medium.en"),
+            coreml_size_mb: coreml_size_mb("medium.en"),
+        },
         ModelInfo {
             id: "large".to_string(),
             name: "Large v3".to_string(),
@@ -249,62 +503,183 @@ fn get_model_download_url(size: &str) -> String {
     }
 }
 
+/// Calls `on_progress(progress, mirror)` whenever the whole percentage changes.
 pub async fn download_model(
     size: &str,
-    on_progress: impl Fn(u8),
+    on_progress: impl Fn(&DownloadProgress, &str),
 ) -> Result<(), WhisperError> {
+    let path = get_model_path(size);
+    fetch_model(size, &path, on_progress).await?;
+    Ok(())
+}
+
+/// Download `size` into `dest`, verified against the hash expected for the
+/// model's own path, and record that hash. Returns the file's SHA256. The
+/// bytes go to a `.part` file first, so `dest` only ever holds a whole model.
+async fn fetch_model(
+    size: &str,
+    dest: &Path,
+    on_progress: impl Fn(&DownloadProgress, &str),
+) -> Result<String, WhisperError> {
     let models_dir = get_models_dir();
     std::fs::create_dir_all(&models_dir)?;
 
-    let model_name = get_model_filename(size);
     let url = get_model_download_url(size);
-    let path = models_dir.join(&model_name);
+    let path = get_model_path(size);
 
-    log::info!("Downloading model '{}' from {} to {:?}", size, url, path);
+    log::info!("Downloading model '{}' from {} to {:?}", size, url, dest);
 
-    let response = reqwest::get(&url)
+    let expected_sha256 = integrity::expected_for_download(&url).await;
+
+    let remote = download::open(&url)
         .await
         .map_err(|e| WhisperError::DownloadError(e.to_string()))?;
+    let mirror = remote.mirror().to_string();
 
-    if !response.status().is_success() {
-        return Err(WhisperError::DownloadError(format!(
-            "HTTP {}",
-            response.status()
-        )));
-    }
-
-    let total_size = response.content_length().unwrap_or_else(|| ggml_size_bytes(size));
-    let mut downloaded: u64 = 0;
+    let total_size = remote.total_bytes().unwrap_or_else(|| ggml_size_bytes(size));
+    let mut tracker = ProgressTracker::new(total_size);
     let mut last_percent: u8 = 0;
-    let mut file =
-        std::fs::File::create(&path).map_err(|e| WhisperError::DownloadError(e.to_string()))?;
-    let mut response = response;
 
-    while let Some(chunk) = response
-        .chunk()
-        .await
-        .map_err(|e| WhisperError::DownloadError(e.to_string()))?
-    {
-        file.write_all(&chunk)?;
-        downloaded += chunk.len() as u64;
-        if total_size > 0 {
-            let percent = (downloaded * 100 / total_size).min(100) as u8;
-            if percent != last_percent {
-                last_percent = percent;
-                on_progress(percent);
+    let tmp_path = dest.with_extension("part");
+    let actual_sha256 = remote
+        .save_to(&tmp_path, |bytes| {
+            tracker.advance(bytes);
+            if total_size > 0 {
+                let progress = tracker.progress(100.0);
+                if progress.percent as u8 != last_percent {
+                    last_percent = progress.percent as u8;
+                    on_progress(&progress, &mirror);
+                }
             }
+        })
+        .await
+        .map_err(|e| match e {
+            // Kept as IO so a full disk can be recognized
+            NetError::IoError(e) => WhisperError::IoError(e),
+            e => WhisperError::DownloadError(e.to_string()),
+        })?;
+
+    integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
+    std::fs::rename(&tmp_path, dest)?;
+    integrity::record(&path, &actual_sha256);
+
+    log::info!("Model downloaded successfully ({} bytes)", tracker.downloaded_bytes());
+    Ok(actual_sha256)
+}
+
+/// Compare a downloaded model with the latest revision. Models downloaded
+/// before hashes were recorded are hashed once here.
+pub async fn check_model_update(size: &str) -> Result<ModelUpdate, WhisperError> {
+    let path = get_model_path(size);
+    if !path.exists() {
+        return Err(WhisperError::ModelNotFound(size.to_string()));
+    }
+
+    let local_sha256 = match integrity::recorded_sha256(&path) {
+        Some(hash) => hash,
+        None => {
+            let hash_path = path.clone();
+            let hash = tokio::task::spawn_blocking(move || integrity::hash_file(&hash_path))
+                .await
+                .map_err(|e| WhisperError::DownloadError(e.to_string()))??;
+            integrity::record(&path, &hash);
+            hash
         }
+    };
+    let latest_sha256 = integrity::expected_for_download(&get_model_download_url(size)).await;
+    let update_available = latest_sha256
+        .as_deref()
+        .map(|latest| !latest.eq_ignore_ascii_case(&local_sha256))
+        .unwrap_or(false);
+
+    Ok(ModelUpdate {
+        id: size.to_string(),
+        local_sha256: Some(local_sha256),
+        latest_sha256,
+        update_available,
+    })
+}
+
+/// `check_model_update` for every downloaded model.
+pub async fn check_model_updates() -> Vec<ModelUpdate> {
+    let mut updates = Vec::new();
+    for model in get_available_models().into_iter().filter(|m| m.downloaded) {
+        match check_model_update(&model.id).await {
+            Ok(update) => updates.push(update),
+            Err(e) => log::warn!("Update check failed for model '{}': {}", model.id, e),
+        }
+    }
+    updates
+}
+
+/// Replace a downloaded model with the latest revision.
+///
+/// The hosts serve whole files only, so there is no binary diff: the new
+/// revision is downloaded next to the current one and swapped in once
+/// verified, leaving the current model usable if the download fails.
+pub async fn update_model(
+    size: &str,
+    on_progress: impl Fn(&DownloadProgress, &str),
+) -> Result<(), WhisperError> {
+    let path = get_model_path(size);
+    if !path.exists() {
+        return Err(WhisperError::ModelNotFound(size.to_string()));
     }
 
-    log::info!("Model downloaded successfully ({} bytes)", downloaded);
+    let staged = path.with_extension("bin.update");
+    let result = fetch_model(size, &staged, on_progress).await;
+    if let Err(e) = result {
+        std::fs::remove_file(&staged).ok();
+        return Err(e);
+    }
+    std::fs::rename(&staged, &path)?;
+
+    // The cached context still holds the old revision; the next use reloads
+    if let Ok(mut cache) = MODEL_CACHE.lock() {
+        if cache.model_size == size {
+            cache.context = None;
+            cache.model_size.clear();
+            cache.model_path = PathBuf::new();
+        }
+    }
+    if let Ok(mut state_cache) = STATE_CACHE.lock() {
+        if state_cache.as_ref().map(|c| c.model_size == size).unwrap_or(false) {
+            *state_cache = None;
+        }
+    }
+    log::info!("Updated GGML model: {}", size);
     Ok(())
 }
 
+/// Drop the cached model context, pre-created state and VAD context so the
+/// next use loads them from disk again. An inference in progress holds its
+/// own reference and finishes normally.
+pub fn clear_caches() {
+    if let Ok(mut cache) = MODEL_CACHE.lock() {
+        cache.context = None;
+        cache.model_size.clear();
+        cache.model_path = PathBuf::new();
+    }
+    if let Ok(mut state_cache) = STATE_CACHE.lock() {
+        *state_cache = None;
+    }
+    if let Ok(mut active) = ACTIVE_ENCODER.lock() {
+        *active = None;
+    }
+    if let Ok(mut vad) = VAD_CACHE.lock() {
+        *vad = None;
+    }
+    SILERO_FAILED.store(false, Ordering::Relaxed);
+    log::info!("Whisper caches cleared");
+}
+
 /// Delete a downloaded GGML model.
 pub fn delete_model(size: &str) -> Result<(), WhisperError> {
     let model_path = get_model_path(size);
     if model_path.exists() {
         std::fs::remove_file(&model_path)?;
+        integrity::forget(&model_path);
+        std::fs::remove_file(get_models_dir().join(NO_COREML_DIR).join(get_model_filename(size))).ok();
         // Clear cache if this was the cached model
         if let Ok(mut cache) = MODEL_CACHE.lock() {
             if cache.model_size == size {
@@ -330,10 +705,10 @@ pub fn delete_coreml_model(size: &str) -> Result<(), WhisperError> {
 
 /// Download the CoreML encoder model for a given size.
 /// Downloads the zip from HuggingFace and extracts it into the models directory.
-/// Calls `on_progress(percent)` during download (0-99), and 100 is reserved for extraction complete.
+/// Calls `on_progress(progress, mirror)` during download (0-99%), and 100% is reserved for extraction complete.
 pub async fn download_coreml_model(
     size: &str,
-    on_progress: impl Fn(u8),
+    on_progress: impl Fn(&DownloadProgress, &str),
 ) -> Result<(), WhisperError> {
     let models_dir = get_models_dir();
     std::fs::create_dir_all(&models_dir)?;
@@ -342,56 +717,55 @@ pub async fn download_coreml_model(
     let zip_name = format!("{}.zip", encoder_name);
     let url = format!("{}/{}", MODEL_BASE_URL, zip_name);
     let zip_path = models_dir.join(&zip_name);
+    let tmp_path = zip_path.with_extension("zip.part");
     let dest_dir = models_dir.join(&encoder_name);
 
     // Skip if already downloaded
     if dest_dir.is_dir() {
         log::info!("CoreML model already exists: {:?}", dest_dir);
-        on_progress(100);
+        on_progress(&ProgressTracker::new(0).finished(), "local");
         return Ok(());
     }
 
     log::info!("Downloading CoreML model from {} to {:?}", url, zip_path);
 
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| WhisperError::DownloadError(e.to_string()))?;
+    let expected_sha256 = integrity::expected_for_download(&url).await;
 
-    if !response.status().is_success() {
-        return Err(WhisperError::DownloadError(format!(
-            "HTTP {} for CoreML model",
-            response.status()
-        )));
-    }
+    let remote = download::open(&url)
+        .await
+        .map_err(|e| WhisperError::DownloadError(format!("CoreML model: {}", e)))?;
+    let mirror = remote.mirror().to_string();
 
-    let total_size = response.content_length().unwrap_or_else(|| coreml_size_bytes(size));
-    let mut downloaded: u64 = 0;
+    let total_size = remote.total_bytes().unwrap_or_else(|| coreml_size_bytes(size));
+    let mut tracker = ProgressTracker::new(total_size);
     let mut last_percent: u8 = 0;
-    let mut file = std::fs::File::create(&zip_path)
-        .map_err(|e| WhisperError::DownloadError(e.to_string()))?;
-    let mut response = response;
 
-    while let Some(chunk) = response
-        .chunk()
-        .await
-        .map_err(|e| WhisperError::DownloadError(e.to_string()))?
-    {
-        file.write_all(&chunk)?;
-        downloaded += chunk.len() as u64;
-        if total_size > 0 {
-            // Cap download phase at 99% — 100% means extraction done
-            let percent = (downloaded * 99 / total_size).min(99) as u8;
-            if percent != last_percent {
-                last_percent = percent;
-                on_progress(percent);
+    let actual_sha256 = remote
+        .save_to(&tmp_path, |bytes| {
+            tracker.advance(bytes);
+            if total_size > 0 {
+                // Cap download phase at 99% — 100% means extraction done
+                let progress = tracker.progress(99.0);
+                if progress.percent as u8 != last_percent {
+                    last_percent = progress.percent as u8;
+                    on_progress(&progress, &mirror);
+                }
             }
-        }
-    }
-    drop(file);
+        })
+        .await
+        .map_err(|e| match e {
+            NetError::IoError(e) => WhisperError::IoError(e),
+            e => WhisperError::DownloadError(e.to_string()),
+        })?;
+
+    // Verify the archive before extracting (the zip itself is deleted afterwards,
+    // so there's nothing to record for load-time checks)
+    integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
+    std::fs::rename(&tmp_path, &zip_path)?;
 
     log::info!(
         "CoreML zip downloaded ({} bytes), extracting...",
-        downloaded
+        tracker.downloaded_bytes()
     );
 
     // Extract using unzip (always available on macOS)
@@ -419,7 +793,7 @@ pub async fn download_coreml_model(
 
     if dest_dir.is_dir() {
         log::info!("CoreML model extracted successfully: {:?}", dest_dir);
-        on_progress(100);
+        on_progress(&tracker.finished(), &mirror);
     } else {
         return Err(WhisperError::DownloadError(format!(
             "Extraction succeeded but {:?} not found",
@@ -434,9 +808,14 @@ fn get_vad_model_path() -> PathBuf {
     get_models_dir().join(VAD_MODEL_FILENAME)
 }
 
+pub fn is_vad_model_downloaded() -> bool {
+    get_vad_model_path().exists()
+}
+
 /// Download the Silero VAD model (~2MB) if not already present.
-/// Called automatically during model preload.
-pub async fn ensure_vad_model() -> Result<(), WhisperError> {
+/// Called automatically during model preload; `on_progress` is only called
+/// when a download actually happens.
+pub async fn ensure_vad_model(on_progress: impl Fn(&DownloadProgress, &str)) -> Result<(), WhisperError> {
     let path = get_vad_model_path();
     if path.exists() {
         return Ok(());
@@ -448,24 +827,33 @@ pub async fn ensure_vad_model() -> Result<(), WhisperError> {
     let url = format!("{}/{}", MODEL_BASE_URL, VAD_MODEL_FILENAME);
     log::info!("Downloading VAD model from {} to {:?}", url, path);
 
-    let response = reqwest::get(&url)
-        .await
-        .map_err(|e| WhisperError::DownloadError(e.to_string()))?;
-
-    if !response.status().is_success() {
-        return Err(WhisperError::DownloadError(format!(
-            "HTTP {}",
-            response.status()
-        )));
-    }
+    let expected_sha256 = integrity::expected_for_download(&url).await;
 
-    let bytes = response
-        .bytes()
+    let remote = download::open(&url)
         .await
         .map_err(|e| WhisperError::DownloadError(e.to_string()))?;
+    let mirror = remote.mirror().to_string();
+
+    let mut tracker = ProgressTracker::new(remote.total_bytes().unwrap_or(0));
+    let tmp_path = path.with_extension("part");
+    let actual_sha256 = remote
+        .save_to(&tmp_path, |bytes| {
+            tracker.advance(bytes);
+            on_progress(&tracker.progress(99.0), &mirror);
+        })
+        .await
+        .map_err(|e| match e {
+            NetError::IoError(e) => WhisperError::IoError(e),
+            e => WhisperError::DownloadError(e.to_string()),
+        })?;
+
+    integrity::check_download(&tmp_path, expected_sha256.as_deref(), &actual_sha256)?;
+    std::fs::rename(&tmp_path, &path)?;
+    integrity::record(&path, &actual_sha256);
 
-    std::fs::write(&path, &bytes)?;
-    log::info!("VAD model downloaded successfully ({} bytes)", bytes.len());
+    log::info!("VAD model downloaded successfully ({} bytes)", tracker.downloaded_bytes());
+    SILERO_FAILED.store(false, Ordering::Relaxed);
+    on_progress(&tracker.finished(), &mirror);
     Ok(())
 }
 
@@ -479,65 +867,133 @@ unsafe impl Send for SendableVadContext {}
 // on every transcription. Saves ~5-20ms per call (disk I/O + ONNX init).
 static VAD_CACHE: Lazy<Mutex<Option<SendableVadContext>>> = Lazy::new(|| Mutex::new(None));
 
-/// Pre-filter audio using Silero VAD to extract only speech segments.
-/// This strips non-speech audio before whisper inference, dramatically reducing
-/// computation for recordings with silence/noise.
-///
-/// Returns the filtered audio samples, or the original samples if VAD is unavailable.
-/// Expects 16kHz mono f32 input.
-fn vad_filter_speech(samples: &[f32]) -> Vec<f32> {
-    let vad_path = get_vad_model_path();
-    if !vad_path.exists() {
-        log::debug!("VAD model not found, skipping pre-filtering");
-        return samples.to_vec();
+/// Set when Silero fails to load in auto mode, so later calls go straight to
+/// the fallback detector. Cleared when the model is (re)downloaded.
+static SILERO_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Silero VAD through whisper.cpp, using the cached context.
+struct SileroDetector;
+
+impl SpeechDetector for SileroDetector {
+    fn name(&self) -> &'static str {
+        "silero"
     }
 
-    let vad_start = std::time::Instant::now();
+    fn speech_segments(&mut self, samples: &[f32]) -> Result<Vec<Range<usize>>, VadError> {
+        let vad_path = get_vad_model_path();
+        if !vad_path.exists() {
+            return Err(VadError::ModelNotFound);
+        }
 
-    // Get or create cached VAD context
-    let mut vad_guard = match VAD_CACHE.lock() {
-        Ok(guard) => guard,
-        Err(e) => {
-            log::warn!("VAD cache lock poisoned: {}, skipping pre-filtering", e);
-            return samples.to_vec();
+        // Get or create cached VAD context
+        let mut vad_guard = VAD_CACHE.lock_or_reset();
+        if vad_guard.is_none() {
+            let mut ctx_params = WhisperVadContextParams::new();
+            ctx_params.set_n_threads(2);
+            let ctx = WhisperVadContext::new(vad_path.to_str().unwrap(), ctx_params)
+                .map_err(|e| VadError::DetectionError(format!("failed to load model: {}", e)))?;
+            log::info!("VAD context created and cached");
+            *vad_guard = Some(SendableVadContext(ctx));
         }
-    };
 
-    if vad_guard.is_none() {
-        let mut ctx_params = WhisperVadContextParams::new();
-        ctx_params.set_n_threads(2);
+        let vad_ctx = &mut vad_guard.as_mut().unwrap().0;
+
+        // Configure VAD params for dictation use
+        let mut vad_params = WhisperVadParams::new();
+        vad_params.set_threshold(0.5);
+        vad_params.set_min_speech_duration(250); // 250ms minimum speech
+        vad_params.set_min_silence_duration(100); // 100ms silence to split
+        vad_params.set_speech_pad(30); // 30ms padding around speech
+
+        let segments = vad_ctx
+            .segments_from_samples(vad_params, samples)
+            .map_err(|e| VadError::DetectionError(e.to_string()))?;
+
+        // Timestamps are in centiseconds (0.01s), convert to sample indices at 16kHz
+        Ok(segments
+            .into_iter()
+            .map(|seg| {
+                let start = (seg.start * 160.0) as usize; // 0.01s * 16000 = 160 samples/cs
+                let end = ((seg.end * 160.0) as usize).min(samples.len());
+                start..end
+            })
+            .filter(|range| range.start < range.end)
+            .collect())
+    }
+}
+
+/// Find speech with the configured VAD backend, returning the segments and the
+/// backend that produced them. In auto mode Silero is tried first and the
+/// built-in fallback takes over when its model is missing or won't load, so
+/// streaming segmentation keeps working either way. None if detection failed
+/// entirely.
+pub(crate) fn detect_speech(samples: &[f32]) -> Option<(Vec<Range<usize>>, &'static str)> {
+    detect_speech_with(samples, true)
+}
 
-        match WhisperVadContext::new(vad_path.to_str().unwrap(), ctx_params) {
-            Ok(ctx) => {
-                log::info!("VAD context created and cached");
-                *vad_guard = Some(SendableVadContext(ctx));
+/// `detect_speech`; `auto_fallback` says whether the built-in fallback may
+/// stand in for Silero in auto mode.
+fn detect_speech_with(samples: &[f32], auto_fallback: bool) -> Option<(Vec<Range<usize>>, &'static str)> {
+    let backend = vad::backend();
+    let try_silero = match backend {
+        VadBackend::Silero => true,
+        VadBackend::Auto => !SILERO_FAILED.load(Ordering::Relaxed),
+        VadBackend::WebRtc | VadBackend::Energy => false,
+    };
+
+    if try_silero {
+        match SileroDetector.speech_segments(samples) {
+            Ok(segments) => return Some((segments, "silero")),
+            Err(VadError::ModelNotFound) if backend == VadBackend::Auto => {
+                log::debug!("Silero VAD model not found, using fallback VAD");
+            }
+            Err(e) if backend == VadBackend::Auto => {
+                log::warn!("Silero VAD unavailable ({}), falling back to built-in VAD", e);
+                SILERO_FAILED.store(true, Ordering::Relaxed);
+            }
+            Err(VadError::ModelNotFound) => {
+                log::debug!("Silero VAD model not found, skipping VAD");
+                return None;
             }
             Err(e) => {
-                log::warn!("Failed to load VAD model: {}, skipping pre-filtering", e);
-                return samples.to_vec();
+                log::warn!("Silero VAD failed: {}", e);
+                return None;
             }
         }
     }
 
-    let vad_ctx = &mut vad_guard.as_mut().unwrap().0;
-
-    // Configure VAD params for dictation use
-    let mut vad_params = WhisperVadParams::new();
-    vad_params.set_threshold(0.5);
-    vad_params.set_min_speech_duration(250); // 250ms minimum speech
-    vad_params.set_min_silence_duration(100); // 100ms silence to split
-    vad_params.set_speech_pad(30); // 30ms padding around speech
+    if backend == VadBackend::Auto && !auto_fallback {
+        log::debug!("Silero VAD unavailable, skipping VAD");
+        return None;
+    }
 
-    // Run VAD to get speech timestamps
-    let segments = match vad_ctx.segments_from_samples(vad_params, samples) {
-        Ok(segs) => segs,
+    let mut detector = vad::fallback_detector(backend);
+    match detector.speech_segments(samples) {
+        Ok(segments) => Some((segments, detector.name())),
         Err(e) => {
-            log::warn!("VAD inference failed: {}, skipping pre-filtering", e);
-            return samples.to_vec();
+            log::warn!("{} VAD failed: {}", detector.name(), e);
+            None
         }
+    }
+}
+
+/// Pre-filter audio with VAD to extract only speech segments.
+/// This strips non-speech audio before whisper inference, dramatically reducing
+/// computation for recordings with silence/noise.
+///
+/// Returns the filtered audio samples, or the original samples if VAD is unavailable.
+/// In auto mode only Silero filters: the built-in detectors can clip quiet
+/// speech, which costs words here, so without Silero the audio passes through.
+/// Expects 16kHz mono f32 input.
+fn vad_filter_speech(samples: &[f32]) -> Vec<f32> {
+    let vad_start = std::time::Instant::now();
+
+    let (segments, backend) = match detect_speech_with(samples, false) {
+        Some(result) => result,
+        None => return samples.to_vec(), // Already logged, skip pre-filtering
     };
 
-    let n_segments = segments.num_segments();
+    let n_segments = segments.len();
     if n_segments == 0 {
         log::info!("VAD: no speech detected in audio, passing through unchanged");
         return samples.to_vec();
@@ -545,13 +1001,8 @@ fn vad_filter_speech(samples: &[f32]) -> Vec<f32> {
 
     // Extract speech samples from detected segments
     let mut speech_samples = Vec::new();
-    for seg in segments {
-        // Timestamps are in centiseconds (0.01s), convert to sample indices at 16kHz
-        let start_sample = (seg.start * 160.0) as usize; // 0.01s * 16000 = 160 samples/cs
-        let end_sample = ((seg.end * 160.0) as usize).min(samples.len());
-        if start_sample < end_sample {
-            speech_samples.extend_from_slice(&samples[start_sample..end_sample]);
-        }
+    for range in segments {
+        speech_samples.extend_from_slice(&samples[range]);
     }
 
     if speech_samples.is_empty() {
@@ -575,7 +1026,8 @@ fn vad_filter_speech(samples: &[f32]) -> Vec<f32> {
     let vad_elapsed = vad_start.elapsed();
 
     log::info!(
-        "VAD: {:.2}s -> {:.2}s ({} segments, {:.0}% reduction) in {:.1}ms",
+        "VAD ({}): {:.2}s -> {:.2}s ({} segments, {:.0}% reduction) in {:.1}ms",
+        backend,
         original_duration,
         filtered_duration,
         n_segments,
@@ -593,8 +1045,8 @@ fn vad_filter_speech(samples: &[f32]) -> Vec<f32> {
 // and transcribes them immediately. On stop, only the remaining "tail" audio
 // needs transcription, reducing perceived latency from 1-3s to 200-500ms.
 
-/// Accumulated transcription text from completed utterances during recording.
-static STREAMING_RESULTS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Accumulated segments from completed utterances during recording.
+static STREAMING_RESULTS: Lazy<Mutex<Vec<SegmentInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
 
 /// How many samples from the start of WHISPER_BUFFER have been fully
 /// transcribed by the streaming monitor. Used to compute the "tail" on stop.
@@ -618,15 +1070,8 @@ pub struct StreamingConfig {
 /// Call this after `start_capture()` to enable background transcription during recording.
 pub fn start_streaming(config: StreamingConfig) {
     // Clear previous streaming state
-    *STREAMING_RESULTS.lock().unwrap() = Vec::new();
-    *STREAMING_CONSUMED.lock().unwrap() = 0;
-
-    // Check if VAD model is available
-    let vad_path = get_vad_model_path();
-    if !vad_path.exists() {
-        log::info!("VAD model not found, streaming transcription disabled");
-        return;
-    }
+    *STREAMING_RESULTS.lock_recover() = Vec::new();
+    *STREAMING_CONSUMED.lock_recover() = 0;
 
     // Check if whisper model is available
     let model_path = get_model_path(&config.model_size);
@@ -639,11 +1084,11 @@ pub fn start_streaming(config: StreamingConfig) {
     let thread = std::thread::Builder::new()
         .name("vad-streaming-monitor".to_string())
         .spawn(move || {
-            vad_monitor_loop(stop_rx, config);
+            crate::panics::catch("VAD streaming monitor", || vad_monitor_loop(stop_rx, config));
         })
         .expect("Failed to spawn VAD monitor thread");
 
-    *VAD_MONITOR.lock().unwrap() = Some(VadMonitorHandle {
+    *VAD_MONITOR.lock_recover() = Some(VadMonitorHandle {
         stop_sender: stop_tx,
         thread_handle: thread,
     });
@@ -651,10 +1096,48 @@ pub fn start_streaming(config: StreamingConfig) {
     log::info!("VAD streaming monitor started");
 }
 
-/// Stop the VAD monitor and return (accumulated_results, consumed_sample_count).
+/// Text of the utterances transcribed so far in the current recording.
+pub fn streaming_text() -> String {
+    STREAMING_RESULTS
+        .lock()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|s| s.text.trim())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+/// Take the utterances transcribed so far while the monitor keeps running, as
+/// (segments, consumed_sample_count). The consumed count may include an
+/// utterance transcribed just after the segments were taken; that one is left
+/// for the next call or `stop_streaming`.
+pub fn take_streaming_results() -> (Vec<SegmentInfo>, usize) {
+    let results = std::mem::take(&mut *STREAMING_RESULTS.lock_recover());
+    let consumed = *STREAMING_CONSUMED.lock_recover();
+    (results, consumed)
+}
+
+/// 16kHz samples of the current recording transcribed by the streaming monitor.
+pub fn streaming_consumed() -> usize {
+    STREAMING_CONSUMED.lock().map(|c| *c).unwrap_or(0)
+}
+
+/// Whether the streaming monitor is running (false if it never started or has exited).
+pub fn is_streaming() -> bool {
+    VAD_MONITOR
+        .lock()
+        .map(|m| m.as_ref().is_some_and(|h| !h.thread_handle.is_finished()))
+        .unwrap_or(false)
+}
+
+/// Stop the VAD monitor and return (accumulated_segments, consumed_sample_count).
 /// After this returns, all streaming transcriptions are complete.
-pub fn stop_streaming() -> (Vec<String>, usize) {
-    let handle = VAD_MONITOR.lock().unwrap().take();
+pub fn stop_streaming() -> (Vec<SegmentInfo>, usize) {
+    let handle = VAD_MONITOR.lock_recover().take();
     if let Some(h) = handle {
         // Signal stop
         h.stop_sender.send(()).ok();
@@ -663,9 +1146,9 @@ pub fn stop_streaming() -> (Vec<String>, usize) {
         log::info!("VAD streaming monitor stopped");
     }
 
-    let results = std::mem::take(&mut *STREAMING_RESULTS.lock().unwrap());
-    let consumed = *STREAMING_CONSUMED.lock().unwrap();
-    *STREAMING_CONSUMED.lock().unwrap() = 0;
+    let results = std::mem::take(&mut *STREAMING_RESULTS.lock_recover());
+    let consumed = *STREAMING_CONSUMED.lock_recover();
+    *STREAMING_CONSUMED.lock_recover() = 0;
 
     log::info!(
         "Streaming results: {} segments, {} samples consumed",
@@ -680,7 +1163,7 @@ pub fn stop_streaming() -> (Vec<String>, usize) {
 /// Periodically reads new audio from WHISPER_BUFFER, runs VAD to detect
 /// completed utterances, and transcribes them in-thread.
 fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingConfig) {
-    use crate::audio::capture::snapshot_whisper_buffer;
+    use crate::audio::capture::read_whisper_buffer;
 
     let mut abs_position: usize = 0; // Next sample to read from WHISPER_BUFFER
     let mut pending_audio: Vec<f32> = Vec::with_capacity(16000 * 10); // ~10s capacity
@@ -711,12 +1194,8 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
 
         std::thread::sleep(std::time::Duration::from_millis(CHECK_INTERVAL_MS));
 
-        // Get new samples from WHISPER_BUFFER
-        let (new_samples, new_len) = snapshot_whisper_buffer(abs_position);
-        if !new_samples.is_empty() {
-            abs_position = new_len;
-            pending_audio.extend_from_slice(&new_samples);
-        }
+        // Append new samples from WHISPER_BUFFER
+        abs_position = read_whisper_buffer(abs_position, &mut pending_audio);
 
         // Need minimum audio to run meaningful VAD
         if pending_audio.len() < MIN_VAD_SAMPLES {
@@ -724,52 +1203,11 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
         }
 
         // Run VAD on pending audio to find speech segments
-        let vad_path = get_vad_model_path();
-        if !vad_path.exists() {
-            continue;
-        }
-
         let vad_start = std::time::Instant::now();
-        let mut vad_guard = match VAD_CACHE.lock() {
-            Ok(guard) => guard,
-            Err(_) => continue,
-        };
-
-        if vad_guard.is_none() {
-            let mut ctx_params = WhisperVadContextParams::new();
-            ctx_params.set_n_threads(2);
-            match WhisperVadContext::new(vad_path.to_str().unwrap(), ctx_params) {
-                Ok(ctx) => {
-                    *vad_guard = Some(SendableVadContext(ctx));
-                }
-                Err(e) => {
-                    log::warn!("Failed to load VAD model for streaming: {}", e);
-                    continue;
-                }
-            }
-        }
-
-        let vad_ctx = &mut vad_guard.as_mut().unwrap().0;
-        let mut vad_params = WhisperVadParams::new();
-        vad_params.set_threshold(0.5);
-        vad_params.set_min_speech_duration(250);
-        vad_params.set_min_silence_duration(100);
-        vad_params.set_speech_pad(30);
-
-        let segments = match vad_ctx.segments_from_samples(vad_params, &pending_audio) {
-            Ok(segs) => segs,
-            Err(e) => {
-                log::warn!("VAD inference failed in streaming: {}", e);
-                continue;
-            }
+        let seg_list = match detect_speech(&pending_audio) {
+            Some((segments, _)) => segments,
+            None => continue,
         };
-
-        // Collect segment timestamps (start/end in centiseconds)
-        let seg_list: Vec<(f32, f32)> = segments.into_iter().map(|s| (s.start, s.end)).collect();
-
-        // Release VAD lock before potentially long transcription
-        drop(vad_guard);
-
         let vad_elapsed = vad_start.elapsed();
 
         if seg_list.is_empty() {
@@ -779,7 +1217,7 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
         // Check if there's a completed utterance: last segment must end with enough
         // silence gap before the end of pending audio
         let pending_duration_sec = pending_audio.len() as f32 / 16000.0;
-        let last_seg_end_sec = seg_list.last().unwrap().1 * 0.01; // centiseconds → seconds
+        let last_seg_end_sec = seg_list.last().unwrap().end as f32 / 16000.0;
         let gap = pending_duration_sec - last_seg_end_sec;
 
         if gap < MIN_SILENCE_GAP {
@@ -789,12 +1227,8 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
 
         // We have a completed utterance! Extract speech samples.
         let mut speech_samples: Vec<f32> = Vec::new();
-        for &(start_cs, end_cs) in &seg_list {
-            let start_sample = (start_cs * 160.0) as usize; // 0.01s * 16000 = 160
-            let end_sample = ((end_cs * 160.0) as usize).min(pending_audio.len());
-            if start_sample < end_sample {
-                speech_samples.extend_from_slice(&pending_audio[start_sample..end_sample]);
-            }
+        for range in &seg_list {
+            speech_samples.extend_from_slice(&pending_audio[range.clone()]);
         }
 
         if speech_samples.len() < MIN_SPEECH_SAMPLES {
@@ -828,14 +1262,22 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
             &speech_samples,
             config.language.as_deref(),
         ) {
-            Ok(text) => {
+            Ok(Decoded { text, confidence, temperature_fallbacks }) => {
                 if !text.is_empty() {
                     log::info!(
                         "VAD streaming: transcribed '{}' in {:.2}s",
                         if text.len() > 60 { format!("{}...", &text[..60]) } else { text.clone() },
                         transcription_start.elapsed().as_secs_f64()
                     );
-                    STREAMING_RESULTS.lock().unwrap().push(text);
+                    STREAMING_RESULTS.lock_recover().push(SegmentInfo {
+                        text,
+                        duration_ms: (speech_samples.len() / 16) as u32,
+                        engine: "whisper".to_string(),
+                        source: "streaming".to_string(),
+                        confidence,
+                        temperature_fallbacks,
+                    });
+                    crate::events::partial(streaming_text());
                 } else {
                     log::info!(
                         "VAD streaming: empty transcription (hallucination suppressed) in {:.2}s",
@@ -850,18 +1292,26 @@ fn vad_monitor_loop(stop_rx: std::sync::mpsc::Receiver<()>, config: StreamingCon
 
         // Advance past the consumed audio. Clear everything up to the end of the
         // last speech segment + some padding to avoid re-processing.
-        let clear_to_sample = ((seg_list.last().unwrap().1 * 160.0) as usize)
-            .min(pending_audio.len());
+        let clear_to_sample = seg_list.last().unwrap().end.min(pending_audio.len());
         pending_start += clear_to_sample;
         pending_audio.drain(..clear_to_sample);
 
         // Update consumed count so stop_capture knows the tail boundary
-        *STREAMING_CONSUMED.lock().unwrap() = pending_start;
+        *STREAMING_CONSUMED.lock_recover() = pending_start;
     }
 
     log::info!("VAD monitor loop exiting");
 }
 
+/// Whether `model_size` is loaded in MODEL_CACHE. Uses `try_lock` so callers on the
+/// recording path never wait behind a load in progress (reported as not loaded).
+pub fn is_model_loaded(model_size: &str) -> bool {
+    MODEL_CACHE
+        .try_lock()
+        .map(|c| c.context.is_some() && c.model_size == model_size)
+        .unwrap_or(false)
+}
+
 /// Preload the Whisper model into MODEL_CACHE so the first transcription is fast.
 ///
 /// This loads the GGML model file from disk, initializes the Metal/CoreML GPU backend,
@@ -883,13 +1333,14 @@ pub fn preload_model(model_size: &str) -> Result<(), WhisperError> {
     let total_start = std::time::Instant::now();
 
     // Lock the cache and check if we already have this model loaded
-    let mut cache = MODEL_CACHE
-        .lock()
-        .map_err(|e| WhisperError::TranscriptionError(format!("Cache lock error: {}", e)))?;
+    let mut cache = MODEL_CACHE.lock_or_reset();
+
+    let selection = select_encoder(model_size);
+    let load_path = model_load_path(model_size, &selection);
 
     if cache.context.is_some()
         && cache.model_size == model_size
-        && cache.model_path == model_path
+        && cache.model_path == load_path
     {
         log::info!(
             "preload_model: model '{}' already cached, skipping",
@@ -898,11 +1349,14 @@ pub fn preload_model(model_size: &str) -> Result<(), WhisperError> {
         return Ok(());
     }
 
+    integrity::verify_before_load(&model_path)?;
+
     // Load the model
     log::info!(
-        "preload_model: loading '{}' from {:?}",
+        "preload_model: loading '{}' from {:?} ({})",
         model_size,
-        model_path
+        load_path,
+        selection.reason
     );
     let load_start = std::time::Instant::now();
 
@@ -911,7 +1365,7 @@ pub fn preload_model(model_size: &str) -> Result<(), WhisperError> {
     ctx_params.use_gpu(true); // Enable Metal GPU acceleration for decoder
 
     let ctx = WhisperContext::new_with_params(
-        model_path.to_str().unwrap(),
+        load_path.to_str().unwrap(),
         ctx_params,
     )
     .map_err(|e| WhisperError::TranscriptionError(format!("Failed to load model: {}", e)))?;
@@ -949,7 +1403,10 @@ pub fn preload_model(model_size: &str) -> Result<(), WhisperError> {
     // Store in cache (Arc-wrapped for lock-free inference)
     cache.context = Some(Arc::new(ctx));
     cache.model_size = model_size.to_string();
-    cache.model_path = model_path;
+    cache.model_path = load_path;
+    if let Ok(mut active) = ACTIVE_ENCODER.lock() {
+        *active = Some(selection);
+    }
 
     let total_elapsed = total_start.elapsed();
     log::info!(
@@ -962,13 +1419,37 @@ pub fn preload_model(model_size: &str) -> Result<(), WhisperError> {
     Ok(())
 }
 
+/// Timings of the most recent tail transcription, reset by `transcribe()` and
+/// filled in by the transcription thread. Read with `take_last_timings()`.
+static LAST_TIMINGS: Lazy<Mutex<EngineTimings>> = Lazy::new(|| Mutex::new(EngineTimings::default()));
+
+pub fn take_last_timings() -> EngineTimings {
+    LAST_TIMINGS
+        .lock()
+        .map(|mut t| std::mem::take(&mut *t))
+        .unwrap_or_default()
+}
+
+/// whisper.cpp skips input under one second ("input is too short") and
+/// returns no text, which lost short utterances such as a spoken "period".
+/// A little over that, so rounding to mel frames can't fall short.
+const MIN_INPUT_SAMPLES: usize = 17600; // 1.1s
+
+/// Pad audio shorter than whisper.cpp accepts with trailing silence.
+fn pad_short_input(mut samples: Vec<f32>) -> Vec<f32> {
+    if samples.len() < MIN_INPUT_SAMPLES {
+        samples.resize(MIN_INPUT_SAMPLES, 0.0);
+    }
+    samples
+}
+
 /// A job for the dedicated transcription thread.
 struct TranscriptionJob {
     samples: Vec<f32>,
     model_size: String,
     language: Option<String>,
     run_vad: bool,
-    result_tx: tokio::sync::oneshot::Sender<Result<String, WhisperError>>,
+    result_tx: tokio::sync::oneshot::Sender<Result<Decoded, WhisperError>>,
 }
 
 /// Lazy-initialized sender for the dedicated transcription thread.
@@ -980,18 +1461,28 @@ static TRANSCRIPTION_TX: Lazy<std::sync::mpsc::Sender<TranscriptionJob>> = Lazy:
         .spawn(move || {
             log::info!("Dedicated transcription thread started");
             for job in rx {
-                let samples = if job.run_vad {
-                    vad_filter_speech(&job.samples)
-                } else {
-                    job.samples
-                };
-
-                let result = if samples.is_empty() {
-                    Ok(String::new())
-                } else {
-                    let path = get_model_path(&job.model_size);
-                    run_whisper(&path, &job.model_size, &samples, job.language.as_deref())
-                };
+                // A panic inside whisper.cpp's callbacks or our own code fails
+                // this job only; the thread stays up for the next one
+                let result = crate::panics::catch("Whisper transcription", || {
+                    let vad_start = std::time::Instant::now();
+                    let samples = if job.run_vad {
+                        vad_filter_speech(&job.samples)
+                    } else {
+                        job.samples
+                    };
+                    if let Ok(mut t) = LAST_TIMINGS.lock() {
+                        t.vad_ms = vad_start.elapsed().as_millis() as u64;
+                    }
+
+                    if samples.is_empty() {
+                        Ok(Decoded::default())
+                    } else {
+                        let samples = pad_short_input(samples);
+                        let path = get_model_path(&job.model_size);
+                        run_whisper(&path, &job.model_size, &samples, job.language.as_deref())
+                    }
+                })
+                .unwrap_or_else(|| Err(WhisperError::TranscriptionError("Transcription panicked".to_string())));
 
                 // Send result back (ignore error if receiver was dropped)
                 job.result_tx.send(result).ok();
@@ -1006,11 +1497,12 @@ static TRANSCRIPTION_TX: Lazy<std::sync::mpsc::Sender<TranscriptionJob>> = Lazy:
 ///
 /// When `streaming_prefix` is provided (from VAD-triggered streaming during recording),
 /// only the tail audio is transcribed and appended to the streaming results.
+/// Returns the combined text and the tail segment (None when there was no tail audio).
 pub async fn transcribe(
     audio: AudioData,
     settings: &UserSettings,
     streaming_prefix: Option<String>,
-) -> Result<String, WhisperError> {
+) -> Result<(String, Option<SegmentInfo>), WhisperError> {
     let model_size = settings
         .transcription
         .model_size
@@ -1022,14 +1514,17 @@ pub async fn transcribe(
     if !model_path.exists() {
         return Err(WhisperError::ModelNotFound(model_size.clone()));
     }
+    crate::storage::mark_model_used(&model_size);
 
     // Prepare audio for Whisper (16kHz mono)
     let samples = prepare_for_whisper(audio);
 
     // If no tail audio, return just the streaming prefix
     if samples.is_empty() {
-        return Ok(streaming_prefix.unwrap_or_default());
+        return Ok((streaming_prefix.unwrap_or_default(), None));
     }
+    let tail_duration_ms = (samples.len() / 16) as u32;
+    take_last_timings();
 
     // Send to dedicated transcription thread (replaces tokio::spawn_blocking).
     // The persistent thread avoids thread-pool scheduling overhead (~1-5ms)
@@ -1047,21 +1542,32 @@ pub async fn transcribe(
         })
         .map_err(|_| WhisperError::TranscriptionError("Transcription thread closed".into()))?;
 
-    let tail_text = result_rx
+    let decoded = result_rx
         .await
         .map_err(|_| WhisperError::TranscriptionError("Transcription thread dropped result".into()))??;
+    let tail_text = decoded.text;
+
+    let tail_segment = SegmentInfo {
+        text: tail_text.clone(),
+        duration_ms: tail_duration_ms,
+        engine: "whisper".to_string(),
+        source: "tail".to_string(),
+        confidence: decoded.confidence,
+        temperature_fallbacks: decoded.temperature_fallbacks,
+    };
 
     // Combine streaming prefix with tail transcription
-    match streaming_prefix {
+    let text = match streaming_prefix {
         Some(prefix) if !prefix.is_empty() => {
             if tail_text.is_empty() {
-                Ok(prefix)
+                prefix
             } else {
-                Ok(format!("{} {}", prefix, tail_text))
+                format!("{} {}", prefix, tail_text)
             }
         }
-        _ => Ok(tail_text),
-    }
+        _ => tail_text,
+    };
+    Ok((text, Some(tail_segment)))
 }
 
 /// Check if a model is a "turbo" variant (pruned to 4 decoder layers).
@@ -1086,42 +1592,100 @@ fn is_lightweight_decoder(model_size: &str) -> bool {
     is_turbo_model(model_size) || is_distil_model(model_size)
 }
 
-/// Known whisper hallucination phrases that appear when the model generates
-/// text from silence or near-silence. These are artifacts from the training
-/// data (YouTube subtitles) that the model memorized.
-const HALLUCINATION_PHRASES: &[&str] = &[
-    "thank you",
-    "thanks for watching",
-    "thanks for listening",
-    "thank you for watching",
-    "thank you for listening",
-    "please subscribe",
-    "like and subscribe",
-    "subtitles by",
-    "transcribed by",
-    "copyright",
-    "the end",
-    "you",
-];
-
-/// Check if text is likely a hallucination (common phrases whisper generates
-/// from silence/noise rather than actual speech).
-fn is_likely_hallucination(text: &str) -> bool {
-    let normalized = text.trim().to_lowercase();
-    // Empty or very short results from non-trivial audio are suspicious
-    if normalized.is_empty() {
-        return false; // Empty is handled elsewhere, not a hallucination
-    }
-    // Check exact matches and prefix matches against known hallucination phrases
-    for phrase in HALLUCINATION_PHRASES {
-        if normalized == *phrase
-            || normalized == format!("{}.", phrase)
-            || normalized == format!("{}!", phrase)
-        {
-            return true;
+/// Output of one Whisper run.
+#[derive(Debug, Default)]
+struct Decoded {
+    text: String,
+    /// Mean token probability
+    confidence: Option<f32>,
+    temperature_fallbacks: u32,
+}
+
+/// Temperature fallbacks since the last `take_temperature_fallbacks`.
+static TEMPERATURE_FALLBACKS: AtomicU32 = AtomicU32::new(0);
+
+/// Number of temperature fallbacks since the last call, for stats.
+pub fn take_temperature_fallbacks() -> u32 {
+    TEMPERATURE_FALLBACKS.swap(0, Ordering::Relaxed)
+}
+
+/// Decoding passes during one `full` call, counted from whisper.cpp's
+/// callbacks. whisper.cpp doesn't report its temperature fallbacks, but each
+/// one decodes the window again from its first token: every 30s window is
+/// encoded once and decoded once, plus once per fallback.
+#[derive(Default)]
+struct DecodePasses {
+    windows: u32,
+    passes: u32,
+}
+
+impl DecodePasses {
+    fn fallbacks(&self) -> u32 {
+        self.passes.saturating_sub(self.windows)
+    }
+}
+
+unsafe extern "C" fn count_window(
+    _ctx: *mut whisper_rs::WhisperSysContext,
+    _state: *mut whisper_rs::WhisperSysState,
+    user_data: *mut std::ffi::c_void,
+) -> bool {
+    (*(user_data as *mut DecodePasses)).windows += 1;
+    true
+}
+
+/// User data of the logits filter: the pass counter and the token sequences
+/// of the user's banned phrases.
+#[derive(Default)]
+struct DecodeFilter {
+    passes: DecodePasses,
+    banned: Vec<Vec<whisper_rs::WhisperTokenId>>,
+    n_vocab: usize,
+}
+
+/// Token sequences for every spelling of the banned phrases.
+fn banned_sequences(ctx: &WhisperContext) -> Vec<Vec<whisper_rs::WhisperTokenId>> {
+    let phrases = crate::dictionary::banned::get_phrases().unwrap_or_default();
+    let mut sequences: Vec<Vec<whisper_rs::WhisperTokenId>> = Vec::new();
+    for text in phrases.iter().flat_map(|p| crate::dictionary::banned::variants(p)) {
+        match ctx.tokenize(&text, 64) {
+            Ok(tokens) if !tokens.is_empty() && !sequences.contains(&tokens) => sequences.push(tokens),
+            Ok(_) => {}
+            Err(e) => log::warn!("Could not tokenize banned phrase {:?}: {}", text, e),
+        }
+    }
+    sequences
+}
+
+/// Called before each token is sampled, with the tokens decoded so far in
+/// this pass. Counts passes and rules out the token that would complete a
+/// banned phrase.
+unsafe extern "C" fn filter_logits(
+    _ctx: *mut whisper_rs::WhisperSysContext,
+    _state: *mut whisper_rs::WhisperSysState,
+    tokens: *const whisper_rs::WhisperTokenData,
+    n_tokens: std::os::raw::c_int,
+    logits: *mut f32,
+    user_data: *mut std::ffi::c_void,
+) {
+    let filter = &mut *(user_data as *mut DecodeFilter);
+    if n_tokens == 0 {
+        filter.passes.passes += 1;
+    }
+    if filter.banned.is_empty() || logits.is_null() {
+        return;
+    }
+    let decoded: Vec<whisper_rs::WhisperTokenId> = if n_tokens > 0 && !tokens.is_null() {
+        std::slice::from_raw_parts(tokens, n_tokens as usize).iter().map(|t| t.id).collect()
+    } else {
+        Vec::new()
+    };
+    let logits = std::slice::from_raw_parts_mut(logits, filter.n_vocab);
+    for token in crate::dictionary::banned::blocked_tokens(&decoded, &filter.banned) {
+        if let Some(logit) = logits.get_mut(token as usize) {
+            *logit = f32::NEG_INFINITY;
         }
     }
-    false
 }
 
 fn run_whisper(
@@ -1129,27 +1693,31 @@ fn run_whisper(
     model_size: &str,
     samples: &[f32],
     language: Option<&str>,
-) -> Result<String, WhisperError> {
+) -> Result<Decoded, WhisperError> {
     use whisper_rs::{FullParams, SamplingStrategy};
 
     let run_start = std::time::Instant::now();
 
     // Get or create the cached context, then clone the Arc and release the lock.
     // This ensures inference (which takes 1-30s) doesn't block preload or other callers.
+    let selection = select_encoder(model_size);
+    let has_coreml = selection.coreml;
     let ctx = {
-        let mut cache = MODEL_CACHE
-            .lock()
-            .map_err(|e| WhisperError::TranscriptionError(format!("Cache lock error: {}", e)))?;
+        let mut cache = MODEL_CACHE.lock_or_reset();
 
-        // Check if we need to reload the model
+        // Check if we need to reload the model (switching the encoder changes the load path)
+        let load_path = model_load_path(model_size, &selection);
         if cache.context.is_none()
             || cache.model_size != model_size
-            || cache.model_path != *model_path
+            || cache.model_path != load_path
         {
+            integrity::verify_before_load(model_path)?;
+
             log::info!(
-                "Loading Whisper model: {} from {:?}",
+                "Loading Whisper model: {} from {:?} ({})",
                 model_size,
-                model_path
+                load_path,
+                selection.reason
             );
 
             let load_start = std::time::Instant::now();
@@ -1159,19 +1727,29 @@ fn run_whisper(
             ctx_params.use_gpu(true); // Enable Metal GPU acceleration for decoder
 
             let new_ctx = WhisperContext::new_with_params(
-                model_path.to_str().unwrap(),
+                load_path.to_str().unwrap(),
                 ctx_params,
             )
             .map_err(|e| WhisperError::TranscriptionError(e.to_string()))?;
 
             cache.context = Some(Arc::new(new_ctx));
             cache.model_size = model_size.to_string();
-            cache.model_path = model_path.clone();
+            cache.model_path = load_path;
+            if let Ok(mut active) = ACTIVE_ENCODER.lock() {
+                *active = Some(selection);
+            }
+            // A pre-created state belongs to the previous context (possibly the other encoder)
+            if let Ok(mut state_cache) = STATE_CACHE.lock() {
+                *state_cache = None;
+            }
 
             log::info!(
                 "Whisper model loaded and cached in {:.2}s",
                 load_start.elapsed().as_secs_f64()
             );
+            if let Ok(mut t) = LAST_TIMINGS.lock() {
+                t.model_load_ms = load_start.elapsed().as_millis() as u64;
+            }
         } else {
             log::info!("Using cached Whisper model: {}", model_size);
         }
@@ -1221,7 +1799,6 @@ fn run_whisper(
     let is_turbo = is_turbo_model(model_size);
     let is_distil = is_distil_model(model_size);
     let is_lightweight = is_lightweight_decoder(model_size);
-    let has_coreml = is_coreml_downloaded(model_size);
     let audio_seconds = samples.len() as f32 / 16000.0;
 
     // === Dynamic audio_ctx: limit encoder window to actual audio length ===
@@ -1322,8 +1899,10 @@ fn run_whisper(
         log::info!("Whisper initial_prompt set with {} vocabulary words", prompt.split(", ").count());
     }
 
-    // Set language if specified
-    if let Some(lang) = language {
+    // Set language if specified. English-only models can't decode anything else.
+    if is_english_only(model_size) {
+        params.set_language(Some("en"));
+    } else if let Some(lang) = language {
         if lang != "auto" {
             params.set_language(Some(lang));
         }
@@ -1336,11 +1915,42 @@ fn run_whisper(
     params.set_print_timestamps(false);
     params.set_token_timestamps(false);
 
+    // The filter outlives `full`, which calls back on this thread only
+    let mut filter = DecodeFilter {
+        banned: banned_sequences(&ctx),
+        n_vocab: ctx.n_vocab().max(0) as usize,
+        ..Default::default()
+    };
+    if !filter.banned.is_empty() {
+        log::info!("Whisper suppressing {} banned token sequences", filter.banned.len());
+    }
+    let filter_ptr = &mut filter as *mut DecodeFilter;
+    unsafe {
+        let passes_ptr = std::ptr::addr_of_mut!((*filter_ptr).passes);
+        params.set_start_encoder_callback(Some(count_window));
+        params.set_start_encoder_callback_user_data(passes_ptr as *mut std::ffi::c_void);
+        params.set_filter_logits_callback(Some(filter_logits));
+        params.set_filter_logits_callback_user_data(filter_ptr as *mut std::ffi::c_void);
+    }
+
     let inference_start = std::time::Instant::now();
     state
         .full(params, samples)
         .map_err(|e| WhisperError::TranscriptionError(e.to_string()))?;
     let inference_elapsed = inference_start.elapsed();
+    if let Ok(mut t) = LAST_TIMINGS.lock() {
+        t.inference_ms = inference_elapsed.as_millis() as u64;
+    }
+    let temperature_fallbacks = filter.passes.fallbacks();
+    if temperature_fallbacks > 0 {
+        log::warn!(
+            "Whisper fell back to a higher temperature {} time(s) (model={}, {:.1}s audio)",
+            temperature_fallbacks,
+            model_size,
+            audio_seconds
+        );
+        TEMPERATURE_FALLBACKS.fetch_add(temperature_fallbacks, Ordering::Relaxed);
+    }
 
     let num_segments = state.full_n_segments();
 
@@ -1351,6 +1961,8 @@ fn run_whisper(
     );
 
     let mut text = String::new();
+    let mut prob_sum = 0.0f32;
+    let mut prob_count = 0u32;
     for i in 0..num_segments {
         let segment = state
             .get_segment(i)
@@ -1360,22 +1972,34 @@ fn run_whisper(
             .map_err(|e| WhisperError::TranscriptionError(e.to_string()))?;
         log::debug!("Segment {}: '{}'", i, segment_text);
         text.push_str(segment_text);
+        for t in 0..segment.n_tokens() {
+            if let Some(token) = segment.get_token(t) {
+                prob_sum += token.token_probability();
+                prob_count += 1;
+            }
+        }
     }
 
     let result = text.trim().to_string();
+    let confidence = (prob_count > 0).then(|| prob_sum / prob_count as f32);
 
     // === Post-inference hallucination guard ===
     // Even with proper parameters, the full large-v3 model can occasionally produce
-    // known hallucination phrases (especially on very short audio). If the result
-    // matches a known hallucination pattern AND the audio was short, return empty
-    // rather than injecting garbage text into the user's document.
-    if is_likely_hallucination(&result) {
+    // known hallucination phrases (especially on very short audio), and noisy audio
+    // can send the decoder into repeated-token loops. Loops are collapsed; output
+    // that is mostly garbage is dropped rather than injected into the user's document.
+    let guarded = super::hallucination::filter(&result, audio_seconds);
+    if let Some(reason) = &guarded.reason {
         log::warn!(
-            "Whisper output '{}' matches known hallucination pattern (model={}, {:.1}s audio), suppressing",
+            "Whisper output '{}' looks hallucinated ({}; model={}, {:.1}s audio)",
             result,
+            reason,
             model_size,
             audio_seconds
         );
+    }
+    let result = guarded.text;
+    if result.is_empty() && guarded.reason.is_some() {
         let total_elapsed = run_start.elapsed();
         log::info!(
             "Whisper transcription complete in {:.2}s -- hallucination suppressed (0 chars)",
@@ -1395,7 +2019,10 @@ fn run_whisper(
                 }
             }
         });
-        return Ok(String::new());
+        return Ok(Decoded {
+            temperature_fallbacks,
+            ..Decoded::default()
+        });
     }
 
     let total_elapsed = run_start.elapsed();
@@ -1438,5 +2065,9 @@ fn run_whisper(
         }
     });
 
-    Ok(result)
+    Ok(Decoded {
+        text: result,
+        confidence,
+        temperature_fallbacks,
+    })
 }
@@ -0,0 +1,139 @@
+//! Reload settings.json and dictionary.json when they change on disk.
+//!
+//! Both are cached in memory, so a hand edit or a dotfile sync used to take
+//! effect only after a restart. The config directory is watched rather than
+//! the files, since editors and sync tools usually replace a file instead of
+//! writing to it. Changes are applied once the files have been quiet for
+//! `SETTLE`, so a save in several writes is read once, complete.
+//!
+//! The app's own saves also show up here; they match what's in memory and
+//! are ignored.
+
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{dictionary, settings, AppState};
+
+/// Quiet period after the last change before files are read.
+const SETTLE: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct Changed {
+    settings: bool,
+    dictionary: bool,
+}
+
+impl Changed {
+    /// By file name: event paths may be spelled differently (symlinks,
+    /// /private on macOS), and only the config directory is watched.
+    fn note(&mut self, path: &Path) {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("settings.json") => self.settings = true,
+            Some("dictionary.json") => self.dictionary = true,
+            _ => {}
+        }
+    }
+}
+
+/// Watch the config directory for the lifetime of the app.
+pub fn start(app: AppHandle) {
+    let dir: PathBuf = match settings::get_settings_path().parent() {
+        Some(dir) => dir.to_path_buf(),
+        None => return,
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("Config watcher: failed to create {:?}: {}", dir, e);
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            log::warn!("Config watcher: failed to create watcher: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        log::warn!("Config watcher: failed to watch {:?}: {}", dir, e);
+        return;
+    }
+    log::info!("Watching {:?} for external changes", dir);
+
+    std::thread::Builder::new()
+        .name("config-watcher".to_string())
+        .spawn(move || {
+            // Owned by the thread so watching stops only with the app
+            let _watcher = watcher;
+            while let Ok(event) = rx.recv() {
+                let mut changed = Changed::default();
+                let mut next = Some(event);
+                loop {
+                    if let Some(Ok(event)) = next.take() {
+                        if !event.kind.is_access() {
+                            event.paths.iter().for_each(|p| changed.note(p));
+                        }
+                    }
+                    match rx.recv_timeout(SETTLE) {
+                        Ok(event) => next = Some(event),
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                if changed.settings {
+                    reload_settings(&app);
+                }
+                if changed.dictionary {
+                    reload_dictionary(&app);
+                }
+            }
+        })
+        .ok();
+}
+
+/// Apply an edited settings.json through `update_settings`, which validates
+/// it, reconfigures what changed and emits `settings-changed`.
+fn reload_settings(app: &AppHandle) {
+    if !settings::get_settings_path().exists() {
+        // Mid-replace, or removed: keep what's in memory
+        return;
+    }
+    let loaded = match settings::load_settings() {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            log::warn!("settings.json changed but can't be read, keeping current settings: {}", e);
+            return;
+        }
+    };
+
+    let state = app.state::<AppState>();
+    let unchanged = match state.settings.lock() {
+        Ok(current) => serde_json::to_value(&*current).ok() == serde_json::to_value(&loaded).ok(),
+        Err(_) => return,
+    };
+    if unchanged {
+        return;
+    }
+
+    log::info!("[settings] settings.json changed on disk, reloading");
+    if let Err(e) = crate::update_settings(app.clone(), loaded, state) {
+        log::warn!("Edited settings.json rejected, keeping current settings: {}", e);
+    }
+}
+
+fn reload_dictionary(app: &AppHandle) {
+    if !dictionary::get_dictionary_path().exists() {
+        return;
+    }
+    match dictionary::reload() {
+        Ok(Some(entries)) => {
+            log::info!("[dictionary] dictionary.json changed on disk, reloaded {} entries", entries.len());
+            app.emit("dictionary-changed", &entries).ok();
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("dictionary.json changed but can't be read, keeping current entries: {}", e),
+    }
+}
@@ -1,9 +1,12 @@
 import { useEffect, useState, useRef, useCallback } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
+import { ask } from '@tauri-apps/plugin-dialog';
 import { DictationBar } from './components/DictationBar';
 import { Dashboard } from './components/dashboard/Dashboard';
 import { useStore } from './lib/store';
+import { errorMessage, isAppError } from './lib/tauri';
+import type { AccuracyBoost, AppError, AudioStall, CleanupReport, CloudConsentRequest, FallbackHint, FileFailed, FileTranscribed, HotkeyEvent, RecordingStatus, StorageFull } from './types';
 import {
   MAX_HISTORY_ENTRIES, MIC_ERROR_TIMEOUT_MS, ERROR_TIMEOUT_MS,
   MODEL_PRELOAD_ERROR_TIMEOUT_MS, MODEL_DOWNLOAD_ERROR_TIMEOUT_MS,
@@ -13,10 +16,19 @@ import {
 
 type WindowType = 'dictation' | 'dashboard';
 
+// Sandboxed builds that can't paste leave the text on the clipboard and say so
+function injectionErrorMessage(error: unknown): string {
+  const message = errorMessage(error);
+  return message.startsWith('Copied to clipboard') ? message : `Failed to paste: ${message}`;
+}
+
 function App() {
   const [isRecording, setIsRecording] = useState(false);
   const [isProcessing, setIsProcessing] = useState(false);
   const [audioLevel, setAudioLevel] = useState(0);
+  const [elapsedMs, setElapsedMs] = useState<number | undefined>(undefined);
+  const [pendingSecs, setPendingSecs] = useState<number | undefined>(undefined);
+  const [partialText, setPartialText] = useState('');
   const [error, setError] = useState<string | null>(null);
   const [isDownloadingModel, setIsDownloadingModel] = useState(false);
   const [isPreloading, setIsPreloading] = useState(false);
@@ -26,6 +38,12 @@ function App() {
   const isRecordingRef = useRef(isRecording);
   const isProcessingRef = useRef(isProcessing);
   const settingsRef = useRef(settings);
+  // Pending hold-mode start, cancelled if the hotkey is released before hold_delay_ms
+  const holdTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
+  // Text of this dictation already injected (chunks, tail-first prefix); later parts continue it
+  const continuationRef = useRef(false);
+  // Tail-first prefix being injected while the tail is transcribed
+  const prefixRef = useRef<{ text: string; injected: Promise<void> } | null>(null);
 
   // Helper to save transcription to history
   const saveToHistory = useCallback((text: string) => {
@@ -67,26 +85,36 @@ function App() {
 
   const [windowType] = useState<WindowType>(getWindowType);
 
-  const startRecording = useCallback(async () => {
+  const startRecording = useCallback(async (incognito = false, hotkey?: string) => {
     if (isRecordingRef.current || isProcessingRef.current) {
       console.log('Already recording or processing, skipping start');
       return;
     }
     // Set ref immediately to prevent duplicate calls during await
     isRecordingRef.current = true;
+    continuationRef.current = false;
+    prefixRef.current = null;
     try {
       console.log('Starting recording...');
-      await invoke('start_recording');
+      await invoke('start_recording', { incognito, hotkey });
       setIsRecording(true);
+      setElapsedMs(0);
+      setPendingSecs(undefined);
+      setPartialText('');
       console.log('Recording started');
     } catch (error) {
       // Reset ref on error
       isRecordingRef.current = false;
       console.error('Failed to start recording:', error);
-      const errorMsg = error instanceof Error ? error.message : String(error);
-      if (errorMsg.includes('Model not found') || errorMsg.includes('not downloaded') || errorMsg.includes('not loaded')) {
+      const kind = isAppError(error) ? error.kind : null;
+      if (kind === 'model_missing') {
         setError('Model not loaded — download in Settings');
         setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+      } else if (kind === 'consent_required') {
+        // cloud-consent-required asks and starts again
+      } else if (kind === 'privacy_blocked' || kind === 'unsupported' || kind === 'permission') {
+        setError(errorMessage(error));
+        setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
       } else {
         setError('Mic busy — try again');
         setTimeout(() => setError(null), MIC_ERROR_TIMEOUT_MS);
@@ -111,13 +139,21 @@ function App() {
       const text = await invoke<string>('stop_recording');
       console.log('Recording stopped, transcribed text:', text);
 
+      // With tail-first delivery the prefix is already going in; the result is the rest
+      const prefix = prefixRef.current;
+      prefixRef.current = null;
+      if (prefix) {
+        await prefix.injected;
+        if (!text || !text.trim()) saveToHistory(prefix.text);
+      }
+
       if (text && text.trim()) {
         try {
-          await invoke('inject_text', { text });
-          saveToHistory(text); // Save to history on success
+          await invoke('inject_text', { text, continuation: continuationRef.current });
+          saveToHistory(prefix ? prefix.text + text : text); // Save to history on success
         } catch (injectionError) {
           console.error('Failed to inject text:', injectionError);
-          setError(`Failed to paste: ${injectionError}`);
+          setError(injectionErrorMessage(injectionError));
           // Clear error after 5 seconds
           setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
         }
@@ -126,10 +162,8 @@ function App() {
       }
     } catch (err: unknown) {
       console.error('Failed to stop recording:', err);
-      const errorMessage = err instanceof Error ? err.message : String(err);
-
-      // Check if it's a model not found error
-      if (errorMessage.includes('Model not found')) {
+      // A missing model is downloaded right away
+      if (isAppError(err) && err.kind === 'model_missing') {
         const modelSize = settingsRef.current?.transcription?.model_size || 'small';
         setError(`No speech model. Downloading ${modelSize}...`);
         // Trigger model download
@@ -140,7 +174,7 @@ function App() {
           setError('Please download a model in Settings');
         }
       } else {
-        setError(`Failed: ${errorMessage}`);
+        setError(`Failed: ${errorMessage(err)}`);
       }
       setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
     } finally {
@@ -192,32 +226,52 @@ function App() {
 
   // Set up event listeners (only once)
   useEffect(() => {
-    const unlistenPressed = listen('hotkey-pressed', async () => {
+    const unlistenPressed = listen<HotkeyEvent | null>('hotkey-pressed', async (event) => {
       // Only the dictation window should handle recording — dashboard must ignore
       // to prevent race conditions where both windows invoke start/stop simultaneously
       if (windowType !== 'dictation') return;
 
-      const mode = settingsRef.current?.hotkey?.mode ?? DEFAULT_HOTKEY_MODE; // Default to toggle
+      // The backend debounced the key for this mode; follow it over possibly stale settings
+      const mode = event.payload?.mode ?? settingsRef.current?.hotkey?.mode ?? DEFAULT_HOTKEY_MODE;
       console.log('Hotkey pressed, mode:', mode, 'isRecording:', isRecordingRef.current);
 
       if (mode === 'toggle') {
         if (isRecordingRef.current) {
           await stopRecording();
         } else {
-          await startRecording();
+          await startRecording(event.payload?.incognito ?? false, event.payload?.key);
         }
       } else {
-        // Hold mode - start on press
-        await startRecording();
+        // Hold mode - start on press, optionally only once the key has been held a moment
+        const holdDelay = settingsRef.current?.recording?.hold_delay_ms ?? 0;
+        if (holdDelay > 0) {
+          if (holdTimerRef.current) clearTimeout(holdTimerRef.current);
+          const incognito = event.payload?.incognito ?? false;
+          const key = event.payload?.key;
+          holdTimerRef.current = setTimeout(() => {
+            holdTimerRef.current = null;
+            startRecording(incognito, key);
+          }, holdDelay);
+        } else {
+          await startRecording(event.payload?.incognito ?? false, event.payload?.key);
+        }
       }
     });
 
-    const unlistenReleased = listen('hotkey-released', async () => {
+    const unlistenReleased = listen<HotkeyEvent | null>('hotkey-released', async (event) => {
       if (windowType !== 'dictation') return;
 
-      const mode = settingsRef.current?.hotkey?.mode ?? DEFAULT_HOTKEY_MODE;
+      const mode = event.payload?.mode ?? settingsRef.current?.hotkey?.mode ?? DEFAULT_HOTKEY_MODE;
       console.log('Hotkey released, mode:', mode, 'isRecording:', isRecordingRef.current);
 
+      if (holdTimerRef.current) {
+        // Released before the hold delay elapsed - treat as an accidental tap
+        clearTimeout(holdTimerRef.current);
+        holdTimerRef.current = null;
+        console.log('Hotkey released before hold delay, recording not started');
+        return;
+      }
+
       if (mode !== 'toggle' && isRecordingRef.current) {
         // Hold mode - stop on release
         await stopRecording();
@@ -234,10 +288,145 @@ function App() {
       setIsProcessing(false);
     });
 
+    const unlistenTooShort = listen<{ duration_ms: number; min_duration_ms: number }>('recording-too-short', (event) => {
+      console.log(`Recording too short (${event.payload.duration_ms}ms < ${event.payload.min_duration_ms}ms), discarded`);
+    });
+
+    // Long dictations are finalized in chunks while recording continues (recording.chunk_minutes)
+    const unlistenChunk = listen<string>('transcription-chunk', async (event) => {
+      if (windowType !== 'dictation') return;
+      const text = event.payload;
+      if (!text.trim()) return;
+      try {
+        await invoke('inject_text', { text, continuation: continuationRef.current });
+        continuationRef.current = true;
+        saveToHistory(text);
+      } catch (injectionError) {
+        console.error('Failed to inject chunk:', injectionError);
+        setError(injectionErrorMessage(injectionError));
+        setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+      }
+    });
+
+    // Tail-first delivery (output.tail_first): the streaming prefix arrives while the tail is transcribed
+    const unlistenPrefix = listen<string>('transcription-prefix', (event) => {
+      if (windowType !== 'dictation') return;
+      const text = event.payload;
+      const injected = invoke('inject_text', { text, continuation: continuationRef.current })
+        .then(() => { continuationRef.current = true; })
+        .catch((injectionError) => {
+          console.error('Failed to inject prefix:', injectionError);
+        });
+      prefixRef.current = { text, injected };
+    });
+
+    // Live text while recording; the final text replaces it
+    const unlistenPartial = listen<string>('transcription-partial', (event) => {
+      if (windowType !== 'dictation') return;
+      setPartialText(event.payload);
+    });
+
     const unlistenAudioLevel = listen<number>('audio-level', (event) => {
       setAudioLevel(event.payload);
     });
 
+    const unlistenRecordingStatus = listen<RecordingStatus>('recording-status', (event) => {
+      setElapsedMs(event.payload.elapsed_ms);
+      setPendingSecs(event.payload.live ? 0 : event.payload.pending_secs);
+    });
+
+    // Injection falls back to the clipboard until the permission returns
+    const unlistenPermissionLost = listen('permission-lost', () => {
+      if (windowType !== 'dictation') return;
+      setError('Accessibility permission was turned off. Dictations will be copied to the clipboard.');
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // Paste queue mode: the paste-next hotkey couldn't insert the item
+    const unlistenPasteQueueFailed = listen<string>('paste-queue-failed', (event) => {
+      if (windowType !== 'dictation') return;
+      setError(injectionErrorMessage(event.payload));
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // The input stream opened but delivers nothing (or only digital silence)
+    const unlistenAudioStalled = listen<AudioStall>('audio-stalled', (event) => {
+      if (windowType !== 'dictation') return;
+      const { kind, causes, restarted_on } = event.payload;
+      const what = kind === 'silent' ? 'The microphone only records silence' : 'The microphone isn\'t delivering audio';
+      setError(restarted_on
+        ? `${what}; switched to ${restarted_on === 'default' ? 'the default microphone' : restarted_on}.`
+        : `${what}. ${causes[0] ?? ''}`.trim());
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // The dictation was still output; only saving it failed
+    const unlistenStorageFull = listen<StorageFull>('storage-full', (event) => {
+      if (windowType !== 'dictation') return;
+      setError(event.payload.message);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // privacy.confirm_cloud: ask before this recording sends anything off the machine
+    const unlistenCloudConsent = listen<CloudConsentRequest>('cloud-consent-required', async (event) => {
+      if (windowType !== 'dictation') return;
+      const { what, incognito, hotkey, hold } = event.payload;
+      const agreed = await ask(`This recording will send ${what}. Continue?`, {
+        title: 'Send to a third party?',
+        kind: 'warning',
+      });
+      if (!agreed) return;
+      await invoke('grant_cloud_consent');
+      if (hold) {
+        setError('Hold the key again to record');
+        setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+      } else {
+        await startRecording(incognito, hotkey ?? undefined);
+      }
+    });
+
+    // AI cleanup failed or timed out; the dictation is output as transcribed
+    const unlistenCleanup = listen<CleanupReport>('cleanup-complete', (event) => {
+      if (windowType !== 'dictation' || !event.payload.error) return;
+      setError(`AI cleanup failed, used the original text: ${event.payload.error}`);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // Whisper keeps re-decoding the audio: suggest a larger model or VAD tuning
+    const unlistenAccuracyHint = listen<FallbackHint>('accuracy-hint', (event) => {
+      if (windowType !== 'dictation') return;
+      const percent = Math.round(event.payload.fallback_rate * 100);
+      setError(`Whisper struggled with ${percent}% of your dictations. A larger model or adjusting voice detection may help.`);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // Accuracy boost hotkey: confirm which model the next dictation uses
+    const unlistenAccuracyBoost = listen<AccuracyBoost>('accuracy-boost', (event) => {
+      if (windowType !== 'dictation' || event.payload.status !== 'armed') return;
+      setError(`Next dictation uses ${event.payload.model}`);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    const unlistenAccuracyBoostError = listen<AppError>('accuracy-boost-error', (event) => {
+      if (windowType !== 'dictation') return;
+      setError(event.payload.message);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    // WAV files dropped on the pill or copied (`transcribe_clipboard`)
+    const unlistenFileComplete = listen<FileTranscribed>('file-transcription-complete', (event) => {
+      if (windowType !== 'dictation') return;
+      const name = event.payload.path.split(/[\\/]/).pop();
+      setError(`Transcribed ${name} to the clipboard`);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
+    const unlistenFileError = listen<FileFailed>('file-transcription-error', (event) => {
+      if (windowType !== 'dictation') return;
+      setError(event.payload.error.message);
+      setTimeout(() => setError(null), ERROR_TIMEOUT_MS);
+    });
+
     // Handle model preloading status events
     const unlistenPreloadStart = listen<string>('model-preload-start', (event) => {
       console.log(`Model preload started: ${event.payload}`);
@@ -281,13 +470,29 @@ function App() {
       unlistenReleased.then((f) => f());
       unlistenProcessing.then((f) => f());
       unlistenComplete.then((f) => f());
+      unlistenTooShort.then((f) => f());
+      unlistenChunk.then((f) => f());
+      unlistenPrefix.then((f) => f());
       unlistenAudioLevel.then((f) => f());
+      unlistenPartial.then((f) => f());
+      unlistenRecordingStatus.then((f) => f());
+      unlistenStorageFull.then((f) => f());
+      unlistenCloudConsent.then((f) => f());
+      unlistenCleanup.then((f) => f());
+      unlistenAudioStalled.then((f) => f());
+      unlistenPermissionLost.then((f) => f());
+      unlistenPasteQueueFailed.then((f) => f());
+      unlistenAccuracyHint.then((f) => f());
+      unlistenAccuracyBoost.then((f) => f());
+      unlistenAccuracyBoostError.then((f) => f());
+      unlistenFileComplete.then((f) => f());
+      unlistenFileError.then((f) => f());
       unlistenPreloadStart.then((f) => f());
       unlistenPreloadComplete.then((f) => f());
       unlistenPreloadError.then((f) => f());
       unlistenModelDownload.then((f) => f());
     };
-  }, [startRecording, stopRecording]);
+  }, [startRecording, stopRecording, saveToHistory]);
 
   // Render based on window type
   if (windowType === 'dashboard') {
@@ -305,12 +510,22 @@ function App() {
 
   // Main dictation bar overlay
   return (
-    <div className="dictation-container">
+    <div
+      className="dictation-container"
+      onMouseEnter={() => {
+        if (settings?.transcription?.preload_policy === 'on-hotkey-hover') {
+          invoke('warm_up_model').catch(() => {});
+        }
+      }}
+    >
       <DictationBar
         isRecording={isRecording}
         isProcessing={isProcessing || isDownloadingModel}
         isPreloading={isPreloading}
         audioLevel={audioLevel}
+        elapsedMs={isRecording ? elapsedMs : undefined}
+        pendingSecs={isRecording ? pendingSecs : undefined}
+        partialText={isRecording ? partialText : undefined}
         error={error}
         statusOverride={isDownloadingModel ? 'Downloading model...' : undefined}
         draggable={draggableValue}
@@ -1,20 +1,33 @@
 import { FC, useEffect, useState, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../lib/tauri';
 import {
   WAVEFORM_BAR_COUNT, WAVEFORM_INITIAL_HEIGHT, WAVEFORM_UPDATE_INTERVAL_MS,
   WAVEFORM_SMOOTHING, WAVEFORM_BASE_MIN, WAVEFORM_CENTER_AMPLITUDE,
   WAVEFORM_NOISE_RANGE, WAVEFORM_RANDOM_RANGE, AUDIO_BOOST_BASE,
   AUDIO_BOOST_RANGE, WAVEFORM_MAX_HEIGHT, BAR_MIN_HEIGHT_PX, BAR_HEIGHT_SCALE,
-  PROCESSING_DOT_COUNT, PROCESSING_DOT_DELAY_STEP,
+  PROCESSING_DOT_COUNT, PROCESSING_DOT_DELAY_STEP, PARTIAL_TEXT_MAX_CHARS,
   CURSOR_POLL_INTERVAL_MS, PRELOAD_FLASH_DURATION_MS,
   DEFAULT_HOTKEY_LABEL, DEFAULT_HOTKEY_MODE,
 } from '../config/widget';
 
+/** 75000 -> "1:15" */
+function formatElapsed(ms: number): string {
+  const secs = Math.floor(ms / 1000);
+  return `${Math.floor(secs / 60)}:${String(secs % 60).padStart(2, '0')}`;
+}
+
 interface DictationBarProps {
   isRecording: boolean;
   isProcessing: boolean;
   isPreloading?: boolean;
   audioLevel?: number;
+  /** Elapsed recording time, from `recording-status`; shown next to the waveform */
+  elapsedMs?: number;
+  /** Audio streaming hasn't transcribed yet (0 while it keeps up); stop has to wait for it */
+  pendingSecs?: number;
+  /** Text transcribed so far, from `transcription-partial` */
+  partialText?: string;
   error?: string | null;
   statusOverride?: string;
   draggable?: boolean;
@@ -28,6 +41,9 @@ export const DictationBar: FC<DictationBarProps> = ({
   isProcessing,
   isPreloading = false,
   audioLevel = 0,
+  elapsedMs,
+  pendingSecs,
+  partialText = '',
   error = null,
   draggable = false,
   opacity = 1.0,
@@ -167,7 +183,7 @@ export const DictationBar: FC<DictationBarProps> = ({
     e.preventDefault();
     flog('[drag] Starting native drag via NSEvent monitors');
     invoke('start_native_drag').catch((err) => {
-      flog(`[drag] ERROR: start_native_drag failed: ${err}`);
+      flog(`[drag] ERROR: start_native_drag failed: ${errorMessage(err)}`);
     });
   }, [draggable, flog]);
 
@@ -206,6 +222,22 @@ export const DictationBar: FC<DictationBarProps> = ({
           }}
         />
       ))}
+      {elapsedMs != null && (
+        <span
+          className={`wispr-timer${pendingSecs === 0 ? ' live' : ''}`}
+          title={pendingSecs ? `${Math.round(pendingSecs)}s still to transcribe on stop` : 'Transcribing live'}
+        >
+          {formatElapsed(elapsedMs)}
+          {pendingSecs != null && pendingSecs >= 1 && <span className="wispr-pending"> +{Math.round(pendingSecs)}s</span>}
+        </span>
+      )}
+      {partialText && (
+        <span className="wispr-partial" title={partialText}>
+          {partialText.length > PARTIAL_TEXT_MAX_CHARS
+            ? `…${partialText.slice(-PARTIAL_TEXT_MAX_CHARS).trimStart()}`
+            : partialText}
+        </span>
+      )}
     </div>
   );
 
@@ -239,6 +271,8 @@ export const DictationBar: FC<DictationBarProps> = ({
         <div className="wispr-tooltip">
           {hotkeyMode === 'hold'
             ? <>Hold <span className="wispr-hotkey">{hotkeyLabel}</span> to start dictating</>
+            : hotkeyMode === 'hybrid'
+            ? <>Tap or hold <span className="wispr-hotkey">{hotkeyLabel}</span> to start dictating</>
             : <>Press <span className="wispr-hotkey">{hotkeyLabel}</span> to start dictating</>}
         </div>
       )}
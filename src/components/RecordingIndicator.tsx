@@ -0,0 +1,10 @@
+import { FC } from 'react';
+
+interface RecordingIndicatorProps {
+  style: 'strip' | 'icon';
+}
+
+/** Contents of the per-display indicator windows the backend opens while recording. */
+export const RecordingIndicator: FC<RecordingIndicatorProps> = ({ style }) => {
+  return <div className={`recording-indicator recording-indicator-${style}`} />;
+};
@@ -167,12 +167,13 @@ export const Settings: FC<SettingsProps> = ({ onBack, embedded = false }) => {
           <div>
             <label className="block text-sm text-gray-400 mb-1">Mode</label>
             <select
-              value={settings.hotkey.mode || 'hold'}
+              value={settings.hotkey.mode || 'toggle'}
               onChange={(e) => handleChange('hotkey', 'mode', e.target.value)}
               className="w-full bg-gray-700 border border-gray-600 rounded px-3 py-2"
             >
               <option value="hold">Hold to talk</option>
               <option value="toggle">Toggle on/off</option>
+              <option value="hybrid">Tap to toggle, hold to talk</option>
             </select>
           </div>
         </div>
@@ -276,7 +277,7 @@ export const Settings: FC<SettingsProps> = ({ onBack, embedded = false }) => {
                 onChange={(e) =>
                   handleChange('cleanup', 'api_key', e.target.value)
                 }
-                placeholder="Enter your API key"
+                placeholder={settings.cleanup.api_key_ref ? 'Stored in keychain — type to replace' : 'Enter your API key'}
                 className="w-full bg-gray-700 border border-gray-600 rounded px-3 py-2"
               />
             </div>
@@ -1,4 +1,5 @@
 import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useDictionaryStore } from '../../lib/dictionaryStore';
 import type { DictionaryEntry } from '../../types';
 
@@ -178,7 +179,7 @@ function EditModal({ entry, onSave, onCancel }: EditModalProps) {
 }
 
 export function DictionaryPage() {
-  const { entries, isLoading, loadDictionary, addEntry, updateEntry, removeEntry, toggleEntry } = useDictionaryStore();
+  const { entries, isLoading, loadDictionary, addEntry, updateEntry, removeEntry, toggleEntry, toggleProseOnly } = useDictionaryStore();
   const [showModal, setShowModal] = useState(false);
   const [editingEntry, setEditingEntry] = useState<DictionaryEntry | undefined>();
   const [hoveredId, setHoveredId] = useState<string | null>(null);
@@ -187,6 +188,14 @@ export function DictionaryPage() {
     loadDictionary();
   }, [loadDictionary]);
 
+  // dictionary.json edited outside the app
+  useEffect(() => {
+    const unlisten = listen<DictionaryEntry[]>('dictionary-changed', (event) => {
+      useDictionaryStore.setState({ entries: event.payload });
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
   const handleAdd = () => {
     setEditingEntry(undefined);
     setShowModal(true);
@@ -227,6 +236,14 @@ export function DictionaryPage() {
     }
   };
 
+  const handleToggleProseOnly = async (id: string) => {
+    try {
+      await toggleProseOnly(id);
+    } catch (err) {
+      console.error('Failed to toggle prose-only:', err);
+    }
+  };
+
   return (
     <div className="h-full overflow-y-auto">
       <div className="max-w-3xl mx-auto px-8 py-8">
@@ -350,7 +367,20 @@ export function DictionaryPage() {
                           }`}>
                             {isVocabulary ? 'word' : 'replace'}
                           </span>
+                          {entry.prose_only && (
+                            <span className="inline-flex items-center px-1.5 py-0.5 rounded text-[10px] font-medium uppercase tracking-wider bg-stone-100 dark:bg-stone-700/60 text-stone-400 dark:text-stone-500">
+                              prose only
+                            </span>
+                          )}
                         </div>
+                        {!isVocabulary && (
+                          <div className="mt-1 text-xs text-stone-400 dark:text-stone-500">
+                            {entry.hit_count
+                              ? `Used ${entry.hit_count} ${entry.hit_count === 1 ? 'time' : 'times'}` +
+                                (entry.last_used ? `, last on ${new Date(entry.last_used).toLocaleDateString()}` : '')
+                              : 'Never used'}
+                          </div>
+                        )}
                       </td>
                       <td className="px-4 py-3.5 text-center">
                         <button
@@ -378,6 +408,13 @@ export function DictionaryPage() {
                             ${hoveredId === entry.id ? 'opacity-100' : 'opacity-0'}
                           `}
                         >
+                          <button
+                            onClick={() => handleToggleProseOnly(entry.id)}
+                            className="px-2 py-1.5 bg-white dark:bg-stone-700 rounded-lg text-xs font-mono text-stone-400 dark:text-stone-400 hover:text-stone-600 dark:hover:text-stone-200 shadow-sm transition-all duration-200"
+                            title={entry.prose_only ? 'Also apply in code mode' : 'Skip in code mode'}
+                          >
+                            {'</>'}
+                          </button>
                           <button
                             onClick={() => handleEdit(entry)}
                             className="p-2 bg-white dark:bg-stone-700 rounded-lg text-stone-400 dark:text-stone-400 hover:text-stone-600 dark:hover:text-stone-200 shadow-sm transition-all duration-200"
@@ -3,6 +3,8 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { useStore, UserSettings } from '../../lib/store';
 import { useTheme } from '../../lib/theme';
+import type { DownloadProgressEvent } from '../../types';
+import { errorMessage, getAudioDevices, validateHotkey, type InputDevice } from '../../lib/tauri';
 
 // Icons
 const SunIcon = () => (
@@ -159,6 +161,11 @@ interface ModelInfo {
   coreml_size_mb: number;
 }
 
+interface ModelUpdate {
+  id: string;
+  update_available: boolean;
+}
+
 interface CoremlStatus {
   compiled: boolean;
   supported: boolean;
@@ -175,6 +182,35 @@ interface VoxtralStatus {
   metal: boolean;
   model_downloaded: boolean;
   model_loaded: boolean;
+  revision: string | null;
+}
+
+interface EngineCapabilities {
+  engine: string;
+  available: boolean;
+  streaming: boolean;
+  translation: boolean;
+  timestamps: boolean;
+  language_selection: boolean; // false = always auto-detects
+  languages: string[];
+  gpu: boolean;
+  model_sizes: string[];
+}
+
+interface LanguageInfo {
+  code: string;
+  name: string;
+}
+
+// Returned by get_app_status; only the parts this page reads
+interface AppStatus {
+  version: string;
+  engines: EngineCapabilities[];
+  coreml: CoremlStatus;
+  metal: MetalStatus;
+  voxtral: VoxtralStatus;
+  models: ModelInfo[];
+  voxtral_models: ModelInfo[];
 }
 
 // Section Component
@@ -478,7 +514,7 @@ function parseHotkey(hotkey: string): ParsedHotkey {
   if (!hotkey) return { modifiers: [], key: null };
 
   const parts = hotkey.split('+').map(p => p.trim());
-  const modifierNames = ['Ctrl', 'Alt', 'Shift', 'Meta', 'Cmd', 'Control', 'Option'];
+  const modifierNames = ['Ctrl', 'Alt', 'Shift', 'Meta', 'Cmd', 'Control', 'Option', 'Win', 'Super'];
   const modifiers: string[] = [];
   let key: string | null = null;
 
@@ -488,7 +524,7 @@ function parseHotkey(hotkey: string): ParsedHotkey {
       let normalizedMod = part;
       if (part.toLowerCase() === 'control') normalizedMod = 'Ctrl';
       if (part.toLowerCase() === 'option') normalizedMod = 'Alt';
-      if (part.toLowerCase() === 'cmd') normalizedMod = 'Meta';
+      if (['cmd', 'win', 'super'].includes(part.toLowerCase())) normalizedMod = 'Meta';
       modifiers.push(normalizedMod.charAt(0).toUpperCase() + normalizedMod.slice(1).toLowerCase());
     } else {
       key = part;
@@ -503,6 +539,7 @@ function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps) {
   const [currentModifiers, setCurrentModifiers] = useState<Set<string>>(new Set());
   const [currentKey, setCurrentKey] = useState<string | null>(null);
   const [showSuccess, setShowSuccess] = useState(false);
+  const [validationError, setValidationError] = useState<string | null>(null);
   const containerRef = useRef<HTMLDivElement>(null);
 
   const parsed = parseHotkey(value);
@@ -523,8 +560,26 @@ function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps) {
       'CapsLock': 'Caps',
       'Control': 'Ctrl',
       'Meta': '⌘',
+      'NumpadAdd': 'Num +',
+      'NumpadSubtract': 'Num −',
+      'NumpadMultiply': 'Num ×',
+      'NumpadDivide': 'Num ÷',
+      'NumpadDecimal': 'Num .',
+      'NumpadEnter': 'Num ↵',
+      'NumpadEqual': 'Num =',
+      'MediaPlayPause': '⏯',
+      'MediaPlay': '▶',
+      'MediaPause': '⏸',
+      'MediaStop': '⏹',
+      'MediaTrackNext': '⏭',
+      'MediaTrackPrevious': '⏮',
+      'AudioVolumeUp': 'Vol +',
+      'AudioVolumeDown': 'Vol −',
+      'AudioVolumeMute': 'Mute',
     };
-    return keyMap[key] || key;
+    if (keyMap[key]) return keyMap[key];
+    const numpadDigit = key.match(/^Numpad(\d)$/);
+    return numpadDigit ? `Num ${numpadDigit[1]}` : key;
   };
 
   // Get platform-specific modifier symbol
@@ -557,17 +612,24 @@ function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps) {
       // Check if this is a non-modifier key
       const modifierKeys = ['Control', 'Alt', 'Shift', 'Meta'];
       if (!modifierKeys.includes(e.key)) {
-        const keyName = e.key.length === 1 ? e.key.toUpperCase() : e.key;
+        // The physical key, as global shortcuts see it, whatever the layout types
+        const keyName = e.code.startsWith('Key') ? e.code.slice(3) : e.code.startsWith('Digit') ? e.code.slice(5) : e.code;
         setCurrentKey(keyName);
 
         // Build the final hotkey string
         const modArray = Array.from(newModifiers);
         const hotkeyString = [...modArray, keyName].join('+');
-
-        onChange(hotkeyString);
         setIsRecording(false);
-        setShowSuccess(true);
-        setTimeout(() => setShowSuccess(false), 1500);
+
+        // Reserved by the OS or in the way of typing: keep the old shortcut
+        validateHotkey(hotkeyString)
+          .then((normalized) => {
+            setValidationError(null);
+            onChange(normalized);
+            setShowSuccess(true);
+            setTimeout(() => setShowSuccess(false), 1500);
+          })
+          .catch((error) => setValidationError(errorMessage(error)));
       }
     };
 
@@ -609,6 +671,7 @@ function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps) {
   }, [isRecording]);
 
   const startRecording = () => {
+    setValidationError(null);
     setIsRecording(true);
     setCurrentModifiers(new Set());
     setCurrentKey(null);
@@ -746,6 +809,8 @@ function HotkeyRecorder({ value, onChange }: HotkeyRecorderProps) {
         </div>
       </div>
 
+      {validationError && <p className="text-xs text-red-500">{validationError}</p>}
+
       {/* Action Buttons */}
       <div className="flex gap-2">
         {isRecording ? (
@@ -881,6 +946,78 @@ function Toggle({ label, description, checked, onChange }: ToggleProps) {
   );
 }
 
+// Microphone Test Component
+interface MonitorLevel {
+  level: number;
+  peak: number; // 1.0 = clipping
+}
+
+function MicrophoneTest({ inputDevice }: { inputDevice: string | null }) {
+  const [device, setDevice] = useState<string | null>(null);
+  const [level, setLevel] = useState<MonitorLevel>({ level: 0, peak: 0 });
+  const [error, setError] = useState<string | null>(null);
+  const testing = device !== null;
+
+  useEffect(() => {
+    if (!testing) return;
+    const unlisten = listen<MonitorLevel>('monitor-level', (event) => setLevel(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [testing]);
+
+  // Never leave the microphone open after leaving the settings page
+  useEffect(() => {
+    return () => {
+      invoke('stop_audio_monitor').catch(() => {});
+    };
+  }, []);
+
+  async function toggleTest() {
+    if (testing) {
+      await invoke('stop_audio_monitor');
+      setDevice(null);
+      setLevel({ level: 0, peak: 0 });
+      return;
+    }
+    try {
+      setError(null);
+      setDevice(await invoke<string>('start_audio_monitor', { device: inputDevice }));
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  return (
+    <div>
+      <div className="flex items-center justify-between">
+        <div>
+          <span className="text-sm font-medium text-stone-700 dark:text-stone-300">Microphone check</span>
+          <p className="text-xs text-stone-500 dark:text-stone-400 mt-0.5">
+            {testing ? `Listening on ${device}` : 'Speak to confirm your microphone is picked up (nothing is recorded)'}
+          </p>
+        </div>
+        <button
+          type="button"
+          onClick={toggleTest}
+          className="px-3 py-1.5 rounded-lg text-xs font-medium border border-stone-200 dark:border-stone-700 text-stone-700 dark:text-stone-300 hover:border-stone-300 dark:hover:border-stone-600 transition-colors"
+        >
+          {testing ? 'Stop' : 'Test'}
+        </button>
+      </div>
+      {testing && (
+        <div className="mt-3 h-2 rounded-full bg-stone-200 dark:bg-stone-700 overflow-hidden">
+          <div
+            className={`h-full transition-all duration-75 ${level.peak >= 0.99 ? 'bg-red-500' : 'bg-amber-500 dark:bg-amber-400'}`}
+            style={{ width: `${Math.round(level.level * 100)}%` }}
+          />
+        </div>
+      )}
+      {error && <p className="text-xs text-red-500 mt-2">{error}</p>}
+    </div>
+  );
+}
+
 // Input Component
 interface InputProps {
   label: string;
@@ -959,29 +1096,51 @@ export function SettingsPage() {
   const { settings, updateSettings } = useStore();
   const [models, setModels] = useState<ModelInfo[]>([]);
   const [downloading, setDownloading] = useState<string | null>(null);
+  // Downloaded models with a newer revision available
+  const [modelUpdates, setModelUpdates] = useState<string[]>([]);
   const [coremlStatus, setCoremlStatus] = useState<CoremlStatus | null>(null);
   const [metalStatus, setMetalStatus] = useState<MetalStatus | null>(null);
   const [downloadingCoreml, setDownloadingCoreml] = useState<string | null>(null);
   const [downloadProgress, setDownloadProgress] = useState<Record<string, number>>({});
+  const [downloadDetails, setDownloadDetails] = useState<Record<string, DownloadProgressEvent>>({});
   const [deleting, setDeleting] = useState<string | null>(null);
   const [voxtralStatus, setVoxtralStatus] = useState<VoxtralStatus | null>(null);
   const [voxtralModels, setVoxtralModels] = useState<ModelInfo[]>([]);
   const [downloadingVoxtral, setDownloadingVoxtral] = useState(false);
+  const [engineCapabilities, setEngineCapabilities] = useState<EngineCapabilities[]>([]);
+  const [supportedLanguages, setSupportedLanguages] = useState<LanguageInfo[]>([]);
+  const [inputDevices, setInputDevices] = useState<InputDevice[]>([]);
+
+  // Languages of the selected model (English only for the .en models)
+  useEffect(() => {
+    if (!settings) return;
+    const model = settings.transcription.engine === 'voxtral'
+      ? 'voxtral'
+      : settings.transcription.model_size || 'small';
+    invoke<LanguageInfo[]>('get_supported_languages', { model })
+      .then(setSupportedLanguages)
+      .catch((error) => console.error('Failed to load supported languages:', error));
+  }, [settings?.transcription.engine, settings?.transcription.model_size]);
 
   useEffect(() => {
-    loadModels();
-    loadCoremlStatus();
-    loadMetalStatus();
-    loadVoxtralStatus();
+    loadAppStatus();
+    // Network and possibly hashing; doesn't hold up the page
+    invoke<ModelUpdate[]>('check_model_updates')
+      .then((updates) => setModelUpdates(updates.filter((u) => u.update_available).map((u) => u.id)))
+      .catch((error) => console.error('Failed to check for model updates:', error));
+    getAudioDevices()
+      .then(setInputDevices)
+      .catch((error) => console.error('Failed to list input devices:', error));
   }, []);
 
   // Listen for download progress events from the backend
   useEffect(() => {
-    const unlisten = listen<{ model_type: string; model_id: string; percent: number }>(
+    const unlisten = listen<DownloadProgressEvent>(
       'download-progress',
       (event) => {
         const key = `${event.payload.model_type}:${event.payload.model_id}`;
         setDownloadProgress((prev) => ({ ...prev, [key]: event.payload.percent }));
+        setDownloadDetails((prev) => ({ ...prev, [key]: event.payload }));
       }
     );
     return () => {
@@ -989,21 +1148,19 @@ export function SettingsPage() {
     };
   }, []);
 
-  async function loadCoremlStatus() {
+  // Everything the page starts from in one call; the loaders below refresh
+  // single parts after downloads and deletions
+  async function loadAppStatus() {
     try {
-      const status = await invoke<CoremlStatus>('get_coreml_status');
-      setCoremlStatus(status);
+      const status = await invoke<AppStatus>('get_app_status');
+      setModels(status.models);
+      setCoremlStatus(status.coreml);
+      setMetalStatus(status.metal);
+      setVoxtralStatus(status.voxtral);
+      setVoxtralModels(status.voxtral_models);
+      setEngineCapabilities(status.engines);
     } catch (error) {
-      console.error('Failed to load CoreML status:', error);
-    }
-  }
-
-  async function loadMetalStatus() {
-    try {
-      const status = await invoke<MetalStatus>('get_metal_status');
-      setMetalStatus(status);
-    } catch (error) {
-      console.error('Failed to load Metal status:', error);
+      console.error('Failed to load app status:', error);
     }
   }
 
@@ -1075,6 +1232,23 @@ export function SettingsPage() {
     });
   }
 
+  async function updateModel(modelId: string) {
+    setDownloading(modelId);
+    setDownloadProgress((prev) => ({ ...prev, [`ggml:${modelId}`]: 0 }));
+    try {
+      await invoke('update_model', { id: modelId });
+      setModelUpdates((prev) => prev.filter((id) => id !== modelId));
+    } catch (error) {
+      console.error('Failed to update model:', error);
+    }
+    setDownloading(null);
+    setDownloadProgress((prev) => {
+      const next = { ...prev };
+      delete next[`ggml:${modelId}`];
+      return next;
+    });
+  }
+
   async function downloadCoremlModel(modelId: string) {
     setDownloadingCoreml(modelId);
     setDownloadProgress((prev) => ({ ...prev, [`coreml:${modelId}`]: 0 }));
@@ -1125,6 +1299,21 @@ export function SettingsPage() {
     return `${mb}MB`;
   }
 
+  /** "42% · 12.3 MB/s · 8m left" for an in-flight download */
+  function formatDownload(key: string): string {
+    const percent = `${Math.round(downloadProgress[key])}%`;
+    const details = downloadDetails[key];
+    if (!details || details.bytes_per_sec <= 0) return percent;
+    const speed = `${(details.bytes_per_sec / 1_000_000).toFixed(1)} MB/s`;
+    if (details.eta_secs == null) return `${percent} · ${speed}`;
+    const eta = details.eta_secs >= 3600
+      ? `${Math.floor(details.eta_secs / 3600)}h ${Math.floor((details.eta_secs % 3600) / 60)}m`
+      : details.eta_secs >= 60
+        ? `${Math.floor(details.eta_secs / 60)}m`
+        : `${details.eta_secs}s`;
+    return `${percent} · ${speed} · ${eta} left`;
+  }
+
   function handleChange<K extends keyof UserSettings>(
     section: K,
     key: keyof UserSettings[K],
@@ -1157,6 +1346,10 @@ export function SettingsPage() {
     );
   }
 
+  const activeCapabilities = engineCapabilities.find(
+    (c) => c.engine === (settings.transcription.engine || 'whisper')
+  );
+
   // Check if selected model supports CoreML (for info banner in CoreML section)
   const selectedModelInfo = models.find(m => m.id === settings.transcription.model_size);
   const selectedModelLacksCoreml = selectedModelInfo && selectedModelInfo.coreml_size_mb === 0;
@@ -1216,6 +1409,27 @@ export function SettingsPage() {
                 className="w-full h-1.5 rounded-full appearance-none cursor-pointer bg-stone-200 dark:bg-stone-700 accent-amber-500 dark:accent-amber-400"
               />
             </div>
+            <Dropdown
+              label="Update rate"
+              value={String(settings.widget?.event_rate_hz ?? 40)}
+              onChange={(value) => handleChange('widget', 'event_rate_hz', parseInt(value))}
+              options={[
+                { value: '15', label: '15 Hz', description: 'For slower machines' },
+                { value: '30', label: '30 Hz', description: 'Balanced' },
+                { value: '40', label: '40 Hz', description: 'Default' },
+                { value: '60', label: '60 Hz', description: 'Smoothest level meter' },
+              ]}
+            />
+            <Dropdown
+              label="Recording indicator on all displays"
+              value={settings.widget?.recording_indicator ?? 'off'}
+              onChange={(value) => handleChange('widget', 'recording_indicator', value)}
+              options={[
+                { value: 'off', label: 'Off', description: 'Only the widget shows recording' },
+                { value: 'strip', label: 'Strip', description: 'Thin red bar along the top of each display' },
+                { value: 'icon', label: 'Icon', description: 'Red dot in the top-right corner of each display' },
+              ]}
+            />
           </SettingsSection>
 
           {/* Transcription */}
@@ -1268,25 +1482,50 @@ export function SettingsPage() {
               </div>
             )}
 
+            {(activeCapabilities?.language_selection ?? true) && (
+              <Dropdown
+                label="Language"
+                value={settings.transcription.language || 'auto'}
+                onChange={(value) => handleChange('transcription', 'language', value)}
+                options={[
+                  { value: 'auto', label: 'Auto-detect', icon: <GlobeIcon />, description: 'Automatically detect language' },
+                  { value: 'en', label: 'English', description: 'United States, UK, Australia' },
+                  { value: 'es', label: 'Spanish', description: 'Spain, Latin America' },
+                  { value: 'fr', label: 'French', description: 'France, Canada, Belgium' },
+                  { value: 'de', label: 'German', description: 'Germany, Austria, Switzerland' },
+                  { value: 'zh', label: 'Chinese', description: 'Simplified & Traditional' },
+                  { value: 'ja', label: 'Japanese', description: 'Japan' },
+                  { value: 'ko', label: 'Korean', description: 'South Korea' },
+                  { value: 'pt', label: 'Portuguese', description: 'Portugal, Brazil' },
+                  { value: 'it', label: 'Italian', description: 'Italy' },
+                  { value: 'ru', label: 'Russian', description: 'Russia' },
+                ].filter((option) =>
+                  option.value === 'auto' || supportedLanguages.length === 0 || supportedLanguages.some((l) => l.code === option.value)
+                ).concat(
+                  supportedLanguages
+                    .filter((l) => !['en', 'es', 'fr', 'de', 'zh', 'ja', 'ko', 'pt', 'it', 'ru'].includes(l.code))
+                    .map((l) => ({ value: l.code, label: l.name, description: l.code }))
+                )}
+            />
+            )}
+
             <Dropdown
-              label="Language"
-              value={settings.transcription.language || 'auto'}
-              onChange={(value) => handleChange('transcription', 'language', value)}
-              options={[
-                { value: 'auto', label: 'Auto-detect', icon: <GlobeIcon />, description: 'Automatically detect language' },
-                { value: 'en', label: 'English', description: 'United States, UK, Australia' },
-                { value: 'es', label: 'Spanish', description: 'Spain, Latin America' },
-                { value: 'fr', label: 'French', description: 'France, Canada, Belgium' },
-                { value: 'de', label: 'German', description: 'Germany, Austria, Switzerland' },
-                { value: 'zh', label: 'Chinese', description: 'Simplified & Traditional' },
-                { value: 'ja', label: 'Japanese', description: 'Japan' },
-                { value: 'ko', label: 'Korean', description: 'South Korea' },
-                { value: 'pt', label: 'Portuguese', description: 'Portugal, Brazil' },
-                { value: 'it', label: 'Italian', description: 'Italy' },
-                { value: 'ru', label: 'Russian', description: 'Russia' },
-              ]}
+              label="Microphone"
+              value={settings.recording?.selected_input_device ?? ''}
+              onChange={(value) => handleChange('recording', 'selected_input_device', value || undefined)}
+              options={[{ value: '', label: 'System default', description: 'Follows the system input device' }].concat(
+                inputDevices.map((d) => ({
+                  value: d.name,
+                  label: d.name,
+                  description: [d.is_default ? 'Default' : null, d.sample_rate ? `${d.sample_rate / 1000} kHz` : null]
+                    .filter(Boolean)
+                    .join(' · '),
+                }))
+              )}
             />
 
+            <MicrophoneTest inputDevice={settings.recording?.selected_input_device ?? null} />
+
             {/* Voxtral Model Management */}
             {(settings.transcription.engine === 'voxtral') && voxtralStatus?.compiled && (
               <div>
@@ -1316,6 +1555,21 @@ export function SettingsPage() {
                       <div className="flex items-center gap-2">
                         {model.downloaded ? (
                           <>
+                            {downloading === model.id ? (
+                              <span className="text-xs font-medium text-amber-600 dark:text-amber-400">
+                                {ggmlProgress != null ? formatDownload(`ggml:${model.id}`) : 'Updating...'}
+                              </span>
+                            ) : modelUpdates.includes(model.id) && (
+                              <button
+                                onClick={() => updateModel(model.id)}
+                                disabled={downloading !== null}
+                                className="flex items-center gap-1.5 text-xs font-medium text-amber-600 dark:text-amber-400 hover:text-amber-700 dark:hover:text-amber-300 bg-amber-100 dark:bg-amber-900/30 hover:bg-amber-200 dark:hover:bg-amber-900/50 px-3 py-1.5 rounded-lg transition-colors"
+                                title="A newer revision of this model is available"
+                              >
+                                <DownloadIcon />
+                                Update
+                              </button>
+                            )}
                             <span className="flex items-center gap-1 text-xs font-medium text-green-600 dark:text-green-400 bg-green-100 dark:bg-green-900/30 px-2 py-1 rounded-lg">
                               <CheckIcon />
                               Ready
@@ -1335,7 +1589,7 @@ export function SettingsPage() {
                               <circle className="opacity-25" cx="12" cy="12" r="10" stroke="currentColor" strokeWidth="4" />
                               <path className="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4zm2 5.291A7.962 7.962 0 014 12H0c0 3.042 1.135 5.824 3 7.938l3-2.647z" />
                             </svg>
-                            {voxProgress != null ? `${Math.round(voxProgress)}%` : 'Downloading...'}
+                            {voxProgress != null ? formatDownload('voxtral:voxtral-mini-4b') : 'Downloading...'}
                           </span>
                         ) : (
                           <button
@@ -1407,7 +1661,18 @@ export function SettingsPage() {
                           type="radio"
                           name="model"
                           checked={isSelected}
-                          onChange={() => handleChange('transcription', 'model_size', model.id)}
+                          onChange={() => {
+                            // English-only models reject any other language
+                            const language = settings.transcription.language;
+                            if (model.id.endsWith('.en') && language && language !== 'auto' && language !== 'en') {
+                              updateSettings({
+                                ...settings,
+                                transcription: { ...settings.transcription, model_size: model.id, language: 'auto' },
+                              });
+                            } else {
+                              handleChange('transcription', 'model_size', model.id);
+                            }
+                          }}
                           disabled={!model.downloaded}
                           className="w-4 h-4 text-amber-500 focus:ring-amber-500/20 border-stone-300 dark:border-stone-600"
                         />
@@ -1428,6 +1693,14 @@ export function SettingsPage() {
                               Distilled
                             </span>
                           )}
+                          {settings.transcription.model_recommendation?.model_size === model.id && (
+                            <span
+                              className="text-[10px] font-medium px-1.5 py-0.5 rounded bg-amber-100 dark:bg-amber-900/30 text-amber-700 dark:text-amber-400 ml-2"
+                              title={settings.transcription.model_recommendation.rationale}
+                            >
+                              Recommended
+                            </span>
+                          )}
                           {(model.id.includes('q5') || model.id.includes('q8')) && (
                             <span className="text-[10px] font-medium px-1.5 py-0.5 rounded bg-purple-100 dark:bg-purple-900/30 text-purple-700 dark:text-purple-400 ml-2">
                               Quantized
@@ -1473,7 +1746,7 @@ export function SettingsPage() {
                               <circle className="opacity-25" cx="12" cy="12" r="10" stroke="currentColor" strokeWidth="4" />
                               <path className="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4zm2 5.291A7.962 7.962 0 014 12H0c0 3.042 1.135 5.824 3 7.938l3-2.647z" />
                             </svg>
-                            {ggmlProgress != null ? `${ggmlProgress}%` : 'Downloading...'}
+                            {ggmlProgress != null ? formatDownload(`ggml:${model.id}`) : 'Downloading...'}
                           </span>
                         ) : (
                           <button
@@ -1621,7 +1894,7 @@ export function SettingsPage() {
                                 {coremlProgress != null
                                   ? coremlProgress >= 99
                                     ? 'Extracting...'
-                                    : `${coremlProgress}%`
+                                    : formatDownload(`coreml:${model.id}`)
                                   : 'Downloading...'
                                 }
                               </span>
@@ -1679,11 +1952,76 @@ export function SettingsPage() {
             <div className="pt-4 border-t border-stone-100 dark:border-stone-800">
               <CardSelect
                 label="Activation Mode"
-                value={settings.hotkey.mode || 'hold'}
+                value={settings.hotkey.mode || 'toggle'}
                 onChange={(value) => handleChange('hotkey', 'mode', value)}
                 options={[
                   { value: 'hold', label: 'Hold to Talk', icon: <HoldIcon />, description: 'Press and hold key while speaking' },
                   { value: 'toggle', label: 'Toggle On/Off', icon: <ToggleIcon />, description: 'Press once to start, again to stop' },
+                  { value: 'hybrid', label: 'Tap or Hold', icon: <HoldIcon />, description: 'Tap to toggle, or hold while speaking' },
+                ]}
+              />
+
+              {settings.hotkey.mode === 'hybrid' && (
+                <div className="mt-4">
+                  <div className="flex items-center justify-between mb-2">
+                    <div>
+                      <span className="text-sm font-medium text-stone-700 dark:text-stone-300">Long Press</span>
+                      <p className="text-xs text-stone-500 dark:text-stone-400">
+                        Held at least this long, releasing the key stops recording
+                      </p>
+                    </div>
+                    <span className="text-sm font-medium text-stone-600 dark:text-stone-400 tabular-nums">
+                      {settings.hotkey.long_press_ms ?? 600}ms
+                    </span>
+                  </div>
+                  <input
+                    type="range"
+                    min={300}
+                    max={1500}
+                    step={50}
+                    value={settings.hotkey.long_press_ms ?? 600}
+                    onChange={(e) => handleChange('hotkey', 'long_press_ms', parseInt(e.target.value))}
+                    className="w-full h-2 bg-stone-200 dark:bg-stone-700 rounded-lg appearance-none cursor-pointer accent-amber-500"
+                  />
+                </div>
+              )}
+            </div>
+
+            <div className="pt-4 border-t border-stone-100 dark:border-stone-800">
+              <Dropdown
+                label="Code mode key"
+                value={settings.hotkey.code_key || ''}
+                onChange={(value) => handleChange('hotkey', 'code_key', value || undefined)}
+                options={[
+                  { value: '', label: 'None', description: 'Use code mode apps only' },
+                  ...['F1', 'F2', 'F3', 'F4', 'F5', 'F6', 'F7', 'F8', 'F9', 'F10', 'F11', 'F12']
+                    .filter((key) => key !== (settings.hotkey.key || 'F6'))
+                    .map((key) => ({ value: key, label: key, description: 'Dictate code: spoken symbols, no auto-capitalize' })),
+                ]}
+              />
+            </div>
+
+            <div className="pt-4 border-t border-stone-100 dark:border-stone-800 space-y-4">
+              <Dropdown
+                label="Accuracy boost key"
+                value={settings.hotkey.boost_key || ''}
+                onChange={(value) => handleChange('hotkey', 'boost_key', value || undefined)}
+                options={[
+                  { value: '', label: 'None', description: 'No accuracy boost hotkey' },
+                  ...['F1', 'F2', 'F3', 'F4', 'F5', 'F6', 'F7', 'F8', 'F9', 'F10', 'F11', 'F12']
+                    .filter((key) => key !== (settings.hotkey.key || 'F6') && key !== settings.hotkey.code_key)
+                    .map((key) => ({ value: key, label: key, description: 'Transcribe the next dictation with the boost model' })),
+                ]}
+              />
+              <Dropdown
+                label="Accuracy boost model"
+                value={settings.transcription.boost_model || ''}
+                onChange={(value) => handleChange('transcription', 'boost_model', value || undefined)}
+                options={[
+                  { value: '', label: 'None', description: 'Boost is off' },
+                  ...models
+                    .filter((model) => model.downloaded && model.id !== settings.transcription.model_size)
+                    .map((model) => ({ value: model.id, label: model.name, description: 'Used for one dictation, then the selected model again' })),
                 ]}
               />
             </div>
@@ -1714,6 +2052,66 @@ export function SettingsPage() {
               checked={settings.output.auto_capitalize ?? true}
               onChange={(checked) => handleChange('output', 'auto_capitalize', checked)}
             />
+
+            <Toggle
+              label="Insert streamed text first"
+              description="Insert what was transcribed while you spoke as soon as you stop, then the rest"
+              checked={settings.output.tail_first ?? false}
+              onChange={(checked) => handleChange('output', 'tail_first', checked)}
+            />
+
+            {/* 1,234.56 in en-US, 1.234,56 in de-DE, 1 234,56 in fr-FR */}
+            <Toggle
+              label="Localize numbers"
+              description="Use your locale's decimal and thousands separators in dictated numbers"
+              checked={settings.output.localize_numbers ?? false}
+              onChange={(checked) => handleChange('output', 'localize_numbers', checked)}
+            />
+            {settings.output.localize_numbers && (
+              <Input
+                label="Number locale"
+                value={settings.output.number_locale ?? ''}
+                onChange={(value) => handleChange('output', 'number_locale', value || undefined)}
+                placeholder="System default (e.g. de-DE)"
+              />
+            )}
+
+            {/* Run in this order; steps left out are skipped */}
+            <Input
+              label="Post-processing steps"
+              value={(settings.output.post_process_steps ?? []).join(', ')}
+              onChange={(value) =>
+                handleChange(
+                  'output',
+                  'post_process_steps',
+                  value ? value.split(',').map((step) => step.trim()) : undefined
+                )
+              }
+              placeholder="punctuation, casing, addresses, numbers, dictionary"
+            />
+
+            {/* Say "open paren", "underscore", "camel case foo bar" ... in these apps */}
+            <Input
+              label="Code mode apps"
+              value={(settings.output.code_apps ?? []).join(', ')}
+              onChange={(value) =>
+                handleChange('output', 'code_apps', value ? value.split(',').map((app) => app.trim()) : [])
+              }
+              placeholder={isMac ? 'com.microsoft.VSCode, com.apple.Terminal' : 'Code.exe, WindowsTerminal.exe'}
+            />
+
+            {/* End a dictation with "all caps", "lowercase that" or "title case that" to override once */}
+            <Dropdown
+              label="Text casing"
+              value={settings.output.casing || 'sentence'}
+              onChange={(value) => handleChange('output', 'casing', value)}
+              options={[
+                { value: 'sentence', label: 'Sentence case', description: 'Follows auto-capitalize' },
+                { value: 'lowercase', label: 'lowercase', description: 'Terminals and code' },
+                { value: 'title', label: 'Title Case', description: 'Headlines and titles' },
+                { value: 'uppercase', label: 'UPPERCASE', description: 'Shouting, constants' },
+              ]}
+            />
           </SettingsSection>
 
           {/* AI Cleanup */}
@@ -1740,15 +2138,32 @@ export function SettingsPage() {
                     { value: 'anthropic', label: 'Anthropic', description: 'Claude Sonnet, Haiku' },
                     { value: 'openrouter', label: 'OpenRouter', description: 'Multiple providers' },
                     { value: 'ollama', label: 'Ollama', description: 'Local models (free)' },
+                    { value: 'custom', label: 'Custom', description: 'OpenAI-compatible endpoint' },
                   ]}
                 />
 
+                {settings.cleanup.provider === 'custom' && (
+                  <Input
+                    label="Endpoint"
+                    value={settings.cleanup.custom_endpoint ?? ''}
+                    onChange={(value) => handleChange('cleanup', 'custom_endpoint', value || undefined)}
+                    placeholder="http://localhost:8080/v1/chat/completions"
+                  />
+                )}
+
+                <Input
+                  label="Model"
+                  value={settings.cleanup.model ?? ''}
+                  onChange={(value) => handleChange('cleanup', 'model', value || undefined)}
+                  placeholder="Provider default"
+                />
+
                 <Input
                   label="API Key"
                   type="password"
                   value={settings.cleanup.api_key || ''}
                   onChange={(value) => handleChange('cleanup', 'api_key', value)}
-                  placeholder="Enter your API key"
+                  placeholder={settings.cleanup.api_key_ref ? 'Stored in keychain — type to replace' : 'Enter your API key'}
                 />
 
                 <div className="space-y-3 pt-2">
@@ -15,6 +15,9 @@ export const WAVEFORM_MAX_HEIGHT = 1.0;
 export const BAR_MIN_HEIGHT_PX = 4;
 export const BAR_HEIGHT_SCALE = 20;
 
+// ---- Live transcript ----
+export const PARTIAL_TEXT_MAX_CHARS = 48; // the end of longer text is shown
+
 // ---- Processing ----
 export const PROCESSING_DOT_COUNT = 8;
 export const PROCESSING_DOT_DELAY_STEP = 0.1;
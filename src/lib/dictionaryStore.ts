@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from './tauri';
 import type { DictionaryEntry } from '../types';
 
 interface DictionaryStore {
@@ -11,6 +12,8 @@ interface DictionaryStore {
   updateEntry: (id: string, phrase: string, replacement: string, enabled: boolean) => Promise<void>;
   removeEntry: (id: string) => Promise<void>;
   toggleEntry: (id: string) => Promise<void>;
+  toggleProseOnly: (id: string) => Promise<void>;
+  setEntryApps: (id: string, apps: string[]) => Promise<void>;
   refresh: () => Promise<void>;
 }
 
@@ -27,7 +30,7 @@ export const useDictionaryStore = create<DictionaryStore>((set, get) => ({
       set({ entries, isLoading: false });
     } catch (error) {
       console.error('Failed to load dictionary:', error);
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
     }
   },
 
@@ -84,6 +87,49 @@ export const useDictionaryStore = create<DictionaryStore>((set, get) => ({
     await get().updateEntry(id, entry.phrase, entry.replacement, !entry.enabled);
   },
 
+  toggleProseOnly: async (id: string) => {
+    const entry = get().entries.find((e) => e.id === id);
+    if (!entry) return;
+    const proseOnly = !entry.prose_only;
+    try {
+      await invoke('update_dictionary_entry', {
+        id,
+        phrase: entry.phrase,
+        replacement: entry.replacement,
+        enabled: entry.enabled,
+        proseOnly,
+      });
+      set((state) => ({
+        entries: state.entries.map((e) =>
+          e.id === id ? { ...e, prose_only: proseOnly, synced: false } : e
+        ),
+      }));
+    } catch (error) {
+      console.error('Failed to update dictionary entry:', error);
+      throw error;
+    }
+  },
+
+  setEntryApps: async (id: string, apps: string[]) => {
+    const entry = get().entries.find((e) => e.id === id);
+    if (!entry) return;
+    try {
+      const updated = await invoke<DictionaryEntry>('update_dictionary_entry', {
+        id,
+        phrase: entry.phrase,
+        replacement: entry.replacement,
+        enabled: entry.enabled,
+        apps,
+      });
+      set((state) => ({
+        entries: state.entries.map((e) => (e.id === id ? updated : e)),
+      }));
+    } catch (error) {
+      console.error('Failed to update dictionary entry:', error);
+      throw error;
+    }
+  },
+
   refresh: async () => {
     await get().loadDictionary();
   },
@@ -1,13 +1,17 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { TranscriptionEntry } from '../types';
+import { errorMessage } from './tauri';
+import type { HistoryFilters, HistoryPage, HistoryQuery, TranscriptionEntry } from '../types';
 
 interface HistoryStore {
   entries: TranscriptionEntry[];
-  totalCount: number;
+  totalCount: number; // entries matching the filters
   isLoading: boolean;
   hasMore: boolean;
   error: string | null;
+  filters: HistoryFilters;
+  cursor: string | null; // where the next page starts
+  setFilters: (filters: HistoryFilters) => Promise<void>;
   loadHistory: (reset?: boolean) => Promise<void>;
   loadMore: () => Promise<void>;
   deleteEntry: (id: string) => Promise<void>;
@@ -23,46 +27,51 @@ export const useHistoryStore = create<HistoryStore>((set, get) => ({
   isLoading: false,
   hasMore: true,
   error: null,
+  filters: {},
+  cursor: null,
+
+  setFilters: async (filters: HistoryFilters) => {
+    set({ filters });
+    await get().loadHistory(true);
+  },
 
   loadHistory: async (reset = true) => {
     if (get().isLoading) return;
     set({ isLoading: true, error: null });
     try {
-      const entries = await invoke<TranscriptionEntry[]>('get_history', {
-        limit: PAGE_SIZE,
-        offset: 0,
-      });
-      const totalCount = await invoke<number>('get_history_count');
+      const params: HistoryQuery = { ...get().filters, limit: PAGE_SIZE };
+      const page = await invoke<HistoryPage>('query_history', { params });
       set({
-        entries: reset ? entries : [...get().entries, ...entries],
-        totalCount,
+        entries: reset ? page.entries : [...get().entries, ...page.entries],
+        totalCount: page.total,
+        cursor: page.next_cursor,
         isLoading: false,
-        hasMore: entries.length >= PAGE_SIZE,
+        hasMore: page.next_cursor !== null,
       });
     } catch (error) {
       console.error('Failed to load history:', error);
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
     }
   },
 
   loadMore: async () => {
-    const { entries, isLoading, hasMore } = get();
+    const { entries, isLoading, hasMore, filters, cursor } = get();
     if (isLoading || !hasMore) return;
 
     set({ isLoading: true });
     try {
-      const newEntries = await invoke<TranscriptionEntry[]>('get_history', {
-        limit: PAGE_SIZE,
-        offset: entries.length,
-      });
+      const params: HistoryQuery = { ...filters, limit: PAGE_SIZE, cursor };
+      const page = await invoke<HistoryPage>('query_history', { params });
       set({
-        entries: [...entries, ...newEntries],
+        entries: [...entries, ...page.entries],
+        totalCount: page.total,
+        cursor: page.next_cursor,
         isLoading: false,
-        hasMore: newEntries.length >= PAGE_SIZE,
+        hasMore: page.next_cursor !== null,
       });
     } catch (error) {
       console.error('Failed to load more history:', error);
-      set({ isLoading: false, error: String(error) });
+      set({ isLoading: false, error: errorMessage(error) });
     }
   },
 
@@ -82,7 +91,7 @@ export const useHistoryStore = create<HistoryStore>((set, get) => ({
   clearAll: async () => {
     try {
       await invoke('clear_history');
-      set({ entries: [], totalCount: 0, hasMore: false });
+      set({ entries: [], totalCount: 0, hasMore: false, cursor: null });
     } catch (error) {
       console.error('Failed to clear history:', error);
       throw error;
@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from './tauri';
 import type { LocalStats } from '../types';
 
 interface StatsStore {
@@ -35,7 +36,7 @@ export const useStatsStore = create<StatsStore>((set, get) => ({
       set({
         stats: defaultStats,
         isLoading: false,
-        error: String(error)
+        error: errorMessage(error)
       });
     }
   },
@@ -6,35 +6,175 @@ export interface TranscriptionSettings {
   language?: string;
   model_size?: string;
   cloud_provider?: string;
+  cloud_timeout_secs?: number; // give up on a cloud transcript after this long (default 60)
   use_coreml?: boolean | null;
-  engine?: string;            // "whisper" | "voxtral" (default: "whisper")
+  coreml_models?: Record<string, 'auto' | 'on' | 'off'>; // per-model override of use_coreml
+  engine?: string;            // "whisper" | "voxtral" | "parakeet" | "apple" | "sapi" | "cloud" (default: "whisper")
   voxtral_delay_ms?: number;  // 80-2400, default 480
+  preload_policy?: 'always' | 'on-first-use' | 'on-hotkey-hover'; // default: "always"
+  boost_model?: string; // Whisper model for one boosted dictation (setAccuracyBoost)
+  voxtral_revision?: string;  // branch, tag or commit hash (default: "main")
+  parakeet_repo?: string;     // Hugging Face repo of the ONNX export
+  apple_on_device?: boolean;  // keep Apple Speech on the device (default: true)
+  sapi_recognizer?: string;   // Windows speech recognizer token id; unset follows `language`
+  verify_models_on_load?: boolean;
+  language_from_keyboard?: boolean | null; // use the active keyboard layout's language at recording start (default: false)
+  hallucination_guard?: HallucinationSettings;
+  model_recommendation?: ModelRecommendation | null; // set on first run; read-only
+}
+
+export interface ModelRecommendation {
+  model_size: string;
+  rationale: string; // e.g. "Apple Silicon with 16 GB: Large v3 Turbo (quantized) runs on the GPU ..."
+  system: {
+    memory_gb: number | null;
+    cpu_cores: number;
+    apple_silicon: boolean;
+  };
+}
+
+export interface HallucinationSettings {
+  filter_phrases?: boolean;       // default true
+  max_words_per_second?: number;  // default 6.0; 0 = off
+  max_repeats?: number;           // default 5; 0 = off
 }
 
 export interface CleanupSettings {
   enabled: boolean;
-  provider?: string;
-  model?: string;
-  custom_endpoint?: string;
-  api_key?: string;
+  provider?: 'openai' | 'anthropic' | 'openrouter' | 'ollama' | 'custom'; // default: openai
+  model?: string;           // default depends on the provider
+  custom_endpoint?: string; // OpenAI-compatible chat completions URL for 'custom'
+  api_key?: string;     // only sent when entering a key; stored in the OS keychain ("" removes it)
+  api_key_ref?: string; // set when a key is stored in the keychain
   remove_filler: boolean;
   add_punctuation: boolean;
   format_paragraphs: boolean;
+  timeout_ms?: number; // keep the engine's text after this long (default: 8000)
 }
 
 export interface HotkeySettings {
   key?: string;
-  mode?: string;
+  mode?: 'hold' | 'toggle' | 'hybrid'; // default: toggle; hybrid: tap toggles, long press holds
+  long_press_ms?: number; // hybrid: press held this long counts as holding (default: 600)
+  incognito_key?: string; // starts a dictation that is never saved
+  languages?: Record<string, string>; // fixed transcription language per hotkey, e.g. { F7: "de" }
+  code_key?: string;   // starts a dictation in code mode
+  paste_next_key?: string; // pastes the next queued utterance (insert_method "queue")
+  boost_key?: string; // the next dictation uses transcription.boost_model; again to cancel
 }
 
 export interface OutputSettings {
-  insert_method?: string;
+  insert_method?: string; // "auto" | "ax_api" | "type" | "paste" | "paste_restore" | "queue" (queue: each utterance waits for the paste-next key)
   auto_capitalize?: boolean;
+  learn_insert_method?: boolean; // auto mode remembers the working method per app (default true)
+  smart_spacing?: boolean;       // add spaces around inserted text where the field can be read (default true)
+  casing?: 'sentence' | 'lowercase' | 'title' | 'uppercase'; // unset follows auto_capitalize; spoken "all caps" / "lowercase that" overrides per dictation
+  code_apps?: string[]; // app ids (bundle id / executable name) that always use code mode
+  tail_first?: boolean; // insert the streamed text as soon as recording stops, then the rest (default false)
+  localize_numbers?: boolean; // rewrite number separators to number_locale's convention (default false)
+  number_locale?: string;      // e.g. "de-DE"; unset uses the OS locale
+  post_process_steps?: PostProcessStep[]; // in the order they run; unset uses the default steps
+}
+
+// Overrides `output` for one app (see UserSettings.app_profiles)
+export interface AppProfile {
+  insert_method?: string;  // "auto" | "ax_api" | "type" | "paste" | "paste_restore"; unset follows output.insert_method
+  chunk_delay_ms?: number; // pause between typed chunks on macOS (default 2)
+  trailing_space?: boolean; // add a space after the inserted text (default false)
 }
 
+export type PostProcessStep =
+  | 'punctuation'
+  | 'filler-removal'
+  | 'casing'
+  | 'addresses'
+  | 'numbers'
+  | 'profanity'
+  | 'dictionary'
+  | `plugin:${string}`; // an installed plugin, by manifest name
+
 export interface WidgetSettings {
   draggable: boolean;
   opacity: number; // 0.2 to 1.0
+  event_rate_hz?: number; // audio-level / transcription-partial events per second, 5-60 (default: 40)
+  recording_indicator?: 'off' | 'strip' | 'icon'; // shown on every display while recording (default: off)
+}
+
+export interface ResamplerBenchmark {
+  recommended: 'linear' | 'cubic' | 'sinc';
+  budget_us: number; // time the audio callback can spend per chunk
+  timings: { quality: 'linear' | 'cubic' | 'sinc'; chunk_us: number }[];
+  measured_at: string;
+}
+
+export interface RecordingSettings {
+  selected_input_device?: string; // device name to record from (default: system default)
+  min_duration_ms?: number; // shorter recordings are discarded (default: 0 = off)
+  hold_delay_ms?: number;   // hold mode: press must last this long before capture starts (default: 0)
+  preroll_ms?: number;      // audio kept from before the press via a standby stream (default: 0 = off)
+  standby: boolean;         // keep the input stream open between dictations for instant start
+  standby_idle_minutes?: number; // close the standby stream after this long idle (default: 10)
+  vad_backend?: 'auto' | 'silero' | 'webrtc' | 'energy'; // speech detection (default: auto = Silero, with built-in fallback for streaming only)
+  chunk_minutes?: number;   // long dictations: insert what's transcribed every N minutes while recording (default: off)
+  restart_on_stall?: boolean; // restart capture on fallback_device when the input stream stalls (default false)
+  fallback_device?: string;   // input device to restart on (default: system default)
+  resampler_quality?: 'auto' | 'linear' | 'cubic' | 'sinc'; // device rate to 16kHz (default: auto = benchmark's pick)
+  resampler_benchmark?: ResamplerBenchmark | null; // measured once per install; read-only
+}
+
+export interface HistorySettings {
+  session_idle_minutes?: number; // start a new dictation session after this long idle (default: 5)
+  retention_days?: number;       // compaction removes entries older than this (default: keep)
+  auto_compact?: boolean;        // compact on quit, at most weekly (default false)
+}
+
+export interface BackupSettings {
+  enabled?: boolean;        // back up settings, history, dictionary and stats on a schedule
+  folder?: string;          // where archives go (default: "backups" in the config directory)
+  interval_hours?: number;  // hours between backups (default 24)
+  keep?: number;            // archives kept; older ones are deleted (default 7)
+}
+
+export interface ProxySettings {
+  mode?: string;      // "system" | "none" | "manual" (default: "system")
+  host?: string;
+  port?: number;
+  username?: string; // the password is set with setProxyPassword and kept in the keychain
+  bypass: string[];
+}
+
+export interface DownloadSettings {
+  mirrors: string[];  // preset names ("huggingface", "hf-mirror"), URL templates, or base URLs
+  connections?: number; // parallel connections per large file, 1-8 (default: 4)
+  auto_download?: boolean; // download missing models at startup (default: false)
+  auto_download_max_mb?: number; // larger downloads still ask first (default: 1000)
+}
+
+export interface PunctuationSettings {
+  enabled: boolean;     // restore punctuation locally when the engine omits it
+  languages: string[];  // empty = all languages
+  model_repo?: string;  // HuggingFace repo with an ONNX export (default: oliverguhr/fullstop-punctuation-multilang-large)
+}
+
+export interface PrivacySettings {
+  incognito: boolean; // never save dictations to history or stats
+  allow_cloud?: boolean;   // allow audio/text to be sent to a third party (default: false)
+  confirm_cloud?: boolean; // with allow_cloud, ask before each recording that would
+  allowed_plugins?: string[]; // plugins (by name) the user allowed to run, sandboxed without network
+}
+
+export interface GoalSettings {
+  daily_word_goal?: number;
+  notifications: boolean;       // emit goal-reached / streak-at-risk events
+  streak_reminder_hour?: number; // 0-23 (default: 20)
+}
+
+export interface DebugSettings {
+  // diagnostic output per subsystem (default: all off; MENTASCRIBE_DEBUG also turns them on)
+  audio: boolean;
+  injection: boolean;
+  window: boolean;
+  engine: boolean;
 }
 
 export interface UserSettings {
@@ -42,7 +182,17 @@ export interface UserSettings {
   cleanup: CleanupSettings;
   hotkey: HotkeySettings;
   output: OutputSettings;
+  app_profiles?: Record<string, AppProfile>; // keyed by app id (bundle id / executable name)
   widget: WidgetSettings;
+  recording: RecordingSettings;
+  history: HistorySettings;
+  backup?: BackupSettings;
+  proxy: ProxySettings;
+  download: DownloadSettings;
+  goals: GoalSettings;
+  privacy: PrivacySettings;
+  punctuation: PunctuationSettings;
+  debug?: DebugSettings;
 }
 
 interface Store {
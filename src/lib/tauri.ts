@@ -1,4 +1,29 @@
 import { invoke } from '@tauri-apps/api/core';
+import type {
+  AppError,
+  AppHealth,
+  AppleSpeechStatus,
+  BackupInfo,
+  CompactionReport,
+  Correction,
+  ImportSummary,
+  RecentTranscription,
+  SapiStatus,
+  SearchMatch,
+  UsageHeatmap,
+} from '../types';
+
+/**
+ * Commands reject with an AppError; anything else (a JS error) is wrapped
+ */
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+export function errorMessage(error: unknown): string {
+  if (isAppError(error)) return error.message;
+  return error instanceof Error ? error.message : String(error);
+}
 
 export interface AuthToken {
   access_token: string;
@@ -19,11 +44,159 @@ export interface ModelInfo {
   downloaded: boolean;
 }
 
+export interface ModelUpdate {
+  id: string;
+  local_sha256: string | null;
+  latest_sha256: string | null;
+  update_available: boolean;
+}
+
+export interface InjectionMethodResult {
+  method: string;
+  duration_ms: number;
+  succeeded: boolean;
+  verified: boolean | null; // null where the field can't be read back
+  error: string | null;
+}
+
+export interface InjectionBenchmark {
+  app_id: string | null;
+  text: string;
+  results: InjectionMethodResult[];
+}
+
+export interface SafeModeStatus {
+  active: boolean;
+  reason: string | null;
+  settings_error: string | null; // why settings.json can't be loaded
+}
+
+export interface PanicReport {
+  thread: string;
+  message: string;
+  location: string | null; // file:line
+  at: string; // RFC 3339
+}
+
+export interface BuildInfo {
+  app_version: string;
+  target: string; // target triple
+  profile: string; // 'debug' | 'release'
+  rustc: string;
+  features: string[]; // cargo features compiled in
+  crates: { name: string; version: string }[];
+  whisper_system_info: string; // whisper.cpp's system info, verbatim
+  whisper_flags: string[]; // e.g. 'AVX2', 'NEON', 'METAL'
+}
+
+export interface InputDevice {
+  name: string;
+  is_default: boolean;
+  sample_rate: number | null; // default config; null if the device wouldn't report one
+  channels: number | null;
+}
+
+export interface PluginInfo {
+  name: string; // use as `plugin:<name>` in output.post_process_steps, once in privacy.allowed_plugins
+  description: string;
+  dir: string;
+  error: string | null; // why it can't be used, if it can't
+}
+
+export interface FileCheck {
+  file: string; // relative to the models directory
+  status: 'ok' | 'missing' | 'mismatch' | 'unreadable';
+}
+
+export interface RequiredModel {
+  model_type: 'ggml' | 'coreml' | 'vad' | 'voxtral' | 'parakeet';
+  id: string;
+  size_mb: number;
+  downloaded: boolean;
+}
+
+/**
+ * Whether the app started in safe mode (default settings, no hotkeys, no
+ * model preload) and why
+ */
+export async function getSafeModeStatus(): Promise<SafeModeStatus> {
+  return invoke('get_safe_mode_status');
+}
+
+/**
+ * Move settings.json aside and use defaults; resolves to the backup's path
+ */
+export async function resetSettings(): Promise<string | null> {
+  return invoke('reset_settings');
+}
+
+/**
+ * Restart the app, leaving safe mode
+ */
+export async function restartNormally(): Promise<void> {
+  return invoke('restart_normally');
+}
+
+/**
+ * Check a hotkey before saving it. Resolves to its canonical spelling
+ * ("Ctrl+Shift+D"); rejects with the reason it can't be used (reserved by
+ * the OS, or a typing key without a modifier)
+ */
+export async function validateHotkey(key: string): Promise<string> {
+  return invoke('validate_hotkey', { key });
+}
+
+/** Engine readiness, latency, streak, words today, pending sync and storage for the dashboard home */
+export async function getAppHealth(): Promise<AppHealth> {
+  return invoke('get_app_health');
+}
+
+/** Transcribe the WAV files copied to the clipboard (not copied audio data) to the clipboard; returns how many were queued */
+export async function transcribeClipboard(): Promise<number> {
+  return invoke('transcribe_clipboard');
+}
+
+/** Text-transform plugins found in the plugins directory */
+export async function getPlugins(): Promise<PluginInfo[]> {
+  return invoke('get_plugins');
+}
+
+/** Scan the plugins directory again after installing or editing a plugin */
+export async function reloadPlugins(): Promise<PluginInfo[]> {
+  return invoke('reload_plugins');
+}
+
+/** Input devices to record from, for `recording.selected_input_device` */
+export async function getAudioDevices(): Promise<InputDevice[]> {
+  return invoke('get_audio_devices');
+}
+
+/**
+ * The last panic in a background thread this session; later ones are
+ * emitted as `last-panic`
+ */
+export async function getLastPanic(): Promise<PanicReport | null> {
+  return invoke('get_last_panic');
+}
+
+/**
+ * Agree to send data to a third party for the next recording, in answer to
+ * `cloud-consent-required`; then start the recording again
+ */
+export async function grantCloudConsent(): Promise<void> {
+  return invoke('grant_cloud_consent');
+}
+
+/** Versions, enabled features and whisper.cpp's CPU/GPU support, for bug reports */
+export async function getBuildInfo(): Promise<BuildInfo> {
+  return invoke('get_build_info');
+}
+
 /**
  * Start audio recording
  */
-export async function startRecording(): Promise<void> {
-  return invoke('start_recording');
+export async function startRecording(incognito = false, hotkey?: string): Promise<void> {
+  return invoke('start_recording', { incognito, hotkey });
 }
 
 /**
@@ -40,6 +213,56 @@ export async function injectText(text: string): Promise<void> {
   return invoke('inject_text', { text });
 }
 
+/**
+ * Utterances waiting in the paste queue (insert_method "queue"), oldest first
+ */
+export async function getPasteQueue(): Promise<string[]> {
+  return invoke('get_paste_queue');
+}
+
+/**
+ * Paste the next queued utterance at the cursor, as the paste-next hotkey does
+ */
+export async function pasteNext(): Promise<void> {
+  return invoke('paste_next');
+}
+
+/**
+ * Drop everything in the paste queue
+ */
+export async function clearPasteQueue(): Promise<void> {
+  return invoke('clear_paste_queue');
+}
+
+/**
+ * Insert a test string into the focused field with each insert method, after
+ * `delayMs` (default 3000) to let the user focus the field
+ */
+export async function benchmarkInjection(text?: string, delayMs?: number): Promise<InjectionBenchmark> {
+  return invoke('benchmark_injection', { text, delayMs });
+}
+
+/**
+ * Make auto mode start at `method` for an app (e.g. the benchmark's winner)
+ */
+export async function setLearnedInsertMethod(appId: string, method: string): Promise<void> {
+  return invoke('set_learned_insert_method', { appId, method });
+}
+
+/**
+ * Most recent transcription result, kept in memory (null before the first one)
+ */
+export async function getLastTranscription(): Promise<RecentTranscription | null> {
+  return invoke('get_last_transcription');
+}
+
+/**
+ * Copy the most recent transcription result to the clipboard; false if there is none
+ */
+export async function copyLastTranscription(): Promise<boolean> {
+  return invoke('copy_last_transcription');
+}
+
 /**
  * Login with email and password
  */
@@ -47,6 +270,113 @@ export async function login(email: string, password: string): Promise<AuthToken>
   return invoke('login', { email, password });
 }
 
+/**
+ * Store a cloud STT provider's API key in the keychain; an empty key removes it
+ */
+export async function setCloudApiKey(provider: string, key: string): Promise<void> {
+  return invoke('set_cloud_api_key', { provider, key });
+}
+
+export async function hasCloudApiKey(provider: string): Promise<boolean> {
+  return invoke('has_cloud_api_key', { provider });
+}
+
+/**
+ * Store the manual proxy's password in the keychain; an empty password removes it
+ */
+export async function setProxyPassword(password: string): Promise<void> {
+  return invoke('set_proxy_password', { password });
+}
+
+/**
+ * Make a dictionary entry from a correction to a transcription; with `reapply`,
+ * recent history entries with the same mistake are corrected too
+ */
+export async function suggestDictionaryEntry(wrong: string, right: string, reapply = false): Promise<Correction> {
+  return invoke('suggest_dictionary_entry', { wrong, right, reapply });
+}
+
+/**
+ * Phrases Whisper is kept from producing (other engines ignore the list)
+ */
+export async function getBannedPhrases(): Promise<string[]> {
+  return invoke('get_banned_phrases');
+}
+
+/**
+ * Replace the banned phrase list; resolves to the list as stored
+ */
+export async function setBannedPhrases(phrases: string[]): Promise<string[]> {
+  return invoke('set_banned_phrases', { phrases });
+}
+
+/**
+ * Write settings, dictionary and optionally stats to one file (API keys and
+ * passwords excluded), for setting up another machine
+ */
+export async function exportConfig(path: string, includeStats = false): Promise<void> {
+  return invoke('export_config', { path, includeStats });
+}
+
+/**
+ * Apply a file written by exportConfig; dictionary entries are merged, stats replaced
+ */
+export async function importConfig(path: string): Promise<ImportSummary> {
+  return invoke('import_config', { path });
+}
+
+/**
+ * Back up settings, history, dictionary and stats to the backup folder now
+ */
+export async function createBackup(): Promise<BackupInfo> {
+  return invoke('create_backup');
+}
+
+/**
+ * Archives in the backup folder, newest first
+ */
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke('list_backups');
+}
+
+/**
+ * Replace all app data with a backup archive. The current data is backed up
+ * first; that backup is returned so the restore can be undone
+ */
+export async function restoreBackup(path: string): Promise<BackupInfo> {
+  return invoke('restore_backup', { path });
+}
+
+/**
+ * Remove history entries past the retention period and rewrite the store
+ */
+export async function compactHistory(): Promise<CompactionReport> {
+  return invoke('compact_history');
+}
+
+/**
+ * Transcribe the next dictation only with `transcription.boost_model`
+ * (false cancels); the configured model is loaded again afterwards
+ */
+export async function setAccuracyBoost(armed: boolean): Promise<void> {
+  return invoke('set_accuracy_boost', { armed });
+}
+
+/**
+ * History entries containing every word of `query`, best match first; the
+ * date bounds take RFC 3339 or a local date ('2025-03-01')
+ */
+export async function searchHistory(query: string, dateFrom?: string, dateTo?: string): Promise<SearchMatch[]> {
+  return invoke('search_history', { query, dateFrom, dateTo });
+}
+
+/**
+ * Words and transcriptions by hour of day and weekday, for the activity heatmap
+ */
+export async function getUsageHeatmap(): Promise<UsageHeatmap> {
+  return invoke('get_usage_heatmap');
+}
+
 /**
  * Download a Whisper model
  */
@@ -54,9 +384,61 @@ export async function downloadModel(size: string): Promise<void> {
   return invoke('download_model', { size });
 }
 
+/**
+ * Compare downloaded models with their latest revisions
+ */
+export async function checkModelUpdates(): Promise<ModelUpdate[]> {
+  return invoke('check_model_updates');
+}
+
+/**
+ * Drop cached dictionary and model state and re-check model files on disk,
+ * after they were replaced or deleted by hand
+ */
+export async function rebuildCaches(): Promise<FileCheck[]> {
+  return invoke('rebuild_caches');
+}
+
+/**
+ * Download every model file the current settings need that is missing
+ * (model, CoreML encoder, VAD). Resolves to what was downloaded.
+ */
+export async function downloadRequiredModels(): Promise<RequiredModel[]> {
+  return invoke('download_required_models');
+}
+
+/**
+ * Replace a downloaded model with its latest revision
+ */
+export async function updateModel(id: string): Promise<void> {
+  return invoke('update_model', { id });
+}
+
 /**
  * Get list of available models
  */
 export async function getAvailableModels(): Promise<ModelInfo[]> {
   return invoke('get_available_models');
 }
+
+/**
+ * Speech Recognition permission and availability of the Apple engine (macOS)
+ */
+export async function getAppleSpeechStatus(): Promise<AppleSpeechStatus> {
+  return invoke('get_apple_speech_status');
+}
+
+/**
+ * Ask for the Speech Recognition permission; resolves to the authorization
+ * once the user has answered
+ */
+export async function requestAppleSpeechPermission(): Promise<AppleSpeechStatus['authorization']> {
+  return invoke('request_apple_speech_permission');
+}
+
+/**
+ * Installed Windows speech recognizers (language packs) and the one dictation would use
+ */
+export async function getSapiStatus(): Promise<SapiStatus> {
+  return invoke('get_sapi_status');
+}
@@ -1,10 +1,17 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import App from './App';
+import { RecordingIndicator } from './components/RecordingIndicator';
 import './styles/globals.css';
 
+// Indicator windows (#indicator/<style>) only draw a static shape, without the app's listeners
+const hash = window.location.hash.slice(1);
+const root = hash.startsWith('indicator')
+  ? <RecordingIndicator style={hash.endsWith('icon') ? 'icon' : 'strip'} />
+  : <App />;
+
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <React.StrictMode>
-    <App />
+    {root}
   </React.StrictMode>
 );
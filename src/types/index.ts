@@ -14,15 +14,288 @@ export interface LocalStats {
   streak_days: number;
   last_used_date: string | null;
   daily_history: DailyStats[];
+  goal_reached_date?: string | null;
+  streak_reminder_date?: string | null;
+  repetition_loops?: number; // whisper repetition loops truncated or dropped
+  temperature_fallbacks?: number; // whisper decodes redone at a higher temperature
+  fallback_transcriptions?: number; // transcriptions that needed a fallback
+  fallback_hint_date?: string | null;
+  hourly_words?: number[]; // 24 buckets, local hour of day
+  hourly_transcriptions?: number[];
+  weekday_words?: number[]; // 7 buckets, Monday first
+  weekday_transcriptions?: number[];
+}
+
+/** Returned by get_usage_heatmap */
+export interface UsageHeatmap {
+  hourly_words: number[];
+  hourly_transcriptions: number[];
+  weekday_words: number[]; // Monday first
+  weekday_transcriptions: number[];
+  daily: DailyStats[]; // oldest first
+}
+
+/** Payload of the `accuracy-hint` event: Whisper often had to re-decode at a higher temperature */
+export interface FallbackHint {
+  fallback_rate: number; // 0.0-1.0
+  transcriptions: number;
+}
+
+export interface GoalProgress {
+  goal: number;
+  words_today: number;
+  percent: number;
+  reached: boolean;
+  streak_days: number;
+}
+
+export interface SegmentInfo {
+  text: string;
+  duration_ms: number;
+  engine: 'whisper' | 'voxtral' | 'parakeet' | 'apple' | 'sapi' | 'cloud';
+  source: 'streaming' | 'tail';
+  confidence: number | null;
+  temperature_fallbacks?: number;
+}
+
+export interface TranscriptionTimings {
+  stop_ms: number;
+  vad_ms: number;
+  model_load_ms: number;
+  inference_ms: number;
+  post_process_ms: number;
+  injection_ms: number | null;
+  total_ms: number;
+  tail_audio_secs: number;
+  slow_reason: string | null;
+  dropouts?: number;     // times the audio callback lost audio
+  concealed_ms?: number; // silence inserted in its place
+}
+
+// Payload of the "app-health" event (every 30s while the dashboard is open) and get_app_health
+export interface AppHealth {
+  engine: 'whisper' | 'voxtral' | 'parakeet' | 'apple' | 'sapi' | 'cloud';
+  model_loaded: boolean;
+  recording: boolean;
+  last_timings: TranscriptionTimings | null; // last transcription this session
+  streak_days: number;
+  words_today: number;
+  pending_sync: number | null; // history and dictionary entries not yet synced
+  storage_bytes: number | null; // downloaded models
+}
+
+// Payload of the "pipeline-report" event emitted after each dictation
+export interface StageReport {
+  stage: 'finalize-audio' | 'transcribe-tail' | 'merge' | 'post-process' | 'persist' | 'output';
+  duration_ms: number;
+  error: string | null;
+}
+
+export interface PipelineReport {
+  stages: StageReport[];
+  // Steps of the post-process stage (see OutputSettings.post_process_steps)
+  post_process: { stage: string; duration_ms: number; error: string | null }[];
 }
 
 export interface TranscriptionEntry {
   id: string;
   text: string;
+  raw_text?: string | null; // engine output before cleanup, when cleanup changed it
   word_count: number;
   duration_ms: number;
   timestamp: string;
   synced: boolean;
+  segments?: SegmentInfo[];
+  timings?: TranscriptionTimings | null;
+  session_id?: string | null; // absent on entries saved before sessions existed
+  app_id?: string | null;     // bundle id / executable name of the target app
+  sent_to_third_party?: boolean; // audio or text left the machine (e.g. Apple's speech servers)
+  language?: string | null;      // transcription language; null when auto-detected
+}
+
+/** Filters and sort of `query_history`; all optional */
+export interface HistoryFilters {
+  sort?: 'newest' | 'oldest' | 'longest' | 'most_words'; // default: newest
+  from?: string;        // RFC 3339, or a local date ('2025-03-01'), inclusive
+  to?: string;          // RFC 3339, or a local date (the whole day), inclusive
+  app_id?: string;
+  min_words?: number;
+  language?: string;    // 'auto' matches entries with a detected language
+  synced?: boolean;
+}
+
+/** `query_history` parameters: filters plus the page to return */
+export interface HistoryQuery extends HistoryFilters {
+  cursor?: string | null; // next_cursor of the previous page
+  limit?: number;         // default 50, at most 500
+}
+
+export interface HistoryPage {
+  entries: TranscriptionEntry[];
+  next_cursor: string | null; // null on the last page
+  total: number;              // entries matching the filters
+}
+
+/** A span of an entry's text matching a search, in JavaScript string indices */
+export interface Highlight {
+  start: number;
+  end: number; // exclusive
+}
+
+/** A `search_history` result, best match first */
+export interface SearchMatch {
+  entry: TranscriptionEntry;
+  score: number;
+  highlights: Highlight[]; // sorted, non-overlapping
+}
+
+/** `accuracy-boost` event: whether the next (or current) dictation is boosted */
+export interface AccuracyBoost {
+  status: 'armed' | 'active' | 'off';
+  model: string | null;
+}
+
+/** An audio file transcribed to the clipboard (`file-transcription-complete`) */
+export interface FileTranscribed {
+  path: string;
+  text: string;
+  /** null when nothing was saved (incognito) */
+  entry_id: string | null;
+}
+
+/** An audio file that couldn't be transcribed (`file-transcription-error`) */
+export interface FileFailed {
+  path: string;
+  error: AppError;
+}
+
+/** A recent result kept in memory by the backend (`get_last_transcription`) */
+export interface RecentTranscription {
+  text: string;
+  word_count: number;
+  timestamp: string;
+}
+
+export interface SessionSummary {
+  id: string;
+  started_at: string;
+  ended_at: string;
+  entry_count: number;
+  word_count: number;
+  duration_ms: number;
+  app_id?: string | null;
+  preview: string;
+}
+
+/** Payload of the `recording-status` event, emitted about once a second while recording */
+export interface RecordingStatus {
+  elapsed_ms: number;
+  buffered_secs: number;     // 16kHz audio captured so far
+  consumed_samples: number;  // 16kHz samples handled by streaming; stalled if this stops moving
+  engine: 'whisper' | 'voxtral' | 'parakeet' | 'apple' | 'sapi' | 'cloud';
+  streaming: boolean;        // streaming monitor running
+  consumed_secs: number;
+  pending_secs: number;      // audio streaming hasn't reached yet; stop has to transcribe it
+  live: boolean;             // streaming keeps up, so stop will be near-instant
+}
+
+/** Payload of `hotkey-pressed` / `hotkey-released`, already debounced for the mode */
+export interface HotkeyEvent {
+  key: string;
+  incognito: boolean;
+  mode: 'hold' | 'toggle';
+}
+
+/**
+ * Payload of `cloud-consent-required`: a recording was refused until the user
+ * agrees to send `what` off the machine (`privacy.confirm_cloud`)
+ */
+export interface CloudConsentRequest {
+  what: string;
+  incognito: boolean;
+  hotkey: string | null;
+  hold: boolean; // started by a held key, which has been released by the time the user answers
+}
+
+/** Payload of `cleanup-complete`; with `error` set the engine's text was used */
+export interface CleanupReport {
+  provider: string;
+  model: string;
+  duration_ms: number;
+  error: string | null;
+  sent_to_third_party: boolean;
+}
+
+/** Payload of the `audio-stalled` event: the input stream delivers no audio */
+export interface AudioStall {
+  kind: 'no_callbacks' | 'silent';
+  device: string | null;
+  causes: string[];              // most likely first
+  restarted_on: string | null;   // set when capture was restarted on this device
+}
+
+export type AppErrorKind =
+  | 'invalid_input'
+  | 'not_configured'
+  | 'not_found'
+  | 'busy'
+  | 'model_missing'
+  | 'model_corrupt'
+  | 'network'
+  | 'timeout'
+  | 'unauthorized'
+  | 'permission'
+  | 'device'
+  | 'privacy_blocked'
+  | 'consent_required'
+  | 'disk_full'
+  | 'unsupported'
+  | 'internal';
+
+/** What a failed command rejects with */
+export interface AppError {
+  kind: AppErrorKind;
+  message: string;
+  recoverable: boolean; // false: nothing the user can do (unsupported, internal)
+  suggested_action: 'open_settings' | 'retry' | 'download_model' | 'redownload_model' | 'sign_in'
+    | 'grant_permission' | 'check_microphone' | 'open_privacy_settings' | 'confirm_upload'
+    | 'free_disk_space' | null;
+}
+
+/** Payload of the `storage-full` event: a write failed because the disk is full */
+export interface StorageFull {
+  what: 'history' | 'stats' | 'dictionary' | 'model';
+  message: string;
+}
+
+/** Payload of the `download-progress` event */
+export interface DownloadProgressEvent {
+  model_type: 'ggml' | 'coreml' | 'vad' | 'voxtral' | 'parakeet' | 'punctuation';
+  model_id: string;
+  percent: number;
+  downloaded_bytes: number;
+  total_bytes: number;
+  bytes_per_sec: number;  // smoothed over recent chunks
+  eta_secs?: number | null; // null until a rate is known
+  mirror: string;
+}
+
+export interface EncoderSelection {
+  model_size: string;
+  setting: 'auto' | 'on' | 'off';
+  coreml: boolean;
+  reason: string;
+}
+
+export interface DiffChunk {
+  kind: 'equal' | 'insert' | 'delete';
+  text: string;
+}
+
+export interface EntryDiff {
+  raw_text: string;
+  cleaned_text: string;
+  changes: DiffChunk[];
 }
 
 export interface DictionaryEntry {
@@ -31,7 +304,105 @@ export interface DictionaryEntry {
   replacement: string;
   enabled: boolean;
   synced: boolean;
+  prose_only?: boolean; // skipped in code mode
+  apps?: string[];      // only applied when dictating into these apps; empty = all
+  hit_count?: number;   // times the replacement fired in a saved dictation
+  last_used?: string | null;
+}
+
+// Returned by suggest_dictionary_entry
+export interface Correction {
+  entry: DictionaryEntry;
+  reprocessed: TranscriptionEntry[]; // history entries the correction was re-applied to
+}
+
+// Returned by compact_history
+export interface CompactionReport {
+  removed_entries: number;
+  bytes_before: number;
+  bytes_after: number;
+}
+
+// Returned by import_config
+export interface ImportSummary {
+  dictionary_entries: number;
+  stats: boolean; // stats were in the file and replaced the local ones
+}
+
+export interface BackupInfo {
+  path: string;
+  created_at: string; // RFC 3339
+  size_bytes: number;
+}
+
+export interface StorageItem {
+  kind: 'ggml' | 'coreml' | 'voxtral' | 'punctuation' | 'vad' | 'partial' | 'other';
+  id: string;
+  path: string;
+  size_bytes: number;
+  last_used: string | null;
+}
+
+export interface StorageReport {
+  models_dir: string;
+  total_bytes: number;
+  items: StorageItem[];
+}
+
+export interface CleanupOptions {
+  remove_partial?: boolean;
+  remove_orphaned_coreml?: boolean;
+  unused_days?: number | null;
+  dry_run?: boolean;
+}
+
+export interface CleanupResult {
+  removed: StorageItem[];
+  freed_bytes: number;
+  dry_run: boolean;
+}
+
+export interface LedgerEntry {
+  id: string;
+  kind: 'dictation' | 'file' | 'preroll' | 'standby' | 'monitor' | 'plugin';
+  started_at: string;
+  ended_at: string;
+  duration_ms: number;
+  engine: string | null;
+  audio_uploaded: boolean;
+  incognito: boolean;
+  third_party: boolean; // text went through a plugin
+}
+
+export interface MicrophoneState {
+  recording: boolean;
+  standby: boolean;
+  preroll: boolean;
+  monitor: boolean;
 }
 
 // Dashboard navigation
 export type DashboardPage = 'home' | 'history' | 'dictionary' | 'settings';
+
+/** Returned by `get_apple_speech_status`; `supported` is false off macOS */
+export interface AppleSpeechStatus {
+  supported: boolean;
+  authorization: 'not_determined' | 'denied' | 'restricted' | 'authorized' | '';
+  locale: string | null;     // e.g. "en-GB"
+  available: boolean;        // a recognizer for the locale can be used now
+  on_device: boolean;        // the locale can be recognized without Apple's servers
+}
+
+/** A Windows speech recognizer, installed with a language's speech pack */
+export interface SapiRecognizer {
+  id: string;           // token id, for `transcription.sapi_recognizer`
+  name: string;
+  languages: string[];  // e.g. ["en-US", "en"]
+}
+
+/** Returned by `get_sapi_status`; `supported` is false off Windows */
+export interface SapiStatus {
+  supported: boolean;
+  recognizers: SapiRecognizer[];
+  selected: string | null; // recognizer a dictation would use now
+}